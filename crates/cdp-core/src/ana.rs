@@ -0,0 +1,752 @@
+use crate::fs::{self, File};
+use crate::{CoreError, Result};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Window overlap assumed when a file carries no `arate` or `decfactor`
+const DEFAULT_OVERLAP: u32 = 3;
+
+/// Source sample size assumed when a file carries no `original sampsize`
+const DEFAULT_ORIGINAL_BITS: u16 = 16;
+
+/// Analysis parameters stored in a CDP .ana file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnaHeader {
+    /// Sample rate of the analysed sound
+    pub sample_rate: u32,
+    /// Floats per frame: (fft_size / 2 + 1) value pairs
+    pub channels: u16,
+    /// Analysis window length (FFT size)
+    pub window_len: u32,
+    /// Decimation factor: samples between successive windows (CDP's D)
+    pub dec_factor: u32,
+    /// Bits per sample of the analysed sound, restored on resynthesis
+    pub original_bits: u16,
+}
+
+impl AnaHeader {
+    /// Header for an analysis with the given FFT size and overlap
+    ///
+    /// Successive windows are `fft_size / overlap` samples apart. Both must
+    /// be greater than 0.
+    pub fn new(sample_rate: u32, fft_size: u32, overlap: u32) -> Result<Self> {
+        if fft_size == 0 || overlap == 0 {
+            return Err(CoreError::OutOfRange(format!(
+                "FFT size and overlap must be greater than 0, got {} and {}",
+                fft_size, overlap
+            )));
+        }
+        Ok(Self::with_hop(
+            sample_rate,
+            fft_size,
+            (fft_size / overlap).max(1),
+        ))
+    }
+
+    /// Header for an analysis with the given FFT size and hop in samples
+    pub fn with_hop(sample_rate: u32, fft_size: u32, hop: u32) -> Self {
+        Self {
+            sample_rate,
+            channels: ((fft_size / 2 + 1) * 2) as u16,
+            window_len: fft_size,
+            dec_factor: hop,
+            original_bits: DEFAULT_ORIGINAL_BITS,
+        }
+    }
+
+    /// FFT size implied by the channel count
+    pub fn fft_size(&self) -> usize {
+        (self.channels as usize / 2 - 1) * 2
+    }
+
+    /// Number of frequency bins per frame (DC to Nyquist inclusive)
+    pub fn num_bins(&self) -> usize {
+        self.channels as usize / 2
+    }
+
+    /// Samples between successive analysis windows
+    pub fn hop_size(&self) -> u32 {
+        self.dec_factor
+    }
+
+    /// Analysis frames per second
+    pub fn frame_rate(&self) -> f32 {
+        self.sample_rate as f32 / self.hop_size() as f32
+    }
+
+    /// Width of one frequency bin in Hz
+    pub fn bin_width(&self) -> f32 {
+        self.sample_rate as f32 / self.fft_size() as f32
+    }
+}
+
+/// One analysis window: an amplitude/frequency pair per frequency bin
+#[derive(Debug, Clone, Copy)]
+pub struct SpectralFrame<'a> {
+    values: &'a [f32],
+}
+
+impl<'a> SpectralFrame<'a> {
+    /// Raw interleaved values of this frame
+    pub fn values(&self) -> &'a [f32] {
+        self.values
+    }
+
+    /// Number of bins in this frame
+    pub fn num_bins(&self) -> usize {
+        self.values.len() / 2
+    }
+
+    /// The stored value pair for a bin
+    pub fn pair(&self, bin: usize) -> (f32, f32) {
+        (self.values[bin * 2], self.values[bin * 2 + 1])
+    }
+
+    /// Amplitude of the partial in a bin
+    pub fn amplitude(&self, bin: usize) -> f32 {
+        self.values[bin * 2]
+    }
+
+    /// Frequency in Hz of the partial in a bin
+    pub fn frequency(&self, bin: usize) -> f32 {
+        self.values[bin * 2 + 1]
+    }
+}
+
+/// A CDP analysis file held in memory
+///
+/// .ana files are IEEE float WAVs whose "channels" are the values of one
+/// analysis frame, with analysis parameters in a LIST/adtl note chunk.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnaFile {
+    /// Analysis parameters
+    pub header: AnaHeader,
+    /// Frames stored back to back, `header.channels` floats each
+    pub data: Vec<f32>,
+}
+
+impl AnaFile {
+    /// Create an analysis file, checking that `data` holds whole frames
+    pub fn new(header: AnaHeader, data: Vec<f32>) -> Result<Self> {
+        if header.channels < 4 || header.channels % 2 != 0 {
+            return Err(CoreError::InvalidAnaFile(format!(
+                "invalid channel count {}",
+                header.channels
+            )));
+        }
+        if data.len() % header.channels as usize != 0 {
+            return Err(CoreError::InvalidAnaFile(
+                "data size doesn't match channel count".into(),
+            ));
+        }
+        Ok(Self { header, data })
+    }
+
+    /// Create an empty analysis file to push frames into
+    pub fn empty(header: AnaHeader) -> Result<Self> {
+        Self::new(header, Vec::new())
+    }
+
+    /// Floats in each frame
+    pub fn frame_len(&self) -> usize {
+        self.header.channels as usize
+    }
+
+    /// Number of analysis frames
+    pub fn num_frames(&self) -> usize {
+        self.data.len() / self.frame_len()
+    }
+
+    /// A single frame by index
+    pub fn frame(&self, index: usize) -> Option<SpectralFrame<'_>> {
+        let len = self.frame_len();
+        self.data
+            .get(index * len..(index + 1) * len)
+            .map(|values| SpectralFrame { values })
+    }
+
+    /// Iterate over all frames in order
+    pub fn frames(&self) -> impl Iterator<Item = SpectralFrame<'_>> {
+        self.data
+            .chunks_exact(self.frame_len())
+            .map(|values| SpectralFrame { values })
+    }
+
+    /// Iterate mutably over the raw values of each frame
+    pub fn frames_mut(&mut self) -> impl Iterator<Item = &mut [f32]> {
+        let len = self.frame_len();
+        self.data.chunks_exact_mut(len)
+    }
+
+    /// Append a frame of `header.channels` values
+    pub fn push_frame(&mut self, values: &[f32]) -> Result<()> {
+        if values.len() != self.frame_len() {
+            return Err(CoreError::InvalidAnaFile(format!(
+                "frame has {} values, expected {}",
+                values.len(),
+                self.frame_len()
+            )));
+        }
+        self.data.extend_from_slice(values);
+        Ok(())
+    }
+
+    /// Read a .ana file
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::read_from(BufReader::new(fs::open(path)?))
+    }
+
+    /// Read .ana data from any source, such as bytes held in memory
+    pub fn read_from<R: Read + Seek>(reader: R) -> Result<Self> {
+        let mut reader = AnaFrameReader::new(reader)?;
+        let frame_len = reader.header().channels as usize;
+        let mut data = vec![0.0f32; reader.num_frames() * frame_len];
+        for frame in data.chunks_exact_mut(frame_len) {
+            reader.read_frame(frame)?;
+        }
+        Self::new(reader.header().clone(), data)
+    }
+
+    /// Write a .ana file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        self.write_to(BufWriter::new(fs::create(path)?))
+    }
+
+    /// Write .ana data to any destination, such as a buffer in memory
+    pub fn write_to<W: Write + Seek>(&self, writer: W) -> Result<()> {
+        let mut writer = AnaFrameWriter::new(writer, self.header.clone())?;
+        for frame in self.data.chunks_exact(self.frame_len()) {
+            writer.write_frame(frame)?;
+        }
+        writer.finalize()?;
+        Ok(())
+    }
+}
+
+/// Pitch value marking a window with sound but no clear pitch
+pub const NOT_PITCH: f32 = -1.0;
+
+/// Pitch value marking a silent window
+pub const NOT_SOUND: f32 = -2.0;
+
+/// A CDP pitch data file held in memory
+///
+/// Pitch files are mono IEEE float WAVs holding one frequency in Hz per
+/// analysis window, or [`NOT_PITCH`] / [`NOT_SOUND`], with the parameters
+/// of the analysis they came from in the same note chunk as a .ana file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PitchFile {
+    /// Parameters of the analysis the pitches were taken from
+    pub header: AnaHeader,
+    /// One value per analysis window
+    pub pitches: Vec<f32>,
+}
+
+impl PitchFile {
+    /// Create pitch data for windows of the given analysis
+    pub fn new(header: AnaHeader, pitches: Vec<f32>) -> Self {
+        Self { header, pitches }
+    }
+
+    /// Number of analysis windows
+    pub fn num_windows(&self) -> usize {
+        self.pitches.len()
+    }
+
+    /// Read a pitch file
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut reader = BufReader::new(fs::open(path)?);
+        let raw = read_raw_header(&mut reader)?;
+        if raw.channels != 1 {
+            return Err(CoreError::InvalidAnaFile(format!(
+                "pitch data must have 1 channel, found {}",
+                raw.channels
+            )));
+        }
+        let window_len = match raw.window_len {
+            Some(len) if len >= 2 => len,
+            _ => {
+                return Err(CoreError::InvalidAnaFile(
+                    "pitch data has no analysis window length".into(),
+                ))
+            }
+        };
+        let header = AnaHeader {
+            dec_factor: raw.hop(window_len)?,
+            original_bits: raw.original_bits,
+            ..AnaHeader::with_hop(raw.sample_rate, window_len, 1)
+        };
+
+        let mut bytes = vec![0u8; raw.data_size as usize / 4 * 4];
+        reader.read_exact(&mut bytes)?;
+        let pitches = bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        Ok(Self { header, pitches })
+    }
+
+    /// Write a pitch file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let data_size = u32::try_from(self.pitches.len() * 4).map_err(|_| {
+            CoreError::InvalidAnaFile("pitch data exceeds the 4 GB WAV limit".into())
+        })?;
+        let mut writer = BufWriter::new(fs::create(path)?);
+        write_header(&mut writer, &self.header, 1, data_size)?;
+        for &value in &self.pitches {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Pull-based reader yielding one analysis frame at a time
+///
+/// Only a single frame is buffered, so arbitrarily long analysis files can
+/// be processed in bounded memory.
+#[derive(Debug)]
+pub struct AnaFrameReader<R> {
+    reader: R,
+    header: AnaHeader,
+    num_frames: usize,
+    position: usize,
+    bytes: Vec<u8>,
+}
+
+impl AnaFrameReader<BufReader<File>> {
+    /// Open a .ana file for frame-by-frame reading
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::new(BufReader::new(fs::open(path)?))
+    }
+}
+
+impl<R: Read + Seek> AnaFrameReader<R> {
+    /// Parse the header and position the reader at the first frame
+    pub fn new(mut reader: R) -> Result<Self> {
+        let (header, data_size) = read_ana_header(&mut reader)?;
+        let frame_bytes = header.channels as usize * 4;
+        if data_size as usize % frame_bytes != 0 {
+            return Err(CoreError::InvalidAnaFile(
+                "data size doesn't match channel count".into(),
+            ));
+        }
+        Ok(Self {
+            reader,
+            header,
+            num_frames: data_size as usize / frame_bytes,
+            position: 0,
+            bytes: vec![0u8; frame_bytes],
+        })
+    }
+
+    /// Analysis parameters of the file
+    pub fn header(&self) -> &AnaHeader {
+        &self.header
+    }
+
+    /// Total number of frames in the file
+    pub fn num_frames(&self) -> usize {
+        self.num_frames
+    }
+
+    /// Frames not yet read
+    pub fn frames_remaining(&self) -> usize {
+        self.num_frames - self.position
+    }
+
+    /// Read the next frame into `frame`
+    ///
+    /// `frame` must hold `header.channels` values. Returns `false` once all
+    /// frames have been read.
+    pub fn read_frame(&mut self, frame: &mut [f32]) -> Result<bool> {
+        if frame.len() != self.header.channels as usize {
+            return Err(CoreError::InvalidAnaFile(format!(
+                "frame buffer has {} values, expected {}",
+                frame.len(),
+                self.header.channels
+            )));
+        }
+        if self.position == self.num_frames {
+            return Ok(false);
+        }
+
+        self.reader.read_exact(&mut self.bytes)?;
+        for (value, b) in frame.iter_mut().zip(self.bytes.chunks_exact(4)) {
+            *value = f32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+        }
+        self.position += 1;
+        Ok(true)
+    }
+}
+
+/// Push-based writer appending one analysis frame at a time
+///
+/// Sizes in the RIFF and data chunk headers are patched by
+/// [`finalize`](Self::finalize); a writer dropped without finalizing
+/// leaves an incomplete file.
+#[derive(Debug)]
+pub struct AnaFrameWriter<W: Write + Seek> {
+    writer: W,
+    header: AnaHeader,
+    data_size_pos: u64,
+    data_size: u32,
+}
+
+impl AnaFrameWriter<BufWriter<File>> {
+    /// Create a .ana file for frame-by-frame writing
+    pub fn create(path: &Path, header: AnaHeader) -> Result<Self> {
+        Self::new(BufWriter::new(fs::create(path)?), header)
+    }
+}
+
+impl<W: Write + Seek> AnaFrameWriter<W> {
+    /// Write the file header, leaving the data chunk open for frames
+    pub fn new(mut writer: W, header: AnaHeader) -> Result<Self> {
+        // Validate the header the same way in-memory files are
+        AnaFile::empty(header.clone())?;
+        write_ana_header(&mut writer, &header, 0)?;
+        let data_size_pos = writer.stream_position()? - 4;
+        Ok(Self {
+            writer,
+            header,
+            data_size_pos,
+            data_size: 0,
+        })
+    }
+
+    /// Analysis parameters of the file
+    pub fn header(&self) -> &AnaHeader {
+        &self.header
+    }
+
+    /// Append a frame of `header.channels` values
+    pub fn write_frame(&mut self, frame: &[f32]) -> Result<()> {
+        if frame.len() != self.header.channels as usize {
+            return Err(CoreError::InvalidAnaFile(format!(
+                "frame has {} values, expected {}",
+                frame.len(),
+                self.header.channels
+            )));
+        }
+        let frame_bytes = frame.len() as u32 * 4;
+        let limit = u32::MAX - self.data_size_pos as u32;
+        if frame_bytes > limit - self.data_size {
+            return Err(CoreError::InvalidAnaFile(
+                "analysis data exceeds the 4 GB WAV limit".into(),
+            ));
+        }
+        self.data_size += frame_bytes;
+
+        for &value in frame {
+            self.writer.write_all(&value.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Patch the chunk sizes and flush, returning the underlying writer
+    pub fn finalize(mut self) -> Result<W> {
+        let riff_size = self.data_size_pos as u32 - 4 + self.data_size;
+        self.writer.seek(SeekFrom::Start(4))?;
+        self.writer.write_all(&riff_size.to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(self.data_size_pos))?;
+        self.writer.write_all(&self.data_size.to_le_bytes())?;
+        self.writer.seek(SeekFrom::End(0))?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Fields of a CDP float WAV header, before interpretation
+struct RawHeader {
+    channels: u16,
+    sample_rate: u32,
+    window_len: Option<u32>,
+    dec_factor: Option<u32>,
+    frame_rate: Option<f32>,
+    original_bits: u16,
+    data_size: u32,
+}
+
+impl RawHeader {
+    /// Samples between windows for an analysis of `window_len`
+    ///
+    /// The analysis rate fixes the hop exactly, whatever convention the
+    /// decfactor field was written with.
+    fn hop(&self, window_len: u32) -> Result<u32> {
+        let dec_factor = self
+            .frame_rate
+            .filter(|&rate| rate > 0.0)
+            .map(|rate| (self.sample_rate as f32 / rate).round() as u32)
+            .or(self.dec_factor)
+            .unwrap_or(window_len / DEFAULT_OVERLAP);
+        if dec_factor == 0 {
+            return Err(CoreError::InvalidAnaFile(
+                "decfactor must be non-zero".into(),
+            ));
+        }
+        Ok(dec_factor)
+    }
+}
+
+/// Parse a CDP float WAV header, leaving the reader at the first data byte
+fn read_raw_header<R: Read + Seek>(reader: &mut R) -> Result<RawHeader> {
+    let mut riff = [0u8; 12];
+    reader.read_exact(&mut riff)?;
+    if &riff[0..4] != b"RIFF" || &riff[8..12] != b"WAVE" {
+        return Err(CoreError::InvalidAnaFile("not a WAV file".into()));
+    }
+
+    let mut format: Option<(u16, u32)> = None;
+    let mut window_len = None;
+    let mut dec_factor = None;
+    let mut frame_rate = None;
+    let mut original_bits = DEFAULT_ORIGINAL_BITS;
+
+    let data_size = loop {
+        let mut chunk_header = [0u8; 8];
+        if reader.read_exact(&mut chunk_header).is_err() {
+            return Err(CoreError::InvalidAnaFile("missing data chunk".into()));
+        }
+        let chunk_id = &chunk_header[0..4];
+        let chunk_size = u32::from_le_bytes([
+            chunk_header[4],
+            chunk_header[5],
+            chunk_header[6],
+            chunk_header[7],
+        ]);
+
+        match chunk_id {
+            b"fmt " => {
+                let mut fmt_data = vec![0u8; chunk_size as usize];
+                reader.read_exact(&mut fmt_data)?;
+                if fmt_data.len() < 16 || u16::from_le_bytes([fmt_data[0], fmt_data[1]]) != 3 {
+                    return Err(CoreError::InvalidAnaFile("not IEEE float format".into()));
+                }
+                format = Some((
+                    u16::from_le_bytes([fmt_data[2], fmt_data[3]]),
+                    u32::from_le_bytes([fmt_data[4], fmt_data[5], fmt_data[6], fmt_data[7]]),
+                ));
+            }
+            b"LIST" => {
+                let mut list_data = vec![0u8; chunk_size as usize];
+                reader.read_exact(&mut list_data)?;
+
+                // Analysis parameters live in an adtl note as "key: value" lines
+                if list_data.starts_with(b"adtl") && list_data.len() > 12 {
+                    let metadata = String::from_utf8_lossy(&list_data[12..]);
+                    for line in metadata.lines() {
+                        if let Some(rest) = line.strip_prefix("analwinlen:") {
+                            window_len = rest.trim().parse().ok();
+                        } else if let Some(rest) = line.strip_prefix("decfactor:") {
+                            dec_factor = rest.trim().parse::<u32>().ok();
+                        } else if let Some(rest) = line.strip_prefix("arate:") {
+                            frame_rate = rest.trim().parse::<f32>().ok();
+                        } else if let Some(rest) = line.strip_prefix("original sampsize:") {
+                            original_bits = rest.trim().parse().unwrap_or(DEFAULT_ORIGINAL_BITS);
+                        }
+                    }
+                }
+            }
+            b"data" => break chunk_size,
+            _ => {
+                reader.seek(SeekFrom::Current(chunk_size as i64))?;
+            }
+        }
+
+        // Align to word boundary
+        if chunk_size % 2 != 0 {
+            reader.seek(SeekFrom::Current(1))?;
+        }
+    };
+
+    let (channels, sample_rate) =
+        format.ok_or_else(|| CoreError::InvalidAnaFile("missing fmt chunk".into()))?;
+    Ok(RawHeader {
+        channels,
+        sample_rate,
+        window_len,
+        dec_factor,
+        frame_rate,
+        original_bits,
+        data_size,
+    })
+}
+
+/// Parse a .ana header, leaving the reader at the first data byte
+///
+/// Returns the header and the size of the data chunk in bytes.
+pub(crate) fn read_ana_header<R: Read + Seek>(reader: &mut R) -> Result<(AnaHeader, u32)> {
+    let raw = read_raw_header(reader)?;
+    if raw.channels < 4 || raw.channels % 2 != 0 {
+        return Err(CoreError::InvalidAnaFile(format!(
+            "invalid channel count {}",
+            raw.channels
+        )));
+    }
+
+    let fft_size = (raw.channels as u32 / 2 - 1) * 2;
+    let window_len = raw.window_len.unwrap_or(fft_size);
+    let header = AnaHeader {
+        sample_rate: raw.sample_rate,
+        channels: raw.channels,
+        window_len,
+        dec_factor: raw.hop(window_len)?,
+        original_bits: raw.original_bits,
+    };
+    Ok((header, raw.data_size))
+}
+
+/// Write a .ana header (fmt, LIST/adtl note, data chunk header)
+pub(crate) fn write_ana_header<W: Write>(
+    writer: &mut W,
+    header: &AnaHeader,
+    data_size: u32,
+) -> Result<()> {
+    write_header(writer, header, header.channels, data_size)
+}
+
+/// Write a CDP float WAV header of `channels` channels carrying `header`
+fn write_header<W: Write>(
+    writer: &mut W,
+    header: &AnaHeader,
+    channels: u16,
+    data_size: u32,
+) -> Result<()> {
+    let metadata = format!(
+        "original sampsize: {}\n\
+         original sample rate: {}\n\
+         arate: {}\n\
+         analwinlen: {}\n\
+         decfactor: {}\n\
+         origrate: {}\n\
+         DATE: CDP Phase Vocoder Analysis\n",
+        header.original_bits,
+        header.sample_rate,
+        header.frame_rate(),
+        header.window_len,
+        header.dec_factor,
+        header.sample_rate
+    );
+
+    let list_data = metadata.as_bytes();
+    let list_size = 4 + 4 + 4 + list_data.len(); // "adtl" + "note" + size + data
+    let list_size_padded = list_size + list_size % 2;
+
+    let riff_size = 4 + // "WAVE"
+        8 + 16 + // fmt chunk
+        8 + list_size_padded as u32 + // LIST chunk
+        8 + data_size; // data chunk
+
+    // Write RIFF header
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&riff_size.to_le_bytes())?;
+    writer.write_all(b"WAVE")?;
+
+    // Write fmt chunk (IEEE float format)
+    writer.write_all(b"fmt ")?;
+    writer.write_all(&16u32.to_le_bytes())?;
+    writer.write_all(&3u16.to_le_bytes())?; // format type 3 = IEEE float
+    writer.write_all(&channels.to_le_bytes())?;
+    writer.write_all(&header.sample_rate.to_le_bytes())?;
+    let byte_rate = header.sample_rate * channels as u32 * 4;
+    writer.write_all(&byte_rate.to_le_bytes())?;
+    writer.write_all(&(channels * 4).to_le_bytes())?;
+    writer.write_all(&32u16.to_le_bytes())?;
+
+    // Write LIST chunk
+    writer.write_all(b"LIST")?;
+    writer.write_all(&(list_size_padded as u32).to_le_bytes())?;
+    writer.write_all(b"adtl")?;
+    writer.write_all(b"note")?;
+    writer.write_all(&(list_data.len() as u32).to_le_bytes())?;
+    writer.write_all(list_data)?;
+    if list_data.len() % 2 != 0 {
+        writer.write_all(&[0u8])?;
+    }
+
+    // Write data chunk header
+    writer.write_all(b"data")?;
+    writer.write_all(&data_size.to_le_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_load_round_trip() {
+        let dir = std::env::temp_dir().join(format!("cdp-core-ana-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.ana");
+
+        // A hop that doesn't divide the FFT size survives the round trip
+        let mut header = AnaHeader::with_hop(44100, 4, 3);
+        header.original_bits = 24;
+        assert_eq!(header.channels, 6);
+        assert_eq!(header.num_bins(), 3);
+
+        let mut ana = AnaFile::empty(header).unwrap();
+        ana.push_frame(&[3.0, 4.0, 0.0, 1.0, -1.0, 0.0]).unwrap();
+        ana.push_frame(&[0.5; 6]).unwrap();
+        assert!(ana.push_frame(&[0.0; 4]).is_err());
+        ana.save(&path).unwrap();
+
+        let loaded = AnaFile::load(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(loaded, ana);
+
+        let frame = loaded.frames().next().unwrap();
+        assert_eq!(frame.num_bins(), 3);
+        assert_eq!(frame.amplitude(0), 3.0);
+        assert_eq!(frame.frequency(1), 1.0);
+        assert_eq!(loaded.frame(1).unwrap().pair(2), (0.5, 0.5));
+        assert!(loaded.frame(2).is_none());
+    }
+
+    #[test]
+    fn test_pitch_file_round_trip() {
+        let dir = std::env::temp_dir().join(format!("cdp-core-pitch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.frq");
+
+        let pitch = PitchFile::new(
+            AnaHeader::with_hop(44100, 1024, 128),
+            vec![220.0, 221.5, NOT_PITCH, NOT_SOUND],
+        );
+        pitch.save(&path).unwrap();
+        let loaded = PitchFile::load(&path).unwrap();
+
+        // Pitch data is not analysis data
+        assert!(AnaFile::load(&path).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(loaded, pitch);
+        assert_eq!(loaded.header.num_bins(), 513);
+    }
+
+    #[test]
+    fn test_frame_reader_writer_streaming() {
+        assert!(AnaHeader::new(48000, 2, 0).is_err());
+        assert!(AnaHeader::new(48000, 0, 3).is_err());
+        let header = AnaHeader::new(48000, 2, 3).unwrap();
+        let mut writer = AnaFrameWriter::new(std::io::Cursor::new(Vec::new()), header).unwrap();
+        writer.write_frame(&[1.0, 2.0, 3.0, 4.0]).unwrap();
+        writer.write_frame(&[5.0, 6.0, 7.0, 8.0]).unwrap();
+        assert!(writer.write_frame(&[0.0; 6]).is_err());
+        let mut bytes = writer.finalize().unwrap();
+
+        bytes.set_position(0);
+        let mut reader = AnaFrameReader::new(bytes).unwrap();
+        assert_eq!(reader.header().sample_rate, 48000);
+        assert_eq!(reader.num_frames(), 2);
+
+        let mut frame = [0.0f32; 4];
+        assert!(reader.read_frame(&mut frame).unwrap());
+        assert_eq!(frame, [1.0, 2.0, 3.0, 4.0]);
+        assert!(reader.read_frame(&mut frame).unwrap());
+        assert_eq!(frame, [5.0, 6.0, 7.0, 8.0]);
+        assert_eq!(reader.frames_remaining(), 0);
+        assert!(!reader.read_frame(&mut frame).unwrap());
+    }
+}
@@ -0,0 +1,256 @@
+use crate::{CoreError, Result};
+use std::f64::consts::PI;
+
+/// Interleaved float audio with its sample rate and channel layout
+///
+/// Samples are levels where 1.0 is full scale. Integer formats are
+/// converted on load, so processing code never deals with bit depths.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioBuffer {
+    /// Sample rate in Hz
+    pub sample_rate: u32,
+    /// Number of interleaved channels
+    pub channels: u16,
+    /// Interleaved samples, `channels` per frame
+    pub data: Vec<f32>,
+}
+
+impl AudioBuffer {
+    /// Create a buffer, checking that `data` holds whole frames
+    pub fn new(sample_rate: u32, channels: u16, data: Vec<f32>) -> Result<Self> {
+        if sample_rate == 0 {
+            return Err(CoreError::InvalidBuffer(
+                "sample rate must be greater than 0".into(),
+            ));
+        }
+        if channels == 0 {
+            return Err(CoreError::InvalidBuffer(
+                "channel count must be greater than 0".into(),
+            ));
+        }
+        if data.len() % channels as usize != 0 {
+            return Err(CoreError::InvalidBuffer(format!(
+                "{} samples do not form whole {}-channel frames",
+                data.len(),
+                channels
+            )));
+        }
+        Ok(Self {
+            sample_rate,
+            channels,
+            data,
+        })
+    }
+
+    /// Create a silent buffer of `frames` frames
+    pub fn silence(sample_rate: u32, channels: u16, frames: usize) -> Result<Self> {
+        Self::new(sample_rate, channels, vec![0.0; frames * channels as usize])
+    }
+
+    /// Build an interleaved buffer from one sample vector per channel
+    pub fn from_channels(sample_rate: u32, channels: &[Vec<f32>]) -> Result<Self> {
+        let frames = channels.first().map_or(0, Vec::len);
+        if channels.iter().any(|channel| channel.len() != frames) {
+            return Err(CoreError::InvalidBuffer(
+                "all channels must have the same length".into(),
+            ));
+        }
+
+        let mut data = Vec::with_capacity(frames * channels.len());
+        for frame in 0..frames {
+            data.extend(channels.iter().map(|channel| channel[frame]));
+        }
+        Self::new(sample_rate, channels.len() as u16, data)
+    }
+
+    /// Number of frames (samples per channel)
+    pub fn frame_count(&self) -> usize {
+        self.data.len() / self.channels as usize
+    }
+
+    /// Duration in seconds
+    pub fn duration(&self) -> f64 {
+        self.frame_count() as f64 / f64::from(self.sample_rate)
+    }
+
+    /// Iterate over frames, each holding one sample per channel
+    pub fn frames(&self) -> impl Iterator<Item = &[f32]> {
+        self.data.chunks_exact(self.channels as usize)
+    }
+
+    /// Copy out a single channel (0-based)
+    pub fn channel(&self, index: usize) -> Result<Vec<f32>> {
+        if index >= self.channels as usize {
+            return Err(CoreError::InvalidBuffer(format!(
+                "channel {} does not exist (buffer has {} channels)",
+                index, self.channels
+            )));
+        }
+        Ok(self
+            .data
+            .iter()
+            .skip(index)
+            .step_by(self.channels as usize)
+            .copied()
+            .collect())
+    }
+
+    /// Mix all channels down to a mono buffer by averaging
+    pub fn to_mono(&self) -> Self {
+        let channels = self.channels as usize;
+        let data = self
+            .frames()
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+            .collect();
+        Self {
+            sample_rate: self.sample_rate,
+            channels: 1,
+            data,
+        }
+    }
+
+    /// Largest absolute sample level
+    pub fn peak(&self) -> f32 {
+        self.data.iter().fold(0.0f32, |peak, s| peak.max(s.abs()))
+    }
+}
+
+/// Resample interleaved audio so it plays `ratio` times faster and higher
+///
+/// Linear interpolation between frames; the output ends at the last input
+/// frame, so it holds `(frames - 1) / ratio + 1` frames.
+pub fn transpose(samples: &[f32], channels: usize, ratio: f64) -> Vec<f32> {
+    let frames = samples.len() / channels;
+    if ratio == 1.0 || frames == 0 {
+        return samples.to_vec();
+    }
+    let out_frames = ((frames - 1) as f64 / ratio).floor() as usize + 1;
+    let mut output = Vec::with_capacity(out_frames * channels);
+    for frame in 0..out_frames {
+        let position = frame as f64 * ratio;
+        let index = position as usize;
+        let frac = (position - index as f64) as f32;
+        let next = (index + 1).min(frames - 1);
+        for ch in 0..channels {
+            let a = samples[index * channels + ch];
+            let b = samples[next * channels + ch];
+            output.push(a + (b - a) * frac);
+        }
+    }
+    output
+}
+
+/// Kaiser-windowed sinc low-pass kernel for band-limited interpolation
+///
+/// Weighting input samples by [`KaiserSinc::tap`] at their distance from a
+/// fractional read position reconstructs the signal there, with content
+/// above `cutoff` (a fraction of the input Nyquist) removed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KaiserSinc {
+    cutoff: f64,
+    half_width: f64,
+    beta: f64,
+}
+
+impl KaiserSinc {
+    /// Create a kernel reaching `half_width` input samples each side of its
+    /// centre, with Kaiser window shape `beta`
+    pub fn new(cutoff: f64, half_width: f64, beta: f64) -> Self {
+        Self {
+            cutoff,
+            half_width,
+            beta,
+        }
+    }
+
+    /// Input samples covered on each side of the centre
+    pub fn half_width(&self) -> f64 {
+        self.half_width
+    }
+
+    /// Weight of an input sample `t` samples from the read position
+    pub fn tap(&self, t: f64) -> f64 {
+        self.cutoff * sinc(self.cutoff * t) * kaiser(t / self.half_width, self.beta)
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+/// Kaiser window at `x` in [-1, 1], zero outside
+fn kaiser(x: f64, beta: f64) -> f64 {
+    if x.abs() > 1.0 {
+        return 0.0;
+    }
+    bessel_i0(beta * (1.0 - x * x).sqrt()) / bessel_i0(beta)
+}
+
+/// Zeroth-order modified Bessel function of the first kind
+fn bessel_i0(x: f64) -> f64 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let quarter = x * x / 4.0;
+    for k in 1..50 {
+        term *= quarter / (k * k) as f64;
+        sum += term;
+        if term < sum * 1e-12 {
+            break;
+        }
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_round_trip() {
+        let left = vec![1.0, 2.0, 3.0];
+        let right = vec![-1.0, -2.0, -3.0];
+        let buffer = AudioBuffer::from_channels(48000, &[left.clone(), right.clone()]).unwrap();
+
+        assert_eq!(buffer.data, vec![1.0, -1.0, 2.0, -2.0, 3.0, -3.0]);
+        assert_eq!(buffer.frame_count(), 3);
+        assert_eq!(buffer.channel(0).unwrap(), left);
+        assert_eq!(buffer.channel(1).unwrap(), right);
+        assert!(buffer.channel(2).is_err());
+        assert_eq!(buffer.to_mono().data, vec![0.0, 0.0, 0.0]);
+        assert_eq!(buffer.peak(), 3.0);
+    }
+
+    #[test]
+    fn test_transpose() {
+        let stereo = [0.0, 1.0, 1.0, 0.0, 2.0, -1.0];
+        assert_eq!(transpose(&stereo, 2, 1.0), stereo);
+        assert_eq!(
+            transpose(&stereo, 2, 0.5),
+            vec![0.0, 1.0, 0.5, 0.5, 1.0, 0.0, 1.5, -0.5, 2.0, -1.0]
+        );
+        assert_eq!(transpose(&stereo, 2, 2.0), vec![0.0, 1.0, 2.0, -1.0]);
+        assert!(transpose(&[], 2, 2.0).is_empty());
+    }
+
+    #[test]
+    fn test_rejects_partial_frames() {
+        assert!(AudioBuffer::new(44100, 2, vec![0.0; 3]).is_err());
+        assert!(AudioBuffer::new(44100, 0, Vec::new()).is_err());
+        assert!(AudioBuffer::new(0, 1, Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_kaiser_sinc() {
+        let kernel = KaiserSinc::new(0.5, 16.0, 8.0);
+        assert_eq!(kernel.tap(0.0), 0.5);
+        // Zero at the sinc's zero crossings and beyond the window
+        assert!(kernel.tap(2.0).abs() < 1e-12);
+        assert!(kernel.tap(-6.0).abs() < 1e-12);
+        assert_eq!(kernel.tap(17.0), 0.0);
+        assert_eq!(kernel.tap(1.5), kernel.tap(-1.5));
+    }
+}
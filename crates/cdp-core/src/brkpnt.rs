@@ -0,0 +1,226 @@
+//! Time-varying parameter breakpoints
+//!
+//! Most CDP parameters accept either a number or a breakpoint file: a text
+//! file of `time value` pairs separated by whitespace, with times in
+//! seconds. Between breakpoints values are interpolated, and before the
+//! first or after the last they hold the end values.
+
+use crate::{CoreError, Result};
+use std::path::Path;
+
+/// How values change between breakpoints
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Interpolation {
+    /// Straight line between values
+    Linear,
+    /// Constant ratio per unit time, for frequencies, gains and other
+    /// values heard logarithmically; falls back to linear unless both
+    /// values are positive
+    Log,
+    /// Constant ratio between positive values, for pitch contours; a
+    /// segment starting or ending on zero or a negative marker (such as
+    /// unpitched) holds its starting value instead
+    Pitch,
+}
+
+/// A validated breakpoint envelope
+///
+/// Holds at least one point, with finite non-negative times that strictly
+/// increase and finite values. Serialized as a list of (time, value) pairs,
+/// checked again when deserialized.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "Vec<(f64, f64)>", into = "Vec<(f64, f64)>")
+)]
+pub struct Breakpoints {
+    points: Vec<(f64, f64)>,
+}
+
+impl Breakpoints {
+    /// Create breakpoints from (time, value) pairs
+    pub fn new(points: Vec<(f64, f64)>) -> Result<Self> {
+        if points.is_empty() {
+            return Err(CoreError::InvalidBreakpoints(
+                "at least one breakpoint is required".into(),
+            ));
+        }
+        if !points
+            .iter()
+            .all(|&(time, value)| time >= 0.0 && time.is_finite() && value.is_finite())
+        {
+            return Err(CoreError::InvalidBreakpoints(
+                "times must be non-negative and values finite".into(),
+            ));
+        }
+        if points.windows(2).any(|pair| pair[1].0 <= pair[0].0) {
+            return Err(CoreError::InvalidBreakpoints("times must increase".into()));
+        }
+        Ok(Self { points })
+    }
+
+    /// A single value that holds for all time
+    pub fn constant(value: f64) -> Self {
+        Self {
+            points: vec![(0.0, value)],
+        }
+    }
+
+    /// Parse CDP breakpoint text: whitespace-separated `time value` pairs
+    pub fn parse(text: &str) -> Result<Self> {
+        let numbers = text
+            .split_whitespace()
+            .map(|field| {
+                field.parse::<f64>().map_err(|_| {
+                    CoreError::InvalidBreakpoints(format!("invalid number '{}'", field))
+                })
+            })
+            .collect::<Result<Vec<f64>>>()?;
+        if numbers.len() % 2 != 0 {
+            return Err(CoreError::InvalidBreakpoints(
+                "values must come in time-value pairs".into(),
+            ));
+        }
+        Self::new(numbers.chunks_exact(2).map(|p| (p[0], p[1])).collect())
+    }
+
+    /// Read a CDP breakpoint file
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::parse(&crate::fs::read_to_string(path)?)
+    }
+
+    /// The (time, value) pairs
+    pub fn points(&self) -> &[(f64, f64)] {
+        &self.points
+    }
+
+    /// Time of the last breakpoint in seconds
+    pub fn duration(&self) -> f64 {
+        self.points[self.points.len() - 1].0
+    }
+
+    /// Check every value lies within `min..=max`
+    ///
+    /// `max` may be infinite for values only bounded below. The error names
+    /// the parameter and the first offending breakpoint.
+    pub fn check_range(&self, name: &str, min: f64, max: f64) -> Result<()> {
+        let Some((time, value)) = self
+            .points
+            .iter()
+            .find(|(_, value)| !(min..=max).contains(value))
+        else {
+            return Ok(());
+        };
+        let range = if max.is_infinite() {
+            format!("at least {}", min)
+        } else {
+            format!("between {} and {}", min, max)
+        };
+        Err(CoreError::InvalidBreakpoints(format!(
+            "{} must be {}, got {} at time {}",
+            name, range, value, time
+        )))
+    }
+
+    /// Value at a time, interpolating linearly
+    pub fn value_at(&self, time: f64) -> f64 {
+        self.value_at_with(time, Interpolation::Linear)
+    }
+
+    /// Value at a time with the given interpolation
+    pub fn value_at_with(&self, time: f64, interpolation: Interpolation) -> f64 {
+        let next = self.points.partition_point(|&(t, _)| t <= time);
+        if next == 0 {
+            return self.points[0].1;
+        }
+        if next == self.points.len() {
+            return self.points[next - 1].1;
+        }
+
+        let (t0, v0) = self.points[next - 1];
+        let (t1, v1) = self.points[next];
+        let frac = (time - t0) / (t1 - t0);
+        match interpolation {
+            Interpolation::Log | Interpolation::Pitch if v0 > 0.0 && v1 > 0.0 => {
+                v0 * (v1 / v0).powf(frac)
+            }
+            Interpolation::Pitch => v0,
+            _ => v0 + frac * (v1 - v0),
+        }
+    }
+
+    /// Values at `count` frame times spaced `interval` seconds apart from zero
+    pub fn resample(&self, interval: f64, count: usize, interpolation: Interpolation) -> Vec<f64> {
+        (0..count)
+            .map(|frame| self.value_at_with(frame as f64 * interval, interpolation))
+            .collect()
+    }
+}
+
+impl TryFrom<Vec<(f64, f64)>> for Breakpoints {
+    type Error = CoreError;
+
+    fn try_from(points: Vec<(f64, f64)>) -> Result<Self> {
+        Self::new(points)
+    }
+}
+
+impl From<Breakpoints> for Vec<(f64, f64)> {
+    fn from(breakpoints: Breakpoints) -> Self {
+        breakpoints.points
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_validate() {
+        let brk = Breakpoints::parse("0 1\n1.5 4\n\n2 2 3 8\n").unwrap();
+        assert_eq!(
+            brk.points(),
+            &[(0.0, 1.0), (1.5, 4.0), (2.0, 2.0), (3.0, 8.0)]
+        );
+        assert_eq!(brk.duration(), 3.0);
+        assert!(brk.check_range("Gain", 0.0, 8.0).is_ok());
+        let err = brk.check_range("Gain", 0.0, 4.0).unwrap_err();
+        assert!(err.to_string().contains("got 8 at time 3"));
+        let err = brk.check_range("Gain", 2.0, f64::INFINITY).unwrap_err();
+        assert!(err.to_string().contains("at least 2, got 1 at time 0"));
+
+        assert!(Breakpoints::parse("").is_err());
+        assert!(Breakpoints::parse("0 1 2").is_err());
+        assert!(Breakpoints::parse("0 x").is_err());
+        assert!(Breakpoints::parse("1 1 1 2").is_err());
+        assert!(Breakpoints::parse("-1 1").is_err());
+
+        let points: Vec<(f64, f64)> = brk.clone().into();
+        assert_eq!(Breakpoints::try_from(points).unwrap(), brk);
+        assert!(Breakpoints::try_from(vec![(1.0, 1.0), (1.0, 2.0)]).is_err());
+    }
+
+    #[test]
+    fn test_interpolation() {
+        let brk = Breakpoints::new(vec![(1.0, 100.0), (2.0, 400.0)]).unwrap();
+        assert_eq!(brk.value_at(0.0), 100.0);
+        assert_eq!(brk.value_at(1.5), 250.0);
+        assert_eq!(brk.value_at(3.0), 400.0);
+        assert!((brk.value_at_with(1.5, Interpolation::Log) - 200.0).abs() < 1e-9);
+        assert!((brk.value_at_with(1.5, Interpolation::Pitch) - 200.0).abs() < 1e-9);
+
+        let unpitched = Breakpoints::new(vec![(0.0, 100.0), (1.0, -1.0), (2.0, 100.0)]).unwrap();
+        assert_eq!(unpitched.value_at_with(0.5, Interpolation::Pitch), 100.0);
+        assert_eq!(unpitched.value_at_with(1.5, Interpolation::Pitch), -1.0);
+        assert_eq!(unpitched.value_at_with(0.5, Interpolation::Log), 49.5);
+
+        assert_eq!(
+            brk.resample(0.5, 5, Interpolation::Linear),
+            vec![100.0, 100.0, 100.0, 250.0, 400.0]
+        );
+        assert_eq!(Breakpoints::constant(3.0).value_at(10.0), 3.0);
+    }
+}
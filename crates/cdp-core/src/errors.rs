@@ -20,6 +20,30 @@ pub enum CoreError {
         window: usize,
     },
 
+    /// Audio buffer with inconsistent rate, channels or length
+    #[error("Invalid audio buffer: {0}")]
+    InvalidBuffer(String),
+
+    /// Malformed or inconsistent analysis file
+    #[error("Invalid analysis file: {0}")]
+    InvalidAnaFile(String),
+
+    /// Malformed or out-of-range breakpoint data
+    #[error("Invalid breakpoints: {0}")]
+    InvalidBreakpoints(String),
+
+    /// Parameter outside the range CDP accepts
+    #[error("{0}")]
+    OutOfRange(String),
+
+    /// I/O error reading or writing a file
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Operation stopped by its progress sink
+    #[error("Operation cancelled")]
+    Cancelled,
+
     /// General numerical computation error
     #[error("Numerical error: {0}")]
     Numerical(String),
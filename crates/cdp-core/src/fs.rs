@@ -0,0 +1,452 @@
+//! File access shared by every crate
+//!
+//! Library code opens, reads and writes files through these functions
+//! rather than `std::fs`, so that it still runs where there is no file
+//! system. Natively they are thin wrappers over `std::fs`. On
+//! `wasm32-unknown-unknown` paths name files in an in-memory store instead:
+//! a browser host puts its input there with [`memory::insert`], runs any
+//! path-based operation, and collects the output with [`memory::remove`].
+
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Whether paths refer to the in-memory store rather than the disk
+const IN_MEMORY: bool = cfg!(all(target_arch = "wasm32", target_os = "unknown"));
+
+/// An open file, on disk or in memory
+#[derive(Debug)]
+pub struct File(Inner);
+
+#[derive(Debug)]
+enum Inner {
+    Disk(std::fs::File),
+    Memory(memory::MemoryFile),
+}
+
+impl Read for File {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match &mut self.0 {
+            Inner::Disk(file) => file.read(buf),
+            Inner::Memory(file) => file.read(buf),
+        }
+    }
+}
+
+impl Write for File {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.0 {
+            Inner::Disk(file) => file.write(buf),
+            Inner::Memory(file) => file.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.0 {
+            Inner::Disk(file) => file.flush(),
+            Inner::Memory(file) => file.flush(),
+        }
+    }
+}
+
+impl Seek for File {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match &mut self.0 {
+            Inner::Disk(file) => file.seek(pos),
+            Inner::Memory(file) => file.seek(pos),
+        }
+    }
+}
+
+/// Open a file for reading
+pub fn open(path: impl AsRef<Path>) -> io::Result<File> {
+    let path = path.as_ref();
+    Ok(File(if IN_MEMORY {
+        Inner::Memory(memory::open(path)?)
+    } else {
+        Inner::Disk(std::fs::File::open(path)?)
+    }))
+}
+
+/// Create or truncate a file for writing
+pub fn create(path: impl AsRef<Path>) -> io::Result<File> {
+    let path = path.as_ref();
+    Ok(File(if IN_MEMORY {
+        Inner::Memory(memory::create(path))
+    } else {
+        Inner::Disk(std::fs::File::create(path)?)
+    }))
+}
+
+/// Read a whole file
+pub fn read(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    let path = path.as_ref();
+    if IN_MEMORY {
+        memory::get(path).ok_or_else(|| memory::not_found(path))
+    } else {
+        std::fs::read(path)
+    }
+}
+
+/// Read a whole file as UTF-8 text
+pub fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+    let path = path.as_ref();
+    String::from_utf8(read(path)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Write a whole file, replacing any existing contents
+pub fn write(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let path = path.as_ref();
+    if IN_MEMORY {
+        memory::insert(path, contents.as_ref().to_vec());
+        Ok(())
+    } else {
+        std::fs::write(path, contents)
+    }
+}
+
+/// Rename a file, replacing `to` if it exists
+pub fn rename(from: impl AsRef<Path>, to: impl AsRef<Path>) -> io::Result<()> {
+    let from = from.as_ref();
+    let to = to.as_ref();
+    if IN_MEMORY {
+        let contents = memory::remove(from).ok_or_else(|| memory::not_found(from))?;
+        memory::insert(to, contents);
+        Ok(())
+    } else {
+        std::fs::rename(from, to)
+    }
+}
+
+/// Delete a file
+pub fn remove_file(path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref();
+    if IN_MEMORY {
+        memory::remove(path)
+            .map(drop)
+            .ok_or_else(|| memory::not_found(path))
+    } else {
+        std::fs::remove_file(path)
+    }
+}
+
+/// Create a directory and its parents; the in-memory store needs none
+pub fn create_dir_all(path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref();
+    if IN_MEMORY {
+        Ok(())
+    } else {
+        std::fs::create_dir_all(path)
+    }
+}
+
+/// Paths of the entries directly inside `dir`, in no particular order
+pub fn read_dir(dir: impl AsRef<Path>) -> io::Result<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    if IN_MEMORY {
+        Ok(memory::entries(dir))
+    } else {
+        std::fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect()
+    }
+}
+
+/// Whether `path` is a file
+pub fn is_file(path: impl AsRef<Path>) -> bool {
+    let path = path.as_ref();
+    if IN_MEMORY {
+        memory::get(path).is_some()
+    } else {
+        path.is_file()
+    }
+}
+
+/// Whether `path` is a directory; in memory, whether any file is inside it
+pub fn is_dir(path: impl AsRef<Path>) -> bool {
+    let path = path.as_ref();
+    if IN_MEMORY {
+        memory::is_dir(path)
+    } else {
+        path.is_dir()
+    }
+}
+
+/// WAV files inside `dir` in name order; with `recursive`, each
+/// subdirectory's follow in place of its name
+pub fn wav_files(dir: impl AsRef<Path>, recursive: bool) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    collect_wav_files(dir.as_ref(), recursive, &mut files)?;
+    Ok(files)
+}
+
+fn collect_wav_files(dir: &Path, recursive: bool, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut entries = read_dir(dir)?;
+    entries.sort();
+    for path in entries {
+        if is_dir(&path) {
+            if recursive {
+                collect_wav_files(&path, recursive, files)?;
+            }
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("wav"))
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Whether `a` and `b` are the same existing file, once links and relative
+/// components are resolved
+pub fn same_file(a: impl AsRef<Path>, b: impl AsRef<Path>) -> bool {
+    let (a, b) = (a.as_ref(), b.as_ref());
+    if IN_MEMORY {
+        a == b && memory::get(a).is_some()
+    } else {
+        match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// The in-memory store backing paths on `wasm32-unknown-unknown`
+///
+/// Available on every target, so hosts and tests can fill and inspect it,
+/// but only consulted by the functions above on WebAssembly.
+pub mod memory {
+    use std::collections::BTreeMap;
+    use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
+    use std::path::{Path, PathBuf};
+    use std::sync::{Mutex, MutexGuard};
+
+    static FILES: Mutex<BTreeMap<PathBuf, Vec<u8>>> = Mutex::new(BTreeMap::new());
+
+    fn files() -> MutexGuard<'static, BTreeMap<PathBuf, Vec<u8>>> {
+        FILES
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub(super) fn not_found(path: &Path) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} is not in the in-memory store", path.display()),
+        )
+    }
+
+    /// Store `contents` at `path`, replacing any file already there
+    pub fn insert(path: &Path, contents: Vec<u8>) {
+        files().insert(path.to_path_buf(), contents);
+    }
+
+    /// A copy of the file at `path`
+    pub fn get(path: &Path) -> Option<Vec<u8>> {
+        files().get(path).cloned()
+    }
+
+    /// Take the file at `path` out of the store
+    pub fn remove(path: &Path) -> Option<Vec<u8>> {
+        files().remove(path)
+    }
+
+    /// Empty the store
+    pub fn clear() {
+        files().clear();
+    }
+
+    /// Paths of the files directly inside `dir`
+    pub fn list(dir: &Path) -> Vec<PathBuf> {
+        files()
+            .keys()
+            .filter(|path| path.parent() == Some(dir))
+            .cloned()
+            .collect()
+    }
+
+    /// Files and subdirectories directly inside `dir`
+    pub(super) fn entries(dir: &Path) -> Vec<PathBuf> {
+        let mut entries: Vec<PathBuf> = files()
+            .keys()
+            .filter_map(|path| {
+                let relative = path.strip_prefix(dir).ok()?;
+                relative.components().next().map(|first| dir.join(first))
+            })
+            .collect();
+        entries.dedup();
+        entries
+    }
+
+    /// Whether any file in the store is inside `dir`
+    pub(super) fn is_dir(dir: &Path) -> bool {
+        files()
+            .keys()
+            .any(|path| path != dir && path.starts_with(dir))
+    }
+
+    /// A file in the store, open for reading or writing
+    ///
+    /// Written contents reach the store when the file is flushed or
+    /// dropped.
+    #[derive(Debug)]
+    pub struct MemoryFile {
+        /// Where to store the contents, for files open for writing
+        path: Option<PathBuf>,
+        contents: Cursor<Vec<u8>>,
+    }
+
+    /// Open the file at `path` for reading
+    pub fn open(path: &Path) -> io::Result<MemoryFile> {
+        let contents = get(path).ok_or_else(|| not_found(path))?;
+        Ok(MemoryFile {
+            path: None,
+            contents: Cursor::new(contents),
+        })
+    }
+
+    /// Create an empty file at `path` for writing
+    pub fn create(path: &Path) -> MemoryFile {
+        insert(path, Vec::new());
+        MemoryFile {
+            path: Some(path.to_path_buf()),
+            contents: Cursor::new(Vec::new()),
+        }
+    }
+
+    impl Read for MemoryFile {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.contents.read(buf)
+        }
+    }
+
+    impl Write for MemoryFile {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.path.is_none() {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "File is open for reading only",
+                ));
+            }
+            self.contents.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            if let Some(path) = &self.path {
+                insert(path, self.contents.get_ref().clone());
+            }
+            Ok(())
+        }
+    }
+
+    impl Seek for MemoryFile {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.contents.seek(pos)
+        }
+    }
+
+    impl Drop for MemoryFile {
+        fn drop(&mut self) {
+            if let Some(path) = self.path.take() {
+                insert(&path, std::mem::take(self.contents.get_mut()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_files() {
+        let dir = Path::new("/memory-test");
+        let path = dir.join("a.bin");
+
+        let mut file = memory::create(&path);
+        file.write_all(b"hello world").unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        file.write_all(b"J").unwrap();
+        assert_eq!(memory::get(&path).unwrap(), b"");
+        file.flush().unwrap();
+        assert_eq!(memory::get(&path).unwrap(), b"Jello world");
+        file.write_all(b"!").unwrap();
+        drop(file);
+        assert_eq!(memory::get(&path).unwrap(), b"J!llo world");
+
+        let mut file = memory::open(&path).unwrap();
+        let mut text = String::new();
+        file.read_to_string(&mut text).unwrap();
+        assert_eq!(text, "J!llo world");
+        assert!(file.write_all(b"x").is_err());
+
+        memory::insert(&dir.join("b.bin"), vec![1, 2]);
+        memory::insert(&dir.join("sub/c.bin"), vec![3]);
+        let mut listed = memory::list(dir);
+        listed.sort();
+        assert_eq!(listed, [dir.join("a.bin"), dir.join("b.bin")]);
+        assert_eq!(
+            memory::entries(dir),
+            [dir.join("a.bin"), dir.join("b.bin"), dir.join("sub")]
+        );
+        assert!(memory::is_dir(dir) && memory::is_dir(&dir.join("sub")));
+        assert!(!memory::is_dir(&path));
+
+        assert_eq!(memory::remove(&path).unwrap(), b"J!llo world");
+        let missing = memory::open(&path).unwrap_err();
+        assert_eq!(missing.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_disk_files() {
+        let dir = std::env::temp_dir().join(format!("cdp-core-fs-{}", std::process::id()));
+        create_dir_all(&dir).unwrap();
+        let path = dir.join("a.txt");
+        let renamed = dir.join("b.txt");
+
+        let mut file = create(&path).unwrap();
+        file.write_all(b"breakpoints").unwrap();
+        drop(file);
+        rename(&path, &renamed).unwrap();
+        assert_eq!(read_to_string(&renamed).unwrap(), "breakpoints");
+        assert!(is_file(&renamed) && !is_file(&path));
+        assert!(same_file(&renamed, dir.join(".").join("b.txt")));
+        assert!(is_dir(&dir) && !is_dir(&renamed));
+        assert!(!same_file(&renamed, &path));
+        assert_eq!(read_dir(&dir).unwrap(), std::slice::from_ref(&renamed));
+
+        let mut file = open(&renamed).unwrap();
+        file.seek(SeekFrom::Start(5)).unwrap();
+        let mut tail = Vec::new();
+        file.read_to_end(&mut tail).unwrap();
+        assert_eq!(tail, b"points");
+
+        remove_file(&renamed).unwrap();
+        assert!(read(&renamed).is_err());
+        std::fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_wav_files() {
+        let dir = std::env::temp_dir().join(format!("cdp-core-wavs-{}", std::process::id()));
+        create_dir_all(dir.join("a.wav")).unwrap();
+        for name in ["b.WAV", "c.txt", "a.wav/d.wav", "e.wav"] {
+            write(dir.join(name), b"").unwrap();
+        }
+
+        assert_eq!(
+            wav_files(&dir, false).unwrap(),
+            [dir.join("b.WAV"), dir.join("e.wav")]
+        );
+        assert_eq!(
+            wav_files(&dir, true).unwrap(),
+            [
+                dir.join("a.wav/d.wav"),
+                dir.join("b.WAV"),
+                dir.join("e.wav")
+            ]
+        );
+        assert!(wav_files(dir.join("missing"), false).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -8,17 +8,40 @@
 //! This module is FROZEN after validation against CDP.
 //! Do not modify without explicit approval and re-validation.
 
+/// CDP analysis (.ana) files
+pub mod ana;
+/// Shared interleaved float audio buffer
+pub mod audio;
+/// Time-varying parameter breakpoints
+pub mod brkpnt;
 /// CDP-compatible constants and parameters
 pub mod constants;
 /// Error types for core operations
 pub mod errors;
 /// FFT processing for spectral analysis
 pub mod fft;
+/// File access that also works without a file system
+pub mod fs;
+/// CDP parameter ranges
+pub mod params;
+/// Progress reporting and cancellation
+pub mod progress;
+/// Seeded pseudo-random numbers
+pub mod rng;
 /// Window functions for spectral processing
 pub mod window;
 
+pub use ana::{
+    AnaFile, AnaFrameReader, AnaFrameWriter, AnaHeader, PitchFile, SpectralFrame, NOT_PITCH,
+    NOT_SOUND,
+};
+pub use audio::AudioBuffer;
+pub use brkpnt::{Breakpoints, Interpolation};
 pub use errors::{CoreError, Result};
 pub use fft::{Fft, FftProcessor};
+pub use params::{validate, Param, ParamRange};
+pub use progress::{CancelToken, ProgressSink, TextProgress};
+pub use rng::Rng;
 pub use window::{Window, WindowFunction};
 
 #[cfg(test)]
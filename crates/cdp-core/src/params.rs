@@ -0,0 +1,229 @@
+//! CDP parameter ranges
+//!
+//! One table of the ranges CDP documents for its parameters, so library
+//! functions and CLI wrappers reject the same values with the same
+//! message.
+
+use crate::{CoreError, Result};
+
+/// A CDP parameter with a documented range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Param {
+    /// Windows averaged by spectral blur (1 to the length of the file)
+    BlurWindows,
+    /// Spectral time-stretch factor
+    StretchFactor,
+    /// Spectral pitch shift as a frequency ratio
+    ShiftFactor,
+    /// Spectral pitch shift in whole octaves
+    OctaveShift,
+    /// Phase vocoder window overlap
+    Overlap,
+    /// Gain change in dB
+    GainDb,
+    /// Waveset multiplication factor
+    MultiplyFactor,
+    /// Waveset division factor
+    DivideFactor,
+    /// Overload clipping threshold
+    OverloadThreshold,
+    /// Overload drive
+    Drive,
+    /// Wavesets per group in distort operations
+    CycleCount,
+    /// Repetitions of each waveset group
+    Repeats,
+    /// Wet/dry mix
+    Mix,
+    /// Wavesets per group when reversing or repeating
+    WavesetGroup,
+    /// Waveset groups shuffled together
+    ShuffleWindow,
+    /// Level applied before waveset processing
+    Prescale,
+    /// Harmonic number in distort harmonic
+    HarmonicNumber,
+    /// Fractal scaling in distort fractal
+    FractalScaling,
+    /// Level of each fractal layer relative to the one above
+    FractalLoudness,
+    /// Pitch warp depth in semitones
+    WarpDepth,
+    /// Pitch warp step between wavesets
+    WarpStep,
+    /// Proportion of channels scattered by spectral scatter
+    ScatterProportion,
+    /// Spectral spread towards the frame's mean level
+    Spread,
+    /// Proportion of noise in spectral noise
+    NoiseAmount,
+    /// Harmonics in spectral add-harmonics, including the original
+    HarmonicCount,
+    /// Level of each added harmonic relative to the one below
+    HarmonicRolloff,
+}
+
+/// The documented range of a parameter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParamRange {
+    /// Name used in error messages
+    pub name: &'static str,
+    /// Smallest valid value
+    pub min: f64,
+    /// Largest valid value, infinite when only bounded below
+    pub max: f64,
+    /// Whether `min` itself is invalid, for values that must be positive
+    pub exclusive_min: bool,
+    /// Whether only whole numbers are valid
+    pub integer: bool,
+}
+
+impl Param {
+    /// Every parameter in the table
+    pub const ALL: [Param; 26] = [
+        Param::BlurWindows,
+        Param::StretchFactor,
+        Param::ShiftFactor,
+        Param::OctaveShift,
+        Param::Overlap,
+        Param::GainDb,
+        Param::MultiplyFactor,
+        Param::DivideFactor,
+        Param::OverloadThreshold,
+        Param::Drive,
+        Param::CycleCount,
+        Param::Repeats,
+        Param::Mix,
+        Param::WavesetGroup,
+        Param::ShuffleWindow,
+        Param::Prescale,
+        Param::HarmonicNumber,
+        Param::FractalScaling,
+        Param::FractalLoudness,
+        Param::WarpDepth,
+        Param::WarpStep,
+        Param::ScatterProportion,
+        Param::Spread,
+        Param::NoiseAmount,
+        Param::HarmonicCount,
+        Param::HarmonicRolloff,
+    ];
+
+    /// The range CDP accepts for this parameter
+    pub fn range(self) -> ParamRange {
+        let (name, min, max, integer) = match self {
+            Param::BlurWindows => ("Blur windows", 1.0, f64::INFINITY, true),
+            Param::StretchFactor => ("Stretch factor", 0.01, 100.0, false),
+            Param::ShiftFactor => ("Shift factor", 0.1, 10.0, false),
+            Param::OctaveShift => ("Octave shift", -8.0, 8.0, true),
+            Param::Overlap => ("Overlap", 1.0, 4.0, true),
+            Param::GainDb => ("dB gain", -96.0, 96.0, false),
+            Param::MultiplyFactor => ("Multiply factor", 1.0, 16.0, false),
+            Param::DivideFactor => ("Divide factor", 2.0, 16.0, true),
+            Param::OverloadThreshold => ("Threshold", 0.1, 1.0, false),
+            Param::Drive => ("Drive", 1.0, 100.0, false),
+            Param::CycleCount => ("Cycle count", 2.0, 1000.0, true),
+            Param::Repeats => ("Repeats", 1.0, 100.0, true),
+            Param::Mix => ("Mix", 0.0, 1.0, false),
+            Param::WavesetGroup => ("Cycle count", 1.0, 1000.0, true),
+            Param::ShuffleWindow => ("Shuffle window", 2.0, 1000.0, true),
+            Param::Prescale => ("Prescale", 0.0, f64::INFINITY, false),
+            Param::HarmonicNumber => ("Harmonic number", 1.0, 256.0, true),
+            Param::FractalScaling => ("Fractal scaling", 2.0, 16.0, true),
+            Param::FractalLoudness => ("Fractal loudness", 0.0, 1.0, false),
+            Param::WarpDepth => ("Warp depth", 0.0, 48.0, false),
+            Param::WarpStep => ("Warp step", 0.0, 1.0, false),
+            Param::ScatterProportion => ("Scatter proportion", 0.0, 1.0, false),
+            Param::Spread => ("Spread", 0.0, 1.0, false),
+            Param::NoiseAmount => ("Noise amount", 0.0, 1.0, false),
+            Param::HarmonicCount => ("Harmonic count", 2.0, f64::INFINITY, true),
+            Param::HarmonicRolloff => ("Harmonic rolloff", 0.0, 1.0, false),
+        };
+        let exclusive_min = matches!(
+            self,
+            Param::Prescale | Param::FractalLoudness | Param::WarpStep | Param::HarmonicRolloff
+        );
+        ParamRange {
+            name,
+            min,
+            max,
+            exclusive_min,
+            integer,
+        }
+    }
+}
+
+/// Check a value against a parameter's CDP range, returning it unchanged
+///
+/// Bounds are compared at single precision, so `f32` arguments widened
+/// with `as f64` sit exactly on them.
+pub fn validate(param: Param, value: f64) -> Result<f64> {
+    let range = param.range();
+    let single = value as f32;
+    let above_min = if range.exclusive_min {
+        single > range.min as f32
+    } else {
+        single >= range.min as f32
+    };
+    if !(above_min && single <= range.max as f32 && single.is_finite()) {
+        let message = match (range.exclusive_min, range.max.is_infinite()) {
+            (false, true) => format!("{} must be at least {}", range.name, range.min),
+            (false, false) => format!(
+                "{} must be between {} and {}",
+                range.name, range.min, range.max
+            ),
+            (true, true) => format!("{} must be greater than {}", range.name, range.min),
+            (true, false) => format!(
+                "{} must be greater than {} and at most {}",
+                range.name, range.min, range.max
+            ),
+        };
+        return Err(CoreError::OutOfRange(message));
+    }
+    if range.integer && value.fract() != 0.0 {
+        return Err(CoreError::OutOfRange(format!(
+            "{} must be a whole number",
+            range.name
+        )));
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(validate(Param::StretchFactor, 2.5).unwrap(), 2.5);
+        assert_eq!(
+            validate(Param::StretchFactor, 0.001)
+                .unwrap_err()
+                .to_string(),
+            "Stretch factor must be between 0.01 and 100"
+        );
+        assert_eq!(
+            validate(Param::BlurWindows, 0.0).unwrap_err().to_string(),
+            "Blur windows must be at least 1"
+        );
+        assert!(validate(Param::BlurWindows, 1e6).is_ok());
+        assert!(validate(Param::CycleCount, 2.5).is_err());
+        assert!(validate(Param::Mix, f64::NAN).is_err());
+
+        // Positive-only parameters reject their lower bound
+        assert!(validate(Param::WarpStep, 1.0).is_ok());
+        assert_eq!(
+            validate(Param::WarpStep, 0.0).unwrap_err().to_string(),
+            "Warp step must be greater than 0 and at most 1"
+        );
+        assert_eq!(
+            validate(Param::Prescale, -1.0).unwrap_err().to_string(),
+            "Prescale must be greater than 0"
+        );
+        assert!(validate(Param::Prescale, f64::INFINITY).is_err());
+
+        // Single-precision callers sit exactly on the bounds
+        assert!(validate(Param::OverloadThreshold, 0.1f32 as f64).is_ok());
+        assert!(Param::ALL.iter().all(|p| p.range().min <= p.range().max));
+    }
+}
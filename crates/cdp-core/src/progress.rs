@@ -0,0 +1,149 @@
+//! Progress reporting and cancellation for long-running operations
+//!
+//! Analysing or stretching a long file can take minutes. Operations that
+//! accept a [`ProgressSink`] report how many of their frames or windows are
+//! done, and stop with [`CoreError::Cancelled`] once the sink asks them to.
+
+use crate::errors::{CoreError, Result};
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Width of the text progress bar, in characters
+const BAR_WIDTH: usize = 40;
+
+/// Receives progress from a long-running operation, and can stop it
+pub trait ProgressSink {
+    /// `done` of `total` units (frames or windows) have been processed
+    fn progress(&mut self, done: usize, total: usize);
+
+    /// Whether the operation should stop; checked at every report
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+}
+
+/// Ignores progress and never cancels
+impl ProgressSink for () {
+    fn progress(&mut self, _done: usize, _total: usize) {}
+}
+
+/// Report progress to `sink`, failing if it asks for cancellation
+pub fn report(sink: &mut dyn ProgressSink, done: usize, total: usize) -> Result<()> {
+    sink.progress(done, total);
+    if sink.is_cancelled() {
+        return Err(CoreError::Cancelled);
+    }
+    Ok(())
+}
+
+/// Cancellation flag shared between the caller and a running operation
+///
+/// Clones share the flag, so one clone can be handed to the operation as
+/// its sink while another cancels it from a different thread.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Create a token that has not been cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the operation to stop at its next progress report
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+impl ProgressSink for CancelToken {
+    fn progress(&mut self, _done: usize, _total: usize) {}
+
+    fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Progress bar drawn on standard error, for command-line programs
+///
+/// Draws nothing unless standard error is a terminal, so scripted runs
+/// and their captured output are unaffected.
+#[derive(Debug)]
+pub struct TextProgress {
+    enabled: bool,
+    percent: Option<usize>,
+}
+
+impl TextProgress {
+    /// Create a bar, enabled only when standard error is a terminal
+    pub fn new() -> Self {
+        Self {
+            enabled: io::stderr().is_terminal(),
+            percent: None,
+        }
+    }
+}
+
+impl Default for TextProgress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressSink for TextProgress {
+    fn progress(&mut self, done: usize, total: usize) {
+        if !self.enabled || total == 0 {
+            return;
+        }
+        // Redraw only when the whole percentage changes
+        let percent = done.min(total) * 100 / total;
+        if self.percent == Some(percent) {
+            return;
+        }
+        self.percent = Some(percent);
+
+        let filled = percent * BAR_WIDTH / 100;
+        let mut stderr = io::stderr().lock();
+        let _ = write!(
+            stderr,
+            "\r[{}{}] {:3}%",
+            "#".repeat(filled),
+            " ".repeat(BAR_WIDTH - filled),
+            percent
+        );
+        if percent == 100 {
+            let _ = writeln!(stderr);
+        }
+        let _ = stderr.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Recorder(Vec<(usize, usize)>);
+
+    impl ProgressSink for Recorder {
+        fn progress(&mut self, done: usize, total: usize) {
+            self.0.push((done, total));
+        }
+    }
+
+    #[test]
+    fn test_report_and_cancel() {
+        let mut recorder = Recorder(Vec::new());
+        report(&mut recorder, 1, 4).unwrap();
+        report(&mut recorder, 4, 4).unwrap();
+        assert_eq!(recorder.0, [(1, 4), (4, 4)]);
+
+        let mut token = CancelToken::new();
+        report(&mut token, 1, 4).unwrap();
+        token.clone().cancel();
+        assert!(matches!(
+            report(&mut token, 2, 4),
+            Err(CoreError::Cancelled)
+        ));
+        assert!(report(&mut (), 2, 4).is_ok());
+    }
+}
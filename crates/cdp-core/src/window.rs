@@ -3,6 +3,8 @@ use std::f32::consts::PI;
 
 /// Window function types for spectral processing
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum WindowFunction {
     /// Hann (Hanning) window - good frequency resolution
     Hann,
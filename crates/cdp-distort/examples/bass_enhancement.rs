@@ -2,7 +2,6 @@
 
 use cdp_distort::{divide, multiply, overload, ClipType};
 use hound::{SampleFormat, WavSpec, WavWriter};
-use std::f32::consts::PI;
 use std::fs;
 use std::path::Path;
 
@@ -25,30 +24,19 @@ fn generate_bass_line(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
         (41.2, 0.5), // E1
     ];
 
-    let note_duration = 0.5;
-    let samples_per_note = (sample_rate as f32 * note_duration) as usize;
+    for &(freq, note_duration) in notes.iter() {
+        // Bass with harmonics
+        let note = cdp_synth::partials(
+            &[(freq, 0.6), (freq * 2.0, 0.2), (freq * 3.0, 0.1)],
+            note_duration,
+            sample_rate,
+        )?;
 
-    for (freq, _) in notes.iter().cycle().take(8) {
-        for i in 0..samples_per_note {
+        for (i, sample) in note.data.into_iter().enumerate() {
             let t = i as f32 / sample_rate as f32;
 
-            // Bass with harmonics
-            let fundamental = (2.0 * PI * freq * t).sin() * 0.6;
-            let second = (2.0 * PI * freq * 2.0 * t).sin() * 0.2;
-            let third = (2.0 * PI * freq * 3.0 * t).sin() * 0.1;
-
             // Pluck envelope
-            let envelope = (-t * 4.0).exp();
-
-            let sample = (fundamental + second + third) * envelope;
-            samples.push(sample);
-
-            if samples.len() >= num_samples {
-                break;
-            }
-        }
-        if samples.len() >= num_samples {
-            break;
+            samples.push(sample * (-t * 4.0).exp());
         }
     }
 
@@ -9,24 +9,20 @@ use std::path::Path;
 fn generate_test_signal(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
     // Generate a complex test signal with multiple frequencies
     let sample_rate = 44100;
-    let duration = 2.0;
-    let num_samples = (sample_rate as f32 * duration) as usize;
 
-    let mut samples = Vec::with_capacity(num_samples);
+    // Mix of frequencies
+    let signal = cdp_synth::partials(
+        &[(220.0, 0.3), (440.0, 0.2), (110.0, 0.2)],
+        2.0,
+        sample_rate,
+    )?;
 
-    for i in 0..num_samples {
+    let mut samples = signal.data;
+    for (i, sample) in samples.iter_mut().enumerate() {
         let t = i as f32 / sample_rate as f32;
 
-        // Mix of frequencies
-        let fundamental = (2.0 * PI * 220.0 * t).sin() * 0.3;
-        let harmonic = (2.0 * PI * 440.0 * t).sin() * 0.2;
-        let sub = (2.0 * PI * 110.0 * t).sin() * 0.2;
-
         // Envelope
-        let envelope = (0.5 + 0.5 * (2.0 * PI * 0.5 * t).sin()) * 0.8;
-
-        let sample = (fundamental + harmonic + sub) * envelope;
-        samples.push(sample);
+        *sample *= (0.5 + 0.5 * (2.0 * PI * 0.5 * t).sin()) * 0.8;
     }
 
     let spec = WavSpec {
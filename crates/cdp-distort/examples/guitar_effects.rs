@@ -2,33 +2,27 @@
 
 use cdp_distort::{overload, ClipType};
 use hound::{SampleFormat, WavSpec, WavWriter};
-use std::f32::consts::PI;
 use std::fs;
 use std::path::Path;
 
 fn generate_guitar_note(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
     let sample_rate = 44100;
-    let duration = 3.0;
-    let num_samples = (sample_rate as f32 * duration) as usize;
 
-    let mut samples = Vec::with_capacity(num_samples);
+    // E note (82.4 Hz) with harmonics like a guitar
+    let note = cdp_synth::partials(
+        &[(82.4, 0.5), (164.8, 0.3), (247.2, 0.15), (329.6, 0.1)],
+        3.0,
+        sample_rate,
+    )?;
 
-    for i in 0..num_samples {
+    let mut samples = note.data;
+    for (i, sample) in samples.iter_mut().enumerate() {
         let t = i as f32 / sample_rate as f32;
 
-        // E note (82.4 Hz) with harmonics like a guitar
-        let fundamental = (2.0 * PI * 82.4 * t).sin() * 0.5;
-        let second = (2.0 * PI * 164.8 * t).sin() * 0.3;
-        let third = (2.0 * PI * 247.2 * t).sin() * 0.15;
-        let fourth = (2.0 * PI * 329.6 * t).sin() * 0.1;
-
         // Attack/decay envelope
         let attack = (t * 20.0).min(1.0);
         let decay = (-t * 0.5).exp();
-        let envelope = attack * decay;
-
-        let sample = (fundamental + second + third + fourth) * envelope;
-        samples.push(sample);
+        *sample *= attack * decay;
     }
 
     let spec = WavSpec {
@@ -8,19 +8,18 @@ use std::path::Path;
 
 fn generate_vocal_sample(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
     let sample_rate = 44100;
-    let duration = 2.0;
-    let num_samples = (sample_rate as f32 * duration) as usize;
 
-    let mut samples = Vec::with_capacity(num_samples);
+    // Simulate vocal formants
+    let formants = cdp_synth::partials(
+        &[(700.0, 0.3), (1220.0, 0.2), (2600.0, 0.1)],
+        2.0,
+        sample_rate,
+    )?;
 
-    for i in 0..num_samples {
+    let mut samples = formants.data;
+    for (i, sample) in samples.iter_mut().enumerate() {
         let t = i as f32 / sample_rate as f32;
 
-        // Simulate vocal formants
-        let f1 = (2.0 * PI * 700.0 * t).sin() * 0.3; // First formant
-        let f2 = (2.0 * PI * 1220.0 * t).sin() * 0.2; // Second formant
-        let f3 = (2.0 * PI * 2600.0 * t).sin() * 0.1; // Third formant
-
         // Add some vibrato
         let vibrato_freq = 5.0;
         let vibrato_depth = 0.02;
@@ -34,8 +33,7 @@ fn generate_vocal_sample(path: &Path) -> Result<(), Box<dyn std::error::Error>>
         let attack = (t * 10.0).min(1.0);
         let envelope = attack * phrase_envelope;
 
-        let sample = (fundamental + f1 + f2 + f3) * envelope;
-        samples.push(sample);
+        *sample = (fundamental + *sample) * envelope;
     }
 
     let spec = WavSpec {
@@ -0,0 +1,89 @@
+//! Waveset averaging
+//!
+//! Replaces groups of wavesets with their average shape, like CDP's
+//! distort average, smoothing the waveform's irregularities into a more
+//! periodic, buzzy tone.
+
+use crate::error::{check_param, Result};
+use crate::wav_io::write_audio;
+use crate::waveset::{read_mono, resample_waveset, wavesets};
+use cdp_core::Param;
+use std::path::Path;
+
+/// Replace each group of wavesets with their average shape
+///
+/// # Arguments
+/// * `input_path` - Path to mono input audio file
+/// * `output_path` - Path to output audio file
+/// * `cycle_count` - Wavesets per averaged group (2-1000)
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(DistortError)` on failure
+pub fn average(input_path: &Path, output_path: &Path, cycle_count: usize) -> Result<()> {
+    check_cycle_count(cycle_count)?;
+    let mut audio = read_mono(input_path)?;
+    audio.data = average_buf(&audio.data, cycle_count)?;
+    write_audio(output_path, &audio)
+}
+
+/// Replace each group of wavesets of in-memory mono samples with their average
+///
+/// Buffer-based counterpart of [`average`]; returns the processed samples.
+/// Wavesets of unequal length are time-normalized to the group's mean
+/// length before averaging, and the average is repeated once per waveset
+/// in the group, so each group keeps its length to within rounding. A
+/// final short group is averaged over the wavesets it has.
+pub fn average_buf(samples: &[f32], cycle_count: usize) -> Result<Vec<f32>> {
+    check_cycle_count(cycle_count)?;
+
+    let sets: Vec<&[f32]> = wavesets(samples).collect();
+    let mut output = Vec::with_capacity(samples.len());
+    for group in sets.chunks(cycle_count) {
+        let shape = average_shape(group);
+        for _ in 0..group.len() {
+            output.extend_from_slice(&shape);
+        }
+    }
+
+    Ok(output)
+}
+
+/// Average of a non-empty group of wavesets, time-normalized to their mean length
+pub(crate) fn average_shape(group: &[&[f32]]) -> Vec<f32> {
+    let total: usize = group.iter().map(|set| set.len()).sum();
+    let len = (total as f32 / group.len() as f32).round().max(1.0) as usize;
+
+    let mut shape = vec![0.0f32; len];
+    for waveset in group {
+        for (sum, value) in shape.iter_mut().zip(resample_waveset(waveset, len)) {
+            *sum += value;
+        }
+    }
+    let scale = 1.0 / group.len() as f32;
+    shape.iter_mut().for_each(|value| *value *= scale);
+    shape
+}
+
+fn check_cycle_count(cycle_count: usize) -> Result<()> {
+    check_param(Param::CycleCount, cycle_count as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_average_buf() {
+        // A two-sample and a six-sample waveset average at four samples
+        let samples = [0.4, -0.4, 0.0, 0.8, 0.8, 0.8, -0.8, -0.8];
+        let output = average_buf(&samples, 2).unwrap();
+        assert_eq!(output.len(), samples.len());
+        assert_eq!(&output[..4], &output[4..]);
+        for (value, expected) in output.iter().zip([0.2, 0.4, 0.2, -0.6]) {
+            assert!((value - expected).abs() < 1e-6);
+        }
+
+        assert!(average_buf(&samples, 1).is_err());
+    }
+}
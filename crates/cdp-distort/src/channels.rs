@@ -0,0 +1,73 @@
+//! Per-channel processing of interleaved audio
+//!
+//! Sample-by-sample effects with state, such as zero-crossing detection,
+//! must see each channel on its own rather than the interleaved stream.
+
+/// Run `process` on each channel of interleaved samples and reinterleave
+///
+/// `process` receives the channel index and that channel's samples, and
+/// must return as many samples as it was given.
+pub(crate) fn process_channels(
+    samples: &[f32],
+    channels: usize,
+    mut process: impl FnMut(usize, &[f32]) -> Vec<f32>,
+) -> Vec<f32> {
+    if channels <= 1 {
+        return process(0, samples);
+    }
+
+    let mut output = vec![0.0; samples.len()];
+    for channel in 0..channels {
+        let input: Vec<f32> = samples
+            .iter()
+            .skip(channel)
+            .step_by(channels)
+            .copied()
+            .collect();
+        for (slot, value) in output
+            .iter_mut()
+            .skip(channel)
+            .step_by(channels)
+            .zip(process(channel, &input))
+        {
+            *slot = value;
+        }
+    }
+    output
+}
+
+/// Scale samples down to a 0.99 peak if any exceed full scale
+///
+/// Applied once across every channel so their balance is kept.
+pub(crate) fn normalize(samples: &mut [f32]) {
+    let max_val = samples
+        .iter()
+        .map(|s| s.abs())
+        .fold(0.0f32, |a, b| a.max(b));
+
+    if max_val > 1.0 {
+        let scale = 0.99 / max_val;
+        for sample in samples.iter_mut() {
+            *sample *= scale;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_channels() {
+        let samples = [1.0, -1.0, 2.0, -2.0, 3.0, -3.0];
+        let output = process_channels(&samples, 2, |channel, input| {
+            assert_eq!(input.len(), 3);
+            input.iter().map(|s| s * (channel + 1) as f32).collect()
+        });
+        assert_eq!(output, vec![1.0, -2.0, 2.0, -4.0, 3.0, -6.0]);
+
+        let mut loud = vec![2.0, -0.5];
+        normalize(&mut loud);
+        assert_eq!(loud, vec![0.99, -0.2475]);
+    }
+}
@@ -0,0 +1,194 @@
+//! Waveset deletion and omission
+//!
+//! Thins a sound out waveset by waveset: [`delete`] time-contracts it by
+//! keeping one waveset of each group, like CDP's distort delete, while
+//! [`omit`] keeps its length and replaces the dropped wavesets, like CDP's
+//! distort omit.
+
+use crate::error::{check_param, Result};
+use crate::wav_io::write_audio;
+use crate::waveset::{join_wavesets, read_mono, wavesets};
+use cdp_core::{Param, Rng};
+use std::path::Path;
+
+/// Which waveset of each group [`delete`] keeps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DeleteMode {
+    /// The first of the group (CDP mode 1)
+    First,
+    /// The one with the highest peak (CDP mode 2)
+    Strongest,
+    /// The one with the lowest peak (CLI mode 4, not in CDP)
+    Weakest,
+    /// One chosen at random, from a generator seeded with this value (CDP
+    /// mode 3)
+    Random(u32),
+}
+
+/// What [`omit`] puts in place of the wavesets it drops
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum OmitFill {
+    /// Silence of the same length
+    Silence,
+    /// A repeat of the last waveset kept
+    Hold,
+}
+
+/// Time-contract a sound by keeping one waveset in each group
+///
+/// # Arguments
+/// * `input_path` - Path to mono input audio file
+/// * `output_path` - Path to output audio file
+/// * `cycle_count` - Wavesets per group, one of which is kept (2-1000)
+/// * `mode` - Which waveset of each group to keep
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(DistortError)` on failure
+pub fn delete(
+    input_path: &Path,
+    output_path: &Path,
+    cycle_count: usize,
+    mode: DeleteMode,
+) -> Result<()> {
+    check_cycle_count(cycle_count)?;
+    let mut audio = read_mono(input_path)?;
+    audio.data = delete_buf(&audio.data, cycle_count, mode)?;
+    write_audio(output_path, &audio)
+}
+
+/// Keep one waveset in each group of in-memory mono samples
+///
+/// Buffer-based counterpart of [`delete`]; returns the processed samples.
+/// A final incomplete group still contributes one waveset.
+pub fn delete_buf(samples: &[f32], cycle_count: usize, mode: DeleteMode) -> Result<Vec<f32>> {
+    check_cycle_count(cycle_count)?;
+
+    let mut rng = match mode {
+        DeleteMode::Random(seed) => Some(Rng::new(seed)),
+        _ => None,
+    };
+    let sets: Vec<&[f32]> = wavesets(samples).collect();
+    let kept = sets.chunks(cycle_count).map(|group| {
+        let peaks = group.iter().map(|set| peak(set));
+        let index = match mode {
+            DeleteMode::First => 0,
+            DeleteMode::Strongest => position_by(peaks, |a, b| a > b),
+            DeleteMode::Weakest => position_by(peaks, |a, b| a < b),
+            DeleteMode::Random(_) => rng.as_mut().unwrap().below(group.len()),
+        };
+        group[index]
+    });
+
+    Ok(join_wavesets(kept))
+}
+
+/// Keep one waveset in every few, replacing the rest
+///
+/// # Arguments
+/// * `input_path` - Path to mono input audio file
+/// * `output_path` - Path to output audio file
+/// * `keep_every` - Keep the first waveset of each group of this many (2-1000)
+/// * `fill` - What replaces the other wavesets of each group
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(DistortError)` on failure
+pub fn omit(
+    input_path: &Path,
+    output_path: &Path,
+    keep_every: usize,
+    fill: OmitFill,
+) -> Result<()> {
+    check_cycle_count(keep_every)?;
+    let mut audio = read_mono(input_path)?;
+    audio.data = omit_buf(&audio.data, keep_every, fill)?;
+    write_audio(output_path, &audio)
+}
+
+/// Keep one waveset in every few of in-memory mono samples
+///
+/// Buffer-based counterpart of [`omit`]; returns the processed samples.
+/// Silence keeps the length exactly; holding follows the kept waveset's
+/// length, so it keeps the length of periodic sounds only approximately.
+pub fn omit_buf(samples: &[f32], keep_every: usize, fill: OmitFill) -> Result<Vec<f32>> {
+    check_cycle_count(keep_every)?;
+
+    let mut output = Vec::with_capacity(samples.len());
+    for group in wavesets(samples).collect::<Vec<_>>().chunks(keep_every) {
+        let kept = group[0];
+        output.extend_from_slice(kept);
+        for dropped in &group[1..] {
+            match fill {
+                OmitFill::Silence => output.resize(output.len() + dropped.len(), 0.0),
+                OmitFill::Hold => output.extend_from_slice(kept),
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+/// Largest absolute sample of a waveset
+fn peak(waveset: &[f32]) -> f32 {
+    waveset.iter().map(|s| s.abs()).fold(0.0f32, f32::max)
+}
+
+/// Index of the first value that no later value beats
+fn position_by(values: impl Iterator<Item = f32>, beats: impl Fn(f32, f32) -> bool) -> usize {
+    let mut best: Option<(usize, f32)> = None;
+    for (index, value) in values.enumerate() {
+        if best.map_or(true, |(_, best_value)| beats(value, best_value)) {
+            best = Some((index, value));
+        }
+    }
+    best.map_or(0, |(index, _)| index)
+}
+
+fn check_cycle_count(cycle_count: usize) -> Result<()> {
+    check_param(Param::CycleCount, cycle_count as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delete_validation() {
+        let input = Path::new("test.wav");
+        let output = Path::new("out.wav");
+
+        assert!(delete(input, output, 1, DeleteMode::First).is_err());
+        assert!(omit(input, output, 1001, OmitFill::Silence).is_err());
+    }
+
+    #[test]
+    fn test_delete_and_omit_buf() {
+        // Three wavesets of rising level
+        let samples = [0.25, -0.25, 0.5, -0.5, 1.0, -1.0];
+
+        let output = delete_buf(&samples, 2, DeleteMode::First).unwrap();
+        assert_eq!(output, vec![0.25, -0.25, 1.0, -1.0]);
+        let output = delete_buf(&samples, 3, DeleteMode::Strongest).unwrap();
+        assert_eq!(output, vec![1.0, -1.0]);
+        let output = delete_buf(&samples, 3, DeleteMode::Weakest).unwrap();
+        assert_eq!(output, vec![0.25, -0.25]);
+
+        // The same seed picks the same wavesets
+        let random = delete_buf(&samples, 3, DeleteMode::Random(7)).unwrap();
+        assert_eq!(random.len(), 2);
+        assert_eq!(
+            random,
+            delete_buf(&samples, 3, DeleteMode::Random(7)).unwrap()
+        );
+
+        let output = omit_buf(&samples, 2, OmitFill::Silence).unwrap();
+        assert_eq!(output, vec![0.25, -0.25, 0.0, 0.0, 1.0, -1.0]);
+        let output = omit_buf(&samples, 3, OmitFill::Hold).unwrap();
+        assert_eq!(output, vec![0.25, -0.25, 0.25, -0.25, 0.25, -0.25]);
+    }
+}
@@ -2,12 +2,17 @@
 //!
 //! Creates subharmonics by dividing signal frequency content.
 
-use crate::error::{DistortError, Result};
-use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+use crate::channels::{normalize, process_channels};
+use crate::error::{check_param, Result};
+use crate::wav_io::{read_audio, write_audio};
+use cdp_core::Param;
 use std::path::Path;
 
 /// Apply subharmonic division distortion
 ///
+/// Each channel of a multichannel file is processed on its own, so zero
+/// crossings are tracked per channel.
+///
 /// # Arguments
 /// * `input_path` - Path to input audio file
 /// * `output_path` - Path to output audio file
@@ -18,43 +23,35 @@ use std::path::Path;
 /// * `Ok(())` on success
 /// * `Err(DistortError)` on failure
 pub fn divide(input_path: &Path, output_path: &Path, divide_factor: u32, mix: f32) -> Result<()> {
-    // Validate parameters
-    if !(2..=16).contains(&divide_factor) {
-        return Err(DistortError::InvalidInput(
-            "Divide factor must be between 2 and 16".to_string(),
-        ));
-    }
-
-    if !(0.0..=1.0).contains(&mix) {
-        return Err(DistortError::InvalidInput(
-            "Mix must be between 0.0 and 1.0".to_string(),
-        ));
-    }
+    check_params(divide_factor, mix)?;
+    let mut audio = read_audio(input_path)?;
+    audio.data = process_channels(&audio.data, audio.channels as usize, |_, channel| {
+        divide_samples(channel, divide_factor, mix)
+    });
+    normalize(&mut audio.data);
+    write_audio(output_path, &audio)
+}
 
-    // Open input file
-    let reader = WavReader::open(input_path)?;
-    let spec = reader.spec();
-
-    // Collect samples
-    let samples: Vec<f32> = match spec.sample_format {
-        SampleFormat::Float => reader
-            .into_samples::<f32>()
-            .collect::<std::result::Result<Vec<_>, _>>()?,
-        SampleFormat::Int => {
-            // Prevent integer overflow for large bit depths
-            if spec.bits_per_sample >= 32 {
-                return Err(DistortError::InvalidInput(
-                    "Bit depth too large for safe processing".to_string(),
-                ));
-            }
-            let max_val = (1 << (spec.bits_per_sample - 1)) as f32;
-            reader
-                .into_samples::<i32>()
-                .map(|s| s.map(|sample| sample as f32 / max_val))
-                .collect::<std::result::Result<Vec<_>, _>>()?
-        }
-    };
+/// Apply subharmonic division distortion to interleaved in-memory samples
+///
+/// Buffer-based counterpart of [`divide`]; returns the processed samples.
+/// Zero crossings are tracked separately for each of `channels`.
+pub fn divide_buf(
+    samples: &[f32],
+    channels: usize,
+    divide_factor: u32,
+    mix: f32,
+) -> Result<Vec<f32>> {
+    check_params(divide_factor, mix)?;
+    let mut output = process_channels(samples, channels, |_, channel| {
+        divide_samples(channel, divide_factor, mix)
+    });
+    normalize(&mut output);
+    Ok(output)
+}
 
+/// Divide one channel of samples, without normalizing
+fn divide_samples(samples: &[f32], divide_factor: u32, mix: f32) -> Vec<f32> {
     // Process samples with subharmonic generation
     let mut output = Vec::with_capacity(samples.len());
     let mut last_sample = 0.0f32;
@@ -77,31 +74,12 @@ pub fn divide(input_path: &Path, output_path: &Path, divide_factor: u32, mix: f3
         last_sample = *sample;
     }
 
-    // Normalize
-    let max_val = output.iter().map(|s| s.abs()).fold(0.0f32, |a, b| a.max(b));
-
-    if max_val > 1.0 {
-        let scale = 0.99 / max_val;
-        for sample in output.iter_mut() {
-            *sample *= scale;
-        }
-    }
-
-    // Write output
-    let output_spec = WavSpec {
-        channels: spec.channels,
-        sample_rate: spec.sample_rate,
-        bits_per_sample: 32,
-        sample_format: SampleFormat::Float,
-    };
-
-    let mut writer = WavWriter::create(output_path, output_spec)?;
-    for sample in output {
-        writer.write_sample(sample)?;
-    }
-    writer.finalize()?;
+    output
+}
 
-    Ok(())
+fn check_params(divide_factor: u32, mix: f32) -> Result<()> {
+    check_param(Param::DivideFactor, divide_factor as f64)?;
+    check_param(Param::Mix, mix as f64)
 }
 
 #[cfg(test)]
@@ -127,4 +105,25 @@ mod tests {
         let result = divide(input, output, 2, 1.5);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_divide_buf_channels() {
+        let left = [0.5, -0.5, 0.5, -0.5, 0.5, -0.5];
+        let right = [0.25, 0.25, -0.25, -0.25, 0.25, 0.25];
+        let stereo: Vec<f32> = left
+            .iter()
+            .zip(&right)
+            .flat_map(|(&l, &r)| [l, r])
+            .collect();
+
+        // Each channel counts its own zero crossings
+        let output = divide_buf(&stereo, 2, 2, 1.0).unwrap();
+        let mut expected: Vec<f32> = divide_samples(&left, 2, 1.0)
+            .into_iter()
+            .zip(divide_samples(&right, 2, 1.0))
+            .flat_map(|(l, r)| [l, r])
+            .collect();
+        normalize(&mut expected);
+        assert_eq!(output, expected);
+    }
 }
@@ -9,9 +9,13 @@ pub enum DistortError {
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
 
-    /// Audio format error
-    #[error("Audio format error: {0}")]
-    AudioFormat(#[from] hound::Error),
+    /// WAV read or write error
+    #[error("Housekeep error: {0}")]
+    Housekeep(#[from] cdp_housekeep::HousekeepError),
+
+    /// Invalid audio buffer
+    #[error("Core error: {0}")]
+    Core(#[from] cdp_core::CoreError),
 
     /// Invalid input parameter
     #[error("Invalid input: {0}")]
@@ -24,3 +28,10 @@ pub enum DistortError {
 
 /// Result type for distortion operations
 pub type Result<T> = std::result::Result<T, DistortError>;
+
+/// Check a value against its CDP range, reporting failures as invalid input
+pub(crate) fn check_param(param: cdp_core::Param, value: f64) -> Result<()> {
+    cdp_core::validate(param, value)
+        .map(|_| ())
+        .map_err(|e| DistortError::InvalidInput(e.to_string()))
+}
@@ -0,0 +1,168 @@
+//! Waveset harmonic superimposition
+//!
+//! Adds copies of each waveset, squeezed to a whole fraction of its length
+//! and repeated to fill it, onto the waveset itself. [`harmonic`] adds a
+//! chosen set of harmonics, like CDP's distort harmonic; [`fractal`] adds
+//! ever smaller copies of the waveform, like CDP's distort fractal.
+
+use crate::channels::normalize;
+use crate::error::{check_param, DistortError, Result};
+use crate::wav_io::write_audio;
+use crate::waveset::{read_mono, wavesets};
+use cdp_core::Param;
+use std::path::Path;
+
+/// Superimpose harmonics onto every waveset
+///
+/// # Arguments
+/// * `input_path` - Path to mono input audio file
+/// * `output_path` - Path to output audio file
+/// * `harmonics` - (harmonic number 1-256, amplitude) pairs; harmonic 1 is
+///   the waveset itself
+/// * `prescale` - Gain applied to the input before processing (> 0)
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(DistortError)` on failure
+pub fn harmonic(
+    input_path: &Path,
+    output_path: &Path,
+    harmonics: &[(usize, f32)],
+    prescale: f32,
+) -> Result<()> {
+    check_harmonics(harmonics, prescale)?;
+    let mut audio = read_mono(input_path)?;
+    audio.data = harmonic_buf(&audio.data, harmonics, prescale)?;
+    write_audio(output_path, &audio)
+}
+
+/// Superimpose harmonics onto every waveset of in-memory mono samples
+///
+/// Buffer-based counterpart of [`harmonic`]; returns the processed samples.
+/// Harmonic `n` plays the waveset `n` times over its own length by reading
+/// every `n`th sample, so it follows the waveset's shape exactly.
+pub fn harmonic_buf(
+    samples: &[f32],
+    harmonics: &[(usize, f32)],
+    prescale: f32,
+) -> Result<Vec<f32>> {
+    check_harmonics(harmonics, prescale)?;
+    let scaled: Vec<f32> = samples.iter().map(|s| s * prescale).collect();
+    Ok(superimpose(&scaled, |_| harmonics.to_vec()))
+}
+
+/// Superimpose smaller and smaller copies of every waveset onto itself
+///
+/// # Arguments
+/// * `input_path` - Path to mono input audio file
+/// * `output_path` - Path to output audio file
+/// * `scaling` - Length ratio between successive copies (2-16)
+/// * `loudness` - Level of each copy relative to the one above (0-1]
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(DistortError)` on failure
+pub fn fractal(input_path: &Path, output_path: &Path, scaling: usize, loudness: f32) -> Result<()> {
+    check_fractal(scaling, loudness)?;
+    let mut audio = read_mono(input_path)?;
+    audio.data = fractal_buf(&audio.data, scaling, loudness)?;
+    write_audio(output_path, &audio)
+}
+
+/// Superimpose smaller copies of every waveset of in-memory mono samples
+///
+/// Buffer-based counterpart of [`fractal`]; returns the processed samples.
+/// Copies at `scaling`, `scaling²`, ... times the waveset's rate are added
+/// at `loudness`, `loudness²`, ... for as long as a copy is at least two
+/// samples long.
+pub fn fractal_buf(samples: &[f32], scaling: usize, loudness: f32) -> Result<Vec<f32>> {
+    check_fractal(scaling, loudness)?;
+    Ok(superimpose(samples, |len| {
+        let mut layers = vec![(1, 1.0)];
+        let (mut multiple, mut level) = (scaling, loudness);
+        while len / multiple >= 2 {
+            layers.push((multiple, level));
+            multiple *= scaling;
+            level *= loudness;
+        }
+        layers
+    }))
+}
+
+/// Sum resampled copies of each waveset, normalizing if the result clips
+///
+/// `layers` gives the (multiple, amplitude) copies for a waveset of the
+/// given length.
+fn superimpose(samples: &[f32], layers: impl Fn(usize) -> Vec<(usize, f32)>) -> Vec<f32> {
+    let mut output = Vec::with_capacity(samples.len());
+    for waveset in wavesets(samples) {
+        let len = waveset.len();
+        let layers = layers(len);
+        output.extend((0..len).map(|i| {
+            layers
+                .iter()
+                .map(|&(multiple, amp)| amp * waveset[i * multiple % len])
+                .sum::<f32>()
+        }));
+    }
+
+    normalize(&mut output);
+
+    output
+}
+
+fn check_harmonics(harmonics: &[(usize, f32)], prescale: f32) -> Result<()> {
+    if harmonics.is_empty() {
+        return Err(DistortError::InvalidInput(
+            "Harmonics must not be empty".to_string(),
+        ));
+    }
+
+    for &(number, amp) in harmonics {
+        check_param(Param::HarmonicNumber, number as f64)?;
+        if !amp.is_finite() {
+            return Err(DistortError::InvalidInput(
+                "Harmonic amplitudes must be finite".to_string(),
+            ));
+        }
+    }
+    check_param(Param::Prescale, prescale as f64)
+}
+
+fn check_fractal(scaling: usize, loudness: f32) -> Result<()> {
+    check_param(Param::FractalScaling, scaling as f64)?;
+    check_param(Param::FractalLoudness, loudness as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_harmonic_validation() {
+        let input = Path::new("test.wav");
+        let output = Path::new("out.wav");
+
+        assert!(harmonic(input, output, &[], 1.0).is_err());
+        assert!(harmonic(input, output, &[(0, 1.0)], 1.0).is_err());
+        assert!(fractal(input, output, 1, 0.5).is_err());
+        assert!(fractal(input, output, 2, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_harmonic_and_fractal_buf() {
+        // One four-sample waveset
+        let samples = [0.0, 0.4, 0.0, -0.4];
+
+        // The second harmonic reads every other sample
+        let output = harmonic_buf(&samples, &[(1, 1.0), (2, 0.5)], 1.0).unwrap();
+        assert_eq!(output, vec![0.0, 0.4, 0.0, -0.4]);
+        let output = harmonic_buf(&samples, &[(3, 1.0)], 0.5).unwrap();
+        assert_eq!(output, vec![0.0, -0.2, 0.0, 0.2]);
+
+        // Only one copy, at half length, fits in four samples
+        let samples = [0.1, 0.4, -0.2, -0.3];
+        let output = fractal_buf(&samples, 2, 0.5).unwrap();
+        assert_eq!(output, vec![0.15, 0.3, -0.15, -0.4]);
+    }
+}
@@ -0,0 +1,127 @@
+//! Two-input waveset operations
+//!
+//! Builds a sound from the wavesets of two others: [`interleave`]
+//! alternates between them, like CDP's distort interact, and
+//! [`interpolate`] morphs waveset by waveset from the first to the second.
+
+use crate::error::{DistortError, Result};
+use crate::wav_io::write_audio;
+use crate::waveset::{read_mono, resample_waveset, wavesets};
+use cdp_core::AudioBuffer;
+use std::path::Path;
+
+/// Alternate wavesets from two sounds
+///
+/// # Arguments
+/// * `input_a` - Path to first mono input audio file
+/// * `input_b` - Path to second mono input audio file
+/// * `output_path` - Path to output audio file
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(DistortError)` on failure
+pub fn interleave(input_a: &Path, input_b: &Path, output_path: &Path) -> Result<()> {
+    two_inputs(input_a, input_b, output_path, interleave_buf)
+}
+
+/// Alternate wavesets of two in-memory mono signals
+///
+/// Buffer-based counterpart of [`interleave`]; returns the processed
+/// samples. The output starts with a waveset of A and stops when either
+/// input runs out.
+pub fn interleave_buf(a: &[f32], b: &[f32]) -> Result<Vec<f32>> {
+    let mut output = Vec::with_capacity(a.len().max(b.len()));
+    for (set_a, set_b) in wavesets(a).zip(wavesets(b)) {
+        output.extend_from_slice(set_a);
+        output.extend_from_slice(set_b);
+    }
+    Ok(output)
+}
+
+/// Morph waveset shapes from one sound to another
+///
+/// # Arguments
+/// * `input_a` - Path to mono input audio file to morph from
+/// * `input_b` - Path to mono input audio file to morph to
+/// * `output_path` - Path to output audio file
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(DistortError)` on failure
+pub fn interpolate(input_a: &Path, input_b: &Path, output_path: &Path) -> Result<()> {
+    two_inputs(input_a, input_b, output_path, interpolate_buf)
+}
+
+/// Morph waveset shapes between two in-memory mono signals
+///
+/// Buffer-based counterpart of [`interpolate`]; returns the processed
+/// samples. The `k`th wavesets of A and B are resampled to a common length
+/// and crossfaded, moving linearly from all A to all B across as many
+/// wavesets as the shorter input has. Lengths are interpolated too, so the
+/// pitch glides between the two.
+pub fn interpolate_buf(a: &[f32], b: &[f32]) -> Result<Vec<f32>> {
+    let sets_a: Vec<&[f32]> = wavesets(a).collect();
+    let sets_b: Vec<&[f32]> = wavesets(b).collect();
+    let count = sets_a.len().min(sets_b.len());
+
+    let mut output = Vec::with_capacity(a.len().max(b.len()));
+    for (k, (set_a, set_b)) in sets_a.iter().zip(&sets_b).enumerate() {
+        let mix = if count > 1 {
+            k as f32 / (count - 1) as f32
+        } else {
+            0.0
+        };
+        let len = (set_a.len() as f32 + (set_b.len() as f32 - set_a.len() as f32) * mix)
+            .round()
+            .max(1.0) as usize;
+        let shape_a = resample_waveset(set_a, len);
+        let shape_b = resample_waveset(set_b, len);
+        output.extend(
+            shape_a
+                .iter()
+                .zip(&shape_b)
+                .map(|(&sa, &sb)| sa + (sb - sa) * mix),
+        );
+    }
+    Ok(output)
+}
+
+/// Read two mono inputs with a common sample rate, process and write
+fn two_inputs(
+    input_a: &Path,
+    input_b: &Path,
+    output_path: &Path,
+    process: fn(&[f32], &[f32]) -> Result<Vec<f32>>,
+) -> Result<()> {
+    let a = read_mono(input_a)?;
+    let b = read_mono(input_b)?;
+    if a.sample_rate != b.sample_rate {
+        return Err(DistortError::InvalidInput(
+            "Input files must have the same sample rate".to_string(),
+        ));
+    }
+    let output = process(&a.data, &b.data)?;
+    write_audio(output_path, &AudioBuffer::new(a.sample_rate, 1, output)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interleave_and_interpolate_buf() {
+        // Three two-sample wavesets and two four-sample ones
+        let a = [0.2, -0.2, 0.4, -0.4, 0.6, -0.6];
+        let b = [0.0, 1.0, 0.0, -1.0, 0.0, 0.5, 0.0, -0.5];
+
+        let output = interleave_buf(&a, &b).unwrap();
+        assert_eq!(
+            output,
+            vec![0.2, -0.2, 0.0, 1.0, 0.0, -1.0, 0.4, -0.4, 0.0, 0.5, 0.0, -0.5]
+        );
+
+        // Starts as A's first waveset and ends as B's second
+        let output = interpolate_buf(&a, &b).unwrap();
+        assert_eq!(output, vec![0.2, -0.2, 0.0, 0.5, 0.0, -0.5]);
+    }
+}
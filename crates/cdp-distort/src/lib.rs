@@ -4,14 +4,171 @@
 //! Distortion and saturation effects
 //!
 //! This module provides various distortion algorithms including
-//! harmonic multiplication, subharmonic generation, and clipping.
+//! harmonic multiplication, subharmonic generation, and clipping, along
+//! with waveset segmentation for CDP's waveset distortions.
 
+pub mod average;
+mod channels;
+pub mod cli;
+pub mod delete;
 pub mod divide;
 pub mod error;
+pub mod harmonic;
+pub mod interact;
 pub mod multiply;
 pub mod overload;
+pub mod reform;
+pub mod reorder;
+pub mod repeat;
+pub mod telescope;
+pub mod warp;
+mod wav_io;
+pub mod waveset;
 
-pub use divide::divide;
+pub use average::{average, average_buf};
+pub use delete::{delete, delete_buf, omit, omit_buf, DeleteMode, OmitFill};
+pub use divide::{divide, divide_buf};
 pub use error::{DistortError, Result};
-pub use multiply::multiply;
-pub use overload::{overload, ClipType};
+pub use harmonic::{fractal, fractal_buf, harmonic, harmonic_buf};
+pub use interact::{interleave, interleave_buf, interpolate, interpolate_buf};
+pub use multiply::{multiply, multiply_buf};
+pub use overload::{
+    overload, overload_buf, overload_per_channel, overload_per_channel_buf, overload_with_curve,
+    overload_with_curve_buf, ClipType, CurveInterpolation,
+};
+pub use reform::{reform, reform_buf, ReformShape};
+pub use reorder::{reverse, reverse_buf, shuffle, shuffle_buf};
+pub use repeat::{repeat, repeat_buf};
+pub use telescope::{telescope, telescope_buf, TelescopeMode};
+pub use warp::{pitch_warp, pitch_warp_buf, WarpMode};
+pub use waveset::{join_wavesets, resample_waveset, transform_wavesets, wavesets, Wavesets};
+
+/// CLI compatibility layer - matches CDP's command-line interface
+///
+/// Takes CDP's positional syntax: `average infile outfile cyclecnt`,
+/// `multiply infile outfile N`, `divide infile outfile N`, `reverse infile
+/// outfile cyclecnt`, `repeat infile outfile multiplier [-ccyclecnt]`,
+/// `delete mode infile outfile cyclecnt [-sseed]` (modes 1 in time order, 2
+/// keep the loudest, 3 at random, and 4, beyond CDP, keep the quietest),
+/// `fractal infile outfile scaling loudness`, `reform mode
+/// infile outfile` (modes 1-7, see [`ReformShape::from_mode`]) and
+/// `interact 1 infile1 infile2 outfile`. Other operations and modes report
+/// that they are not yet implemented.
+pub fn distort(operation: &str, args: &[&str]) -> Result<()> {
+    use std::path::Path;
+
+    let usage =
+        |syntax: &str| DistortError::InvalidInput(format!("Usage: {} {}", operation, syntax));
+    let number = |arg: &str| {
+        arg.parse::<f64>()
+            .map_err(|_| DistortError::InvalidInput(format!("Invalid value: {}", arg)))
+    };
+
+    match (operation, args) {
+        ("average", &[input, output, cycles]) => average(
+            Path::new(input),
+            Path::new(output),
+            number(cycles)? as usize,
+        ),
+        ("multiply", &[input, output, factor]) => multiply(
+            Path::new(input),
+            Path::new(output),
+            number(factor)? as f32,
+            1.0,
+        ),
+        ("divide", &[input, output, factor]) => divide(
+            Path::new(input),
+            Path::new(output),
+            number(factor)? as u32,
+            1.0,
+        ),
+        ("reverse", &[input, output, cycles]) => reverse(
+            Path::new(input),
+            Path::new(output),
+            number(cycles)? as usize,
+        ),
+        ("repeat", &[input, output, multiplier, ref flags @ ..]) => {
+            let mut cycles = 1;
+            for flag in flags {
+                match flag.strip_prefix("-c") {
+                    Some(value) => cycles = number(value)? as usize,
+                    None => {
+                        return Err(DistortError::InvalidInput(format!(
+                            "Unknown repeat flag: {}",
+                            flag
+                        )))
+                    }
+                }
+            }
+            repeat(
+                Path::new(input),
+                Path::new(output),
+                number(multiplier)? as u32,
+                cycles,
+                1.0,
+                1.0,
+            )
+        }
+        ("delete", &[mode, input, output, cycles, ref flags @ ..]) => {
+            let mut seed = 0;
+            for flag in flags {
+                match flag.strip_prefix("-s") {
+                    Some(value) => seed = number(value)? as u32,
+                    None => {
+                        return Err(DistortError::InvalidInput(format!(
+                            "Unknown delete flag: {}",
+                            flag
+                        )))
+                    }
+                }
+            }
+            let mode = match mode {
+                "1" => DeleteMode::First,
+                "2" => DeleteMode::Strongest,
+                "3" => DeleteMode::Random(seed),
+                "4" => DeleteMode::Weakest,
+                _ => {
+                    return Err(DistortError::InvalidInput(format!(
+                        "Delete mode {} not yet implemented",
+                        mode
+                    )))
+                }
+            };
+            delete(
+                Path::new(input),
+                Path::new(output),
+                number(cycles)? as usize,
+                mode,
+            )
+        }
+        ("fractal", &[input, output, scaling, loudness]) => fractal(
+            Path::new(input),
+            Path::new(output),
+            number(scaling)? as usize,
+            number(loudness)? as f32,
+        ),
+        ("reform", &[mode, input, output]) => reform(
+            Path::new(input),
+            Path::new(output),
+            ReformShape::from_mode(number(mode)? as i32)?,
+        ),
+        ("interact", &["1", first, second, output]) => {
+            interleave(Path::new(first), Path::new(second), Path::new(output))
+        }
+        ("interact", &[mode, _, _, _]) => Err(DistortError::InvalidInput(format!(
+            "Interact mode {} not yet implemented",
+            mode
+        ))),
+        ("average" | "reverse", _) => Err(usage("infile outfile cyclecnt")),
+        ("multiply" | "divide", _) => Err(usage("infile outfile N")),
+        ("repeat", _) => Err(usage("infile outfile multiplier [-ccyclecnt]")),
+        ("delete", _) => Err(usage("mode infile outfile cyclecnt")),
+        ("fractal", _) => Err(usage("infile outfile scaling loudness")),
+        ("reform", _) => Err(usage("mode infile outfile")),
+        ("interact", _) => Err(usage("mode infile1 infile2 outfile")),
+        _ => Err(DistortError::InvalidInput(format!(
+            "Distort operation {} not yet implemented",
+            operation
+        ))),
+    }
+}
@@ -2,12 +2,16 @@
 //!
 //! Creates harmonic distortion by multiplying signal frequency content.
 
-use crate::error::{DistortError, Result};
-use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+use crate::channels::{normalize, process_channels};
+use crate::error::{check_param, Result};
+use crate::wav_io::{read_audio, write_audio};
+use cdp_core::Param;
 use std::path::Path;
 
 /// Apply harmonic multiplication distortion
 ///
+/// Each channel of a multichannel file is processed on its own.
+///
 /// # Arguments
 /// * `input_path` - Path to input audio file
 /// * `output_path` - Path to output audio file
@@ -23,44 +27,29 @@ pub fn multiply(
     multiply_factor: f32,
     mix: f32,
 ) -> Result<()> {
-    // Validate parameters
-    if !(1.0..=16.0).contains(&multiply_factor) {
-        return Err(DistortError::InvalidInput(
-            "Multiply factor must be between 1.0 and 16.0".to_string(),
-        ));
-    }
+    check_params(multiply_factor, mix)?;
+    let mut audio = read_audio(input_path)?;
+    audio.data = process_channels(&audio.data, audio.channels as usize, |_, channel| {
+        multiply_samples(channel, multiply_factor, mix)
+    });
+    normalize(&mut audio.data);
+    write_audio(output_path, &audio)
+}
 
-    if !(0.0..=1.0).contains(&mix) {
-        return Err(DistortError::InvalidInput(
-            "Mix must be between 0.0 and 1.0".to_string(),
-        ));
-    }
+/// Apply harmonic multiplication distortion to in-memory samples
+///
+/// Buffer-based counterpart of [`multiply`]; returns the processed samples.
+/// Each sample is shaped on its own, so interleaved samples of any channel
+/// count can be passed directly.
+pub fn multiply_buf(samples: &[f32], multiply_factor: f32, mix: f32) -> Result<Vec<f32>> {
+    check_params(multiply_factor, mix)?;
+    let mut output = multiply_samples(samples, multiply_factor, mix);
+    normalize(&mut output);
+    Ok(output)
+}
 
-    // Open input file
-    let reader = WavReader::open(input_path)?;
-    let spec = reader.spec();
-
-    // Collect samples
-    let samples: Vec<f32> = match spec.sample_format {
-        SampleFormat::Float => reader
-            .into_samples::<f32>()
-            .collect::<std::result::Result<Vec<_>, _>>()?,
-        SampleFormat::Int => {
-            // Prevent integer overflow for large bit depths
-            if spec.bits_per_sample >= 32 {
-                return Err(DistortError::InvalidInput(
-                    "Bit depth too large for safe processing".to_string(),
-                ));
-            }
-            let max_val = (1 << (spec.bits_per_sample - 1)) as f32;
-            reader
-                .into_samples::<i32>()
-                .map(|s| s.map(|sample| sample as f32 / max_val))
-                .collect::<std::result::Result<Vec<_>, _>>()?
-        }
-    };
-
-    // Process samples
+/// Multiply one channel of samples, without normalizing
+fn multiply_samples(samples: &[f32], multiply_factor: f32, mix: f32) -> Vec<f32> {
     let mut output = Vec::with_capacity(samples.len());
 
     for sample in samples.iter() {
@@ -73,31 +62,12 @@ pub fn multiply(
         output.push(result);
     }
 
-    // Normalize to prevent clipping
-    let max_val = output.iter().map(|s| s.abs()).fold(0.0f32, |a, b| a.max(b));
-
-    if max_val > 1.0 {
-        let scale = 0.99 / max_val;
-        for sample in output.iter_mut() {
-            *sample *= scale;
-        }
-    }
-
-    // Write output
-    let output_spec = WavSpec {
-        channels: spec.channels,
-        sample_rate: spec.sample_rate,
-        bits_per_sample: 32,
-        sample_format: SampleFormat::Float,
-    };
-
-    let mut writer = WavWriter::create(output_path, output_spec)?;
-    for sample in output {
-        writer.write_sample(sample)?;
-    }
-    writer.finalize()?;
+    output
+}
 
-    Ok(())
+fn check_params(multiply_factor: f32, mix: f32) -> Result<()> {
+    check_param(Param::MultiplyFactor, multiply_factor as f64)?;
+    check_param(Param::Mix, mix as f64)
 }
 
 #[cfg(test)]
@@ -123,4 +93,16 @@ mod tests {
         let result = multiply(input, output, 2.0, 1.5);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_multiply_buf() {
+        // A fully dry mix passes samples through unchanged
+        let samples = vec![0.5, -0.25, 0.0];
+        assert_eq!(multiply_buf(&samples, 4.0, 0.0).unwrap(), samples);
+
+        // A fully wet mix applies the tanh shaper
+        let wet = multiply_buf(&samples, 2.0, 1.0).unwrap();
+        assert!((wet[0] - 1.0f32.tanh()).abs() < 1e-6);
+        assert!((wet[1] - (-0.5f32).tanh()).abs() < 1e-6);
+    }
 }
@@ -1,13 +1,18 @@
 //! Clipping distortion effects
 //!
-//! Various types of clipping and saturation distortion.
+//! Various types of clipping and saturation distortion, plus waveshaping
+//! through a user-drawn transfer curve.
 
-use crate::error::{DistortError, Result};
-use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+use crate::channels::{normalize, process_channels};
+use crate::error::{check_param, DistortError, Result};
+use crate::wav_io::{read_audio, write_audio};
+use cdp_core::Param;
 use std::path::Path;
 
 /// Clipping curve types
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum ClipType {
     /// Hard clipping (digital)
     Hard,
@@ -19,8 +24,21 @@ pub enum ClipType {
     Asymmetric,
 }
 
+/// How [`overload_with_curve`] joins the points of a transfer curve
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CurveInterpolation {
+    /// Straight lines between points
+    Linear,
+    /// A smooth cubic through every point
+    Cubic,
+}
+
 /// Apply clipping/overload distortion
 ///
+/// Each channel of a multichannel file is processed on its own.
+///
 /// # Arguments
 /// * `input_path` - Path to input audio file
 /// * `output_path` - Path to output audio file
@@ -38,44 +56,91 @@ pub fn overload(
     drive: f32,
     clip_type: ClipType,
 ) -> Result<()> {
-    // Validate parameters
-    if !(0.1..=1.0).contains(&threshold) {
-        return Err(DistortError::InvalidInput(
-            "Threshold must be between 0.1 and 1.0".to_string(),
-        ));
+    check_params(threshold, drive)?;
+    let mut audio = read_audio(input_path)?;
+    let drives = vec![drive; audio.channels as usize];
+    audio.data = overload_per_channel_buf(&audio.data, threshold, &drives, clip_type)?;
+    write_audio(output_path, &audio)
+}
+
+/// Apply clipping/overload distortion to in-memory samples
+///
+/// Buffer-based counterpart of [`overload`]; returns the processed samples.
+pub fn overload_buf(
+    samples: &[f32],
+    threshold: f32,
+    drive: f32,
+    clip_type: ClipType,
+) -> Result<Vec<f32>> {
+    check_params(threshold, drive)?;
+    let mut output = overload_samples(samples, threshold, drive, clip_type);
+    normalize(&mut output);
+    Ok(output)
+}
+
+/// Apply clipping/overload distortion with a separate drive for each channel
+///
+/// # Arguments
+/// * `input_path` - Path to input audio file
+/// * `output_path` - Path to output audio file
+/// * `threshold` - Clipping threshold (0.1-1.0)
+/// * `drives` - Input gain before clipping (1.0-100.0), one per channel
+/// * `clip_type` - Type of clipping curve
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(DistortError)` on failure
+pub fn overload_per_channel(
+    input_path: &Path,
+    output_path: &Path,
+    threshold: f32,
+    drives: &[f32],
+    clip_type: ClipType,
+) -> Result<()> {
+    for &drive in drives {
+        check_params(threshold, drive)?;
+    }
+    let mut audio = read_audio(input_path)?;
+    if drives.len() != audio.channels as usize {
+        return Err(DistortError::InvalidInput(format!(
+            "Expected {} drives, one per channel, but got {}",
+            audio.channels,
+            drives.len()
+        )));
     }
+    audio.data = overload_per_channel_buf(&audio.data, threshold, drives, clip_type)?;
+    write_audio(output_path, &audio)
+}
 
-    if !(1.0..=100.0).contains(&drive) {
+/// Apply per-channel clipping/overload distortion to interleaved samples
+///
+/// Buffer-based counterpart of [`overload_per_channel`]; returns the
+/// processed samples. The number of drives sets the channel count.
+/// Normalization is shared by all channels, so their balance is kept.
+pub fn overload_per_channel_buf(
+    samples: &[f32],
+    threshold: f32,
+    drives: &[f32],
+    clip_type: ClipType,
+) -> Result<Vec<f32>> {
+    if drives.is_empty() || samples.len() % drives.len() != 0 {
         return Err(DistortError::InvalidInput(
-            "Drive must be between 1.0 and 100.0".to_string(),
+            "Drives must be given for every channel of the samples".to_string(),
         ));
     }
+    for &drive in drives {
+        check_params(threshold, drive)?;
+    }
 
-    // Open input file
-    let reader = WavReader::open(input_path)?;
-    let spec = reader.spec();
-
-    // Collect samples
-    let samples: Vec<f32> = match spec.sample_format {
-        SampleFormat::Float => reader
-            .into_samples::<f32>()
-            .collect::<std::result::Result<Vec<_>, _>>()?,
-        SampleFormat::Int => {
-            // Prevent integer overflow for large bit depths
-            if spec.bits_per_sample >= 32 {
-                return Err(DistortError::InvalidInput(
-                    "Bit depth too large for safe processing".to_string(),
-                ));
-            }
-            let max_val = (1 << (spec.bits_per_sample - 1)) as f32;
-            reader
-                .into_samples::<i32>()
-                .map(|s| s.map(|sample| sample as f32 / max_val))
-                .collect::<std::result::Result<Vec<_>, _>>()?
-        }
-    };
+    let mut output = process_channels(samples, drives.len(), |channel, input| {
+        overload_samples(input, threshold, drives[channel], clip_type)
+    });
+    normalize(&mut output);
+    Ok(output)
+}
 
-    // Process samples
+/// Drive and clip one channel of samples, without normalizing
+fn overload_samples(samples: &[f32], threshold: f32, drive: f32, clip_type: ClipType) -> Vec<f32> {
     let mut output = Vec::with_capacity(samples.len());
 
     for sample in samples.iter() {
@@ -100,31 +165,116 @@ pub fn overload(
         output.push(result);
     }
 
-    // Final normalization
-    let max_val = output.iter().map(|s| s.abs()).fold(0.0f32, |a, b| a.max(b));
+    output
+}
+
+/// Waveshape through a user transfer curve
+///
+/// # Arguments
+/// * `input_path` - Path to input audio file
+/// * `output_path` - Path to output audio file
+/// * `curve` - Vec of (input level, output level) pairs, input levels
+///   increasing between -1.0 and 1.0
+/// * `interpolation` - How levels between the curve's points are found
+/// * `drive` - Input gain before shaping (1.0-100.0)
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(DistortError)` on failure
+pub fn overload_with_curve(
+    input_path: &Path,
+    output_path: &Path,
+    curve: &[(f32, f32)],
+    interpolation: CurveInterpolation,
+    drive: f32,
+) -> Result<()> {
+    check_curve(curve, drive)?;
+    let mut audio = read_audio(input_path)?;
+    audio.data = overload_with_curve_buf(&audio.data, curve, interpolation, drive)?;
+    write_audio(output_path, &audio)
+}
+
+/// Waveshape in-memory samples through a user transfer curve
+///
+/// Buffer-based counterpart of [`overload_with_curve`]; returns the
+/// processed samples. Driven samples beyond the curve's first or last
+/// input level take that point's output level. The cubic is a Hermite
+/// spline with tangents from neighbouring points, so a curve drawn from
+/// Chebyshev polynomial values reproduces their harmonics closely.
+pub fn overload_with_curve_buf(
+    samples: &[f32],
+    curve: &[(f32, f32)],
+    interpolation: CurveInterpolation,
+    drive: f32,
+) -> Result<Vec<f32>> {
+    check_curve(curve, drive)?;
+
+    let mut output: Vec<f32> = samples
+        .iter()
+        .map(|&sample| shape(curve, interpolation, sample * drive))
+        .collect();
+    normalize(&mut output);
+    Ok(output)
+}
+
+/// Output level of a transfer curve for one input level
+fn shape(curve: &[(f32, f32)], interpolation: CurveInterpolation, level: f32) -> f32 {
+    let last = curve.len() - 1;
+    if level <= curve[0].0 {
+        return curve[0].1;
+    }
+    if level >= curve[last].0 {
+        return curve[last].1;
+    }
 
-    if max_val > 1.0 {
-        let scale = 0.99 / max_val;
-        for sample in output.iter_mut() {
-            *sample *= scale;
+    let upper = curve.partition_point(|&(x, _)| x <= level);
+    let (x0, y0) = curve[upper - 1];
+    let (x1, y1) = curve[upper];
+    let width = x1 - x0;
+    let t = (level - x0) / width;
+    match interpolation {
+        CurveInterpolation::Linear => y0 + (y1 - y0) * t,
+        CurveInterpolation::Cubic => {
+            let m0 = tangent(curve, upper - 1);
+            let m1 = tangent(curve, upper);
+            let t2 = t * t;
+            let t3 = t2 * t;
+            (2.0 * t3 - 3.0 * t2 + 1.0) * y0
+                + (t3 - 2.0 * t2 + t) * width * m0
+                + (-2.0 * t3 + 3.0 * t2) * y1
+                + (t3 - t2) * width * m1
         }
     }
+}
 
-    // Write output
-    let output_spec = WavSpec {
-        channels: spec.channels,
-        sample_rate: spec.sample_rate,
-        bits_per_sample: 32,
-        sample_format: SampleFormat::Float,
-    };
+/// Slope of a transfer curve at one of its points, from its neighbours
+fn tangent(curve: &[(f32, f32)], index: usize) -> f32 {
+    let before = curve[index.saturating_sub(1)];
+    let after = curve[(index + 1).min(curve.len() - 1)];
+    (after.1 - before.1) / (after.0 - before.0)
+}
 
-    let mut writer = WavWriter::create(output_path, output_spec)?;
-    for sample in output {
-        writer.write_sample(sample)?;
+fn check_curve(curve: &[(f32, f32)], drive: f32) -> Result<()> {
+    if curve.len() < 2 {
+        return Err(DistortError::InvalidInput(
+            "Transfer curve must have at least 2 points".to_string(),
+        ));
     }
-    writer.finalize()?;
+    if curve
+        .iter()
+        .any(|&(x, y)| !(-1.0..=1.0).contains(&x) || !y.is_finite())
+        || curve.windows(2).any(|pair| pair[1].0 <= pair[0].0)
+    {
+        return Err(DistortError::InvalidInput(
+            "Transfer curve input levels must increase between -1.0 and 1.0".to_string(),
+        ));
+    }
+    check_param(Param::Drive, drive as f64)
+}
 
-    Ok(())
+fn check_params(threshold: f32, drive: f32) -> Result<()> {
+    check_param(Param::OverloadThreshold, threshold as f64)?;
+    check_param(Param::Drive, drive as f64)
 }
 
 /// Hard clipping function
@@ -220,4 +370,39 @@ mod tests {
         assert_eq!(asymmetric_clip(0.8, 0.7), 0.7);
         assert!(asymmetric_clip(-0.6, 0.7).abs() <= 0.6);
     }
+
+    #[test]
+    fn test_overload_per_channel_buf() {
+        // Left clipped hard, right barely driven
+        let samples = [0.5, 0.5, -0.5, -0.5];
+        let output = overload_per_channel_buf(&samples, 0.5, &[4.0, 1.0], ClipType::Hard).unwrap();
+        assert_eq!(output, vec![0.25, 0.5, -0.25, -0.5]);
+
+        assert!(overload_per_channel_buf(&samples, 0.5, &[1.0; 3], ClipType::Hard).is_err());
+        assert!(overload_per_channel_buf(&samples, 0.5, &[1.0, 0.5], ClipType::Hard).is_err());
+    }
+
+    #[test]
+    fn test_overload_with_curve_buf() {
+        let curve = [(-1.0, -1.0), (0.0, 0.0), (1.0, 0.5)];
+        let output =
+            overload_with_curve_buf(&[0.5, -0.5, 0.75], &curve, CurveInterpolation::Linear, 2.0)
+                .unwrap();
+        assert_eq!(output, vec![0.5, -1.0, 0.5]);
+
+        // A straight line stays straight under cubic interpolation
+        let line = [(-1.0, -1.0), (0.0, 0.0), (1.0, 1.0)];
+        let output =
+            overload_with_curve_buf(&[0.3, -0.6], &line, CurveInterpolation::Cubic, 1.0).unwrap();
+        assert!((output[0] - 0.3).abs() < 1e-6 && (output[1] + 0.6).abs() < 1e-6);
+
+        assert!(
+            overload_with_curve_buf(&[0.0], &[(0.0, 0.0)], CurveInterpolation::Linear, 1.0)
+                .is_err()
+        );
+        let unordered = [(0.5, 0.0), (0.0, 1.0)];
+        assert!(
+            overload_with_curve_buf(&[0.0], &unordered, CurveInterpolation::Linear, 1.0).is_err()
+        );
+    }
 }
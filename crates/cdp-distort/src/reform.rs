@@ -0,0 +1,178 @@
+//! Waveset reshaping
+//!
+//! Replaces the shape of every waveset with an archetypal waveform, like
+//! CDP's distort reform. Durations and levels are kept, so the sound's
+//! rhythm and envelope survive while its timbre is rebuilt.
+
+use crate::error::{DistortError, Result};
+use crate::wav_io::write_audio;
+use crate::waveset::{read_mono, wavesets};
+use std::f32::consts::PI;
+use std::path::Path;
+
+/// Archetypal half-cycle shape used by [`reform`]
+///
+/// [`ReformShape::from_mode`] maps CDP's reform modes onto these; `Ramp`
+/// and `Exponential` have no CDP mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ReformShape {
+    /// Half a sine cycle
+    Sine,
+    /// Linear rise to the peak and fall back
+    Triangle,
+    /// Constant at the peak
+    Square,
+    /// Constant at full scale, whatever the waveset's level
+    FixedSquare,
+    /// Linear rise to full scale and fall back
+    FixedTriangle,
+    /// The half-cycle's contour turned upside down within its peak
+    Inverted,
+    /// A single sample at the peak, silent for the rest of the half-cycle
+    Click,
+    /// Linear rise to the peak, then an instant drop
+    Ramp,
+    /// Instant jump to the peak, then an exponential decay
+    Exponential,
+}
+
+impl ReformShape {
+    /// Shape for a CDP distort reform mode
+    ///
+    /// Modes 1 and 2 give fixed-level and peak-following square waves, 3
+    /// and 4 the same for triangles, 5 inverted half-cycles, 6 a click
+    /// stream and 7 a sinusoid.
+    pub fn from_mode(mode: i32) -> Result<Self> {
+        match mode {
+            1 => Ok(ReformShape::FixedSquare),
+            2 => Ok(ReformShape::Square),
+            3 => Ok(ReformShape::FixedTriangle),
+            4 => Ok(ReformShape::Triangle),
+            5 => Ok(ReformShape::Inverted),
+            6 => Ok(ReformShape::Click),
+            7 => Ok(ReformShape::Sine),
+            _ => Err(DistortError::InvalidInput(format!(
+                "Reform mode {} not yet implemented",
+                mode
+            ))),
+        }
+    }
+
+    /// Level (0-1) of sample `i` of a `len`-sample half-cycle, given the
+    /// original sample's level (0-1) relative to the half-cycle's peak
+    fn level(self, i: usize, len: usize, original: f32) -> f32 {
+        let x = (i as f32 + 0.5) / len as f32;
+        match self {
+            ReformShape::Sine => (PI * x).sin(),
+            ReformShape::Triangle | ReformShape::FixedTriangle => 1.0 - (2.0 * x - 1.0).abs(),
+            ReformShape::Square | ReformShape::FixedSquare => 1.0,
+            ReformShape::Ramp => x,
+            ReformShape::Exponential => (-5.0 * x).exp(),
+            ReformShape::Inverted => 1.0 - original,
+            ReformShape::Click => {
+                if i == 0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+
+    /// Whether every half-cycle is drawn at full scale
+    fn fixed_level(self) -> bool {
+        matches!(self, ReformShape::FixedSquare | ReformShape::FixedTriangle)
+    }
+}
+
+/// Reshape every waveset to an archetypal waveform
+///
+/// # Arguments
+/// * `input_path` - Path to mono input audio file
+/// * `output_path` - Path to output audio file
+/// * `shape` - Shape given to each half-cycle
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(DistortError)` on failure
+pub fn reform(input_path: &Path, output_path: &Path, shape: ReformShape) -> Result<()> {
+    let mut audio = read_mono(input_path)?;
+    audio.data = reform_buf(&audio.data, shape)?;
+    write_audio(output_path, &audio)
+}
+
+/// Reshape every waveset of in-memory mono samples
+///
+/// Buffer-based counterpart of [`reform`]; returns the processed samples.
+/// Each positive and negative half-cycle of a waveset is redrawn in the
+/// chosen shape, keeping its length in samples and its peak level.
+pub fn reform_buf(samples: &[f32], shape: ReformShape) -> Result<Vec<f32>> {
+    let mut output = Vec::with_capacity(samples.len());
+    for waveset in wavesets(samples) {
+        let mut rest = waveset;
+        while let Some(&first) = rest.first() {
+            // Samples of one half-cycle share the sign of its first sample
+            let positive = first >= 0.0;
+            let len = rest
+                .iter()
+                .position(|&s| (s >= 0.0) != positive)
+                .unwrap_or(rest.len());
+            let (half, remainder) = rest.split_at(len);
+            rest = remainder;
+
+            let peak = half.iter().fold(0.0f32, |max, &s| max.max(s.abs()));
+            let level = if shape.fixed_level() { 1.0 } else { peak };
+            let sign = if positive { 1.0 } else { -1.0 };
+            output.extend(half.iter().enumerate().map(|(i, &s)| {
+                let original = if peak > 0.0 { s.abs() / peak } else { 0.0 };
+                sign * level * shape.level(i, len, original)
+            }));
+        }
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reform_buf() {
+        // One waveset with unequal half-cycles
+        let samples = [0.2, 0.4, -0.1, -0.3];
+
+        let output = reform_buf(&samples, ReformShape::Square).unwrap();
+        assert_eq!(output, vec![0.4, 0.4, -0.3, -0.3]);
+
+        let output = reform_buf(&samples, ReformShape::Triangle).unwrap();
+        assert_eq!(output, vec![0.2, 0.2, -0.15, -0.15]);
+
+        let output = reform_buf(&samples, ReformShape::Ramp).unwrap();
+        for (sample, expected) in output.iter().zip([0.1, 0.3, -0.075, -0.225]) {
+            assert!((sample - expected).abs() < 1e-6);
+        }
+
+        let output = reform_buf(&samples, ReformShape::FixedSquare).unwrap();
+        assert_eq!(output, vec![1.0, 1.0, -1.0, -1.0]);
+
+        let output = reform_buf(&samples, ReformShape::Inverted).unwrap();
+        for (sample, expected) in output.iter().zip([0.2, 0.0, -0.2, 0.0]) {
+            assert!((sample - expected).abs() < 1e-6);
+        }
+
+        let output = reform_buf(&samples, ReformShape::Click).unwrap();
+        assert_eq!(output, vec![0.4, 0.0, -0.3, 0.0]);
+
+        assert_eq!(ReformShape::from_mode(1).unwrap(), ReformShape::FixedSquare);
+        assert_eq!(ReformShape::from_mode(7).unwrap(), ReformShape::Sine);
+        assert!(ReformShape::from_mode(0).is_err());
+        assert!(ReformShape::from_mode(8).is_err());
+        assert_eq!(
+            reform_buf(&[], ReformShape::Sine).unwrap(),
+            Vec::<f32>::new()
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! Waveset reordering
+//!
+//! Reverses or shuffles wavesets in small groups, giving CDP's granular
+//! distort reverse and distort shuffle textures while keeping the length
+//! of the sound.
+
+use crate::error::{check_param, Result};
+use crate::wav_io::write_audio;
+use crate::waveset::{read_mono, wavesets};
+use cdp_core::{Param, Rng};
+use std::path::Path;
+
+/// Time-reverse each group of wavesets
+///
+/// # Arguments
+/// * `input_path` - Path to mono input audio file
+/// * `output_path` - Path to output audio file
+/// * `cycle_count` - Wavesets per reversed group (1-1000)
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(DistortError)` on failure
+pub fn reverse(input_path: &Path, output_path: &Path, cycle_count: usize) -> Result<()> {
+    check_param(Param::WavesetGroup, cycle_count as f64)?;
+    let mut audio = read_mono(input_path)?;
+    audio.data = reverse_buf(&audio.data, cycle_count)?;
+    write_audio(output_path, &audio)
+}
+
+/// Time-reverse each group of wavesets of in-memory mono samples
+///
+/// Buffer-based counterpart of [`reverse`]; returns the processed samples.
+/// Each group is played backwards sample by sample, so the groups stay in
+/// order while everything within them runs in reverse.
+pub fn reverse_buf(samples: &[f32], cycle_count: usize) -> Result<Vec<f32>> {
+    check_param(Param::WavesetGroup, cycle_count as f64)?;
+
+    let sets: Vec<&[f32]> = wavesets(samples).collect();
+    let mut output = Vec::with_capacity(samples.len());
+    for group in sets.chunks(cycle_count) {
+        for waveset in group.iter().rev() {
+            output.extend(waveset.iter().rev());
+        }
+    }
+
+    Ok(output)
+}
+
+/// Shuffle the order of wavesets within successive windows
+///
+/// # Arguments
+/// * `input_path` - Path to mono input audio file
+/// * `output_path` - Path to output audio file
+/// * `window` - Wavesets per shuffled window (2-1000)
+/// * `seed` - Random seed; the same seed gives the same output
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(DistortError)` on failure
+pub fn shuffle(input_path: &Path, output_path: &Path, window: usize, seed: u32) -> Result<()> {
+    check_param(Param::ShuffleWindow, window as f64)?;
+    let mut audio = read_mono(input_path)?;
+    audio.data = shuffle_buf(&audio.data, window, seed)?;
+    write_audio(output_path, &audio)
+}
+
+/// Shuffle waveset order within windows of in-memory mono samples
+///
+/// Buffer-based counterpart of [`shuffle`]; returns the processed samples.
+/// Wavesets never leave their window, so the sound's overall evolution is
+/// kept.
+pub fn shuffle_buf(samples: &[f32], window: usize, seed: u32) -> Result<Vec<f32>> {
+    check_param(Param::ShuffleWindow, window as f64)?;
+
+    let mut rng = Rng::new(seed);
+    let mut sets: Vec<&[f32]> = wavesets(samples).collect();
+    for group in sets.chunks_mut(window) {
+        // Fisher-Yates shuffle
+        for i in (1..group.len()).rev() {
+            group.swap(i, rng.below(i + 1));
+        }
+    }
+
+    Ok(sets.concat())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reorder_validation() {
+        let input = Path::new("test.wav");
+        let output = Path::new("out.wav");
+
+        assert!(reverse(input, output, 0).is_err());
+        assert!(shuffle(input, output, 1, 0).is_err());
+    }
+
+    #[test]
+    fn test_reverse_and_shuffle_buf() {
+        // Three wavesets
+        let samples = [0.1, -0.2, 0.3, -0.4, 0.5, -0.6];
+
+        let output = reverse_buf(&samples, 2).unwrap();
+        assert_eq!(output, vec![-0.4, 0.3, -0.2, 0.1, -0.6, 0.5]);
+
+        // Shuffling only reorders whole wavesets, reproducibly
+        let output = shuffle_buf(&samples, 3, 42).unwrap();
+        assert_eq!(output, shuffle_buf(&samples, 3, 42).unwrap());
+        let mut sets: Vec<Vec<f32>> = output.chunks(2).map(<[f32]>::to_vec).collect();
+        sets.sort_by(|a, b| a[0].total_cmp(&b[0]));
+        assert_eq!(sets.concat(), samples);
+    }
+}
@@ -0,0 +1,113 @@
+//! Waveset repetition
+//!
+//! Time-stretches a sound by playing each group of wavesets several times
+//! over, like CDP's distort repeat. The pitch of periodic material is kept
+//! while noisy material takes on a buzzing, stepped character.
+
+use crate::channels::normalize;
+use crate::error::{check_param, Result};
+use crate::wav_io::write_audio;
+use crate::waveset::{read_mono, wavesets};
+use cdp_core::Param;
+use std::path::Path;
+
+/// Repeat each group of wavesets
+///
+/// # Arguments
+/// * `input_path` - Path to mono input audio file
+/// * `output_path` - Path to output audio file
+/// * `repeats` - Times each group is played (1-100)
+/// * `cycle_count` - Wavesets per repeated group (1-1000)
+/// * `prescale` - Gain applied to the input before processing (> 0)
+/// * `mix` - Dry/wet mix (0.0 = dry, 1.0 = wet)
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(DistortError)` on failure
+pub fn repeat(
+    input_path: &Path,
+    output_path: &Path,
+    repeats: u32,
+    cycle_count: usize,
+    prescale: f32,
+    mix: f32,
+) -> Result<()> {
+    check_params(repeats, cycle_count, prescale, mix)?;
+    let mut audio = read_mono(input_path)?;
+    audio.data = repeat_buf(&audio.data, repeats, cycle_count, prescale, mix)?;
+    write_audio(output_path, &audio)
+}
+
+/// Repeat each group of wavesets of in-memory mono samples
+///
+/// Buffer-based counterpart of [`repeat`]; returns the processed samples.
+/// The output is about `repeats` times longer than the input, so the dry
+/// signal only sounds under its start.
+pub fn repeat_buf(
+    samples: &[f32],
+    repeats: u32,
+    cycle_count: usize,
+    prescale: f32,
+    mix: f32,
+) -> Result<Vec<f32>> {
+    check_params(repeats, cycle_count, prescale, mix)?;
+
+    let scaled: Vec<f32> = samples.iter().map(|s| s * prescale).collect();
+    let sets: Vec<&[f32]> = wavesets(&scaled).collect();
+    let mut output = Vec::with_capacity(samples.len() * repeats as usize);
+    for group in sets.chunks(cycle_count) {
+        for _ in 0..repeats {
+            for waveset in group {
+                output.extend_from_slice(waveset);
+            }
+        }
+    }
+
+    // Mix with dry signal
+    for (i, sample) in output.iter_mut().enumerate() {
+        let dry = scaled.get(i).copied().unwrap_or(0.0);
+        *sample = dry * (1.0 - mix) + *sample * mix;
+    }
+
+    normalize(&mut output);
+
+    Ok(output)
+}
+
+fn check_params(repeats: u32, cycle_count: usize, prescale: f32, mix: f32) -> Result<()> {
+    check_param(Param::Repeats, repeats as f64)?;
+    check_param(Param::WavesetGroup, cycle_count as f64)?;
+    check_param(Param::Prescale, prescale as f64)?;
+    check_param(Param::Mix, mix as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeat_validation() {
+        let input = Path::new("test.wav");
+        let output = Path::new("out.wav");
+
+        assert!(repeat(input, output, 0, 1, 1.0, 1.0).is_err());
+        assert!(repeat(input, output, 2, 0, 1.0, 1.0).is_err());
+        assert!(repeat(input, output, 2, 1, 0.0, 1.0).is_err());
+        assert!(repeat(input, output, 2, 1, 1.0, 1.5).is_err());
+    }
+
+    #[test]
+    fn test_repeat_buf() {
+        // Two wavesets of two samples each
+        let samples = [0.5, -0.5, 0.25, -0.25];
+        let output = repeat_buf(&samples, 2, 1, 1.0, 1.0).unwrap();
+        assert_eq!(output, vec![0.5, -0.5, 0.5, -0.5, 0.25, -0.25, 0.25, -0.25]);
+
+        // Groups of two play through whole before repeating, at half level
+        let output = repeat_buf(&samples, 2, 2, 0.5, 1.0).unwrap();
+        assert_eq!(
+            output,
+            vec![0.25, -0.25, 0.125, -0.125, 0.25, -0.25, 0.125, -0.125]
+        );
+    }
+}
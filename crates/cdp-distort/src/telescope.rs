@@ -0,0 +1,95 @@
+//! Waveset telescoping
+//!
+//! Squeezes each group of wavesets into the space of one, like CDP's
+//! distort telescope, radically shortening and roughening the sound.
+
+use crate::average::average_shape;
+use crate::error::{check_param, Result};
+use crate::wav_io::write_audio;
+use crate::waveset::{read_mono, wavesets};
+use cdp_core::Param;
+use std::path::Path;
+
+/// How [`telescope`] compresses a group of wavesets into one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum TelescopeMode {
+    /// Keep every Nth sample of the group, squeezing all its wavesets in
+    Decimate,
+    /// Use the group's time-normalized average shape
+    Average,
+}
+
+/// Time-compress each group of wavesets into a single waveset
+///
+/// # Arguments
+/// * `input_path` - Path to mono input audio file
+/// * `output_path` - Path to output audio file
+/// * `cycle_count` - Wavesets per compressed group (2-1000)
+/// * `mode` - How each group is compressed
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(DistortError)` on failure
+pub fn telescope(
+    input_path: &Path,
+    output_path: &Path,
+    cycle_count: usize,
+    mode: TelescopeMode,
+) -> Result<()> {
+    check_cycle_count(cycle_count)?;
+    let mut audio = read_mono(input_path)?;
+    audio.data = telescope_buf(&audio.data, cycle_count, mode)?;
+    write_audio(output_path, &audio)
+}
+
+/// Time-compress groups of wavesets of in-memory mono samples
+///
+/// Buffer-based counterpart of [`telescope`]; returns the processed
+/// samples. Each group becomes roughly its mean waveset length, so the
+/// sound shortens by about `cycle_count` times. Decimation keeps the
+/// group's detail at a higher pitch, with the aliasing that implies;
+/// averaging smooths it into one representative cycle.
+pub fn telescope_buf(samples: &[f32], cycle_count: usize, mode: TelescopeMode) -> Result<Vec<f32>> {
+    check_cycle_count(cycle_count)?;
+
+    let sets: Vec<&[f32]> = wavesets(samples).collect();
+    let mut output = Vec::with_capacity(samples.len() / cycle_count + 1);
+    for group in sets.chunks(cycle_count) {
+        match mode {
+            TelescopeMode::Decimate => {
+                output.extend(group.iter().flat_map(|set| set.iter()).step_by(group.len()))
+            }
+            TelescopeMode::Average => output.extend(average_shape(group)),
+        }
+    }
+
+    Ok(output)
+}
+
+fn check_cycle_count(cycle_count: usize) -> Result<()> {
+    check_param(Param::CycleCount, cycle_count as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_telescope_buf() {
+        // Two four-sample wavesets of different heights
+        let samples = [0.2, 0.4, -0.2, -0.4, 0.6, 0.8, -0.6, -0.8];
+
+        let output = telescope_buf(&samples, 2, TelescopeMode::Decimate).unwrap();
+        assert_eq!(output, vec![0.2, -0.2, 0.6, -0.6]);
+
+        let output = telescope_buf(&samples, 2, TelescopeMode::Average).unwrap();
+        assert_eq!(output.len(), 4);
+        for (value, expected) in output.iter().zip([0.4, 0.6, -0.4, -0.6]) {
+            assert!((value - expected).abs() < 1e-6);
+        }
+
+        assert!(telescope_buf(&samples, 1, TelescopeMode::Average).is_err());
+    }
+}
@@ -0,0 +1,144 @@
+//! Waveset pitch warping
+//!
+//! Transposes successive wavesets by a drifting amount, like CDP's distort
+//! pitch, so the pitch of the sound warbles within a range that can change
+//! over time.
+
+use crate::error::{check_param, DistortError, Result};
+use crate::wav_io::write_audio;
+use crate::waveset::{read_mono, resample_waveset, wavesets};
+use cdp_core::{Breakpoints, Param, Rng};
+use std::path::Path;
+
+/// How [`pitch_warp`] moves the transposition from one waveset to the next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum WarpMode {
+    /// A random step up or down, from a generator seeded with this value
+    Random(u32),
+    /// A steady sweep between the top and bottom of the range
+    Cyclic,
+}
+
+/// Warp the pitch of a sound waveset by waveset
+///
+/// # Arguments
+/// * `input_path` - Path to mono input audio file
+/// * `output_path` - Path to output audio file
+/// * `depths` - Vec of (time, depth) pairs; depth is the largest
+///   transposition up or down, in semitones (0-48)
+/// * `step` - Largest move per waveset as a fraction (0-1] of the range
+/// * `mode` - Whether the transposition drifts randomly or cyclically
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(DistortError)` on failure
+pub fn pitch_warp(
+    input_path: &Path,
+    output_path: &Path,
+    depths: &[(f64, f64)],
+    step: f64,
+    mode: WarpMode,
+) -> Result<()> {
+    check_warp_params(depths, step)?;
+    let mut audio = read_mono(input_path)?;
+    audio.data = pitch_warp_buf(&audio.data, audio.sample_rate, depths, step, mode)?;
+    write_audio(output_path, &audio)
+}
+
+/// Warp the pitch of in-memory mono samples waveset by waveset
+///
+/// Buffer-based counterpart of [`pitch_warp`]; returns the processed
+/// samples. A warp position between -1 and 1 starts at 0 and accumulates a
+/// move of up to `step` after every waveset, reflecting at the ends of the
+/// range. Each waveset is resampled to transpose it by the position times
+/// the depth at its start time, so the output length drifts with the pitch.
+pub fn pitch_warp_buf(
+    samples: &[f32],
+    sample_rate: u32,
+    depths: &[(f64, f64)],
+    step: f64,
+    mode: WarpMode,
+) -> Result<Vec<f32>> {
+    check_warp_params(depths, step)?;
+    let depths = Breakpoints::new(depths.to_vec())?;
+
+    let mut rng = match mode {
+        WarpMode::Random(seed) => Some(Rng::new(seed)),
+        WarpMode::Cyclic => None,
+    };
+    let mut position = 0.0f64;
+    let mut direction = 1.0f64;
+    let mut start = 0usize;
+    let mut output = Vec::with_capacity(samples.len());
+    for waveset in wavesets(samples) {
+        let time = start as f64 / sample_rate as f64;
+        start += waveset.len();
+
+        let semitones = position * depths.value_at(time);
+        let ratio = 2.0f64.powf(semitones / 12.0);
+        let len = (waveset.len() as f64 / ratio).round().max(1.0) as usize;
+        output.extend(resample_waveset(waveset, len));
+
+        position += match rng.as_mut() {
+            Some(rng) => (rng.uniform() * 2.0 - 1.0) * step,
+            None => direction * step,
+        };
+        if position.abs() > 1.0 {
+            position = position.signum() * 2.0 - position;
+            direction = -direction;
+        }
+    }
+
+    Ok(output)
+}
+
+fn check_warp_params(depths: &[(f64, f64)], step: f64) -> Result<()> {
+    if depths.is_empty() {
+        return Err(DistortError::InvalidInput(
+            "Warp depths must not be empty".to_string(),
+        ));
+    }
+    for &(_, depth) in depths {
+        check_param(Param::WarpDepth, depth)?;
+    }
+    check_param(Param::WarpStep, step)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pitch_warp_validation() {
+        let samples = [0.0; 8];
+        let depths = [(0.0, 12.0)];
+        assert!(pitch_warp_buf(&samples, 44100, &[], 0.5, WarpMode::Cyclic).is_err());
+        assert!(pitch_warp_buf(&samples, 44100, &[(0.0, 60.0)], 0.5, WarpMode::Cyclic).is_err());
+        assert!(pitch_warp_buf(&samples, 44100, &depths, 0.0, WarpMode::Cyclic).is_err());
+        assert!(pitch_warp_buf(&samples, 44100, &depths, 1.5, WarpMode::Cyclic).is_err());
+    }
+
+    #[test]
+    fn test_pitch_warp_buf() {
+        // Five eight-sample wavesets
+        let samples: Vec<f32> = [0.0, 0.5, 1.0, 0.5, 0.0, -0.5, -1.0, -0.5].repeat(5);
+        let depths = [(0.0, 12.0)];
+
+        // Sweeps 0, up an octave, 0, down an octave, 0
+        let output = pitch_warp_buf(&samples, 44100, &depths, 1.0, WarpMode::Cyclic).unwrap();
+        let lengths: Vec<usize> = wavesets(&output).map(|set| set.len()).collect();
+        assert_eq!(lengths, vec![8, 4, 8, 16, 8]);
+
+        let first = pitch_warp_buf(&samples, 44100, &depths, 0.5, WarpMode::Random(7)).unwrap();
+        let second = pitch_warp_buf(&samples, 44100, &depths, 0.5, WarpMode::Random(7)).unwrap();
+        assert_eq!(first, second);
+        assert!(wavesets(&first).all(|set| (4..=16).contains(&set.len())));
+
+        // Zero depth leaves the sound unchanged
+        let output =
+            pitch_warp_buf(&samples, 44100, &[(0.0, 0.0)], 1.0, WarpMode::Random(7)).unwrap();
+        assert_eq!(output, samples);
+    }
+}
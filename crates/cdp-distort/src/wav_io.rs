@@ -0,0 +1,21 @@
+//! Shared WAV input/output for the distortion effects
+//!
+//! Inputs of any format the CDP WAV reader supports are read as float
+//! levels; outputs are always written as 32-bit float.
+
+use crate::error::Result;
+use cdp_core::AudioBuffer;
+use cdp_housekeep::wav_cdp::{self, SampleType};
+use std::path::Path;
+
+/// Read a WAV file into an [`AudioBuffer`] of float levels
+pub fn read_audio(input_path: &Path) -> Result<AudioBuffer> {
+    let (audio, _) = wav_cdp::read_audio_buffer(input_path)?;
+    Ok(audio)
+}
+
+/// Write an [`AudioBuffer`] as a 32-bit float WAV
+pub fn write_audio(output_path: &Path, audio: &AudioBuffer) -> Result<()> {
+    wav_cdp::write_audio_buffer(output_path, audio, SampleType::Float32)?;
+    Ok(())
+}
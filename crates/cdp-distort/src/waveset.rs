@@ -0,0 +1,165 @@
+//! Waveset segmentation
+//!
+//! A waveset is a zero-crossing-delimited pseudo-cycle: the samples from
+//! one upward zero crossing to the next, a positive half-cycle followed by
+//! a negative one. For periodic sounds wavesets are the waveform's cycles;
+//! for complex sounds they are irregular fragments. Most CDP distort
+//! programs work by rearranging, repeating or reshaping wavesets.
+
+use crate::error::{DistortError, Result};
+use crate::wav_io::{read_audio, write_audio};
+use cdp_core::AudioBuffer;
+use std::path::Path;
+
+/// Iterator over the wavesets of a mono signal
+///
+/// Wavesets tile the signal exactly: any samples before the first upward
+/// zero crossing form the first item and any after the last form the
+/// final item, so joining every item restores the input.
+#[derive(Debug, Clone)]
+pub struct Wavesets<'a> {
+    samples: &'a [f32],
+    position: usize,
+}
+
+impl<'a> Iterator for Wavesets<'a> {
+    type Item = &'a [f32];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position >= self.samples.len() {
+            return None;
+        }
+        let start = self.position;
+        let end = (start + 1..self.samples.len())
+            .find(|&i| self.samples[i - 1] < 0.0 && self.samples[i] >= 0.0)
+            .unwrap_or(self.samples.len());
+        self.position = end;
+        Some(&self.samples[start..end])
+    }
+}
+
+/// Split a mono signal into wavesets
+pub fn wavesets(samples: &[f32]) -> Wavesets<'_> {
+    Wavesets {
+        samples,
+        position: 0,
+    }
+}
+
+/// Join wavesets back into a continuous signal
+pub fn join_wavesets<I, S>(wavesets: I) -> Vec<f32>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<[f32]>,
+{
+    let mut output = Vec::new();
+    for waveset in wavesets {
+        output.extend_from_slice(waveset.as_ref());
+    }
+    output
+}
+
+/// Resample a waveset to `len` samples by linear interpolation
+///
+/// The waveset's time span is stretched or squeezed to fit, so the copy
+/// keeps its shape at a new length and therefore a new pitch.
+pub fn resample_waveset(waveset: &[f32], len: usize) -> Vec<f32> {
+    if waveset.is_empty() {
+        return vec![0.0; len];
+    }
+    let step = waveset.len() as f64 / len as f64;
+    (0..len)
+        .map(|i| {
+            let position = i as f64 * step;
+            let index = position as usize;
+            let frac = (position - index as f64) as f32;
+            let current = waveset[index];
+            let next = waveset.get(index + 1).copied().unwrap_or(current);
+            current + (next - current) * frac
+        })
+        .collect()
+}
+
+/// Rewrite a mono sound from a transformed sequence of its wavesets
+///
+/// `transform` receives every waveset of the input in order and returns
+/// the wavesets of the output, which are joined to form the new sound.
+///
+/// # Arguments
+/// * `input_path` - Path to mono input audio file
+/// * `output_path` - Path to output audio file
+/// * `transform` - Maps the input wavesets to the output wavesets
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(DistortError)` on failure
+pub fn transform_wavesets(
+    input_path: &Path,
+    output_path: &Path,
+    transform: impl FnOnce(&[&[f32]]) -> Vec<Vec<f32>>,
+) -> Result<()> {
+    let audio = read_mono(input_path)?;
+    let input: Vec<&[f32]> = wavesets(&audio.data).collect();
+    let output = join_wavesets(transform(&input));
+    write_audio(
+        output_path,
+        &AudioBuffer::new(audio.sample_rate, 1, output)?,
+    )
+}
+
+/// Read a WAV file for waveset processing, which needs a single channel
+pub(crate) fn read_mono(input_path: &Path) -> Result<AudioBuffer> {
+    let audio = read_audio(input_path)?;
+    if audio.channels != 1 {
+        return Err(DistortError::InvalidInput(
+            "Waveset operations require a mono input".to_string(),
+        ));
+    }
+    Ok(audio)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wavesets_tile_the_signal() {
+        // Starts mid-cycle, then two whole cycles of four samples
+        let samples = [-0.5, 0.0, 1.0, 0.0, -1.0, 0.5, 0.5, -0.5, -0.5, 0.2];
+        let sets: Vec<&[f32]> = wavesets(&samples).collect();
+        assert_eq!(
+            sets,
+            vec![
+                &[-0.5][..],
+                &[0.0, 1.0, 0.0, -1.0],
+                &[0.5, 0.5, -0.5, -0.5],
+                &[0.2]
+            ]
+        );
+        assert_eq!(join_wavesets(&sets), samples);
+        assert_eq!(wavesets(&[]).count(), 0);
+    }
+
+    #[test]
+    fn test_resample_waveset() {
+        let waveset = [0.0, 1.0, 0.0, -1.0];
+        assert_eq!(
+            resample_waveset(&waveset, 8),
+            vec![0.0, 0.5, 1.0, 0.5, 0.0, -0.5, -1.0, -1.0]
+        );
+        assert_eq!(resample_waveset(&waveset, 2), vec![0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_transform_wavesets_requires_mono() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("stereo.wav");
+        let output = dir.path().join("out.wav");
+        write_audio(&input, &AudioBuffer::new(44100, 2, vec![0.0; 8]).unwrap()).unwrap();
+
+        let result = transform_wavesets(&input, &output, |sets| {
+            sets.iter().map(|set| set.to_vec()).collect()
+        });
+        assert!(matches!(result, Err(DistortError::InvalidInput(_))));
+    }
+}
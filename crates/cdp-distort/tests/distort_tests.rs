@@ -1,4 +1,6 @@
-use cdp_distort::{divide, multiply, overload, ClipType};
+use cdp_distort::{
+    delete_buf, distort, divide, divide_buf, multiply, overload, reverse_buf, ClipType, DeleteMode,
+};
 use hound::{SampleFormat, WavSpec, WavWriter};
 use std::fs;
 use tempfile::tempdir;
@@ -155,3 +157,133 @@ fn test_multiply_extreme_values() {
         assert!(sample.abs() <= 1.0);
     }
 }
+
+#[test]
+fn test_24bit_input_is_scaled() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input24.wav");
+    let output_path = dir.path().join("output.wav");
+
+    // Create a 24-bit integer test file
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 48000,
+        bits_per_sample: 24,
+        sample_format: SampleFormat::Int,
+    };
+    let input: Vec<i32> = vec![0, 4_194_304, -4_194_304, 8_388_607, -8_388_608];
+    let mut writer = WavWriter::create(&input_path, spec).unwrap();
+    for &sample in &input {
+        writer.write_sample(sample).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    // A fully dry mix passes the input straight through
+    multiply(&input_path, &output_path, 1.0, 0.0).unwrap();
+
+    let reader = hound::WavReader::open(&output_path).unwrap();
+    assert_eq!(reader.spec().sample_rate, 48000);
+    let output_samples: Vec<f32> = reader
+        .into_samples::<f32>()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    for (out, &inp) in output_samples.iter().zip(&input) {
+        assert!((out - inp as f32 / 8_388_608.0).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_divide_processes_channels_independently() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.wav");
+    let output_path = dir.path().join("output.wav");
+
+    // Sine on the left, silence on the right
+    let left: Vec<f32> = (0..4410)
+        .map(|i| (i as f32 * 2.0 * std::f32::consts::PI * 440.0 / 44100.0).sin() * 0.5)
+        .collect();
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 44100,
+        bits_per_sample: 32,
+        sample_format: SampleFormat::Float,
+    };
+    let mut writer = WavWriter::create(&input_path, spec).unwrap();
+    for &sample in &left {
+        writer.write_sample(sample).unwrap();
+        writer.write_sample(0.0f32).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    divide(&input_path, &output_path, 2, 1.0).unwrap();
+
+    // The silent channel must not disturb the left channel's zero crossings
+    let reader = hound::WavReader::open(&output_path).unwrap();
+    let output_samples: Vec<f32> = reader
+        .into_samples::<f32>()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let output_left: Vec<f32> = output_samples.iter().step_by(2).copied().collect();
+    assert_eq!(output_left, divide_buf(&left, 1, 2, 1.0).unwrap());
+    assert!(output_samples.iter().skip(1).step_by(2).all(|&s| s == 0.0));
+}
+
+#[test]
+fn test_cdp_syntax_dispatch() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.wav");
+    let output_path = dir.path().join("output.wav");
+    let samples = vec![0.1, -0.2, 0.3, -0.4, 0.5, -0.6];
+    create_test_wav(&input_path, samples.clone());
+
+    let input = input_path.to_str().unwrap();
+    let output = output_path.to_str().unwrap();
+    distort("reverse", &[input, output, "2"]).unwrap();
+
+    let mut reader = hound::WavReader::open(&output_path).unwrap();
+    let reversed: Vec<f32> = reader.samples::<f32>().map(|s| s.unwrap()).collect();
+    assert_eq!(reversed, reverse_buf(&samples, 2).unwrap());
+
+    assert!(distort("reverse", &[input, output]).is_err());
+    assert!(distort("delete", &["5", input, output, "2"]).is_err());
+    assert!(distort("envelope", &[input, output]).is_err());
+}
+
+#[test]
+fn test_delete_cli_modes() {
+    let dir = tempdir().unwrap();
+    let input_path = dir.path().join("input.wav");
+    let output_path = dir.path().join("output.wav");
+    // Wavesets of rising then falling peaks
+    let samples = vec![
+        0.1, -0.1, 0.5, -0.5, 0.3, -0.3, 0.2, -0.2, 0.4, -0.4, 0.6, -0.6,
+    ];
+    create_test_wav(&input_path, samples.clone());
+
+    let input = input_path.to_str().unwrap();
+    let output = output_path.to_str().unwrap();
+    let read_output = || {
+        let mut reader = hound::WavReader::open(&output_path).unwrap();
+        reader
+            .samples::<f32>()
+            .map(|s| s.unwrap())
+            .collect::<Vec<f32>>()
+    };
+    for (mode, expected) in [
+        ("1", DeleteMode::First),
+        ("2", DeleteMode::Strongest),
+        ("3", DeleteMode::Random(0)),
+        ("4", DeleteMode::Weakest),
+    ] {
+        distort("delete", &[mode, input, output, "3"]).unwrap();
+        assert_eq!(read_output(), delete_buf(&samples, 3, expected).unwrap());
+    }
+
+    distort("delete", &["3", input, output, "3", "-s7"]).unwrap();
+    assert_eq!(
+        read_output(),
+        delete_buf(&samples, 3, DeleteMode::Random(7)).unwrap()
+    );
+    assert!(distort("delete", &["3", input, output, "3", "-x"]).is_err());
+}
@@ -0,0 +1,114 @@
+//! Breakpoint envelopes
+//!
+//! An envelope is a list of (time, level) points joined by straight lines,
+//! read from and written to CDP breakpoint text files.
+
+use super::{EnvelError, Result};
+use cdp_core::Breakpoints;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Amplitude envelope as (time in seconds, level) breakpoints
+#[derive(Debug, Clone, PartialEq)]
+pub struct Envelope {
+    breakpoints: Breakpoints,
+}
+
+impl Envelope {
+    /// Create an envelope from breakpoints
+    ///
+    /// There must be at least one point, times must start at or after 0 and
+    /// increase, and levels must be finite and not negative.
+    pub fn new(points: Vec<(f64, f64)>) -> Result<Self> {
+        let breakpoints =
+            Breakpoints::new(points).map_err(|e| EnvelError::InvalidEnvelope(e.to_string()))?;
+        breakpoints
+            .check_range("Envelope level", 0.0, f64::INFINITY)
+            .map_err(|e| EnvelError::InvalidEnvelope(e.to_string()))?;
+        Ok(Self { breakpoints })
+    }
+
+    /// Breakpoints in time order
+    pub fn points(&self) -> &[(f64, f64)] {
+        self.breakpoints.points()
+    }
+
+    /// Time of the last breakpoint
+    pub fn duration(&self) -> f64 {
+        self.breakpoints.duration()
+    }
+
+    /// Highest level of any breakpoint
+    pub fn peak(&self) -> f64 {
+        self.points()
+            .iter()
+            .fold(0.0, |peak, &(_, level)| peak.max(level))
+    }
+
+    /// Level at a time, interpolating linearly and holding the end levels
+    pub fn level_at(&self, time: f64) -> f64 {
+        self.breakpoints.value_at(time)
+    }
+
+    /// Parse breakpoint text: one `time level` pair per line
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut points = Vec::new();
+        for (line_idx, line) in text.lines().enumerate() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.is_empty() {
+                continue;
+            }
+            let parsed = match fields[..] {
+                [time, level] => time.parse::<f64>().ok().zip(level.parse::<f64>().ok()),
+                _ => None,
+            };
+            let point = parsed.ok_or_else(|| {
+                EnvelError::InvalidEnvelope(format!(
+                    "line {} is not a time and level pair",
+                    line_idx + 1
+                ))
+            })?;
+            points.push(point);
+        }
+        Self::new(points)
+    }
+
+    /// Format as breakpoint text
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for &(time, level) in self.points() {
+            let _ = writeln!(text, "{:.6}\t{:.6}", time, level);
+        }
+        text
+    }
+
+    /// Read a breakpoint file
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::parse(&cdp_core::fs::read_to_string(path)?)
+    }
+
+    /// Write a breakpoint file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        cdp_core::fs::write(path, self.to_text())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope_text_round_trip() {
+        let envelope = Envelope::parse("0 0\n\n0.5 1.0\n2.0\t0.25\n").unwrap();
+        assert_eq!(envelope.points(), &[(0.0, 0.0), (0.5, 1.0), (2.0, 0.25)]);
+        assert_eq!(envelope.level_at(0.25), 0.5);
+        assert_eq!(envelope.level_at(3.0), 0.25);
+        assert_eq!(Envelope::parse(&envelope.to_text()).unwrap(), envelope);
+
+        assert!(Envelope::parse("").is_err());
+        assert!(Envelope::parse("0 0\n0 1\n").is_err());
+        assert!(Envelope::parse("0 0 0\n").is_err());
+        assert!(Envelope::parse("0 -1\n").is_err());
+    }
+}
@@ -0,0 +1,85 @@
+//! Envelope extraction
+//!
+//! Follows the amplitude of a sound window by window, like CDP's envel
+//! extract, producing a breakpoint envelope.
+
+use super::{EnvelError, Result};
+use crate::envelope::Envelope;
+use cdp_core::AudioBuffer;
+use cdp_housekeep::wav_cdp;
+use std::path::Path;
+
+/// Extract the amplitude envelope of a sound to a breakpoint file
+///
+/// # Arguments
+/// * `input` - Path to input audio file
+/// * `output` - Path to output breakpoint file
+/// * `window_ms` - Window size in milliseconds
+pub fn extract(input: &Path, output: &Path, window_ms: f64) -> Result<()> {
+    check_window(window_ms)?;
+    let (audio, _) = wav_cdp::read_audio_buffer(input)?;
+    extract_buf(&audio, window_ms)?.save(output)
+}
+
+/// Extract the amplitude envelope of an in-memory sound
+///
+/// Each window contributes a breakpoint at its start time holding the
+/// peak level of any channel within it. A final point at the end of the
+/// sound repeats the last window's level, so the envelope spans the whole
+/// duration.
+pub fn extract_buf(audio: &AudioBuffer, window_ms: f64) -> Result<Envelope> {
+    check_window(window_ms)?;
+    let window_frames = ((window_ms / 1000.0 * audio.sample_rate as f64).round() as usize).max(1);
+    let frames: Vec<&[f32]> = audio.frames().collect();
+
+    let mut points: Vec<(f64, f64)> = frames
+        .chunks(window_frames)
+        .enumerate()
+        .map(|(window_idx, window)| {
+            let peak = window
+                .iter()
+                .flat_map(|frame| frame.iter())
+                .fold(0.0f32, |peak, s| peak.max(s.abs()));
+            let time = (window_idx * window_frames) as f64 / audio.sample_rate as f64;
+            (time, peak as f64)
+        })
+        .collect();
+
+    let last_level = points.last().map_or(0.0, |&(_, level)| level);
+    let duration = audio.duration();
+    if points.last().map_or(true, |&(time, _)| time < duration) {
+        points.push((duration, last_level));
+    }
+    Envelope::new(points)
+}
+
+fn check_window(window_ms: f64) -> Result<()> {
+    if !(window_ms > 0.0 && window_ms.is_finite()) {
+        return Err(EnvelError::InvalidParameter(
+            "Window size must be greater than 0 ms".into(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_buf() {
+        // Stereo at 1kHz: two 2ms windows of different peaks
+        let audio =
+            AudioBuffer::new(1000, 2, vec![0.1, -0.2, 0.0, 0.1, 0.5, 0.0, -0.8, 0.3]).unwrap();
+        let envelope = extract_buf(&audio, 2.0).unwrap();
+        let points = envelope.points();
+        assert_eq!(points.len(), 3);
+        assert_eq!(points[0].0, 0.0);
+        assert!((points[0].1 - 0.2).abs() < 1e-6);
+        assert_eq!(points[1].0, 0.002);
+        assert!((points[1].1 - 0.8).abs() < 1e-6);
+        assert_eq!(points[2].0, 0.004);
+
+        assert!(extract_buf(&audio, 0.0).is_err());
+    }
+}
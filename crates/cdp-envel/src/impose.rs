@@ -0,0 +1,75 @@
+//! Envelope imposition
+//!
+//! Shapes a sound with a breakpoint envelope, like CDP's envel impose.
+
+use super::Result;
+use crate::envelope::Envelope;
+use cdp_core::AudioBuffer;
+use cdp_housekeep::wav_cdp;
+use std::path::Path;
+
+/// Impose a breakpoint envelope file on a sound
+///
+/// # Arguments
+/// * `input` - Path to input audio file
+/// * `envelope` - Path to breakpoint envelope file
+/// * `output` - Path to output audio file, written in the input's format
+pub fn impose(input: &Path, envelope: &Path, output: &Path) -> Result<()> {
+    let envelope = Envelope::load(envelope)?;
+    let (mut audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    impose_buf(&mut audio, &envelope);
+    wav_cdp::write_audio_buffer(output, &audio, sample_type)?;
+    Ok(())
+}
+
+/// Impose an envelope on an in-memory sound
+///
+/// Every channel of each frame is multiplied by the envelope's level at
+/// that frame's time; beyond the envelope's ends its first or last level
+/// holds.
+pub fn impose_buf(audio: &mut AudioBuffer, envelope: &Envelope) {
+    let channels = audio.channels as usize;
+    let sample_rate = audio.sample_rate as f64;
+    for (frame_idx, frame) in audio.data.chunks_exact_mut(channels).enumerate() {
+        let level = envelope.level_at(frame_idx as f64 / sample_rate) as f32;
+        for sample in frame {
+            *sample *= level;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_impose_buf() {
+        let mut audio = AudioBuffer::new(4, 2, vec![1.0, -1.0, 1.0, -1.0, 1.0, -1.0]).unwrap();
+        let envelope = Envelope::new(vec![(0.0, 0.0), (0.5, 1.0)]).unwrap();
+        impose_buf(&mut audio, &envelope);
+        assert_eq!(audio.data, vec![0.0, 0.0, 0.5, -0.5, 1.0, -1.0]);
+    }
+
+    #[test]
+    fn test_impose_preserves_format() {
+        let temp_dir = TempDir::new().unwrap();
+        let input = temp_dir.path().join("input.wav");
+        let envelope = temp_dir.path().join("env.brk");
+        let output = temp_dir.path().join("output.wav");
+
+        let format = wav_cdp::WavFormat {
+            channels: 1,
+            sample_rate: 2,
+            bits_per_sample: 16,
+            data_size: 0,
+        };
+        wav_cdp::write_wav_cdp(&input, &format, &[16384, 16384, 16384]).unwrap();
+        std::fs::write(&envelope, "0 1\n1 0\n").unwrap();
+
+        impose(&input, &envelope, &output).unwrap();
+        let (out_format, samples) = wav_cdp::read_wav_basic(&output).unwrap();
+        assert_eq!(out_format.bits_per_sample, 16);
+        assert_eq!(samples, vec![16384, 8192, 0]);
+    }
+}
@@ -0,0 +1,40 @@
+//! CDP Envel module - Amplitude envelope operations
+//!
+//! This module implements CDP's envelope operations including:
+//! - Envelope extraction to breakpoint files
+//! - Imposing an envelope on a sound
+//! - Envelope warping (normalize, exaggerate, corrugate, tremolo)
+//!
+//! Envelopes are stored as CDP breakpoint text files of `time level` lines.
+
+use thiserror::Error;
+
+pub mod envelope;
+pub mod extract;
+pub mod impose;
+pub mod warp;
+
+/// Result type for envel operations
+pub type Result<T> = std::result::Result<T, EnvelError>;
+
+/// Errors that can occur during envel operations
+#[derive(Error, Debug)]
+pub enum EnvelError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Housekeep error: {0}")]
+    Housekeep(#[from] cdp_housekeep::HousekeepError),
+
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
+
+    #[error("Invalid envelope file: {0}")]
+    InvalidEnvelope(String),
+}
+
+// Re-export main functions for convenience
+pub use envelope::Envelope;
+pub use extract::{extract, extract_buf};
+pub use impose::{impose, impose_buf};
+pub use warp::{warp, warp_envelope, Warp};
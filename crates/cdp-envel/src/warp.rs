@@ -0,0 +1,141 @@
+//! Envelope warping
+//!
+//! Reshapes breakpoint envelopes, like CDP's envel warp, before they are
+//! imposed on a sound.
+
+use super::{EnvelError, Result};
+use crate::envelope::Envelope;
+use std::f64::consts::PI;
+use std::path::Path;
+
+/// Breakpoints per tremolo cycle
+const TREMOLO_POINTS_PER_CYCLE: usize = 16;
+
+/// Envelope warp operations
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Warp {
+    /// Scale so the peak level is 1
+    Normalize,
+    /// Raise levels, relative to the peak, to this power; above 1 deepens
+    /// the contours, below 1 flattens them
+    Exaggerate(f64),
+    /// Cut every trough to silence, separating the peaks
+    Corrugate,
+    /// Add amplitude modulation
+    Tremolo {
+        /// Modulation rate in Hz
+        frequency: f64,
+        /// How far (0-1) each cycle dips towards silence
+        depth: f64,
+    },
+}
+
+/// Warp a breakpoint envelope file
+///
+/// # Arguments
+/// * `input` - Path to input breakpoint file
+/// * `output` - Path to output breakpoint file
+/// * `warp` - Warp operation to apply
+pub fn warp(input: &Path, output: &Path, warp: Warp) -> Result<()> {
+    check_warp(warp)?;
+    let envelope = Envelope::load(input)?;
+    warp_envelope(&envelope, warp)?.save(output)
+}
+
+/// Warp an in-memory envelope
+///
+/// Silent envelopes are returned unchanged by normalizing and
+/// exaggerating. Tremolo resamples the envelope at 16 points per cycle
+/// from its first to its last breakpoint, each cycle starting at full
+/// level.
+pub fn warp_envelope(envelope: &Envelope, warp: Warp) -> Result<Envelope> {
+    check_warp(warp)?;
+    let points = envelope.points();
+    let peak = envelope.peak();
+
+    let warped = match warp {
+        Warp::Normalize | Warp::Exaggerate(_) if peak == 0.0 => points.to_vec(),
+        Warp::Normalize => points.iter().map(|&(t, v)| (t, v / peak)).collect(),
+        Warp::Exaggerate(exponent) => points
+            .iter()
+            .map(|&(t, v)| (t, peak * (v / peak).powf(exponent)))
+            .collect(),
+        Warp::Corrugate => points
+            .iter()
+            .enumerate()
+            .map(|(i, &(t, v))| {
+                let below =
+                    |j: Option<usize>| j.and_then(|j| points.get(j)).map_or(true, |p| v < p.1);
+                let trough = points.len() > 1 && below(i.checked_sub(1)) && below(Some(i + 1));
+                (t, if trough { 0.0 } else { v })
+            })
+            .collect(),
+        Warp::Tremolo { frequency, depth } => {
+            let start = points[0].0;
+            let span = envelope.duration() - start;
+            let count = (span * frequency * TREMOLO_POINTS_PER_CYCLE as f64).ceil() as usize;
+            (0..=count)
+                .map(|i| {
+                    let time = if count == 0 {
+                        start
+                    } else {
+                        start + span * i as f64 / count as f64
+                    };
+                    let dip = (1.0 - (2.0 * PI * frequency * (time - start)).cos()) / 2.0;
+                    (time, envelope.level_at(time) * (1.0 - depth * dip))
+                })
+                .collect()
+        }
+    };
+    Envelope::new(warped)
+}
+
+fn check_warp(warp: Warp) -> Result<()> {
+    match warp {
+        Warp::Exaggerate(exponent) if !(exponent > 0.0 && exponent.is_finite()) => Err(
+            EnvelError::InvalidParameter("Exaggeration must be greater than 0".into()),
+        ),
+        Warp::Tremolo { frequency, .. } if !(frequency > 0.0 && frequency.is_finite()) => Err(
+            EnvelError::InvalidParameter("Tremolo frequency must be greater than 0".into()),
+        ),
+        Warp::Tremolo { depth, .. } if !(0.0..=1.0).contains(&depth) => Err(
+            EnvelError::InvalidParameter("Tremolo depth must be between 0 and 1".into()),
+        ),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_warp_envelope() {
+        let envelope =
+            Envelope::new(vec![(0.0, 0.5), (1.0, 0.25), (2.0, 0.125), (3.0, 0.5)]).unwrap();
+
+        let normalized = warp_envelope(&envelope, Warp::Normalize).unwrap();
+        assert_eq!(normalized.points()[1], (1.0, 0.5));
+
+        let exaggerated = warp_envelope(&envelope, Warp::Exaggerate(2.0)).unwrap();
+        assert_eq!(exaggerated.points()[1], (1.0, 0.125));
+
+        let corrugated = warp_envelope(&envelope, Warp::Corrugate).unwrap();
+        let levels: Vec<f64> = corrugated.points().iter().map(|p| p.1).collect();
+        assert_eq!(levels, vec![0.5, 0.25, 0.0, 0.5]);
+
+        // One cycle a second dips fully to silence half way through
+        let tremolo = Warp::Tremolo {
+            frequency: 1.0,
+            depth: 1.0,
+        };
+        let trembled = warp_envelope(&envelope, tremolo).unwrap();
+        assert_eq!(trembled.points().len(), 49);
+        assert!(trembled.level_at(0.5) < 1e-12);
+        assert_eq!(trembled.level_at(1.0), 0.25);
+
+        assert!(warp_envelope(&envelope, Warp::Exaggerate(0.0)).is_err());
+    }
+}
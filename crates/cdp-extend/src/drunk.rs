@@ -0,0 +1,194 @@
+//! Drunken-walk playback
+//!
+//! Plays short segments of a sound from positions that wander at random
+//! around a locus, like CDP's extend drunk.
+
+use super::{ExtendError, Result};
+use crate::splice::{splice_segments, to_frames, Segment, DEFAULT_SPLICE_MS};
+use cdp_core::{AudioBuffer, Rng};
+use cdp_housekeep::wav_cdp;
+use std::path::Path;
+
+/// Settings for [`drunk`]
+///
+/// Times are in seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DrunkOptions {
+    /// Length of the output
+    pub duration: f64,
+    /// Time in the input the walk starts from and centres on
+    pub locus: f64,
+    /// Furthest the walk may stray from the locus
+    pub ambitus: f64,
+    /// Largest move between segments
+    pub step: f64,
+    /// Time between segment starts in the output
+    pub clock: f64,
+    /// Crossfade between segments
+    pub splice: f64,
+    /// Seed for the walk
+    pub seed: u32,
+}
+
+/// Play a sound by a drunken walk around a locus
+pub fn drunk(input: &Path, output: &Path, options: &DrunkOptions) -> Result<()> {
+    let (audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    wav_cdp::write_audio_buffer(output, &drunk_buf(&audio, options)?, sample_type)?;
+    Ok(())
+}
+
+/// Play an in-memory sound by a drunken walk around a locus
+///
+/// Buffer-based counterpart of [`drunk`]. Each segment is `clock` long
+/// plus the splice that overlaps the next one. The read position moves by
+/// up to `step` either way between segments, reflecting off the edges of
+/// the ambitus and of the sound.
+pub fn drunk_buf(audio: &AudioBuffer, options: &DrunkOptions) -> Result<AudioBuffer> {
+    check_options(options, audio.duration())?;
+
+    let rate = audio.sample_rate;
+    let frames = audio.frame_count();
+    let clock = to_frames(options.clock, rate).max(1);
+    let splice = to_frames(options.splice, rate);
+    let seglen = (clock + splice).min(frames);
+    let locus = to_frames(options.locus, rate) as f64;
+    let ambitus = to_frames(options.ambitus, rate) as f64;
+    let step = to_frames(options.step, rate) as f64;
+    let low = (locus - ambitus).max(0.0);
+    let high = (locus + ambitus).min((frames - seglen) as f64);
+    let out_frames = to_frames(options.duration, rate);
+
+    let mut rng = Rng::new(options.seed);
+    let mut position = locus.clamp(low, high.max(low));
+    let mut segments = Vec::new();
+    for _ in 0..(out_frames + clock - 1) / clock {
+        let start = position.round() as usize;
+        segments.push(Segment {
+            start,
+            end: start + seglen,
+            reversed: false,
+        });
+        position = reflect(position + (rng.uniform() * 2.0 - 1.0) * step, low, high);
+    }
+
+    let channels = audio.channels as usize;
+    let mut data = splice_segments(&audio.data, channels, &segments, splice);
+    data.truncate(out_frames * channels);
+    Ok(AudioBuffer::new(rate, audio.channels, data)?)
+}
+
+/// Fold a position back inside `low..=high`
+fn reflect(mut position: f64, low: f64, high: f64) -> f64 {
+    if high <= low {
+        return low;
+    }
+    while !(low..=high).contains(&position) {
+        position = if position < low {
+            2.0 * low - position
+        } else {
+            2.0 * high - position
+        };
+    }
+    position
+}
+
+fn check_options(options: &DrunkOptions, duration: f64) -> Result<()> {
+    if !(options.duration > 0.0 && options.duration.is_finite()) {
+        return Err(ExtendError::InvalidParameter(
+            "Output duration must be greater than 0".into(),
+        ));
+    }
+    if !(0.0..=duration).contains(&options.locus) {
+        return Err(ExtendError::InvalidParameter(
+            "Locus must lie within the sound".into(),
+        ));
+    }
+    if !(options.clock > 0.0 && options.clock.is_finite()) {
+        return Err(ExtendError::InvalidParameter(
+            "Clock must be greater than 0".into(),
+        ));
+    }
+    if ![options.ambitus, options.step, options.splice]
+        .iter()
+        .all(|t| *t >= 0.0 && t.is_finite())
+    {
+        return Err(ExtendError::InvalidParameter(
+            "Ambitus, step and splice cannot be negative".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// CLI compatibility layer for extend drunk
+///
+/// Usage: `drunk 1 infile outfile outdur locus ambitus step clock [splice
+/// seed]`. Times are in seconds apart from the splice, which is in
+/// milliseconds as in CDP.
+pub fn drunk_cli(mode: i32, args: &[&str]) -> Result<()> {
+    if mode != 1 {
+        return Err(ExtendError::UnsupportedOperation(format!(
+            "Drunk mode {} not yet implemented",
+            mode
+        )));
+    }
+    if args.len() < 7 {
+        return Err(ExtendError::InvalidParameter(
+            "Usage: drunk 1 infile outfile outdur locus ambitus step clock [splice seed]".into(),
+        ));
+    }
+    let number = |arg: &str| {
+        arg.parse::<f64>()
+            .map_err(|_| ExtendError::InvalidParameter(format!("Invalid value: {}", arg)))
+    };
+    let optional = |index: usize, default: f64| args.get(index).map_or(Ok(default), |a| number(a));
+
+    let options = DrunkOptions {
+        duration: number(args[2])?,
+        locus: number(args[3])?,
+        ambitus: number(args[4])?,
+        step: number(args[5])?,
+        clock: number(args[6])?,
+        splice: optional(7, DEFAULT_SPLICE_MS)? / 1000.0,
+        seed: optional(8, 0.0)? as u32,
+    };
+    drunk(Path::new(args[0]), Path::new(args[1]), &options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drunk_buf() {
+        let audio = AudioBuffer::new(100, 1, (0..1000).map(|i| i as f32).collect()).unwrap();
+        let options = DrunkOptions {
+            duration: 5.0,
+            locus: 5.0,
+            ambitus: 1.0,
+            step: 0.3,
+            clock: 0.1,
+            splice: 0.0,
+            seed: 7,
+        };
+        let output = drunk_buf(&audio, &options).unwrap();
+        assert_eq!(output.frame_count(), 500);
+
+        // Every segment starts within the ambitus and plays forwards
+        for segment in output.data.chunks_exact(10) {
+            assert!((400.0..=600.0).contains(&segment[0]));
+            assert!(segment.windows(2).all(|pair| pair[1] == pair[0] + 1.0));
+        }
+
+        // Same seed, same walk
+        assert_eq!(drunk_buf(&audio, &options).unwrap(), output);
+        assert!(drunk_buf(
+            &audio,
+            &DrunkOptions {
+                clock: 0.0,
+                ..options
+            }
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,304 @@
+//! Iterative layering
+//!
+//! Overlays repeated copies of a sound at a regular delay, each with its
+//! own random pitch shift and level, like CDP's extend iterate.
+
+use super::{ExtendError, Result};
+use crate::splice::to_frames;
+use cdp_core::audio::{self, AudioBuffer};
+use cdp_core::Rng;
+use cdp_housekeep::wav_cdp;
+use std::path::Path;
+
+/// How many copies to layer
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum IterateCount {
+    /// Keep adding copies until the output is this many seconds long
+    Duration(f64),
+    /// A fixed number of copies, including the first unaltered one
+    Repeats(usize),
+}
+
+/// Settings for [`iterate`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IterateOptions {
+    /// Length of the output or number of copies
+    pub count: IterateCount,
+    /// Time between the starts of successive copies, in seconds
+    pub delay: f64,
+    /// Randomisation of each delay (0-1), as a fraction of the delay
+    pub rand: f64,
+    /// Largest random pitch shift of a copy either way, in semitones
+    pub pitch_shift: f64,
+    /// Largest random level cut of a copy (0-1)
+    pub amp_cut: f64,
+    /// Level of each copy relative to the one before (0-1)
+    pub fade: f64,
+    /// Output gain; zero scales the output back to the input's peak
+    pub gain: f64,
+    /// Seed for the randomisation
+    pub seed: u32,
+}
+
+/// Layer delayed copies of a sound
+pub fn iterate(input: &Path, output: &Path, options: &IterateOptions) -> Result<()> {
+    let (audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    wav_cdp::write_audio_buffer(output, &iterate_buf(&audio, options)?, sample_type)?;
+    Ok(())
+}
+
+/// Layer delayed copies of an in-memory sound
+///
+/// Buffer-based counterpart of [`iterate`]. The first copy starts at zero
+/// unaltered. Copy `k` nominally starts `k * delay` later, shifted by up to
+/// half of `rand * delay` either way, is transposed by resampling, and
+/// plays at `fade^k` times a random level between `1 - amp_cut` and 1.
+pub fn iterate_buf(audio: &AudioBuffer, options: &IterateOptions) -> Result<AudioBuffer> {
+    check_options(options)?;
+
+    let rate = audio.sample_rate;
+    let channels = audio.channels as usize;
+    let delay = options.delay * rate as f64;
+    let limit = match options.count {
+        IterateCount::Duration(duration) => Some(to_frames(duration, rate)),
+        IterateCount::Repeats(_) => None,
+    };
+
+    let mut rng = Rng::new(options.seed);
+    let mut data: Vec<f32> = Vec::new();
+    let mut level = 1.0f64;
+    for index in 0.. {
+        let nominal = index as f64 * delay;
+        let done = match options.count {
+            IterateCount::Repeats(repeats) => index == repeats,
+            IterateCount::Duration(duration) => nominal >= duration * rate as f64,
+        };
+        if done {
+            break;
+        }
+
+        let (start, ratio, gain) = if index == 0 {
+            (0, 1.0, 1.0)
+        } else {
+            let jitter = (rng.uniform() - 0.5) * options.rand * delay;
+            let semitones = (rng.uniform() * 2.0 - 1.0) * options.pitch_shift;
+            let cut = 1.0 - rng.uniform() * options.amp_cut;
+            level *= options.fade;
+            (
+                (nominal + jitter).round().max(0.0) as usize,
+                2.0f64.powf(semitones / 12.0),
+                level * cut,
+            )
+        };
+
+        let copy = audio::transpose(&audio.data, channels, ratio);
+        let end = start * channels + copy.len();
+        if data.len() < end {
+            data.resize(end, 0.0);
+        }
+        for (out, &sample) in data[start * channels..end].iter_mut().zip(&copy) {
+            *out += sample * gain as f32;
+        }
+    }
+    if let Some(limit) = limit {
+        data.resize(limit * channels, 0.0);
+    }
+
+    let gain = if options.gain == 0.0 {
+        let peak = data.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        if peak > 0.0 {
+            audio.peak() / peak
+        } else {
+            1.0
+        }
+    } else {
+        options.gain as f32
+    };
+    for sample in &mut data {
+        *sample *= gain;
+    }
+    Ok(AudioBuffer::new(rate, audio.channels, data)?)
+}
+
+fn check_options(options: &IterateOptions) -> Result<()> {
+    match options.count {
+        IterateCount::Duration(duration) if !(duration > 0.0 && duration.is_finite()) => {
+            return Err(ExtendError::InvalidParameter(
+                "Output duration must be greater than 0".into(),
+            ))
+        }
+        IterateCount::Repeats(repeats) if !(1..=32767).contains(&repeats) => {
+            return Err(ExtendError::InvalidParameter(
+                "Repeats must be between 1 and 32767".into(),
+            ))
+        }
+        _ => {}
+    }
+    if !(options.delay > 0.0 && options.delay.is_finite()) {
+        return Err(ExtendError::InvalidParameter(
+            "Delay must be greater than 0".into(),
+        ));
+    }
+    if !(0.0..=12.0).contains(&options.pitch_shift) {
+        return Err(ExtendError::InvalidParameter(
+            "Pitch shift must be between 0 and 12 semitones".into(),
+        ));
+    }
+    for (name, value) in [
+        ("Delay randomisation", options.rand),
+        ("Amplitude cut", options.amp_cut),
+        ("Fade", options.fade),
+    ] {
+        if !(0.0..=1.0).contains(&value) {
+            return Err(ExtendError::InvalidParameter(format!(
+                "{} must be between 0 and 1",
+                name
+            )));
+        }
+    }
+    if !(options.gain >= 0.0 && options.gain.is_finite()) {
+        return Err(ExtendError::InvalidParameter(
+            "Gain cannot be negative".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// CLI compatibility layer for extend iterate
+///
+/// Usage: `iterate 1 infile outfile outduration delay [-rrand] [-ppshift]
+/// [-aampcut] [-ffade] [-ggain] [-sseed]`, or mode 2 with a repeat count
+/// in place of the output duration.
+pub fn iterate_cli(mode: i32, args: &[&str]) -> Result<()> {
+    if !(1..=2).contains(&mode) {
+        return Err(ExtendError::UnsupportedOperation(format!(
+            "Iterate mode {} not yet implemented",
+            mode
+        )));
+    }
+    if args.len() < 4 {
+        return Err(ExtendError::InvalidParameter(
+            "Usage: iterate mode infile outfile outduration|repeats delay [-rrand] [-ppshift] \
+             [-aampcut] [-ffade] [-ggain] [-sseed]"
+                .into(),
+        ));
+    }
+    let number = |arg: &str| {
+        arg.parse::<f64>()
+            .map_err(|_| ExtendError::InvalidParameter(format!("Invalid value: {}", arg)))
+    };
+
+    let count = if mode == 1 {
+        IterateCount::Duration(number(args[2])?)
+    } else {
+        IterateCount::Repeats(
+            args[2]
+                .parse()
+                .map_err(|_| ExtendError::InvalidParameter("Invalid repeat count".into()))?,
+        )
+    };
+    let mut options = IterateOptions {
+        count,
+        delay: number(args[3])?,
+        rand: 0.0,
+        pitch_shift: 0.0,
+        amp_cut: 0.0,
+        fade: 1.0,
+        gain: 0.0,
+        seed: 0,
+    };
+    for arg in &args[4..] {
+        if let Some(value) = arg.strip_prefix("-r") {
+            options.rand = number(value)?;
+        } else if let Some(value) = arg.strip_prefix("-p") {
+            options.pitch_shift = number(value)?;
+        } else if let Some(value) = arg.strip_prefix("-a") {
+            options.amp_cut = number(value)?;
+        } else if let Some(value) = arg.strip_prefix("-f") {
+            options.fade = number(value)?;
+        } else if let Some(value) = arg.strip_prefix("-g") {
+            options.gain = number(value)?;
+        } else if let Some(value) = arg.strip_prefix("-s") {
+            options.seed = number(value)? as u32;
+        } else {
+            return Err(ExtendError::InvalidParameter(format!(
+                "Unknown iterate flag: {}",
+                arg
+            )));
+        }
+    }
+    iterate(Path::new(args[0]), Path::new(args[1]), &options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(count: IterateCount) -> IterateOptions {
+        IterateOptions {
+            count,
+            delay: 0.2,
+            rand: 0.0,
+            pitch_shift: 0.0,
+            amp_cut: 0.0,
+            fade: 0.5,
+            gain: 1.0,
+            seed: 0,
+        }
+    }
+
+    #[test]
+    fn test_iterate_buf() {
+        // A click repeated every two frames, halving each time
+        let audio = AudioBuffer::new(10, 1, vec![1.0, 0.0, 0.0]).unwrap();
+        let output = iterate_buf(&audio, &options(IterateCount::Repeats(3))).unwrap();
+        assert_eq!(output.data, vec![1.0, 0.0, 0.5, 0.0, 0.25, 0.0, 0.0]);
+
+        let output = iterate_buf(&audio, &options(IterateCount::Duration(0.5))).unwrap();
+        assert_eq!(output.data, vec![1.0, 0.0, 0.5, 0.0, 0.25]);
+
+        // Automatic gain brings the peak back to the input's
+        let automatic = IterateOptions {
+            delay: 0.1,
+            fade: 1.0,
+            gain: 0.0,
+            ..options(IterateCount::Repeats(2))
+        };
+        let audio = AudioBuffer::new(10, 1, vec![0.5, 0.5]).unwrap();
+        let output = iterate_buf(&audio, &automatic).unwrap();
+        assert_eq!(output.data, vec![0.25, 0.5, 0.25]);
+    }
+
+    #[test]
+    fn test_iterate_randomisation() {
+        let audio =
+            AudioBuffer::new(100, 1, (0..100).map(|i| (i as f32 * 0.3).sin()).collect()).unwrap();
+        let random = IterateOptions {
+            rand: 0.5,
+            pitch_shift: 3.0,
+            amp_cut: 0.5,
+            seed: 3,
+            ..options(IterateCount::Repeats(8))
+        };
+        let first = iterate_buf(&audio, &random).unwrap();
+        let empty = AudioBuffer::new(100, 1, Vec::new()).unwrap();
+        let silent = iterate_buf(&empty, &random).unwrap();
+        assert!(silent.data.iter().all(|&s| s == 0.0));
+        assert_eq!(iterate_buf(&audio, &random).unwrap(), first);
+        assert_ne!(
+            iterate_buf(&audio, &IterateOptions { seed: 4, ..random }).unwrap(),
+            first
+        );
+        assert!(iterate_buf(
+            &audio,
+            &IterateOptions {
+                fade: 1.5,
+                ..random
+            }
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,64 @@
+//! CDP Extend module - Time-domain extension operations
+//!
+//! This module implements CDP's extend operations including:
+//! - Iterative layering with random pitch drift and decay
+//! - Looping a segment, advancing through the sound with a search field
+//! - Drunken-walk playback around a locus
+//! - Zigzagging forwards and backwards between times
+//! - Scrambling the order of chunks
+//!
+//! Loop, drunk, zigzag and scramble join the pieces they play with crossfade splices.
+
+use thiserror::Error;
+
+pub mod drunk;
+pub mod iterate;
+pub mod looping;
+pub mod scramble;
+mod splice;
+pub mod zigzag;
+
+/// Result type for extend operations
+pub type Result<T> = std::result::Result<T, ExtendError>;
+
+/// Errors that can occur during extend operations
+#[derive(Error, Debug)]
+pub enum ExtendError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Housekeep error: {0}")]
+    Housekeep(#[from] cdp_housekeep::HousekeepError),
+
+    #[error("Core error: {0}")]
+    Core(#[from] cdp_core::CoreError),
+
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
+
+    #[error("Unsupported operation: {0}")]
+    UnsupportedOperation(String),
+}
+
+// Re-export main functions for convenience
+pub use drunk::{drunk, drunk_buf, DrunkOptions};
+pub use iterate::{iterate, iterate_buf, IterateCount, IterateOptions};
+pub use looping::{loop_buf, loop_sound, LoopCount, LoopOptions};
+pub use scramble::{scramble, scramble_buf, scramble_with, ScrambleMode, ScrambleOptions};
+pub use zigzag::{zigzag, zigzag_buf};
+
+/// CLI compatibility layer - matches CDP's command-line interface
+/// This is just for oracle testing. Real users should use the library functions directly.
+pub fn extend(operation: &str, mode: i32, args: &[&str]) -> Result<()> {
+    match operation {
+        "drunk" => drunk::drunk_cli(mode, args),
+        "iterate" => iterate::iterate_cli(mode, args),
+        "loop" => looping::loop_cli(mode, args),
+        "scramble" => scramble::scramble_cli(mode, args),
+        "zigzag" => zigzag::zigzag_cli(mode, args),
+        _ => Err(ExtendError::UnsupportedOperation(format!(
+            "Unknown operation: {}",
+            operation
+        ))),
+    }
+}
@@ -0,0 +1,244 @@
+//! Segment looping
+//!
+//! Repeats a segment of a sound, like CDP's extend loop, optionally
+//! advancing the segment through the sound and jittering where each
+//! repeat starts.
+
+use super::{ExtendError, Result};
+use crate::splice::{splice_segments, to_frames, Segment, DEFAULT_SPLICE_MS};
+use cdp_core::{AudioBuffer, Rng};
+use cdp_housekeep::wav_cdp;
+use std::path::Path;
+
+/// How many loops to play
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum LoopCount {
+    /// A fixed number of loops
+    Repeats(usize),
+    /// Keep advancing until the next loop would run past the end
+    ToEnd,
+}
+
+/// Settings for [`loop_sound`]
+///
+/// Times are in seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LoopOptions {
+    /// Start of the first loop
+    pub start: f64,
+    /// Length of each loop
+    pub length: f64,
+    /// How far each loop's start advances past the previous one
+    pub step: f64,
+    /// Each loop starts up to this much later than its nominal start, at random
+    pub search: f64,
+    /// Number of loops
+    pub count: LoopCount,
+    /// Crossfade between loops
+    pub splice: f64,
+    /// Seed for the search jitter
+    pub seed: u32,
+}
+
+/// Loop a segment of a sound
+pub fn loop_sound(input: &Path, output: &Path, options: &LoopOptions) -> Result<()> {
+    let (audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    wav_cdp::write_audio_buffer(output, &loop_buf(&audio, options)?, sample_type)?;
+    Ok(())
+}
+
+/// Loop a segment of an in-memory sound
+///
+/// Buffer-based counterpart of [`loop_sound`]. Loops are clamped so none
+/// runs past the end of the sound, and the output holds only the loops.
+pub fn loop_buf(audio: &AudioBuffer, options: &LoopOptions) -> Result<AudioBuffer> {
+    check_options(options, audio.duration(), audio.sample_rate)?;
+
+    let rate = audio.sample_rate;
+    let frames = audio.frame_count();
+    let start = to_frames(options.start, rate);
+    let length = to_frames(options.length, rate).clamp(1, frames);
+    let step = to_frames(options.step, rate);
+    let search = to_frames(options.search, rate);
+
+    let mut rng = Rng::new(options.seed);
+    let mut segments = Vec::new();
+    for index in 0.. {
+        let nominal = start + index * step;
+        let done = match options.count {
+            LoopCount::Repeats(repeats) => index == repeats,
+            LoopCount::ToEnd => nominal + length > frames,
+        };
+        if done {
+            break;
+        }
+        let jitter = if search > 0 { rng.below(search + 1) } else { 0 };
+        let loop_start = (nominal + jitter).min(frames - length);
+        segments.push(Segment {
+            start: loop_start,
+            end: loop_start + length,
+            reversed: false,
+        });
+    }
+
+    let channels = audio.channels as usize;
+    let data = splice_segments(
+        &audio.data,
+        channels,
+        &segments,
+        to_frames(options.splice, rate),
+    );
+    Ok(AudioBuffer::new(rate, audio.channels, data)?)
+}
+
+fn check_options(options: &LoopOptions, duration: f64, rate: u32) -> Result<()> {
+    if !(options.length > 0.0 && options.length <= duration) {
+        return Err(ExtendError::InvalidParameter(
+            "Loop length must be greater than 0 and within the sound".into(),
+        ));
+    }
+    if !(0.0..=duration - options.length).contains(&options.start) {
+        return Err(ExtendError::InvalidParameter(
+            "Loop must start within the sound and leave room for its length".into(),
+        ));
+    }
+    if ![options.step, options.search, options.splice]
+        .iter()
+        .all(|t| *t >= 0.0 && t.is_finite())
+    {
+        return Err(ExtendError::InvalidParameter(
+            "Loop step, search and splice cannot be negative".into(),
+        ));
+    }
+    match options.count {
+        LoopCount::Repeats(repeats) if !(1..=10000).contains(&repeats) => Err(
+            ExtendError::InvalidParameter("Loop repeats must be between 1 and 10000".into()),
+        ),
+        // A step that rounds to no frames would never reach the end
+        LoopCount::ToEnd if to_frames(options.step, rate) == 0 => {
+            Err(ExtendError::InvalidParameter(
+                "Looping to the end needs a step of at least one sample".into(),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// CLI compatibility layer for extend loop
+///
+/// Usage: `loop 1 infile outfile start looplen step [search splice seed]`
+/// loops to the end of the sound, and `loop 2 infile outfile start looplen
+/// step repeats [search splice seed]` plays a fixed number of loops. As in
+/// CDP, the start is in seconds and the other times are in milliseconds.
+pub fn loop_cli(mode: i32, args: &[&str]) -> Result<()> {
+    let required = match mode {
+        1 => 5,
+        2 => 6,
+        _ => {
+            return Err(ExtendError::UnsupportedOperation(format!(
+                "Loop mode {} not yet implemented",
+                mode
+            )))
+        }
+    };
+    if args.len() < required {
+        return Err(ExtendError::InvalidParameter(
+            "Usage: loop mode infile outfile start looplen step [repeats] [search splice seed]"
+                .into(),
+        ));
+    }
+    let number = |arg: &str| {
+        arg.parse::<f64>()
+            .map_err(|_| ExtendError::InvalidParameter(format!("Invalid value: {}", arg)))
+    };
+    let optional = |index: usize, default: f64| args.get(index).map_or(Ok(default), |a| number(a));
+
+    let count = if mode == 1 {
+        LoopCount::ToEnd
+    } else {
+        LoopCount::Repeats(
+            args[5]
+                .parse()
+                .map_err(|_| ExtendError::InvalidParameter("Invalid repeat count".into()))?,
+        )
+    };
+    let options = LoopOptions {
+        start: number(args[2])?,
+        length: number(args[3])? / 1000.0,
+        step: number(args[4])? / 1000.0,
+        search: optional(required, 0.0)? / 1000.0,
+        count,
+        splice: optional(required + 1, DEFAULT_SPLICE_MS)? / 1000.0,
+        seed: optional(required + 2, 0.0)? as u32,
+    };
+    loop_sound(Path::new(args[0]), Path::new(args[1]), &options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramp(frames: usize) -> AudioBuffer {
+        AudioBuffer::new(10, 1, (0..frames).map(|i| i as f32).collect()).unwrap()
+    }
+
+    #[test]
+    fn test_loop_buf() {
+        let options = LoopOptions {
+            start: 0.2,
+            length: 0.3,
+            step: 0.0,
+            search: 0.0,
+            count: LoopCount::Repeats(3),
+            splice: 0.0,
+            seed: 0,
+        };
+        let output = loop_buf(&ramp(10), &options).unwrap();
+        assert_eq!(
+            output.data,
+            vec![2.0, 3.0, 4.0, 2.0, 3.0, 4.0, 2.0, 3.0, 4.0]
+        );
+
+        // Advancing by two frames until the loop would pass the end
+        let options = LoopOptions {
+            step: 0.2,
+            count: LoopCount::ToEnd,
+            ..options
+        };
+        let output = loop_buf(&ramp(10), &options).unwrap();
+        assert_eq!(
+            output.data,
+            vec![2.0, 3.0, 4.0, 4.0, 5.0, 6.0, 6.0, 7.0, 8.0]
+        );
+    }
+
+    #[test]
+    fn test_loop_validation() {
+        let options = LoopOptions {
+            start: 0.9,
+            length: 0.3,
+            step: 0.0,
+            search: 0.0,
+            count: LoopCount::Repeats(2),
+            splice: 0.0,
+            seed: 0,
+        };
+        assert!(loop_buf(&ramp(10), &options).is_err());
+        let options = LoopOptions {
+            start: 0.0,
+            count: LoopCount::ToEnd,
+            ..options
+        };
+        assert!(loop_buf(&ramp(10), &options).is_err());
+
+        // Less than half a frame rounds to no step at all
+        let options = LoopOptions {
+            step: 0.04,
+            ..options
+        };
+        assert!(loop_buf(&ramp(10), &options).is_err());
+    }
+}
@@ -0,0 +1,90 @@
+//! Crossfade splicing of source segments
+//!
+//! The extend operations all build their output by playing stretches of
+//! the input one after another; this joins them without clicks.
+
+/// Splice length used by the CLI when none is given, in ms
+pub(crate) const DEFAULT_SPLICE_MS: f64 = 15.0;
+
+/// A stretch of source frames, played forwards or backwards
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Segment {
+    /// First frame of the stretch
+    pub(crate) start: usize,
+    /// Frame after the last of the stretch
+    pub(crate) end: usize,
+    /// Play from the end back to the start
+    pub(crate) reversed: bool,
+}
+
+/// Join segments of interleaved audio, crossfading each into the last
+///
+/// Each join overlaps the end of the output so far with the start of the
+/// next segment by up to `splice` frames, but never more than the segment
+/// itself, fading linearly from one to the other.
+pub(crate) fn splice_segments(
+    samples: &[f32],
+    channels: usize,
+    segments: &[Segment],
+    splice: usize,
+) -> Vec<f32> {
+    let mut output: Vec<f32> = Vec::new();
+    for segment in segments {
+        let len = segment.end - segment.start;
+        let written = output.len() / channels;
+        let overlap = splice.min(len).min(written);
+        let base = written - overlap;
+
+        for offset in 0..len {
+            let frame = if segment.reversed {
+                segment.end - 1 - offset
+            } else {
+                segment.start + offset
+            };
+            let source = &samples[frame * channels..(frame + 1) * channels];
+            if offset < overlap {
+                let gain = (offset + 1) as f32 / (overlap + 1) as f32;
+                let at = (base + offset) * channels;
+                for (out, &sample) in output[at..at + channels].iter_mut().zip(source) {
+                    *out = *out * (1.0 - gain) + sample * gain;
+                }
+            } else {
+                output.extend_from_slice(source);
+            }
+        }
+    }
+    output
+}
+
+/// Convert seconds to a whole number of frames
+pub(crate) fn to_frames(seconds: f64, sample_rate: u32) -> usize {
+    (seconds * sample_rate as f64).round() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_splice_segments() {
+        let samples = [0.0, 1.0, 2.0, 3.0, 4.0, 5.0];
+        let forward = Segment {
+            start: 0,
+            end: 3,
+            reversed: false,
+        };
+        let backward = Segment {
+            start: 3,
+            end: 6,
+            reversed: true,
+        };
+
+        // Butt joins without a splice
+        let output = splice_segments(&samples, 1, &[forward, backward], 0);
+        assert_eq!(output, vec![0.0, 1.0, 2.0, 5.0, 4.0, 3.0]);
+
+        // One frame of overlap blends the join half and half
+        let output = splice_segments(&samples, 1, &[forward, backward], 1);
+        assert_eq!(output, vec![0.0, 1.0, 3.5, 4.0, 3.0]);
+    }
+}
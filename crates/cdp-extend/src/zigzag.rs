@@ -0,0 +1,128 @@
+//! Forwards and backwards playback between times
+//!
+//! Plays a sound back and forth through a list of times, like CDP's
+//! extend zigzag.
+
+use super::{ExtendError, Result};
+use crate::splice::{splice_segments, to_frames, Segment, DEFAULT_SPLICE_MS};
+use cdp_core::AudioBuffer;
+use cdp_housekeep::wav_cdp;
+use std::path::Path;
+
+/// Zigzag through a sound between the given times
+///
+/// `times` are in seconds and `splice` is the crossfade at each turn, also
+/// in seconds.
+pub fn zigzag(input: &Path, output: &Path, times: &[f64], splice: f64) -> Result<()> {
+    let (audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    wav_cdp::write_audio_buffer(output, &zigzag_buf(&audio, times, splice)?, sample_type)?;
+    Ok(())
+}
+
+/// Zigzag through an in-memory sound between the given times
+///
+/// Buffer-based counterpart of [`zigzag`]. The sound plays from each time
+/// to the next, forwards when the time increases and backwards when it
+/// decreases.
+pub fn zigzag_buf(audio: &AudioBuffer, times: &[f64], splice: f64) -> Result<AudioBuffer> {
+    let duration = audio.duration();
+    if times.len() < 2 {
+        return Err(ExtendError::InvalidParameter(
+            "Zigzag needs at least two times".into(),
+        ));
+    }
+    if !times.iter().all(|t| (0.0..=duration).contains(t)) {
+        return Err(ExtendError::InvalidParameter(
+            "Zigzag times must lie within the sound".into(),
+        ));
+    }
+    if !(splice >= 0.0 && splice.is_finite()) {
+        return Err(ExtendError::InvalidParameter(
+            "Splice cannot be negative".into(),
+        ));
+    }
+
+    let rate = audio.sample_rate;
+    let segments: Vec<Segment> = times
+        .windows(2)
+        .map(|pair| (to_frames(pair[0], rate), to_frames(pair[1], rate)))
+        .filter(|(from, to)| from != to)
+        .map(|(from, to)| Segment {
+            start: from.min(to),
+            end: from.max(to),
+            reversed: to < from,
+        })
+        .collect();
+    if segments.is_empty() {
+        return Err(ExtendError::InvalidParameter(
+            "Zigzag times must not all be the same".into(),
+        ));
+    }
+
+    let data = splice_segments(
+        &audio.data,
+        audio.channels as usize,
+        &segments,
+        to_frames(splice, rate),
+    );
+    Ok(AudioBuffer::new(rate, audio.channels, data)?)
+}
+
+/// CLI compatibility layer for extend zigzag
+///
+/// Usage: `zigzag 2 infile outfile timesfile [splice]`, where the times
+/// file lists the turning points in seconds and the splice is in
+/// milliseconds as in CDP.
+pub fn zigzag_cli(mode: i32, args: &[&str]) -> Result<()> {
+    if mode != 2 {
+        return Err(ExtendError::UnsupportedOperation(format!(
+            "Zigzag mode {} not yet implemented",
+            mode
+        )));
+    }
+    if args.len() < 3 {
+        return Err(ExtendError::InvalidParameter(
+            "Usage: zigzag 2 infile outfile timesfile [splice]".into(),
+        ));
+    }
+    let times = read_times(Path::new(args[2]))?;
+    let splice = match args.get(3) {
+        Some(arg) => arg
+            .parse::<f64>()
+            .map_err(|_| ExtendError::InvalidParameter(format!("Invalid splice: {}", arg)))?,
+        None => DEFAULT_SPLICE_MS,
+    };
+    zigzag(
+        Path::new(args[0]),
+        Path::new(args[1]),
+        &times,
+        splice / 1000.0,
+    )
+}
+
+/// Read a whitespace-separated list of times
+fn read_times(path: &Path) -> Result<Vec<f64>> {
+    cdp_core::fs::read_to_string(path)?
+        .split_whitespace()
+        .map(|field| {
+            field
+                .parse::<f64>()
+                .map_err(|_| ExtendError::InvalidParameter(format!("Invalid time: {}", field)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zigzag_buf() {
+        let audio = AudioBuffer::new(10, 1, (0..10).map(|i| i as f32).collect()).unwrap();
+        let output = zigzag_buf(&audio, &[0.2, 0.5, 0.3, 0.6], 0.0).unwrap();
+        assert_eq!(output.data, vec![2.0, 3.0, 4.0, 4.0, 3.0, 3.0, 4.0, 5.0]);
+
+        assert!(zigzag_buf(&audio, &[0.2], 0.0).is_err());
+        assert!(zigzag_buf(&audio, &[0.2, 1.5], 0.0).is_err());
+    }
+}
@@ -0,0 +1,140 @@
+/* Generated by cbindgen from crates/cdp-ffi. Do not edit. */
+
+#ifndef CDP_FFI_H
+#define CDP_FFI_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Outcome of a call
+typedef enum CdpStatus {
+  // The call succeeded
+  CDP_STATUS_OK = 0,
+  // A required pointer was null
+  CDP_STATUS_NULL_POINTER = 1,
+  // The output buffer is too small; the needed length was written
+  CDP_STATUS_BUFFER_TOO_SMALL = 2,
+  // The operation rejected its input or settings
+  CDP_STATUS_ERROR = 3,
+  // The library panicked; the arguments were left unchanged or
+  // partially processed
+  CDP_STATUS_PANIC = 4,
+} CdpStatus;
+
+// Spectral frames of one channel, from `cdp_pvoc_anal`
+typedef struct CdpSpectrum CdpSpectrum;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Message describing the last failure on this thread, or an empty string
+//
+// The string stays valid until the next call on the same thread.
+const char *cdp_last_error(void);
+
+// Multiply `len` samples by `gain`, in place
+//
+// # Safety
+//
+// `samples` must point to `len` writable floats, or may be null if `len`
+// is 0.
+enum CdpStatus cdp_gain(float *samples, size_t len, float gain);
+
+// Scale `len` samples so their peak is `target_level` (at most 1.0), in
+// place
+//
+// The gain applied is written to `applied_gain` unless it is null. Silent
+// buffers are left unchanged.
+//
+// # Safety
+//
+// `samples` must point to `len` writable floats, or may be null if `len`
+// is 0. `applied_gain` must be null or point to a writable float.
+enum CdpStatus cdp_normalize(float *samples, size_t len, float target_level, float *applied_gain);
+
+// Phase vocoder analysis of `len` samples with a Hann window
+//
+// `fft_size` is a power of 2 from 2 to 32768 and `overlap` from 1 to 4,
+// CDP's defaults being 1024 and 3. On success `*spectrum` is a new
+// spectrum, to be released with `cdp_spectrum_free`.
+//
+// # Safety
+//
+// `samples` must point to `len` readable floats, and `spectrum` to a
+// writable pointer.
+enum CdpStatus cdp_pvoc_anal(const float *samples,
+                             size_t len,
+                             uint32_t sample_rate,
+                             uint32_t fft_size,
+                             uint32_t overlap,
+                             struct CdpSpectrum **spectrum);
+
+// Resynthesise `spectrum` into `output`, which holds `capacity` samples
+//
+// The number of samples produced is written to `written`, and is also
+// written with `CDP_STATUS_BUFFER_TOO_SMALL` when `output` cannot hold
+// them. `cdp_pvoc_synth_len` gives the length without synthesising.
+//
+// # Safety
+//
+// `spectrum` must come from `cdp_pvoc_anal`, `output` must point to
+// `capacity` writable floats and `written` to a writable `size_t`.
+enum CdpStatus cdp_pvoc_synth(const struct CdpSpectrum *spectrum,
+                              float *output,
+                              size_t capacity,
+                              size_t *written);
+
+// Samples `cdp_pvoc_synth` will produce from `spectrum`, or 0 if it is null
+//
+// # Safety
+//
+// `spectrum` must be null or come from `cdp_pvoc_anal`.
+size_t cdp_pvoc_synth_len(const struct CdpSpectrum *spectrum);
+
+// Analysis windows in `spectrum`, or 0 if it is null
+//
+// # Safety
+//
+// `spectrum` must be null or come from `cdp_pvoc_anal`.
+size_t cdp_spectrum_windows(const struct CdpSpectrum *spectrum);
+
+// Release a spectrum; null is ignored
+//
+// # Safety
+//
+// `spectrum` must be null or come from `cdp_pvoc_anal`, and not be used
+// afterwards.
+void cdp_spectrum_free(struct CdpSpectrum *spectrum);
+
+// Time-average `spectrum` over `windows` analysis windows, in place
+//
+// # Safety
+//
+// `spectrum` must come from `cdp_pvoc_anal`.
+enum CdpStatus cdp_blur(struct CdpSpectrum *spectrum, uint32_t windows);
+
+// Time-stretch `spectrum` by `factor`, in place
+//
+// # Safety
+//
+// `spectrum` must come from `cdp_pvoc_anal`.
+enum CdpStatus cdp_stretch(struct CdpSpectrum *spectrum, double factor);
+
+// Pitch-shift `spectrum` by the frequency ratio `factor`, in place
+//
+// With `preserve_formants` the spectral envelope stays where it was.
+//
+// # Safety
+//
+// `spectrum` must come from `cdp_pvoc_anal`.
+enum CdpStatus cdp_pitch(struct CdpSpectrum *spectrum, double factor, bool preserve_formants);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CDP_FFI_H */
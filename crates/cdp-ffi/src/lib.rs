@@ -0,0 +1,440 @@
+//! CDP FFI - C entry points for plugin hosts
+//!
+//! Exposes the core operations to C and C++, for Max/MSP externals, JUCE
+//! plugins and other hosts that own their audio buffers:
+//! - `cdp_gain` and `cdp_normalize` work on sample buffers in place
+//! - `cdp_pvoc_anal` analyses a buffer into an opaque `CdpSpectrum`
+//! - `cdp_blur`, `cdp_stretch` and `cdp_pitch` transform a spectrum in place
+//! - `cdp_pvoc_synth` resynthesises a spectrum into a caller's buffer
+//!
+//! Buffers hold one channel of float samples; hosts process each channel
+//! in turn. Every function returns a [`CdpStatus`], and on failure
+//! `cdp_last_error` describes what went wrong. Panics never cross the
+//! boundary. The C declarations are in `include/cdp.h`, generated from this
+//! file by cbindgen and checked by the tests.
+
+use cdp_core::{AnaFile, AudioBuffer};
+use cdp_pvoc::{PvocAnalParams, PvocSynthParams};
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::panic::{self, AssertUnwindSafe};
+use thiserror::Error;
+
+/// Outcome of a call
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CdpStatus {
+    /// The call succeeded
+    Ok = 0,
+    /// A required pointer was null
+    NullPointer = 1,
+    /// The output buffer is too small; the needed length was written
+    BufferTooSmall = 2,
+    /// The operation rejected its input or settings
+    Error = 3,
+    /// The library panicked; the arguments were left unchanged or
+    /// partially processed
+    Panic = 4,
+}
+
+/// Spectral frames of one channel, from `cdp_pvoc_anal`
+pub struct CdpSpectrum(AnaFile);
+
+#[derive(Error, Debug)]
+enum FfiError {
+    #[error("{0} is null")]
+    NullPointer(&'static str),
+
+    #[error("Output buffer holds {capacity} samples, {needed} needed")]
+    BufferTooSmall { capacity: usize, needed: usize },
+
+    #[error(transparent)]
+    Core(#[from] cdp_core::CoreError),
+
+    #[error(transparent)]
+    Modify(#[from] cdp_modify::ModifyError),
+
+    #[error(transparent)]
+    Pvoc(#[from] cdp_pvoc::PvocError),
+
+    #[error(transparent)]
+    Spectral(#[from] cdp_spectral::SpectralError),
+}
+
+impl FfiError {
+    fn status(&self) -> CdpStatus {
+        match self {
+            Self::NullPointer(_) => CdpStatus::NullPointer,
+            Self::BufferTooSmall { .. } => CdpStatus::BufferTooSmall,
+            _ => CdpStatus::Error,
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, FfiError>;
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn set_last_error(message: &str) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+}
+
+/// Run `call`, turning its error or panic into a status
+fn guard(call: impl FnOnce() -> Result<()>) -> CdpStatus {
+    match panic::catch_unwind(AssertUnwindSafe(call)) {
+        Ok(Ok(())) => {
+            set_last_error("");
+            CdpStatus::Ok
+        }
+        Ok(Err(e)) => {
+            set_last_error(&e.to_string());
+            e.status()
+        }
+        Err(_) => {
+            set_last_error("Internal error: the operation panicked");
+            CdpStatus::Panic
+        }
+    }
+}
+
+/// Borrow `len` samples at `samples`, which may be null when `len` is 0
+unsafe fn samples<'a>(samples: *const f32, len: usize) -> Result<&'a [f32]> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+    if samples.is_null() {
+        return Err(FfiError::NullPointer("samples"));
+    }
+    Ok(std::slice::from_raw_parts(samples, len))
+}
+
+/// Mutably borrow `len` samples at `samples`, which may be null when `len`
+/// is 0
+unsafe fn samples_mut<'a>(samples: *mut f32, len: usize) -> Result<&'a mut [f32]> {
+    if len == 0 {
+        return Ok(&mut []);
+    }
+    if samples.is_null() {
+        return Err(FfiError::NullPointer("samples"));
+    }
+    Ok(std::slice::from_raw_parts_mut(samples, len))
+}
+
+unsafe fn spectrum<'a>(spectrum: *mut CdpSpectrum) -> Result<&'a mut CdpSpectrum> {
+    spectrum.as_mut().ok_or(FfiError::NullPointer("spectrum"))
+}
+
+/// Samples `cdp_pvoc_synth` produces from `analysis`
+fn synth_len(analysis: &AnaFile) -> usize {
+    match analysis.num_frames() {
+        0 => 0,
+        frames => (frames - 1) * analysis.header.hop_size() as usize + analysis.header.fft_size(),
+    }
+}
+
+/// Message describing the last failure on this thread, or an empty string
+///
+/// The string stays valid until the next call on the same thread.
+#[no_mangle]
+pub extern "C" fn cdp_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ptr())
+}
+
+/// Multiply `len` samples by `gain`, in place
+///
+/// # Safety
+///
+/// `samples` must point to `len` writable floats, or may be null if `len`
+/// is 0.
+#[no_mangle]
+pub unsafe extern "C" fn cdp_gain(samples: *mut f32, len: usize, gain: f32) -> CdpStatus {
+    guard(|| {
+        cdp_modify::apply_gain_buf(samples_mut(samples, len)?, gain);
+        Ok(())
+    })
+}
+
+/// Scale `len` samples so their peak is `target_level` (at most 1.0), in
+/// place
+///
+/// The gain applied is written to `applied_gain` unless it is null. Silent
+/// buffers are left unchanged.
+///
+/// # Safety
+///
+/// `samples` must point to `len` writable floats, or may be null if `len`
+/// is 0. `applied_gain` must be null or point to a writable float.
+#[no_mangle]
+pub unsafe extern "C" fn cdp_normalize(
+    samples: *mut f32,
+    len: usize,
+    target_level: f32,
+    applied_gain: *mut f32,
+) -> CdpStatus {
+    guard(|| {
+        let gain = cdp_modify::normalize_buf(samples_mut(samples, len)?, Some(target_level))?;
+        if let Some(applied_gain) = applied_gain.as_mut() {
+            *applied_gain = gain;
+        }
+        Ok(())
+    })
+}
+
+/// Phase vocoder analysis of `len` samples with a Hann window
+///
+/// `fft_size` is a power of 2 from 2 to 32768 and `overlap` from 1 to 4,
+/// CDP's defaults being 1024 and 3. On success `*spectrum` is a new
+/// spectrum, to be released with `cdp_spectrum_free`.
+///
+/// # Safety
+///
+/// `samples` must point to `len` readable floats, and `spectrum` to a
+/// writable pointer.
+#[no_mangle]
+pub unsafe extern "C" fn cdp_pvoc_anal(
+    samples: *const f32,
+    len: usize,
+    sample_rate: u32,
+    fft_size: u32,
+    overlap: u32,
+    spectrum: *mut *mut CdpSpectrum,
+) -> CdpStatus {
+    guard(|| {
+        let spectrum = spectrum.as_mut().ok_or(FfiError::NullPointer("spectrum"))?;
+        let params = PvocAnalParams::builder()
+            .fft_size(fft_size)
+            .overlap(overlap)
+            .build()?;
+        let audio = AudioBuffer::new(sample_rate, 1, self::samples(samples, len)?.to_vec())?;
+        let analysis = cdp_pvoc::pvoc_anal_buf(&audio, &params, &mut ())?
+            .pop()
+            .expect("one analysis per channel");
+        *spectrum = Box::into_raw(Box::new(CdpSpectrum(analysis)));
+        Ok(())
+    })
+}
+
+/// Resynthesise `spectrum` into `output`, which holds `capacity` samples
+///
+/// The number of samples produced is written to `written`, and is also
+/// written with `CDP_STATUS_BUFFER_TOO_SMALL` when `output` cannot hold
+/// them. `cdp_pvoc_synth_len` gives the length without synthesising.
+///
+/// # Safety
+///
+/// `spectrum` must come from `cdp_pvoc_anal`, `output` must point to
+/// `capacity` writable floats and `written` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn cdp_pvoc_synth(
+    spectrum: *const CdpSpectrum,
+    output: *mut f32,
+    capacity: usize,
+    written: *mut usize,
+) -> CdpStatus {
+    guard(|| {
+        let written = written.as_mut().ok_or(FfiError::NullPointer("written"))?;
+        let analysis = &self::spectrum(spectrum.cast_mut())?.0;
+        let needed = synth_len(analysis);
+        *written = needed;
+        if capacity < needed {
+            return Err(FfiError::BufferTooSmall { capacity, needed });
+        }
+        let output = samples_mut(output, capacity)?;
+        let audio = cdp_pvoc::pvoc_synth_buf(
+            std::slice::from_ref(analysis),
+            &PvocSynthParams::default(),
+            &mut (),
+        )?;
+        output[..audio.data.len()].copy_from_slice(&audio.data);
+        *written = audio.data.len();
+        Ok(())
+    })
+}
+
+/// Samples `cdp_pvoc_synth` will produce from `spectrum`, or 0 if it is null
+///
+/// # Safety
+///
+/// `spectrum` must be null or come from `cdp_pvoc_anal`.
+#[no_mangle]
+pub unsafe extern "C" fn cdp_pvoc_synth_len(spectrum: *const CdpSpectrum) -> usize {
+    spectrum
+        .as_ref()
+        .map_or(0, |spectrum| synth_len(&spectrum.0))
+}
+
+/// Analysis windows in `spectrum`, or 0 if it is null
+///
+/// # Safety
+///
+/// `spectrum` must be null or come from `cdp_pvoc_anal`.
+#[no_mangle]
+pub unsafe extern "C" fn cdp_spectrum_windows(spectrum: *const CdpSpectrum) -> usize {
+    spectrum
+        .as_ref()
+        .map_or(0, |spectrum| spectrum.0.num_frames())
+}
+
+/// Release a spectrum; null is ignored
+///
+/// # Safety
+///
+/// `spectrum` must be null or come from `cdp_pvoc_anal`, and not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn cdp_spectrum_free(spectrum: *mut CdpSpectrum) {
+    if !spectrum.is_null() {
+        drop(Box::from_raw(spectrum));
+    }
+}
+
+/// Time-average `spectrum` over `windows` analysis windows, in place
+///
+/// # Safety
+///
+/// `spectrum` must come from `cdp_pvoc_anal`.
+#[no_mangle]
+pub unsafe extern "C" fn cdp_blur(spectrum: *mut CdpSpectrum, windows: u32) -> CdpStatus {
+    guard(|| {
+        let spectrum = self::spectrum(spectrum)?;
+        spectrum.0 = cdp_spectral::blur_frames(&spectrum.0, windows)?;
+        Ok(())
+    })
+}
+
+/// Time-stretch `spectrum` by `factor`, in place
+///
+/// # Safety
+///
+/// `spectrum` must come from `cdp_pvoc_anal`.
+#[no_mangle]
+pub unsafe extern "C" fn cdp_stretch(spectrum: *mut CdpSpectrum, factor: f64) -> CdpStatus {
+    guard(|| {
+        let spectrum = self::spectrum(spectrum)?;
+        spectrum.0 = cdp_spectral::stretch_time_frames(&spectrum.0, factor)?;
+        Ok(())
+    })
+}
+
+/// Pitch-shift `spectrum` by the frequency ratio `factor`, in place
+///
+/// With `preserve_formants` the spectral envelope stays where it was.
+///
+/// # Safety
+///
+/// `spectrum` must come from `cdp_pvoc_anal`.
+#[no_mangle]
+pub unsafe extern "C" fn cdp_pitch(
+    spectrum: *mut CdpSpectrum,
+    factor: f64,
+    preserve_formants: bool,
+) -> CdpStatus {
+    guard(|| {
+        let spectrum = self::spectrum(spectrum)?;
+        spectrum.0 =
+            cdp_spectral::pitch_shift_formant_frames(&spectrum.0, factor, preserve_formants)?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+    use std::ffi::CStr;
+    use std::ptr;
+
+    fn last_error() -> String {
+        unsafe { CStr::from_ptr(cdp_last_error()) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn test_gain_and_normalize() {
+        let mut samples = [0.0f32, 0.25, -0.5];
+        unsafe {
+            assert_eq!(cdp_gain(samples.as_mut_ptr(), 3, 2.0), CdpStatus::Ok);
+            assert_eq!(samples, [0.0, 0.5, -1.0]);
+
+            let mut gain = 0.0;
+            let status = cdp_normalize(samples.as_mut_ptr(), 3, 0.5, &mut gain);
+            assert_eq!(status, CdpStatus::Ok);
+            assert_eq!(samples, [0.0, 0.25, -0.5]);
+            assert_eq!(gain, 0.5);
+
+            let status = cdp_normalize(samples.as_mut_ptr(), 3, 2.0, ptr::null_mut());
+            assert_eq!(status, CdpStatus::Error);
+            assert!(last_error().contains("cannot exceed"));
+
+            assert_eq!(cdp_gain(ptr::null_mut(), 4, 2.0), CdpStatus::NullPointer);
+            assert_eq!(cdp_gain(ptr::null_mut(), 0, 2.0), CdpStatus::Ok);
+            assert_eq!(last_error(), "");
+        }
+    }
+
+    #[test]
+    fn test_spectral_round_trip() {
+        let input: Vec<f32> = (0..8192)
+            .map(|i| 0.5 * (2.0 * PI * 440.0 * i as f32 / 44100.0).sin())
+            .collect();
+        unsafe {
+            let mut spectrum = ptr::null_mut();
+            let status = cdp_pvoc_anal(input.as_ptr(), input.len(), 44100, 1024, 3, &mut spectrum);
+            assert_eq!(status, CdpStatus::Ok);
+            let windows = cdp_spectrum_windows(spectrum);
+            assert!(windows > 0);
+
+            assert_eq!(cdp_blur(spectrum, 3), CdpStatus::Ok);
+            assert_eq!(cdp_pitch(spectrum, 2.0, false), CdpStatus::Ok);
+
+            assert_eq!(cdp_stretch(spectrum, 2.0), CdpStatus::Ok);
+            assert_eq!(cdp_spectrum_windows(spectrum), 2 * windows);
+            assert_eq!(cdp_stretch(spectrum, -1.0), CdpStatus::Error);
+
+            let len = cdp_pvoc_synth_len(spectrum);
+            let mut written = 0;
+            let mut output = vec![0.0f32; len];
+            let status = cdp_pvoc_synth(spectrum, output.as_mut_ptr(), len - 1, &mut written);
+            assert_eq!(status, CdpStatus::BufferTooSmall);
+            assert_eq!(written, len);
+            let status = cdp_pvoc_synth(spectrum, output.as_mut_ptr(), len, &mut written);
+            assert_eq!(status, CdpStatus::Ok);
+            assert_eq!(written, len);
+            assert!(output.iter().any(|s| s.abs() > 0.1));
+
+            cdp_spectrum_free(spectrum);
+            cdp_spectrum_free(ptr::null_mut());
+
+            let status = cdp_pvoc_anal(input.as_ptr(), input.len(), 44100, 1000, 3, &mut spectrum);
+            assert_eq!(status, CdpStatus::Error);
+            assert_eq!(cdp_blur(ptr::null_mut(), 3), CdpStatus::NullPointer);
+        }
+    }
+
+    #[test]
+    fn test_header_is_current() {
+        let crate_dir = env!("CARGO_MANIFEST_DIR");
+        let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir)).unwrap();
+        let mut generated = Vec::new();
+        cbindgen::Builder::new()
+            .with_crate(crate_dir)
+            .with_config(config)
+            .generate()
+            .unwrap()
+            .write(&mut generated);
+
+        let path = format!("{}/include/cdp.h", crate_dir);
+        if std::env::var_os("CDP_FFI_UPDATE_HEADER").is_some() {
+            std::fs::write(&path, &generated).unwrap();
+        }
+        let committed = std::fs::read(&path).unwrap_or_default();
+        assert!(
+            committed == generated,
+            "include/cdp.h is out of date: rerun with CDP_FFI_UPDATE_HEADER=1"
+        );
+    }
+}
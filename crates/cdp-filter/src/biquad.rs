@@ -0,0 +1,329 @@
+//! Biquad filters
+//!
+//! Second-order lowpass, highpass, bandpass and notch filters with a
+//! resonance Q, using the Audio EQ Cookbook designs. The cutoff or centre
+//! frequency may follow a breakpoint file, like CDP's filter variable.
+
+use super::{FilterError, Result};
+use cdp_core::{AudioBuffer, Breakpoints, Interpolation};
+use cdp_housekeep::wav_cdp;
+use std::f64::consts::PI;
+use std::path::Path;
+
+/// Frames between coefficient updates when a filter is retuned over time
+pub(crate) const CONTROL_BLOCK: usize = 32;
+
+/// Response of a [`Biquad`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FilterType {
+    /// Passes frequencies below the cutoff
+    Lowpass,
+    /// Passes frequencies above the cutoff
+    Highpass,
+    /// Passes a band around the centre, with unity gain at the centre
+    Bandpass,
+    /// Removes a band around the centre
+    Notch,
+}
+
+/// One second-order filter section with its own state
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    /// Design a filter at `frequency` Hz
+    ///
+    /// `q` sets the resonance, or for bandpass and notch filters the
+    /// centre frequency over the bandwidth; 0.707 gives the flattest
+    /// lowpass and highpass response.
+    pub fn new(kind: FilterType, frequency: f64, q: f64, sample_rate: u32) -> Result<Self> {
+        check_frequency(frequency, sample_rate)?;
+        check_q(q)?;
+        let mut filter = Self::from_coefficients([1.0, 0.0, 0.0], [1.0, 0.0, 0.0]);
+        filter.retune(kind, frequency, q, sample_rate);
+        Ok(filter)
+    }
+
+    /// Design a peaking filter boosting or cutting by `gain_db` around
+    /// `frequency` Hz, with `q` setting the width of the bell
+    pub fn peaking(frequency: f64, q: f64, gain_db: f64, sample_rate: u32) -> Result<Self> {
+        check_frequency(frequency, sample_rate)?;
+        check_q(q)?;
+        let a = 10.0f64.powf(gain_db / 40.0);
+        let (sin, cos) = (2.0 * PI * frequency / sample_rate as f64).sin_cos();
+        let alpha = sin / (2.0 * q);
+        Ok(Self::from_coefficients(
+            [1.0 + alpha * a, -2.0 * cos, 1.0 - alpha * a],
+            [1.0 + alpha / a, -2.0 * cos, 1.0 - alpha / a],
+        ))
+    }
+
+    /// Design a shelf boosting or cutting by `gain_db` below `frequency`
+    /// Hz, where the gain is halfway in dB
+    pub fn low_shelf(frequency: f64, gain_db: f64, sample_rate: u32) -> Result<Self> {
+        Self::shelf(frequency, gain_db, sample_rate, -1.0)
+    }
+
+    /// Design a shelf boosting or cutting by `gain_db` above `frequency`
+    /// Hz, where the gain is halfway in dB
+    pub fn high_shelf(frequency: f64, gain_db: f64, sample_rate: u32) -> Result<Self> {
+        Self::shelf(frequency, gain_db, sample_rate, 1.0)
+    }
+
+    /// Cookbook shelf with the steepest slope free of overshoot; `side` is
+    /// -1 for a low shelf and 1 for a high shelf
+    fn shelf(frequency: f64, gain_db: f64, sample_rate: u32, side: f64) -> Result<Self> {
+        check_frequency(frequency, sample_rate)?;
+        let a = 10.0f64.powf(gain_db / 40.0);
+        let (sin, cos) = (2.0 * PI * frequency / sample_rate as f64).sin_cos();
+        let root = 2.0 * a.sqrt() * sin / std::f64::consts::SQRT_2;
+        let cos = side * cos;
+        Ok(Self::from_coefficients(
+            [
+                a * ((a + 1.0) + (a - 1.0) * cos + root),
+                -2.0 * side * a * ((a - 1.0) + (a + 1.0) * cos),
+                a * ((a + 1.0) + (a - 1.0) * cos - root),
+            ],
+            [
+                (a + 1.0) - (a - 1.0) * cos + root,
+                2.0 * side * ((a - 1.0) - (a + 1.0) * cos),
+                (a + 1.0) - (a - 1.0) * cos - root,
+            ],
+        ))
+    }
+
+    fn from_coefficients(b: [f64; 3], a: [f64; 3]) -> Self {
+        let mut filter = Self {
+            b: [0.0; 3],
+            a: [0.0; 2],
+            x: [0.0; 2],
+            y: [0.0; 2],
+        };
+        filter.set_coefficients(b, a);
+        filter
+    }
+
+    /// Change the design while keeping the filter's state
+    pub(crate) fn retune(&mut self, kind: FilterType, frequency: f64, q: f64, sample_rate: u32) {
+        let w0 = 2.0 * PI * frequency / sample_rate as f64;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * q);
+        let b = match kind {
+            FilterType::Lowpass => [(1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0],
+            FilterType::Highpass => [(1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0],
+            FilterType::Bandpass => [alpha, 0.0, -alpha],
+            FilterType::Notch => [1.0, -2.0 * cos, 1.0],
+        };
+        self.set_coefficients(b, [1.0 + alpha, -2.0 * cos, 1.0 - alpha]);
+    }
+
+    /// Set coefficients, normalising by `a[0]`
+    fn set_coefficients(&mut self, b: [f64; 3], a: [f64; 3]) {
+        self.b = [b[0] / a[0], b[1] / a[0], b[2] / a[0]];
+        self.a = [a[1] / a[0], a[2] / a[0]];
+    }
+
+    /// Filter one sample
+    pub fn process(&mut self, input: f64) -> f64 {
+        let output = self.b[0] * input + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0]
+            - self.a[1] * self.y[1];
+        self.x = [input, self.x[0]];
+        self.y = [output, self.y[0]];
+        output
+    }
+
+    /// Clear the filter's memory of past samples
+    pub fn reset(&mut self) {
+        self.x = [0.0; 2];
+        self.y = [0.0; 2];
+    }
+}
+
+/// Settings for [`filter`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FilterOptions {
+    /// Filter response
+    pub kind: FilterType,
+    /// Cutoff or centre frequency in Hz over time
+    pub frequency: Breakpoints,
+    /// Resonance
+    pub q: f64,
+    /// Linear gain applied to the output
+    pub gain: f64,
+}
+
+/// Filter a sound file
+pub fn filter(input: &Path, output: &Path, options: &FilterOptions) -> Result<()> {
+    let (audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    wav_cdp::write_audio_buffer(output, &filter_buf(&audio, options)?, sample_type)?;
+    Ok(())
+}
+
+/// Filter an in-memory sound
+///
+/// Buffer-based counterpart of [`filter`]. Each channel is filtered
+/// separately. A moving frequency is interpolated in pitch and the filter
+/// retuned every few milliseconds.
+pub fn filter_buf(audio: &AudioBuffer, options: &FilterOptions) -> Result<AudioBuffer> {
+    let nyquist = audio.sample_rate as f64 / 2.0;
+    options.frequency.check_range("Frequency", 1.0, nyquist)?;
+    check_q(options.q)?;
+    check_gain(options.gain)?;
+
+    let channels = audio.channels as usize;
+    let rate = audio.sample_rate as f64;
+    let frequency_at = |frame: usize| {
+        options
+            .frequency
+            .value_at_with(frame as f64 / rate, Interpolation::Log)
+    };
+    let mut filters =
+        vec![Biquad::new(options.kind, frequency_at(0), options.q, audio.sample_rate)?; channels];
+    let moving = options.frequency.points().len() > 1;
+
+    let mut data = Vec::with_capacity(audio.data.len());
+    for (index, frame) in audio.frames().enumerate() {
+        if moving && index % CONTROL_BLOCK == 0 {
+            let frequency = frequency_at(index);
+            for filter in &mut filters {
+                filter.retune(options.kind, frequency, options.q, audio.sample_rate);
+            }
+        }
+        for (&sample, filter) in frame.iter().zip(&mut filters) {
+            data.push((filter.process(sample as f64) * options.gain) as f32);
+        }
+    }
+    Ok(AudioBuffer::new(audio.sample_rate, audio.channels, data)?)
+}
+
+pub(crate) fn check_frequency(frequency: f64, sample_rate: u32) -> Result<()> {
+    if !(frequency > 0.0 && frequency <= sample_rate as f64 / 2.0) {
+        return Err(FilterError::InvalidParameter(format!(
+            "Frequency {} must be between 0 and the Nyquist frequency",
+            frequency
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) fn check_q(q: f64) -> Result<()> {
+    if !(q > 0.0 && q <= 10000.0) {
+        return Err(FilterError::InvalidParameter(
+            "Q must be greater than 0 and at most 10000".into(),
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) fn check_gain(gain: f64) -> Result<()> {
+    if !(gain >= 0.0 && gain.is_finite()) {
+        return Err(FilterError::InvalidParameter(
+            "Gain cannot be negative".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// CLI compatibility layer for filter variable
+///
+/// Usage: `variable mode infile outfile q gain frq`, with mode 1 highpass,
+/// 2 lowpass, 3 bandpass or 4 notch. The frequency may be a breakpoint
+/// file.
+pub fn variable_cli(mode: i32, args: &[&str]) -> Result<()> {
+    let kind = match mode {
+        1 => FilterType::Highpass,
+        2 => FilterType::Lowpass,
+        3 => FilterType::Bandpass,
+        4 => FilterType::Notch,
+        _ => {
+            return Err(FilterError::UnsupportedOperation(format!(
+                "Variable filter mode {} not yet implemented",
+                mode
+            )))
+        }
+    };
+    if args.len() != 5 {
+        return Err(FilterError::InvalidParameter(
+            "Usage: variable mode infile outfile q gain frq".into(),
+        ));
+    }
+    let number = |arg: &str| {
+        arg.parse::<f64>()
+            .map_err(|_| FilterError::InvalidParameter(format!("Invalid value: {}", arg)))
+    };
+    let frequency = match args[4].parse::<f64>() {
+        Ok(value) => Breakpoints::constant(value),
+        Err(_) => Breakpoints::load(Path::new(args[4]))?,
+    };
+    let options = FilterOptions {
+        kind,
+        frequency,
+        q: number(args[2])?,
+        gain: number(args[3])?,
+    };
+    filter(Path::new(args[0]), Path::new(args[1]), &options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Peak level over the second half, once the filter has settled
+    fn settled_peak(audio: &AudioBuffer) -> f32 {
+        audio.data[audio.data.len() / 2..]
+            .iter()
+            .fold(0.0, |peak, s| peak.max(s.abs()))
+    }
+
+    fn options(kind: FilterType, frequency: f64) -> FilterOptions {
+        FilterOptions {
+            kind,
+            frequency: Breakpoints::constant(frequency),
+            q: 0.707,
+            gain: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_biquad_responses() {
+        let low = cdp_synth::sine(100.0, 0.5, 8000, 1.0).unwrap();
+        let high = cdp_synth::sine(2000.0, 0.5, 8000, 1.0).unwrap();
+        let level = |audio: &AudioBuffer, kind, frequency| {
+            settled_peak(&filter_buf(audio, &options(kind, frequency)).unwrap())
+        };
+
+        assert!(level(&low, FilterType::Lowpass, 500.0) > 0.95);
+        assert!(level(&high, FilterType::Lowpass, 500.0) < 0.1);
+        assert!(level(&low, FilterType::Highpass, 500.0) < 0.1);
+        assert!(level(&high, FilterType::Highpass, 500.0) > 0.95);
+        assert!((level(&high, FilterType::Bandpass, 2000.0) - 1.0).abs() < 0.01);
+        assert!(level(&low, FilterType::Bandpass, 2000.0) < 0.1);
+        assert!(level(&high, FilterType::Notch, 2000.0) < 0.01);
+        assert!(level(&low, FilterType::Notch, 2000.0) > 0.95);
+
+        assert!(Biquad::new(FilterType::Lowpass, 5000.0, 0.7, 8000).is_err());
+        assert!(Biquad::new(FilterType::Lowpass, 500.0, 0.0, 8000).is_err());
+    }
+
+    #[test]
+    fn test_moving_frequency() {
+        // A lowpass sweeping down past the tone silences it by the end
+        let tone = cdp_synth::sine(1000.0, 1.0, 8000, 1.0).unwrap();
+        let sweep = FilterOptions {
+            frequency: Breakpoints::new(vec![(0.0, 3000.0), (0.5, 100.0)]).unwrap(),
+            ..options(FilterType::Lowpass, 0.0)
+        };
+        let output = filter_buf(&tone, &sweep).unwrap();
+        assert!(output.data[..400].iter().any(|s| s.abs() > 0.9));
+        assert!(settled_peak(&output) < 0.02);
+    }
+}
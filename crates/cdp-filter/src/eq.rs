@@ -0,0 +1,226 @@
+//! Corrective equalisation
+//!
+//! A graphic equaliser with one gain per octave band, and a spectral tilt
+//! that raises or lowers the spectrum by a fixed number of dB per octave
+//! either side of a pivot frequency.
+
+use super::{FilterError, Result};
+use crate::biquad::Biquad;
+use cdp_core::AudioBuffer;
+use cdp_housekeep::wav_cdp;
+use std::path::Path;
+
+/// Centre frequencies of the graphic equaliser's octave bands
+pub const OCTAVE_BANDS: [f64; 10] = [
+    31.25, 62.5, 125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0, 16000.0,
+];
+
+/// Q giving each band a bandwidth of one octave
+const BAND_Q: f64 = std::f64::consts::SQRT_2;
+
+/// Lowest frequency down to which a tilt keeps its slope
+const TILT_FLOOR: f64 = 20.0;
+
+/// Largest boost or cut of a single band, in dB
+const MAX_BAND_DB: f64 = 24.0;
+
+/// Steepest tilt, in dB per octave
+const MAX_TILT_DB: f64 = 12.0;
+
+/// Equalise a sound file
+pub fn graphic_eq(input: &Path, output: &Path, gains_db: &[f64]) -> Result<()> {
+    let (audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    wav_cdp::write_audio_buffer(output, &graphic_eq_buf(&audio, gains_db)?, sample_type)?;
+    Ok(())
+}
+
+/// Equalise an in-memory sound
+///
+/// Buffer-based counterpart of [`graphic_eq`]. `gains_db` gives the boost
+/// or cut of each band in [`OCTAVE_BANDS`] from the lowest up; bands left
+/// out, and bands above the Nyquist frequency, are flat. Each band is a
+/// peaking filter an octave wide, so raising neighbouring bands together
+/// adds a little where they overlap, as on an analogue graphic equaliser.
+pub fn graphic_eq_buf(audio: &AudioBuffer, gains_db: &[f64]) -> Result<AudioBuffer> {
+    if gains_db.len() > OCTAVE_BANDS.len() {
+        return Err(FilterError::InvalidParameter(format!(
+            "At most {} band gains can be given",
+            OCTAVE_BANDS.len()
+        )));
+    }
+    if gains_db
+        .iter()
+        .any(|gain| !(-MAX_BAND_DB..=MAX_BAND_DB).contains(gain))
+    {
+        return Err(FilterError::InvalidParameter(format!(
+            "Band gains must be between -{0} and {0} dB",
+            MAX_BAND_DB
+        )));
+    }
+    let nyquist = audio.sample_rate as f64 / 2.0;
+    let sections = OCTAVE_BANDS
+        .iter()
+        .zip(gains_db)
+        .filter(|&(&centre, &gain)| gain != 0.0 && centre < nyquist)
+        .map(|(&centre, &gain)| Biquad::peaking(centre, BAND_Q, gain, audio.sample_rate))
+        .collect::<Result<Vec<_>>>()?;
+    cascade(audio, &sections)
+}
+
+/// Tilt the spectrum of a sound file
+pub fn tilt(input: &Path, output: &Path, slope_db: f64, pivot: f64) -> Result<()> {
+    let (audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    wav_cdp::write_audio_buffer(output, &tilt_buf(&audio, slope_db, pivot)?, sample_type)?;
+    Ok(())
+}
+
+/// Tilt the spectrum of an in-memory sound
+///
+/// Buffer-based counterpart of [`tilt`]. Frequencies `n` octaves above
+/// `pivot` change by `n * slope_db` dB and those below by the opposite,
+/// so a positive slope brightens the sound and a negative one darkens it.
+/// The slope is built from a shelf at every octave, each adding one step
+/// of `slope_db`, and holds from 20 Hz to near the Nyquist frequency.
+pub fn tilt_buf(audio: &AudioBuffer, slope_db: f64, pivot: f64) -> Result<AudioBuffer> {
+    if !(-MAX_TILT_DB..=MAX_TILT_DB).contains(&slope_db) {
+        return Err(FilterError::InvalidParameter(format!(
+            "Slope must be between -{0} and {0} dB per octave",
+            MAX_TILT_DB
+        )));
+    }
+    let nyquist = audio.sample_rate as f64 / 2.0;
+    if !(pivot >= TILT_FLOOR && pivot < nyquist) {
+        return Err(FilterError::InvalidParameter(format!(
+            "Pivot must be between {} Hz and the Nyquist frequency",
+            TILT_FLOOR
+        )));
+    }
+
+    // Shelves sit half an octave either side of each whole octave from the
+    // pivot, so the steps blend into a straight line through them
+    let mut sections = Vec::new();
+    if slope_db != 0.0 {
+        let corners = |direction: f64| {
+            (0..)
+                .map(move |k| pivot * 2.0f64.powf(direction * (k as f64 + 0.5)))
+                .take_while(|&corner| corner >= TILT_FLOOR && corner < nyquist)
+        };
+        for corner in corners(1.0) {
+            sections.push(Biquad::high_shelf(corner, slope_db, audio.sample_rate)?);
+        }
+        for corner in corners(-1.0) {
+            sections.push(Biquad::low_shelf(corner, -slope_db, audio.sample_rate)?);
+        }
+    }
+    cascade(audio, &sections)
+}
+
+/// Run every channel through the same chain of sections
+fn cascade(audio: &AudioBuffer, sections: &[Biquad]) -> Result<AudioBuffer> {
+    let mut chains = vec![sections.to_vec(); audio.channels as usize];
+    let mut data = Vec::with_capacity(audio.data.len());
+    for frame in audio.frames() {
+        for (&sample, chain) in frame.iter().zip(&mut chains) {
+            let filtered = chain
+                .iter_mut()
+                .fold(sample as f64, |signal, section| section.process(signal));
+            data.push(filtered as f32);
+        }
+    }
+    Ok(AudioBuffer::new(audio.sample_rate, audio.channels, data)?)
+}
+
+/// CLI compatibility layer for the graphic equaliser
+///
+/// Usage: `eq 1 infile outfile gain [gain...]`, with gains in dB for the
+/// octave bands from 31.25 Hz up.
+pub fn eq_cli(mode: i32, args: &[&str]) -> Result<()> {
+    check_mode("Graphic EQ", mode)?;
+    if args.len() < 3 {
+        return Err(FilterError::InvalidParameter(
+            "Usage: eq 1 infile outfile gain [gain...]".into(),
+        ));
+    }
+    let gains = args[2..]
+        .iter()
+        .map(|arg| number(arg))
+        .collect::<Result<Vec<f64>>>()?;
+    graphic_eq(Path::new(args[0]), Path::new(args[1]), &gains)
+}
+
+/// CLI compatibility layer for spectral tilt
+///
+/// Usage: `tilt 1 infile outfile slope pivot`, with the slope in dB per
+/// octave and the pivot in Hz.
+pub fn tilt_cli(mode: i32, args: &[&str]) -> Result<()> {
+    check_mode("Tilt", mode)?;
+    if args.len() != 4 {
+        return Err(FilterError::InvalidParameter(
+            "Usage: tilt 1 infile outfile slope pivot".into(),
+        ));
+    }
+    tilt(
+        Path::new(args[0]),
+        Path::new(args[1]),
+        number(args[2])?,
+        number(args[3])?,
+    )
+}
+
+fn check_mode(name: &str, mode: i32) -> Result<()> {
+    if mode != 1 {
+        return Err(FilterError::UnsupportedOperation(format!(
+            "{} mode {} not yet implemented",
+            name, mode
+        )));
+    }
+    Ok(())
+}
+
+fn number(arg: &str) -> Result<f64> {
+    arg.parse::<f64>()
+        .map_err(|_| FilterError::InvalidParameter(format!("Invalid value: {}", arg)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Settled gain in dB of a sine at `frequency` through `process`
+    fn gain_db(frequency: f64, process: impl Fn(&AudioBuffer) -> AudioBuffer) -> f64 {
+        let tone = cdp_synth::sine(frequency, 1.0, 44100, 0.5).unwrap();
+        let output = process(&tone);
+        let peak = output.data[22050..]
+            .iter()
+            .fold(0.0f32, |peak, s| peak.max(s.abs()));
+        20.0 * (peak as f64 / 0.5).log10()
+    }
+
+    #[test]
+    fn test_graphic_eq() {
+        let gains = [0.0, 0.0, 0.0, 0.0, 0.0, 6.0, 0.0, 0.0, -12.0];
+        let eq = |audio: &AudioBuffer| graphic_eq_buf(audio, &gains).unwrap();
+        // Each band sets the level at its centre, give or take its neighbours' skirts
+        assert!((gain_db(1000.0, eq) - 6.0).abs() < 0.25);
+        assert!((gain_db(8000.0, eq) + 12.0).abs() < 0.25);
+        assert!(gain_db(100.0, eq).abs() < 0.2);
+
+        assert!(graphic_eq_buf(&cdp_synth::silence(0.1, 44100).unwrap(), &[30.0]).is_err());
+        assert!(graphic_eq_buf(&cdp_synth::silence(0.1, 44100).unwrap(), &[0.0; 11]).is_err());
+    }
+
+    #[test]
+    fn test_tilt() {
+        let tilted = |audio: &AudioBuffer| tilt_buf(audio, 3.0, 1000.0).unwrap();
+        assert!(gain_db(1000.0, tilted).abs() < 0.5);
+        assert!((gain_db(4000.0, tilted) - 6.0).abs() < 0.5);
+        assert!((gain_db(250.0, tilted) + 6.0).abs() < 0.5);
+        assert!((gain_db(62.5, tilted) + 12.0).abs() < 0.5);
+
+        let flat = |audio: &AudioBuffer| tilt_buf(audio, 0.0, 1000.0).unwrap();
+        assert!(gain_db(300.0, flat).abs() < 0.01);
+        let silence = cdp_synth::silence(0.1, 44100).unwrap();
+        assert!(tilt_buf(&silence, 3.0, 30000.0).is_err());
+        assert!(tilt_buf(&silence, 20.0, 1000.0).is_err());
+    }
+}
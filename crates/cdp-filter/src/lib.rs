@@ -0,0 +1,64 @@
+//! CDP Filter module - Fixed and time-varying filters
+//!
+//! This module implements CDP's filter operations including:
+//! - Biquad lowpass, highpass, bandpass and notch filters with a Q, whose
+//!   frequency may follow a breakpoint file
+//! - Varibank filter banks: bandpass bands whose frequencies and levels
+//!   glide through time, read from a data file
+//! - IIR resonator banks
+//! - A graphic equaliser with a gain per octave band
+//! - Spectral tilt: a slope in dB per octave about a pivot frequency
+
+use thiserror::Error;
+
+pub mod biquad;
+pub mod eq;
+pub mod resonator;
+pub mod varibank;
+
+/// Result type for filter operations
+pub type Result<T> = std::result::Result<T, FilterError>;
+
+/// Errors that can occur during filter operations
+#[derive(Error, Debug)]
+pub enum FilterError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Housekeep error: {0}")]
+    Housekeep(#[from] cdp_housekeep::HousekeepError),
+
+    #[error("Core error: {0}")]
+    Core(#[from] cdp_core::CoreError),
+
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
+
+    #[error("Invalid filter data: {0}")]
+    InvalidFilterData(String),
+
+    #[error("Unsupported operation: {0}")]
+    UnsupportedOperation(String),
+}
+
+// Re-export main functions for convenience
+pub use biquad::{filter, filter_buf, Biquad, FilterOptions, FilterType};
+pub use eq::{graphic_eq, graphic_eq_buf, tilt, tilt_buf, OCTAVE_BANDS};
+pub use resonator::{resonator_bank, resonator_bank_buf, Resonator};
+pub use varibank::{varibank, varibank_buf, BankUnits, VaribankData};
+
+/// CLI compatibility layer - matches CDP's command-line interface
+/// This is just for oracle testing. Real users should use the library functions directly.
+pub fn filter_cli(operation: &str, mode: i32, args: &[&str]) -> Result<()> {
+    match operation {
+        "variable" => biquad::variable_cli(mode, args),
+        "varibank" => varibank::varibank_cli(mode, args),
+        "userbank" => resonator::userbank_cli(mode, args),
+        "eq" => eq::eq_cli(mode, args),
+        "tilt" => eq::tilt_cli(mode, args),
+        _ => Err(FilterError::UnsupportedOperation(format!(
+            "Unknown operation: {}",
+            operation
+        ))),
+    }
+}
@@ -0,0 +1,208 @@
+//! IIR resonator banks
+//!
+//! Each resonator is a two-pole filter that rings at its frequency, with
+//! a bandwidth setting how long it rings. A bank of them sums the rings,
+//! like CDP's filter userbank.
+
+use super::{FilterError, Result};
+use crate::biquad::{check_frequency, check_gain};
+use crate::varibank::BankUnits;
+use cdp_core::AudioBuffer;
+use cdp_housekeep::wav_cdp;
+use std::f64::consts::PI;
+use std::path::Path;
+
+/// One resonance in a bank
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Resonator {
+    /// Centre frequency in Hz
+    pub frequency: f64,
+    /// Width of the resonance in Hz; a narrow band rings longer
+    pub bandwidth: f64,
+    /// Level of the resonance
+    pub amplitude: f64,
+}
+
+/// Filter state for one resonator on one channel
+#[derive(Debug, Clone, Copy)]
+struct Poles {
+    gain: f64,
+    a1: f64,
+    a2: f64,
+    y: [f64; 2],
+}
+
+impl Poles {
+    /// Poles for `resonator`, normalised for unity gain at its centre
+    fn new(resonator: &Resonator, sample_rate: u32) -> Self {
+        let w = 2.0 * PI * resonator.frequency / sample_rate as f64;
+        let r = (-PI * resonator.bandwidth / sample_rate as f64).exp();
+        let gain = (1.0 - r) * (1.0 - 2.0 * r * (2.0 * w).cos() + r * r).sqrt();
+        Self {
+            gain: gain * resonator.amplitude,
+            a1: 2.0 * r * w.cos(),
+            a2: -r * r,
+            y: [0.0; 2],
+        }
+    }
+
+    fn process(&mut self, input: f64) -> f64 {
+        let output = self.gain * input + self.a1 * self.y[0] + self.a2 * self.y[1];
+        self.y = [output, self.y[0]];
+        output
+    }
+}
+
+/// Filter a sound file through a resonator bank
+pub fn resonator_bank(
+    input: &Path,
+    output: &Path,
+    resonators: &[Resonator],
+    gain: f64,
+) -> Result<()> {
+    let (audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    let filtered = resonator_bank_buf(&audio, resonators, gain)?;
+    wav_cdp::write_audio_buffer(output, &filtered, sample_type)?;
+    Ok(())
+}
+
+/// Filter an in-memory sound through a resonator bank
+///
+/// Buffer-based counterpart of [`resonator_bank`]. Each resonator passes
+/// its centre frequency at its amplitude, and the sum is scaled by `gain`.
+/// The output runs on only as long as the input.
+pub fn resonator_bank_buf(
+    audio: &AudioBuffer,
+    resonators: &[Resonator],
+    gain: f64,
+) -> Result<AudioBuffer> {
+    if resonators.is_empty() {
+        return Err(FilterError::InvalidParameter(
+            "Resonator bank needs at least one resonator".into(),
+        ));
+    }
+    for resonator in resonators {
+        check_frequency(resonator.frequency, audio.sample_rate)?;
+        if !(resonator.bandwidth > 0.0 && resonator.amplitude.is_finite()) {
+            return Err(FilterError::InvalidParameter(
+                "Resonators need a positive bandwidth and a finite amplitude".into(),
+            ));
+        }
+    }
+    check_gain(gain)?;
+
+    let bank: Vec<Poles> = resonators
+        .iter()
+        .map(|resonator| Poles::new(resonator, audio.sample_rate))
+        .collect();
+    let mut banks = vec![bank; audio.channels as usize];
+    let mut data = Vec::with_capacity(audio.data.len());
+    for frame in audio.frames() {
+        for (&sample, bank) in frame.iter().zip(&mut banks) {
+            let sum: f64 = bank
+                .iter_mut()
+                .map(|poles| poles.process(sample as f64))
+                .sum();
+            data.push((sum * gain) as f32);
+        }
+    }
+    Ok(AudioBuffer::new(audio.sample_rate, audio.channels, data)?)
+}
+
+/// CLI compatibility layer for filter userbank
+///
+/// Usage: `userbank mode infile outfile datafile q gain`, with mode 1 for
+/// frequencies in Hz and 2 for MIDI note numbers. The data file lists
+/// `freq amp` pairs; each resonator's bandwidth is its frequency over Q.
+pub fn userbank_cli(mode: i32, args: &[&str]) -> Result<()> {
+    let units = match mode {
+        1 => BankUnits::Hz,
+        2 => BankUnits::Midi,
+        _ => {
+            return Err(FilterError::UnsupportedOperation(format!(
+                "Userbank mode {} not yet implemented",
+                mode
+            )))
+        }
+    };
+    if args.len() != 5 {
+        return Err(FilterError::InvalidParameter(
+            "Usage: userbank mode infile outfile datafile q gain".into(),
+        ));
+    }
+    let number = |arg: &str| {
+        arg.parse::<f64>()
+            .map_err(|_| FilterError::InvalidParameter(format!("Invalid value: {}", arg)))
+    };
+    let q = number(args[3])?;
+    let values = cdp_core::fs::read_to_string(args[2])?
+        .split_whitespace()
+        .map(number)
+        .collect::<Result<Vec<f64>>>()?;
+    if values.is_empty() || values.len() % 2 != 0 {
+        return Err(FilterError::InvalidFilterData(
+            "Userbank data must be frequency and amplitude pairs".into(),
+        ));
+    }
+    let resonators: Vec<Resonator> = values
+        .chunks_exact(2)
+        .map(|pair| {
+            let frequency = units.to_hz(pair[0]);
+            Resonator {
+                frequency,
+                bandwidth: frequency / q,
+                amplitude: pair[1],
+            }
+        })
+        .collect();
+    resonator_bank(
+        Path::new(args[0]),
+        Path::new(args[1]),
+        &resonators,
+        number(args[4])?,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resonator_bank() {
+        let resonators = [
+            Resonator {
+                frequency: 500.0,
+                bandwidth: 20.0,
+                amplitude: 1.0,
+            },
+            Resonator {
+                frequency: 1500.0,
+                bandwidth: 20.0,
+                amplitude: 0.5,
+            },
+        ];
+
+        // Tones at the centres come through at each resonator's amplitude
+        for (frequency, level) in [(500.0, 1.0), (1500.0, 0.5)] {
+            let tone = cdp_synth::sine(frequency, 1.0, 8000, 1.0).unwrap();
+            let output = resonator_bank_buf(&tone, &resonators, 1.0).unwrap();
+            let settled = output.data[4000..]
+                .iter()
+                .fold(0.0f32, |peak, s| peak.max(s.abs()));
+            assert!((settled - level).abs() < 0.02);
+        }
+
+        // A click rings on at the resonant frequency
+        let mut click = vec![0.0f32; 800];
+        click[0] = 1.0;
+        let click = AudioBuffer::new(8000, 1, click).unwrap();
+        let output = resonator_bank_buf(&click, &resonators[..1], 1.0).unwrap();
+        let crossings = output.data[400..]
+            .windows(2)
+            .filter(|pair| pair[0] < 0.0 && pair[1] >= 0.0)
+            .count();
+        assert!((24..=26).contains(&crossings));
+
+        assert!(resonator_bank_buf(&click, &[], 1.0).is_err());
+    }
+}
@@ -0,0 +1,272 @@
+//! Time-varying filter banks
+//!
+//! A varibank data file has one line per time: `time freq amp freq amp
+//! ...`, giving the centre frequency and level of every band at that
+//! time. Each band is a bandpass filter, and the bands glide between the
+//! lines, like CDP's filter varibank.
+
+use super::{FilterError, Result};
+use crate::biquad::{check_gain, check_q, Biquad, FilterType, CONTROL_BLOCK};
+use cdp_core::AudioBuffer;
+use cdp_housekeep::wav_cdp;
+use std::path::Path;
+
+/// How band frequencies are written in a data file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BankUnits {
+    /// Frequencies in Hz
+    Hz,
+    /// MIDI note numbers
+    Midi,
+}
+
+impl BankUnits {
+    /// Convert a value in these units to Hz
+    pub fn to_hz(self, value: f64) -> f64 {
+        match self {
+            BankUnits::Hz => value,
+            BankUnits::Midi => 440.0 * 2.0f64.powf((value - 69.0) / 12.0),
+        }
+    }
+}
+
+/// Band frequencies and levels over time
+#[derive(Debug, Clone, PartialEq)]
+pub struct VaribankData {
+    lines: Vec<(f64, Vec<(f64, f64)>)>,
+}
+
+impl VaribankData {
+    /// Create from `(time, bands)` lines, each band a frequency in Hz and
+    /// a level
+    ///
+    /// Times must start at zero and increase, and every line must list the
+    /// same number of bands.
+    pub fn new(lines: Vec<(f64, Vec<(f64, f64)>)>) -> Result<Self> {
+        let invalid = |message: &str| Err(FilterError::InvalidFilterData(message.into()));
+        let Some((first_time, first_bands)) = lines.first() else {
+            return invalid("no bands given");
+        };
+        if *first_time != 0.0 {
+            return invalid("first time must be zero");
+        }
+        if first_bands.is_empty() {
+            return invalid("each line needs at least one band");
+        }
+        if lines.windows(2).any(|pair| pair[1].0 <= pair[0].0) {
+            return invalid("times must increase");
+        }
+        if lines
+            .iter()
+            .any(|(_, bands)| bands.len() != first_bands.len())
+        {
+            return invalid("every line must have the same number of bands");
+        }
+        if lines
+            .iter()
+            .flat_map(|(_, bands)| bands)
+            .any(|&(frequency, level)| {
+                !(frequency > 0.0 && frequency.is_finite() && level.is_finite())
+            })
+        {
+            return invalid("frequencies must be positive and levels finite");
+        }
+        Ok(Self { lines })
+    }
+
+    /// Parse data file text
+    pub fn parse(text: &str, units: BankUnits) -> Result<Self> {
+        let mut lines = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let values = line
+                .split_whitespace()
+                .map(|field| field.parse::<f64>())
+                .collect::<std::result::Result<Vec<f64>, _>>()
+                .map_err(|_| {
+                    FilterError::InvalidFilterData(format!("line {}: invalid number", number + 1))
+                })?;
+            if values.is_empty() {
+                continue;
+            }
+            if values.len() % 2 == 0 {
+                return Err(FilterError::InvalidFilterData(format!(
+                    "line {}: expected a time then frequency and level pairs",
+                    number + 1
+                )));
+            }
+            let bands = values[1..]
+                .chunks_exact(2)
+                .map(|pair| (units.to_hz(pair[0]), pair[1]))
+                .collect();
+            lines.push((values[0], bands));
+        }
+        Self::new(lines)
+    }
+
+    /// Load a data file
+    pub fn load(path: &Path, units: BankUnits) -> Result<Self> {
+        Self::parse(&cdp_core::fs::read_to_string(path)?, units)
+    }
+
+    /// Number of bands
+    pub fn band_count(&self) -> usize {
+        self.lines[0].1.len()
+    }
+
+    /// Frequency and level of every band at `time`
+    ///
+    /// Frequencies are interpolated in pitch and levels linearly.
+    pub fn bands_at(&self, time: f64) -> Vec<(f64, f64)> {
+        let next = self.lines.partition_point(|&(t, _)| t <= time);
+        if next == self.lines.len() {
+            return self.lines[next - 1].1.clone();
+        }
+        let (t0, before) = &self.lines[next - 1];
+        let (t1, after) = &self.lines[next];
+        let frac = (time - t0) / (t1 - t0);
+        before
+            .iter()
+            .zip(after)
+            .map(|(&(f0, l0), &(f1, l1))| (f0 * (f1 / f0).powf(frac), l0 + frac * (l1 - l0)))
+            .collect()
+    }
+}
+
+/// Filter a sound file through a varibank
+pub fn varibank(input: &Path, output: &Path, data: &VaribankData, q: f64, gain: f64) -> Result<()> {
+    let (audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    wav_cdp::write_audio_buffer(output, &varibank_buf(&audio, data, q, gain)?, sample_type)?;
+    Ok(())
+}
+
+/// Filter an in-memory sound through a varibank
+///
+/// Buffer-based counterpart of [`varibank`]. The output is the sum of the
+/// bands, each a bandpass filter of the given Q with unity gain at its
+/// centre, scaled by its level and then by `gain`. Bands above the Nyquist
+/// frequency are held just below it.
+pub fn varibank_buf(
+    audio: &AudioBuffer,
+    data: &VaribankData,
+    q: f64,
+    gain: f64,
+) -> Result<AudioBuffer> {
+    check_q(q)?;
+    check_gain(gain)?;
+    let channels = audio.channels as usize;
+    let rate = audio.sample_rate as f64;
+    let highest = rate / 2.0 * 0.999;
+
+    let mut filters =
+        vec![
+            vec![Biquad::new(FilterType::Bandpass, 1.0, q, audio.sample_rate)?; data.band_count()];
+            channels
+        ];
+    let mut levels = vec![0.0; data.band_count()];
+    let mut out = Vec::with_capacity(audio.data.len());
+    for (index, frame) in audio.frames().enumerate() {
+        if index % CONTROL_BLOCK == 0 {
+            for (band, (frequency, level)) in
+                data.bands_at(index as f64 / rate).into_iter().enumerate()
+            {
+                levels[band] = level;
+                for channel in &mut filters {
+                    channel[band].retune(
+                        FilterType::Bandpass,
+                        frequency.min(highest),
+                        q,
+                        audio.sample_rate,
+                    );
+                }
+            }
+        }
+        for (&sample, bands) in frame.iter().zip(&mut filters) {
+            let sum: f64 = bands
+                .iter_mut()
+                .zip(&levels)
+                .map(|(filter, level)| filter.process(sample as f64) * level)
+                .sum();
+            out.push((sum * gain) as f32);
+        }
+    }
+    Ok(AudioBuffer::new(audio.sample_rate, audio.channels, out)?)
+}
+
+/// CLI compatibility layer for filter varibank
+///
+/// Usage: `varibank mode infile outfile datafile q gain`, with mode 1 for
+/// frequencies in Hz and 2 for MIDI note numbers.
+pub fn varibank_cli(mode: i32, args: &[&str]) -> Result<()> {
+    let units = match mode {
+        1 => BankUnits::Hz,
+        2 => BankUnits::Midi,
+        _ => {
+            return Err(FilterError::UnsupportedOperation(format!(
+                "Varibank mode {} not yet implemented",
+                mode
+            )))
+        }
+    };
+    if args.len() != 5 {
+        return Err(FilterError::InvalidParameter(
+            "Usage: varibank mode infile outfile datafile q gain".into(),
+        ));
+    }
+    let number = |arg: &str| {
+        arg.parse::<f64>()
+            .map_err(|_| FilterError::InvalidParameter(format!("Invalid value: {}", arg)))
+    };
+    let data = VaribankData::load(Path::new(args[2]), units)?;
+    varibank(
+        Path::new(args[0]),
+        Path::new(args[1]),
+        &data,
+        number(args[3])?,
+        number(args[4])?,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_varibank() {
+        let data = VaribankData::parse("0 69 1 81 0.5\n2 81 0 93 1\n", BankUnits::Midi).unwrap();
+        assert_eq!(data.band_count(), 2);
+        let bands = data.bands_at(1.0);
+        assert!((bands[0].0 - 622.254).abs() < 1e-3);
+        assert_eq!(bands[0].1, 0.5);
+        assert_eq!(data.bands_at(5.0), vec![(880.0, 0.0), (1760.0, 1.0)]);
+
+        assert!(VaribankData::parse("0 440 1\n1 440\n", BankUnits::Hz).is_err());
+        assert!(VaribankData::parse("1 440 1\n", BankUnits::Hz).is_err());
+        assert!(VaribankData::parse("0 440 1\n1 440 1 880 1\n", BankUnits::Hz).is_err());
+    }
+
+    #[test]
+    fn test_varibank_filtering() {
+        // The band moves off the first tone and onto the second
+        let tones = cdp_synth::partials(&[(500.0, 0.5), (2000.0, 0.5)], 2.0, 8000).unwrap();
+        let data = VaribankData::new(vec![
+            (0.0, vec![(500.0, 1.0)]),
+            (0.5, vec![(500.0, 1.0)]),
+            (1.0, vec![(2000.0, 1.0)]),
+        ])
+        .unwrap();
+        let output = varibank_buf(&tones, &data, 20.0, 1.0).unwrap();
+        let level_of = |range: std::ops::Range<usize>, frequency: f64| {
+            let (mut re, mut im) = (0.0, 0.0);
+            for i in range.clone() {
+                let phase = 2.0 * std::f64::consts::PI * frequency * i as f64 / 8000.0;
+                re += output.data[i] as f64 * phase.cos();
+                im += output.data[i] as f64 * phase.sin();
+            }
+            2.0 * (re * re + im * im).sqrt() / range.len() as f64
+        };
+        assert!((level_of(2000..4000, 500.0) - 0.5).abs() < 0.02);
+        assert!(level_of(2000..4000, 2000.0) < 0.02);
+        assert!((level_of(12000..16000, 2000.0) - 0.5).abs() < 0.02);
+        assert!(level_of(12000..16000, 500.0) < 0.02);
+    }
+}
@@ -0,0 +1,185 @@
+//! Grain detection
+//!
+//! Finds the grains of a sound by gating its amplitude: a grain starts
+//! where the level rises to the threshold and ends where it stays below
+//! the threshold for longer than the gate hold time.
+
+use super::{GrainError, Result};
+use cdp_core::AudioBuffer;
+use cdp_housekeep::wav_cdp;
+use std::path::Path;
+
+/// How long the level must stay below the threshold to end a grain, in ms
+///
+/// Long enough that the zero crossings of a low note do not split it.
+const GATE_HOLD_MS: f64 = 15.0;
+
+/// Gate level used by the CLI when none is given, as in CDP
+const DEFAULT_GATE: f32 = 0.3;
+
+/// Gate settings for finding grains
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GrainGate {
+    /// Linear level (0-1] a grain must reach
+    pub threshold: f32,
+    /// Shortest time between grain starts, in seconds; a grain starting
+    /// sooner after the previous one is merged into it
+    pub min_len: f64,
+}
+
+/// A detected grain, as frame positions in the source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Grain {
+    /// First frame at or above the threshold
+    pub start: usize,
+    /// Frame after the last one at or above the threshold
+    pub end: usize,
+}
+
+impl Grain {
+    /// Length in frames
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Whether the grain holds no frames
+    pub fn is_empty(&self) -> bool {
+        self.end == self.start
+    }
+}
+
+/// Count the grains in a sound
+pub fn count_grains(input: &Path, gate: &GrainGate) -> Result<usize> {
+    let (audio, _) = wav_cdp::read_audio_buffer(input)?;
+    Ok(detect_grains(&audio, gate)?.len())
+}
+
+/// Find the grains in an in-memory sound, in time order
+pub fn detect_grains(audio: &AudioBuffer, gate: &GrainGate) -> Result<Vec<Grain>> {
+    if !(gate.threshold > 0.0 && gate.threshold <= 1.0) {
+        return Err(GrainError::InvalidParameter(
+            "Gate threshold must be greater than 0 and at most 1".into(),
+        ));
+    }
+    if !(gate.min_len >= 0.0 && gate.min_len.is_finite()) {
+        return Err(GrainError::InvalidParameter(
+            "Minimum grain length cannot be negative".into(),
+        ));
+    }
+
+    let rate = audio.sample_rate as f64;
+    let hold = (GATE_HOLD_MS * rate / 1000.0).round() as usize;
+    let min_len = (gate.min_len * rate).round() as usize;
+
+    let mut grains: Vec<Grain> = Vec::new();
+    let mut current: Option<Grain> = None;
+    for (index, frame) in audio.frames().enumerate() {
+        let peak = frame.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        if peak >= gate.threshold {
+            match current.as_mut() {
+                Some(grain) => grain.end = index + 1,
+                None => {
+                    current = Some(Grain {
+                        start: index,
+                        end: index + 1,
+                    })
+                }
+            }
+        } else if let Some(grain) = current {
+            if index - grain.end >= hold {
+                push_grain(&mut grains, grain, min_len);
+                current = None;
+            }
+        }
+    }
+    if let Some(grain) = current {
+        push_grain(&mut grains, grain, min_len);
+    }
+    Ok(grains)
+}
+
+/// Add a grain, merging it into the last if it starts too soon after it
+fn push_grain(grains: &mut Vec<Grain>, grain: Grain, min_len: usize) {
+    match grains.last_mut() {
+        Some(last) if grain.start - last.start < min_len => last.end = grain.end,
+        _ => grains.push(grain),
+    }
+}
+
+/// Split CLI arguments into gate flags (`-lgate`, `-hminlen`) and the rest
+pub(crate) fn parse_gate<'a>(args: &[&'a str]) -> Result<(GrainGate, Vec<&'a str>)> {
+    let mut gate = GrainGate {
+        threshold: DEFAULT_GATE,
+        min_len: 0.0,
+    };
+    let mut rest = Vec::new();
+    for &arg in args {
+        if let Some(value) = arg.strip_prefix("-l") {
+            gate.threshold = value
+                .parse()
+                .map_err(|_| GrainError::InvalidParameter(format!("Invalid gate: {}", value)))?;
+        } else if let Some(value) = arg.strip_prefix("-h") {
+            gate.min_len = value.parse().map_err(|_| {
+                GrainError::InvalidParameter(format!("Invalid minimum length: {}", value))
+            })?;
+        } else {
+            rest.push(arg);
+        }
+    }
+    Ok((gate, rest))
+}
+
+/// CLI compatibility layer for grain count
+///
+/// Usage: `count infile [-lgate] [-hminlen]`
+pub fn count_cli(args: &[&str]) -> Result<()> {
+    let (gate, rest) = parse_gate(args)?;
+    if rest.len() != 1 {
+        return Err(GrainError::InvalidParameter(
+            "Usage: count infile [-lgate] [-hminlen]".into(),
+        ));
+    }
+    println!("{} grains found", count_grains(Path::new(rest[0]), &gate)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_grains() {
+        // Three bursts at 1 kHz; the first gap is shorter than the 15 frame hold
+        let mut data = vec![0.0f32; 200];
+        for range in [10..20, 30..40, 100..120] {
+            for (i, sample) in data[range].iter_mut().enumerate() {
+                *sample = if i % 2 == 0 { 0.5 } else { -0.5 };
+            }
+        }
+        let audio = AudioBuffer::new(1000, 1, data).unwrap();
+
+        let gate = GrainGate {
+            threshold: 0.25,
+            min_len: 0.0,
+        };
+        let grains = detect_grains(&audio, &gate).unwrap();
+        assert_eq!(
+            grains,
+            vec![
+                Grain { start: 10, end: 40 },
+                Grain {
+                    start: 100,
+                    end: 120
+                }
+            ]
+        );
+
+        // A minimum length longer than the time between starts merges them
+        let gate = GrainGate {
+            min_len: 0.1,
+            ..gate
+        };
+        assert_eq!(detect_grains(&audio, &gate).unwrap().len(), 1);
+    }
+}
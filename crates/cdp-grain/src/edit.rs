@@ -0,0 +1,746 @@
+//! Grain editing
+//!
+//! Operations on the detected grain list, like CDP's grain suite. Each
+//! builds a new arrangement of the grains and mixes them into the output
+//! at their new start times, so gaps between grains are kept or rescaled
+//! rather than cut.
+//!
+//! A grain plays everything from its onset up to the next grain's onset,
+//! so decays below the gate travel with it. Each cut is a short crossfade
+//! ending at the onset, which leaves an unedited sound unchanged.
+
+use super::{GrainError, Result};
+use crate::detect::{detect_grains, parse_gate, Grain, GrainGate};
+use cdp_core::audio::{self, AudioBuffer};
+use cdp_core::progress::{self, ProgressSink};
+use cdp_core::{Breakpoints, TextProgress};
+use cdp_housekeep::wav_cdp;
+use std::path::Path;
+
+/// Length of the crossfade before each grain onset
+const SPLICE_MS: f64 = 15.0;
+
+/// The stretch of the source a grain plays
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    /// First frame
+    start: usize,
+    /// Grain onset
+    onset: usize,
+    /// Length of the fade-in ending at `onset`
+    fade_in: usize,
+    /// Frame after the last: the next onset, or the end of the sound
+    end: usize,
+    /// Length of the fade-out ending at `end`
+    fade_out: usize,
+}
+
+impl Segment {
+    /// Splice gain at a source position
+    ///
+    /// The fade-out before an onset and the next segment's fade-in cover
+    /// the same frames and sum to 1.
+    fn gain(&self, position: f64) -> f32 {
+        let fade_start = (self.onset - self.fade_in) as f64;
+        if position >= fade_start && position < self.onset as f64 {
+            return ((position - fade_start + 0.5) / self.fade_in as f64) as f32;
+        }
+        if position >= (self.end - self.fade_out) as f64 {
+            return ((self.end as f64 - position - 0.5) / self.fade_out as f64) as f32;
+        }
+        1.0
+    }
+}
+
+/// A grain placed in the output
+struct Placement {
+    segment: Segment,
+    /// Output frame the grain's onset lands on
+    at: usize,
+    /// Playback speed; above 1 raises the pitch and shortens the grain
+    ratio: f64,
+}
+
+/// Grains placed in the output, and the output's shortest length
+struct Arrangement {
+    placements: Vec<Placement>,
+    min_frames: usize,
+}
+
+/// Keep `keep` grains out of every `out_of`, silencing the rest
+pub fn omit(
+    input: &Path,
+    output: &Path,
+    gate: &GrainGate,
+    keep: usize,
+    out_of: usize,
+) -> Result<()> {
+    omit_with_progress(input, output, gate, keep, out_of, &mut ())
+}
+
+/// As [`omit`], reporting each grain placed to `progress`
+///
+/// Stops with [`cdp_core::CoreError::Cancelled`] if the sink asks, leaving
+/// no output file.
+pub fn omit_with_progress(
+    input: &Path,
+    output: &Path,
+    gate: &GrainGate,
+    keep: usize,
+    out_of: usize,
+    progress: &mut dyn ProgressSink,
+) -> Result<()> {
+    process(input, output, progress, |audio| {
+        omit_arrangement(audio, gate, keep, out_of)
+    })
+}
+
+/// Play each grain `copies` times in succession
+pub fn duplicate(input: &Path, output: &Path, gate: &GrainGate, copies: usize) -> Result<()> {
+    duplicate_with_progress(input, output, gate, copies, &mut ())
+}
+
+/// As [`duplicate`], reporting each grain placed to `progress`
+///
+/// Stops with [`cdp_core::CoreError::Cancelled`] if the sink asks, leaving
+/// no output file.
+pub fn duplicate_with_progress(
+    input: &Path,
+    output: &Path,
+    gate: &GrainGate,
+    copies: usize,
+    progress: &mut dyn ProgressSink,
+) -> Result<()> {
+    process(input, output, progress, |audio| {
+        duplicate_arrangement(audio, gate, copies)
+    })
+}
+
+/// Play the grains in reverse order, each still forwards
+pub fn reverse(input: &Path, output: &Path, gate: &GrainGate) -> Result<()> {
+    reverse_with_progress(input, output, gate, &mut ())
+}
+
+/// As [`reverse`], reporting each grain placed to `progress`
+///
+/// Stops with [`cdp_core::CoreError::Cancelled`] if the sink asks, leaving
+/// no output file.
+pub fn reverse_with_progress(
+    input: &Path,
+    output: &Path,
+    gate: &GrainGate,
+    progress: &mut dyn ProgressSink,
+) -> Result<()> {
+    process(input, output, progress, |audio| {
+        reverse_arrangement(audio, gate)
+    })
+}
+
+/// Transpose each grain by a number of semitones, keeping its start time
+pub fn repitch(
+    input: &Path,
+    output: &Path,
+    gate: &GrainGate,
+    semitones: &Breakpoints,
+) -> Result<()> {
+    repitch_with_progress(input, output, gate, semitones, &mut ())
+}
+
+/// As [`repitch`], reporting each grain placed to `progress`
+///
+/// Stops with [`cdp_core::CoreError::Cancelled`] if the sink asks, leaving
+/// no output file.
+pub fn repitch_with_progress(
+    input: &Path,
+    output: &Path,
+    gate: &GrainGate,
+    semitones: &Breakpoints,
+    progress: &mut dyn ProgressSink,
+) -> Result<()> {
+    process(input, output, progress, |audio| {
+        repitch_arrangement(audio, gate, semitones)
+    })
+}
+
+/// Move the grains to the given start times, in seconds
+pub fn reposition(input: &Path, output: &Path, gate: &GrainGate, times: &[f64]) -> Result<()> {
+    reposition_with_progress(input, output, gate, times, &mut ())
+}
+
+/// As [`reposition`], reporting each grain placed to `progress`
+///
+/// Stops with [`cdp_core::CoreError::Cancelled`] if the sink asks, leaving
+/// no output file.
+pub fn reposition_with_progress(
+    input: &Path,
+    output: &Path,
+    gate: &GrainGate,
+    times: &[f64],
+    progress: &mut dyn ProgressSink,
+) -> Result<()> {
+    process(input, output, progress, |audio| {
+        reposition_arrangement(audio, gate, times)
+    })
+}
+
+/// Scale the time between grain starts by a factor
+pub fn timewarp(input: &Path, output: &Path, gate: &GrainGate, factor: &Breakpoints) -> Result<()> {
+    timewarp_with_progress(input, output, gate, factor, &mut ())
+}
+
+/// As [`timewarp`], reporting each grain placed to `progress`
+///
+/// Stops with [`cdp_core::CoreError::Cancelled`] if the sink asks, leaving
+/// no output file.
+pub fn timewarp_with_progress(
+    input: &Path,
+    output: &Path,
+    gate: &GrainGate,
+    factor: &Breakpoints,
+    progress: &mut dyn ProgressSink,
+) -> Result<()> {
+    process(input, output, progress, |audio| {
+        timewarp_arrangement(audio, gate, factor)
+    })
+}
+
+/// Arrange the grains of a file and write the rendered result
+///
+/// Nothing is written until every grain has been placed.
+fn process<F>(
+    input: &Path,
+    output: &Path,
+    progress: &mut dyn ProgressSink,
+    arrange: F,
+) -> Result<()>
+where
+    F: FnOnce(&AudioBuffer) -> Result<Arrangement>,
+{
+    let (audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    let rendered = render(&audio, &arrange(&audio)?, progress)?;
+    wav_cdp::write_audio_buffer(output, &rendered, sample_type)?;
+    Ok(())
+}
+
+/// Keep `keep` grains out of every `out_of` in an in-memory sound
+///
+/// Omitted grains become silence, so the output keeps the input's timing
+/// and length.
+pub fn omit_buf(
+    audio: &AudioBuffer,
+    gate: &GrainGate,
+    keep: usize,
+    out_of: usize,
+) -> Result<AudioBuffer> {
+    render(
+        audio,
+        &omit_arrangement(audio, gate, keep, out_of)?,
+        &mut (),
+    )
+}
+
+/// Placements for [`omit_buf`]
+fn omit_arrangement(
+    audio: &AudioBuffer,
+    gate: &GrainGate,
+    keep: usize,
+    out_of: usize,
+) -> Result<Arrangement> {
+    if !(out_of >= 1 && keep <= out_of) {
+        return Err(GrainError::InvalidParameter(
+            "Grains kept must be at most the group size, which must be at least 1".into(),
+        ));
+    }
+    let placements = grains(audio, gate)?
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| index % out_of < keep)
+        .map(|(_, segment)| Placement {
+            segment,
+            at: segment.onset,
+            ratio: 1.0,
+        })
+        .collect::<Vec<_>>();
+    Ok(Arrangement {
+        placements,
+        min_frames: audio.frame_count(),
+    })
+}
+
+/// Play each grain of an in-memory sound `copies` times in succession
+///
+/// Each copy follows the last after the grain's original spacing.
+pub fn duplicate_buf(audio: &AudioBuffer, gate: &GrainGate, copies: usize) -> Result<AudioBuffer> {
+    render(audio, &duplicate_arrangement(audio, gate, copies)?, &mut ())
+}
+
+/// Placements for [`duplicate_buf`]
+fn duplicate_arrangement(
+    audio: &AudioBuffer,
+    gate: &GrainGate,
+    copies: usize,
+) -> Result<Arrangement> {
+    if !(1..=1000).contains(&copies) {
+        return Err(GrainError::InvalidParameter(
+            "Copies must be between 1 and 1000".into(),
+        ));
+    }
+    let grains = grains(audio, gate)?;
+    let spacings = spacings(&grains);
+    let mut at = grains[0].onset;
+    let mut placements = Vec::new();
+    for (&segment, spacing) in grains.iter().zip(spacings) {
+        for _ in 0..copies {
+            placements.push(Placement {
+                segment,
+                at,
+                ratio: 1.0,
+            });
+            at += spacing;
+        }
+    }
+    Ok(Arrangement {
+        placements,
+        min_frames: 0,
+    })
+}
+
+/// Play the grains of an in-memory sound in reverse order
+///
+/// The rhythm reverses too: each grain is followed by the spacing it had
+/// in the original.
+pub fn reverse_buf(audio: &AudioBuffer, gate: &GrainGate) -> Result<AudioBuffer> {
+    render(audio, &reverse_arrangement(audio, gate)?, &mut ())
+}
+
+/// Placements for [`reverse_buf`]
+fn reverse_arrangement(audio: &AudioBuffer, gate: &GrainGate) -> Result<Arrangement> {
+    let grains = grains(audio, gate)?;
+    let spacings = spacings(&grains);
+    let mut at = grains[0].onset;
+    let mut placements = Vec::new();
+    for (&segment, spacing) in grains.iter().zip(spacings).rev() {
+        placements.push(Placement {
+            segment,
+            at,
+            ratio: 1.0,
+        });
+        at += spacing;
+    }
+    Ok(Arrangement {
+        placements,
+        min_frames: 0,
+    })
+}
+
+/// Transpose each grain of an in-memory sound
+///
+/// `semitones` is read at each grain's start time and must lie within two
+/// octaves either way.
+pub fn repitch_buf(
+    audio: &AudioBuffer,
+    gate: &GrainGate,
+    semitones: &Breakpoints,
+) -> Result<AudioBuffer> {
+    render(
+        audio,
+        &repitch_arrangement(audio, gate, semitones)?,
+        &mut (),
+    )
+}
+
+/// Placements for [`repitch_buf`]
+fn repitch_arrangement(
+    audio: &AudioBuffer,
+    gate: &GrainGate,
+    semitones: &Breakpoints,
+) -> Result<Arrangement> {
+    semitones.check_range("Transposition", -24.0, 24.0)?;
+    let rate = audio.sample_rate as f64;
+    let placements = grains(audio, gate)?
+        .into_iter()
+        .map(|segment| Placement {
+            segment,
+            at: segment.onset,
+            ratio: 2.0f64.powf(semitones.value_at(segment.onset as f64 / rate) / 12.0),
+        })
+        .collect::<Vec<_>>();
+    Ok(Arrangement {
+        placements,
+        min_frames: audio.frame_count(),
+    })
+}
+
+/// Move the grains of an in-memory sound to the given start times
+///
+/// There must be one time, in seconds, for each grain.
+pub fn reposition_buf(audio: &AudioBuffer, gate: &GrainGate, times: &[f64]) -> Result<AudioBuffer> {
+    render(audio, &reposition_arrangement(audio, gate, times)?, &mut ())
+}
+
+/// Placements for [`reposition_buf`]
+fn reposition_arrangement(
+    audio: &AudioBuffer,
+    gate: &GrainGate,
+    times: &[f64],
+) -> Result<Arrangement> {
+    let grains = grains(audio, gate)?;
+    if times.len() != grains.len() {
+        return Err(GrainError::InvalidParameter(format!(
+            "Found {} grains but was given {} times",
+            grains.len(),
+            times.len()
+        )));
+    }
+    if !times.iter().all(|t| *t >= 0.0 && t.is_finite()) {
+        return Err(GrainError::InvalidParameter(
+            "Grain times cannot be negative".into(),
+        ));
+    }
+    let rate = audio.sample_rate as f64;
+    let placements = grains
+        .into_iter()
+        .zip(times)
+        .map(|(segment, time)| Placement {
+            segment,
+            at: (time * rate).round() as usize,
+            ratio: 1.0,
+        })
+        .collect::<Vec<_>>();
+    Ok(Arrangement {
+        placements,
+        min_frames: 0,
+    })
+}
+
+/// Scale the time between grain starts of an in-memory sound
+///
+/// `factor` is read at each grain's start time; above 1 spreads the
+/// grains out and below 1 packs them together.
+pub fn timewarp_buf(
+    audio: &AudioBuffer,
+    gate: &GrainGate,
+    factor: &Breakpoints,
+) -> Result<AudioBuffer> {
+    render(audio, &timewarp_arrangement(audio, gate, factor)?, &mut ())
+}
+
+/// Placements for [`timewarp_buf`]
+fn timewarp_arrangement(
+    audio: &AudioBuffer,
+    gate: &GrainGate,
+    factor: &Breakpoints,
+) -> Result<Arrangement> {
+    factor.check_range("Timewarp", 0.01, 100.0)?;
+    let rate = audio.sample_rate as f64;
+    let grains = grains(audio, gate)?;
+    let spacings = spacings(&grains);
+    let mut at = grains[0].onset as f64;
+    let mut placements = Vec::new();
+    for (&segment, spacing) in grains.iter().zip(spacings) {
+        placements.push(Placement {
+            segment,
+            at: at.round() as usize,
+            ratio: 1.0,
+        });
+        at += spacing as f64 * factor.value_at(segment.onset as f64 / rate);
+    }
+    Ok(Arrangement {
+        placements,
+        min_frames: 0,
+    })
+}
+
+/// Detect grains and the segments they play, failing if there are none
+fn grains(audio: &AudioBuffer, gate: &GrainGate) -> Result<Vec<Segment>> {
+    let grains = detect_grains(audio, gate)?;
+    if grains.is_empty() {
+        return Err(GrainError::NoGrains);
+    }
+    Ok(segments(audio, &grains))
+}
+
+/// Extend each grain to the next onset, with a splice before every onset
+///
+/// A splice is never longer than the time since the previous onset. The
+/// first grain also plays whatever precedes it, unfaded, if that is no
+/// longer than a splice; otherwise that material is left out.
+fn segments(audio: &AudioBuffer, grains: &[Grain]) -> Vec<Segment> {
+    let splice = (SPLICE_MS * audio.sample_rate as f64 / 1000.0).round() as usize;
+    let fades: Vec<usize> = grains
+        .iter()
+        .enumerate()
+        .map(|(index, grain)| {
+            let previous = index.checked_sub(1).map_or(0, |i| grains[i].start);
+            splice.min(grain.start - previous)
+        })
+        .collect();
+    grains
+        .iter()
+        .enumerate()
+        .map(|(index, grain)| {
+            let fade_in = if index == 0 && grain.start <= splice {
+                0
+            } else {
+                fades[index]
+            };
+            Segment {
+                start: grain.start - fades[index],
+                onset: grain.start,
+                fade_in,
+                end: grains
+                    .get(index + 1)
+                    .map_or(audio.frame_count(), |next| next.start),
+                fade_out: fades.get(index + 1).copied().unwrap_or(0),
+            }
+        })
+        .collect()
+}
+
+/// Frames from each onset to the next one's, or to the end of the sound
+/// for the last
+fn spacings(segments: &[Segment]) -> Vec<usize> {
+    segments
+        .iter()
+        .map(|segment| segment.end - segment.onset)
+        .collect()
+}
+
+/// Mix the arranged grains into a new sound, reporting each to `progress`
+///
+/// A grain whose fade-in would start before the beginning of the output
+/// loses that part of it.
+fn render(
+    audio: &AudioBuffer,
+    arrangement: &Arrangement,
+    progress: &mut dyn ProgressSink,
+) -> Result<AudioBuffer> {
+    let channels = audio.channels as usize;
+    let placements = &arrangement.placements;
+    let mut data = vec![0.0f32; arrangement.min_frames * channels];
+    for (index, placement) in placements.iter().enumerate() {
+        let segment = placement.segment;
+        let source = audio::transpose(
+            &audio.data[segment.start * channels..segment.end * channels],
+            channels,
+            placement.ratio,
+        );
+        let frames = source.len() / channels;
+        let lead = ((segment.onset - segment.start) as f64 / placement.ratio).round() as usize;
+        let skip = lead.saturating_sub(placement.at);
+        let end = (placement.at + frames - lead) * channels;
+        if data.len() < end {
+            data.resize(end, 0.0);
+        }
+        for frame in skip..frames {
+            let gain = segment.gain(segment.start as f64 + frame as f64 * placement.ratio);
+            let out = (placement.at + frame - lead) * channels;
+            for ch in 0..channels {
+                data[out + ch] += source[frame * channels + ch] * gain;
+            }
+        }
+        progress::report(progress, index + 1, placements.len())?;
+    }
+    Ok(AudioBuffer::new(audio.sample_rate, audio.channels, data)?)
+}
+
+/// CLI compatibility layer for the grain editing operations
+///
+/// Usage: `operation infile outfile [params] [-lgate] [-hminlen]`, where
+/// the params are `keep out_of` for omit, `copies` for duplicate, none for
+/// reverse, `semitones` for repitch, `factor` for timewarp (either may be
+/// a breakpoint file) and a file of times for reposition.
+pub fn edit_cli(operation: &str, args: &[&str]) -> Result<()> {
+    let (gate, rest) = parse_gate(args)?;
+    let params = match operation {
+        "omit" => 2,
+        "duplicate" | "repitch" | "reposition" | "timewarp" => 1,
+        _ => 0,
+    };
+    if rest.len() != params + 2 {
+        return Err(GrainError::InvalidParameter(format!(
+            "Usage: {} infile outfile{} [-lgate] [-hminlen]",
+            operation,
+            " param".repeat(params)
+        )));
+    }
+    let (input, output) = (Path::new(rest[0]), Path::new(rest[1]));
+    let count = |arg: &str| {
+        arg.parse::<usize>()
+            .map_err(|_| GrainError::InvalidParameter(format!("Invalid count: {}", arg)))
+    };
+    let breakpoints = |arg: &str| match arg.parse::<f64>() {
+        Ok(value) => Ok(Breakpoints::constant(value)),
+        Err(_) => Breakpoints::load(Path::new(arg)),
+    };
+    let progress = &mut TextProgress::new();
+    match operation {
+        "omit" => omit_with_progress(
+            input,
+            output,
+            &gate,
+            count(rest[2])?,
+            count(rest[3])?,
+            progress,
+        ),
+        "duplicate" => duplicate_with_progress(input, output, &gate, count(rest[2])?, progress),
+        "reverse" => reverse_with_progress(input, output, &gate, progress),
+        "repitch" => repitch_with_progress(input, output, &gate, &breakpoints(rest[2])?, progress),
+        "reposition" => {
+            let times = cdp_core::fs::read_to_string(rest[2])?
+                .split_whitespace()
+                .map(|field| {
+                    field.parse::<f64>().map_err(|_| {
+                        GrainError::InvalidParameter(format!("Invalid time: {}", field))
+                    })
+                })
+                .collect::<Result<Vec<f64>>>()?;
+            reposition_with_progress(input, output, &gate, &times, progress)
+        }
+        "timewarp" => {
+            timewarp_with_progress(input, output, &gate, &breakpoints(rest[2])?, progress)
+        }
+        _ => Err(GrainError::UnsupportedOperation(format!(
+            "Unknown grain operation: {}",
+            operation
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Grains of 1, 2 and 3 at frames 0, 30 and 60 of a 100 Hz sound
+    fn three_grains() -> AudioBuffer {
+        let mut data = vec![0.0f32; 80];
+        data[0] = 1.0;
+        data[30] = 2.0;
+        data[60] = 3.0;
+        AudioBuffer::new(100, 1, data).unwrap()
+    }
+
+    const GATE: GrainGate = GrainGate {
+        threshold: 0.5,
+        min_len: 0.0,
+    };
+
+    fn starts(audio: &AudioBuffer) -> Vec<(usize, f32)> {
+        audio
+            .data
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| **s != 0.0)
+            .map(|(i, s)| (i, *s))
+            .collect()
+    }
+
+    #[test]
+    fn test_grain_edits() {
+        let audio = three_grains();
+
+        let output = omit_buf(&audio, &GATE, 1, 2).unwrap();
+        assert_eq!(starts(&output), vec![(0, 1.0), (60, 3.0)]);
+        assert_eq!(output.frame_count(), 80);
+
+        let output = duplicate_buf(&audio, &GATE, 2).unwrap();
+        assert_eq!(
+            starts(&output),
+            vec![
+                (0, 1.0),
+                (30, 1.0),
+                (60, 2.0),
+                (90, 2.0),
+                (120, 3.0),
+                (140, 3.0)
+            ]
+        );
+
+        // The last grain plays to the end of the sound, 20 frames
+        let output = reverse_buf(&audio, &GATE).unwrap();
+        assert_eq!(starts(&output), vec![(0, 3.0), (20, 2.0), (50, 1.0)]);
+
+        let output = timewarp_buf(&audio, &GATE, &Breakpoints::constant(0.5)).unwrap();
+        assert_eq!(starts(&output), vec![(0, 1.0), (15, 2.0), (30, 3.0)]);
+
+        let output = reposition_buf(&audio, &GATE, &[0.5, 0.0, 0.2]).unwrap();
+        assert_eq!(starts(&output), vec![(0, 2.0), (20, 3.0), (50, 1.0)]);
+        assert!(reposition_buf(&audio, &GATE, &[0.5]).is_err());
+    }
+
+    #[test]
+    fn test_edit_progress() {
+        struct Grains(Vec<usize>);
+        impl ProgressSink for Grains {
+            fn progress(&mut self, done: usize, total: usize) {
+                assert_eq!(total, 6);
+                self.0.push(done);
+            }
+        }
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let input = dir.path().join("in.wav");
+        let output = dir.path().join("out.wav");
+        wav_cdp::write_audio_buffer(&input, &three_grains(), wav_cdp::SampleType::Float32).unwrap();
+
+        let mut grains = Grains(Vec::new());
+        duplicate_with_progress(&input, &output, &GATE, 2, &mut grains).unwrap();
+        assert_eq!(grains.0, (1..=6).collect::<Vec<_>>());
+
+        std::fs::remove_file(&output).unwrap();
+        let mut token = cdp_core::CancelToken::new();
+        token.cancel();
+        assert!(matches!(
+            duplicate_with_progress(&input, &output, &GATE, 2, &mut token),
+            Err(GrainError::Core(cdp_core::CoreError::Cancelled))
+        ));
+        assert!(!output.exists());
+    }
+
+    #[test]
+    fn test_repitch_buf() {
+        // An octave up halves a ramp grain, keeping its start
+        let mut data = vec![0.0f32; 40];
+        for (i, sample) in data[10..19].iter_mut().enumerate() {
+            *sample = 0.6 + i as f32 * 0.05;
+        }
+        let audio = AudioBuffer::new(1000, 1, data).unwrap();
+        let output = repitch_buf(&audio, &GATE, &Breakpoints::constant(12.0)).unwrap();
+        let grain: Vec<f32> = starts(&output).into_iter().map(|(_, s)| s).collect();
+        assert_eq!(starts(&output)[0].0, 10);
+        assert_eq!(grain.len(), 5);
+        assert!((grain[4] - 1.0).abs() < 1e-6);
+
+        assert!(repitch_buf(&audio, &GATE, &Breakpoints::constant(30.0)).is_err());
+    }
+
+    #[test]
+    fn test_identity_edits() {
+        // Decaying bursts whose tails fall well below the gate
+        let data: Vec<f32> = (0..3000)
+            .map(|i| {
+                let t = (i % 1000) as f32;
+                (-t / 80.0).exp() * (i as f32 * 0.3).sin()
+            })
+            .collect();
+        let audio = AudioBuffer::new(10000, 1, data).unwrap();
+        let gate = GrainGate {
+            threshold: 0.3,
+            min_len: 0.05,
+        };
+        assert_eq!(detect_grains(&audio, &gate).unwrap().len(), 3);
+
+        let outputs = [
+            omit_buf(&audio, &gate, 1, 1).unwrap(),
+            duplicate_buf(&audio, &gate, 1).unwrap(),
+            repitch_buf(&audio, &gate, &Breakpoints::constant(0.0)).unwrap(),
+            timewarp_buf(&audio, &gate, &Breakpoints::constant(1.0)).unwrap(),
+        ];
+        for output in outputs {
+            assert_eq!(output.frame_count(), audio.frame_count());
+            for (a, b) in output.data.iter().zip(&audio.data) {
+                assert!((a - b).abs() < 1e-6);
+            }
+        }
+    }
+}
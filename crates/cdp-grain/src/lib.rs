@@ -0,0 +1,58 @@
+//! CDP Grain module - Grain detection and manipulation
+//!
+//! This module implements CDP's grain operations including:
+//! - Grain detection by amplitude gating, and grain counting
+//! - Omitting and duplicating grains
+//! - Reversing grain order
+//! - Repitching grains in place
+//! - Repositioning grains and time-warping the spaces between them
+//!
+//! A grain is a stretch of sound above a gate level, separated from the
+//! next by a stretch below it.
+
+use thiserror::Error;
+
+pub mod detect;
+pub mod edit;
+
+/// Result type for grain operations
+pub type Result<T> = std::result::Result<T, GrainError>;
+
+/// Errors that can occur during grain operations
+#[derive(Error, Debug)]
+pub enum GrainError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Housekeep error: {0}")]
+    Housekeep(#[from] cdp_housekeep::HousekeepError),
+
+    #[error("Core error: {0}")]
+    Core(#[from] cdp_core::CoreError),
+
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
+
+    #[error("No grains found above the gate level")]
+    NoGrains,
+
+    #[error("Unsupported operation: {0}")]
+    UnsupportedOperation(String),
+}
+
+// Re-export main functions for convenience
+pub use detect::{count_grains, detect_grains, Grain, GrainGate};
+pub use edit::{
+    duplicate, duplicate_buf, duplicate_with_progress, omit, omit_buf, omit_with_progress, repitch,
+    repitch_buf, repitch_with_progress, reposition, reposition_buf, reposition_with_progress,
+    reverse, reverse_buf, reverse_with_progress, timewarp, timewarp_buf, timewarp_with_progress,
+};
+
+/// CLI compatibility layer - matches CDP's command-line interface
+/// This is just for oracle testing. Real users should use the library functions directly.
+pub fn grain(operation: &str, args: &[&str]) -> Result<()> {
+    match operation {
+        "count" => detect::count_cli(args),
+        _ => edit::edit_cli(operation, args),
+    }
+}
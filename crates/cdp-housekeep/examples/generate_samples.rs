@@ -3,8 +3,9 @@
 //! Run this first to create the sample WAV files needed by other examples:
 //! cargo run -p cdp-housekeep --example generate_samples
 
-use cdp_housekeep::wav_cdp::{self, WavFormat};
-use std::f32::consts::PI;
+use cdp_core::AudioBuffer;
+use cdp_housekeep::wav_cdp::{self, SampleType};
+use cdp_synth::Sweep;
 use std::path::Path;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -50,224 +51,78 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-fn generate_stereo_tone() -> Result<(), Box<dyn std::error::Error>> {
-    let path = Path::new("crates/cdp-housekeep/examples/stereo_tone.wav");
-    let sample_rate = 44100;
-    let duration = 2.0;
-    let num_samples = (sample_rate as f32 * duration) as usize;
-
-    let mut samples = Vec::new();
-    for i in 0..num_samples {
-        let t = i as f32 / sample_rate as f32;
-        let left = (2.0 * PI * 440.0 * t).sin() * 0.7; // A4
-        let right = (2.0 * PI * 880.0 * t).sin() * 0.7; // A5
-
-        samples.push((left * 32767.0) as i16);
-        samples.push((right * 32767.0) as i16);
-    }
-
-    let format = WavFormat {
-        channels: 2,
-        sample_rate,
-        bits_per_sample: 16,
-        data_size: (samples.len() * 2) as u32,
-    };
+/// Write a generated buffer into the examples directory as 16-bit
+fn write_sample(name: &str, audio: &AudioBuffer) -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new("crates/cdp-housekeep/examples").join(name);
+    wav_cdp::write_audio_buffer(&path, audio, SampleType::Int16)?;
+    Ok(())
+}
 
-    wav_cdp::write_wav_cdp(path, &format, &samples)?;
+fn generate_stereo_tone() -> Result<(), Box<dyn std::error::Error>> {
+    let left = cdp_synth::sine(440.0, 2.0, 44100, 0.7)?; // A4
+    let right = cdp_synth::sine(880.0, 2.0, 44100, 0.7)?; // A5
+    let stereo = AudioBuffer::from_channels(44100, &[left.data, right.data])?;
+    write_sample("stereo_tone.wav", &stereo)?;
     println!("  Created: stereo_tone.wav (440Hz left, 880Hz right, 2 seconds)");
     Ok(())
 }
 
 fn generate_mono_sine() -> Result<(), Box<dyn std::error::Error>> {
-    let path = Path::new("crates/cdp-housekeep/examples/mono_sine.wav");
-    let sample_rate = 44100;
-    let duration = 1.0;
-    let frequency = 440.0;
-    let num_samples = (sample_rate as f32 * duration) as usize;
-
-    let mut samples = Vec::new();
-    for i in 0..num_samples {
-        let t = i as f32 / sample_rate as f32;
-        let sample = (2.0 * PI * frequency * t).sin() * 0.8;
-        samples.push((sample * 32767.0) as i16);
-    }
-
-    let format = WavFormat {
-        channels: 1,
-        sample_rate,
-        bits_per_sample: 16,
-        data_size: (samples.len() * 2) as u32,
-    };
-
-    wav_cdp::write_wav_cdp(path, &format, &samples)?;
+    write_sample("mono_sine.wav", &cdp_synth::sine(440.0, 1.0, 44100, 0.8)?)?;
     println!("  Created: mono_sine.wav (440Hz, 1 second)");
     Ok(())
 }
 
 fn generate_white_noise() -> Result<(), Box<dyn std::error::Error>> {
-    let path = Path::new("crates/cdp-housekeep/examples/white_noise.wav");
-    let sample_rate = 44100;
-    let duration = 1.0;
-    let num_samples = (sample_rate as f32 * duration) as usize;
-
-    // Simple pseudo-random noise generator
-    let mut samples = Vec::new();
-    let mut seed = 12345u32;
-    for _ in 0..num_samples {
-        // Linear congruential generator
-        seed = (seed.wrapping_mul(1103515245).wrapping_add(12345)) & 0x7fffffff;
-        let normalized = (seed as f32 / 0x7fffffff as f32) * 2.0 - 1.0;
-        samples.push((normalized * 0.3 * 32767.0) as i16); // Keep it quieter
-    }
-
-    let format = WavFormat {
-        channels: 1,
-        sample_rate,
-        bits_per_sample: 16,
-        data_size: (samples.len() * 2) as u32,
-    };
-
-    wav_cdp::write_wav_cdp(path, &format, &samples)?;
+    // Keep it quieter
+    let noise = cdp_synth::white_noise(1.0, 44100, 0.3, 12345)?;
+    write_sample("white_noise.wav", &noise)?;
     println!("  Created: white_noise.wav (1 second)");
     Ok(())
 }
 
 fn generate_chirp() -> Result<(), Box<dyn std::error::Error>> {
-    let path = Path::new("crates/cdp-housekeep/examples/chirp.wav");
-    let sample_rate = 44100;
-    let duration = 2.0;
-    let start_freq = 100.0;
-    let end_freq = 2000.0;
-    let num_samples = (sample_rate as f32 * duration) as usize;
-
-    let mut samples = Vec::new();
-    for i in 0..num_samples {
-        let t = i as f32 / sample_rate as f32;
-        let progress = t / duration;
-        let freq = start_freq + (end_freq - start_freq) * progress;
-        let sample = (2.0 * PI * freq * t).sin() * 0.7;
-        samples.push((sample * 32767.0) as i16);
-    }
-
-    let format = WavFormat {
-        channels: 1,
-        sample_rate,
-        bits_per_sample: 16,
-        data_size: (samples.len() * 2) as u32,
-    };
-
-    wav_cdp::write_wav_cdp(path, &format, &samples)?;
+    let chirp = cdp_synth::chirp(100.0, 2000.0, Sweep::Linear, 2.0, 44100, 0.7)?;
+    write_sample("chirp.wav", &chirp)?;
     println!("  Created: chirp.wav (100Hz to 2000Hz sweep, 2 seconds)");
     Ok(())
 }
 
 fn generate_sine_tone() -> Result<(), Box<dyn std::error::Error>> {
-    let path = Path::new("crates/cdp-housekeep/examples/sine_tone.wav");
-    let sample_rate = 44100;
-    let duration = 2.0;
-    let frequency = 440.0;
-    let num_samples = (sample_rate as f32 * duration) as usize;
-
-    let mut samples = Vec::new();
-    for i in 0..num_samples {
-        let t = i as f32 / sample_rate as f32;
-        let sample = (2.0 * PI * frequency * t).sin() * 0.8;
-        samples.push((sample * 32767.0) as i16);
-    }
-
-    let format = WavFormat {
-        channels: 1,
-        sample_rate,
-        bits_per_sample: 16,
-        data_size: (samples.len() * 2) as u32,
-    };
-
-    wav_cdp::write_wav_cdp(path, &format, &samples)?;
+    write_sample("sine_tone.wav", &cdp_synth::sine(440.0, 2.0, 44100, 0.8)?)?;
     println!("  Created: sine_tone.wav (440Hz, 2 seconds)");
     Ok(())
 }
 
 fn generate_sawtooth_tone() -> Result<(), Box<dyn std::error::Error>> {
-    let path = Path::new("crates/cdp-housekeep/examples/sawtooth_tone.wav");
-    let sample_rate = 44100;
-    let duration = 2.0;
-    let frequency = 220.0; // Lower frequency for better harmonics
-    let num_samples = (sample_rate as f32 * duration) as usize;
-
-    let mut samples = Vec::new();
-    for i in 0..num_samples {
-        let t = i as f32 / sample_rate as f32;
-        // Sawtooth wave: ramp from -1 to 1
-        let phase = (frequency * t) % 1.0;
-        let sample = (2.0 * phase - 1.0) * 0.6;
-        samples.push((sample * 32767.0) as i16);
-    }
-
-    let format = WavFormat {
-        channels: 1,
-        sample_rate,
-        bits_per_sample: 16,
-        data_size: (samples.len() * 2) as u32,
-    };
-
-    wav_cdp::write_wav_cdp(path, &format, &samples)?;
+    // Lower frequency for better harmonics
+    let saw = cdp_synth::sawtooth(220.0, 2.0, 44100, 0.6)?;
+    write_sample("sawtooth_tone.wav", &saw)?;
     println!("  Created: sawtooth_tone.wav (220Hz sawtooth, 2 seconds)");
     Ok(())
 }
 
 fn generate_complex_tone() -> Result<(), Box<dyn std::error::Error>> {
-    let path = Path::new("crates/cdp-housekeep/examples/complex_tone.wav");
-    let sample_rate = 44100;
-    let duration = 2.0;
+    // Fundamental with 2nd, 3rd and 5th harmonics
     let fundamental = 220.0;
-    let num_samples = (sample_rate as f32 * duration) as usize;
-
-    let mut samples = Vec::new();
-    for i in 0..num_samples {
-        let t = i as f32 / sample_rate as f32;
-        // Mix of harmonics to create complex tone
-        let mut sample = 0.0;
-        sample += (2.0 * PI * fundamental * t).sin() * 0.5; // Fundamental
-        sample += (2.0 * PI * fundamental * 2.0 * t).sin() * 0.3; // 2nd harmonic
-        sample += (2.0 * PI * fundamental * 3.0 * t).sin() * 0.2; // 3rd harmonic
-        sample += (2.0 * PI * fundamental * 5.0 * t).sin() * 0.1; // 5th harmonic
-        samples.push((sample * 32767.0) as i16);
-    }
-
-    let format = WavFormat {
-        channels: 1,
-        sample_rate,
-        bits_per_sample: 16,
-        data_size: (samples.len() * 2) as u32,
-    };
-
-    wav_cdp::write_wav_cdp(path, &format, &samples)?;
+    let tone = cdp_synth::partials(
+        &[
+            (fundamental, 0.5),
+            (fundamental * 2.0, 0.3),
+            (fundamental * 3.0, 0.2),
+            (fundamental * 5.0, 0.1),
+        ],
+        2.0,
+        44100,
+    )?;
+    write_sample("complex_tone.wav", &tone)?;
     println!("  Created: complex_tone.wav (220Hz with harmonics, 2 seconds)");
     Ok(())
 }
 
 fn generate_quiet_signal() -> Result<(), Box<dyn std::error::Error>> {
-    let path = Path::new("crates/cdp-housekeep/examples/quiet_sine.wav");
-    let sample_rate = 44100;
-    let duration = 1.0;
-    let frequency = 440.0;
-    let num_samples = (sample_rate as f32 * duration) as usize;
-
-    let mut samples = Vec::new();
-    for i in 0..num_samples {
-        let t = i as f32 / sample_rate as f32;
-        let sample = (2.0 * PI * frequency * t).sin() * 0.1; // Very quiet
-        samples.push((sample * 32767.0) as i16);
-    }
-
-    let format = WavFormat {
-        channels: 1,
-        sample_rate,
-        bits_per_sample: 16,
-        data_size: (samples.len() * 2) as u32,
-    };
-
-    wav_cdp::write_wav_cdp(path, &format, &samples)?;
+    // Very quiet
+    write_sample("quiet_sine.wav", &cdp_synth::sine(440.0, 1.0, 44100, 0.1)?)?;
     println!("  Created: quiet_sine.wav (440Hz at -20dB, 1 second)");
     Ok(())
 }
@@ -0,0 +1,273 @@
+//! Housekeep batch processing - apply an operation across a directory tree
+//!
+//! Walks a directory for WAV files, applies a copy, format conversion or
+//! normalization to each, and mirrors the tree under an output directory.
+//! Failures are collected per file rather than aborting the whole batch.
+
+use super::quantize::{QuantizeOptions, Quantizer};
+use super::wav_cdp::{self, SampleType};
+use super::{HousekeepError, Result};
+use cdp_core::fs;
+use std::path::{Path, PathBuf};
+
+/// Operation applied to every file in a batch
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum BatchOperation {
+    /// Copy with CDP metadata
+    Copy,
+    /// Convert to the given sample type, dithering integer reductions
+    Convert {
+        sample_type: SampleType,
+        quantize: QuantizeOptions,
+    },
+    /// Normalize to the given peak level (0.0 - 1.0)
+    Normalize(f32),
+}
+
+/// How output files are named relative to their inputs
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum OutputNaming {
+    /// Keep the input file name
+    Same,
+    /// Insert a suffix before the extension (input.wav -> input_suffix.wav)
+    Suffix(String),
+    /// Prepend a prefix to the file name
+    Prefix(String),
+}
+
+impl OutputNaming {
+    fn file_name(&self, input: &Path) -> String {
+        let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+        match self {
+            OutputNaming::Same => format!("{}.wav", stem),
+            OutputNaming::Suffix(suffix) => format!("{}{}.wav", stem, suffix),
+            OutputNaming::Prefix(prefix) => format!("{}{}.wav", prefix, stem),
+        }
+    }
+}
+
+/// Outcome for a single file in a batch
+#[derive(Debug)]
+pub struct BatchEntry {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub result: Result<()>,
+}
+
+/// Per-file results of a batch run
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    pub entries: Vec<BatchEntry>,
+}
+
+impl BatchReport {
+    /// Number of files processed successfully
+    pub fn succeeded(&self) -> usize {
+        self.entries.iter().filter(|e| e.result.is_ok()).count()
+    }
+
+    /// Entries that failed, with their errors
+    pub fn failures(&self) -> impl Iterator<Item = &BatchEntry> {
+        self.entries.iter().filter(|e| e.result.is_err())
+    }
+}
+
+/// Apply `operation` to every WAV file under `input_dir`
+///
+/// Subdirectories are mirrored under `output_dir`. Only errors reading the
+/// input tree abort the batch; per-file failures are recorded in the report.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "info", skip_all, fields(input_dir = %input_dir.display()))
+)]
+pub fn batch(
+    input_dir: &Path,
+    output_dir: &Path,
+    operation: BatchOperation,
+    naming: &OutputNaming,
+) -> Result<BatchReport> {
+    // Collect the file list up front so outputs written inside the input
+    // tree are never picked up as inputs
+    let inputs = fs::wav_files(input_dir, true)?;
+
+    let mut report = BatchReport::default();
+    for input in inputs {
+        let relative_dir = input
+            .parent()
+            .and_then(|dir| dir.strip_prefix(input_dir).ok())
+            .unwrap_or(Path::new(""));
+        let output = output_dir.join(relative_dir).join(naming.file_name(&input));
+
+        let result = fs::create_dir_all(output.parent().unwrap())
+            .map_err(HousekeepError::from)
+            .and_then(|_| process_file(&input, &output, operation));
+        report.entries.push(BatchEntry {
+            input,
+            output,
+            result,
+        });
+    }
+
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        succeeded = report.succeeded(),
+        failed = report.entries.len() - report.succeeded(),
+        "batch finished"
+    );
+    Ok(report)
+}
+
+/// Apply a single batch operation to one file
+///
+/// Refuses to write over `input`, which is still being read as the output
+/// is written.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "info",
+        skip_all,
+        fields(input = %input.display(), output = %output.display()),
+        err
+    )
+)]
+pub fn process_file(input: &Path, output: &Path, operation: BatchOperation) -> Result<()> {
+    if fs::same_file(input, output) {
+        return Err(HousekeepError::InvalidFile(format!(
+            "Output {} would overwrite its input",
+            output.display()
+        )));
+    }
+
+    match operation {
+        BatchOperation::Copy => wav_cdp::copy_wav_cdp(input, output),
+        BatchOperation::Convert {
+            sample_type,
+            quantize,
+        } => transcode(input, output, Some(sample_type), quantize, 1.0),
+        BatchOperation::Normalize(level) => {
+            if !(0.0..=1.0).contains(&level) {
+                return Err(HousekeepError::InvalidFile(
+                    "Normalize level must be between 0.0 and 1.0".into(),
+                ));
+            }
+            let peak = wav_cdp::scan_peak(input)?.0;
+            if peak == 0.0 {
+                // Silent file, just copy
+                return wav_cdp::copy_wav_cdp(input, output);
+            }
+            transcode(
+                input,
+                output,
+                None,
+                QuantizeOptions::default(),
+                level / peak,
+            )
+        }
+    }
+}
+
+/// Stream a file to a new sample type, scaling every sample by `gain`
+fn transcode(
+    input: &Path,
+    output: &Path,
+    sample_type: Option<SampleType>,
+    quantize: QuantizeOptions,
+    gain: f32,
+) -> Result<()> {
+    let mut reader = wav_cdp::WavReader::open(input)?;
+    let mut format = reader.format().clone();
+    if let Some(sample_type) = sample_type {
+        format.bits_per_sample = sample_type.bits_per_sample();
+    }
+
+    let mut quantizer = Quantizer::new(format.sample_type()?, quantize, format.channels as usize);
+    let mut writer = wav_cdp::WavWriter::create(output, &format)?;
+    let mut block = vec![0.0f32; wav_cdp::DEFAULT_BLOCK_FRAMES * format.channels as usize];
+    loop {
+        let count = reader.read_block(&mut block)?;
+        if count == 0 {
+            break;
+        }
+        for sample in &mut block[..count] {
+            *sample *= gain;
+        }
+        quantizer.process(&mut block[..count]);
+        writer.write_block(&block[..count])?;
+    }
+
+    writer.finalize()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_batch_mirrors_tree_and_reports_failures() {
+        let temp_dir = TempDir::new().unwrap();
+        let input_dir = temp_dir.path().join("in");
+        let output_dir = temp_dir.path().join("out");
+        fs::create_dir_all(input_dir.join("sub")).unwrap();
+
+        let format = wav_cdp::WavFormat {
+            channels: 1,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            data_size: 0,
+        };
+        wav_cdp::write_wav_cdp(&input_dir.join("a.wav"), &format, &[100, -200]).unwrap();
+        wav_cdp::write_wav_cdp(&input_dir.join("sub/b.WAV"), &format, &[50]).unwrap();
+        fs::write(input_dir.join("broken.wav"), b"not a wav").unwrap();
+        fs::write(input_dir.join("notes.txt"), b"ignored").unwrap();
+
+        let naming = OutputNaming::Suffix("_norm".into());
+        let report = batch(
+            &input_dir,
+            &output_dir,
+            BatchOperation::Normalize(1.0),
+            &naming,
+        )
+        .unwrap();
+
+        assert_eq!(report.entries.len(), 3);
+        assert_eq!(report.succeeded(), 2);
+        assert_eq!(
+            report.failures().next().unwrap().input,
+            input_dir.join("broken.wav")
+        );
+
+        let (_, samples) = wav_cdp::read_wav_basic(&output_dir.join("a_norm.wav")).unwrap();
+        assert_eq!(samples, vec![16383, -32767]);
+        assert!(output_dir.join("sub/b_norm.wav").exists());
+    }
+
+    #[test]
+    fn test_batch_refuses_to_overwrite_inputs() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let format = wav_cdp::WavFormat {
+            channels: 1,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            data_size: 0,
+        };
+        wav_cdp::write_wav_cdp(&dir.join("a.wav"), &format, &[100, -200]).unwrap();
+
+        // The same directory reached by another route still resolves to the input
+        let same_dir = dir.join(".");
+        let report = batch(dir, &same_dir, BatchOperation::Copy, &OutputNaming::Same).unwrap();
+        assert_eq!(report.succeeded(), 0);
+        assert!(matches!(
+            report.entries[0].result,
+            Err(HousekeepError::InvalidFile(_))
+        ));
+        let (_, samples) = wav_cdp::read_wav_basic(&dir.join("a.wav")).unwrap();
+        assert_eq!(samples, vec![100, -200]);
+    }
+}
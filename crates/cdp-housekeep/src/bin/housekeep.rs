@@ -12,7 +12,7 @@ fn main() {
     if args.len() < 2 {
         eprintln!("CDP-RS Housekeep (Oracle Validation Binary)");
         eprintln!("Usage: housekeep <operation> [args...]");
-        eprintln!("Operations: copy, chans, extract");
+        eprintln!("Operations: copy, chans, respec, extract, raw, dump, recover");
         process::exit(1);
     }
 
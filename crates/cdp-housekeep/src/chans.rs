@@ -4,7 +4,7 @@
 
 use super::wav_cdp;
 use super::{HousekeepError, Result};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Extract a single channel from a multi-channel file to a specific output file
 ///
@@ -16,8 +16,9 @@ pub fn extract_channel_to(input: &Path, channel: usize, output: &Path) -> Result
         ));
     }
 
-    // Read the input file
-    let (format, samples) = wav_cdp::read_wav_basic(input)?;
+    // Open the input file for streaming
+    let mut reader = wav_cdp::WavReader::open(input)?;
+    let format = reader.format().clone();
 
     if format.channels == 1 {
         return Err(HousekeepError::InvalidFile(
@@ -32,22 +33,33 @@ pub fn extract_channel_to(input: &Path, channel: usize, output: &Path) -> Result
         )));
     }
 
+    // Create mono format
+    let channels = format.channels as usize;
+    let mut mono_format = format.clone();
+    mono_format.channels = 1;
+    mono_format.data_size = format.data_size / channels as u32;
+
+    let mut writer = wav_cdp::WavWriter::create(output, &mono_format)?;
+
     // Extract the requested channel (convert to 0-based indexing)
     let chan_idx = channel - 1;
-    let mut extracted = Vec::new();
+    let mut block = vec![0.0f32; wav_cdp::DEFAULT_BLOCK_FRAMES * channels];
+    let mut extracted = Vec::with_capacity(wav_cdp::DEFAULT_BLOCK_FRAMES);
 
-    // Samples are interleaved: L R L R L R for stereo
-    for i in (chan_idx..samples.len()).step_by(format.channels as usize) {
-        extracted.push(samples[i]);
-    }
+    loop {
+        let count = reader.read_block(&mut block)?;
+        if count == 0 {
+            break;
+        }
 
-    // Create mono format
-    let mut mono_format = format.clone();
-    mono_format.channels = 1;
-    mono_format.data_size = (extracted.len() * 2) as u32;
+        // Samples are interleaved: L R L R L R for stereo
+        extracted.clear();
+        extracted.extend(block[..count].iter().skip(chan_idx).step_by(channels));
+        writer.write_block(&extracted)?;
+    }
 
-    // Write the extracted channel with CDP format
-    wav_cdp::write_wav_cdp(output, &mono_format, &extracted)?;
+    // Finish the extracted channel with CDP format
+    writer.finalize()?;
 
     Ok(())
 }
@@ -57,36 +69,57 @@ pub fn extract_channel_to(input: &Path, channel: usize, output: &Path) -> Result
 /// Channel numbers are 1-based (1 = first channel, 2 = second, etc.)
 /// Output filename will be input_c1.wav, input_c2.wav, etc.
 pub fn extract_channel(input: &Path, channel: usize) -> Result<()> {
-    // Create output filename: input_c1.wav, input_c2.wav, etc.
-    let stem = input.file_stem().unwrap().to_str().unwrap();
-    let output_name = format!("{}_c{}.wav", stem, channel);
-    let output = input.with_file_name(output_name);
+    extract_channel_to(input, channel, &channel_path(input, channel)?)
+}
 
-    extract_channel_to(input, channel, &output)
+/// Output filename for one channel of `input`: input_c1.wav, input_c2.wav, etc.
+fn channel_path(input: &Path, channel: usize) -> Result<PathBuf> {
+    let stem = input
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .ok_or_else(|| {
+            HousekeepError::InvalidFile(format!(
+                "Cannot name channel files after {}",
+                input.display()
+            ))
+        })?;
+    Ok(input.with_file_name(format!("{}_c{}.wav", stem, channel)))
 }
 
 /// Mix stereo/multi-channel file to mono
 pub fn mix_to_mono(input: &Path, output: &Path, invert_phase: bool) -> Result<()> {
-    // Read input file
-    let (format, samples) = wav_cdp::read_wav_basic(input)?;
+    // Open input file for streaming
+    let mut reader = wav_cdp::WavReader::open(input)?;
+    let format = reader.format().clone();
 
     if format.channels == 1 {
         // Already mono, just copy
-        wav_cdp::write_wav_cdp(output, &format, &samples)?;
-        return Ok(());
+        return wav_cdp::copy_wav_cdp(input, output);
     }
 
-    // Mix channels together
-    let mut mixed = Vec::new();
+    // Create mono format
     let channels = format.channels as usize;
+    let mut mono_format = format.clone();
+    mono_format.channels = 1;
+    mono_format.data_size = format.data_size / channels as u32;
+
+    let mut writer = wav_cdp::WavWriter::create(output, &mono_format)?;
+    let mut block = vec![0.0f32; wav_cdp::DEFAULT_BLOCK_FRAMES * channels];
+    let mut mixed = Vec::with_capacity(wav_cdp::DEFAULT_BLOCK_FRAMES);
+
+    loop {
+        let count = reader.read_block(&mut block)?;
+        if count == 0 {
+            break;
+        }
 
-    for i in (0..samples.len()).step_by(channels) {
-        let mut sum = 0i32;
+        // Mix channels together
+        mixed.clear();
+        for frame in block[..count].chunks(channels) {
+            let mut sum = 0.0f32;
 
-        // Add all channels together
-        for ch in 0..channels {
-            if i + ch < samples.len() {
-                let sample = samples[i + ch] as i32;
+            // Add all channels together
+            for (ch, &sample) in frame.iter().enumerate() {
                 // For stereo with phase inversion, invert right channel
                 if invert_phase && channels == 2 && ch == 1 {
                     sum -= sample;
@@ -94,24 +127,211 @@ pub fn mix_to_mono(input: &Path, output: &Path, invert_phase: bool) -> Result<()
                     sum += sample;
                 }
             }
+
+            // Average the sum (prevent clipping)
+            mixed.push(sum / channels as f32);
+        }
+
+        writer.write_block(&mixed)?;
+    }
+
+    // Write output
+    writer.finalize()?;
+    Ok(())
+}
+
+/// Convert a mono file to stereo by duplicating it into both channels
+///
+/// Each channel is scaled by its own gain; the PEAK chunk is recomputed
+/// over the interleaved stereo output.
+pub fn mono_to_stereo(input: &Path, output: &Path, left_gain: f32, right_gain: f32) -> Result<()> {
+    // Open input file for streaming
+    let mut reader = wav_cdp::WavReader::open(input)?;
+    let format = reader.format().clone();
+
+    if format.channels != 1 {
+        return Err(HousekeepError::InvalidFile(format!(
+            "Input must be mono (file has {} channels)",
+            format.channels
+        )));
+    }
+
+    // Create stereo format
+    let mut stereo_format = format.clone();
+    stereo_format.channels = 2;
+    stereo_format.data_size = format.data_size * 2;
+
+    let mut writer = wav_cdp::WavWriter::create(output, &stereo_format)?;
+    let mut block = vec![0.0f32; wav_cdp::DEFAULT_BLOCK_FRAMES];
+    let mut stereo = Vec::with_capacity(wav_cdp::DEFAULT_BLOCK_FRAMES * 2);
+
+    loop {
+        let count = reader.read_block(&mut block)?;
+        if count == 0 {
+            break;
+        }
+
+        // Duplicate each sample into an L R pair
+        stereo.clear();
+        for &sample in &block[..count] {
+            stereo.push(sample * left_gain);
+            stereo.push(sample * right_gain);
+        }
+
+        writer.write_block(&stereo)?;
+    }
+
+    // Write output
+    writer.finalize()?;
+    Ok(())
+}
+
+/// Interleave mono files into a single multi-channel file
+///
+/// Input `n` becomes channel `n + 1` of the output. All inputs must be mono
+/// and share sample rate and sample type; shorter inputs are padded with
+/// silence to the length of the longest.
+pub fn interleave(inputs: &[&Path], output: &Path) -> Result<()> {
+    if inputs.len() < 2 {
+        return Err(HousekeepError::InvalidFile(
+            "Interleaving needs at least 2 input files".into(),
+        ));
+    }
+
+    let mut readers = inputs
+        .iter()
+        .map(|input| wav_cdp::WavReader::open(input))
+        .collect::<std::io::Result<Vec<_>>>()?;
+
+    let format = readers[0].format().clone();
+    for (input, reader) in inputs.iter().zip(&readers) {
+        let input_format = reader.format();
+        if input_format.channels != 1 {
+            return Err(HousekeepError::InvalidFile(format!(
+                "{} is not mono",
+                input.display()
+            )));
+        }
+        if input_format.sample_rate != format.sample_rate
+            || input_format.bits_per_sample != format.bits_per_sample
+        {
+            return Err(HousekeepError::InvalidFile(format!(
+                "{} does not match the sample rate and type of {}",
+                input.display(),
+                inputs[0].display()
+            )));
+        }
+    }
+
+    // Create multi-channel format sized for the longest input
+    let channels = inputs.len();
+    let mut multi_format = format.clone();
+    multi_format.channels = channels as u16;
+    multi_format.data_size = readers
+        .iter()
+        .map(|reader| reader.format().data_size)
+        .max()
+        .unwrap_or(0)
+        * channels as u32;
+
+    let mut writer = wav_cdp::WavWriter::create(output, &multi_format)?;
+    let mut block = vec![0.0f32; wav_cdp::DEFAULT_BLOCK_FRAMES];
+    let mut interleaved = vec![0.0f32; wav_cdp::DEFAULT_BLOCK_FRAMES * channels];
+
+    loop {
+        // Fill each channel slot, leaving silence where an input has ended
+        interleaved.fill(0.0);
+        let mut frames = 0;
+        for (ch, reader) in readers.iter_mut().enumerate() {
+            let count = reader.read_block(&mut block)?;
+            for (i, &sample) in block[..count].iter().enumerate() {
+                interleaved[i * channels + ch] = sample;
+            }
+            frames = frames.max(count);
+        }
+
+        if frames == 0 {
+            break;
         }
+        writer.write_block(&interleaved[..frames * channels])?;
+    }
+
+    // Write output
+    writer.finalize()?;
+    Ok(())
+}
+
+/// Split a multi-channel file into one mono file per channel
+///
+/// `outputs[n]` receives channel `n + 1`; there must be exactly one output
+/// path per input channel.
+pub fn deinterleave(input: &Path, outputs: &[&Path]) -> Result<()> {
+    // Open the input file for streaming
+    let mut reader = wav_cdp::WavReader::open(input)?;
+    let format = reader.format().clone();
+    let channels = format.channels as usize;
 
-        // Average the sum (prevent clipping)
-        let avg = sum / channels as i32;
-        let clamped = avg.clamp(-32768, 32767) as i16;
-        mixed.push(clamped);
+    if outputs.len() != channels {
+        return Err(HousekeepError::InvalidFile(format!(
+            "Expected {} output files (one per channel), got {}",
+            channels,
+            outputs.len()
+        )));
     }
 
     // Create mono format
     let mut mono_format = format.clone();
     mono_format.channels = 1;
-    mono_format.data_size = (mixed.len() * 2) as u32;
+    mono_format.data_size = format.data_size / channels as u32;
 
-    // Write output
-    wav_cdp::write_wav_cdp(output, &mono_format, &mixed)?;
+    let mut writers = outputs
+        .iter()
+        .map(|output| wav_cdp::WavWriter::create(output, &mono_format))
+        .collect::<std::io::Result<Vec<_>>>()?;
+
+    let mut block = vec![0.0f32; wav_cdp::DEFAULT_BLOCK_FRAMES * channels];
+    let mut extracted = Vec::with_capacity(wav_cdp::DEFAULT_BLOCK_FRAMES);
+
+    loop {
+        let count = reader.read_block(&mut block)?;
+        if count == 0 {
+            break;
+        }
+
+        // Route each interleaved channel to its own writer
+        for (ch, writer) in writers.iter_mut().enumerate() {
+            extracted.clear();
+            extracted.extend(block[..count].iter().skip(ch).step_by(channels));
+            writer.write_block(&extracted)?;
+        }
+    }
+
+    // Finish every channel with CDP format
+    for writer in writers {
+        writer.finalize()?;
+    }
     Ok(())
 }
 
+/// Split a multi-channel file into mono files (auto-generates output filenames)
+///
+/// Output filenames will be input_c1.wav, input_c2.wav, etc.
+pub fn extract_all_channels(input: &Path) -> Result<()> {
+    let channels = wav_cdp::WavReader::open(input)?.format().channels as usize;
+    if channels == 1 {
+        return Err(HousekeepError::InvalidFile(
+            "Cannot extract channels from mono file".into(),
+        ));
+    }
+
+    let outputs = (1..=channels)
+        .map(|channel| channel_path(input, channel))
+        .collect::<Result<Vec<_>>>()?;
+    let outputs: Vec<&Path> = outputs.iter().map(|p| p.as_path()).collect();
+
+    deinterleave(input, &outputs)
+}
+
 /// CLI compatibility layer for channel operations
 pub fn chans(mode: i32, args: &[&str]) -> Result<()> {
     match mode {
@@ -129,10 +349,11 @@ pub fn chans(mode: i32, args: &[&str]) -> Result<()> {
             extract_channel(input, channel)
         }
         2 => {
-            // Extract all channels - TODO
-            Err(HousekeepError::UnsupportedFormat(
-                "Mode 2 (extract all) not yet implemented".into(),
-            ))
+            // Extract all channels
+            if args.is_empty() {
+                return Err(HousekeepError::InvalidFile("Usage: chans 2 infile".into()));
+            }
+            extract_all_channels(Path::new(args[0]))
         }
         3 => {
             // Zero one channel - TODO
@@ -153,10 +374,24 @@ pub fn chans(mode: i32, args: &[&str]) -> Result<()> {
             mix_to_mono(input, output, invert_phase)
         }
         5 => {
-            // Mono to stereo - TODO
-            Err(HousekeepError::UnsupportedFormat(
-                "Mode 5 (mono to stereo) not yet implemented".into(),
-            ))
+            // Mono to stereo
+            if args.len() != 2 && args.len() != 4 {
+                return Err(HousekeepError::InvalidFile(
+                    "Usage: chans 5 infile outfile [left_gain right_gain]".into(),
+                ));
+            }
+            let input = Path::new(args[0]);
+            let output = Path::new(args[1]);
+            let (left_gain, right_gain) = if args.len() == 4 {
+                let parse_gain = |arg: &str| {
+                    arg.parse::<f32>()
+                        .map_err(|_| HousekeepError::InvalidFile(format!("Invalid gain: {}", arg)))
+                };
+                (parse_gain(args[2])?, parse_gain(args[3])?)
+            } else {
+                (1.0, 1.0)
+            };
+            mono_to_stereo(input, output, left_gain, right_gain)
         }
         _ => Err(HousekeepError::UnsupportedFormat(format!(
             "Unknown chans mode: {}",
@@ -179,4 +414,102 @@ mod tests {
         let result = extract_channel(&input, 0);
         assert!(result.is_err());
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_channel_files_need_a_unicode_name() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let input = temp_dir.path().join(OsStr::from_bytes(b"st\xffreo.wav"));
+        let format = wav_cdp::WavFormat {
+            channels: 2,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            data_size: 0,
+        };
+        wav_cdp::write_wav_cdp(&input, &format, &[1, 2, 3, 4]).unwrap();
+
+        assert!(matches!(
+            extract_all_channels(&input),
+            Err(HousekeepError::InvalidFile(_))
+        ));
+        assert!(matches!(
+            extract_channel(&input, 1),
+            Err(HousekeepError::InvalidFile(_))
+        ));
+    }
+
+    #[test]
+    fn test_mono_to_stereo_gains_and_peak() {
+        let temp_dir = TempDir::new().unwrap();
+        let input = temp_dir.path().join("mono.wav");
+        let output = temp_dir.path().join("stereo.wav");
+
+        let format = wav_cdp::WavFormat {
+            channels: 1,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            data_size: 0,
+        };
+        wav_cdp::write_wav_cdp(&input, &format, &[1000, -16000, 8000]).unwrap();
+
+        mono_to_stereo(&input, &output, 1.0, 0.5).unwrap();
+
+        let (out_format, samples) = wav_cdp::read_wav_basic(&output).unwrap();
+        assert_eq!(out_format.channels, 2);
+        assert_eq!(samples, vec![1000, 500, -16000, -8000, 8000, 4000]);
+
+        // PEAK holds a value and frame position for each channel
+        let bytes = std::fs::read(&output).unwrap();
+        assert_eq!(&bytes[36..40], b"PEAK");
+        assert_eq!(u32::from_le_bytes(bytes[40..44].try_into().unwrap()), 24);
+        let peak = |offset: usize| {
+            (
+                f32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap()),
+                u32::from_le_bytes(bytes[offset + 4..offset + 8].try_into().unwrap()),
+            )
+        };
+        assert_eq!(peak(52), (16000.0 / 32767.0, 1));
+        assert_eq!(peak(60), (8000.0 / 32767.0, 1));
+
+        // Stereo input is rejected
+        assert!(mono_to_stereo(&output, &input, 1.0, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_interleave_deinterleave_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let left = temp_dir.path().join("left.wav");
+        let right = temp_dir.path().join("right.wav");
+        let stereo = temp_dir.path().join("stereo.wav");
+        let out_left = temp_dir.path().join("out_left.wav");
+        let out_right = temp_dir.path().join("out_right.wav");
+
+        let format = wav_cdp::WavFormat {
+            channels: 1,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            data_size: 0,
+        };
+        wav_cdp::write_wav_cdp(&left, &format, &[1, 2, 3]).unwrap();
+        wav_cdp::write_wav_cdp(&right, &format, &[-1, -2]).unwrap();
+
+        interleave(&[&left, &right], &stereo).unwrap();
+        let (stereo_format, samples) = wav_cdp::read_wav_basic(&stereo).unwrap();
+        assert_eq!(stereo_format.channels, 2);
+        // The shorter input is padded with silence
+        assert_eq!(samples, vec![1, -1, 2, -2, 3, 0]);
+
+        deinterleave(&stereo, &[&out_left, &out_right]).unwrap();
+        assert_eq!(wav_cdp::read_wav_basic(&out_left).unwrap().1, vec![1, 2, 3]);
+        assert_eq!(
+            wav_cdp::read_wav_basic(&out_right).unwrap().1,
+            vec![-1, -2, 0]
+        );
+
+        // One output path is needed per channel
+        assert!(deinterleave(&stereo, &[&out_left]).is_err());
+    }
 }
@@ -0,0 +1,104 @@
+//! Housekeep concatenation - join sound files end to end
+//!
+//! Sample-accurate joining of compatible files, with an optional silence
+//! gap between them, in the manner of CDP's file joining operations.
+
+use super::wav_cdp;
+use super::{HousekeepError, Result};
+use std::path::Path;
+
+/// Join files end to end, separated by `gap_secs` seconds of silence
+///
+/// All inputs must share sample rate and channel count. The output uses
+/// the sample type of the first input, and its PEAK chunk covers the whole
+/// joined file.
+pub fn concat(inputs: &[&Path], output: &Path, gap_secs: f64) -> Result<()> {
+    if inputs.is_empty() {
+        return Err(HousekeepError::InvalidFile(
+            "Concatenation needs at least 1 input file".into(),
+        ));
+    }
+    if gap_secs < 0.0 {
+        return Err(HousekeepError::InvalidFile("Gap cannot be negative".into()));
+    }
+
+    // Validate every input before writing anything
+    let mut readers = inputs
+        .iter()
+        .map(|input| wav_cdp::WavReader::open(input))
+        .collect::<std::io::Result<Vec<_>>>()?;
+
+    let format = readers[0].format().clone();
+    for (input, reader) in inputs.iter().zip(&readers) {
+        let input_format = reader.format();
+        if input_format.sample_rate != format.sample_rate
+            || input_format.channels != format.channels
+        {
+            return Err(HousekeepError::InvalidFile(format!(
+                "{} does not match the sample rate and channel count of {}",
+                input.display(),
+                inputs[0].display()
+            )));
+        }
+    }
+
+    let channels = format.channels as usize;
+    let gap = vec![0.0f32; (gap_secs * format.sample_rate as f64).round() as usize * channels];
+
+    let mut writer = wav_cdp::WavWriter::create(output, &format)?;
+    let mut block = vec![0.0f32; wav_cdp::DEFAULT_BLOCK_FRAMES * channels];
+
+    let last = readers.len() - 1;
+    for (i, reader) in readers.iter_mut().enumerate() {
+        loop {
+            let count = reader.read_block(&mut block)?;
+            if count == 0 {
+                break;
+            }
+            writer.write_block(&block[..count])?;
+        }
+
+        // Silence only goes between files, not after the last one
+        if i < last {
+            writer.write_block(&gap)?;
+        }
+    }
+
+    writer.finalize()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_concat_with_gap() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = temp_dir.path().join("first.wav");
+        let second = temp_dir.path().join("second.wav");
+        let stereo = temp_dir.path().join("stereo.wav");
+        let output = temp_dir.path().join("output.wav");
+
+        let mut format = wav_cdp::WavFormat {
+            channels: 1,
+            sample_rate: 10,
+            bits_per_sample: 16,
+            data_size: 0,
+        };
+        wav_cdp::write_wav_cdp(&first, &format, &[1, 2]).unwrap();
+        wav_cdp::write_wav_cdp(&second, &format, &[3, -4]).unwrap();
+
+        concat(&[&first, &second], &output, 0.2).unwrap();
+        assert_eq!(
+            wav_cdp::read_wav_basic(&output).unwrap().1,
+            vec![1, 2, 0, 0, 3, -4]
+        );
+
+        // Channel counts must match
+        format.channels = 2;
+        wav_cdp::write_wav_cdp(&stereo, &format, &[1, 2]).unwrap();
+        assert!(concat(&[&first, &stereo], &output, 0.0).is_err());
+    }
+}
@@ -0,0 +1,505 @@
+//! Housekeep extract operations - cut segments and strip silence
+//!
+//! Implements CDP's segment extraction: pulling the significant sounds out
+//! of a file with a gate, topping and tailing low-level signal from the
+//! start and end of a sound, removing DC offset, cutting a time range to a
+//! new file, and splitting a file at a list of times.
+
+use super::wav_cdp;
+use super::{HousekeepError, Result};
+use cdp_core::fs::{self, File};
+use std::io::BufReader;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Quiet time a sound may contain before gated extraction ends it
+pub const DEFAULT_HOLD_MS: f64 = 50.0;
+
+/// Cut the time range `start..end` (in seconds) to a new file
+///
+/// An `end` beyond the end of the file is clamped to the file length.
+pub fn cut(input: &Path, output: &Path, start: f64, end: f64) -> Result<()> {
+    if start < 0.0 || end <= start {
+        return Err(HousekeepError::InvalidFile(format!(
+            "Invalid cut range: {} to {} seconds",
+            start, end
+        )));
+    }
+
+    let mut reader = wav_cdp::WavReader::open(input)?;
+    let total_frames = reader.format().frame_count();
+    let start_frame = time_to_frame(start, reader.format().sample_rate);
+    let end_frame = time_to_frame(end, reader.format().sample_rate).min(total_frames);
+
+    if start_frame >= total_frames {
+        return Err(HousekeepError::InvalidFile(format!(
+            "Cut start {} seconds is beyond the end of the file",
+            start
+        )));
+    }
+
+    skip_frames(&mut reader, start_frame)?;
+    write_frames(&mut reader, output, end_frame - start_frame)
+}
+
+/// Split a file into consecutive segments at the given times (in seconds)
+///
+/// `times` must be strictly increasing and lie inside the file; there must
+/// be one more output path than split times.
+pub fn split(input: &Path, times: &[f64], outputs: &[&Path]) -> Result<()> {
+    if outputs.len() != times.len() + 1 {
+        return Err(HousekeepError::InvalidFile(format!(
+            "Expected {} output files for {} split times, got {}",
+            times.len() + 1,
+            times.len(),
+            outputs.len()
+        )));
+    }
+
+    let mut reader = wav_cdp::WavReader::open(input)?;
+    let total_frames = reader.format().frame_count();
+    let sample_rate = reader.format().sample_rate;
+
+    // Segment boundaries in frames, including the start and end of the file
+    let mut boundaries = vec![0];
+    for &time in times {
+        let frame = time_to_frame(time, sample_rate);
+        if time < 0.0 || frame <= *boundaries.last().unwrap() || frame >= total_frames {
+            return Err(HousekeepError::InvalidFile(format!(
+                "Split time {} seconds is out of order or outside the file",
+                time
+            )));
+        }
+        boundaries.push(frame);
+    }
+    boundaries.push(total_frames);
+
+    for (window, output) in boundaries.windows(2).zip(outputs) {
+        write_frames(&mut reader, output, window[1] - window[0])?;
+    }
+    Ok(())
+}
+
+/// Remove low-level signal from the start and end of a file
+///
+/// Leading and trailing frames in which no channel exceeds `threshold`
+/// (a level between 0.0 and 1.0) are discarded.
+pub fn top_and_tail(input: &Path, output: &Path, threshold: f32) -> Result<()> {
+    if !(0.0..1.0).contains(&threshold) {
+        return Err(HousekeepError::InvalidFile(
+            "Threshold must be between 0.0 and 1.0".into(),
+        ));
+    }
+
+    // First pass: find the first and last frames above the threshold
+    let sounds = find_sounds(input, threshold, 0)?;
+    let (Some(first), Some(last)) = (sounds.first(), sounds.last()) else {
+        return Err(HousekeepError::InvalidFile(
+            "No signal above threshold".into(),
+        ));
+    };
+    let range = first.start..last.end;
+
+    // Second pass: keep only the significant range
+    let mut reader = wav_cdp::WavReader::open(input)?;
+    skip_frames(&mut reader, range.start)?;
+    write_frames(&mut reader, output, range.len())
+}
+
+/// Times (start, end in seconds) of the significant sounds in a file
+///
+/// A sound starts at the first frame in which any channel exceeds `gate`
+/// (a level between 0.0 and 1.0) and ends after its last such frame, once
+/// the signal has stayed at or below the gate for longer than `hold`
+/// seconds; shorter dips do not split a sound. These are the sounds
+/// [`extract_gated`] would write.
+pub fn gated_sounds(input: &Path, gate: f32, hold: f64) -> Result<Vec<(f64, f64)>> {
+    let sample_rate = wav_cdp::WavReader::open(input)?.format().sample_rate as f64;
+    let sounds = find_gated_sounds(input, gate, hold)?;
+    Ok(sounds
+        .iter()
+        .map(|sound| {
+            (
+                sound.start as f64 / sample_rate,
+                sound.end as f64 / sample_rate,
+            )
+        })
+        .collect())
+}
+
+/// Write each significant sound in a file to its own file
+///
+/// Sounds are found as [`gated_sounds`] describes and written as
+/// `name_1.wav`, `name_2.wav`, ... beside `output`, whose paths are
+/// returned.
+pub fn extract_gated(input: &Path, output: &Path, gate: f32, hold: f64) -> Result<Vec<PathBuf>> {
+    let sounds = find_gated_sounds(input, gate, hold)?;
+    if sounds.is_empty() {
+        return Err(HousekeepError::InvalidFile("No signal above gate".into()));
+    }
+
+    let mut reader = wav_cdp::WavReader::open(input)?;
+    let mut position = 0;
+    let mut outputs = Vec::with_capacity(sounds.len());
+    for (index, sound) in sounds.iter().enumerate() {
+        let path = numbered_path(output, index + 1);
+        skip_frames(&mut reader, sound.start - position)?;
+        write_frames(&mut reader, &path, sound.len())?;
+        position = sound.end;
+        outputs.push(path);
+    }
+    Ok(outputs)
+}
+
+/// Subtract each channel's mean level, removing any DC offset
+pub fn remove_dc(input: &Path, output: &Path) -> Result<()> {
+    // First pass: the mean of each channel
+    let mut reader = wav_cdp::WavReader::open(input)?;
+    let format = reader.format().clone();
+    let channels = format.channels.max(1) as usize;
+    let mut block = vec![0.0f32; wav_cdp::DEFAULT_BLOCK_FRAMES * channels];
+    let mut sums = vec![0.0f64; channels];
+    loop {
+        let count = reader.read_block(&mut block)?;
+        if count == 0 {
+            break;
+        }
+        for frame in block[..count].chunks(channels) {
+            for (sum, &sample) in sums.iter_mut().zip(frame) {
+                *sum += sample as f64;
+            }
+        }
+    }
+    let frames = format.frame_count().max(1) as f64;
+    let offsets: Vec<f32> = sums.iter().map(|sum| (sum / frames) as f32).collect();
+
+    // Second pass: remove the offsets
+    let mut reader = wav_cdp::WavReader::open(input)?;
+    let mut writer = wav_cdp::WavWriter::create(output, &format)?;
+    loop {
+        let count = reader.read_block(&mut block)?;
+        if count == 0 {
+            break;
+        }
+        for frame in block[..count].chunks_mut(channels) {
+            for (sample, offset) in frame.iter_mut().zip(&offsets) {
+                *sample -= offset;
+            }
+        }
+        writer.write_block(&block[..count])?;
+    }
+    writer.finalize()?;
+    Ok(())
+}
+
+/// CLI compatibility layer for extract operations
+///
+/// Modes:
+/// 1. `extract 1 infile outfile gate [hold_ms]`: write each significant
+///    sound to `outfile_1.wav`, `outfile_2.wav`, ...
+/// 2. `extract 2 infile outfile gate [hold_ms]`: preview mode 1, writing
+///    the start and end time of each sound to a text file
+/// 3. `extract 3 infile outfile gate`: top and tail
+/// 4. `extract 4 infile outfile`: remove DC offset
+/// 5. `extract 5 infile outfile start end`: cut a time range
+/// 6. `extract 6 infile outfile time [time ...]`: split at the given
+///    times into `outfile_1.wav`, `outfile_2.wav`, ...
+pub fn extract(mode: i32, args: &[&str]) -> Result<()> {
+    let (usage, required) = match mode {
+        1 | 2 => ("infile outfile gate [hold_ms]", 3),
+        3 => ("infile outfile gate", 3),
+        4 => ("infile outfile", 2),
+        5 => ("infile outfile start end", 4),
+        6 => ("infile outfile time [time ...]", 3),
+        _ => {
+            return Err(HousekeepError::UnsupportedFormat(format!(
+                "Unknown extract mode: {}",
+                mode
+            )))
+        }
+    };
+    if args.len() < required {
+        return Err(HousekeepError::InvalidFile(format!(
+            "Usage: extract {} {}",
+            mode, usage
+        )));
+    }
+
+    let input = Path::new(args[0]);
+    let output = Path::new(args[1]);
+    let number = |arg: &str, name: &str| {
+        arg.parse::<f64>()
+            .map_err(|_| HousekeepError::InvalidFile(format!("Invalid {}: {}", name, arg)))
+    };
+    let hold = || {
+        args.get(3)
+            .map_or(Ok(DEFAULT_HOLD_MS), |arg| number(arg, "hold time"))
+            .map(|ms| ms / 1000.0)
+    };
+
+    match mode {
+        1 => extract_gated(
+            input,
+            output,
+            number(args[2], "gate level")? as f32,
+            hold()?,
+        )
+        .map(drop),
+        2 => {
+            let sounds = gated_sounds(input, number(args[2], "gate level")? as f32, hold()?)?;
+            let text: String = sounds
+                .iter()
+                .map(|(start, end)| format!("{:.6}\t{:.6}\n", start, end))
+                .collect();
+            fs::write(output, text)?;
+            Ok(())
+        }
+        3 => top_and_tail(input, output, number(args[2], "gate level")? as f32),
+        4 => remove_dc(input, output),
+        5 => cut(
+            input,
+            output,
+            number(args[2], "start time")?,
+            number(args[3], "end time")?,
+        ),
+        _ => {
+            let times = args[2..]
+                .iter()
+                .map(|arg| number(arg, "split time"))
+                .collect::<Result<Vec<_>>>()?;
+            let outputs: Vec<PathBuf> = (1..=times.len() + 1)
+                .map(|index| numbered_path(output, index))
+                .collect();
+            let outputs: Vec<&Path> = outputs.iter().map(PathBuf::as_path).collect();
+            split(input, &times, &outputs)
+        }
+    }
+}
+
+/// `name.wav` becomes `name_1.wav`, `name_2.wav`, ...
+fn numbered_path(path: &Path, index: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{}_{}.wav", stem, index))
+}
+
+/// Frame ranges of the sounds in a file, ended by `hold` seconds of quiet
+fn find_gated_sounds(input: &Path, gate: f32, hold: f64) -> Result<Vec<Range<usize>>> {
+    if !(0.0..1.0).contains(&gate) {
+        return Err(HousekeepError::InvalidFile(
+            "Gate must be between 0.0 and 1.0".into(),
+        ));
+    }
+    if !(hold >= 0.0 && hold.is_finite()) {
+        return Err(HousekeepError::InvalidFile(
+            "Hold time cannot be negative".into(),
+        ));
+    }
+    let sample_rate = wav_cdp::WavReader::open(input)?.format().sample_rate;
+    find_sounds(input, gate, time_to_frame(hold, sample_rate))
+}
+
+/// Frame ranges in which some channel exceeds `threshold`, merging ranges
+/// separated by no more than `hold` quiet frames
+fn find_sounds(input: &Path, threshold: f32, hold: usize) -> Result<Vec<Range<usize>>> {
+    let mut reader = wav_cdp::WavReader::open(input)?;
+    let channels = reader.format().channels.max(1) as usize;
+    let mut block = vec![0.0f32; wav_cdp::DEFAULT_BLOCK_FRAMES * channels];
+    let mut sounds: Vec<Range<usize>> = Vec::new();
+    let mut frame_offset = 0;
+
+    loop {
+        let count = reader.read_block(&mut block)?;
+        if count == 0 {
+            break;
+        }
+        for (i, frame) in block[..count].chunks(channels).enumerate() {
+            if !frame.iter().any(|sample| sample.abs() > threshold) {
+                continue;
+            }
+            let index = frame_offset + i;
+            match sounds.last_mut() {
+                Some(sound) if index - sound.end <= hold => sound.end = index + 1,
+                _ => sounds.push(index..index + 1),
+            }
+        }
+        frame_offset += count / channels;
+    }
+    Ok(sounds)
+}
+
+fn time_to_frame(time: f64, sample_rate: u32) -> usize {
+    (time * sample_rate as f64).round() as usize
+}
+
+/// Read and discard `frames` frames from the reader
+fn skip_frames(reader: &mut wav_cdp::WavReader<BufReader<File>>, frames: usize) -> Result<()> {
+    let channels = reader.format().channels as usize;
+    let mut block = vec![0.0f32; wav_cdp::DEFAULT_BLOCK_FRAMES * channels];
+    let mut remaining = frames * channels;
+
+    while remaining > 0 {
+        let len = remaining.min(block.len());
+        let count = reader.read_block(&mut block[..len])?;
+        if count == 0 {
+            break;
+        }
+        remaining -= count;
+    }
+    Ok(())
+}
+
+/// Stream the next `frames` frames from the reader to a new CDP file
+fn write_frames(
+    reader: &mut wav_cdp::WavReader<BufReader<File>>,
+    output: &Path,
+    frames: usize,
+) -> Result<()> {
+    let channels = reader.format().channels as usize;
+    let mut format = reader.format().clone();
+    format.data_size = (frames * channels * format.bytes_per_sample()) as u32;
+
+    let mut writer = wav_cdp::WavWriter::create(output, &format)?;
+    let mut block = vec![0.0f32; wav_cdp::DEFAULT_BLOCK_FRAMES * channels];
+    let mut remaining = frames * channels;
+
+    while remaining > 0 {
+        let len = remaining.min(block.len());
+        let count = reader.read_block(&mut block[..len])?;
+        if count == 0 {
+            break;
+        }
+        writer.write_block(&block[..count])?;
+        remaining -= count;
+    }
+
+    writer.finalize()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn mono_format(sample_rate: u32) -> wav_cdp::WavFormat {
+        wav_cdp::WavFormat {
+            channels: 1,
+            sample_rate,
+            bits_per_sample: 16,
+            data_size: 0,
+        }
+    }
+
+    #[test]
+    fn test_cut_and_split() {
+        let temp_dir = TempDir::new().unwrap();
+        let input = temp_dir.path().join("input.wav");
+        let cut_out = temp_dir.path().join("cut.wav");
+        let parts: Vec<_> = (0..3)
+            .map(|i| temp_dir.path().join(format!("part{}.wav", i)))
+            .collect();
+
+        // 10 Hz sample rate makes times map to whole frames
+        let samples: Vec<i16> = (0..10).collect();
+        wav_cdp::write_wav_cdp(&input, &mono_format(10), &samples).unwrap();
+
+        cut(&input, &cut_out, 0.2, 0.5).unwrap();
+        assert_eq!(wav_cdp::read_wav_basic(&cut_out).unwrap().1, vec![2, 3, 4]);
+
+        let outputs: Vec<&Path> = parts.iter().map(|p| p.as_path()).collect();
+        split(&input, &[0.3, 0.7], &outputs).unwrap();
+        assert_eq!(wav_cdp::read_wav_basic(&parts[0]).unwrap().1, vec![0, 1, 2]);
+        assert_eq!(
+            wav_cdp::read_wav_basic(&parts[1]).unwrap().1,
+            vec![3, 4, 5, 6]
+        );
+        assert_eq!(wav_cdp::read_wav_basic(&parts[2]).unwrap().1, vec![7, 8, 9]);
+
+        // Split times must be increasing
+        assert!(split(&input, &[0.7, 0.3], &outputs).is_err());
+    }
+
+    #[test]
+    fn test_top_and_tail() {
+        let temp_dir = TempDir::new().unwrap();
+        let input = temp_dir.path().join("input.wav");
+        let output = temp_dir.path().join("output.wav");
+
+        let samples = vec![0, 10, 5000, -20, 8000, 30, 0];
+        wav_cdp::write_wav_cdp(&input, &mono_format(44100), &samples).unwrap();
+
+        top_and_tail(&input, &output, 0.01).unwrap();
+        assert_eq!(
+            wav_cdp::read_wav_basic(&output).unwrap().1,
+            vec![5000, -20, 8000]
+        );
+
+        // Nothing survives a gate above the loudest sample
+        assert!(top_and_tail(&input, &output, 0.5).is_err());
+    }
+
+    #[test]
+    fn test_gated_extraction() {
+        let temp_dir = TempDir::new().unwrap();
+        let input = temp_dir.path().join("input.wav");
+        let output = temp_dir.path().join("sound.wav");
+
+        // Two sounds, the first with a one-frame dip, separated by silence
+        let samples = vec![0, 9000, 0, 9000, 0, 0, 0, 0, 8000, 7000, 0];
+        wav_cdp::write_wav_cdp(&input, &mono_format(10), &samples).unwrap();
+
+        assert_eq!(
+            gated_sounds(&input, 0.1, 0.1).unwrap(),
+            vec![(0.1, 0.4), (0.8, 1.0)]
+        );
+        let outputs = extract_gated(&input, &output, 0.1, 0.1).unwrap();
+        assert_eq!(
+            outputs,
+            vec![
+                temp_dir.path().join("sound_1.wav"),
+                temp_dir.path().join("sound_2.wav")
+            ]
+        );
+        assert_eq!(
+            wav_cdp::read_wav_basic(&outputs[0]).unwrap().1,
+            vec![9000, 0, 9000]
+        );
+        assert_eq!(
+            wav_cdp::read_wav_basic(&outputs[1]).unwrap().1,
+            vec![8000, 7000]
+        );
+
+        // Without any hold the dip splits the first sound
+        assert_eq!(gated_sounds(&input, 0.1, 0.0).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_remove_dc_and_cli_modes() {
+        let temp_dir = TempDir::new().unwrap();
+        let input = temp_dir.path().join("input.wav");
+        let output = temp_dir.path().join("output.wav");
+        let samples: Vec<i16> = vec![1100, 900, 1100, 900];
+        wav_cdp::write_wav_cdp(&input, &mono_format(10), &samples).unwrap();
+
+        remove_dc(&input, &output).unwrap();
+        assert_eq!(
+            wav_cdp::read_wav_basic(&output).unwrap().1,
+            vec![100, -100, 100, -100]
+        );
+
+        let arg = |path: &Path| path.to_str().unwrap().to_string();
+        let (input_arg, output_arg) = (arg(&input), arg(&output));
+        extract(5, &[&input_arg, &output_arg, "0.1", "0.3"]).unwrap();
+        assert_eq!(wav_cdp::read_wav_basic(&output).unwrap().1, vec![900, 1100]);
+
+        extract(6, &[&input_arg, &output_arg, "0.1"]).unwrap();
+        let second = temp_dir.path().join("output_2.wav");
+        assert_eq!(
+            wav_cdp::read_wav_basic(&second).unwrap().1,
+            vec![900, 1100, 900]
+        );
+
+        assert!(extract(5, &[&input_arg, &output_arg]).is_err());
+        assert!(extract(7, &[&input_arg, &output_arg]).is_err());
+    }
+}
@@ -3,14 +3,29 @@
 //! This module implements CDP's housekeeping operations including:
 //! - File copying with CDP metadata preservation
 //! - Channel extraction and manipulation
-//! - Format conversion
+//! - Sample-accurate concatenation
+//! - Segment extraction (gated extraction, top and tail, DC removal, cut, split)
+//! - Header respecification (sample rate, channel count)
+//! - Sample rate conversion with polyphase windowed-sinc filtering
+//! - Dump and recover: rescuing the samples of files with damaged headers
+//! - Raw (headerless) sample import and export in either byte order
+//! - Format conversion, with optional dither on bit-depth reduction
+//! - Batch processing over directory trees
 //!
 //! All operations are validated against CDP binaries for byte-perfect compatibility.
 
 use thiserror::Error;
 
+pub mod batch;
 pub mod chans;
+pub mod concat;
 pub mod copy;
+pub mod extract;
+pub mod quantize;
+pub mod raw;
+pub mod recover;
+pub mod resample;
+pub mod respec;
 pub mod wav_cdp;
 
 /// Result type for housekeep operations
@@ -27,12 +42,29 @@ pub enum HousekeepError {
 
     #[error("Unsupported format: {0}")]
     UnsupportedFormat(String),
+
+    #[error("Core error: {0}")]
+    Core(#[from] cdp_core::CoreError),
 }
 
 // Re-export main functions for convenience
-pub use chans::{extract_channel, extract_channel_to, mix_to_mono};
+pub use batch::{batch, BatchOperation, BatchReport, OutputNaming};
+pub use chans::{
+    deinterleave, extract_all_channels, extract_channel, extract_channel_to, interleave,
+    mix_to_mono, mono_to_stereo,
+};
+pub use concat::concat;
 pub use copy::{copy, copy_file};
-pub use wav_cdp::{read_wav_basic, write_wav_cdp};
+pub use extract::{cut, extract_gated, gated_sounds, remove_dc, split, top_and_tail};
+pub use quantize::{Dither, NoiseShaping, QuantizeOptions, Quantizer};
+pub use raw::{read_raw, write_raw, Endianness, RawFormat};
+pub use recover::{dump, recover, DumpReport};
+pub use resample::{resample, resample_buf, ResampleQuality};
+pub use respec::respecify;
+pub use wav_cdp::{
+    read_audio_buffer, read_audio_buffer_from, read_wav_basic, read_wav_int, write_audio_buffer,
+    write_audio_buffer_to, write_wav_cdp, write_wav_cdp_int,
+};
 
 /// CLI compatibility layer - matches CDP's command-line interface
 /// This is just for oracle testing. Real users should use the library functions directly.
@@ -60,6 +92,35 @@ pub fn housekeep(operation: &str, args: &[&str]) -> Result<()> {
             let mode = args[0].parse::<i32>().unwrap_or(1);
             chans::chans(mode, &args[1..])
         }
+        "extract" => {
+            if args.is_empty() {
+                return Err(HousekeepError::InvalidFile(
+                    "Usage: extract <mode> <infile> <outfile> [args...]".into(),
+                ));
+            }
+            let mode = args[0].parse::<i32>().unwrap_or(3);
+            extract::extract(mode, &args[1..])
+        }
+        "respec" => {
+            if args.is_empty() {
+                return Err(HousekeepError::InvalidFile(
+                    "Usage: respec <mode> <infile> <outfile> [args...]".into(),
+                ));
+            }
+            let mode = args[0].parse::<i32>().unwrap_or(3);
+            respec::respec(mode, &args[1..])
+        }
+        "raw" => {
+            if args.is_empty() {
+                return Err(HousekeepError::InvalidFile(
+                    "Usage: raw <mode> <infile> <outfile> [args...]".into(),
+                ));
+            }
+            let mode = args[0].parse::<i32>().unwrap_or(1);
+            raw::raw(mode, &args[1..])
+        }
+        "dump" => recover::dump_cli(args),
+        "recover" => recover::recover_cli(args),
         _ => Err(HousekeepError::UnsupportedFormat(format!(
             "Unknown operation: {}",
             operation
@@ -0,0 +1,140 @@
+//! Bit-depth reduction with optional dither and noise shaping
+//!
+//! The CDP writers truncate float levels to the target integer format. When
+//! reducing float or 24-bit audio to 16-bit, a [`Quantizer`] can be run over
+//! each block first to add TPDF dither and shape the requantization noise
+//! away from the most audible frequencies.
+//!
+//! The quantizer leaves every sample exactly on the target integer grid, so
+//! the writer's truncation becomes a no-op.
+
+use super::wav_cdp::SampleType;
+use cdp_core::Rng;
+
+/// Dither added before rounding to the target resolution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Dither {
+    /// Plain truncation, matching the writers
+    #[default]
+    None,
+    /// Triangular PDF dither of ±1 LSB
+    Tpdf,
+}
+
+/// Error-feedback filter applied to the quantization error
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum NoiseShaping {
+    /// Flat (white) quantization noise
+    #[default]
+    None,
+    /// First-order highpass shaping: (1 - z^-1)
+    FirstOrder,
+    /// Second-order highpass shaping: (1 - z^-1)^2
+    SecondOrder,
+}
+
+impl NoiseShaping {
+    /// Feedback coefficients for previous errors e[n-1], e[n-2]
+    fn coefficients(self) -> [f32; 2] {
+        match self {
+            NoiseShaping::None => [0.0, 0.0],
+            NoiseShaping::FirstOrder => [1.0, 0.0],
+            NoiseShaping::SecondOrder => [2.0, -1.0],
+        }
+    }
+}
+
+/// Options for reducing bit depth
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct QuantizeOptions {
+    pub dither: Dither,
+    pub shaping: NoiseShaping,
+}
+
+/// Stateful quantizer for interleaved blocks of float levels
+///
+/// Dither and error-feedback state carry across blocks, so one quantizer
+/// should be used for a whole stream.
+pub struct Quantizer {
+    full_scale: f32,
+    options: QuantizeOptions,
+    channels: usize,
+    /// Previous two quantization errors per channel, in LSBs
+    errors: Vec<[f32; 2]>,
+    rng: Rng,
+}
+
+impl Quantizer {
+    /// Create a quantizer targeting `sample_type`
+    ///
+    /// Float targets pass through unchanged.
+    pub fn new(sample_type: SampleType, options: QuantizeOptions, channels: usize) -> Self {
+        Self {
+            full_scale: sample_type.full_scale(),
+            options,
+            channels: channels.max(1),
+            errors: vec![[0.0; 2]; channels.max(1)],
+            rng: Rng::new(0x9E37_79B9),
+        }
+    }
+
+    /// Quantize an interleaved block in place
+    pub fn process(&mut self, block: &mut [f32]) {
+        if self.full_scale == 1.0 || self.options == QuantizeOptions::default() {
+            // Float output, or plain truncation left to the writer
+            return;
+        }
+
+        let max_value = self.full_scale - 1.0;
+        let [h1, h2] = self.options.shaping.coefficients();
+
+        for (i, sample) in block.iter_mut().enumerate() {
+            let ch = i % self.channels;
+            let [e1, e2] = self.errors[ch];
+
+            // Work in LSBs, subtracting the filtered past error
+            let target = *sample * self.full_scale - (h1 * e1 + h2 * e2);
+            let dither = match self.options.dither {
+                Dither::None => 0.0,
+                Dither::Tpdf => (self.rng.uniform() - self.rng.uniform()) as f32,
+            };
+            let quantized = (target + dither).round().clamp(-max_value - 1.0, max_value);
+
+            self.errors[ch] = [quantized - target, e1];
+            *sample = quantized / self.full_scale;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dithered_output_is_on_grid_and_unbiased() {
+        let options = QuantizeOptions {
+            dither: Dither::Tpdf,
+            shaping: NoiseShaping::SecondOrder,
+        };
+        let mut quantizer = Quantizer::new(SampleType::Int16, options, 1);
+
+        // A constant level a quarter of an LSB above zero
+        let level = 0.25 / 32768.0;
+        let mut block = vec![level; 4096];
+        quantizer.process(&mut block);
+
+        for &sample in &block {
+            let lsb = sample * 32768.0;
+            assert_eq!(lsb, lsb.round());
+        }
+        // Noise shaping keeps the long-term average at the input level
+        let mean = block.iter().sum::<f32>() / block.len() as f32;
+        assert!((mean - level).abs() < 0.01 / 32768.0);
+    }
+}
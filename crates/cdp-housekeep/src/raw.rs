@@ -0,0 +1,235 @@
+//! Raw (headerless) audio import and export
+//!
+//! Reads and writes bare sample data whose format is given explicitly:
+//! sample rate, channel count, sample type and byte order. This lets
+//! material from hardware synths, other tools and old CDP `.sf`
+//! installations enter the pipeline, and sends audio back out to them.
+
+use super::wav_cdp::{self, SampleType};
+use super::{HousekeepError, Result};
+use cdp_core::fs;
+use cdp_core::AudioBuffer;
+use std::path::Path;
+
+/// Byte order of raw samples
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Endianness {
+    /// Least significant byte first, as in WAV files
+    #[default]
+    Little,
+    /// Most significant byte first, as in AIFF and many older systems
+    Big,
+}
+
+/// Layout of raw sample data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RawFormat {
+    /// Sample rate in Hz
+    pub sample_rate: u32,
+    /// Number of interleaved channels
+    pub channels: u16,
+    /// Encoding of each sample
+    pub sample_type: SampleType,
+    /// Byte order of each sample
+    pub endianness: Endianness,
+    /// Bytes to skip before the first sample, such as a foreign header
+    pub offset: u64,
+}
+
+impl RawFormat {
+    /// Little-endian samples starting at the first byte
+    pub fn new(sample_rate: u32, channels: u16, sample_type: SampleType) -> Self {
+        Self {
+            sample_rate,
+            channels,
+            sample_type,
+            endianness: Endianness::Little,
+            offset: 0,
+        }
+    }
+}
+
+/// Read a raw sample file
+///
+/// Trailing bytes that do not make up a whole frame are dropped.
+pub fn read_raw(input: &Path, format: &RawFormat) -> Result<AudioBuffer> {
+    if format.sample_rate == 0 || format.channels == 0 {
+        return Err(HousekeepError::InvalidFile(
+            "Sample rate and channel count must be greater than 0".into(),
+        ));
+    }
+    let bytes = fs::read(input)?;
+    let Some(data) = bytes.get(format.offset as usize..) else {
+        return Err(HousekeepError::InvalidFile(format!(
+            "Offset {} is past the end of the file ({} bytes)",
+            format.offset,
+            bytes.len()
+        )));
+    };
+
+    let width = format.sample_type.bits_per_sample() as usize / 8;
+    let frame_bytes = width * format.channels as usize;
+    let whole = data.len() / frame_bytes * frame_bytes;
+    let mut sample = [0u8; 4];
+    let levels = data[..whole]
+        .chunks_exact(width)
+        .map(|bytes| {
+            sample[..width].copy_from_slice(bytes);
+            if format.endianness == Endianness::Big {
+                sample[..width].reverse();
+            }
+            format.sample_type.decode(&sample[..width])
+        })
+        .collect();
+
+    Ok(AudioBuffer::new(
+        format.sample_rate,
+        format.channels,
+        levels,
+    )?)
+}
+
+/// Write an [`AudioBuffer`] as raw samples
+///
+/// Integer formats are quantized and clamped as the WAV writer does; the
+/// sample rate and channel count are not stored anywhere.
+pub fn write_raw(
+    output: &Path,
+    audio: &AudioBuffer,
+    sample_type: SampleType,
+    endianness: Endianness,
+) -> Result<()> {
+    let width = sample_type.bits_per_sample() as usize / 8;
+    let mut bytes = Vec::with_capacity(audio.data.len() * width);
+    for &level in &audio.data {
+        let mut sample = sample_type.encode(level);
+        if endianness == Endianness::Big {
+            sample[..width].reverse();
+        }
+        bytes.extend_from_slice(&sample[..width]);
+    }
+    fs::write(output, bytes)?;
+    Ok(())
+}
+
+/// Parse a sample size given as 16, 24 or 32 (float) bits
+pub(crate) fn parse_sample_type(arg: &str) -> Result<SampleType> {
+    match arg {
+        "16" => Ok(SampleType::Int16),
+        "24" => Ok(SampleType::Int24),
+        "32" => Ok(SampleType::Float32),
+        _ => Err(HousekeepError::InvalidFile(format!(
+            "Invalid sample size: {} (expected 16, 24 or 32)",
+            arg
+        ))),
+    }
+}
+
+/// CLI compatibility layer for raw import and export
+///
+/// Mode 1 imports: `raw 1 infile outfile srate channels sampsize [-b]
+/// [-ooffset]` writes a CDP WAV from raw data. Mode 2 exports: `raw 2
+/// infile outfile sampsize [-b]` writes the samples of a WAV as raw data.
+/// Sample sizes are 16, 24 or 32 (float); `-b` selects big-endian order.
+pub fn raw(mode: i32, args: &[&str]) -> Result<()> {
+    let positional = match mode {
+        1 => 5,
+        2 => 3,
+        _ => {
+            return Err(HousekeepError::UnsupportedFormat(format!(
+                "Raw mode {} not yet implemented",
+                mode
+            )))
+        }
+    };
+    if args.len() < positional {
+        return Err(HousekeepError::InvalidFile(if mode == 1 {
+            "Usage: raw 1 infile outfile srate channels sampsize [-b] [-ooffset]".into()
+        } else {
+            "Usage: raw 2 infile outfile sampsize [-b]".into()
+        }));
+    }
+
+    let mut endianness = Endianness::Little;
+    let mut offset = 0;
+    for arg in &args[positional..] {
+        if *arg == "-b" {
+            endianness = Endianness::Big;
+        } else if let Some(value) = arg.strip_prefix("-o").filter(|_| mode == 1) {
+            offset = value
+                .parse::<u64>()
+                .map_err(|_| HousekeepError::InvalidFile(format!("Invalid offset: {}", value)))?;
+        } else {
+            return Err(HousekeepError::InvalidFile(format!(
+                "Unknown raw flag: {}",
+                arg
+            )));
+        }
+    }
+
+    let input = Path::new(args[0]);
+    let output = Path::new(args[1]);
+    if mode == 2 {
+        let sample_type = parse_sample_type(args[2])?;
+        let (audio, _) = wav_cdp::read_audio_buffer(input)?;
+        return write_raw(output, &audio, sample_type, endianness);
+    }
+
+    let sample_rate = args[2]
+        .parse::<u32>()
+        .map_err(|_| HousekeepError::InvalidFile(format!("Invalid sample rate: {}", args[2])))?;
+    let channels = args[3]
+        .parse::<u16>()
+        .map_err(|_| HousekeepError::InvalidFile(format!("Invalid channel count: {}", args[3])))?;
+    let sample_type = parse_sample_type(args[4])?;
+    let format = RawFormat {
+        endianness,
+        offset,
+        ..RawFormat::new(sample_rate, channels, sample_type)
+    };
+    let audio = read_raw(input, &format)?;
+    wav_cdp::write_audio_buffer(output, &audio, sample_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_raw_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("audio.raw");
+        let audio = AudioBuffer::new(48000, 2, vec![0.5, -0.25, 0.125, -1.0]).unwrap();
+
+        for sample_type in [SampleType::Int16, SampleType::Int24, SampleType::Float32] {
+            for endianness in [Endianness::Little, Endianness::Big] {
+                write_raw(&path, &audio, sample_type, endianness).unwrap();
+                let format = RawFormat {
+                    endianness,
+                    ..RawFormat::new(48000, 2, sample_type)
+                };
+                assert_eq!(read_raw(&path, &format).unwrap(), audio);
+            }
+        }
+
+        // Big-endian 16-bit puts the high byte first
+        write_raw(&path, &audio, SampleType::Int16, Endianness::Big).unwrap();
+        assert_eq!(fs::read(&path).unwrap()[..2], [0x40, 0x00]);
+
+        // Skipping a foreign header and dropping a partial frame
+        let mut bytes = vec![0xAA; 6];
+        bytes.extend(fs::read(&path).unwrap());
+        bytes.push(0);
+        fs::write(&path, &bytes).unwrap();
+        let format = RawFormat {
+            endianness: Endianness::Big,
+            offset: 6,
+            ..RawFormat::new(48000, 2, SampleType::Int16)
+        };
+        assert_eq!(read_raw(&path, &format).unwrap(), audio);
+    }
+}
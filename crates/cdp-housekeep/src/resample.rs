@@ -0,0 +1,259 @@
+//! Sample rate conversion
+//!
+//! Implements CDP's "housekeep respec" mode 1: converting a file to a new
+//! sample rate while keeping its duration and pitch. Conversion runs a
+//! Kaiser-windowed sinc filter in polyphase form, one phase per distinct
+//! fractional input position, so common ratios such as 48000 to 44100
+//! (160:147) are computed exactly. The output is written with a fresh
+//! header and PEAK chunk at the new rate.
+
+use super::wav_cdp;
+use super::{HousekeepError, Result};
+use cdp_core::audio::KaiserSinc;
+use cdp_core::AudioBuffer;
+use std::path::Path;
+
+/// Most filter phases kept in the table; finer ratios use the nearest phase
+const MAX_PHASES: usize = 4096;
+
+/// Filter quality presets, trading speed for passband width and stopband
+/// rejection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ResampleQuality {
+    /// 8 zero crossings each side, about 50 dB rejection
+    Fast,
+    /// 16 zero crossings each side, about 80 dB rejection
+    #[default]
+    Medium,
+    /// 32 zero crossings each side, about 100 dB rejection
+    Best,
+}
+
+impl ResampleQuality {
+    /// Zero crossings of the sinc on each side of the centre tap
+    fn zero_crossings(self) -> usize {
+        match self {
+            ResampleQuality::Fast => 8,
+            ResampleQuality::Medium => 16,
+            ResampleQuality::Best => 32,
+        }
+    }
+
+    /// Cutoff as a fraction of the lower Nyquist frequency
+    fn rolloff(self) -> f64 {
+        match self {
+            ResampleQuality::Fast => 0.85,
+            ResampleQuality::Medium => 0.92,
+            ResampleQuality::Best => 0.95,
+        }
+    }
+
+    /// Kaiser window shape parameter
+    fn beta(self) -> f64 {
+        match self {
+            ResampleQuality::Fast => 5.0,
+            ResampleQuality::Medium => 8.0,
+            ResampleQuality::Best => 10.0,
+        }
+    }
+}
+
+/// Convert a sound file to a new sample rate
+///
+/// The sample type is kept; integer output is clamped where filtering
+/// overshoots full scale.
+pub fn resample(
+    input: &Path,
+    output: &Path,
+    target_rate: u32,
+    quality: ResampleQuality,
+) -> Result<()> {
+    let (audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    let resampled = resample_buf(&audio, target_rate, quality)?;
+    wav_cdp::write_audio_buffer(output, &resampled, sample_type)
+}
+
+/// Convert an [`AudioBuffer`] to a new sample rate
+///
+/// The result holds `ceil(frames * target_rate / sample_rate)` frames.
+pub fn resample_buf(
+    audio: &AudioBuffer,
+    target_rate: u32,
+    quality: ResampleQuality,
+) -> Result<AudioBuffer> {
+    if target_rate == 0 {
+        return Err(HousekeepError::InvalidFile(
+            "Sample rate must be greater than 0".into(),
+        ));
+    }
+    if target_rate == audio.sample_rate {
+        return Ok(audio.clone());
+    }
+
+    // Output frame n sits at input position n * down / up
+    let divisor = gcd(target_rate as u64, audio.sample_rate as u64);
+    let up = target_rate as u64 / divisor;
+    let down = audio.sample_rate as u64 / divisor;
+    let filter = PolyphaseFilter::new(up, down, quality);
+
+    let channels = audio.channels as usize;
+    let frames = audio.frame_count();
+    let out_frames = ((frames as u64 * up + down - 1) / down) as usize;
+    let mut data = Vec::with_capacity(out_frames * channels);
+    for n in 0..out_frames as u64 {
+        let position = n * down;
+        let centre = (position / up) as i64;
+        let taps = filter.phase(position % up);
+        let first = centre - filter.half as i64 + 1;
+        for channel in 0..channels {
+            let mut sum = 0.0f64;
+            for (k, &tap) in taps.iter().enumerate() {
+                let frame = first + k as i64;
+                if (0..frames as i64).contains(&frame) {
+                    sum += tap * audio.data[frame as usize * channels + channel] as f64;
+                }
+            }
+            data.push(sum as f32);
+        }
+    }
+
+    Ok(AudioBuffer::new(target_rate, audio.channels, data)?)
+}
+
+/// Windowed-sinc coefficients for each fractional input position
+struct PolyphaseFilter {
+    /// Taps on each side of the centre
+    half: usize,
+    /// Interpolation factor of the conversion ratio
+    up: u64,
+    /// `phases` rows of `2 * half` taps
+    table: Vec<f64>,
+    phases: usize,
+}
+
+impl PolyphaseFilter {
+    fn new(up: u64, down: u64, quality: ResampleQuality) -> Self {
+        // When downsampling the cutoff falls below the input Nyquist, and
+        // the filter widens to keep the same number of zero crossings
+        let scale = (up as f64 / down as f64).min(1.0);
+        let cutoff = quality.rolloff() * scale;
+        let half = (quality.zero_crossings() as f64 / scale).ceil() as usize;
+        let kernel = KaiserSinc::new(cutoff, half as f64, quality.beta());
+        let phases = (up as usize).min(MAX_PHASES);
+
+        let mut table = Vec::with_capacity(phases * 2 * half);
+        for phase in 0..phases {
+            let fraction = phase as f64 / phases as f64;
+            for k in 0..2 * half {
+                // Distance in input samples from the output position
+                table.push(kernel.tap(k as f64 - half as f64 + 1.0 - fraction));
+            }
+        }
+        Self {
+            half,
+            up,
+            table,
+            phases,
+        }
+    }
+
+    /// Taps for an output position `numerator / up` past an input sample
+    fn phase(&self, numerator: u64) -> &[f64] {
+        let phase = (numerator * self.phases as u64 / self.up) as usize;
+        let width = 2 * self.half;
+        &self.table[phase * width..(phase + 1) * width]
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Parse a quality preset given as 1 (fast), 2 (medium) or 3 (best)
+pub(crate) fn parse_quality(arg: &str) -> Result<ResampleQuality> {
+    match arg {
+        "1" => Ok(ResampleQuality::Fast),
+        "2" => Ok(ResampleQuality::Medium),
+        "3" => Ok(ResampleQuality::Best),
+        _ => Err(HousekeepError::InvalidFile(format!(
+            "Invalid quality: {} (expected 1, 2 or 3)",
+            arg
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+    use tempfile::TempDir;
+
+    fn sine(rate: u32, frequency: f64, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .map(|i| (0.5 * (2.0 * PI * frequency * i as f64 / rate as f64).sin()) as f32)
+            .collect()
+    }
+
+    #[test]
+    fn test_resample_sine() {
+        let audio = AudioBuffer::new(48000, 1, sine(48000, 1000.0, 4800)).unwrap();
+        let resampled = resample_buf(&audio, 44100, ResampleQuality::Medium).unwrap();
+        assert_eq!(resampled.sample_rate, 44100);
+        assert_eq!(resampled.frame_count(), 4410);
+
+        // Away from the edges the tone is reproduced at the new rate
+        let expected = sine(44100, 1000.0, 4410);
+        let error = resampled.data[500..3900]
+            .iter()
+            .zip(&expected[500..3900])
+            .fold(0.0f32, |max, (a, b)| max.max((a - b).abs()));
+        assert!(error < 1e-3, "error {}", error);
+
+        // Upsampling back recovers the original length
+        let back = resample_buf(&resampled, 48000, ResampleQuality::Fast).unwrap();
+        assert_eq!(back.frame_count(), 4800);
+    }
+
+    #[test]
+    fn test_resample_removes_aliases() {
+        // 20 kHz is above the Nyquist frequency of 22.05 kHz / 2
+        let audio = AudioBuffer::new(48000, 2, {
+            let tone = sine(48000, 20000.0, 4800);
+            tone.iter().flat_map(|&s| [s, -s]).collect()
+        })
+        .unwrap();
+        let resampled = resample_buf(&audio, 22050, ResampleQuality::Best).unwrap();
+        let middle = &resampled.data[400..resampled.data.len() - 400];
+        let peak = middle.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+        assert!(peak < 1e-3, "peak {}", peak);
+    }
+
+    #[test]
+    fn test_resample_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let input = temp_dir.path().join("input.wav");
+        let output = temp_dir.path().join("output.wav");
+
+        let audio = AudioBuffer::new(44100, 1, sine(44100, 440.0, 4410)).unwrap();
+        wav_cdp::write_audio_buffer(&input, &audio, wav_cdp::SampleType::Int16).unwrap();
+        resample(&input, &output, 96000, ResampleQuality::Medium).unwrap();
+
+        let (resampled, sample_type) = wav_cdp::read_audio_buffer(&output).unwrap();
+        assert_eq!(sample_type, wav_cdp::SampleType::Int16);
+        assert_eq!(resampled.sample_rate, 96000);
+        assert_eq!(resampled.frame_count(), 9600);
+
+        // The PEAK chunk describes the new sample data
+        let bytes = std::fs::read(&output).unwrap();
+        let peak = bytes.windows(4).position(|w| w == b"PEAK").unwrap() + 16;
+        let value = f32::from_le_bytes(bytes[peak..peak + 4].try_into().unwrap());
+        assert!((value - 0.5).abs() < 2e-3, "peak {}", value);
+
+        assert!(resample(&input, &output, 0, ResampleQuality::Fast).is_err());
+    }
+}
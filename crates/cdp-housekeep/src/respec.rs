@@ -0,0 +1,164 @@
+//! Housekeep respec operation - rewrite declared file properties
+//!
+//! Implements CDP's "housekeep respec" mode 3, which fixes wrongly-tagged
+//! files by changing the header's sample rate or channel count while
+//! leaving the sample data untouched. Mode 1 converts the sample data
+//! itself to a new rate; see [`crate::resample`].
+
+use super::resample::{self, ResampleQuality};
+use super::wav_cdp;
+use super::{HousekeepError, Result};
+use std::path::Path;
+
+/// Rewrite the declared sample rate and/or channel count of a file
+///
+/// Samples are copied unchanged, so a new sample rate alters playback speed
+/// and pitch, and a new channel count regroups the existing interleaved
+/// samples into frames of a different width.
+pub fn respecify(
+    input: &Path,
+    output: &Path,
+    sample_rate: Option<u32>,
+    channels: Option<u16>,
+) -> Result<()> {
+    let mut reader = wav_cdp::WavReader::open(input)?;
+    let format = reader.format().clone();
+
+    let mut new_format = format.clone();
+    if let Some(rate) = sample_rate {
+        if rate == 0 {
+            return Err(HousekeepError::InvalidFile(
+                "Sample rate must be greater than 0".into(),
+            ));
+        }
+        new_format.sample_rate = rate;
+    }
+    if let Some(channels) = channels {
+        if channels == 0 {
+            return Err(HousekeepError::InvalidFile(
+                "Channel count must be 1 or greater".into(),
+            ));
+        }
+        // Whole frames must still fit the existing sample data
+        if reader.remaining_samples() % channels as usize != 0 {
+            return Err(HousekeepError::InvalidFile(format!(
+                "{} samples cannot be split into {} channels",
+                reader.remaining_samples(),
+                channels
+            )));
+        }
+        new_format.channels = channels;
+    }
+
+    let mut writer = wav_cdp::WavWriter::create(output, &new_format)?;
+    let mut block = vec![0.0f32; wav_cdp::DEFAULT_BLOCK_FRAMES * format.channels as usize];
+    loop {
+        let count = reader.read_block(&mut block)?;
+        if count == 0 {
+            break;
+        }
+        writer.write_block(&block[..count])?;
+    }
+
+    writer.finalize()?;
+    Ok(())
+}
+
+/// CLI compatibility layer for respec operations
+pub fn respec(mode: i32, args: &[&str]) -> Result<()> {
+    match mode {
+        1 => {
+            // Resample to a new rate
+            if args.len() < 3 {
+                return Err(HousekeepError::InvalidFile(
+                    "Usage: respec 1 infile outfile srate [-qquality]".into(),
+                ));
+            }
+            let sample_rate = args[2].parse::<u32>().map_err(|_| {
+                HousekeepError::InvalidFile(format!("Invalid sample rate: {}", args[2]))
+            })?;
+            let mut quality = ResampleQuality::default();
+            for arg in &args[3..] {
+                if let Some(value) = arg.strip_prefix("-q") {
+                    quality = resample::parse_quality(value)?;
+                } else {
+                    return Err(HousekeepError::InvalidFile(format!(
+                        "Unknown respec flag: {}",
+                        arg
+                    )));
+                }
+            }
+            resample::resample(Path::new(args[0]), Path::new(args[1]), sample_rate, quality)
+        }
+        2 => Err(HousekeepError::UnsupportedFormat(format!(
+            "Respec mode {} not yet implemented",
+            mode
+        ))),
+        3 => {
+            // Change sample rate and/or channel count
+            if args.len() < 3 {
+                return Err(HousekeepError::InvalidFile(
+                    "Usage: respec 3 infile outfile [-ssrate] [-cchannels]".into(),
+                ));
+            }
+            let input = Path::new(args[0]);
+            let output = Path::new(args[1]);
+
+            let mut sample_rate = None;
+            let mut channels = None;
+            for arg in &args[2..] {
+                if let Some(value) = arg.strip_prefix("-s") {
+                    sample_rate = Some(value.parse::<u32>().map_err(|_| {
+                        HousekeepError::InvalidFile(format!("Invalid sample rate: {}", value))
+                    })?);
+                } else if let Some(value) = arg.strip_prefix("-c") {
+                    channels = Some(value.parse::<u16>().map_err(|_| {
+                        HousekeepError::InvalidFile(format!("Invalid channel count: {}", value))
+                    })?);
+                } else {
+                    return Err(HousekeepError::InvalidFile(format!(
+                        "Unknown respec flag: {}",
+                        arg
+                    )));
+                }
+            }
+            respecify(input, output, sample_rate, channels)
+        }
+        _ => Err(HousekeepError::UnsupportedFormat(format!(
+            "Unknown respec mode: {}",
+            mode
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_respecify_keeps_samples() {
+        let temp_dir = TempDir::new().unwrap();
+        let input = temp_dir.path().join("input.wav");
+        let output = temp_dir.path().join("output.wav");
+
+        let format = wav_cdp::WavFormat {
+            channels: 1,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            data_size: 0,
+        };
+        let samples = vec![10, -20, 30, -40];
+        wav_cdp::write_wav_cdp(&input, &format, &samples).unwrap();
+
+        respecify(&input, &output, Some(48000), Some(2)).unwrap();
+
+        let (out_format, out_samples) = wav_cdp::read_wav_basic(&output).unwrap();
+        assert_eq!(out_format.sample_rate, 48000);
+        assert_eq!(out_format.channels, 2);
+        assert_eq!(out_samples, samples);
+
+        // Samples that don't divide into whole frames are rejected
+        assert!(respecify(&input, &output, None, Some(3)).is_err());
+    }
+}
@@ -4,13 +4,23 @@
 //! cue points, and LIST metadata.
 
 use super::Result;
-use std::fs::File;
-use std::io::{self, BufReader, BufWriter, Read, Write};
+use cdp_core::fs::{self, File};
+use cdp_core::AudioBuffer;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+/// Default number of sample frames processed per block by streaming operations
+pub const DEFAULT_BLOCK_FRAMES: usize = 8192;
+
+/// fmt chunk format tag for IEEE float data
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+
+/// fmt chunk format tag for WAVE_FORMAT_EXTENSIBLE headers
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
 /// WAV format information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct WavFormat {
     pub channels: u16,
     pub sample_rate: u32,
@@ -18,13 +28,197 @@ pub struct WavFormat {
     pub data_size: u32,
 }
 
-/// CDP-specific PEAK chunk
+impl WavFormat {
+    /// Bytes used to store one sample
+    pub fn bytes_per_sample(&self) -> usize {
+        self.bits_per_sample as usize / 8
+    }
+
+    /// Largest positive sample value at this bit depth
+    pub fn max_sample_value(&self) -> i32 {
+        (1i32 << (self.bits_per_sample - 1)) - 1
+    }
+
+    /// Number of sample frames in the data chunk
+    pub fn frame_count(&self) -> usize {
+        self.data_size as usize / (self.bytes_per_sample() * self.channels.max(1) as usize)
+    }
+
+    /// Sample encoding implied by the bit depth
+    ///
+    /// 16- and 24-bit data is integer PCM; 32-bit data is IEEE float.
+    pub fn sample_type(&self) -> io::Result<SampleType> {
+        match self.bits_per_sample {
+            16 => Ok(SampleType::Int16),
+            24 => Ok(SampleType::Int24),
+            32 => Ok(SampleType::Float32),
+            bits => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unsupported bit depth: {}", bits),
+            )),
+        }
+    }
+}
+
+/// Sample encodings supported by the CDP WAV I/O
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SampleType {
+    /// 16-bit signed integer PCM
+    Int16,
+    /// 24-bit signed integer PCM
+    Int24,
+    /// 32-bit IEEE float
+    Float32,
+}
+
+impl SampleType {
+    /// Bits used to store one sample
+    pub fn bits_per_sample(self) -> u16 {
+        match self {
+            SampleType::Int16 => 16,
+            SampleType::Int24 => 24,
+            SampleType::Float32 => 32,
+        }
+    }
+
+    /// Whether samples are stored as IEEE float
+    pub fn is_float(self) -> bool {
+        self == SampleType::Float32
+    }
+
+    /// Integer value corresponding to a float level of 1.0
+    pub(crate) fn full_scale(self) -> f32 {
+        match self {
+            SampleType::Int16 => 32768.0,
+            SampleType::Int24 => 8_388_608.0,
+            SampleType::Float32 => 1.0,
+        }
+    }
+
+    /// Quantize a float level to this integer encoding
+    ///
+    /// Truncates toward zero and clamps to the representable range.
+    fn quantize(self, sample: f32) -> i32 {
+        let full_scale = self.full_scale();
+        let max_value = full_scale as i32 - 1;
+        ((sample * full_scale) as i32).clamp(-max_value - 1, max_value)
+    }
+
+    /// Encode a float level as little-endian bytes, quantizing as the writer does
+    ///
+    /// Only the first `bits_per_sample / 8` bytes are meaningful.
+    pub(crate) fn encode(self, sample: f32) -> [u8; 4] {
+        if self.is_float() {
+            sample.to_le_bytes()
+        } else {
+            self.quantize(sample).to_le_bytes()
+        }
+    }
+
+    /// Decode one little-endian sample to a float level
+    pub(crate) fn decode(self, bytes: &[u8]) -> f32 {
+        match self {
+            SampleType::Int16 => i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32768.0,
+            SampleType::Int24 => {
+                (i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8) as f32 / 8_388_608.0
+            }
+            SampleType::Float32 => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        }
+    }
+}
+
+impl std::fmt::Display for SampleType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SampleType::Int16 => write!(f, "16bit"),
+            SampleType::Int24 => write!(f, "24bit"),
+            SampleType::Float32 => write!(f, "32bit float"),
+        }
+    }
+}
+
+/// Sample data in its native encoding
+///
+/// Integer variants hold values at their native scale; conversion to and
+/// from float levels uses power-of-two scaling, so integer data survives a
+/// round trip through [`Samples::to_f32`] and [`Samples::from_f32`] exactly.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Samples {
+    /// 16-bit integer samples
+    I16(Vec<i16>),
+    /// 24-bit integer samples, sign-extended into i32
+    I24(Vec<i32>),
+    /// 32-bit float samples
+    F32(Vec<f32>),
+}
+
+impl Samples {
+    /// Encoding of these samples
+    pub fn sample_type(&self) -> SampleType {
+        match self {
+            Samples::I16(_) => SampleType::Int16,
+            Samples::I24(_) => SampleType::Int24,
+            Samples::F32(_) => SampleType::Float32,
+        }
+    }
+
+    /// Number of samples (not frames)
+    pub fn len(&self) -> usize {
+        match self {
+            Samples::I16(samples) => samples.len(),
+            Samples::I24(samples) => samples.len(),
+            Samples::F32(samples) => samples.len(),
+        }
+    }
+
+    /// Whether there are no samples
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Convert to float levels (full scale = 1.0)
+    pub fn to_f32(&self) -> Vec<f32> {
+        match self {
+            Samples::I16(samples) => samples.iter().map(|&s| s as f32 / 32768.0).collect(),
+            Samples::I24(samples) => samples.iter().map(|&s| s as f32 / 8_388_608.0).collect(),
+            Samples::F32(samples) => samples.clone(),
+        }
+    }
+
+    /// Encode float levels as the given sample type
+    pub fn from_f32(sample_type: SampleType, samples: &[f32]) -> Self {
+        match sample_type {
+            SampleType::Int16 => Samples::I16(
+                samples
+                    .iter()
+                    .map(|&s| sample_type.quantize(s) as i16)
+                    .collect(),
+            ),
+            SampleType::Int24 => {
+                Samples::I24(samples.iter().map(|&s| sample_type.quantize(s)).collect())
+            }
+            SampleType::Float32 => Samples::F32(samples.to_vec()),
+        }
+    }
+}
+
+/// Peak level of one channel, as stored in the PEAK chunk
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelPeak {
+    /// Peak magnitude, relative to full scale for integer formats
+    pub value: f32,
+    /// Frame at which the peak first occurs
+    pub position: u32,
+}
+
+/// CDP-specific PEAK chunk, holding one peak per channel
 #[derive(Debug, Clone)]
 pub struct PeakChunk {
     pub version: u32,
     pub timestamp: u32,
-    pub peak_value: f32,
-    pub peak_position: u32,
+    pub peaks: Vec<ChannelPeak>,
 }
 
 #[derive(Debug, Clone)]
@@ -47,6 +241,25 @@ pub struct ListChunk {
     pub note_data: Vec<u8>,
 }
 
+/// A RIFF chunk that the CDP writer does not manage, kept byte for byte
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawChunk {
+    pub id: [u8; 4],
+    pub data: Vec<u8>,
+}
+
+/// What to do with chunks other than fmt, PEAK, cue, LIST, fact and data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum UnknownChunks {
+    /// Drop them, as CDP does
+    #[default]
+    Discard,
+    /// Write them back unchanged after the CDP chunks
+    Preserve,
+}
+
 /// CDP metadata chunks
 #[derive(Debug, Clone)]
 pub struct CdpChunks {
@@ -55,55 +268,485 @@ pub struct CdpChunks {
     pub list: ListChunk,
 }
 
-/// Read a WAV file (basic version without CDP metadata)
+/// Read a 16-bit WAV file (basic version without CDP metadata)
 pub fn read_wav_basic(input: &Path) -> io::Result<(WavFormat, Vec<i16>)> {
-    let mut reader = BufReader::new(File::open(input)?);
-    read_wav(&mut reader)
+    match read_wav_samples(input)? {
+        (format, Samples::I16(samples)) => Ok((format, samples)),
+        (_, other) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Expected 16-bit samples, found {}", other.sample_type()),
+        )),
+    }
 }
 
-/// Write a WAV file with CDP metadata (for internal use)
+/// Write a 16-bit WAV file with CDP metadata (for internal use)
 pub fn write_wav_cdp(output: &Path, format: &WavFormat, samples: &[i16]) -> io::Result<()> {
-    // Calculate peak
-    let (peak_value, peak_position) = calculate_peak(samples);
-
-    // Create CDP chunks
-    let cdp_chunks = create_cdp_chunks(
-        peak_value,
-        peak_position,
-        samples.len() as u32 / (format.channels as u32),
-    );
-
-    // Write output
-    let mut writer = BufWriter::new(File::create(output)?);
-    write_wav_cdp_internal(&mut writer, format, samples, &cdp_chunks)?;
-    writer.flush()?;
+    write_wav_cdp_samples(output, format, &Samples::I16(samples.to_vec()))
+}
+
+/// Read a 16- or 24-bit WAV file, returning samples at their native scale
+pub fn read_wav_int(input: &Path) -> io::Result<(WavFormat, Vec<i32>)> {
+    match read_wav_samples(input)? {
+        (format, Samples::I16(samples)) => {
+            Ok((format, samples.into_iter().map(|s| s as i32).collect()))
+        }
+        (format, Samples::I24(samples)) => Ok((format, samples)),
+        (_, other) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Expected integer samples, found {}", other.sample_type()),
+        )),
+    }
+}
+
+/// Write a 16- or 24-bit WAV file with CDP metadata
+///
+/// Samples are at the native scale of `format.bits_per_sample`.
+pub fn write_wav_cdp_int(output: &Path, format: &WavFormat, samples: &[i32]) -> io::Result<()> {
+    let samples = match format.sample_type()? {
+        SampleType::Int16 => Samples::I16(samples.iter().map(|&s| s as i16).collect()),
+        SampleType::Int24 => Samples::I24(samples.to_vec()),
+        SampleType::Float32 => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Integer samples cannot be written to a float format",
+            ))
+        }
+    };
+    write_wav_cdp_samples(output, format, &samples)
+}
+
+/// Read a 16-bit, 24-bit or float WAV file in its native encoding
+pub fn read_wav_samples(input: &Path) -> io::Result<(WavFormat, Samples)> {
+    let mut reader = BufReader::new(fs::open(input)?);
+    read_wav(&mut reader)
+}
+
+/// Write samples in their native encoding with CDP metadata
+///
+/// The bit depth is taken from `samples`; `format` supplies the channel
+/// count and sample rate.
+pub fn write_wav_cdp_samples(
+    output: &Path,
+    format: &WavFormat,
+    samples: &Samples,
+) -> io::Result<()> {
+    let mut format = format.clone();
+    format.bits_per_sample = samples.sample_type().bits_per_sample();
+
+    let mut writer = WavWriter::create(output, &format)?;
+    writer.write_samples(samples)?;
+    writer.finalize()
+}
+
+/// Read a WAV file into an [`AudioBuffer`] of float levels
+///
+/// Also returns the file's sample type so the buffer can be written back
+/// at the same resolution.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(input = %input.display()))
+)]
+pub fn read_audio_buffer(input: &Path) -> Result<(AudioBuffer, SampleType)> {
+    read_audio_buffer_from(BufReader::new(fs::open(input)?))
+}
+
+/// Read WAV data from any source, such as bytes held in memory, into an
+/// [`AudioBuffer`] of float levels
+pub fn read_audio_buffer_from<R: Read>(reader: R) -> Result<(AudioBuffer, SampleType)> {
+    let mut reader = WavReader::new(reader)?;
+    let format = reader.format().clone();
+    let mut data = vec![0.0f32; reader.remaining_samples()];
+    let count = reader.read_block(&mut data)?;
+    data.truncate(count);
+
+    let buffer = AudioBuffer::new(format.sample_rate, format.channels, data)?;
+    Ok((buffer, reader.sample_type()))
+}
+
+/// Write an [`AudioBuffer`] as a CDP-format WAV with the given sample type
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(output = %output.display()))
+)]
+pub fn write_audio_buffer(
+    output: &Path,
+    buffer: &AudioBuffer,
+    sample_type: SampleType,
+) -> Result<()> {
+    write_audio_buffer_to(BufWriter::new(fs::create(output)?), buffer, sample_type)
+}
+
+/// Write an [`AudioBuffer`] as CDP-format WAV data to any destination,
+/// such as a buffer in memory
+pub fn write_audio_buffer_to<W: Write + Seek>(
+    writer: W,
+    buffer: &AudioBuffer,
+    sample_type: SampleType,
+) -> Result<()> {
+    let format = WavFormat {
+        channels: buffer.channels,
+        sample_rate: buffer.sample_rate,
+        bits_per_sample: sample_type.bits_per_sample(),
+        data_size: (buffer.data.len() * sample_type.bits_per_sample() as usize / 8) as u32,
+    };
+
+    let mut writer = WavWriter::new(writer, &format)?;
+    writer.write_block(&buffer.data)?;
+    writer.finalize()?;
     Ok(())
 }
 
 /// Copy a WAV file with CDP metadata
 pub fn copy_wav_cdp(input: &Path, output: &Path) -> Result<()> {
-    let mut reader = BufReader::new(File::open(input)?);
-    let (format, samples) = read_wav(&mut reader)?;
-
-    // Calculate peak while reading
-    let (peak_value, peak_position) = calculate_peak(&samples);
-
-    // Create CDP chunks
-    let cdp_chunks = create_cdp_chunks(
-        peak_value,
-        peak_position,
-        samples.len() as u32 / (format.channels as u32),
-    );
-
-    // Write output
-    let mut writer = BufWriter::new(File::create(output)?);
-    write_wav_cdp_internal(&mut writer, &format, &samples, &cdp_chunks)?;
-    writer.flush()?;
+    copy_wav_cdp_blocks(input, output, DEFAULT_BLOCK_FRAMES)
+}
+
+/// Copy a WAV file with CDP metadata, choosing whether unknown chunks survive
+///
+/// PEAK, cue and LIST are always regenerated; with
+/// [`UnknownChunks::Preserve`] any other chunks (smpl, inst, vendor data)
+/// are carried over byte for byte.
+pub fn copy_wav_cdp_with(input: &Path, output: &Path, unknown: UnknownChunks) -> Result<()> {
+    copy_wav(input, output, DEFAULT_BLOCK_FRAMES, unknown)
+}
+
+/// Copy a WAV file with CDP metadata, streaming `block_frames` frames at a time
+pub fn copy_wav_cdp_blocks(input: &Path, output: &Path, block_frames: usize) -> Result<()> {
+    copy_wav(input, output, block_frames, UnknownChunks::Discard)
+}
+
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(input = %input.display(), output = %output.display()),
+        err
+    )
+)]
+fn copy_wav(
+    input: &Path,
+    output: &Path,
+    block_frames: usize,
+    unknown: UnknownChunks,
+) -> Result<()> {
+    let mut reader = WavReader::open(input)?;
+    let format = reader.format().clone();
+    let extra_chunks = match unknown {
+        UnknownChunks::Discard => &[][..],
+        UnknownChunks::Preserve => reader.extra_chunks(),
+    };
+    let mut writer = WavWriter::create_with_chunks(output, &format, extra_chunks)?;
+
+    let mut buf = vec![0.0f32; block_frames.max(1) * format.channels.max(1) as usize];
+    loop {
+        let count = reader.read_block(&mut buf)?;
+        if count == 0 {
+            break;
+        }
+        writer.write_block(&buf[..count])?;
+    }
+
+    writer.finalize()?;
     Ok(())
 }
 
+/// Scan a WAV file for its peak level and the sample index where it occurs
+///
+/// Integer formats report the peak relative to the largest positive sample
+/// value (capped at 1.0); float formats report the raw magnitude.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(input = %input.display()), err)
+)]
+pub fn scan_peak(input: &Path) -> io::Result<(f32, usize)> {
+    let mut reader = WavReader::open(input)?;
+    let format = reader.format().clone();
+    let sample_type = reader.sample_type();
+
+    let mut buf = vec![0.0f32; DEFAULT_BLOCK_FRAMES * format.channels.max(1) as usize];
+    let mut peak = 0.0f32;
+    let mut position = 0;
+    let mut offset = 0;
+    loop {
+        let count = reader.read_block(&mut buf)?;
+        if count == 0 {
+            break;
+        }
+        for (i, &sample) in buf[..count].iter().enumerate() {
+            if sample.abs() > peak {
+                peak = sample.abs();
+                position = offset + i;
+            }
+        }
+        offset += count;
+    }
+
+    if !sample_type.is_float() {
+        let max_value = format.max_sample_value() as f32;
+        peak = (peak * sample_type.full_scale()).min(max_value) / max_value;
+    }
+    #[cfg(feature = "tracing")]
+    tracing::debug!(peak, position, "peak computed");
+    Ok((peak, position))
+}
+
+/// Streaming reader over the sample data of a WAV file
+///
+/// The header is parsed on construction; samples are then pulled in blocks
+/// so arbitrarily large files can be processed with bounded memory.
+pub struct WavReader<R: Read> {
+    reader: R,
+    format: WavFormat,
+    sample_type: SampleType,
+    extra_chunks: Vec<RawChunk>,
+    remaining: usize,
+}
+
+impl WavReader<BufReader<File>> {
+    /// Open a WAV file for streaming
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %input.display()))
+    )]
+    pub fn open(input: &Path) -> io::Result<Self> {
+        Self::new(BufReader::new(fs::open(input)?))
+    }
+}
+
+impl<R: Read> WavReader<R> {
+    /// Parse the WAV header, leaving the reader positioned at the first sample
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let (format, extra_chunks) = read_header(&mut reader)?;
+        let sample_type = format.sample_type()?;
+        let remaining = format.data_size as usize / format.bytes_per_sample();
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            channels = format.channels,
+            sample_rate = format.sample_rate,
+            ?sample_type,
+            frames = format.frame_count(),
+            "file opened"
+        );
+        Ok(Self {
+            reader,
+            format,
+            sample_type,
+            extra_chunks,
+            remaining,
+        })
+    }
+
+    /// Format of the file being read
+    pub fn format(&self) -> &WavFormat {
+        &self.format
+    }
+
+    /// Encoding of the samples being read
+    pub fn sample_type(&self) -> SampleType {
+        self.sample_type
+    }
+
+    /// Chunks before the data that the CDP writer does not manage
+    pub fn extra_chunks(&self) -> &[RawChunk] {
+        &self.extra_chunks
+    }
+
+    /// Number of samples (not frames) left to read
+    pub fn remaining_samples(&self) -> usize {
+        self.remaining
+    }
+
+    /// Read up to `buf.len()` interleaved samples as float levels
+    ///
+    /// Integer samples are scaled so full scale is 1.0. Returns the number of
+    /// samples read, or 0 once the data chunk is exhausted.
+    pub fn read_block(&mut self, buf: &mut [f32]) -> io::Result<usize> {
+        let count = buf.len().min(self.remaining);
+        let width = self.format.bytes_per_sample();
+        let mut bytes = vec![0u8; count * width];
+        self.reader.read_exact(&mut bytes)?;
+
+        for (sample, chunk) in buf.iter_mut().zip(bytes.chunks_exact(width)) {
+            *sample = self.sample_type.decode(chunk);
+        }
+
+        self.remaining -= count;
+        Ok(count)
+    }
+
+    /// Read up to `max_samples` interleaved samples in their native encoding
+    pub fn read_samples(&mut self, max_samples: usize) -> io::Result<Samples> {
+        let mut levels = vec![0.0f32; max_samples.min(self.remaining)];
+        let count = self.read_block(&mut levels)?;
+        Ok(Samples::from_f32(self.sample_type, &levels[..count]))
+    }
+}
+
+/// Streaming writer producing a CDP-format WAV file
+///
+/// The CDP header is written up front with placeholder sizes; the PEAK chunk
+/// and chunk sizes are patched in by [`WavWriter::finalize`].
+pub struct WavWriter<W: Write + Seek> {
+    writer: W,
+    format: WavFormat,
+    sample_type: SampleType,
+    chunks: CdpChunks,
+    offsets: HeaderOffsets,
+    samples_written: u32,
+    peak_magnitudes: Vec<f32>,
+}
+
+impl WavWriter<BufWriter<File>> {
+    /// Create a CDP-format WAV file for streaming output
+    pub fn create(output: &Path, format: &WavFormat) -> io::Result<Self> {
+        Self::create_with_chunks(output, format, &[])
+    }
+
+    /// Create a CDP-format WAV file that also carries `extra_chunks`
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %output.display()))
+    )]
+    pub fn create_with_chunks(
+        output: &Path,
+        format: &WavFormat,
+        extra_chunks: &[RawChunk],
+    ) -> io::Result<Self> {
+        Self::new_with_chunks(BufWriter::new(fs::create(output)?), format, extra_chunks)
+    }
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    /// Write the CDP header and prepare for sample data
+    pub fn new(writer: W, format: &WavFormat) -> io::Result<Self> {
+        Self::new_with_chunks(writer, format, &[])
+    }
+
+    /// Write the CDP header followed by `extra_chunks`, ahead of the data
+    pub fn new_with_chunks(
+        mut writer: W,
+        format: &WavFormat,
+        extra_chunks: &[RawChunk],
+    ) -> io::Result<Self> {
+        let sample_type = format.sample_type()?;
+        let chunks = create_cdp_chunks(format.channels);
+        let offsets = write_cdp_header(&mut writer, format, &chunks, extra_chunks, 0)?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            channels = format.channels,
+            sample_rate = format.sample_rate,
+            ?sample_type,
+            extra_chunks = extra_chunks.len(),
+            "header written"
+        );
+        Ok(Self {
+            writer,
+            format: format.clone(),
+            sample_type,
+            chunks,
+            offsets,
+            samples_written: 0,
+            peak_magnitudes: vec![0.0; format.channels as usize],
+        })
+    }
+
+    /// Append a block of interleaved float levels
+    ///
+    /// Integer formats are quantized by truncation and clamped to range;
+    /// float formats are written unchanged.
+    pub fn write_block(&mut self, samples: &[f32]) -> io::Result<()> {
+        let width = self.format.bytes_per_sample();
+        let channels = self.peak_magnitudes.len().max(1);
+        let mut bytes = Vec::with_capacity(samples.len() * width);
+
+        for (i, &sample) in samples.iter().enumerate() {
+            let magnitude = if self.sample_type.is_float() {
+                bytes.extend_from_slice(&sample.to_le_bytes());
+                sample.abs()
+            } else {
+                let quantized = self.sample_type.quantize(sample);
+                bytes.extend_from_slice(&quantized.to_le_bytes()[..width]);
+                quantized.unsigned_abs() as f32
+            };
+
+            let index = self.samples_written as usize + i;
+            let channel = index % channels;
+            if magnitude > self.peak_magnitudes[channel] {
+                self.peak_magnitudes[channel] = magnitude;
+                self.chunks.peak.peaks[channel].position = (index / channels) as u32;
+            }
+        }
+        self.writer.write_all(&bytes)?;
+
+        let scale = if self.sample_type.is_float() {
+            1.0
+        } else {
+            self.format.max_sample_value() as f32
+        };
+        for (peak, &magnitude) in self.chunks.peak.peaks.iter_mut().zip(&self.peak_magnitudes) {
+            peak.value = magnitude / scale;
+        }
+        self.samples_written += samples.len() as u32;
+        #[cfg(feature = "tracing")]
+        tracing::trace!(samples = samples.len(), "chunk written");
+        Ok(())
+    }
+
+    /// Append interleaved samples in their native encoding
+    pub fn write_samples(&mut self, samples: &Samples) -> io::Result<()> {
+        self.write_block(&samples.to_f32())
+    }
+
+    /// Patch sizes and PEAK data into the header and flush the output
+    pub fn finalize(mut self) -> io::Result<()> {
+        let data_size = self.samples_written * self.format.bytes_per_sample() as u32;
+        let riff_size = self.offsets.data_start - 8 + data_size as u64;
+
+        self.writer.seek(SeekFrom::Start(4))?;
+        self.writer.write_all(&(riff_size as u32).to_le_bytes())?;
+
+        self.writer.seek(SeekFrom::Start(self.offsets.peaks))?;
+        for peak in &self.chunks.peak.peaks {
+            self.writer.write_all(&peak.value.to_le_bytes())?;
+            self.writer.write_all(&peak.position.to_le_bytes())?;
+        }
+
+        self.writer
+            .seek(SeekFrom::Start(self.offsets.data_start - 4))?;
+        self.writer.write_all(&data_size.to_le_bytes())?;
+
+        self.writer.seek(SeekFrom::End(0))?;
+        self.writer.flush()?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            samples = self.samples_written,
+            peaks = ?self.chunks.peak.peaks.iter().map(|peak| peak.value).collect::<Vec<_>>(),
+            "PEAK chunk written"
+        );
+        Ok(())
+    }
+}
+
 /// Read WAV file (handles both simple and CDP-format WAVs)
-fn read_wav<R: Read>(reader: &mut R) -> io::Result<(WavFormat, Vec<i16>)> {
+fn read_wav<R: Read>(reader: &mut R) -> io::Result<(WavFormat, Samples)> {
+    let mut wav = WavReader::new(reader)?;
+    let samples = wav.read_samples(wav.remaining_samples())?;
+
+    if samples.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Missing fmt or data chunk",
+        ));
+    }
+
+    Ok((wav.format, samples))
+}
+
+/// Read chunks up to the start of the data chunk
+///
+/// Returns the format and any chunks the CDP writer does not regenerate.
+fn read_header<R: Read>(reader: &mut R) -> io::Result<(WavFormat, Vec<RawChunk>)> {
     let mut header = [0u8; 12];
     reader.read_exact(&mut header)?;
 
@@ -120,7 +763,7 @@ fn read_wav<R: Read>(reader: &mut R) -> io::Result<(WavFormat, Vec<i16>)> {
 
     // Now read chunks until we find fmt and data
     let mut format: Option<WavFormat> = None;
-    let mut samples = Vec::new();
+    let mut extra_chunks = Vec::new();
 
     loop {
         let mut chunk_header = [0u8; 8];
@@ -142,6 +785,22 @@ fn read_wav<R: Read>(reader: &mut R) -> io::Result<(WavFormat, Vec<i16>)> {
                 let mut fmt_data = vec![0u8; chunk_size as usize];
                 reader.read_exact(&mut fmt_data)?;
 
+                // Only 32-bit data may be float, and only float may be 32-bit
+                let mut format_tag = u16::from_le_bytes([fmt_data[0], fmt_data[1]]);
+                if format_tag == WAVE_FORMAT_EXTENSIBLE && fmt_data.len() >= 26 {
+                    format_tag = u16::from_le_bytes([fmt_data[24], fmt_data[25]]);
+                }
+                let bits_per_sample = u16::from_le_bytes([fmt_data[14], fmt_data[15]]);
+                if (format_tag == WAVE_FORMAT_IEEE_FLOAT) != (bits_per_sample == 32) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "Unsupported sample format: tag {} with {} bits",
+                            format_tag, bits_per_sample
+                        ),
+                    ));
+                }
+
                 format = Some(WavFormat {
                     channels: u16::from_le_bytes([fmt_data[2], fmt_data[3]]),
                     sample_rate: u32::from_le_bytes([
@@ -150,31 +809,29 @@ fn read_wav<R: Read>(reader: &mut R) -> io::Result<(WavFormat, Vec<i16>)> {
                         fmt_data[6],
                         fmt_data[7],
                     ]),
-                    bits_per_sample: u16::from_le_bytes([fmt_data[14], fmt_data[15]]),
+                    bits_per_sample,
                     data_size: 0, // Will be set when we find data chunk
                 });
             }
             b"data" => {
-                // Read data chunk
-                if let Some(ref mut fmt) = format {
+                // Samples follow directly; leave the reader positioned here
+                if let Some(mut fmt) = format {
                     fmt.data_size = chunk_size;
-
-                    // Read all samples (assuming 16-bit)
-                    let sample_count = chunk_size as usize / 2;
-                    samples = vec![0i16; sample_count];
-
-                    for sample in &mut samples {
-                        let mut buf = [0u8; 2];
-                        reader.read_exact(&mut buf)?;
-                        *sample = i16::from_le_bytes(buf);
-                    }
-                    break; // We have everything we need
+                    return Ok((fmt, extra_chunks));
                 }
             }
+            b"PEAK" | b"cue " | b"LIST" | b"fact" => {
+                // Regenerated (or not written) by the CDP writer
+                io::copy(&mut reader.take(chunk_size as u64), &mut io::sink())?;
+            }
             _ => {
-                // Skip unknown chunks
-                let mut skip_buf = vec![0u8; chunk_size as usize];
-                reader.read_exact(&mut skip_buf)?;
+                // Keep unknown chunks for callers that pass them through
+                let mut data = vec![0u8; chunk_size as usize];
+                reader.read_exact(&mut data)?;
+                extra_chunks.push(RawChunk {
+                    id: [chunk_id[0], chunk_id[1], chunk_id[2], chunk_id[3]],
+                    data,
+                });
             }
         }
 
@@ -185,40 +842,28 @@ fn read_wav<R: Read>(reader: &mut R) -> io::Result<(WavFormat, Vec<i16>)> {
         }
     }
 
-    if let Some(format) = format {
-        if !samples.is_empty() {
-            return Ok((format, samples));
-        }
-    }
-
     Err(io::Error::new(
         io::ErrorKind::InvalidData,
         "Missing fmt or data chunk",
     ))
 }
 
-/// Calculate peak value from samples  
-fn calculate_peak(samples: &[i16]) -> (f32, u32) {
-    let mut max_sample = 0i16;
-    let mut peak_position = 0u32;
-
-    for (i, &sample) in samples.iter().enumerate() {
-        let abs_sample = sample.abs();
-        if abs_sample > max_sample {
-            max_sample = abs_sample;
-            peak_position = i as u32;
-        }
+/// Seconds since the Unix epoch, for the note chunk's DATE entry
+///
+/// WebAssembly without WASI has no clock, so files written there carry 0.
+fn timestamp() -> u32 {
+    if cfg!(all(target_arch = "wasm32", target_os = "unknown")) {
+        return 0;
     }
-
-    (max_sample as f32 / 32767.0, peak_position)
-}
-
-/// Create CDP-specific chunks
-fn create_cdp_chunks(peak_value: f32, peak_position: u32, _frame_count: u32) -> CdpChunks {
-    let timestamp = SystemTime::now()
+    SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
-        .as_secs() as u32;
+        .as_secs() as u32
+}
+
+/// Create CDP-specific chunks, with an empty peak for each channel
+fn create_cdp_chunks(channels: u16) -> CdpChunks {
+    let timestamp = timestamp();
 
     // Create CDP's fixed-size note chunk (2004 bytes)
     let mut note_data = Vec::with_capacity(2004);
@@ -239,8 +884,13 @@ fn create_cdp_chunks(peak_value: f32, peak_position: u32, _frame_count: u32) ->
         peak: PeakChunk {
             version: 1,
             timestamp,
-            peak_value,
-            peak_position,
+            peaks: vec![
+                ChannelPeak {
+                    value: 0.0,
+                    position: 0,
+                };
+                channels as usize
+            ],
         },
         cue: CueChunk {
             cue_points: vec![CuePoint {
@@ -256,17 +906,27 @@ fn create_cdp_chunks(peak_value: f32, peak_position: u32, _frame_count: u32) ->
     }
 }
 
-/// Write WAV file with CDP chunks
-fn write_wav_cdp_internal<W: Write>(
+/// Byte offsets of header fields patched after streaming
+struct HeaderOffsets {
+    /// Offset of the first channel's PEAK value; each value is followed by
+    /// its position, then the next channel's pair
+    peaks: u64,
+    /// Offset of the first sample in the data chunk
+    data_start: u64,
+}
+
+/// Write the CDP header (fmt, PEAK, cue, LIST, then any extra chunks) up to
+/// the start of sample data
+fn write_cdp_header<W: Write + Seek>(
     writer: &mut W,
     format: &WavFormat,
-    samples: &[i16],
     cdp_chunks: &CdpChunks,
-) -> io::Result<()> {
+    extra_chunks: &[RawChunk],
+    data_size: usize,
+) -> io::Result<HeaderOffsets> {
     // Calculate sizes
-    let data_size = samples.len() * 2;
     let fmt_chunk_size = 16;
-    let peak_chunk_size = 16; // 4 * 4 bytes
+    let peak_chunk_size = 8 + 8 * cdp_chunks.peak.peaks.len(); // version, timestamp, then value and position per channel
     let cue_chunk_size = 28; // 4 + 24 for one cue point
 
     // LIST chunk needs padding if note_data length is odd
@@ -276,12 +936,17 @@ fn write_wav_cdp_internal<W: Write>(
         cdp_chunks.list.note_data.len()
     };
     let list_chunk_size = 4 + 4 + 4 + cdp_chunks.list.note_data.len(); // "adtl" + "note" + note_size + data (not padded)
+    let extra_size: usize = extra_chunks
+        .iter()
+        .map(|chunk| 8 + chunk.data.len() + chunk.data.len() % 2)
+        .sum();
 
     let riff_size = 4 + // "WAVE"
         8 + fmt_chunk_size +
         8 + peak_chunk_size +
         8 + cue_chunk_size +
         8 + list_chunk_size + (note_data_padded_len - cdp_chunks.list.note_data.len()) +
+        extra_size +
         8 + data_size;
 
     // Write RIFF header
@@ -292,22 +957,31 @@ fn write_wav_cdp_internal<W: Write>(
     // Write fmt chunk
     writer.write_all(b"fmt ")?;
     writer.write_all(&16u32.to_le_bytes())?; // chunk size
-    writer.write_all(&1u16.to_le_bytes())?; // audio format (PCM)
+    let format_tag = if format.sample_type()?.is_float() {
+        WAVE_FORMAT_IEEE_FLOAT
+    } else {
+        1 // PCM
+    };
+    writer.write_all(&format_tag.to_le_bytes())?; // audio format
     writer.write_all(&format.channels.to_le_bytes())?;
     writer.write_all(&format.sample_rate.to_le_bytes())?;
-    let byte_rate = format.sample_rate * format.channels as u32 * 2;
+    let bytes_per_sample = format.bytes_per_sample() as u32;
+    let byte_rate = format.sample_rate * format.channels as u32 * bytes_per_sample;
     writer.write_all(&byte_rate.to_le_bytes())?;
-    let block_align = format.channels * 2;
+    let block_align = format.channels * bytes_per_sample as u16;
     writer.write_all(&block_align.to_le_bytes())?;
     writer.write_all(&format.bits_per_sample.to_le_bytes())?;
 
     // Write PEAK chunk
     writer.write_all(b"PEAK")?;
-    writer.write_all(&16u32.to_le_bytes())?;
+    writer.write_all(&(peak_chunk_size as u32).to_le_bytes())?;
     writer.write_all(&cdp_chunks.peak.version.to_le_bytes())?;
     writer.write_all(&cdp_chunks.peak.timestamp.to_le_bytes())?;
-    writer.write_all(&cdp_chunks.peak.peak_value.to_le_bytes())?;
-    writer.write_all(&cdp_chunks.peak.peak_position.to_le_bytes())?;
+    let peaks = writer.stream_position()?;
+    for peak in &cdp_chunks.peak.peaks {
+        writer.write_all(&peak.value.to_le_bytes())?;
+        writer.write_all(&peak.position.to_le_bytes())?;
+    }
 
     // Write cue chunk
     writer.write_all(b"cue ")?;
@@ -333,14 +1007,22 @@ fn write_wav_cdp_internal<W: Write>(
         writer.write_all(&[0u8])?;
     }
 
-    // Write data chunk
+    // Write passthrough chunks, word aligned
+    for chunk in extra_chunks {
+        writer.write_all(&chunk.id)?;
+        writer.write_all(&(chunk.data.len() as u32).to_le_bytes())?;
+        writer.write_all(&chunk.data)?;
+        if chunk.data.len() % 2 != 0 {
+            writer.write_all(&[0u8])?;
+        }
+    }
+
+    // Write data chunk header
     writer.write_all(b"data")?;
     writer.write_all(&(data_size as u32).to_le_bytes())?;
-    for &sample in samples {
-        writer.write_all(&sample.to_le_bytes())?;
-    }
+    let data_start = writer.stream_position()?;
 
-    Ok(())
+    Ok(HeaderOffsets { peaks, data_start })
 }
 
 #[cfg(test)]
@@ -349,10 +1031,22 @@ mod tests {
 
     #[test]
     fn test_peak_calculation() {
-        let samples = vec![0, 1000, -2000, 3000, -32767];
-        let (peak, pos) = calculate_peak(&samples);
-        assert_eq!(peak, 32767.0 / 32767.0);
-        assert_eq!(pos, 4);
+        let format = WavFormat {
+            channels: 1,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            data_size: 0,
+        };
+        let samples = Samples::I16(vec![0, 1000, -2000, 3000, -32767]);
+        let mut writer = WavWriter::new(io::Cursor::new(Vec::new()), &format).unwrap();
+        writer.write_samples(&samples).unwrap();
+        assert_eq!(
+            writer.chunks.peak.peaks,
+            vec![ChannelPeak {
+                value: 32767.0 / 32767.0,
+                position: 4
+            }]
+        );
     }
 
     #[test]
@@ -366,4 +1060,132 @@ mod tests {
         assert_eq!(format.channels, 2);
         assert_eq!(format.sample_rate, 44100);
     }
+
+    #[test]
+    fn test_streaming_roundtrip() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("stream.wav");
+
+        let format = WavFormat {
+            channels: 2,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            data_size: 0,
+        };
+        let samples: Vec<i16> = (0..1000).map(|i| ((i * 37) % 2000 - 1000) as i16).collect();
+        let levels = Samples::I16(samples.clone()).to_f32();
+
+        // Write in uneven blocks
+        let mut writer = WavWriter::create(&path, &format).unwrap();
+        for block in levels.chunks(333) {
+            writer.write_block(block).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        // Read back in different uneven blocks
+        let mut reader = WavReader::open(&path).unwrap();
+        assert_eq!(reader.format().channels, 2);
+        assert_eq!(reader.remaining_samples(), samples.len());
+        let mut read_back = Vec::new();
+        let mut buf = vec![0.0f32; 128];
+        loop {
+            let count = reader.read_block(&mut buf).unwrap();
+            if count == 0 {
+                break;
+            }
+            read_back.extend_from_slice(&buf[..count]);
+        }
+        assert_eq!(read_back, levels);
+
+        // Whole-file reads see the same data
+        let (_, whole) = read_wav_basic(&path).unwrap();
+        assert_eq!(whole, samples);
+    }
+
+    #[test]
+    fn test_24bit_roundtrip() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("24bit.wav");
+
+        let format = WavFormat {
+            channels: 1,
+            sample_rate: 48000,
+            bits_per_sample: 24,
+            data_size: 0,
+        };
+        let samples = vec![0, 1, -1, 8_388_607, -8_388_608, 123_456, -654_321];
+        write_wav_cdp_int(&path, &format, &samples).unwrap();
+
+        let (read_format, read_back) = read_wav_int(&path).unwrap();
+        assert_eq!(read_format.bits_per_sample, 24);
+        assert_eq!(read_format.frame_count(), samples.len());
+        assert_eq!(read_back, samples);
+
+        // 16-bit only readers must refuse rather than return garbage
+        assert!(read_wav_basic(&path).is_err());
+    }
+
+    #[test]
+    fn test_float_roundtrip_is_lossless() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let input = temp_dir.path().join("float.wav");
+        let output = temp_dir.path().join("copy.wav");
+
+        let format = WavFormat {
+            channels: 2,
+            sample_rate: 44100,
+            bits_per_sample: 32,
+            data_size: 0,
+        };
+        // Float files may exceed full scale and carry sub-LSB detail
+        let samples = Samples::F32(vec![0.0, 1.5, -2.25, 1e-7, -0.333_333_34, 0.999_999_9]);
+        write_wav_cdp_samples(&input, &format, &samples).unwrap();
+
+        copy_wav_cdp(&input, &output).unwrap();
+
+        let (read_format, read_back) = read_wav_samples(&output).unwrap();
+        assert_eq!(read_format.sample_type().unwrap(), SampleType::Float32);
+        assert_eq!(read_back, samples);
+    }
+
+    #[test]
+    fn test_unknown_chunks_passthrough() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let input = temp_dir.path().join("input.wav");
+        let kept = temp_dir.path().join("kept.wav");
+        let dropped = temp_dir.path().join("dropped.wav");
+
+        let format = WavFormat {
+            channels: 1,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            data_size: 0,
+        };
+        // Odd-length chunks exercise word-alignment padding
+        let extra = vec![
+            RawChunk {
+                id: *b"smpl",
+                data: vec![1, 2, 3],
+            },
+            RawChunk {
+                id: *b"XVND",
+                data: vec![9; 6],
+            },
+        ];
+        let mut writer = WavWriter::create_with_chunks(&input, &format, &extra).unwrap();
+        writer.write_samples(&Samples::I16(vec![5, -6, 7])).unwrap();
+        writer.finalize().unwrap();
+
+        copy_wav_cdp_with(&input, &kept, UnknownChunks::Preserve).unwrap();
+        copy_wav_cdp(&input, &dropped).unwrap();
+
+        let reader = WavReader::open(&kept).unwrap();
+        assert_eq!(reader.extra_chunks(), &extra[..]);
+        assert!(WavReader::open(&dropped).unwrap().extra_chunks().is_empty());
+        assert_eq!(read_wav_basic(&kept).unwrap().1, vec![5, -6, 7]);
+        assert_eq!(
+            std::fs::metadata(&kept).unwrap().len(),
+            std::fs::metadata(&input).unwrap().len()
+        );
+    }
 }
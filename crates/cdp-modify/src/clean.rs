@@ -0,0 +1,145 @@
+//! Clean-up operations
+//!
+//! Removes DC offset, either by subtracting each channel's mean or with a
+//! one-pole high-pass filter that also follows slowly drifting offsets
+
+use super::{ModifyError, Result};
+use cdp_housekeep::wav_cdp;
+use std::path::Path;
+
+/// Cutoff used by the CLI when none is given, in Hz
+const DEFAULT_CUTOFF: f64 = 20.0;
+
+/// How DC offset is removed
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DcRemoval {
+    /// Subtract each channel's mean over the whole sound
+    Mean,
+    /// One-pole high-pass filter with the given cutoff in Hz
+    HighPass(f64),
+}
+
+/// Remove DC offset from a sound
+pub fn remove_dc(input: &Path, output: &Path, method: DcRemoval) -> Result<()> {
+    let (mut audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    remove_dc_buf(&mut audio.data, audio.channels, audio.sample_rate, method)?;
+    wav_cdp::write_audio_buffer(output, &audio, sample_type)?;
+    Ok(())
+}
+
+/// Remove DC offset from interleaved in-memory float samples
+///
+/// Each channel is processed independently. The high-pass filter is
+/// `y[n] = x[n] - x[n-1] + r * y[n-1]` with `r = exp(-2π * cutoff / rate)`,
+/// which has unity gain well above the cutoff and a zero at DC.
+pub fn remove_dc_buf(
+    samples: &mut [f32],
+    channels: u16,
+    sample_rate: u32,
+    method: DcRemoval,
+) -> Result<()> {
+    if channels == 0 || sample_rate == 0 {
+        return Err(ModifyError::InvalidParameter(
+            "Channels and sample rate must be greater than 0".into(),
+        ));
+    }
+    let channels = channels as usize;
+
+    match method {
+        DcRemoval::Mean => {
+            let frames = samples.len() / channels;
+            if frames == 0 {
+                return Ok(());
+            }
+            let mut sums = vec![0.0f64; channels];
+            for frame in samples.chunks_exact(channels) {
+                for (sum, &sample) in sums.iter_mut().zip(frame) {
+                    *sum += sample as f64;
+                }
+            }
+            let means: Vec<f32> = sums.iter().map(|s| (s / frames as f64) as f32).collect();
+            for frame in samples.chunks_exact_mut(channels) {
+                for (sample, mean) in frame.iter_mut().zip(&means) {
+                    *sample -= mean;
+                }
+            }
+        }
+        DcRemoval::HighPass(cutoff) => {
+            if !(cutoff > 0.0 && cutoff < sample_rate as f64 / 2.0) {
+                return Err(ModifyError::InvalidParameter(
+                    "High-pass cutoff must be between 0 Hz and Nyquist".into(),
+                ));
+            }
+            let r = (-2.0 * std::f64::consts::PI * cutoff / sample_rate as f64).exp();
+            let mut previous = vec![(0.0f64, 0.0f64); channels];
+            for frame in samples.chunks_exact_mut(channels) {
+                for (sample, (x1, y1)) in frame.iter_mut().zip(previous.iter_mut()) {
+                    let x = *sample as f64;
+                    let y = x - *x1 + r * *y1;
+                    (*x1, *y1) = (x, y);
+                    *sample = y as f32;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// CLI compatibility layer for clean-up operations
+pub fn clean(mode: i32, args: &[&str]) -> Result<()> {
+    let usage = match mode {
+        1 => "Usage: clean 1 infile outfile",
+        2 => "Usage: clean 2 infile outfile [cutoff_hz]",
+        _ => {
+            return Err(ModifyError::UnsupportedOperation(format!(
+                "Clean mode {} not yet implemented",
+                mode
+            )))
+        }
+    };
+    if args.len() < 2 {
+        return Err(ModifyError::InvalidParameter(usage.into()));
+    }
+    let input = Path::new(args[0]);
+    let output = Path::new(args[1]);
+
+    let method = if mode == 1 {
+        DcRemoval::Mean
+    } else {
+        let cutoff = match args.get(2) {
+            Some(arg) => arg
+                .parse::<f64>()
+                .map_err(|_| ModifyError::InvalidParameter("Invalid cutoff value".into()))?,
+            None => DEFAULT_CUTOFF,
+        };
+        DcRemoval::HighPass(cutoff)
+    };
+    remove_dc(input, output, method)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remove_dc_buf() {
+        // Stereo with a different offset on each channel
+        let mut samples = vec![0.6, -0.2, 0.4, -0.4, 0.5, -0.3];
+        remove_dc_buf(&mut samples, 2, 44100, DcRemoval::Mean).unwrap();
+        let expected = [0.1, 0.1, -0.1, -0.1, 0.0, 0.0];
+        for (sample, expected) in samples.iter().zip(expected) {
+            assert!((sample - expected).abs() < 1e-6);
+        }
+
+        // A constant offset decays away through the high-pass
+        let mut samples = vec![0.5; 44100];
+        remove_dc_buf(&mut samples, 1, 44100, DcRemoval::HighPass(20.0)).unwrap();
+        assert_eq!(samples[0], 0.5);
+        assert!(samples[44099].abs() < 1e-6);
+
+        assert!(remove_dc_buf(&mut samples, 1, 44100, DcRemoval::HighPass(0.0)).is_err());
+        assert!(remove_dc_buf(&mut samples, 1, 44100, DcRemoval::HighPass(30000.0)).is_err());
+    }
+}
@@ -0,0 +1,317 @@
+//! Dynamics processing
+//!
+//! Provides a lookahead brickwall limiter, which guarantees no sample
+//! exceeds a ceiling, and a feed-forward compressor. Both link their gain
+//! across channels so the stereo image does not shift.
+
+use super::{ModifyError, Result};
+use cdp_housekeep::wav_cdp;
+use std::collections::VecDeque;
+use std::path::Path;
+
+/// Lookahead used by the CLI when none is given, in ms
+const DEFAULT_LOOKAHEAD_MS: f64 = 5.0;
+
+/// Release used by the CLI when none is given, in ms
+const DEFAULT_RELEASE_MS: f64 = 50.0;
+
+/// Limit the peaks of a sound to a ceiling
+///
+/// `ceiling` is a linear level (0-1]. Gain reduction ramps in over
+/// `lookahead_ms` before each peak and recovers over `release_ms` after it.
+pub fn limit(
+    input: &Path,
+    output: &Path,
+    ceiling: f32,
+    lookahead_ms: f64,
+    release_ms: f64,
+) -> Result<()> {
+    let (mut audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    limit_buf(
+        &mut audio.data,
+        audio.channels,
+        audio.sample_rate,
+        ceiling,
+        lookahead_ms,
+        release_ms,
+    )?;
+    wav_cdp::write_audio_buffer(output, &audio, sample_type)?;
+    Ok(())
+}
+
+/// Compress the dynamic range of a sound
+///
+/// Levels above `threshold_db` (dBFS) are reduced so that each further dB
+/// of input gives `1 / ratio` dB of output. The detector rises over
+/// `attack_ms` and falls over `release_ms`.
+pub fn compress(
+    input: &Path,
+    output: &Path,
+    threshold_db: f64,
+    ratio: f64,
+    attack_ms: f64,
+    release_ms: f64,
+) -> Result<()> {
+    let (mut audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    compress_buf(
+        &mut audio.data,
+        audio.channels,
+        audio.sample_rate,
+        threshold_db,
+        ratio,
+        attack_ms,
+        release_ms,
+    )?;
+    wav_cdp::write_audio_buffer(output, &audio, sample_type)?;
+    Ok(())
+}
+
+/// Limit the peaks of interleaved in-memory samples to a ceiling
+///
+/// Each frame needs at most `ceiling / peak` gain. The gain curve takes the
+/// smallest need over the lookahead window, recovers towards unity with an
+/// exponential release, and is then averaged over the lookahead window so
+/// it ramps down smoothly. Every value averaged for a frame already covers
+/// that frame's need, so the ceiling is never exceeded.
+pub fn limit_buf(
+    samples: &mut [f32],
+    channels: u16,
+    sample_rate: u32,
+    ceiling: f32,
+    lookahead_ms: f64,
+    release_ms: f64,
+) -> Result<()> {
+    check_format(channels, sample_rate)?;
+    if !(ceiling > 0.0 && ceiling <= 1.0) {
+        return Err(ModifyError::InvalidParameter(
+            "Limiter ceiling must be greater than 0 and at most 1".into(),
+        ));
+    }
+    check_time(lookahead_ms, "Lookahead")?;
+    check_time(release_ms, "Release")?;
+
+    let channels = channels as usize;
+    let needs: Vec<f64> = samples
+        .chunks_exact(channels)
+        .map(|frame| {
+            let peak = frame.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+            if peak > ceiling {
+                (ceiling / peak) as f64
+            } else {
+                1.0
+            }
+        })
+        .collect();
+
+    let lookahead = ms_to_frames(lookahead_ms, sample_rate);
+    let release = coefficient(release_ms, sample_rate);
+
+    // Smallest need over each frame's lookahead window, with release
+    let mut window: VecDeque<usize> = VecDeque::new();
+    let mut targets = Vec::with_capacity(needs.len());
+    let mut released = 1.0f64;
+    let mut next = 0;
+    for frame_idx in 0..needs.len() {
+        let end = (frame_idx + lookahead).min(needs.len() - 1);
+        while next <= end {
+            while window
+                .back()
+                .is_some_and(|&last| needs[last] >= needs[next])
+            {
+                window.pop_back();
+            }
+            window.push_back(next);
+            next += 1;
+        }
+        while window.front().is_some_and(|&first| first < frame_idx) {
+            window.pop_front();
+        }
+
+        released = needs[window[0]].min(1.0 + (released - 1.0) * release);
+        targets.push(released);
+    }
+
+    // Average over the window each target was taken ahead of
+    let mut sum = 0.0f64;
+    for (frame_idx, frame) in samples.chunks_exact_mut(channels).enumerate() {
+        sum += targets[frame_idx];
+        if frame_idx > lookahead {
+            sum -= targets[frame_idx - lookahead - 1];
+        }
+        let gain = (sum / (frame_idx.min(lookahead) + 1) as f64) as f32;
+        for sample in frame {
+            *sample = (*sample * gain).clamp(-ceiling, ceiling);
+        }
+    }
+    Ok(())
+}
+
+/// Compress the dynamic range of interleaved in-memory samples
+///
+/// The detector follows each frame's peak level in dB, rising with the
+/// attack and falling with the release time constant, and the gain
+/// reduction is `(level - threshold) * (1 - 1 / ratio)` dB above threshold.
+pub fn compress_buf(
+    samples: &mut [f32],
+    channels: u16,
+    sample_rate: u32,
+    threshold_db: f64,
+    ratio: f64,
+    attack_ms: f64,
+    release_ms: f64,
+) -> Result<()> {
+    check_format(channels, sample_rate)?;
+    if !(-96.0..=0.0).contains(&threshold_db) {
+        return Err(ModifyError::InvalidParameter(
+            "Compressor threshold must be between -96 and 0 dB".into(),
+        ));
+    }
+    if !(1.0..=100.0).contains(&ratio) {
+        return Err(ModifyError::InvalidParameter(
+            "Compressor ratio must be between 1 and 100".into(),
+        ));
+    }
+    check_time(attack_ms, "Attack")?;
+    check_time(release_ms, "Release")?;
+
+    let attack = coefficient(attack_ms, sample_rate);
+    let release = coefficient(release_ms, sample_rate);
+    let slope = 1.0 - 1.0 / ratio;
+    let mut envelope = -96.0f64;
+    for frame in samples.chunks_exact_mut(channels as usize) {
+        let peak = frame.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        let level = 20.0 * (peak as f64).max(1e-5).log10();
+        let coef = if level > envelope { attack } else { release };
+        envelope = level + (envelope - level) * coef;
+
+        let reduction = (envelope - threshold_db).max(0.0) * slope;
+        let gain = 10.0f64.powf(-reduction / 20.0) as f32;
+        for sample in frame {
+            *sample *= gain;
+        }
+    }
+    Ok(())
+}
+
+/// One-pole smoothing coefficient for a time constant, zero for instant
+fn coefficient(time_ms: f64, sample_rate: u32) -> f64 {
+    if time_ms == 0.0 {
+        0.0
+    } else {
+        (-1000.0 / (time_ms * sample_rate as f64)).exp()
+    }
+}
+
+fn ms_to_frames(time_ms: f64, sample_rate: u32) -> usize {
+    (time_ms * sample_rate as f64 / 1000.0).round() as usize
+}
+
+fn check_format(channels: u16, sample_rate: u32) -> Result<()> {
+    if channels == 0 || sample_rate == 0 {
+        return Err(ModifyError::InvalidParameter(
+            "Channels and sample rate must be greater than 0".into(),
+        ));
+    }
+    Ok(())
+}
+
+fn check_time(time_ms: f64, name: &str) -> Result<()> {
+    if !(0.0..=5000.0).contains(&time_ms) {
+        return Err(ModifyError::InvalidParameter(format!(
+            "{} time must be between 0 and 5000 ms",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// CLI compatibility layer for dynamics operations
+pub fn dynamics(mode: i32, args: &[&str]) -> Result<()> {
+    let number = |arg: &str| {
+        arg.parse::<f64>()
+            .map_err(|_| ModifyError::InvalidParameter(format!("Invalid value: {}", arg)))
+    };
+    match mode {
+        1 => {
+            // Limit, with optional lookahead and release
+            if args.len() < 3 {
+                return Err(ModifyError::InvalidParameter(
+                    "Usage: dynamics 1 infile outfile ceiling [lookahead_ms release_ms]".into(),
+                ));
+            }
+            let lookahead = match args.get(3) {
+                Some(arg) => number(arg)?,
+                None => DEFAULT_LOOKAHEAD_MS,
+            };
+            let release = match args.get(4) {
+                Some(arg) => number(arg)?,
+                None => DEFAULT_RELEASE_MS,
+            };
+            limit(
+                Path::new(args[0]),
+                Path::new(args[1]),
+                number(args[2])? as f32,
+                lookahead,
+                release,
+            )
+        }
+        2 => {
+            // Compress
+            if args.len() < 6 {
+                return Err(ModifyError::InvalidParameter(
+                    "Usage: dynamics 2 infile outfile threshold_db ratio attack_ms release_ms"
+                        .into(),
+                ));
+            }
+            compress(
+                Path::new(args[0]),
+                Path::new(args[1]),
+                number(args[2])?,
+                number(args[3])?,
+                number(args[4])?,
+                number(args[5])?,
+            )
+        }
+        _ => Err(ModifyError::UnsupportedOperation(format!(
+            "Dynamics mode {} not yet implemented",
+            mode
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_buf() {
+        // A stereo burst twice the ceiling in the middle of a quiet sound
+        let mut samples = vec![0.25f32; 200];
+        samples[100] = 1.0;
+        samples[101] = -0.8;
+        limit_buf(&mut samples, 2, 1000, 0.5, 10.0, 10.0).unwrap();
+        assert!(samples.iter().all(|s| s.abs() <= 0.5));
+        assert_eq!(samples[100], 0.5);
+        assert_eq!(samples[101], -0.4);
+
+        // Gain ramps down over the ten frames ahead of the peak
+        assert_eq!(samples[78], 0.25);
+        assert!(samples[80] < 0.25 && samples[98] < samples[80]);
+
+        assert!(limit_buf(&mut samples, 2, 1000, 1.5, 10.0, 10.0).is_err());
+        assert!(limit_buf(&mut samples, 2, 1000, 0.5, -1.0, 10.0).is_err());
+    }
+
+    #[test]
+    fn test_compress_buf() {
+        // Instant detector: -6 dB over a -12 dB threshold at 2:1 loses 3 dB
+        let mut samples = vec![0.5f32, 0.1];
+        compress_buf(&mut samples, 1, 1000, -12.0, 2.0, 0.0, 0.0).unwrap();
+        let expected = 0.5 * 10.0f32.powf(-(20.0 * 0.5f32.log10() + 12.0) / 40.0);
+        assert!((samples[0] - expected).abs() < 1e-5);
+        assert_eq!(samples[1], 0.1);
+
+        assert!(compress_buf(&mut samples, 1, 1000, -12.0, 0.5, 0.0, 0.0).is_err());
+        assert!(compress_buf(&mut samples, 1, 1000, 6.0, 2.0, 0.0, 0.0).is_err());
+    }
+}
@@ -0,0 +1,317 @@
+//! Fade operations
+//!
+//! Provides fade-ins, fade-outs and CDP's envel dovetail, which fades both
+//! ends of a sound
+
+use super::{ModifyError, Result};
+use cdp_housekeep::wav_cdp;
+use std::path::Path;
+
+/// End of the sound a fade applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FadeDirection {
+    /// Rise from silence at the start
+    In,
+    /// Fall to silence at the end
+    Out,
+}
+
+/// Gain curve of a fade
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FadeShape {
+    /// Gain changes at a constant rate
+    Linear,
+    /// Gain changes by a constant number of dB, through 60 dB, before
+    /// reaching silence
+    Exponential,
+}
+
+impl FadeShape {
+    /// Gain at position `x` (0-1) through a fade-in
+    fn gain(self, x: f32) -> f32 {
+        match self {
+            FadeShape::Linear => x,
+            FadeShape::Exponential => (1000.0f32.powf(x) - 1.0) / 999.0,
+        }
+    }
+}
+
+/// Fade one end of a sound
+pub fn fade(
+    input: &Path,
+    output: &Path,
+    direction: FadeDirection,
+    duration: f64,
+    shape: FadeShape,
+) -> Result<()> {
+    let (mut audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    fade_buf(
+        &mut audio.data,
+        audio.channels,
+        audio.sample_rate,
+        direction,
+        duration,
+        shape,
+    )?;
+    wav_cdp::write_audio_buffer(output, &audio, sample_type)?;
+    Ok(())
+}
+
+/// Fade both ends of a sound, like CDP's envel dovetail
+pub fn dovetail(
+    input: &Path,
+    output: &Path,
+    fade_in: f64,
+    fade_out: f64,
+    in_shape: FadeShape,
+    out_shape: FadeShape,
+) -> Result<()> {
+    let (mut audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    dovetail_buf(
+        &mut audio.data,
+        audio.channels,
+        audio.sample_rate,
+        fade_in,
+        fade_out,
+        in_shape,
+        out_shape,
+    )?;
+    wav_cdp::write_audio_buffer(output, &audio, sample_type)?;
+    Ok(())
+}
+
+/// Fade one end of interleaved in-memory float samples
+///
+/// The fade spans `duration` seconds; a fade-in starts at zero gain on the
+/// first frame and a fade-out reaches zero on the last.
+pub fn fade_buf(
+    samples: &mut [f32],
+    channels: u16,
+    sample_rate: u32,
+    direction: FadeDirection,
+    duration: f64,
+    shape: FadeShape,
+) -> Result<()> {
+    let (fade_in, fade_out) = match direction {
+        FadeDirection::In => (duration, 0.0),
+        FadeDirection::Out => (0.0, duration),
+    };
+    dovetail_buf(
+        samples,
+        channels,
+        sample_rate,
+        fade_in,
+        fade_out,
+        shape,
+        shape,
+    )
+}
+
+/// Fade both ends of interleaved in-memory float samples
+///
+/// The fades must not overlap; either may be zero to leave that end alone.
+pub fn dovetail_buf(
+    samples: &mut [f32],
+    channels: u16,
+    sample_rate: u32,
+    fade_in: f64,
+    fade_out: f64,
+    in_shape: FadeShape,
+    out_shape: FadeShape,
+) -> Result<()> {
+    if channels == 0 || sample_rate == 0 {
+        return Err(ModifyError::InvalidParameter(
+            "Channels and sample rate must be greater than 0".into(),
+        ));
+    }
+    if ![fade_in, fade_out]
+        .iter()
+        .all(|d| *d >= 0.0 && d.is_finite())
+    {
+        return Err(ModifyError::InvalidParameter(
+            "Fade durations cannot be negative".into(),
+        ));
+    }
+
+    let channels = channels as usize;
+    let frames = samples.len() / channels;
+    let in_frames = (fade_in * sample_rate as f64).round() as usize;
+    let out_frames = (fade_out * sample_rate as f64).round() as usize;
+    if in_frames + out_frames > frames {
+        return Err(ModifyError::InvalidParameter(
+            "Fades are longer than the sound".into(),
+        ));
+    }
+
+    for (frame_idx, frame) in samples.chunks_exact_mut(channels).enumerate() {
+        let gain = if frame_idx < in_frames {
+            in_shape.gain(frame_idx as f32 / in_frames as f32)
+        } else if frames - 1 - frame_idx < out_frames {
+            out_shape.gain((frames - 1 - frame_idx) as f32 / out_frames as f32)
+        } else {
+            continue;
+        };
+        for sample in frame {
+            *sample *= gain;
+        }
+    }
+    Ok(())
+}
+
+/// CLI compatibility layer for fades
+///
+/// Usage: `fade 1|2 infile outfile duration type`, where mode 1 fades in and
+/// mode 2 fades out, duration is in seconds and type is 0 for linear, 1 for
+/// exponential.
+pub fn fade_cli(mode: i32, args: &[&str]) -> Result<()> {
+    let direction = match mode {
+        1 => FadeDirection::In,
+        2 => FadeDirection::Out,
+        _ => {
+            return Err(ModifyError::UnsupportedOperation(format!(
+                "Fade mode {} not yet implemented",
+                mode
+            )))
+        }
+    };
+    if args.len() < 4 {
+        return Err(ModifyError::InvalidParameter(format!(
+            "Usage: fade {} infile outfile duration type",
+            mode
+        )));
+    }
+    fade(
+        Path::new(args[0]),
+        Path::new(args[1]),
+        direction,
+        parse_duration(args[2])?,
+        parse_shape(args[3])?,
+    )
+}
+
+/// CLI compatibility layer for envel dovetail
+///
+/// Usage: `dovetail 1 infile outfile infadedur outfadedur intype outtype`,
+/// with durations in seconds and types 0 for linear, 1 for exponential.
+pub fn dovetail_cli(mode: i32, args: &[&str]) -> Result<()> {
+    if mode != 1 {
+        return Err(ModifyError::UnsupportedOperation(format!(
+            "Dovetail mode {} not yet implemented",
+            mode
+        )));
+    }
+    if args.len() < 6 {
+        return Err(ModifyError::InvalidParameter(
+            "Usage: dovetail 1 infile outfile infadedur outfadedur intype outtype".into(),
+        ));
+    }
+    dovetail(
+        Path::new(args[0]),
+        Path::new(args[1]),
+        parse_duration(args[2])?,
+        parse_duration(args[3])?,
+        parse_shape(args[4])?,
+        parse_shape(args[5])?,
+    )
+}
+
+fn parse_duration(arg: &str) -> Result<f64> {
+    arg.parse::<f64>()
+        .map_err(|_| ModifyError::InvalidParameter("Invalid fade duration".into()))
+}
+
+fn parse_shape(arg: &str) -> Result<FadeShape> {
+    match arg {
+        "0" => Ok(FadeShape::Linear),
+        "1" => Ok(FadeShape::Exponential),
+        _ => Err(ModifyError::InvalidParameter(
+            "Fade type must be 0 (linear) or 1 (exponential)".into(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_fade_and_dovetail_buf() {
+        // Stereo at 4Hz, one second
+        let mut samples = vec![1.0; 8];
+        fade_buf(
+            &mut samples,
+            2,
+            4,
+            FadeDirection::In,
+            0.5,
+            FadeShape::Linear,
+        )
+        .unwrap();
+        assert_eq!(samples, vec![0.0, 0.0, 0.5, 0.5, 1.0, 1.0, 1.0, 1.0]);
+
+        let mut samples = vec![1.0; 6];
+        let (linear, exponential) = (FadeShape::Linear, FadeShape::Exponential);
+        assert!(dovetail_buf(&mut samples, 1, 4, 1.0, 0.75, linear, exponential).is_err());
+        dovetail_buf(&mut samples, 1, 4, 0.25, 0.5, linear, exponential).unwrap();
+        assert_eq!(samples[0], 0.0);
+        assert_eq!(&samples[1..4], &[1.0, 1.0, 1.0]);
+        assert!((samples[4] - (1000.0f32.sqrt() - 1.0) / 999.0).abs() < 1e-6);
+        assert_eq!(samples[5], 0.0);
+    }
+
+    #[test]
+    fn test_dovetail_cli() {
+        let temp_dir = TempDir::new().unwrap();
+        let input = temp_dir.path().join("input.wav");
+        let output = temp_dir.path().join("output.wav");
+
+        let format = wav_cdp::WavFormat {
+            channels: 1,
+            sample_rate: 4,
+            bits_per_sample: 16,
+            data_size: 0,
+        };
+        wav_cdp::write_wav_cdp(&input, &format, &[1000; 8]).unwrap();
+
+        let (in_path, out_path) = (input.to_str().unwrap(), output.to_str().unwrap());
+        dovetail_cli(1, &[in_path, out_path, "0.5", "0.5", "0", "0"]).unwrap();
+        let (_, samples) = wav_cdp::read_wav_basic(&output).unwrap();
+        assert_eq!(samples, vec![0, 500, 1000, 1000, 1000, 1000, 500, 0]);
+
+        assert!(dovetail_cli(1, &[in_path, out_path, "0.5", "0.5", "2", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_fade_cli() {
+        let temp_dir = TempDir::new().unwrap();
+        let input = temp_dir.path().join("input.wav");
+        let output = temp_dir.path().join("output.wav");
+
+        let format = wav_cdp::WavFormat {
+            channels: 1,
+            sample_rate: 4,
+            bits_per_sample: 16,
+            data_size: 0,
+        };
+        wav_cdp::write_wav_cdp(&input, &format, &[1000; 8]).unwrap();
+
+        let (in_path, out_path) = (input.to_str().unwrap(), output.to_str().unwrap());
+        fade_cli(1, &[in_path, out_path, "0.5", "0"]).unwrap();
+        let (_, samples) = wav_cdp::read_wav_basic(&output).unwrap();
+        assert_eq!(samples, vec![0, 500, 1000, 1000, 1000, 1000, 1000, 1000]);
+
+        crate::modify("fade", 2, &[in_path, out_path, "0.5", "1"]).unwrap();
+        let (_, samples) = wav_cdp::read_wav_basic(&output).unwrap();
+        assert_eq!(&samples[..6], &[1000; 6]);
+        assert_eq!(samples[7], 0);
+
+        assert!(fade_cli(1, &[in_path, out_path, "0.5"]).is_err());
+        assert!(fade_cli(1, &[in_path, out_path, "x", "0"]).is_err());
+        assert!(fade_cli(3, &[in_path, out_path, "0.5", "0"]).is_err());
+    }
+}
@@ -2,14 +2,31 @@
 //!
 //! This module implements CDP's modification operations including:
 //! - Gain adjustment (linear and dB)
-//! - Normalization
-//! - Phase inversion
+//! - Peak, RMS and LUFS (ITU-R BS.1770) normalization
+//! - Balancing and equalizing levels between files
+//! - Phase inversion and DC offset removal
+//! - Lookahead limiting and compression
+//! - Fades and dovetailing
+//! - Speed change by resampling
+//! - Tremolo with sine, square and triangle oscillators
+//! - Reversal, shredding and ring modulation
+//! - Stereo panning, mirroring and narrowing
+//! - Multichannel panning around quad, 5.1 and octophonic layouts
 //!
 //! All operations are validated against CDP binaries for byte-perfect compatibility.
 
 use thiserror::Error;
 
+pub mod clean;
+pub mod dynamics;
+pub mod fade;
 pub mod loudness;
+pub mod lufs;
+pub mod multichannel;
+pub mod radical;
+pub mod space;
+pub mod speed;
+pub mod tremolo;
 
 /// Result type for modify operations
 pub type Result<T> = std::result::Result<T, ModifyError>;
@@ -20,6 +37,12 @@ pub enum ModifyError {
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
 
+    #[error("Housekeep error: {0}")]
+    Housekeep(#[from] cdp_housekeep::HousekeepError),
+
+    #[error("Core error: {0}")]
+    Core(#[from] cdp_core::CoreError),
+
     #[error("Invalid parameter: {0}")]
     InvalidParameter(String),
 
@@ -28,13 +51,40 @@ pub enum ModifyError {
 }
 
 // Re-export main functions for convenience
-pub use loudness::{apply_db_gain, apply_gain, normalize};
+pub use clean::{remove_dc, remove_dc_buf, DcRemoval};
+pub use dynamics::{compress, compress_buf, limit, limit_buf};
+pub use fade::{dovetail, dovetail_buf, fade, fade_buf, FadeDirection, FadeShape};
+pub use loudness::{
+    apply_db_gain, apply_db_gain_buf, apply_gain, apply_gain_buf, balance, balance_buf, equalize,
+    invert_phase, invert_phase_buf, loudest, normalize, normalize_buf, normalize_lufs,
+    normalize_lufs_buf, normalize_rms, normalize_rms_buf, LevelMeasure,
+};
+pub use lufs::{integrated_loudness, LoudnessMeter};
+pub use multichannel::{pan_multi, pan_multi_buf, SpeakerLayout};
+pub use radical::{
+    reverse, reverse_buf, ring_modulate, ring_modulate_buf, ring_modulate_with,
+    ring_modulate_with_buf, shred, shred_buf,
+};
+pub use space::{
+    mirror, mirror_buf, narrow, narrow_buf, pan, pan_buf, pan_varying, pan_varying_buf,
+};
+pub use speed::{speed, speed_buf, speed_varying, speed_varying_buf, SpeedUnits};
+pub use tremolo::{tremolo, tremolo_buf, tremolo_varying, tremolo_varying_buf, TremoloWaveform};
 
 /// CLI compatibility layer - matches CDP's command-line interface
 /// This is just for oracle testing. Real users should use the library functions directly.
 pub fn modify(operation: &str, mode: i32, args: &[&str]) -> Result<()> {
     match operation {
+        "clean" => clean::clean(mode, args),
         "loudness" => loudness::loudness(mode, args),
+        "dovetail" => fade::dovetail_cli(mode, args),
+        "fade" => fade::fade_cli(mode, args),
+        "dynamics" => dynamics::dynamics(mode, args),
+        "mchanpan" => multichannel::mchanpan(mode, args),
+        "radical" => radical::radical(mode, args),
+        "space" => space::space(mode, args),
+        "speed" => speed::speed_cli(mode, args),
+        "tremolo" => tremolo::tremolo_cli(mode, args),
         _ => Err(ModifyError::UnsupportedOperation(format!(
             "Unknown operation: {}",
             operation
@@ -3,39 +3,44 @@
 //! Provides gain adjustment, normalization, and other amplitude-related operations
 
 use super::{ModifyError, Result};
+use crate::lufs::{integrated_loudness, LoudnessMeter};
+use cdp_core::{validate, Param};
 use cdp_housekeep::wav_cdp;
 use std::path::Path;
 
 /// Apply gain to audio samples
 pub fn apply_gain(input: &Path, output: &Path, gain: f32) -> Result<()> {
-    // Read input file
-    let (format, samples) = wav_cdp::read_wav_basic(input)?;
+    // Stream input through the gain stage
+    let mut reader = wav_cdp::WavReader::open(input)?;
+    let format = reader.format().clone();
+    let mut writer = wav_cdp::WavWriter::create(output, &format)?;
 
-    // Apply gain to all samples
-    let mut processed = Vec::with_capacity(samples.len());
-    for sample in samples {
-        let scaled = (sample as f32 * gain) as i32;
-        // Clamp to 16-bit range
-        let clamped = scaled.clamp(-32768, 32767) as i16;
-        processed.push(clamped);
+    let mut block = vec![0.0f32; wav_cdp::DEFAULT_BLOCK_FRAMES * format.channels as usize];
+    loop {
+        let count = reader.read_block(&mut block)?;
+        if count == 0 {
+            break;
+        }
+
+        // Apply gain to all samples; the writer clamps integer formats to
+        // their range while float formats keep the full result
+        apply_gain_buf(&mut block[..count], gain);
+        writer.write_block(&block[..count])?;
     }
 
     // Write output with CDP format
-    wav_cdp::write_wav_cdp(output, &format, &processed)?;
+    writer.finalize()?;
     Ok(())
 }
 
 /// Normalize audio to maximum level (or specified level)
 pub fn normalize(input: &Path, output: &Path, target_level: Option<f32>) -> Result<()> {
-    // Read input file
-    let (format, samples) = wav_cdp::read_wav_basic(input)?;
-
-    // Find peak value
-    let peak = samples.iter().map(|&s| s.abs()).max().unwrap_or(0) as f32 / 32767.0;
+    // First pass: find peak value
+    let peak = wav_cdp::scan_peak(input)?.0;
 
     if peak == 0.0 {
         // Silent file, just copy
-        wav_cdp::write_wav_cdp(output, &format, &samples)?;
+        wav_cdp::copy_wav_cdp(input, output)?;
         return Ok(());
     }
 
@@ -49,25 +54,312 @@ pub fn normalize(input: &Path, output: &Path, target_level: Option<f32>) -> Resu
 
     let gain = target / peak;
 
-    // Apply normalization
-    let mut processed = Vec::with_capacity(samples.len());
-    for sample in samples {
-        let scaled = (sample as f32 * gain) as i32;
-        // Should not need clamping for normalize, but be safe
-        let clamped = scaled.clamp(-32768, 32767) as i16;
-        processed.push(clamped);
+    // Second pass: apply normalization
+    apply_gain(input, output, gain)
+}
+
+/// Normalize audio to a target RMS level in dBFS
+///
+/// Returns the gain applied. Peaks may exceed full scale; integer formats
+/// clip them on output. A silent file is copied unchanged.
+pub fn normalize_rms(input: &Path, output: &Path, target_db: f32) -> Result<f32> {
+    check_target(target_db, -96.0, "RMS target")?;
+    let rms = scan_level(input, LevelMeasure::Rms)?;
+    normalize_to(input, output, rms, db_to_gain(target_db))
+}
+
+/// Normalize audio to a target integrated loudness in LUFS
+///
+/// Loudness is measured with ITU-R BS.1770 K-weighting and gating. Returns
+/// the gain applied. Peaks may exceed full scale; integer formats clip them
+/// on output. Files with no block above the -70 LUFS gate, including any
+/// shorter than 400 ms, are copied unchanged.
+pub fn normalize_lufs(input: &Path, output: &Path, target_lufs: f32) -> Result<f32> {
+    check_target(target_lufs, -70.0, "LUFS target")?;
+
+    let mut reader = wav_cdp::WavReader::open(input)?;
+    let format = reader.format().clone();
+    let mut meter = LoudnessMeter::new(format.channels, format.sample_rate);
+    let mut block = vec![0.0f32; wav_cdp::DEFAULT_BLOCK_FRAMES * format.channels.max(1) as usize];
+    loop {
+        let count = reader.read_block(&mut block)?;
+        if count == 0 {
+            break;
+        }
+        meter.process(&block[..count]);
     }
 
-    // Write output
-    wav_cdp::write_wav_cdp(output, &format, &processed)?;
-    Ok(())
+    match meter.integrated() {
+        Some(lufs) => normalize_to(input, output, 1.0, lufs_gain(lufs, target_lufs)),
+        None => normalize_to(input, output, 0.0, 1.0),
+    }
+}
+
+/// Scale a file from `level` to `target`, copying it when `level` is zero
+fn normalize_to(input: &Path, output: &Path, level: f32, target: f32) -> Result<f32> {
+    if level == 0.0 {
+        wav_cdp::copy_wav_cdp(input, output)?;
+        return Ok(1.0);
+    }
+
+    let gain = target / level;
+    apply_gain(input, output, gain)?;
+    Ok(gain)
 }
 
 /// Apply dB gain adjustment
 pub fn apply_db_gain(input: &Path, output: &Path, db_gain: f32) -> Result<()> {
-    // Convert dB to linear gain
-    let gain = 10.0_f32.powf(db_gain / 20.0);
-    apply_gain(input, output, gain)
+    apply_gain(input, output, db_to_gain(db_gain))
+}
+
+/// Invert the phase of audio by negating every sample
+pub fn invert_phase(input: &Path, output: &Path) -> Result<()> {
+    let mut reader = wav_cdp::WavReader::open(input)?;
+    let format = reader.format().clone();
+    let mut writer = wav_cdp::WavWriter::create(output, &format)?;
+
+    let mut block = vec![0.0f32; wav_cdp::DEFAULT_BLOCK_FRAMES * format.channels as usize];
+    loop {
+        let count = reader.read_block(&mut block)?;
+        if count == 0 {
+            break;
+        }
+        invert_phase_buf(&mut block[..count]);
+        writer.write_block(&block[..count])?;
+    }
+
+    writer.finalize()?;
+    Ok(())
+}
+
+/// Level measure used to compare sounds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum LevelMeasure {
+    /// Largest absolute sample
+    Peak,
+    /// Root mean square over all samples
+    Rms,
+}
+
+/// Match the level of one sound to another
+///
+/// Writes `input` scaled so its level equals that of `reference`, and
+/// returns the gain applied. A silent input is copied unchanged.
+pub fn balance(
+    input: &Path,
+    reference: &Path,
+    output: &Path,
+    measure: LevelMeasure,
+) -> Result<f32> {
+    let level = scan_level(input, measure)?;
+    let target = scan_level(reference, measure)?;
+    if level == 0.0 {
+        wav_cdp::copy_wav_cdp(input, output)?;
+        return Ok(1.0);
+    }
+
+    let gain = target / level;
+    apply_gain(input, output, gain)?;
+    Ok(gain)
+}
+
+/// Find the loudest of a set of sounds by peak level
+///
+/// Returns the index of the loudest input and its peak level.
+pub fn loudest(inputs: &[&Path]) -> Result<(usize, f32)> {
+    if inputs.is_empty() {
+        return Err(ModifyError::InvalidParameter(
+            "At least one input is required".into(),
+        ));
+    }
+
+    let mut loudest = (0, 0.0f32);
+    for (index, input) in inputs.iter().enumerate() {
+        let peak = wav_cdp::scan_peak(input)?.0;
+        if peak > loudest.1 {
+            loudest = (index, peak);
+        }
+    }
+    Ok(loudest)
+}
+
+/// Bring a set of sounds to the peak level of the loudest
+///
+/// Each input is written to the output at the same index; returns the gain
+/// applied to each. Silent inputs are copied unchanged with a gain of 1.
+pub fn equalize(inputs: &[&Path], outputs: &[&Path]) -> Result<Vec<f32>> {
+    if inputs.len() != outputs.len() {
+        return Err(ModifyError::InvalidParameter(
+            "Each input needs exactly one output".into(),
+        ));
+    }
+    let (_, target) = loudest(inputs)?;
+
+    let mut gains = Vec::with_capacity(inputs.len());
+    for (input, output) in inputs.iter().zip(outputs) {
+        let peak = wav_cdp::scan_peak(input)?.0;
+        if peak == 0.0 {
+            wav_cdp::copy_wav_cdp(input, output)?;
+            gains.push(1.0);
+        } else {
+            let gain = target / peak;
+            apply_gain(input, output, gain)?;
+            gains.push(gain);
+        }
+    }
+    Ok(gains)
+}
+
+/// Apply gain to in-memory float samples
+pub fn apply_gain_buf(samples: &mut [f32], gain: f32) {
+    for sample in samples {
+        *sample *= gain;
+    }
+}
+
+/// Invert the phase of in-memory float samples
+pub fn invert_phase_buf(samples: &mut [f32]) {
+    for sample in samples {
+        *sample = -*sample;
+    }
+}
+
+/// Normalize in-memory float samples to maximum level (or specified level)
+///
+/// Returns the gain that was applied; silent buffers are left unchanged.
+pub fn normalize_buf(samples: &mut [f32], target_level: Option<f32>) -> Result<f32> {
+    let target = target_level.unwrap_or(1.0);
+    if target > 1.0 {
+        return Err(ModifyError::InvalidParameter(
+            "Target level cannot exceed 1.0".into(),
+        ));
+    }
+
+    let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    if peak == 0.0 {
+        return Ok(1.0);
+    }
+
+    let gain = target / peak;
+    apply_gain_buf(samples, gain);
+    Ok(gain)
+}
+
+/// Normalize in-memory float samples to a target RMS level in dBFS
+///
+/// Returns the gain that was applied; silent buffers are left unchanged.
+pub fn normalize_rms_buf(samples: &mut [f32], target_db: f32) -> Result<f32> {
+    check_target(target_db, -96.0, "RMS target")?;
+    let rms = buffer_level(samples, LevelMeasure::Rms);
+    if rms == 0.0 {
+        return Ok(1.0);
+    }
+
+    let gain = db_to_gain(target_db) / rms;
+    apply_gain_buf(samples, gain);
+    Ok(gain)
+}
+
+/// Normalize in-memory interleaved samples to a target loudness in LUFS
+///
+/// Returns the gain that was applied; buffers with no block above the
+/// -70 LUFS gate are left unchanged.
+pub fn normalize_lufs_buf(
+    samples: &mut [f32],
+    channels: u16,
+    sample_rate: u32,
+    target_lufs: f32,
+) -> Result<f32> {
+    check_target(target_lufs, -70.0, "LUFS target")?;
+    let Some(lufs) = integrated_loudness(samples, channels, sample_rate) else {
+        return Ok(1.0);
+    };
+
+    let gain = lufs_gain(lufs, target_lufs);
+    apply_gain_buf(samples, gain);
+    Ok(gain)
+}
+
+/// Linear gain moving a measured loudness to a target
+fn lufs_gain(lufs: f64, target_lufs: f32) -> f32 {
+    db_to_gain((target_lufs as f64 - lufs) as f32)
+}
+
+fn check_target(target: f32, min: f32, name: &str) -> Result<()> {
+    if !(min..=0.0).contains(&target) {
+        return Err(ModifyError::InvalidParameter(format!(
+            "{} must be between {} and 0",
+            name, min
+        )));
+    }
+    Ok(())
+}
+
+/// Apply dB gain adjustment to in-memory float samples
+pub fn apply_db_gain_buf(samples: &mut [f32], db_gain: f32) {
+    apply_gain_buf(samples, db_to_gain(db_gain));
+}
+
+/// Scale in-memory float samples to the level of reference samples
+///
+/// Returns the gain that was applied; silent buffers are left unchanged.
+pub fn balance_buf(samples: &mut [f32], reference: &[f32], measure: LevelMeasure) -> f32 {
+    let level = buffer_level(samples, measure);
+    if level == 0.0 {
+        return 1.0;
+    }
+
+    let gain = buffer_level(reference, measure) / level;
+    apply_gain_buf(samples, gain);
+    gain
+}
+
+/// Level of in-memory float samples
+fn buffer_level(samples: &[f32], measure: LevelMeasure) -> f32 {
+    match measure {
+        LevelMeasure::Peak => samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs())),
+        LevelMeasure::Rms if samples.is_empty() => 0.0,
+        LevelMeasure::Rms => {
+            let sum: f64 = samples.iter().map(|&s| s as f64 * s as f64).sum();
+            (sum / samples.len() as f64).sqrt() as f32
+        }
+    }
+}
+
+/// Level of a WAV file, streamed block by block
+fn scan_level(input: &Path, measure: LevelMeasure) -> Result<f32> {
+    if measure == LevelMeasure::Peak {
+        return Ok(wav_cdp::scan_peak(input)?.0);
+    }
+
+    let mut reader = wav_cdp::WavReader::open(input)?;
+    let channels = reader.format().channels.max(1) as usize;
+    let mut block = vec![0.0f32; wav_cdp::DEFAULT_BLOCK_FRAMES * channels];
+    let mut sum = 0.0f64;
+    let mut count = 0usize;
+    loop {
+        let read = reader.read_block(&mut block)?;
+        if read == 0 {
+            break;
+        }
+        sum += block[..read]
+            .iter()
+            .map(|&s| s as f64 * s as f64)
+            .sum::<f64>();
+        count += read;
+    }
+    Ok(if count == 0 {
+        0.0
+    } else {
+        (sum / count as f64).sqrt() as f32
+    })
+}
+
+/// Convert dB to linear gain
+fn db_to_gain(db_gain: f32) -> f32 {
+    10.0_f32.powf(db_gain / 20.0)
 }
 
 /// CLI compatibility layer for loudness operations
@@ -100,11 +392,8 @@ pub fn loudness(mode: i32, args: &[&str]) -> Result<()> {
                 .parse::<f32>()
                 .map_err(|_| ModifyError::InvalidParameter("Invalid dB gain value".into()))?;
 
-            if !(-96.0..=96.0).contains(&db_gain) {
-                return Err(ModifyError::InvalidParameter(
-                    "dB gain must be between -96 and +96".into(),
-                ));
-            }
+            validate(Param::GainDb, db_gain as f64)
+                .map_err(|e| ModifyError::InvalidParameter(e.to_string()))?;
 
             apply_db_gain(input, output, db_gain)
         }
@@ -112,25 +401,50 @@ pub fn loudness(mode: i32, args: &[&str]) -> Result<()> {
             // Normalize
             if args.len() < 2 {
                 return Err(ModifyError::InvalidParameter(
-                    "Usage: loudness 3 infile outfile [-llevel]".into(),
+                    "Usage: loudness 3 infile outfile [-llevel | -rdbfs | -ulufs]".into(),
                 ));
             }
             let input = Path::new(args[0]);
             let output = Path::new(args[1]);
 
-            // Check for optional level parameter
-            let level = if args.len() > 2 && args[2].starts_with("-l") {
-                let level_str = &args[2][2..];
-                Some(
-                    level_str
-                        .parse::<f32>()
-                        .map_err(|_| ModifyError::InvalidParameter("Invalid level value".into()))?,
-                )
+            // Optional flag picks peak level, RMS dBFS or LUFS
+            let flag = args.get(2).copied().unwrap_or("");
+            let value = || {
+                flag[2..]
+                    .parse::<f32>()
+                    .map_err(|_| ModifyError::InvalidParameter("Invalid level value".into()))
+            };
+            if flag.starts_with("-r") {
+                normalize_rms(input, output, value()?)?;
+                Ok(())
+            } else if flag.starts_with("-u") {
+                normalize_lufs(input, output, value()?)?;
+                Ok(())
+            } else if flag.starts_with("-l") {
+                normalize(input, output, Some(value()?))
             } else {
-                None
+                normalize(input, output, None)
+            }
+        }
+        5 => {
+            // Balance against a reference file
+            if args.len() < 3 {
+                return Err(ModifyError::InvalidParameter(
+                    "Usage: loudness 5 infile reffile outfile [-r]".into(),
+                ));
+            }
+            let measure = if args.get(3) == Some(&"-r") {
+                LevelMeasure::Rms
+            } else {
+                LevelMeasure::Peak
             };
-
-            normalize(input, output, level)
+            balance(
+                Path::new(args[0]),
+                Path::new(args[1]),
+                Path::new(args[2]),
+                measure,
+            )?;
+            Ok(())
         }
         6 => {
             // Invert phase
@@ -141,9 +455,31 @@ pub fn loudness(mode: i32, args: &[&str]) -> Result<()> {
             }
             let input = Path::new(args[0]);
             let output = Path::new(args[1]);
-
-            // Invert phase is just gain of -1
-            apply_gain(input, output, -1.0)
+            invert_phase(input, output)
+        }
+        7 => {
+            // Report the loudest file
+            if args.is_empty() {
+                return Err(ModifyError::InvalidParameter(
+                    "Usage: loudness 7 infile [infile ...]".into(),
+                ));
+            }
+            let inputs: Vec<&Path> = args.iter().map(Path::new).collect();
+            let (index, peak) = loudest(&inputs)?;
+            println!("Loudest file is {} (peak {:.6})", args[index], peak);
+            Ok(())
+        }
+        8 => {
+            // Equalize: infiles followed by as many outfiles
+            if args.len() < 2 || args.len() % 2 != 0 {
+                return Err(ModifyError::InvalidParameter(
+                    "Usage: loudness 8 infile [infile ...] outfile [outfile ...]".into(),
+                ));
+            }
+            let paths: Vec<&Path> = args.iter().map(Path::new).collect();
+            let (inputs, outputs) = paths.split_at(paths.len() / 2);
+            equalize(inputs, outputs)?;
+            Ok(())
         }
         _ => Err(ModifyError::UnsupportedOperation(format!(
             "Loudness mode {} not yet implemented",
@@ -178,4 +514,151 @@ mod tests {
         let result = normalize(&input, &output, Some(1.5));
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_gain_preserves_24bit() {
+        let temp_dir = TempDir::new().unwrap();
+        let input = temp_dir.path().join("input.wav");
+        let output = temp_dir.path().join("output.wav");
+
+        let format = wav_cdp::WavFormat {
+            channels: 2,
+            sample_rate: 96000,
+            bits_per_sample: 24,
+            data_size: 0,
+        };
+        let samples = vec![1_000_000, -1_000_000, 4_000_000, -8_000_000];
+        wav_cdp::write_wav_cdp_int(&input, &format, &samples).unwrap();
+
+        apply_gain(&input, &output, 2.0).unwrap();
+
+        let (out_format, out_samples) = wav_cdp::read_wav_int(&output).unwrap();
+        assert_eq!(out_format.bits_per_sample, 24);
+        // Gain is applied at full resolution and clamped to the 24-bit range
+        assert_eq!(
+            out_samples,
+            vec![2_000_000, -2_000_000, 8_000_000, -8_388_608]
+        );
+    }
+
+    #[test]
+    fn test_buffer_gain_and_normalize() {
+        let mut samples = vec![0.25, -0.5, 0.125];
+        apply_gain_buf(&mut samples, 2.0);
+        assert_eq!(samples, vec![0.5, -1.0, 0.25]);
+
+        let gain = normalize_buf(&mut samples, Some(0.5)).unwrap();
+        assert_eq!(gain, 0.5);
+        assert_eq!(samples, vec![0.25, -0.5, 0.125]);
+
+        assert!(normalize_buf(&mut samples, Some(1.5)).is_err());
+    }
+
+    #[test]
+    fn test_balance_and_equalize() {
+        let mut samples = vec![0.5, -0.5, 0.5, -0.5];
+        let gain = balance_buf(&mut samples, &[0.25, 0.0, 0.0, 0.0], LevelMeasure::Peak);
+        assert_eq!(gain, 0.5);
+        let gain = balance_buf(&mut samples, &[0.5, 0.5, 0.5, 0.5], LevelMeasure::Rms);
+        assert_eq!(gain, 2.0);
+        assert_eq!(samples, vec![0.5, -0.5, 0.5, -0.5]);
+
+        let temp_dir = TempDir::new().unwrap();
+        let quiet = temp_dir.path().join("quiet.wav");
+        let loud = temp_dir.path().join("loud.wav");
+        let quiet_out = temp_dir.path().join("quiet_out.wav");
+        let loud_out = temp_dir.path().join("loud_out.wav");
+        let format = wav_cdp::WavFormat {
+            channels: 1,
+            sample_rate: 44100,
+            bits_per_sample: 32,
+            data_size: 0,
+        };
+        let write = |path: &Path, levels: Vec<f32>| {
+            wav_cdp::write_wav_cdp_samples(path, &format, &wav_cdp::Samples::F32(levels)).unwrap()
+        };
+        write(&quiet, vec![0.1, -0.2]);
+        write(&loud, vec![0.8, -0.4]);
+
+        assert_eq!(loudest(&[&quiet, &loud]).unwrap(), (1, 0.8));
+        let gains = equalize(&[&quiet, &loud], &[&quiet_out, &loud_out]).unwrap();
+        assert_eq!(gains, vec![4.0, 1.0]);
+        let (_, out) = wav_cdp::read_wav_samples(&quiet_out).unwrap();
+        assert_eq!(out.to_f32(), vec![0.4, -0.8]);
+
+        // Balance is CDP mode 5; mode 4 (force level) is not implemented
+        let balanced = temp_dir.path().join("balanced.wav");
+        let run = |mode| {
+            loudness(
+                mode,
+                &[
+                    quiet.to_str().unwrap(),
+                    loud.to_str().unwrap(),
+                    balanced.to_str().unwrap(),
+                ],
+            )
+        };
+        assert!(matches!(run(4), Err(ModifyError::UnsupportedOperation(_))));
+        run(5).unwrap();
+        let (_, out) = wav_cdp::read_wav_samples(&balanced).unwrap();
+        assert_eq!(out.to_f32(), vec![0.4, -0.8]);
+    }
+
+    #[test]
+    fn test_rms_and_lufs_normalization() {
+        let mut samples = vec![0.5, -0.5, 0.5, -0.5];
+        let gain = normalize_rms_buf(&mut samples, -20.0).unwrap();
+        assert!((gain - 0.2).abs() < 1e-6);
+        assert!(normalize_rms_buf(&mut samples, 3.0).is_err());
+        assert!(normalize_lufs_buf(&mut samples, 1, 44100, -80.0).is_err());
+
+        let temp_dir = TempDir::new().unwrap();
+        let input = temp_dir.path().join("tone.wav");
+        let output = temp_dir.path().join("tone_out.wav");
+        let format = wav_cdp::WavFormat {
+            channels: 2,
+            sample_rate: 48000,
+            bits_per_sample: 32,
+            data_size: 0,
+        };
+        let tone: Vec<f32> = (0..48000)
+            .flat_map(|i| {
+                let s = 0.5 * (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / 48000.0).sin();
+                [s, s]
+            })
+            .collect();
+        wav_cdp::write_wav_cdp_samples(&input, &format, &wav_cdp::Samples::F32(tone)).unwrap();
+
+        normalize_lufs(&input, &output, -23.0).unwrap();
+        let (_, out) = wav_cdp::read_wav_samples(&output).unwrap();
+        let lufs = integrated_loudness(&out.to_f32(), 2, 48000).unwrap();
+        assert!((lufs + 23.0).abs() < 0.01, "measured {}", lufs);
+    }
+
+    #[test]
+    fn test_float_gain_is_unclamped() {
+        let temp_dir = TempDir::new().unwrap();
+        let input = temp_dir.path().join("input.wav");
+        let gained = temp_dir.path().join("gained.wav");
+        let normalized = temp_dir.path().join("normalized.wav");
+
+        let format = wav_cdp::WavFormat {
+            channels: 1,
+            sample_rate: 44100,
+            bits_per_sample: 32,
+            data_size: 0,
+        };
+        let samples = wav_cdp::Samples::F32(vec![0.25, -0.5, 0.75, 0.125]);
+        wav_cdp::write_wav_cdp_samples(&input, &format, &samples).unwrap();
+
+        // Float output keeps levels above full scale
+        apply_gain(&input, &gained, 2.0).unwrap();
+        let (_, out) = wav_cdp::read_wav_samples(&gained).unwrap();
+        assert_eq!(out.to_f32(), vec![0.5, -1.0, 1.5, 0.25]);
+
+        // Normalizing brings the float peak back to exactly 1.0
+        normalize(&gained, &normalized, None).unwrap();
+        let (_, out) = wav_cdp::read_wav_samples(&normalized).unwrap();
+        assert_eq!(out.to_f32()[2], 1.0);
+    }
 }
@@ -0,0 +1,219 @@
+//! Integrated loudness measurement
+//!
+//! Measures loudness in LUFS following ITU-R BS.1770: each channel is
+//! K-weighted, mean square power is taken over 400 ms blocks overlapping by
+//! 75%, and blocks are gated absolutely at -70 LUFS and then relatively at
+//! 10 LU below the loudness of the blocks that survive.
+
+/// Block length in seconds
+const BLOCK_SECONDS: f64 = 0.4;
+
+/// Blocks step by a quarter of their length
+const STEPS_PER_BLOCK: usize = 4;
+
+/// Blocks quieter than this never count towards the integrated loudness
+const ABSOLUTE_GATE: f64 = -70.0;
+
+/// Blocks this far below the absolutely gated loudness are discarded
+const RELATIVE_GATE: f64 = -10.0;
+
+/// Streaming integrated loudness meter
+///
+/// Feed interleaved samples with [`process`](Self::process) in blocks of
+/// any size, then read the result with [`integrated`](Self::integrated).
+#[derive(Debug, Clone)]
+pub struct LoudnessMeter {
+    channels: usize,
+    weights: Vec<f64>,
+    filters: Vec<[Biquad; 2]>,
+    step_frames: usize,
+    frames_in_step: usize,
+    step_power: f64,
+    steps: Vec<f64>,
+}
+
+impl LoudnessMeter {
+    /// Create a meter for interleaved audio
+    ///
+    /// Six-channel input is treated as 5.1 (L, R, C, LFE, Ls, Rs): the LFE
+    /// is ignored and the surrounds are weighted by 1.41 as BS.1770
+    /// specifies. Every other layout weights all channels equally.
+    pub fn new(channels: u16, sample_rate: u32) -> Self {
+        let channels = channels.max(1) as usize;
+        let weights = if channels == 6 {
+            vec![1.0, 1.0, 1.0, 0.0, 1.41, 1.41]
+        } else {
+            vec![1.0; channels]
+        };
+        let rate = sample_rate as f64;
+        Self {
+            channels,
+            weights,
+            filters: vec![[Biquad::high_shelf(rate), Biquad::high_pass(rate)]; channels],
+            step_frames: ((BLOCK_SECONDS * rate / STEPS_PER_BLOCK as f64).round() as usize).max(1),
+            frames_in_step: 0,
+            step_power: 0.0,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Measure a block of interleaved samples
+    pub fn process(&mut self, samples: &[f32]) {
+        for frame in samples.chunks_exact(self.channels) {
+            for (channel, &sample) in frame.iter().enumerate() {
+                let [shelf, pass] = &mut self.filters[channel];
+                let weighted = pass.process(shelf.process(sample as f64));
+                self.step_power += self.weights[channel] * weighted * weighted;
+            }
+
+            self.frames_in_step += 1;
+            if self.frames_in_step == self.step_frames {
+                self.steps.push(self.step_power);
+                self.frames_in_step = 0;
+                self.step_power = 0.0;
+            }
+        }
+    }
+
+    /// Gated integrated loudness in LUFS
+    ///
+    /// Returns `None` when no block passes the gates, which is the case for
+    /// silence and for input shorter than one 400 ms block.
+    pub fn integrated(&self) -> Option<f64> {
+        let block_frames = (self.step_frames * STEPS_PER_BLOCK) as f64;
+        let powers: Vec<f64> = self
+            .steps
+            .windows(STEPS_PER_BLOCK)
+            .map(|steps| steps.iter().sum::<f64>() / block_frames)
+            .filter(|&power| loudness(power) > ABSOLUTE_GATE)
+            .collect();
+        if powers.is_empty() {
+            return None;
+        }
+
+        let threshold = loudness(mean(&powers)) + RELATIVE_GATE;
+        let gated: Vec<f64> = powers
+            .into_iter()
+            .filter(|&power| loudness(power) > threshold)
+            .collect();
+        Some(loudness(mean(&gated)))
+    }
+}
+
+/// Integrated loudness of in-memory interleaved samples in LUFS
+///
+/// Buffer-based counterpart of [`LoudnessMeter`]; returns `None` when no
+/// block passes the gates.
+pub fn integrated_loudness(samples: &[f32], channels: u16, sample_rate: u32) -> Option<f64> {
+    let mut meter = LoudnessMeter::new(channels, sample_rate);
+    meter.process(samples);
+    meter.integrated()
+}
+
+/// Loudness in LUFS of a weighted mean square power
+fn loudness(power: f64) -> f64 {
+    -0.691 + 10.0 * power.log10()
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Second-order IIR section, transposed direct form II
+#[derive(Debug, Clone, Copy)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    state: [f64; 2],
+}
+
+impl Biquad {
+    /// Normalize coefficients by `a0`
+    fn new(b: [f64; 3], a: [f64; 3]) -> Self {
+        Self {
+            b: [b[0] / a[0], b[1] / a[0], b[2] / a[0]],
+            a: [a[1] / a[0], a[2] / a[0]],
+            state: [0.0; 2],
+        }
+    }
+
+    /// First K-weighting stage: +4 dB shelf above about 1.5 kHz modelling the head
+    fn high_shelf(sample_rate: f64) -> Self {
+        let gain = 10.0f64.powf(4.0 / 40.0);
+        let w0 = 2.0 * std::f64::consts::PI * 1500.0 / sample_rate;
+        let alpha = w0.sin() / (2.0 * std::f64::consts::FRAC_1_SQRT_2);
+        let cos = w0.cos();
+        let root = 2.0 * gain.sqrt() * alpha;
+        Self::new(
+            [
+                gain * ((gain + 1.0) + (gain - 1.0) * cos + root),
+                -2.0 * gain * ((gain - 1.0) + (gain + 1.0) * cos),
+                gain * ((gain + 1.0) + (gain - 1.0) * cos - root),
+            ],
+            [
+                (gain + 1.0) - (gain - 1.0) * cos + root,
+                2.0 * ((gain - 1.0) - (gain + 1.0) * cos),
+                (gain + 1.0) - (gain - 1.0) * cos - root,
+            ],
+        )
+    }
+
+    /// Second K-weighting stage: high-pass at 38 Hz
+    fn high_pass(sample_rate: f64) -> Self {
+        let w0 = 2.0 * std::f64::consts::PI * 38.0 / sample_rate;
+        let alpha = w0.sin() / (2.0 * 0.5);
+        let cos = w0.cos();
+        Self::new(
+            [(1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0],
+            [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+        )
+    }
+
+    fn process(&mut self, input: f64) -> f64 {
+        let output = self.b[0] * input + self.state[0];
+        self.state[0] = self.b[1] * input - self.a[0] * output + self.state[1];
+        self.state[1] = self.b[2] * input - self.a[1] * output;
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(frequency: f32, amplitude: f32, seconds: f32, sample_rate: u32) -> Vec<f32> {
+        let frames = (seconds * sample_rate as f32) as usize;
+        (0..frames)
+            .map(|i| {
+                let phase = 2.0 * std::f32::consts::PI * frequency * i as f32 / sample_rate as f32;
+                amplitude * phase.sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_full_scale_sine_reads_minus_three() {
+        // BS.1770 calibration: a 0 dBFS 1 kHz sine in one channel is -3.01 LUFS
+        let samples = sine(1000.0, 1.0, 2.0, 48000);
+        let lufs = integrated_loudness(&samples, 1, 48000).unwrap();
+        assert!((lufs + 3.01).abs() < 0.05, "measured {}", lufs);
+
+        // Two identical channels are 3 dB louder
+        let stereo: Vec<f32> = samples.iter().flat_map(|&s| [s, s]).collect();
+        let lufs = integrated_loudness(&stereo, 2, 48000).unwrap();
+        assert!(lufs.abs() < 0.05, "measured {}", lufs);
+    }
+
+    #[test]
+    fn test_gating() {
+        assert_eq!(integrated_loudness(&[0.0; 48000], 1, 48000), None);
+        assert_eq!(integrated_loudness(&[0.5; 100], 1, 48000), None);
+
+        // A long quiet tail more than 10 LU down is gated out; only the
+        // blocks straddling the join pull the result slightly down
+        let mut samples = sine(1000.0, 1.0, 2.0, 44100);
+        samples.extend(sine(1000.0, 0.01, 10.0, 44100));
+        let lufs = integrated_loudness(&samples, 1, 44100).unwrap();
+        assert!((lufs + 3.01).abs() < 0.5, "measured {}", lufs);
+    }
+}
@@ -0,0 +1,251 @@
+//! Multichannel panning
+//!
+//! Moves a mono source around a ring of loudspeakers, in the spirit of
+//! CDP's multichannel panning, producing an interleaved N-channel sound
+
+use super::{ModifyError, Result};
+use crate::speed::read_breakpoints;
+use cdp_core::Breakpoints;
+use cdp_housekeep::wav_cdp;
+use std::f64::consts::FRAC_PI_2;
+use std::path::Path;
+
+/// Loudspeaker arrangement for [`pan_multi`]
+///
+/// Angles are in degrees clockwise from straight ahead. Channels follow
+/// the standard WAV order for each layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SpeakerLayout {
+    /// Front left, front right, rear left, rear right at ±45° and ±135°
+    Quad,
+    /// Left, right, centre, LFE, left and right surround at ±30°, 0° and
+    /// ±110°; the LFE channel stays silent
+    Surround51,
+    /// Eight speakers every 45°, the first straight ahead
+    Octophonic,
+    /// This many speakers evenly spaced, the first straight ahead
+    Ring(u16),
+}
+
+impl SpeakerLayout {
+    /// Number of output channels
+    pub fn channels(self) -> u16 {
+        match self {
+            SpeakerLayout::Quad => 4,
+            SpeakerLayout::Surround51 => 6,
+            SpeakerLayout::Octophonic => 8,
+            SpeakerLayout::Ring(count) => count,
+        }
+    }
+
+    /// (angle, channel) of each full-range speaker, sorted by angle in 0-360
+    fn ring(self) -> Vec<(f64, usize)> {
+        let mut ring = match self {
+            SpeakerLayout::Quad => vec![(-45.0, 0), (45.0, 1), (-135.0, 2), (135.0, 3)],
+            SpeakerLayout::Surround51 => {
+                vec![(-30.0, 0), (30.0, 1), (0.0, 2), (-110.0, 4), (110.0, 5)]
+            }
+            SpeakerLayout::Octophonic | SpeakerLayout::Ring(_) => {
+                let count = self.channels() as usize;
+                (0..count)
+                    .map(|channel| (channel as f64 * 360.0 / count as f64, channel))
+                    .collect()
+            }
+        };
+        for speaker in &mut ring {
+            speaker.0 = speaker.0.rem_euclid(360.0);
+        }
+        ring.sort_by(|a, b| a.0.total_cmp(&b.0));
+        ring
+    }
+}
+
+/// Pan a mono sound around a loudspeaker layout
+///
+/// # Arguments
+/// * `input` - Path to mono input audio file
+/// * `output` - Path to output audio file with the layout's channel count
+/// * `layout` - Loudspeaker arrangement
+/// * `trajectory` - Vec of (time, angle) pairs, angles in degrees
+///   clockwise from straight ahead; angles are not wrapped, so 0 to 720
+///   circles twice
+pub fn pan_multi(
+    input: &Path,
+    output: &Path,
+    layout: SpeakerLayout,
+    trajectory: &[(f64, f64)],
+) -> Result<()> {
+    pan_multi_breakpoints(layout, trajectory)?;
+    let (mut audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    if audio.channels != 1 {
+        return Err(ModifyError::InvalidParameter(
+            "Panning requires a mono input".into(),
+        ));
+    }
+    audio.data = pan_multi_buf(&audio.data, audio.sample_rate, layout, trajectory)?;
+    audio.channels = layout.channels();
+    wav_cdp::write_audio_buffer(output, &audio, sample_type)?;
+    Ok(())
+}
+
+/// Pan in-memory mono samples around a loudspeaker layout
+///
+/// Returns interleaved samples with the layout's channel count. Each frame
+/// feeds the two speakers either side of the source's angle with a
+/// constant-power crossfade, so a source on a speaker plays from it alone.
+pub fn pan_multi_buf(
+    samples: &[f32],
+    sample_rate: u32,
+    layout: SpeakerLayout,
+    trajectory: &[(f64, f64)],
+) -> Result<Vec<f32>> {
+    let trajectory = pan_multi_breakpoints(layout, trajectory)?;
+    if sample_rate == 0 {
+        return Err(ModifyError::InvalidParameter(
+            "Sample rate must be greater than 0".into(),
+        ));
+    }
+
+    let ring = layout.ring();
+    let channels = layout.channels() as usize;
+    let mut output = vec![0.0f32; samples.len() * channels];
+    for (frame_idx, &sample) in samples.iter().enumerate() {
+        let time = frame_idx as f64 / sample_rate as f64;
+        let angle = trajectory.value_at(time).rem_euclid(360.0);
+
+        // Speakers either side of the angle, wrapping past 360
+        let next = ring.partition_point(|&(a, _)| a <= angle) % ring.len();
+        let previous = (next + ring.len() - 1) % ring.len();
+        let (from, from_channel) = ring[previous];
+        let (to, to_channel) = ring[next];
+        let span = (to - from).rem_euclid(360.0);
+        let span = if span == 0.0 { 360.0 } else { span };
+        let fraction = (angle - from).rem_euclid(360.0) / span;
+
+        let frame = &mut output[frame_idx * channels..(frame_idx + 1) * channels];
+        frame[from_channel] += sample * (fraction * FRAC_PI_2).cos() as f32;
+        frame[to_channel] += sample * (fraction * FRAC_PI_2).sin() as f32;
+    }
+    Ok(output)
+}
+
+fn pan_multi_breakpoints(layout: SpeakerLayout, trajectory: &[(f64, f64)]) -> Result<Breakpoints> {
+    if let SpeakerLayout::Ring(count) = layout {
+        if !(2..=64).contains(&count) {
+            return Err(ModifyError::InvalidParameter(
+                "Speaker ring must have between 2 and 64 speakers".into(),
+            ));
+        }
+    }
+    Ok(Breakpoints::new(trajectory.to_vec())?)
+}
+
+/// CLI compatibility layer for multichannel panning
+///
+/// Usage: `mchanpan 1 infile outfile layout trajectory`, where layout is
+/// `quad`, `5.1`, `oct` or a speaker count, and trajectory is a fixed angle
+/// or a breakpoint file of `time angle` lines.
+pub fn mchanpan(mode: i32, args: &[&str]) -> Result<()> {
+    if mode != 1 {
+        return Err(ModifyError::UnsupportedOperation(format!(
+            "Multichannel pan mode {} not yet implemented",
+            mode
+        )));
+    }
+    if args.len() < 4 {
+        return Err(ModifyError::InvalidParameter(
+            "Usage: mchanpan 1 infile outfile layout trajectory".into(),
+        ));
+    }
+    let layout = match args[2] {
+        "quad" => SpeakerLayout::Quad,
+        "5.1" => SpeakerLayout::Surround51,
+        "oct" => SpeakerLayout::Octophonic,
+        count => SpeakerLayout::Ring(count.parse::<u16>().map_err(|_| {
+            ModifyError::InvalidParameter(format!("Unknown speaker layout: {}", count))
+        })?),
+    };
+    let trajectory = match args[3].parse::<f64>() {
+        Ok(angle) => vec![(0.0, angle)],
+        Err(_) => read_breakpoints(Path::new(args[3]))?,
+    };
+    pan_multi(Path::new(args[0]), Path::new(args[1]), layout, &trajectory)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_pan_multi_buf() {
+        let gains = |layout, angle| pan_multi_buf(&[1.0], 1, layout, &[(0.0, angle)]).unwrap();
+        let close = |a: &[f32], b: &[f32]| a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-6);
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+
+        assert!(close(
+            &gains(SpeakerLayout::Quad, 45.0),
+            &[0.0, 1.0, 0.0, 0.0]
+        ));
+        assert!(close(
+            &gains(SpeakerLayout::Quad, 0.0),
+            &[half, half, 0.0, 0.0]
+        ));
+        assert!(close(
+            &gains(SpeakerLayout::Quad, 180.0),
+            &[0.0, 0.0, half, half]
+        ));
+        assert!(close(
+            &gains(SpeakerLayout::Surround51, 0.0),
+            &[0.0, 0.0, 1.0, 0.0, 0.0, 0.0]
+        ));
+
+        // A full turn of an octophonic ring passes every speaker
+        let output = pan_multi_buf(
+            &[1.0; 8],
+            8,
+            SpeakerLayout::Octophonic,
+            &[(0.0, 0.0), (1.0, 360.0)],
+        )
+        .unwrap();
+        for (frame_idx, frame) in output.chunks_exact(8).enumerate() {
+            assert!((frame[frame_idx] - 1.0).abs() < 1e-6);
+        }
+
+        assert!(pan_multi_buf(&[1.0], 1, SpeakerLayout::Ring(1), &[(0.0, 0.0)]).is_err());
+        assert!(pan_multi_buf(&[1.0], 1, SpeakerLayout::Quad, &[]).is_err());
+    }
+
+    #[test]
+    fn test_pan_multi_writes_quad() {
+        let temp_dir = TempDir::new().unwrap();
+        let input = temp_dir.path().join("mono.wav");
+        let output = temp_dir.path().join("quad.wav");
+
+        let format = wav_cdp::WavFormat {
+            channels: 1,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            data_size: 0,
+        };
+        wav_cdp::write_wav_cdp(&input, &format, &[1000, -16000, 8000]).unwrap();
+
+        pan_multi(&input, &output, SpeakerLayout::Quad, &[(0.0, 135.0)]).unwrap();
+        let (out_format, samples) = wav_cdp::read_wav_basic(&output).unwrap();
+        assert_eq!(out_format.channels, 4);
+        assert_eq!(samples[8..12], [0, 0, 0, 8000]);
+
+        // Block alignment of four 16-bit channels, and a PEAK entry for each
+        let bytes = std::fs::read(&output).unwrap();
+        assert_eq!(u16::from_le_bytes(bytes[32..34].try_into().unwrap()), 8);
+        assert_eq!(&bytes[36..40], b"PEAK");
+        assert_eq!(
+            u32::from_le_bytes(bytes[40..44].try_into().unwrap()),
+            8 + 4 * 8
+        );
+        let rear_right = f32::from_le_bytes(bytes[76..80].try_into().unwrap());
+        assert_eq!(rear_right, 16000.0 / 32767.0);
+    }
+}
@@ -0,0 +1,356 @@
+//! Radical modification operations
+//!
+//! Provides CDP's modify radical transforms: reversal, shredding into
+//! randomly reordered chunks, and ring modulation by a sine or a second
+//! sound
+
+use super::{ModifyError, Result};
+use cdp_core::Rng;
+use cdp_housekeep::wav_cdp;
+use std::f64::consts::PI;
+use std::path::Path;
+
+/// Crossfade between reassembled shred chunks, in milliseconds
+const SHRED_CROSSFADE_MS: f64 = 5.0;
+
+/// Play a sound backwards
+pub fn reverse(input: &Path, output: &Path) -> Result<()> {
+    let (mut audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    reverse_buf(&mut audio.data, audio.channels)?;
+    wav_cdp::write_audio_buffer(output, &audio, sample_type)?;
+    Ok(())
+}
+
+/// Cut a sound into random chunks and reassemble them in random order
+///
+/// `chunk_duration` is the mean chunk length in seconds; the whole
+/// shredding is repeated `repeats` times (1-100) on its own output.
+pub fn shred(
+    input: &Path,
+    output: &Path,
+    chunk_duration: f64,
+    repeats: usize,
+    seed: u32,
+) -> Result<()> {
+    check_shred(chunk_duration, repeats)?;
+    let (mut audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    audio.data = shred_buf(
+        &audio.data,
+        audio.channels,
+        audio.sample_rate,
+        chunk_duration,
+        repeats,
+        seed,
+    )?;
+    wav_cdp::write_audio_buffer(output, &audio, sample_type)?;
+    Ok(())
+}
+
+/// Ring modulate a sound with a sine wave of the given frequency in Hz
+pub fn ring_modulate(input: &Path, output: &Path, frequency: f64) -> Result<()> {
+    let (mut audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    ring_modulate_buf(
+        &mut audio.data,
+        audio.channels,
+        audio.sample_rate,
+        frequency,
+    )?;
+    wav_cdp::write_audio_buffer(output, &audio, sample_type)?;
+    Ok(())
+}
+
+/// Ring modulate a sound with a second sound
+///
+/// The output is as long as the shorter input and in the first input's
+/// format.
+pub fn ring_modulate_with(input: &Path, modulator: &Path, output: &Path) -> Result<()> {
+    let (mut audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    let (modulator, _) = wav_cdp::read_audio_buffer(modulator)?;
+    if audio.sample_rate != modulator.sample_rate {
+        return Err(ModifyError::InvalidParameter(
+            "Input files must have the same sample rate".into(),
+        ));
+    }
+    audio.data = ring_modulate_with_buf(
+        &audio.data,
+        audio.channels,
+        &modulator.data,
+        modulator.channels,
+    )?;
+    wav_cdp::write_audio_buffer(output, &audio, sample_type)?;
+    Ok(())
+}
+
+/// Reverse interleaved in-memory float samples frame by frame
+pub fn reverse_buf(samples: &mut [f32], channels: u16) -> Result<()> {
+    check_channels(channels)?;
+    let channels = channels as usize;
+    let frames = samples.len() / channels;
+    for frame in 0..frames / 2 {
+        for channel in 0..channels {
+            samples.swap(
+                frame * channels + channel,
+                (frames - 1 - frame) * channels + channel,
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Shred interleaved in-memory float samples
+///
+/// Each pass cuts the sound at random points into about
+/// `duration / chunk_duration` chunks and lays them end to end in a random
+/// order. Every chunk after the first crossfades over 5 ms from whatever
+/// followed the previous chunk in its source, so the length is kept
+/// exactly and joins are click-free.
+pub fn shred_buf(
+    samples: &[f32],
+    channels: u16,
+    sample_rate: u32,
+    chunk_duration: f64,
+    repeats: usize,
+    seed: u32,
+) -> Result<Vec<f32>> {
+    check_channels(channels)?;
+    check_shred(chunk_duration, repeats)?;
+
+    let channels = channels as usize;
+    let frames = samples.len() / channels;
+    let chunk_frames = (chunk_duration * sample_rate as f64).round().max(1.0) as usize;
+    let crossfade = (SHRED_CROSSFADE_MS / 1000.0 * sample_rate as f64).round() as usize;
+    let chunk_count = (frames / chunk_frames).max(1);
+
+    let mut rng = Rng::new(seed);
+    let mut current = samples[..frames * channels].to_vec();
+    for _ in 0..repeats {
+        let mut bounds: Vec<usize> = (1..chunk_count)
+            .map(|_| 1 + rng.below(frames.saturating_sub(1).max(1)))
+            .collect();
+        bounds.push(0);
+        bounds.push(frames);
+        bounds.sort_unstable();
+        bounds.dedup();
+        let mut chunks: Vec<(usize, usize)> = bounds.windows(2).map(|b| (b[0], b[1])).collect();
+        for i in (1..chunks.len()).rev() {
+            chunks.swap(i, rng.below(i + 1));
+        }
+
+        let mut output = vec![0.0f32; frames * channels];
+        let mut position = 0;
+        for (start, end) in chunks {
+            let len = end - start;
+            let fade_in = if position == 0 { 0 } else { crossfade.min(len) };
+            for offset in 0..len {
+                let gain = if offset < fade_in {
+                    offset as f32 / fade_in as f32
+                } else {
+                    1.0
+                };
+                for channel in 0..channels {
+                    output[(position + offset) * channels + channel] +=
+                        current[(start + offset) * channels + channel] * gain;
+                }
+            }
+
+            // Let the chunk's source continue under the next chunk's fade-in
+            let tail = crossfade.min(frames - end).min(frames - position - len);
+            for offset in 0..tail {
+                let gain = 1.0 - offset as f32 / crossfade as f32;
+                for channel in 0..channels {
+                    output[(position + len + offset) * channels + channel] +=
+                        current[(end + offset) * channels + channel] * gain;
+                }
+            }
+            position += len;
+        }
+        current = output;
+    }
+
+    Ok(current)
+}
+
+/// Ring modulate interleaved in-memory float samples with a sine wave
+pub fn ring_modulate_buf(
+    samples: &mut [f32],
+    channels: u16,
+    sample_rate: u32,
+    frequency: f64,
+) -> Result<()> {
+    check_channels(channels)?;
+    if !(frequency > 0.0 && frequency <= sample_rate as f64 / 2.0) {
+        return Err(ModifyError::InvalidParameter(
+            "Modulation frequency must be greater than 0 and at most Nyquist".into(),
+        ));
+    }
+
+    let step = 2.0 * PI * frequency / sample_rate as f64;
+    for (frame_idx, frame) in samples.chunks_exact_mut(channels as usize).enumerate() {
+        let modulation = (step * frame_idx as f64).sin() as f32;
+        for sample in frame {
+            *sample *= modulation;
+        }
+    }
+    Ok(())
+}
+
+/// Ring modulate interleaved in-memory float samples with a second signal
+///
+/// The modulator must be mono, applied to every channel, or have as many
+/// channels as the input. Returns as many frames as the shorter signal.
+pub fn ring_modulate_with_buf(
+    samples: &[f32],
+    channels: u16,
+    modulator: &[f32],
+    modulator_channels: u16,
+) -> Result<Vec<f32>> {
+    check_channels(channels)?;
+    check_channels(modulator_channels)?;
+    if modulator_channels != 1 && modulator_channels != channels {
+        return Err(ModifyError::InvalidParameter(
+            "Modulator must be mono or match the input's channel count".into(),
+        ));
+    }
+
+    let modulator_frames = modulator.chunks_exact(modulator_channels as usize);
+    let output = samples
+        .chunks_exact(channels as usize)
+        .zip(modulator_frames)
+        .flat_map(|(frame, modulation)| {
+            frame
+                .iter()
+                .enumerate()
+                .map(move |(channel, sample)| sample * modulation[channel % modulation.len()])
+        })
+        .collect();
+    Ok(output)
+}
+
+fn check_channels(channels: u16) -> Result<()> {
+    if channels == 0 {
+        return Err(ModifyError::InvalidParameter(
+            "Channels must be greater than 0".into(),
+        ));
+    }
+    Ok(())
+}
+
+fn check_shred(chunk_duration: f64, repeats: usize) -> Result<()> {
+    if !(chunk_duration > 0.0 && chunk_duration.is_finite()) {
+        return Err(ModifyError::InvalidParameter(
+            "Chunk duration must be greater than 0".into(),
+        ));
+    }
+    if !(1..=100).contains(&repeats) {
+        return Err(ModifyError::InvalidParameter(
+            "Shred repeats must be between 1 and 100".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// CLI compatibility layer for radical operations
+pub fn radical(mode: i32, args: &[&str]) -> Result<()> {
+    let parse = |arg: &str, name: &str| {
+        arg.parse::<f64>()
+            .map_err(|_| ModifyError::InvalidParameter(format!("Invalid {} value", name)))
+    };
+    match mode {
+        1 => {
+            // Reverse
+            if args.len() < 2 {
+                return Err(ModifyError::InvalidParameter(
+                    "Usage: radical 1 infile outfile".into(),
+                ));
+            }
+            reverse(Path::new(args[0]), Path::new(args[1]))
+        }
+        2 => {
+            // Shred
+            if args.len() < 4 {
+                return Err(ModifyError::InvalidParameter(
+                    "Usage: radical 2 infile outfile repeats chunklen [-sseed]".into(),
+                ));
+            }
+            let repeats = args[2]
+                .parse::<usize>()
+                .map_err(|_| ModifyError::InvalidParameter("Invalid repeats value".into()))?;
+            let chunk_duration = parse(args[3], "chunk length")?;
+            let seed = match args.get(4).and_then(|arg| arg.strip_prefix("-s")) {
+                Some(seed) => seed
+                    .parse::<u32>()
+                    .map_err(|_| ModifyError::InvalidParameter("Invalid seed value".into()))?,
+                None => 0,
+            };
+            shred(
+                Path::new(args[0]),
+                Path::new(args[1]),
+                chunk_duration,
+                repeats,
+                seed,
+            )
+        }
+        5 => {
+            // Ring modulation
+            if args.len() < 3 {
+                return Err(ModifyError::InvalidParameter(
+                    "Usage: radical 5 infile outfile modulating-freq".into(),
+                ));
+            }
+            let frequency = parse(args[2], "frequency")?;
+            ring_modulate(Path::new(args[0]), Path::new(args[1]), frequency)
+        }
+        6 => {
+            // Cross modulation
+            if args.len() < 3 {
+                return Err(ModifyError::InvalidParameter(
+                    "Usage: radical 6 infile1 infile2 outfile".into(),
+                ));
+            }
+            ring_modulate_with(Path::new(args[0]), Path::new(args[1]), Path::new(args[2]))
+        }
+        _ => Err(ModifyError::UnsupportedOperation(format!(
+            "Radical mode {} not yet implemented",
+            mode
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reverse_and_ring_modulate_buf() {
+        let mut samples = vec![1.0, -1.0, 2.0, -2.0, 3.0, -3.0];
+        reverse_buf(&mut samples, 2).unwrap();
+        assert_eq!(samples, vec![3.0, -3.0, 2.0, -2.0, 1.0, -1.0]);
+
+        // A quarter of the sample rate: 0, 1, 0, -1
+        let mut samples = vec![0.5; 4];
+        ring_modulate_buf(&mut samples, 1, 8, 2.0).unwrap();
+        let expected = [0.0, 0.5, 0.0, -0.5];
+        assert!(samples
+            .iter()
+            .zip(expected)
+            .all(|(a, b)| (a - b).abs() < 1e-6));
+
+        let output = ring_modulate_with_buf(&[1.0, 2.0, 3.0, 4.0], 2, &[0.5], 1).unwrap();
+        assert_eq!(output, vec![0.5, 1.0]);
+        assert!(ring_modulate_with_buf(&[1.0; 6], 3, &[1.0; 4], 2).is_err());
+    }
+
+    #[test]
+    fn test_shred_buf() {
+        // A ramp at 1kHz, so each chunk's source position is recognisable
+        let samples: Vec<f32> = (0..1000).map(|i| i as f32).collect();
+        let output = shred_buf(&samples, 1, 1000, 0.1, 1, 7).unwrap();
+        assert_eq!(output.len(), samples.len());
+        assert_ne!(output, samples);
+        assert_eq!(output, shred_buf(&samples, 1, 1000, 0.1, 1, 7).unwrap());
+
+        // Chunks longer than the sound leave it unchanged
+        assert_eq!(shred_buf(&samples, 1, 1000, 2.0, 3, 7).unwrap(), samples);
+        assert!(shred_buf(&samples, 1, 1000, 0.1, 0, 7).is_err());
+    }
+}
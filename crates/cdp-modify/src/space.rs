@@ -0,0 +1,243 @@
+//! Spatial operations
+//!
+//! Provides CDP's modify space: constant-power panning of mono sounds into
+//! stereo, mirroring and narrowing of stereo images
+
+use super::{ModifyError, Result};
+use crate::speed::read_breakpoints;
+use cdp_core::{AudioBuffer, Breakpoints};
+use cdp_housekeep::wav_cdp;
+use std::f64::consts::FRAC_PI_4;
+use std::path::Path;
+
+/// Pan a mono sound to a fixed stereo position
+///
+/// Positions run from -1.0 (hard left) through 0.0 (centre) to 1.0
+/// (hard right).
+pub fn pan(input: &Path, output: &Path, position: f64) -> Result<()> {
+    pan_varying(input, output, &[(0.0, position)])
+}
+
+/// Pan a mono sound along a trajectory of (time, position) breakpoints
+pub fn pan_varying(input: &Path, output: &Path, positions: &[(f64, f64)]) -> Result<()> {
+    pan_breakpoints(positions)?;
+    let (mut audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    if audio.channels != 1 {
+        return Err(ModifyError::InvalidParameter(
+            "Panning requires a mono input".into(),
+        ));
+    }
+    audio.data = pan_varying_buf(&audio.data, audio.sample_rate, positions)?;
+    audio.channels = 2;
+    wav_cdp::write_audio_buffer(output, &audio, sample_type)?;
+    Ok(())
+}
+
+/// Swap the channels of a stereo sound
+pub fn mirror(input: &Path, output: &Path) -> Result<()> {
+    let (mut audio, sample_type) = read_stereo(input)?;
+    mirror_buf(&mut audio.data)?;
+    wav_cdp::write_audio_buffer(output, &audio, sample_type)?;
+    Ok(())
+}
+
+/// Narrow the image of a stereo sound
+///
+/// `width` is 1.0 for the original image, 0.0 for mono in both channels,
+/// and negative values narrow a mirrored image, down to -1.0 for a plain
+/// channel swap.
+pub fn narrow(input: &Path, output: &Path, width: f64) -> Result<()> {
+    let (mut audio, sample_type) = read_stereo(input)?;
+    narrow_buf(&mut audio.data, width)?;
+    wav_cdp::write_audio_buffer(output, &audio, sample_type)?;
+    Ok(())
+}
+
+/// Pan in-memory mono samples to a fixed position, returning stereo
+pub fn pan_buf(samples: &[f32], position: f64) -> Result<Vec<f32>> {
+    pan_varying_buf(samples, 1, &[(0.0, position)])
+}
+
+/// Pan in-memory mono samples along a trajectory, returning stereo
+///
+/// Positions between breakpoints are interpolated linearly, and each
+/// frame's gains follow a quarter sine and cosine so the total power stays
+/// constant across the image.
+pub fn pan_varying_buf(
+    samples: &[f32],
+    sample_rate: u32,
+    positions: &[(f64, f64)],
+) -> Result<Vec<f32>> {
+    let positions = pan_breakpoints(positions)?;
+    if sample_rate == 0 {
+        return Err(ModifyError::InvalidParameter(
+            "Sample rate must be greater than 0".into(),
+        ));
+    }
+
+    let mut output = Vec::with_capacity(samples.len() * 2);
+    for (frame_idx, &sample) in samples.iter().enumerate() {
+        let position = positions.value_at(frame_idx as f64 / sample_rate as f64);
+        let angle = (position + 1.0) * FRAC_PI_4;
+        output.push(sample * angle.cos() as f32);
+        output.push(sample * angle.sin() as f32);
+    }
+    Ok(output)
+}
+
+/// Swap the channels of in-memory interleaved stereo samples
+pub fn mirror_buf(samples: &mut [f32]) -> Result<()> {
+    check_stereo_len(samples)?;
+    for frame in samples.chunks_exact_mut(2) {
+        frame.swap(0, 1);
+    }
+    Ok(())
+}
+
+/// Narrow the image of in-memory interleaved stereo samples
+///
+/// The side (difference) signal is scaled by `width` while the mid (sum)
+/// signal is kept, so the mono mix is unchanged.
+pub fn narrow_buf(samples: &mut [f32], width: f64) -> Result<()> {
+    check_stereo_len(samples)?;
+    if !(-1.0..=1.0).contains(&width) {
+        return Err(ModifyError::InvalidParameter(
+            "Width must be between -1 and 1".into(),
+        ));
+    }
+    let width = width as f32;
+    for frame in samples.chunks_exact_mut(2) {
+        let mid = (frame[0] + frame[1]) / 2.0;
+        let side = (frame[0] - frame[1]) / 2.0 * width;
+        frame[0] = mid + side;
+        frame[1] = mid - side;
+    }
+    Ok(())
+}
+
+fn read_stereo(input: &Path) -> Result<(AudioBuffer, wav_cdp::SampleType)> {
+    let (audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    if audio.channels != 2 {
+        return Err(ModifyError::InvalidParameter(
+            "Operation requires a stereo input".into(),
+        ));
+    }
+    Ok((audio, sample_type))
+}
+
+fn check_stereo_len(samples: &[f32]) -> Result<()> {
+    if samples.len() % 2 != 0 {
+        return Err(ModifyError::InvalidParameter(
+            "Stereo samples must come in left and right pairs".into(),
+        ));
+    }
+    Ok(())
+}
+
+fn pan_breakpoints(positions: &[(f64, f64)]) -> Result<Breakpoints> {
+    let positions = Breakpoints::new(positions.to_vec())?;
+    positions.check_range("Pan position", -1.0, 1.0)?;
+    Ok(positions)
+}
+
+/// CLI compatibility layer for space operations
+pub fn space(mode: i32, args: &[&str]) -> Result<()> {
+    match mode {
+        1 => {
+            // Pan, by a fixed position or a breakpoint file
+            if args.len() < 3 {
+                return Err(ModifyError::InvalidParameter(
+                    "Usage: space 1 infile outfile pan".into(),
+                ));
+            }
+            let positions = match args[2].parse::<f64>() {
+                Ok(position) => vec![(0.0, position)],
+                Err(_) => read_breakpoints(Path::new(args[2]))?,
+            };
+            pan_varying(Path::new(args[0]), Path::new(args[1]), &positions)
+        }
+        2 => {
+            // Mirror
+            if args.len() < 2 {
+                return Err(ModifyError::InvalidParameter(
+                    "Usage: space 2 infile outfile".into(),
+                ));
+            }
+            mirror(Path::new(args[0]), Path::new(args[1]))
+        }
+        4 => {
+            // Narrow
+            if args.len() < 3 {
+                return Err(ModifyError::InvalidParameter(
+                    "Usage: space 4 infile outfile narrowing".into(),
+                ));
+            }
+            let width = args[2]
+                .parse::<f64>()
+                .map_err(|_| ModifyError::InvalidParameter("Invalid narrowing value".into()))?;
+            narrow(Path::new(args[0]), Path::new(args[1]), width)
+        }
+        _ => Err(ModifyError::UnsupportedOperation(format!(
+            "Space mode {} not yet implemented",
+            mode
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_space_buffers() {
+        let output = pan_buf(&[1.0, -0.5], 0.0).unwrap();
+        let centre = std::f32::consts::FRAC_1_SQRT_2;
+        assert!((output[0] - centre).abs() < 1e-6 && (output[1] - centre).abs() < 1e-6);
+        assert!((output[2] + centre / 2.0).abs() < 1e-6);
+
+        // From hard left to hard right over one second at 2Hz
+        let output = pan_varying_buf(&[1.0; 3], 2, &[(0.0, -1.0), (1.0, 1.0)]).unwrap();
+        assert!((output[0] - 1.0).abs() < 1e-6 && output[1].abs() < 1e-6);
+        assert!(output[4].abs() < 1e-6 && (output[5] - 1.0).abs() < 1e-6);
+        assert!(pan_buf(&[1.0], 1.5).is_err());
+
+        let mut stereo = vec![1.0, 0.0, 0.5, -0.5];
+        mirror_buf(&mut stereo).unwrap();
+        assert_eq!(stereo, vec![0.0, 1.0, -0.5, 0.5]);
+        narrow_buf(&mut stereo, 0.0).unwrap();
+        assert_eq!(stereo, vec![0.5, 0.5, 0.0, 0.0]);
+        assert!(narrow_buf(&mut [0.0; 3], 0.5).is_err());
+    }
+
+    #[test]
+    fn test_pan_writes_channel_peaks() {
+        let temp_dir = TempDir::new().unwrap();
+        let input = temp_dir.path().join("mono.wav");
+        let output = temp_dir.path().join("stereo.wav");
+
+        let format = wav_cdp::WavFormat {
+            channels: 1,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            data_size: 0,
+        };
+        wav_cdp::write_wav_cdp(&input, &format, &[1000, -16000, 8000]).unwrap();
+
+        pan(&input, &output, -1.0).unwrap();
+        let (out_format, samples) = wav_cdp::read_wav_basic(&output).unwrap();
+        assert_eq!(out_format.channels, 2);
+        assert_eq!(samples, vec![1000, 0, -16000, 0, 8000, 0]);
+
+        // One PEAK value and frame position per channel
+        let bytes = std::fs::read(&output).unwrap();
+        assert_eq!(&bytes[36..40], b"PEAK");
+        let left_peak = f32::from_le_bytes(bytes[52..56].try_into().unwrap());
+        let left_position = u32::from_le_bytes(bytes[56..60].try_into().unwrap());
+        let right_peak = f32::from_le_bytes(bytes[60..64].try_into().unwrap());
+        assert_eq!((left_peak, left_position), (16000.0 / 32767.0, 1));
+        assert_eq!(right_peak, 0.0);
+
+        assert!(mirror(&input, &output).is_err());
+    }
+}
@@ -0,0 +1,241 @@
+//! Tremolo
+//!
+//! Provides CDP's tremolo: periodic amplitude modulation by a low-frequency
+//! oscillator whose rate and depth may change over time
+
+use super::{ModifyError, Result};
+use crate::speed::read_breakpoints;
+use cdp_core::Breakpoints;
+use cdp_housekeep::wav_cdp;
+use std::f64::consts::TAU;
+use std::path::Path;
+
+/// Highest tremolo rate, in Hz
+const MAX_RATE: f64 = 500.0;
+
+/// Shape of the tremolo oscillator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum TremoloWaveform {
+    /// Smooth raised cosine
+    Sine,
+    /// Alternates between full and reduced level each half cycle
+    Square,
+    /// Level falls and rises at a constant rate
+    Triangle,
+}
+
+impl TremoloWaveform {
+    /// Oscillator level (0-1) at `phase` (0-1) through a cycle
+    ///
+    /// Every shape starts a cycle at full level and reaches its lowest
+    /// half way through.
+    fn level(self, phase: f64) -> f64 {
+        match self {
+            TremoloWaveform::Sine => (1.0 + (TAU * phase).cos()) / 2.0,
+            TremoloWaveform::Square => {
+                if phase < 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            TremoloWaveform::Triangle => (1.0 - 2.0 * phase).abs(),
+        }
+    }
+}
+
+/// Apply tremolo at a fixed rate and depth
+///
+/// `depth` runs from 0.0 (no change) to 1.0, where the level falls to
+/// silence at the bottom of each cycle.
+pub fn tremolo(
+    input: &Path,
+    output: &Path,
+    rate_hz: f64,
+    depth: f64,
+    waveform: TremoloWaveform,
+) -> Result<()> {
+    tremolo_varying(input, output, &[(0.0, rate_hz)], &[(0.0, depth)], waveform)
+}
+
+/// Apply tremolo with rate and depth following (time, value) breakpoints
+pub fn tremolo_varying(
+    input: &Path,
+    output: &Path,
+    rates: &[(f64, f64)],
+    depths: &[(f64, f64)],
+    waveform: TremoloWaveform,
+) -> Result<()> {
+    tremolo_breakpoints(rates, depths)?;
+    let (mut audio, sample_type) = wav_cdp::read_audio_buffer(input)?;
+    tremolo_varying_buf(
+        &mut audio.data,
+        audio.channels,
+        audio.sample_rate,
+        rates,
+        depths,
+        waveform,
+    )?;
+    wav_cdp::write_audio_buffer(output, &audio, sample_type)?;
+    Ok(())
+}
+
+/// Apply tremolo at a fixed rate and depth to interleaved in-memory samples
+pub fn tremolo_buf(
+    samples: &mut [f32],
+    channels: u16,
+    sample_rate: u32,
+    rate_hz: f64,
+    depth: f64,
+    waveform: TremoloWaveform,
+) -> Result<()> {
+    tremolo_varying_buf(
+        samples,
+        channels,
+        sample_rate,
+        &[(0.0, rate_hz)],
+        &[(0.0, depth)],
+        waveform,
+    )
+}
+
+/// Apply time-varying tremolo to interleaved in-memory samples
+///
+/// Rate and depth are interpolated linearly between breakpoints. The
+/// oscillator's phase advances by the current rate each frame, so rate
+/// changes glide without jumps, and every channel of a frame gets the same
+/// gain of `1 - depth * (1 - level)`.
+pub fn tremolo_varying_buf(
+    samples: &mut [f32],
+    channels: u16,
+    sample_rate: u32,
+    rates: &[(f64, f64)],
+    depths: &[(f64, f64)],
+    waveform: TremoloWaveform,
+) -> Result<()> {
+    let (rates, depths) = tremolo_breakpoints(rates, depths)?;
+    if channels == 0 || sample_rate == 0 {
+        return Err(ModifyError::InvalidParameter(
+            "Channels and sample rate must be greater than 0".into(),
+        ));
+    }
+
+    let mut phase = 0.0f64;
+    for (frame_idx, frame) in samples.chunks_exact_mut(channels as usize).enumerate() {
+        let time = frame_idx as f64 / sample_rate as f64;
+        let depth = depths.value_at(time);
+        let gain = (1.0 - depth * (1.0 - waveform.level(phase))) as f32;
+        for sample in frame {
+            *sample *= gain;
+        }
+        phase = (phase + rates.value_at(time) / sample_rate as f64).fract();
+    }
+    Ok(())
+}
+
+fn tremolo_breakpoints(
+    rates: &[(f64, f64)],
+    depths: &[(f64, f64)],
+) -> Result<(Breakpoints, Breakpoints)> {
+    let rates = Breakpoints::new(rates.to_vec())?;
+    rates.check_range("Tremolo rate", 0.0, MAX_RATE)?;
+    let depths = Breakpoints::new(depths.to_vec())?;
+    depths.check_range("Tremolo depth", 0.0, 1.0)?;
+    Ok((rates, depths))
+}
+
+/// CLI compatibility layer for tremolo
+///
+/// Usage: `tremolo 1 infile outfile rate depth [sine|square|triangle]`,
+/// where rate and depth may also name breakpoint files.
+pub fn tremolo_cli(mode: i32, args: &[&str]) -> Result<()> {
+    if mode != 1 {
+        return Err(ModifyError::UnsupportedOperation(format!(
+            "Tremolo mode {} not yet implemented",
+            mode
+        )));
+    }
+    if args.len() < 4 {
+        return Err(ModifyError::InvalidParameter(
+            "Usage: tremolo 1 infile outfile rate depth [sine|square|triangle]".into(),
+        ));
+    }
+    let breakpoints = |arg: &str| match arg.parse::<f64>() {
+        Ok(value) => Ok(vec![(0.0, value)]),
+        Err(_) => read_breakpoints(Path::new(arg)),
+    };
+    let waveform = match args.get(4).copied().unwrap_or("sine") {
+        "sine" => TremoloWaveform::Sine,
+        "square" => TremoloWaveform::Square,
+        "triangle" => TremoloWaveform::Triangle,
+        other => {
+            return Err(ModifyError::InvalidParameter(format!(
+                "Unknown tremolo waveform: {}",
+                other
+            )))
+        }
+    };
+    tremolo_varying(
+        Path::new(args[0]),
+        Path::new(args[1]),
+        &breakpoints(args[2])?,
+        &breakpoints(args[3])?,
+        waveform,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tremolo_buf() {
+        // One cycle per second at 4Hz: full, half way down, lowest, half way up
+        let mut samples = vec![1.0; 4];
+        tremolo_buf(&mut samples, 1, 4, 1.0, 1.0, TremoloWaveform::Triangle).unwrap();
+        assert_eq!(samples, vec![1.0, 0.5, 0.0, 0.5]);
+
+        let mut samples = vec![1.0; 8];
+        tremolo_buf(&mut samples, 2, 4, 1.0, 0.5, TremoloWaveform::Square).unwrap();
+        assert_eq!(samples, vec![1.0, 1.0, 1.0, 1.0, 0.5, 0.5, 0.5, 0.5]);
+
+        let mut samples = vec![1.0; 3];
+        tremolo_buf(&mut samples, 1, 4, 1.0, 1.0, TremoloWaveform::Sine).unwrap();
+        assert!((samples[1] - 0.5).abs() < 1e-6 && samples[2].abs() < 1e-6);
+
+        assert!(tremolo_buf(&mut samples, 1, 4, 1.0, 1.5, TremoloWaveform::Sine).is_err());
+        assert!(tremolo_buf(&mut samples, 1, 4, -1.0, 0.5, TremoloWaveform::Sine).is_err());
+    }
+
+    #[test]
+    fn test_tremolo_varying_buf() {
+        // Depth rises from nothing to full while the rate holds at 1Hz
+        let mut samples = vec![1.0; 4];
+        let depths = [(0.0, 0.0), (0.5, 1.0)];
+        tremolo_varying_buf(
+            &mut samples,
+            1,
+            4,
+            &[(0.0, 1.0)],
+            &depths,
+            TremoloWaveform::Triangle,
+        )
+        .unwrap();
+        assert_eq!(samples, vec![1.0, 0.75, 0.0, 0.5]);
+
+        // With no rate the oscillator stays at full level
+        let mut samples = vec![1.0; 4];
+        tremolo_varying_buf(
+            &mut samples,
+            1,
+            4,
+            &[(0.0, 0.0)],
+            &[(0.0, 1.0)],
+            TremoloWaveform::Sine,
+        )
+        .unwrap();
+        assert_eq!(samples, vec![1.0; 4]);
+    }
+}
@@ -99,7 +99,7 @@ impl Validator {
         let rust = &rust[..min_len];
 
         // Sample-level comparison
-        let sample_correlation = self.calculate_correlation(cdp, rust);
+        let sample_correlation = correlation(cdp, rust);
 
         // Spectral comparison (more forgiving of small differences)
         let cdp_spectrum = self.analyzer.analyze(cdp);
@@ -107,20 +107,8 @@ impl Validator {
         let spectral_correlation = self.analyzer.compare_spectra(&cdp_spectrum, &rust_spectrum);
 
         // Calculate differences
-        let max_diff = cdp
-            .iter()
-            .zip(rust.iter())
-            .map(|(a, b)| (a - b).abs())
-            .fold(0.0f32, f32::max);
-
-        let rms_diff = {
-            let sum: f32 = cdp
-                .iter()
-                .zip(rust.iter())
-                .map(|(a, b)| (a - b).powi(2))
-                .sum();
-            (sum / min_len as f32).sqrt()
-        };
+        let max_diff = max_difference(cdp, rust);
+        let rms_diff = rms_difference(cdp, rust);
 
         let passed = spectral_correlation >= self.oracle.config.spectral_threshold;
 
@@ -133,24 +121,52 @@ impl Validator {
             rms_difference: rms_diff,
         })
     }
+}
 
-    fn calculate_correlation(&self, a: &[f32], b: &[f32]) -> f32 {
-        let n = a.len() as f32;
-        let sum_a: f32 = a.iter().sum();
-        let sum_b: f32 = b.iter().sum();
-        let sum_aa: f32 = a.iter().map(|x| x * x).sum();
-        let sum_bb: f32 = b.iter().map(|x| x * x).sum();
-        let sum_ab: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
-
-        let numerator = n * sum_ab - sum_a * sum_b;
-        let denominator = ((n * sum_aa - sum_a * sum_a) * (n * sum_bb - sum_b * sum_b)).sqrt();
-
-        if denominator == 0.0 {
-            0.0
-        } else {
-            numerator / denominator
-        }
+/// Pearson correlation of two signals over their common length
+///
+/// Returns 0.0 when either signal is constant. Sums are accumulated in
+/// f64 so long files keep their precision.
+pub fn correlation(a: &[f32], b: &[f32]) -> f32 {
+    let len = a.len().min(b.len());
+    let (a, b) = (&a[..len], &b[..len]);
+    let n = len as f64;
+    let sum_a: f64 = a.iter().map(|&x| x as f64).sum();
+    let sum_b: f64 = b.iter().map(|&x| x as f64).sum();
+    let sum_aa: f64 = a.iter().map(|&x| x as f64 * x as f64).sum();
+    let sum_bb: f64 = b.iter().map(|&x| x as f64 * x as f64).sum();
+    let sum_ab: f64 = a.iter().zip(b).map(|(&x, &y)| x as f64 * y as f64).sum();
+
+    let numerator = n * sum_ab - sum_a * sum_b;
+    let denominator = ((n * sum_aa - sum_a * sum_a) * (n * sum_bb - sum_b * sum_b)).sqrt();
+
+    if denominator == 0.0 {
+        0.0
+    } else {
+        (numerator / denominator) as f32
+    }
+}
+
+/// Largest absolute difference between two signals over their common length
+pub fn max_difference(a: &[f32], b: &[f32]) -> f32 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y).abs())
+        .fold(0.0f32, f32::max)
+}
+
+/// RMS of the difference between two signals over their common length
+pub fn rms_difference(a: &[f32], b: &[f32]) -> f32 {
+    let len = a.len().min(b.len());
+    if len == 0 {
+        return 0.0;
     }
+    let sum: f64 = a
+        .iter()
+        .zip(b)
+        .map(|(&x, &y)| (x as f64 - y as f64).powi(2))
+        .sum();
+    (sum / len as f64).sqrt() as f32
 }
 
 #[cfg(test)]
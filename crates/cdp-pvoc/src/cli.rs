@@ -0,0 +1,221 @@
+//! CDP pvoc command-line interface
+//!
+//! Shared by the pvoc binary and the cdp multicall binary.
+
+use crate::{PvocAnalParams, PvocSynthParams};
+use cdp_core::TextProgress;
+use std::path::Path;
+use std::process;
+
+/// Run pvoc with CDP's syntax, with `args[0]` the program name
+///
+/// Usage errors print CDP's usage text and exit the process.
+pub fn pvoc_cli(args: &[String]) {
+    if args.len() < 2 {
+        print_usage();
+        process::exit(1);
+    }
+
+    match args[1].as_str() {
+        "anal" => handle_anal(&args[2..]),
+        "synth" => handle_synth(&args[2..]),
+        "extract" => handle_extract(&args[2..]),
+        _ => {
+            print_usage();
+            process::exit(1);
+        }
+    }
+}
+
+fn print_usage() {
+    eprintln!("CDP Release 7.1 2016");
+    eprintln!("USAGE: pvoc NAME (mode) infile outfile (parameters)");
+    eprintln!();
+    eprintln!("where NAME can be any one of");
+    eprintln!();
+    eprintln!("anal   synth 	extract");
+    eprintln!();
+    eprintln!("Type 'pvoc anal'  for more info on pvoc anal option... ETC.");
+}
+
+fn handle_anal(args: &[String]) {
+    if args.is_empty() {
+        eprintln!("CDP Release 7.1 2016");
+        eprintln!("CONVERT SOUNDFILE TO SPECTRAL FILE");
+        eprintln!();
+        eprintln!("USAGE: pvoc anal  mode infile outfile [-cpoints] [-ooverlap | -hhop]");
+        eprintln!();
+        eprintln!("MODES ARE....");
+        eprintln!("1) STANDARD ANALYSIS");
+        eprintln!("2) OUTPUT SPECTRAL ENVELOPE VALS ONLY");
+        eprintln!("3) OUTPUT SPECTRAL MAGNITUDE VALS ONLY");
+        eprintln!("POINTS   No of analysis points (2-32768 (power of 2)): default 1024");
+        eprintln!("         More points give better freq resolution");
+        eprintln!("         but worse time-resolution (e.g. rapidly changing spectrum).");
+        eprintln!("OVERLAP  Filter overlap factor (1-4): default 3");
+        eprintln!("HOP      Samples between analysis windows (1-points), instead of overlap");
+        process::exit(1);
+    }
+
+    if args.len() < 3 {
+        eprintln!("ERROR: Insufficient arguments");
+        process::exit(1);
+    }
+
+    let mode: u32 = match args[0].parse() {
+        Ok(m) if (1..=3).contains(&m) => m,
+        _ => {
+            eprintln!("ERROR: Invalid mode (must be 1-3)");
+            process::exit(1);
+        }
+    };
+
+    let infile = Path::new(&args[1]);
+    let outfile = Path::new(&args[2]);
+
+    // Parse optional parameters
+    let mut params = PvocAnalParams::builder().mode(mode);
+
+    let mut i = 3;
+    while i < args.len() {
+        if args[i].starts_with("-c") {
+            if let Ok(c) = args[i][2..].parse::<u32>() {
+                // Verify power of 2
+                if (2..=32768).contains(&c) && (c & (c - 1)) == 0 {
+                    params = params.fft_size(c);
+                } else {
+                    eprintln!("ERROR: Channels must be power of 2 between 2 and 32768");
+                    process::exit(1);
+                }
+            }
+        } else if args[i].starts_with("-o") {
+            if let Ok(o) = args[i][2..].parse::<u32>() {
+                match cdp_core::validate(cdp_core::Param::Overlap, o as f64) {
+                    Ok(_) => params = params.overlap(o),
+                    Err(e) => {
+                        eprintln!("ERROR: {}", e);
+                        process::exit(1);
+                    }
+                }
+            }
+        } else if args[i].starts_with("-h") {
+            match args[i][2..].parse::<u32>() {
+                Ok(h) if h >= 1 => params = params.hop(h),
+                _ => {
+                    eprintln!("ERROR: Hop must be a positive number of samples");
+                    process::exit(1);
+                }
+            }
+        }
+        i += 1;
+    }
+
+    // Call the library function
+    eprintln!("analysis/synthesis beginning");
+    let result = params.build().and_then(|params| {
+        crate::pvoc_anal_with_params(infile, outfile, &params, &mut TextProgress::new())
+    });
+    match result {
+        Ok(_) => {}
+        Err(e) => {
+            eprintln!("ERROR: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn handle_synth(args: &[String]) {
+    if args.is_empty() {
+        eprintln!("CDP Release 7.1 2016");
+        eprintln!("CONVERT SPECTRAL FILE TO SOUNDFILE");
+        eprintln!();
+        eprintln!("USAGE: pvoc synth infile outfile");
+        process::exit(1);
+    }
+
+    if args.len() < 2 {
+        eprintln!("ERROR: Insufficient arguments");
+        process::exit(1);
+    }
+
+    let infile = Path::new(&args[0]);
+    let outfile = Path::new(&args[1]);
+
+    eprintln!("analysis/synthesis beginning");
+    match crate::pvoc_synth_with_params(
+        infile,
+        outfile,
+        &PvocSynthParams::default(),
+        &mut TextProgress::new(),
+    ) {
+        Ok(_) => {}
+        Err(e) => {
+            eprintln!("ERROR: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn handle_extract(args: &[String]) {
+    if args.is_empty() {
+        eprintln!("CDP Release 7.1 2016");
+        eprintln!("EXTRACT FREQUENCY BAND FROM SPECTRAL FILE");
+        eprintln!();
+        eprintln!(
+            "USAGE: pvoc extract infile outfile lo_freq hi_freq [lo_freq hi_freq ...] [-r] [-adb]"
+        );
+        eprintln!();
+        eprintln!("LO_FREQ HI_FREQ  Band to keep, in Hz: several bands may be given");
+        eprintln!("-r               Reject the bands instead of keeping them");
+        eprintln!("-adb             Attenuate removed bins by db instead of zeroing them");
+        process::exit(1);
+    }
+
+    if args.len() < 4 {
+        eprintln!("ERROR: Insufficient arguments");
+        process::exit(1);
+    }
+
+    let infile = Path::new(&args[0]);
+    let outfile = Path::new(&args[1]);
+
+    // Frequencies come in lo/hi pairs, followed by any flags
+    let mut freqs = Vec::new();
+    let mut mode = crate::BandMode::Pass;
+    let mut attenuation = None;
+    for arg in &args[2..] {
+        if arg == "-r" {
+            mode = crate::BandMode::Reject;
+        } else if let Some(db) = arg.strip_prefix("-a") {
+            match db.parse::<f32>() {
+                Ok(db) => attenuation = Some(db),
+                Err(_) => {
+                    eprintln!("ERROR: Invalid attenuation");
+                    process::exit(1);
+                }
+            }
+        } else {
+            match arg.parse::<f32>() {
+                Ok(f) => freqs.push(f),
+                Err(_) => {
+                    eprintln!("ERROR: Invalid frequency {}", arg);
+                    process::exit(1);
+                }
+            }
+        }
+    }
+
+    if freqs.is_empty() || freqs.len() % 2 != 0 {
+        eprintln!("ERROR: Frequencies must be given as lo_freq hi_freq pairs");
+        process::exit(1);
+    }
+    let bands: Vec<(f32, f32)> = freqs.chunks_exact(2).map(|b| (b[0], b[1])).collect();
+
+    match crate::pvoc_extract_bands(infile, outfile, &bands, mode, attenuation) {
+        Ok(_) => {}
+        Err(e) => {
+            eprintln!("ERROR: {}", e);
+            process::exit(1);
+        }
+    }
+}
@@ -2,14 +2,25 @@
 //!
 //! This crate provides phase vocoder functionality matching CDP's implementation.
 //! The analysis files (.ana) are stored as WAV files with IEEE float format.
-
+//!
+//! Standard analyses hold an amplitude/frequency pair per bin, as CDP does:
+//! the amplitude of the partial in that bin and its frequency in Hz, derived
+//! from the phase advance between successive windows.
+
+pub mod cli;
+pub mod params;
+
+use cdp_core::progress::{self, ProgressSink};
+use cdp_core::{fs, AudioBuffer, Window, WindowFunction};
+pub use cdp_core::{AnaFile, AnaHeader};
+use cdp_housekeep::quantize::{QuantizeOptions, Quantizer};
+use cdp_housekeep::wav_cdp::SampleType;
 use num_complex::Complex32;
+pub use params::{PvocAnalParams, PvocAnalParamsBuilder, PvocSynthParams, PvocSynthParamsBuilder};
 use rustfft::{num_complex::ComplexFloat, FftPlanner};
 use std::f32::consts::PI;
-use std::fs::File;
-use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
-use std::path::Path;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::io;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -25,26 +36,20 @@ pub enum PvocError {
 
     #[error("Housekeep error: {0}")]
     Housekeep(#[from] cdp_housekeep::HousekeepError),
+
+    #[error("Core error: {0}")]
+    Core(#[from] cdp_core::CoreError),
 }
 
 pub type Result<T> = std::result::Result<T, PvocError>;
 
-/// CDP .ana file header information
-#[derive(Debug, Clone)]
-pub struct AnaHeader {
-    /// Sample rate of original file
-    pub sample_rate: u32,
-    /// Number of frequency channels (half FFT size)
-    pub channels: u32,
-    /// Analysis window length
-    pub window_len: u32,
-    /// Decimation factor (hop size divisor)
-    pub dec_factor: u32,
-    /// Original file size in samples
-    pub orig_size: u32,
-}
+/// Smallest window gain compensated for, relative to the steady-state gain
+const WINDOW_GAIN_FLOOR: f32 = 0.1;
 
 /// Perform phase vocoder analysis
+///
+/// Multichannel inputs are analysed channel by channel into
+/// `outfile_c1.ana`, `outfile_c2.ana`, etc. (see [`channel_ana_path`]).
 pub fn pvoc_anal(
     input_path: &Path,
     output_path: &Path,
@@ -52,35 +57,216 @@ pub fn pvoc_anal(
     channels: Option<u32>,
     overlap: Option<u32>,
 ) -> Result<()> {
-    // Default parameters
-    let fft_size = channels.unwrap_or(1024);
-    let overlap_factor = overlap.unwrap_or(3);
+    let defaults = PvocAnalParams::default();
+    let params = PvocAnalParams {
+        mode,
+        fft_size: channels.unwrap_or(defaults.fft_size),
+        overlap: overlap.unwrap_or(defaults.overlap),
+        ..defaults
+    };
+    pvoc_anal_with_params(input_path, output_path, &params, &mut ())
+}
 
-    // Validate FFT size is power of 2
-    if !(2..=32768).contains(&fft_size) || (fft_size & (fft_size - 1)) != 0 {
-        return Err(PvocError::InvalidParams(
-            "FFT size must be power of 2 between 2 and 32768".into(),
-        ));
+/// Perform phase vocoder analysis with windows `hop` samples apart
+///
+/// Unlike the overlap of [`pvoc_anal`] the hop needn't divide the FFT size,
+/// allowing finer time resolution. It must lie between 1 and the FFT size.
+pub fn pvoc_anal_with_hop(
+    input_path: &Path,
+    output_path: &Path,
+    mode: u32,
+    channels: Option<u32>,
+    hop: u32,
+) -> Result<()> {
+    pvoc_anal_with_progress(input_path, output_path, mode, channels, hop, &mut ())
+}
+
+/// Perform phase vocoder analysis, reporting each window to `progress`
+///
+/// As [`pvoc_anal_with_hop`]; progress counts analysis windows over all
+/// channels. Stops with [`cdp_core::CoreError::Cancelled`] if the sink
+/// asks, leaving no output file.
+pub fn pvoc_anal_with_progress(
+    input_path: &Path,
+    output_path: &Path,
+    mode: u32,
+    channels: Option<u32>,
+    hop: u32,
+    progress: &mut dyn ProgressSink,
+) -> Result<()> {
+    let defaults = PvocAnalParams::default();
+    let params = PvocAnalParams {
+        mode,
+        fft_size: channels.unwrap_or(defaults.fft_size),
+        hop: Some(hop),
+        ..defaults
+    };
+    pvoc_anal_with_params(input_path, output_path, &params, progress)
+}
+
+/// Perform phase vocoder analysis with the given settings
+///
+/// Progress counts analysis windows over all channels. Stops with
+/// [`cdp_core::CoreError::Cancelled`] if the sink asks, leaving no output
+/// file; pass `&mut ()` to ignore progress.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "info",
+        skip_all,
+        fields(input = %input_path.display(), output = %output_path.display()),
+        err
+    )
+)]
+pub fn pvoc_anal_with_params(
+    input_path: &Path,
+    output_path: &Path,
+    params: &PvocAnalParams,
+    progress: &mut dyn ProgressSink,
+) -> Result<()> {
+    // Read input WAV file as float levels, whatever its sample type
+    let (audio, sample_type) = cdp_housekeep::read_audio_buffer(input_path)?;
+    let mut analyses = pvoc_anal_buf(&audio, params, progress)?;
+    for analysis in &mut analyses {
+        analysis.header.original_bits = sample_type.bits_per_sample();
     }
 
-    // Read input WAV file
-    let (format, samples) = cdp_housekeep::read_wav_basic(input_path)?;
+    // Every channel is analysed before any is written, so cancelling
+    // leaves no partial set of channel files
+    if let [analysis] = analyses.as_slice() {
+        analysis.save(output_path)?;
+        return Ok(());
+    }
+    for (channel, analysis) in analyses.iter().enumerate() {
+        analysis.save(&channel_ana_path(output_path, channel + 1))?;
+    }
+
+    Ok(())
+}
+
+/// Analyse an [`AudioBuffer`], returning one analysis per channel
+///
+/// In-memory counterpart of [`pvoc_anal_with_params`]; progress counts
+/// analysis windows over all channels.
+pub fn pvoc_anal_buf(
+    audio: &AudioBuffer,
+    params: &PvocAnalParams,
+    progress: &mut dyn ProgressSink,
+) -> Result<Vec<AnaFile>> {
+    params.validate()?;
+    let fft_size = params.fft_size;
+    let hop = params.hop_size();
+    let window = create_window(params.window, fft_size as usize)?;
+    let header = AnaHeader::with_hop(audio.sample_rate, fft_size, hop);
+
+    let frames = audio.frame_count();
+    let windows = if frames < fft_size as usize {
+        0
+    } else {
+        (frames - fft_size as usize) / hop as usize + 1
+    };
+    let mut tally = Tally::new(progress, windows * audio.channels as usize);
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        channels = audio.channels,
+        frames = windows,
+        fft_size,
+        hop,
+        "analysing"
+    );
+
+    if audio.channels == 1 {
+        let analysis = analyse_channel(&audio.data, &header, params.mode, &window, &mut tally)?;
+        return Ok(vec![analysis]);
+    }
+
+    let mut analyses = Vec::with_capacity(audio.channels as usize);
+    for channel in 0..audio.channels as usize {
+        let samples = audio.channel(channel)?;
+        analyses.push(analyse_channel(
+            &samples,
+            &header,
+            params.mode,
+            &window,
+            &mut tally,
+        )?);
+    }
+    Ok(analyses)
+}
+
+/// Path of the analysis file for one channel (from 1) of a multichannel sound
+///
+/// `name.ana` becomes `name_c1.ana`, `name_c2.ana`, etc., following the
+/// naming housekeep uses when extracting channels.
+pub fn channel_ana_path(path: &Path, channel: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path
+        .extension()
+        .map_or("ana".into(), |ext| ext.to_string_lossy());
+    path.with_file_name(format!("{}_c{}.{}", stem, channel, extension))
+}
 
-    // Convert samples to float
-    let float_samples: Vec<f32> = samples.iter().map(|&s| s as f32 / 32768.0).collect();
+/// Analysis files making up a sound
+///
+/// A path that doesn't exist but has `_c1`, `_c2`, ... siblings refers to the
+/// per-channel analyses of a multichannel sound.
+fn channel_ana_paths(path: &Path) -> Vec<PathBuf> {
+    if fs::is_file(path) {
+        return vec![path.to_path_buf()];
+    }
+
+    let channels: Vec<PathBuf> = (1..)
+        .map(|channel| channel_ana_path(path, channel))
+        .take_while(|channel_path| fs::is_file(channel_path))
+        .collect();
+    if channels.is_empty() {
+        vec![path.to_path_buf()]
+    } else {
+        channels
+    }
+}
+
+/// Count of work done across channels, passed on to a progress sink
+struct Tally<'a> {
+    sink: &'a mut dyn ProgressSink,
+    done: usize,
+    total: usize,
+}
+
+impl<'a> Tally<'a> {
+    fn new(sink: &'a mut dyn ProgressSink, total: usize) -> Self {
+        Self {
+            sink,
+            done: 0,
+            total,
+        }
+    }
 
-    // Calculate hop size
-    let hop_size = fft_size / overlap_factor;
+    /// Record one more unit of work
+    fn step(&mut self) -> Result<()> {
+        self.done += 1;
+        Ok(progress::report(self.sink, self.done, self.total)?)
+    }
+}
 
-    // Create window function (Hanning)
-    let window = create_hanning_window(fft_size as usize);
+/// Analyse one channel of float samples
+fn analyse_channel(
+    float_samples: &[f32],
+    header: &AnaHeader,
+    mode: u32,
+    window: &[f32],
+    tally: &mut Tally,
+) -> Result<AnaFile> {
+    let fft_size = header.window_len;
+    let hop_size = header.hop_size();
 
     // Prepare FFT
     let mut planner = FftPlanner::<f32>::new();
     let fft = planner.plan_fft_forward(fft_size as usize);
 
     // Process frames
-    let mut spectral_frames = Vec::new();
+    let mut tracker = FrequencyTracker::new(header, amplitude_scale(window));
+    let mut analysis = AnaFile::empty(header.clone())?;
     let mut position = 0;
 
     while position + fft_size as usize <= float_samples.len() {
@@ -91,53 +277,109 @@ pub fn pvoc_anal(
             .map(|(&sample, &w)| Complex32::new(sample * w, 0.0))
             .collect();
 
+        // Centre the window on time zero so a steady partial has the same
+        // phase in every channel of its lobe
+        frame.rotate_left(fft_size as usize / 2);
+
         // Perform FFT
         fft.process(&mut frame);
 
         // Store spectral frame based on mode
         let spectral_data = match mode {
-            1 => convert_to_polar(&frame), // Standard analysis (magnitude + phase)
+            1 => tracker.analyse(&frame), // Standard analysis (amplitude + frequency)
             2 => extract_envelope(&frame), // Envelope only
             3 => extract_magnitude(&frame), // Magnitude only
             _ => return Err(PvocError::InvalidParams("Invalid mode".into())),
         };
 
-        spectral_frames.push(spectral_data);
+        analysis.push_frame(&spectral_data)?;
         position += hop_size as usize;
+        tally.step()?;
     }
 
-    // Write output as IEEE float WAV with CDP metadata
-    write_ana_file(
-        output_path,
-        &spectral_frames,
-        format.sample_rate,
-        fft_size,
-        overlap_factor,
-        float_samples.len() as u32,
-    )?;
+    Ok(analysis)
+}
 
-    Ok(())
+/// Coefficients of an analysis or synthesis window
+fn create_window(function: WindowFunction, size: usize) -> Result<Vec<f32>> {
+    Ok(Window::new(function, size)?.coefficients().to_vec())
 }
 
-/// Create Hanning window
-fn create_hanning_window(size: usize) -> Vec<f32> {
-    (0..size)
-        .map(|i| 0.5 * (1.0 - (2.0 * PI * i as f32 / (size - 1) as f32).cos()))
-        .collect()
+/// Factor turning FFT magnitudes into sinusoid amplitudes
+///
+/// A sinusoid of amplitude A windowed by `window` peaks at A * sum(window) / 2.
+fn amplitude_scale(window: &[f32]) -> f32 {
+    2.0 / window.iter().sum::<f32>()
 }
 
-/// Convert complex FFT output to polar form (magnitude, phase)
-fn convert_to_polar(frame: &[Complex32]) -> Vec<f32> {
-    let mut result = Vec::with_capacity((frame.len() / 2 + 1) * 2);
+/// Wrap a phase into [-PI, PI)
+fn wrap_phase(phase: f32) -> f32 {
+    (phase + PI).rem_euclid(2.0 * PI) - PI
+}
 
-    // CDP format: for each bin from 0 to N/2, store real and imaginary parts
-    // This maintains phase information in rectangular form
-    for complex in frame.iter().take(frame.len() / 2 + 1) {
-        result.push(complex.re);
-        result.push(complex.im);
+/// Converts between FFT frames and amplitude/frequency pairs
+///
+/// Holds the per-bin phase carried from one window to the next: the previous
+/// analysis phase when analysing, the accumulated output phase when
+/// resynthesising.
+struct FrequencyTracker {
+    phases: Vec<f32>,
+    amp_scale: f32,
+    bin_width: f32,
+    /// Phase advance per hop of a partial at 1 Hz
+    phase_per_hz: f32,
+}
+
+impl FrequencyTracker {
+    fn new(header: &AnaHeader, amp_scale: f32) -> Self {
+        Self {
+            phases: vec![0.0; header.num_bins()],
+            amp_scale,
+            bin_width: header.bin_width(),
+            phase_per_hz: 2.0 * PI * header.hop_size() as f32 / header.sample_rate as f32,
+        }
     }
 
-    result
+    /// Convert an FFT frame to amplitude/frequency pairs for bins 0 to N/2
+    fn analyse(&mut self, frame: &[Complex32]) -> Vec<f32> {
+        let mut result = Vec::with_capacity(self.phases.len() * 2);
+
+        for (bin, (complex, last_phase)) in frame.iter().zip(&mut self.phases).enumerate() {
+            let phase = complex.arg();
+            let centre = bin as f32 * self.bin_width;
+
+            // Deviation of the phase advance from that of the bin centre
+            let expected = centre * self.phase_per_hz;
+            let deviation = wrap_phase(phase - *last_phase - expected);
+            *last_phase = phase;
+
+            result.push(complex.abs() * self.amp_scale);
+            result.push(centre + deviation / self.phase_per_hz);
+        }
+
+        result
+    }
+
+    /// Convert amplitude/frequency pairs back to a full FFT frame
+    fn synthesise(&mut self, values: &[f32], fft_size: usize) -> Vec<Complex32> {
+        let mut result = vec![Complex32::new(0.0, 0.0); fft_size];
+
+        for ((val, pair), phase) in result
+            .iter_mut()
+            .zip(values.chunks_exact(2))
+            .zip(&mut self.phases)
+        {
+            *phase = wrap_phase(*phase + pair[1] * self.phase_per_hz);
+            *val = Complex32::from_polar(pair[0] / self.amp_scale, *phase);
+        }
+
+        // Mirror for negative frequencies (except DC and Nyquist)
+        for i in 1..fft_size / 2 {
+            result[fft_size - i] = result[i].conj();
+        }
+
+        result
+    }
 }
 
 /// Extract spectral envelope
@@ -168,277 +410,210 @@ fn extract_magnitude(frame: &[Complex32]) -> Vec<f32> {
     result
 }
 
-/// Write .ana file (IEEE float WAV with CDP metadata)
-fn write_ana_file(
-    path: &Path,
-    frames: &[Vec<f32>],
-    sample_rate: u32,
-    fft_size: u32,
-    overlap_factor: u32,
-    _orig_samples: u32,
+/// Perform phase vocoder synthesis
+///
+/// The output has the sample type recorded for the analysed sound. If
+/// `input_path` doesn't exist but per-channel analyses written by
+/// [`pvoc_anal`] do, they are resynthesised into one multichannel sound.
+pub fn pvoc_synth(input_path: &Path, output_path: &Path) -> Result<()> {
+    pvoc_synth_with_options(input_path, output_path, QuantizeOptions::default())
+}
+
+/// Perform phase vocoder synthesis, dithering integer output
+///
+/// With default options the output is truncated exactly as [`pvoc_synth`].
+pub fn pvoc_synth_with_options(
+    input_path: &Path,
+    output_path: &Path,
+    quantize: QuantizeOptions,
 ) -> Result<()> {
-    let mut writer = BufWriter::new(File::create(path)?);
-
-    // Calculate sizes
-    // CDP stores spectral data as (FFT_size/2 + 1) complex pairs = (FFT_size/2 + 1) * 2 channels
-    let channels = ((fft_size / 2 + 1) * 2) as u16; // CDP convention
-    let frame_count = frames.len() as u32;
-    let data_size = frame_count * channels as u32 * 4; // 4 bytes per float
-
-    // Create LIST chunk metadata
-    let _timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as u32;
-
-    let metadata = format!(
-        "original sampsize: 16\n\
-         original sample rate: {}\n\
-         arate: {}\n\
-         analwinlen: {}\n\
-         decfactor: {}\n\
-         origrate: {}\n\
-         DATE: CDP Phase Vocoder Analysis\n",
-        sample_rate,
-        sample_rate as f32 / (fft_size / overlap_factor) as f32,
-        fft_size,
-        overlap_factor,
-        sample_rate
-    );
+    pvoc_synth_with_progress(input_path, output_path, quantize, &mut ())
+}
 
-    let list_data = metadata.as_bytes();
-    let list_size = 4 + 4 + 4 + list_data.len(); // "adtl" + "note" + size + data
-    let list_size_padded = if list_size % 2 == 0 {
-        list_size
-    } else {
-        list_size + 1
+/// Perform phase vocoder synthesis, reporting each window to `progress`
+///
+/// As [`pvoc_synth_with_options`]; progress counts spectral windows over
+/// all channels. Stops with [`cdp_core::CoreError::Cancelled`] if the
+/// sink asks, leaving no output file.
+pub fn pvoc_synth_with_progress(
+    input_path: &Path,
+    output_path: &Path,
+    quantize: QuantizeOptions,
+    progress: &mut dyn ProgressSink,
+) -> Result<()> {
+    let params = PvocSynthParams {
+        quantize,
+        ..PvocSynthParams::default()
     };
+    pvoc_synth_with_params(input_path, output_path, &params, progress)
+}
+
+/// Perform phase vocoder synthesis with the given settings
+///
+/// Progress counts spectral windows over all channels. Stops with
+/// [`cdp_core::CoreError::Cancelled`] if the sink asks, leaving no output
+/// file; pass `&mut ()` to ignore progress.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "info",
+        skip_all,
+        fields(input = %input_path.display(), output = %output_path.display()),
+        err
+    )
+)]
+pub fn pvoc_synth_with_params(
+    input_path: &Path,
+    output_path: &Path,
+    params: &PvocSynthParams,
+    progress: &mut dyn ProgressSink,
+) -> Result<()> {
+    let mut original_bits = 16;
+    let mut analyses = Vec::new();
+    for path in channel_ana_paths(input_path) {
+        let analysis = AnaFile::load(&path)?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            path = %path.display(),
+            frames = analysis.num_frames(),
+            "file opened"
+        );
+        original_bits = analysis.header.original_bits;
+        analyses.push(analysis);
+    }
+    let mut audio = pvoc_synth_buf(&analyses, params, progress)?;
 
-    // Calculate RIFF size
-    let riff_size = 4 + // "WAVE"
-        8 + 16 + // fmt chunk
-        8 + list_size_padded as u32 + // LIST chunk
-        8 + data_size; // data chunk
-
-    // Write RIFF header
-    writer.write_all(b"RIFF")?;
-    writer.write_all(&riff_size.to_le_bytes())?;
-    writer.write_all(b"WAVE")?;
-
-    // Write fmt chunk (IEEE float format)
-    writer.write_all(b"fmt ")?;
-    writer.write_all(&16u32.to_le_bytes())?; // chunk size
-    writer.write_all(&3u16.to_le_bytes())?; // format type 3 = IEEE float
-    writer.write_all(&channels.to_le_bytes())?;
-    writer.write_all(&sample_rate.to_le_bytes())?;
-    let byte_rate = sample_rate * channels as u32 * 4; // 4 bytes per float
-    writer.write_all(&byte_rate.to_le_bytes())?;
-    let block_align = channels * 4;
-    writer.write_all(&block_align.to_le_bytes())?;
-    writer.write_all(&32u16.to_le_bytes())?; // bits per sample (32 for float)
-
-    // Write LIST chunk
-    writer.write_all(b"LIST")?;
-    writer.write_all(&(list_size_padded as u32).to_le_bytes())?;
-    writer.write_all(b"adtl")?;
-    writer.write_all(b"note")?;
-    writer.write_all(&(list_data.len() as u32).to_le_bytes())?;
-    writer.write_all(list_data)?;
-    if list_data.len() % 2 != 0 {
-        writer.write_all(&[0u8])?; // padding
-    }
-
-    // Write data chunk
-    writer.write_all(b"data")?;
-    writer.write_all(&data_size.to_le_bytes())?;
-
-    // Write spectral frames
-    for frame in frames {
-        for &value in frame {
-            writer.write_all(&value.to_le_bytes())?;
+    // Restore the sample type of the analysed sound
+    let sample_type = match original_bits {
+        24 => SampleType::Int24,
+        32 => SampleType::Float32,
+        _ => SampleType::Int16,
+    };
+
+    // For 16-bit output scale so full scale maps to 32767
+    if sample_type == SampleType::Int16 {
+        for sample in &mut audio.data {
+            *sample *= 32767.0 / 32768.0;
         }
     }
+    let mut quantizer = Quantizer::new(sample_type, params.quantize, audio.channels as usize);
+    quantizer.process(&mut audio.data);
+
+    // Write output WAV
+    cdp_housekeep::write_audio_buffer(output_path, &audio, sample_type)?;
 
-    writer.flush()?;
     Ok(())
 }
 
-/// Perform phase vocoder synthesis
-pub fn pvoc_synth(input_path: &Path, output_path: &Path) -> Result<()> {
-    // Read .ana file
-    let (header, spectral_frames) = read_ana_file(input_path)?;
+/// Resynthesise one channel of audio from each analysis
+///
+/// In-memory counterpart of [`pvoc_synth_with_params`]. The result holds
+/// float levels; the quantize settings only apply when writing a file.
+pub fn pvoc_synth_buf(
+    analyses: &[AnaFile],
+    params: &PvocSynthParams,
+    progress: &mut dyn ProgressSink,
+) -> Result<AudioBuffer> {
+    let Some(first) = analyses.first() else {
+        return Err(PvocError::InvalidParams("No analyses to synthesise".into()));
+    };
+    let sample_rate = first.header.sample_rate;
+    if analyses
+        .iter()
+        .any(|analysis| analysis.header.sample_rate != sample_rate)
+    {
+        return Err(PvocError::InvalidParams(
+            "Channel analyses have different sample rates".into(),
+        ));
+    }
+
+    let total = analyses.iter().map(AnaFile::num_frames).sum();
+    let mut tally = Tally::new(progress, total);
+    let mut channels = Vec::with_capacity(analyses.len());
+    for analysis in analyses {
+        channels.push(synthesise_channel(analysis, params.window, &mut tally)?);
+    }
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        channels = analyses.len(),
+        frames = total,
+        "frames processed"
+    );
+
+    AudioBuffer::from_channels(sample_rate, &channels)
+        .map_err(|_| PvocError::InvalidParams("Channel analyses have different lengths".into()))
+}
+
+/// Resynthesise one channel of audio from an analysis
+fn synthesise_channel(
+    analysis: &AnaFile,
+    window: WindowFunction,
+    tally: &mut Tally,
+) -> Result<Vec<f32>> {
+    let header = &analysis.header;
 
     // Calculate parameters from header
     // CDP uses channels = (fft_size/2 + 1) * 2
-    let fft_size = (header.channels / 2 - 1) * 2;
-    let hop_size = fft_size / header.dec_factor;
+    let fft_size = header.fft_size() as u32;
+    let hop_size = header.hop_size();
+    if analysis.num_frames() == 0 {
+        return Err(PvocError::InvalidParams(
+            "Analysis file has no spectral frames".into(),
+        ));
+    }
 
-    // Create window function (Hanning)
-    let window = create_hanning_window(fft_size as usize);
+    let window = create_window(window, fft_size as usize)?;
+    let mut tracker = FrequencyTracker::new(header, amplitude_scale(&window));
 
     // Prepare IFFT
     let mut planner = FftPlanner::<f32>::new();
     let ifft = planner.plan_fft_inverse(fft_size as usize);
 
     // Synthesize audio
-    let output_length = ((spectral_frames.len() - 1) * hop_size as usize) + fft_size as usize;
+    let output_length = ((analysis.num_frames() - 1) * hop_size as usize) + fft_size as usize;
     let mut output = vec![0.0f32; output_length];
+    let mut window_gain = vec![0.0f32; output_length];
     let mut position = 0;
 
-    for frame_data in &spectral_frames {
-        // Convert polar to complex
-        let mut frame = polar_to_complex(frame_data, fft_size as usize);
+    for frame_data in analysis.frames() {
+        // Accumulate phases from the frequency track
+        let mut frame = tracker.synthesise(frame_data.values(), fft_size as usize);
 
-        // Perform IFFT
+        // Perform IFFT, undoing the analysis rotation
         ifft.process(&mut frame);
+        frame.rotate_right(fft_size as usize / 2);
 
-        // Apply window and overlap-add
+        // Apply synthesis window and overlap-add, tracking the combined
+        // analysis and synthesis window gain at each output sample
         for (i, sample) in frame.iter().enumerate() {
-            if position + i < output.len() {
-                output[position + i] += sample.re * window[i] / fft_size as f32;
-            }
+            output[position + i] += sample.re * window[i] / fft_size as f32;
+            window_gain[position + i] += window[i] * window[i];
         }
 
         position += hop_size as usize;
+        tally.step()?;
     }
 
-    // Normalize to prevent clipping
-    let max_val = output.iter().map(|&x| x.abs()).fold(0.0f32, f32::max);
-    if max_val > 1.0 {
-        for sample in &mut output {
-            *sample /= max_val * 1.1; // Scale with headroom
-        }
+    // Compensate for the window gain so levels match the analysed sound.
+    // The floor stops the faded ends of the file being boosted into noise.
+    let steady_gain = window.iter().map(|w| w * w).sum::<f32>() / hop_size as f32;
+    let gain_floor = steady_gain * WINDOW_GAIN_FLOOR;
+    for (sample, &gain) in output.iter_mut().zip(&window_gain) {
+        *sample /= gain.max(gain_floor);
     }
 
-    // Convert to i16 samples
-    let i16_samples: Vec<i16> = output
-        .iter()
-        .map(|&s| (s * 32767.0).clamp(-32768.0, 32767.0) as i16)
-        .collect();
-
-    // Write output WAV
-    let format = cdp_housekeep::wav_cdp::WavFormat {
-        channels: 1,
-        sample_rate: header.sample_rate,
-        bits_per_sample: 16,
-        data_size: (i16_samples.len() * 2) as u32,
-    };
-
-    cdp_housekeep::write_wav_cdp(output_path, &format, &i16_samples)?;
-
-    Ok(())
+    Ok(output)
 }
 
-/// Convert polar representation back to complex
-fn polar_to_complex(polar_data: &[f32], fft_size: usize) -> Vec<Complex32> {
-    let mut result = vec![Complex32::new(0.0, 0.0); fft_size];
-
-    // CDP format: real and imaginary parts for bins 0 to N/2
-    let mut idx = 0;
-    for val in result.iter_mut().take(fft_size / 2 + 1) {
-        if idx + 1 < polar_data.len() {
-            *val = Complex32::new(polar_data[idx], polar_data[idx + 1]);
-            idx += 2;
-        }
-    }
-
-    // Mirror for negative frequencies (except DC and Nyquist)
-    for i in 1..fft_size / 2 {
-        result[fft_size - i] = result[i].conj();
-    }
-
-    result
-}
-
-/// Read .ana file (IEEE float WAV with CDP metadata)
-fn read_ana_file(path: &Path) -> Result<(AnaHeader, Vec<Vec<f32>>)> {
-    let mut reader = BufReader::new(File::open(path)?);
-
-    // Read RIFF header
-    let mut header = [0u8; 12];
-    reader.read_exact(&mut header)?;
-
-    if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
-        return Err(PvocError::InvalidFormat);
-    }
-
-    let mut ana_header = AnaHeader {
-        sample_rate: 0,
-        channels: 0,
-        window_len: 0,
-        dec_factor: 3, // default
-        orig_size: 0,
-    };
-
-    let mut spectral_data = Vec::new();
-
-    // Read chunks
-    loop {
-        let mut chunk_header = [0u8; 8];
-        if reader.read_exact(&mut chunk_header).is_err() {
-            break;
-        }
-
-        let chunk_id = &chunk_header[0..4];
-        let chunk_size = u32::from_le_bytes([
-            chunk_header[4],
-            chunk_header[5],
-            chunk_header[6],
-            chunk_header[7],
-        ]);
-
-        match chunk_id {
-            b"fmt " => {
-                let mut fmt_data = vec![0u8; chunk_size as usize];
-                reader.read_exact(&mut fmt_data)?;
-
-                let format_type = u16::from_le_bytes([fmt_data[0], fmt_data[1]]);
-                if format_type != 3 {
-                    return Err(PvocError::InvalidFormat);
-                }
-
-                ana_header.channels = u16::from_le_bytes([fmt_data[2], fmt_data[3]]) as u32;
-                ana_header.sample_rate =
-                    u32::from_le_bytes([fmt_data[4], fmt_data[5], fmt_data[6], fmt_data[7]]);
-                ana_header.window_len = (ana_header.channels / 2 - 1) * 2;
-            }
-            b"LIST" => {
-                // Parse metadata for overlap factor
-                let mut list_data = vec![0u8; chunk_size as usize];
-                reader.read_exact(&mut list_data)?;
-
-                if let Ok(metadata) = std::str::from_utf8(&list_data[8..]) {
-                    for line in metadata.lines() {
-                        if line.starts_with("decfactor:") {
-                            if let Some(val) = line.split(':').nth(1) {
-                                ana_header.dec_factor = val.trim().parse().unwrap_or(3);
-                            }
-                        }
-                    }
-                }
-            }
-            b"data" => {
-                let frame_size = ana_header.channels as usize;
-                let num_frames = (chunk_size as usize) / (frame_size * 4);
-
-                for _ in 0..num_frames {
-                    let mut frame = Vec::with_capacity(frame_size);
-                    for _ in 0..frame_size {
-                        let mut float_bytes = [0u8; 4];
-                        reader.read_exact(&mut float_bytes)?;
-                        frame.push(f32::from_le_bytes(float_bytes));
-                    }
-                    spectral_data.push(frame);
-                }
-            }
-            _ => {
-                // Skip unknown chunks
-                reader.seek(SeekFrom::Current(chunk_size as i64))?;
-            }
-        }
-    }
-
-    Ok((ana_header, spectral_data))
+/// Whether [`pvoc_extract_bands`] keeps or removes the given bands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum BandMode {
+    /// Keep the bands, removing everything else (DC and Nyquist are kept)
+    Pass,
+    /// Remove the bands, keeping everything else
+    Reject,
 }
 
 /// Extract a frequency band from analysis file
@@ -448,64 +623,292 @@ pub fn pvoc_extract(
     lo_freq: f32,
     hi_freq: f32,
 ) -> Result<()> {
+    pvoc_extract_bands(
+        input_path,
+        output_path,
+        &[(lo_freq, hi_freq)],
+        BandMode::Pass,
+        None,
+    )
+}
+
+/// Keep or reject several frequency bands of an analysis file in one pass
+///
+/// `bands` are (lo_freq, hi_freq) pairs in Hz. Removed bins are zeroed, or
+/// with `attenuation_db` have their amplitude reduced by that many dB.
+pub fn pvoc_extract_bands(
+    input_path: &Path,
+    output_path: &Path,
+    bands: &[(f32, f32)],
+    mode: BandMode,
+    attenuation_db: Option<f32>,
+) -> Result<()> {
+    if bands.is_empty() {
+        return Err(PvocError::InvalidParams("No frequency bands given".into()));
+    }
+    if let Some(&(lo, hi)) = bands.iter().find(|&&(lo, hi)| !(0.0..=hi).contains(&lo)) {
+        return Err(PvocError::InvalidParams(format!(
+            "Invalid frequency band {}-{}Hz",
+            lo, hi
+        )));
+    }
+    if attenuation_db.is_some_and(|db| db.is_nan() || db < 0.0) {
+        return Err(PvocError::InvalidParams(
+            "Attenuation must be a positive number of dB".into(),
+        ));
+    }
+
     // Read input .ana file
-    let (header, spectral_frames) = read_ana_file(input_path)?;
+    let mut analysis = AnaFile::load(input_path)?;
 
     // Calculate bin frequencies
-    // CDP uses channels = (fft_size/2 + 1) * 2
-    let fft_size = (header.channels / 2 - 1) * 2;
-    let bin_width = header.sample_rate as f32 / fft_size as f32;
-
-    // Calculate bin range for extraction
-    let lo_bin = (lo_freq / bin_width).floor() as usize;
-    let hi_bin = ((hi_freq / bin_width).ceil() as usize).min(fft_size as usize / 2);
-
-    // Extract frequency band from each frame
-    let mut filtered_frames = Vec::new();
-
-    for frame in &spectral_frames {
-        let mut filtered_frame = vec![0.0f32; frame.len()];
-
-        // Process each bin (real/imag pairs)
-        let mut idx = 0;
-        for bin in 0..=fft_size as usize / 2 {
-            if bin == 0 || bin == fft_size as usize / 2 {
-                // Always keep DC and Nyquist
-                filtered_frame[idx] = frame[idx];
-                filtered_frame[idx + 1] = frame[idx + 1];
-            } else if bin >= lo_bin && bin <= hi_bin {
-                // Keep bins in frequency range
-                filtered_frame[idx] = frame[idx]; // real
-                filtered_frame[idx + 1] = frame[idx + 1]; // imag
-            } else {
-                // Zero out bins outside range
-                filtered_frame[idx] = 0.0;
-                filtered_frame[idx + 1] = 0.0;
+    let fft_size = analysis.header.fft_size();
+    let bin_width = analysis.header.bin_width();
+
+    // Calculate bin ranges for extraction
+    let bin_ranges: Vec<(usize, usize)> = bands
+        .iter()
+        .map(|&(lo_freq, hi_freq)| {
+            let lo_bin = (lo_freq / bin_width).floor() as usize;
+            let hi_bin = ((hi_freq / bin_width).ceil() as usize).min(fft_size / 2);
+            (lo_bin, hi_bin)
+        })
+        .collect();
+    let gain = attenuation_db.map(|db| 10.0f32.powf(-db / 20.0));
+
+    // Extract frequency bands from each frame
+    for frame in analysis.frames_mut() {
+        // Process each bin (amp/freq pairs)
+        for (bin, pair) in frame.chunks_exact_mut(2).enumerate() {
+            let in_band = bin_ranges
+                .iter()
+                .any(|&(lo_bin, hi_bin)| (lo_bin..=hi_bin).contains(&bin));
+            let remove = match mode {
+                BandMode::Pass => !in_band && bin != 0 && bin != fft_size / 2,
+                BandMode::Reject => in_band,
+            };
+            if remove {
+                match gain {
+                    Some(gain) => pair[0] *= gain,
+                    None => {
+                        pair[0] = 0.0;
+                        pair[1] = 0.0;
+                    }
+                }
             }
-            idx += 2;
         }
-
-        filtered_frames.push(filtered_frame);
     }
 
     // Write output .ana file
-    write_ana_file(
-        output_path,
-        &filtered_frames,
-        header.sample_rate,
-        fft_size,
-        header.dec_factor,
-        header.orig_size,
-    )?;
+    analysis.save(output_path)?;
 
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn test_placeholder() {
         // Placeholder test until we implement functionality
         assert_eq!(1 + 1, 2);
     }
+
+    #[test]
+    fn test_frequency_tracker_finds_partial() {
+        // A 0.5 amplitude sine between the centres of bins 10 and 11
+        let header = AnaHeader::new(44100, 1024, 4).unwrap();
+        let freq = 10.3 * header.bin_width();
+        let window = create_window(WindowFunction::Hann, 1024).unwrap();
+        let mut tracker = FrequencyTracker::new(&header, amplitude_scale(&window));
+        let mut planner = FftPlanner::<f32>::new();
+        let fft = planner.plan_fft_forward(1024);
+
+        let mut values = Vec::new();
+        for position in [0, header.hop_size() as usize] {
+            let mut frame: Vec<Complex32> = (0..1024)
+                .map(|i| {
+                    let t = (position + i) as f32 / 44100.0;
+                    Complex32::new(0.5 * (2.0 * PI * freq * t).sin() * window[i], 0.0)
+                })
+                .collect();
+            fft.process(&mut frame);
+            values = tracker.analyse(&frame);
+        }
+
+        // Second frame: amplitude and frequency come from the phase advance
+        assert!((values[20] - 0.5).abs() < 0.1);
+        assert!((values[21] - freq).abs() < 1.0);
+        assert!((values[23] - freq).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_anal_progress_and_cancel() {
+        struct Windows(usize, usize);
+        impl ProgressSink for Windows {
+            fn progress(&mut self, done: usize, total: usize) {
+                *self = Windows(done, total);
+            }
+        }
+
+        let dir = std::env::temp_dir().join(format!("cdp-pvoc-progress-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("in.wav");
+        let analysis = dir.join("in.ana");
+        let output = dir.join("out.wav");
+        let audio = AudioBuffer::new(44100, 2, vec![0.25; 2 * 4096]).unwrap();
+        cdp_housekeep::write_audio_buffer(&input, &audio, SampleType::Float32).unwrap();
+
+        // (4096 - 1024) / 256 + 1 windows in each of two channels
+        let mut windows = Windows(0, 0);
+        pvoc_anal_with_progress(&input, &analysis, 1, None, 256, &mut windows).unwrap();
+        let analysed = AnaFile::load(&channel_ana_path(&analysis, 1)).unwrap();
+        assert_eq!(analysed.num_frames(), 13);
+        assert_eq!((windows.0, windows.1), (26, 26));
+
+        let mut windows = Windows(0, 0);
+        pvoc_synth_with_progress(&analysis, &output, QuantizeOptions::default(), &mut windows)
+            .unwrap();
+        assert_eq!((windows.0, windows.1), (26, 26));
+
+        // A cancelled analysis writes no channel files
+        let mut token = cdp_core::CancelToken::new();
+        token.cancel();
+        let cancelled = dir.join("cancelled.ana");
+        let result = pvoc_anal_with_progress(&input, &cancelled, 1, None, 256, &mut token);
+        let written = channel_ana_path(&cancelled, 1).exists();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(
+            result,
+            Err(PvocError::Core(cdp_core::CoreError::Cancelled))
+        ));
+        assert!(!written);
+    }
+
+    #[test]
+    fn test_anal_synth_round_trip_preserves_level() {
+        let dir = std::env::temp_dir().join(format!("cdp-pvoc-roundtrip-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("in.wav");
+        let analysis = dir.join("in.ana");
+        let output = dir.join("out.wav");
+
+        let data: Vec<f32> = (0..22050)
+            .map(|i| 0.5 * (2.0 * PI * 440.0 * i as f32 / 44100.0).sin())
+            .collect();
+        let audio = AudioBuffer::new(44100, 1, data).unwrap();
+        cdp_housekeep::write_audio_buffer(&input, &audio, SampleType::Float32).unwrap();
+
+        pvoc_anal(&input, &analysis, 1, None, None).unwrap();
+        pvoc_synth(&analysis, &output).unwrap();
+        let (result, sample_type) = cdp_housekeep::read_audio_buffer(&output).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(sample_type, SampleType::Float32);
+
+        // Away from the faded ends the resynthesis matches the input
+        let steady = 1024..result.data.len() - 1024;
+        let max_error = steady
+            .map(|i| (result.data[i] - audio.data[i]).abs())
+            .fold(0.0f32, f32::max);
+        assert!(max_error < 1e-3, "max error {}", max_error);
+    }
+
+    #[test]
+    fn test_params_window_round_trip() {
+        let dir = std::env::temp_dir().join(format!("cdp-pvoc-params-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("in.wav");
+        let analysis = dir.join("in.ana");
+        let output = dir.join("out.wav");
+
+        let data: Vec<f32> = (0..16384)
+            .map(|i| 0.5 * (2.0 * PI * 440.0 * i as f32 / 44100.0).sin())
+            .collect();
+        let audio = AudioBuffer::new(44100, 1, data).unwrap();
+        cdp_housekeep::write_audio_buffer(&input, &audio, SampleType::Float32).unwrap();
+
+        let anal = PvocAnalParams::builder()
+            .fft_size(2048)
+            .overlap(4)
+            .window(WindowFunction::Blackman)
+            .build()
+            .unwrap();
+        let synth = PvocSynthParams::builder()
+            .window(WindowFunction::Blackman)
+            .build();
+        pvoc_anal_with_params(&input, &analysis, &anal, &mut ()).unwrap();
+        pvoc_synth_with_params(&analysis, &output, &synth, &mut ()).unwrap();
+        let header = AnaFile::load(&analysis).unwrap().header;
+        let (result, _) = cdp_housekeep::read_audio_buffer(&output).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!((header.window_len, header.hop_size()), (2048, 512));
+        let max_error = (2048..result.data.len() - 2048)
+            .map(|i| (result.data[i] - audio.data[i]).abs())
+            .fold(0.0f32, f32::max);
+        assert!(max_error < 1e-3, "max error {}", max_error);
+    }
+
+    #[test]
+    fn test_stereo_anal_synth_uses_channel_files() {
+        let dir = std::env::temp_dir().join(format!("cdp-pvoc-stereo-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("in.wav");
+        let analysis = dir.join("in.ana");
+        let output = dir.join("out.wav");
+
+        let left: Vec<f32> = (0..8192)
+            .map(|i| 0.5 * (2.0 * PI * 440.0 * i as f32 / 44100.0).sin())
+            .collect();
+        let right = vec![0.0f32; 8192];
+        let audio = AudioBuffer::from_channels(44100, &[left, right]).unwrap();
+        cdp_housekeep::write_audio_buffer(&input, &audio, SampleType::Float32).unwrap();
+
+        pvoc_anal(&input, &analysis, 1, None, None).unwrap();
+        assert!(!analysis.exists());
+        assert_eq!(channel_ana_path(&analysis, 2), dir.join("in_c2.ana"));
+        assert!(channel_ana_path(&analysis, 1).exists());
+        assert!(channel_ana_path(&analysis, 2).exists());
+
+        pvoc_synth(&analysis, &output).unwrap();
+        let (result, _) = cdp_housekeep::read_audio_buffer(&output).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(result.channels, 2);
+        assert!(result.channel(0).unwrap().iter().any(|s| s.abs() > 0.4));
+        assert!(result.channel(1).unwrap().iter().all(|s| s.abs() < 1e-3));
+    }
+
+    #[test]
+    fn test_extract_bands_reject_and_attenuate() {
+        let dir = std::env::temp_dir().join(format!("cdp-pvoc-extract-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("in.ana");
+        let output = dir.join("out.ana");
+
+        // Five bins 100Hz apart, all at amplitude 1
+        let header = AnaHeader::new(800, 8, 2).unwrap();
+        let frame: Vec<f32> = (0..5).flat_map(|bin| [1.0, bin as f32 * 100.0]).collect();
+        AnaFile::new(header, frame).unwrap().save(&input).unwrap();
+
+        let bands = [(100.0, 100.0), (300.0, 300.0)];
+        pvoc_extract_bands(&input, &output, &bands, BandMode::Reject, Some(20.0)).unwrap();
+        let rejected = AnaFile::load(&output).unwrap();
+        let amps: Vec<f32> = (0..5)
+            .map(|bin| rejected.frame(0).unwrap().amplitude(bin))
+            .collect();
+        assert_eq!(amps, vec![1.0, 0.1, 1.0, 0.1, 1.0]);
+        assert_eq!(rejected.frame(0).unwrap().frequency(1), 100.0);
+
+        pvoc_extract_bands(&input, &output, &bands, BandMode::Pass, None).unwrap();
+        let kept = AnaFile::load(&output).unwrap();
+        assert_eq!(kept.frame(0).unwrap().pair(2), (0.0, 0.0));
+        assert_eq!(kept.frame(0).unwrap().pair(3), (1.0, 300.0));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(
+            pvoc_extract_bands(&input, &output, &[(300.0, 100.0)], BandMode::Pass, None).is_err()
+        );
+    }
 }
@@ -0,0 +1,209 @@
+//! Analysis and synthesis settings
+//!
+//! [`PvocAnalParams`] and [`PvocSynthParams`] gather the settings of
+//! [`crate::pvoc_anal_with_params`] and [`crate::pvoc_synth_with_params`].
+//! Their defaults are CDP's, and their builders check the settings as they
+//! are built:
+//!
+//! ```
+//! use cdp_core::WindowFunction;
+//! use cdp_pvoc::PvocAnalParams;
+//!
+//! let params = PvocAnalParams::builder()
+//!     .fft_size(2048)
+//!     .overlap(3)
+//!     .window(WindowFunction::Kaiser(8.0))
+//!     .build()
+//!     .unwrap();
+//! assert_eq!(params.hop_size(), 682);
+//! ```
+
+use crate::{PvocError, Result};
+use cdp_core::WindowFunction;
+use cdp_housekeep::quantize::QuantizeOptions;
+
+/// Phase vocoder analysis settings
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct PvocAnalParams {
+    /// 1 for standard analysis, 2 for the spectral envelope only, 3 for
+    /// magnitudes only
+    pub mode: u32,
+    /// Analysis points: a power of 2 from 2 to 32768
+    pub fft_size: u32,
+    /// Window overlap factor (1-4), spacing windows `fft_size / overlap`
+    /// samples apart
+    pub overlap: u32,
+    /// Samples between windows, replacing the overlap when set
+    pub hop: Option<u32>,
+    /// Analysis window
+    pub window: WindowFunction,
+}
+
+impl Default for PvocAnalParams {
+    /// CDP's defaults: standard analysis of 1024 points with overlap 3
+    fn default() -> Self {
+        Self {
+            mode: 1,
+            fft_size: 1024,
+            overlap: 3,
+            hop: None,
+            window: WindowFunction::Hann,
+        }
+    }
+}
+
+impl PvocAnalParams {
+    /// Start from the defaults
+    pub fn builder() -> PvocAnalParamsBuilder {
+        PvocAnalParamsBuilder(Self::default())
+    }
+
+    /// Samples between successive analysis windows
+    pub fn hop_size(&self) -> u32 {
+        match self.hop {
+            Some(hop) => hop,
+            None => (self.fft_size / self.overlap.max(1)).max(1),
+        }
+    }
+
+    /// Check every setting against its valid range
+    pub fn validate(&self) -> Result<()> {
+        if !(1..=3).contains(&self.mode) {
+            return Err(PvocError::InvalidParams("Invalid mode".into()));
+        }
+        let fft_size = self.fft_size;
+        if !(2..=32768).contains(&fft_size) || (fft_size & (fft_size - 1)) != 0 {
+            return Err(PvocError::InvalidParams(
+                "FFT size must be power of 2 between 2 and 32768".into(),
+            ));
+        }
+        cdp_core::validate(cdp_core::Param::Overlap, self.overlap as f64)
+            .map_err(|e| PvocError::InvalidParams(e.to_string()))?;
+        if !(1..=fft_size).contains(&self.hop_size()) {
+            return Err(PvocError::InvalidParams(format!(
+                "Hop size must be between 1 and {} samples",
+                fft_size
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Builder for [`PvocAnalParams`]
+#[derive(Debug, Clone, Copy)]
+pub struct PvocAnalParamsBuilder(PvocAnalParams);
+
+impl PvocAnalParamsBuilder {
+    /// Analysis mode (1-3)
+    pub fn mode(mut self, mode: u32) -> Self {
+        self.0.mode = mode;
+        self
+    }
+
+    /// Analysis points, a power of 2
+    pub fn fft_size(mut self, fft_size: u32) -> Self {
+        self.0.fft_size = fft_size;
+        self
+    }
+
+    /// Window overlap factor (1-4)
+    pub fn overlap(mut self, overlap: u32) -> Self {
+        self.0.overlap = overlap;
+        self
+    }
+
+    /// Samples between windows, instead of the overlap
+    ///
+    /// Unlike the overlap the hop needn't divide the FFT size, allowing
+    /// finer time resolution.
+    pub fn hop(mut self, hop: u32) -> Self {
+        self.0.hop = Some(hop);
+        self
+    }
+
+    /// Analysis window
+    pub fn window(mut self, window: WindowFunction) -> Self {
+        self.0.window = window;
+        self
+    }
+
+    /// Finish, checking the settings
+    pub fn build(self) -> Result<PvocAnalParams> {
+        self.0.validate()?;
+        Ok(self.0)
+    }
+}
+
+/// Phase vocoder synthesis settings
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct PvocSynthParams {
+    /// Synthesis window, which should match the one the analysis used
+    pub window: WindowFunction,
+    /// Dither and noise shaping for integer output
+    pub quantize: QuantizeOptions,
+}
+
+impl Default for PvocSynthParams {
+    /// CDP's defaults: a Hann window and plain truncation
+    fn default() -> Self {
+        Self {
+            window: WindowFunction::Hann,
+            quantize: QuantizeOptions::default(),
+        }
+    }
+}
+
+impl PvocSynthParams {
+    /// Start from the defaults
+    pub fn builder() -> PvocSynthParamsBuilder {
+        PvocSynthParamsBuilder(Self::default())
+    }
+}
+
+/// Builder for [`PvocSynthParams`]
+#[derive(Debug, Clone, Copy)]
+pub struct PvocSynthParamsBuilder(PvocSynthParams);
+
+impl PvocSynthParamsBuilder {
+    /// Synthesis window
+    pub fn window(mut self, window: WindowFunction) -> Self {
+        self.0.window = window;
+        self
+    }
+
+    /// Dither and noise shaping for integer output
+    pub fn quantize(mut self, quantize: QuantizeOptions) -> Self {
+        self.0.quantize = quantize;
+        self
+    }
+
+    /// Finish
+    pub fn build(self) -> PvocSynthParams {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anal_params_defaults_and_checks() {
+        let params = PvocAnalParams::default();
+        assert_eq!(PvocAnalParams::builder().build().unwrap(), params);
+        assert_eq!(params.hop_size(), 341);
+
+        let params = PvocAnalParams::builder().fft_size(256).hop(100).build();
+        assert_eq!(params.unwrap().hop_size(), 100);
+
+        assert!(PvocAnalParams::builder().fft_size(1000).build().is_err());
+        assert!(PvocAnalParams::builder().overlap(5).build().is_err());
+        assert!(PvocAnalParams::builder().hop(2048).build().is_err());
+        assert!(PvocAnalParams::builder().hop(0).build().is_err());
+        assert!(PvocAnalParams::builder().mode(4).build().is_err());
+    }
+}
@@ -1,5 +1,6 @@
 //! Oracle tests comparing our implementation against CDP
 
+use cdp_housekeep::wav_cdp::{self, SampleType};
 use cdp_oracle::test_utils::cdp_command;
 use std::fs;
 use std::path::Path;
@@ -190,16 +191,10 @@ fn test_pvoc_anal_overlap_factors() {
     }
 }
 
-/// Helper: Generate a simple test WAV file
+/// Helper: Generate a simple test WAV file (2 seconds of 440Hz, 16-bit)
 fn generate_test_wav(path: &Path) {
-    // Use CDP housekeep example or simple Python script
-    Command::new("python3")
-        .args([
-            "../../scripts/generate-test-audio.py",
-            path.to_str().unwrap(),
-        ])
-        .output()
-        .expect("Failed to generate test WAV");
+    let tone = cdp_synth::sine(440.0, 2.0, 44100, 1.0).expect("Failed to generate test tone");
+    wav_cdp::write_audio_buffer(path, &tone, SampleType::Int16).expect("Failed to write test WAV");
 }
 
 /// Helper: Compare two .ana files ignoring timestamps
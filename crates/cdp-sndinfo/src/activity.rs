@@ -0,0 +1,329 @@
+//! Level and activity analysis
+//!
+//! One pass over a sound file gathers its zero-crossing rate, overall RMS
+//! level, an RMS profile over fixed windows and a count of clipped samples:
+//! the figures worth knowing before choosing distortion or gate settings.
+
+use super::{Result, SndinfoError};
+use crate::props::level_db;
+use cdp_housekeep::wav_cdp::WavReader;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Frames read per block while scanning
+const SCAN_BLOCK_FRAMES: usize = 8192;
+
+/// Width of the text profile's level bars, in characters
+const BAR_WIDTH: usize = 50;
+
+/// Lowest level the text profile draws, in dB
+const BAR_FLOOR_DB: f32 = -60.0;
+
+/// Level of one window of the profile
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelWindow {
+    /// Start of the window in seconds
+    pub time: f64,
+    /// RMS level across all channels
+    pub rms: f32,
+    /// Largest absolute sample
+    pub peak: f32,
+}
+
+/// Activity figures of a sound file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Activity {
+    /// Sample rate in Hz
+    pub sample_rate: u32,
+    /// Number of channels
+    pub channels: u16,
+    /// Number of sample frames
+    pub frames: usize,
+    /// Sign changes between consecutive samples, summed over channels
+    pub zero_crossings: usize,
+    /// Samples at or beyond full scale
+    pub clipped: usize,
+    /// RMS level of the whole file across all channels
+    pub rms: f32,
+    /// RMS and peak level of each window
+    pub profile: Vec<LevelWindow>,
+}
+
+impl Activity {
+    /// Fraction (0-1) of sample steps that cross zero, as CDP's zcross reports
+    pub fn zero_crossing_fraction(&self) -> f64 {
+        let steps = self.frames.saturating_sub(1) * self.channels as usize;
+        if steps == 0 {
+            0.0
+        } else {
+            self.zero_crossings as f64 / steps as f64
+        }
+    }
+
+    /// Zero crossings per second of each channel
+    pub fn zero_crossings_per_second(&self) -> f64 {
+        let duration = self.frames as f64 / self.sample_rate as f64;
+        if duration == 0.0 {
+            0.0
+        } else {
+            self.zero_crossings as f64 / self.channels as f64 / duration
+        }
+    }
+
+    /// Overall RMS level in dB
+    pub fn rms_db(&self) -> f32 {
+        level_db(self.rms)
+    }
+
+    /// Format the profile as CSV, one row per window
+    pub fn profile_csv(&self) -> String {
+        let mut csv = String::from("time,rms,rms_db,peak\n");
+        for window in &self.profile {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{}",
+                window.time,
+                window.rms,
+                level_db(window.rms),
+                window.peak
+            );
+        }
+        csv
+    }
+
+    /// Format the profile as text, one bar per window
+    ///
+    /// Bars run from -60 dB at the left to full scale at the right.
+    pub fn profile_text(&self) -> String {
+        let mut text = String::new();
+        for window in &self.profile {
+            let db = level_db(window.rms).max(BAR_FLOOR_DB);
+            let length = ((db - BAR_FLOOR_DB) / -BAR_FLOOR_DB * BAR_WIDTH as f32).round() as usize;
+            let _ = writeln!(
+                text,
+                "{:9.3}  {:7.2} dB  {}",
+                window.time,
+                level_db(window.rms),
+                "#".repeat(length)
+            );
+        }
+        text
+    }
+}
+
+/// Scan a sound file for its activity figures
+///
+/// `window` is the length of each profile window in seconds.
+pub fn activity(input: &Path, window: f64) -> Result<Activity> {
+    let mut reader = WavReader::open(input)?;
+    let format = reader.format().clone();
+    let channels = format.channels.max(1) as usize;
+    let window_frames = (window * format.sample_rate as f64).round() as usize;
+    if !(window.is_finite() && window_frames > 0) {
+        return Err(SndinfoError::InvalidParameter(format!(
+            "Window must be at least one sample long, got {} secs",
+            window
+        )));
+    }
+
+    // Integer formats clip at their largest positive value, which decodes
+    // just below 1.0; the negative extreme decodes to exactly -1.0
+    let clip_level = if reader.sample_type().is_float() {
+        1.0
+    } else {
+        let max_value = format.max_sample_value() as f32;
+        max_value / (max_value + 1.0)
+    };
+
+    let mut previous = vec![None::<bool>; channels];
+    let mut zero_crossings = 0;
+    let mut clipped = 0;
+    let mut total_square = 0.0f64;
+    let mut window_square = 0.0f64;
+    let mut window_peak = 0.0f32;
+    let mut profile = Vec::new();
+
+    let mut buf = vec![0.0f32; SCAN_BLOCK_FRAMES * channels];
+    let mut index = 0;
+    loop {
+        let count = reader.read_block(&mut buf)?;
+        if count == 0 {
+            break;
+        }
+        for &sample in &buf[..count] {
+            let negative = sample < 0.0;
+            let channel = index % channels;
+            if previous[channel].is_some_and(|was| was != negative) {
+                zero_crossings += 1;
+            }
+            previous[channel] = Some(negative);
+
+            if sample.abs() >= clip_level {
+                clipped += 1;
+            }
+            let square = sample as f64 * sample as f64;
+            total_square += square;
+            window_square += square;
+            window_peak = window_peak.max(sample.abs());
+
+            index += 1;
+            if index % (window_frames * channels) == 0 {
+                profile.push(close_window(
+                    &mut window_square,
+                    &mut window_peak,
+                    window_frames * channels,
+                    profile.len() as f64 * window,
+                ));
+            }
+        }
+    }
+    let remainder = index % (window_frames * channels);
+    if remainder > 0 {
+        profile.push(close_window(
+            &mut window_square,
+            &mut window_peak,
+            remainder,
+            profile.len() as f64 * window,
+        ));
+    }
+
+    Ok(Activity {
+        sample_rate: format.sample_rate,
+        channels: channels as u16,
+        frames: index / channels,
+        zero_crossings,
+        clipped,
+        rms: if index == 0 {
+            0.0
+        } else {
+            (total_square / index as f64).sqrt() as f32
+        },
+        profile,
+    })
+}
+
+/// Finish a profile window of `samples` samples and reset its totals
+fn close_window(square: &mut f64, peak: &mut f32, samples: usize, time: f64) -> LevelWindow {
+    let window = LevelWindow {
+        time,
+        rms: (*square / samples as f64).sqrt() as f32,
+        peak: *peak,
+    };
+    *square = 0.0;
+    *peak = 0.0;
+    window
+}
+
+/// CLI compatibility layer for sndinfo zcross
+///
+/// Usage: `zcross infile`.
+pub fn zcross_cli(args: &[&str]) -> Result<()> {
+    let &[input] = args else {
+        return Err(SndinfoError::InvalidFile("Usage: zcross <infile>".into()));
+    };
+    let report = activity(Path::new(input), 1.0)?;
+    println!(
+        "Fraction of zero-crossings = {:.6}",
+        report.zero_crossing_fraction()
+    );
+    println!(
+        "Zero-crossings per second  = {:.2}",
+        report.zero_crossings_per_second()
+    );
+    Ok(())
+}
+
+/// CLI compatibility layer for sndinfo rms
+///
+/// Usage: `rms infile [-wwindow] [--csv]`. Prints the overall level, the
+/// clipped sample count and a level profile over windows of `window`
+/// seconds (default 0.1), as text bars or with `--csv` as CSV.
+pub fn rms_cli(args: &[&str]) -> Result<()> {
+    let mut input = None;
+    let mut window = 0.1;
+    let mut csv = false;
+    for &arg in args {
+        if arg == "--csv" {
+            csv = true;
+        } else if let Some(value) = arg.strip_prefix("-w") {
+            window = value.parse().map_err(|_| {
+                SndinfoError::InvalidParameter(format!("Invalid window: {}", value))
+            })?;
+        } else if input.is_none() && !arg.starts_with('-') {
+            input = Some(arg);
+        } else {
+            return Err(SndinfoError::InvalidFile(format!(
+                "Unknown rms argument: {}",
+                arg
+            )));
+        }
+    }
+    let Some(input) = input else {
+        return Err(SndinfoError::InvalidFile(
+            "Usage: rms <infile> [-wwindow] [--csv]".into(),
+        ));
+    };
+
+    let report = activity(Path::new(input), window)?;
+    if csv {
+        print!("{}", report.profile_csv());
+        return Ok(());
+    }
+    println!(
+        "RMS level: .......... {:.6} ({:.2} dB)",
+        report.rms,
+        report.rms_db()
+    );
+    println!("clipped samples: .... {}", report.clipped);
+    println!();
+    print!("{}", report.profile_text());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cdp_housekeep::wav_cdp::{WavFormat, WavWriter};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_activity() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("square.wav");
+        let format = WavFormat {
+            channels: 1,
+            sample_rate: 1000,
+            bits_per_sample: 16,
+            data_size: 0,
+        };
+
+        // Half a second of a 0.5 square wave at 50 Hz, then half a second
+        // of full-scale clipping at -1
+        let mut samples: Vec<f32> = (0..500)
+            .map(|i| if i % 20 < 10 { 0.5 } else { -0.5 })
+            .collect();
+        samples.extend(std::iter::repeat(-1.0).take(500));
+        let mut writer = WavWriter::create(&path, &format).unwrap();
+        writer.write_block(&samples).unwrap();
+        writer.finalize().unwrap();
+
+        let report = activity(&path, 0.25).unwrap();
+        assert_eq!(report.frames, 1000);
+        // 49 crossings within the square wave; the step into the
+        // clipped half stays negative
+        assert_eq!(report.zero_crossings, 49);
+        assert_eq!(report.clipped, 500);
+        assert!((report.rms - (0.625f32).sqrt()).abs() < 1e-3);
+
+        assert_eq!(report.profile.len(), 4);
+        assert!((report.profile[0].rms - 0.5).abs() < 1e-3);
+        assert_eq!(report.profile[3].time, 0.75);
+        assert_eq!(report.profile[3].peak, 1.0);
+        assert!(report
+            .profile_csv()
+            .starts_with("time,rms,rms_db,peak\n0,0.5,"));
+        assert_eq!(report.profile_text().lines().count(), 4);
+
+        assert!(activity(&path, 0.0).is_err());
+    }
+}
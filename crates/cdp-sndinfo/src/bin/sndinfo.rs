@@ -11,7 +11,7 @@ fn main() {
     if args.len() < 2 {
         eprintln!("CDP-RS SndInfo (Oracle Validation Binary)");
         eprintln!("Usage: sndinfo <operation> <infile> [args...]");
-        eprintln!("Operations: props, maxsamp, len, etc.");
+        eprintln!("Operations: props, maxsamp, len, units, zcross, rms, diff, headroom");
         process::exit(1);
     }
 
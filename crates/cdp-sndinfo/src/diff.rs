@@ -0,0 +1,239 @@
+//! Comparison of two sound files
+//!
+//! Reports how two files differ in format, length and content, using the
+//! same measures the oracle tests use to judge output against CDP.
+
+use super::{Result, SndinfoError};
+use crate::props::{get_props, SoundFileInfo};
+use cdp_housekeep::wav_cdp;
+use cdp_oracle::{validator, wav_compare};
+use std::fmt;
+use std::path::Path;
+
+/// How the samples of two files differ over their common length
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampleDiff {
+    /// Largest absolute difference between corresponding samples
+    pub max_difference: f32,
+    /// RMS of the difference
+    pub rms_difference: f32,
+    /// First frame at which the files differ, counting the end of the
+    /// shorter file as a difference; `None` if they are identical
+    pub first_difference: Option<usize>,
+    /// Correlation of the two signals (1.0 for identical, non-silent files)
+    pub correlation: f32,
+}
+
+/// Structured comparison of two sound files
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoundDiff {
+    /// Properties of the first file
+    pub first: SoundFileInfo,
+    /// Properties of the second file
+    pub second: SoundFileInfo,
+    /// One line per differing format field, e.g. `sample rate: 44100 vs 48000`
+    pub format_differences: Vec<String>,
+    /// Length of the second file less that of the first, in frames
+    pub length_difference: i64,
+    /// Sample comparison; `None` when the channel counts differ
+    pub samples: Option<SampleDiff>,
+    /// Whether the PEAK chunks agree, ignoring their timestamps
+    pub peak_matches: bool,
+    /// Whether both files hold the same set of chunk types
+    pub chunks_match: bool,
+}
+
+impl SoundDiff {
+    /// Whether the files hold the same format and sample data
+    pub fn is_identical(&self) -> bool {
+        self.format_differences.is_empty()
+            && self.length_difference == 0
+            && self
+                .samples
+                .as_ref()
+                .is_some_and(|samples| samples.first_difference.is_none())
+    }
+}
+
+impl fmt::Display for SoundDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.format_differences.is_empty() {
+            writeln!(f, "format: ............. same")?;
+        }
+        for difference in &self.format_differences {
+            writeln!(f, "format differs: ..... {}", difference)?;
+        }
+        writeln!(
+            f,
+            "length: ............. {} vs {} samples ({:+})",
+            self.first.frames, self.second.frames, self.length_difference
+        )?;
+        match &self.samples {
+            Some(samples) => {
+                writeln!(f, "max difference: ..... {:.6}", samples.max_difference)?;
+                writeln!(f, "rms difference: ..... {:.6}", samples.rms_difference)?;
+                match samples.first_difference {
+                    Some(frame) => writeln!(f, "first difference: ... frame {}", frame)?,
+                    None => writeln!(f, "first difference: ... none")?,
+                }
+                writeln!(f, "correlation: ........ {:.6}", samples.correlation)?;
+            }
+            None => writeln!(f, "samples not compared: channel counts differ")?,
+        }
+        writeln!(
+            f,
+            "PEAK chunks: ........ {}",
+            if self.peak_matches { "match" } else { "differ" }
+        )?;
+        writeln!(
+            f,
+            "chunk types: ........ {}",
+            if self.chunks_match { "match" } else { "differ" }
+        )?;
+        if self.is_identical() {
+            writeln!(f, "Files are identical (ignoring timestamps)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare two sound files
+pub fn diff(first: &Path, second: &Path) -> Result<SoundDiff> {
+    let first_info = get_props(first)?;
+    let second_info = get_props(second)?;
+
+    let mut format_differences = Vec::new();
+    let mut check = |field: &str, a: String, b: String| {
+        if a != b {
+            format_differences.push(format!("{}: {} vs {}", field, a, b));
+        }
+    };
+    check(
+        "sample rate",
+        first_info.sample_rate.to_string(),
+        second_info.sample_rate.to_string(),
+    );
+    check(
+        "channels",
+        first_info.channels.to_string(),
+        second_info.channels.to_string(),
+    );
+    let sample_type = |info: &SoundFileInfo| match info.sample_type {
+        Some(sample_type) => sample_type.to_string(),
+        None => format!("{}bit", info.bits_per_sample),
+    };
+    check(
+        "sample type",
+        sample_type(&first_info),
+        sample_type(&second_info),
+    );
+
+    let samples = if first_info.channels == second_info.channels {
+        let (_, a) = wav_cdp::read_wav_samples(first)?;
+        let (_, b) = wav_cdp::read_wav_samples(second)?;
+        Some(compare_samples(
+            &a.to_f32(),
+            &b.to_f32(),
+            first_info.channels.max(1) as usize,
+        ))
+    } else {
+        None
+    };
+
+    let chunks = wav_compare::compare_wav_files(first, second)?;
+    Ok(SoundDiff {
+        length_difference: second_info.frames as i64 - first_info.frames as i64,
+        first: first_info,
+        second: second_info,
+        format_differences,
+        samples,
+        peak_matches: chunks.peak_matches,
+        chunks_match: chunks.chunks_match,
+    })
+}
+
+/// Compare interleaved samples of two files with `channels` channels
+fn compare_samples(a: &[f32], b: &[f32], channels: usize) -> SampleDiff {
+    let common = a.len().min(b.len());
+    let first_difference = a[..common]
+        .iter()
+        .zip(&b[..common])
+        .position(|(x, y)| x != y)
+        .or((a.len() != b.len()).then_some(common))
+        .map(|index| index / channels);
+    SampleDiff {
+        max_difference: validator::max_difference(a, b),
+        rms_difference: validator::rms_difference(a, b),
+        first_difference,
+        correlation: validator::correlation(a, b),
+    }
+}
+
+/// CLI compatibility layer for sndinfo diff
+///
+/// Usage: `diff file1 file2`.
+pub fn diff_cli(args: &[&str]) -> Result<()> {
+    let &[first, second] = args else {
+        return Err(SndinfoError::InvalidFile(
+            "Usage: diff <file1> <file2>".into(),
+        ));
+    };
+    print!("{}", diff(Path::new(first), Path::new(second))?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cdp_housekeep::wav_cdp::{WavFormat, WavWriter};
+    use tempfile::TempDir;
+
+    fn write(path: &Path, sample_rate: u32, samples: &[f32]) {
+        let format = WavFormat {
+            channels: 1,
+            sample_rate,
+            bits_per_sample: 16,
+            data_size: 0,
+        };
+        let mut writer = WavWriter::create(path, &format).unwrap();
+        writer.write_block(samples).unwrap();
+        writer.finalize().unwrap();
+    }
+
+    #[test]
+    fn test_diff() {
+        let dir = TempDir::new().unwrap();
+        let ramp: Vec<f32> = (0..100).map(|i| i as f32 / 200.0).collect();
+        let mut changed = ramp.clone();
+        changed[40] += 0.25;
+        changed.extend([0.0; 10]);
+
+        let (a, b, c) = (
+            dir.path().join("a.wav"),
+            dir.path().join("b.wav"),
+            dir.path().join("c.wav"),
+        );
+        write(&a, 44100, &ramp);
+        write(&b, 44100, &ramp);
+        write(&c, 48000, &changed);
+
+        let same = diff(&a, &b).unwrap();
+        assert!(same.is_identical());
+        assert!(same.peak_matches);
+        assert!((same.samples.unwrap().correlation - 1.0).abs() < 1e-6);
+
+        let different = diff(&a, &c).unwrap();
+        assert!(!different.is_identical());
+        assert_eq!(
+            different.format_differences,
+            ["sample rate: 44100 vs 48000"]
+        );
+        assert_eq!(different.length_difference, 10);
+        let samples = different.samples.as_ref().unwrap();
+        assert_eq!(samples.first_difference, Some(40));
+        assert!((samples.max_difference - 0.25).abs() < 1e-3);
+        assert!(different
+            .to_string()
+            .contains("first difference: ... frame 40"));
+    }
+}
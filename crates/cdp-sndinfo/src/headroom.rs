@@ -0,0 +1,256 @@
+//! Headroom reports
+//!
+//! Measures how far a sound file's peaks sit below full scale: a histogram
+//! of short-term peak levels, the overall peak in dBFS and the gain that
+//! normalizing would apply. A directory can be scanned in one go and
+//! sorted by peak, to check a set of stems before batch normalizing them.
+
+use super::{Result, SndinfoError};
+use crate::props::level_db;
+use cdp_core::fs;
+use cdp_housekeep::wav_cdp::WavReader;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Length of each peak-hold window in seconds
+const HOLD_SECONDS: f64 = 0.01;
+
+/// Width of each histogram bin in dB
+pub const BIN_DB: f32 = 6.0;
+
+/// Number of histogram bins; the last collects everything below -90 dB
+pub const BIN_COUNT: usize = 16;
+
+/// Width of the text histogram's bars, in characters
+const BAR_WIDTH: usize = 40;
+
+/// Peak levels of one sound file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Headroom {
+    /// File the levels were read from
+    pub path: PathBuf,
+    /// Peak level, relative to the largest positive value for integer
+    /// formats (capped at 1.0), as `modify loudness` measures it
+    pub peak: f32,
+    /// Count of peak-hold windows in each bin: bin `i` holds windows whose
+    /// peak lies in `(-(i + 1) * BIN_DB, -i * BIN_DB]` dBFS
+    pub histogram: [usize; BIN_COUNT],
+}
+
+impl Headroom {
+    /// Peak level in dBFS
+    pub fn peak_db(&self) -> f32 {
+        level_db(self.peak)
+    }
+
+    /// Gain that would bring the peak to `target` (a level, 1.0 for full
+    /// scale), as normalizing would apply it
+    ///
+    /// `None` for a silent file.
+    pub fn suggested_gain(&self, target: f32) -> Option<f32> {
+        (self.peak > 0.0).then(|| target / self.peak)
+    }
+
+    /// Format the histogram as text, one bar per bin, scaled to the fullest
+    pub fn histogram_text(&self) -> String {
+        let fullest = self.histogram.iter().copied().max().unwrap_or(0).max(1);
+        let mut text = String::new();
+        for (bin, &count) in self.histogram.iter().enumerate() {
+            let top = -(bin as i32) * BIN_DB as i32;
+            let label = if bin + 1 == BIN_COUNT {
+                format!("below {:4} dB", top)
+            } else {
+                format!("{:4} to {:4} dB", top, top - BIN_DB as i32)
+            };
+            let _ = writeln!(
+                text,
+                "{:>16}  {:<width$}  {}",
+                label,
+                "#".repeat(count * BAR_WIDTH / fullest),
+                count,
+                width = BAR_WIDTH
+            );
+        }
+        text
+    }
+}
+
+/// Measure the peak levels of a sound file
+pub fn headroom(input: &Path) -> Result<Headroom> {
+    let mut reader = WavReader::open(input)?;
+    let format = reader.format().clone();
+    let channels = format.channels.max(1) as usize;
+    let hold_samples = ((HOLD_SECONDS * format.sample_rate as f64) as usize).max(1) * channels;
+
+    let mut histogram = [0; BIN_COUNT];
+    let mut record = |level: f32| {
+        let bin = (-level_db(level) / BIN_DB).max(0.0) as usize;
+        histogram[bin.min(BIN_COUNT - 1)] += 1;
+    };
+
+    let mut peak = 0.0f32;
+    let mut buf = vec![0.0f32; hold_samples];
+    loop {
+        let count = reader.read_block(&mut buf)?;
+        if count == 0 {
+            break;
+        }
+        let held = buf[..count]
+            .iter()
+            .fold(0.0f32, |held, s| held.max(s.abs()));
+        record(held);
+        peak = peak.max(held);
+    }
+
+    if !reader.sample_type().is_float() {
+        let max_value = format.max_sample_value() as f32;
+        peak = (peak * (max_value + 1.0)).min(max_value) / max_value;
+    }
+    Ok(Headroom {
+        path: input.to_path_buf(),
+        peak,
+        histogram,
+    })
+}
+
+/// Measure every `.wav` file in a directory, loudest peak first
+///
+/// Files are taken from the directory itself, not its subdirectories.
+pub fn headroom_dir(dir: &Path) -> Result<Vec<Headroom>> {
+    let mut reports = Vec::new();
+    for path in fs::read_dir(dir)? {
+        if fs::is_file(&path)
+            && path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"))
+        {
+            reports.push(headroom(&path)?);
+        }
+    }
+    reports.sort_by(|a, b| b.peak.total_cmp(&a.peak).then_with(|| a.path.cmp(&b.path)));
+    Ok(reports)
+}
+
+/// Format reports as a table, one file per line
+///
+/// Gains are those that would bring each peak to `target`.
+pub fn headroom_table(reports: &[Headroom], target: f32) -> String {
+    let mut text = format!(
+        "{:>10}  {:>10}  {:>10}  file\n",
+        "peak dBFS", "headroom", "gain"
+    );
+    for report in reports {
+        let gain = match report.suggested_gain(target) {
+            Some(gain) => format!("{:.6}", gain),
+            None => "silent".into(),
+        };
+        let _ = writeln!(
+            text,
+            "{:>10.2}  {:>10.2}  {:>10}  {}",
+            report.peak_db(),
+            -report.peak_db(),
+            gain,
+            report.path.display()
+        );
+    }
+    text
+}
+
+/// CLI compatibility layer for the headroom report
+///
+/// Usage: `headroom infile|directory [-ttarget]`, with the normalization
+/// target a level from 0 to 1 (default 1.0). A file prints its histogram;
+/// a directory prints a table of its `.wav` files, loudest first.
+pub fn headroom_cli(args: &[&str]) -> Result<()> {
+    let mut input = None;
+    let mut target = 1.0f32;
+    for &arg in args {
+        if let Some(value) = arg.strip_prefix("-t") {
+            target = value.parse().map_err(|_| {
+                SndinfoError::InvalidParameter(format!("Invalid target: {}", value))
+            })?;
+        } else if input.is_none() && !arg.starts_with('-') {
+            input = Some(arg);
+        } else {
+            return Err(SndinfoError::InvalidFile(format!(
+                "Unknown headroom argument: {}",
+                arg
+            )));
+        }
+    }
+    let Some(input) = input else {
+        return Err(SndinfoError::InvalidFile(
+            "Usage: headroom <infile|directory> [-ttarget]".into(),
+        ));
+    };
+    if !(target > 0.0 && target <= 1.0) {
+        return Err(SndinfoError::InvalidParameter(
+            "Target level must be above 0 and at most 1.0".into(),
+        ));
+    }
+
+    let input = Path::new(input);
+    if fs::is_dir(input) {
+        print!("{}", headroom_table(&headroom_dir(input)?, target));
+        return Ok(());
+    }
+    let report = headroom(input)?;
+    println!("peak level: ......... {:.2} dBFS", report.peak_db());
+    match report.suggested_gain(target) {
+        Some(gain) => println!("normalizing gain: ... {:.6}", gain),
+        None => println!("File is silent"),
+    }
+    println!();
+    print!("{}", report.histogram_text());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cdp_housekeep::wav_cdp::{WavFormat, WavWriter};
+    use tempfile::TempDir;
+
+    fn write(path: &Path, samples: &[f32]) {
+        let format = WavFormat {
+            channels: 1,
+            sample_rate: 1000,
+            bits_per_sample: 16,
+            data_size: 0,
+        };
+        let mut writer = WavWriter::create(path, &format).unwrap();
+        writer.write_block(samples).unwrap();
+        writer.finalize().unwrap();
+    }
+
+    #[test]
+    fn test_headroom() {
+        let dir = TempDir::new().unwrap();
+        // Ten 10 ms windows at 0.5 (-6 dB), ten at 0.1 (-20 dB), five silent
+        let mut samples = vec![0.5f32; 100];
+        samples.extend([0.1; 100]);
+        samples.extend([0.0; 50]);
+        write(&dir.path().join("loud.wav"), &samples);
+        write(&dir.path().join("quiet.wav"), &[0.25; 100]);
+        write(&dir.path().join("silent.wav"), &[0.0; 100]);
+        fs::write(dir.path().join("notes.txt"), "not audio").unwrap();
+
+        let report = headroom(&dir.path().join("loud.wav")).unwrap();
+        assert!((report.peak_db() + 6.02).abs() < 0.01);
+        assert!((report.suggested_gain(1.0).unwrap() - 2.0).abs() < 1e-3);
+        // 0.5 is -6.02 dB, just inside the second bin
+        assert_eq!(report.histogram[1], 10);
+        assert_eq!(report.histogram[3], 10);
+        assert_eq!(report.histogram[BIN_COUNT - 1], 5);
+        assert_eq!(report.histogram_text().lines().count(), BIN_COUNT);
+
+        let reports = headroom_dir(dir.path()).unwrap();
+        let names: Vec<_> = reports
+            .iter()
+            .map(|r| r.path.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(names, ["loud.wav", "quiet.wav", "silent.wav"]);
+        assert_eq!(reports[2].suggested_gain(1.0), None);
+        assert!(headroom_table(&reports, 1.0).contains("silent"));
+    }
+}
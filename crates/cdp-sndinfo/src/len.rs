@@ -0,0 +1,121 @@
+//! Sound file durations
+//!
+//! Reports the length of one or more sound files in seconds and in sample
+//! frames, read from the header without scanning the data.
+
+use super::{Result, SndinfoError};
+use cdp_housekeep::wav_cdp::WavReader;
+use std::path::{Path, PathBuf};
+
+/// Length of one sound file
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileLength {
+    /// File the length was read from
+    pub path: PathBuf,
+    /// Number of sample frames (samples per channel)
+    pub frames: usize,
+    /// Sample rate in Hz
+    pub sample_rate: u32,
+}
+
+impl FileLength {
+    /// Duration in seconds
+    pub fn seconds(&self) -> f64 {
+        self.frames as f64 / self.sample_rate as f64
+    }
+}
+
+/// Read the length of a sound file
+pub fn file_length(input: &Path) -> Result<FileLength> {
+    let reader = WavReader::open(input)?;
+    let format = reader.format();
+    if format.sample_rate == 0 {
+        return Err(SndinfoError::InvalidFile(format!(
+            "{}: sample rate is zero",
+            input.display()
+        )));
+    }
+    Ok(FileLength {
+        path: input.to_path_buf(),
+        frames: format.frame_count(),
+        sample_rate: format.sample_rate,
+    })
+}
+
+/// Read the lengths of several sound files
+pub fn file_lengths<P: AsRef<Path>>(inputs: &[P]) -> Result<Vec<FileLength>> {
+    inputs
+        .iter()
+        .map(|input| file_length(input.as_ref()))
+        .collect()
+}
+
+/// Combined duration of several files in seconds
+///
+/// Files may differ in sample rate, so only the total time is meaningful.
+pub fn total_seconds(lengths: &[FileLength]) -> f64 {
+    lengths.iter().map(FileLength::seconds).sum()
+}
+
+/// CLI compatibility layer for sndinfo len
+///
+/// Usage: `len infile [infile...]`. A total is printed when more than one
+/// file is given.
+pub fn len_cli(args: &[&str]) -> Result<()> {
+    if args.is_empty() {
+        return Err(SndinfoError::InvalidFile(
+            "Usage: len <infile> [infile...]".into(),
+        ));
+    }
+    let lengths = file_lengths(args)?;
+    for length in &lengths {
+        println!(
+            "{}: duration {:.6} secs\t{} samples",
+            length.path.display(),
+            length.seconds(),
+            length.frames
+        );
+    }
+    if lengths.len() > 1 {
+        println!("TOTAL DURATION: {:.6} secs", total_seconds(&lengths));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cdp_housekeep::wav_cdp::{WavFormat, WavWriter};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_file_lengths() {
+        let dir = TempDir::new().unwrap();
+        let mut paths = Vec::new();
+        for (name, sample_rate, channels, frames) in
+            [("a.wav", 44100, 2, 22050), ("b.wav", 48000, 1, 12000)]
+        {
+            let format = WavFormat {
+                channels,
+                sample_rate,
+                bits_per_sample: 16,
+                data_size: 0,
+            };
+            let path = dir.path().join(name);
+            let mut writer = WavWriter::create(&path, &format).unwrap();
+            writer
+                .write_block(&vec![0.0; frames * channels as usize])
+                .unwrap();
+            writer.finalize().unwrap();
+            paths.push(path);
+        }
+
+        let lengths = file_lengths(&paths).unwrap();
+        assert_eq!(lengths[0].frames, 22050);
+        assert_eq!(lengths[0].seconds(), 0.5);
+        assert_eq!(lengths[1].seconds(), 0.25);
+        assert_eq!(total_seconds(&lengths), 0.75);
+
+        assert!(file_length(&dir.path().join("missing.wav")).is_err());
+    }
+}
@@ -1,15 +1,27 @@
 //! CDP Sndinfo module - Sound file information and analysis
 //!
 //! This module implements CDP's sound file information operations including:
-//! - File properties display
-//! - Peak analysis
-//! - Duration calculation
+//! - File properties display, as CDP text, JSON or CSV
+//! - Peak analysis: true maximum samples per channel, and PEAK chunk repair
+//! - Duration calculation for one or many files
+//! - Conversion between samples, seconds and SMPTE timecode
+//! - Zero-crossing rate, RMS level profiles and clipped sample counts
+//! - Comparison of two sound files
+//! - Headroom reports with peak histograms, for single files or directories
 //!
 //! All operations are validated against CDP binaries for byte-perfect compatibility.
 
 use thiserror::Error;
 
+pub mod activity;
+// Compares through the oracle crate, which runs CDP binaries
+#[cfg(not(target_arch = "wasm32"))]
+pub mod diff;
+pub mod headroom;
+pub mod len;
+pub mod maxsamp;
 pub mod props;
+pub mod units;
 
 /// Result type for sndinfo operations
 pub type Result<T> = std::result::Result<T, SndinfoError>;
@@ -20,26 +32,45 @@ pub enum SndinfoError {
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
 
+    #[error("Housekeep error: {0}")]
+    Housekeep(#[from] cdp_housekeep::HousekeepError),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
     #[error("Invalid file: {0}")]
     InvalidFile(String),
+
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
 }
 
 // Re-export main functions for convenience
-pub use props::show_props;
+pub use activity::{activity, Activity, LevelWindow};
+#[cfg(not(target_arch = "wasm32"))]
+pub use diff::{diff, SampleDiff, SoundDiff};
+pub use headroom::{headroom, headroom_dir, headroom_table, Headroom};
+pub use len::{file_length, file_lengths, total_seconds, FileLength};
+pub use maxsamp::{maxsamp, refresh_peak, ChannelMax, MaxSamp};
+pub use props::{get_props, show_props, SoundFileInfo};
+pub use units::{
+    convert, samples_to_seconds, samples_to_timecode, seconds_to_samples, timecode_to_samples,
+    TimeUnit, Timecode,
+};
 
 /// CLI compatibility layer - matches CDP's command-line interface
 /// This is just for oracle testing. Real users should use the library functions directly.
 pub fn sndinfo(operation: &str, args: &[&str]) -> Result<()> {
-    use std::path::Path;
-
     match operation {
-        "props" => {
-            if args.is_empty() {
-                return Err(SndinfoError::InvalidFile("Usage: props <infile>".into()));
-            }
-            let input = Path::new(args[0]);
-            props::show_props(input)
-        }
+        "props" => props::props_cli(args),
+        "maxsamp" => maxsamp::maxsamp_cli(args),
+        "len" => len::len_cli(args),
+        "units" => units::units_cli(args),
+        "zcross" => activity::zcross_cli(args),
+        "rms" => activity::rms_cli(args),
+        #[cfg(not(target_arch = "wasm32"))]
+        "diff" => diff::diff_cli(args),
+        "headroom" => headroom::headroom_cli(args),
         _ => Err(SndinfoError::InvalidFile(format!(
             "Unknown operation: {}",
             operation
@@ -0,0 +1,271 @@
+//! Maximum sample scanning
+//!
+//! Finds the true maximum absolute sample of every channel by reading the
+//! whole data chunk, rather than trusting the PEAK chunk in the header, and
+//! can write a corrected PEAK chunk back when the stored one is missing or
+//! out of date.
+
+use super::{Result, SndinfoError};
+use crate::props::{get_props, level_db};
+use cdp_core::fs;
+use cdp_housekeep::wav_cdp::{self, ChannelPeak, UnknownChunks, WavReader};
+use std::path::Path;
+
+/// Frames read per block while scanning
+const SCAN_BLOCK_FRAMES: usize = 8192;
+
+/// Largest absolute sample of one channel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelMax {
+    /// Magnitude, relative to full scale for integer formats, measured as
+    /// the PEAK chunk measures it
+    pub level: f32,
+    /// Frame at which the maximum first occurs
+    pub frame: usize,
+}
+
+impl ChannelMax {
+    /// Level in dB relative to full scale
+    pub fn db(&self) -> f32 {
+        level_db(self.level)
+    }
+}
+
+/// Result of scanning a sound file for its maximum samples
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaxSamp {
+    /// Sample rate of the file, for converting frames to times
+    pub sample_rate: u32,
+    /// Maximum of each channel, as found in the data
+    pub channels: Vec<ChannelMax>,
+    /// Entries of the PEAK chunk as stored in the file, if it has one
+    pub stored_peaks: Option<Vec<ChannelPeak>>,
+}
+
+impl MaxSamp {
+    /// Loudest channel's maximum, earliest channel first on a tie
+    pub fn overall(&self) -> ChannelMax {
+        self.channels
+            .iter()
+            .copied()
+            .fold(None, |loudest: Option<ChannelMax>, max| match loudest {
+                Some(best) if best.level >= max.level => Some(best),
+                _ => Some(max),
+            })
+            .unwrap_or(ChannelMax {
+                level: 0.0,
+                frame: 0,
+            })
+    }
+
+    /// Gain that would bring the maximum up to full scale
+    ///
+    /// `None` for a silent file.
+    pub fn max_gain(&self) -> Option<f32> {
+        let level = self.overall().level;
+        (level > 0.0).then(|| 1.0 / level)
+    }
+
+    /// Whether the stored PEAK chunk agrees with the data
+    pub fn peak_is_current(&self) -> bool {
+        let Some(stored) = &self.stored_peaks else {
+            return false;
+        };
+        stored.len() == self.channels.len()
+            && stored.iter().zip(&self.channels).all(|(peak, max)| {
+                peak.position as usize == max.frame && (peak.value - max.level).abs() <= 1e-6
+            })
+    }
+}
+
+/// Scan a sound file for the maximum absolute sample of each channel
+pub fn maxsamp(input: &Path) -> Result<MaxSamp> {
+    let stored_peaks = get_props(input)?.peaks;
+
+    let mut reader = WavReader::open(input)?;
+    let format = reader.format().clone();
+    let channels = format.channels.max(1) as usize;
+
+    let mut magnitudes = vec![0.0f32; channels];
+    let mut frames = vec![0usize; channels];
+    let mut buf = vec![0.0f32; SCAN_BLOCK_FRAMES * channels];
+    let mut offset = 0;
+    loop {
+        let count = reader.read_block(&mut buf)?;
+        if count == 0 {
+            break;
+        }
+        for (i, &sample) in buf[..count].iter().enumerate() {
+            let index = offset + i;
+            let channel = index % channels;
+            if sample.abs() > magnitudes[channel] {
+                magnitudes[channel] = sample.abs();
+                frames[channel] = index / channels;
+            }
+        }
+        offset += count;
+    }
+
+    // Integer peaks are stored relative to the largest positive value, so a
+    // full-scale negative sample reads slightly above 1.0, as in the PEAK chunk
+    let scale = if reader.sample_type().is_float() {
+        1.0
+    } else {
+        let max_value = format.max_sample_value() as f32;
+        (max_value + 1.0) / max_value
+    };
+    let channels = magnitudes
+        .iter()
+        .zip(frames)
+        .map(|(&magnitude, frame)| ChannelMax {
+            level: magnitude * scale,
+            frame,
+        })
+        .collect();
+
+    Ok(MaxSamp {
+        sample_rate: format.sample_rate,
+        channels,
+        stored_peaks,
+    })
+}
+
+/// Rewrite the PEAK chunk of a sound file if it is missing or stale
+///
+/// The file is copied with a freshly computed PEAK chunk, keeping any
+/// other chunks, and the copy then replaces the original. Returns whether
+/// the file was rewritten.
+pub fn refresh_peak(input: &Path) -> Result<bool> {
+    if maxsamp(input)?.peak_is_current() {
+        return Ok(false);
+    }
+    let name = input
+        .file_name()
+        .ok_or_else(|| SndinfoError::InvalidFile(format!("{}", input.display())))?;
+    let mut temp_name = name.to_os_string();
+    temp_name.push(".peak");
+    let temp = input.with_file_name(temp_name);
+
+    if let Err(e) = wav_cdp::copy_wav_cdp_with(input, &temp, UnknownChunks::Preserve) {
+        let _ = fs::remove_file(&temp);
+        return Err(e.into());
+    }
+    fs::rename(&temp, input)?;
+    Ok(true)
+}
+
+/// CLI compatibility layer for sndinfo maxsamp
+///
+/// Usage: `maxsamp infile [-p]`. With `-p`, a missing or stale PEAK chunk
+/// is rewritten from the scanned values.
+pub fn maxsamp_cli(args: &[&str]) -> Result<()> {
+    let mut input = None;
+    let mut rewrite = false;
+    for &arg in args {
+        match arg {
+            "-p" => rewrite = true,
+            _ if input.is_none() && !arg.starts_with('-') => input = Some(arg),
+            _ => {
+                return Err(SndinfoError::InvalidFile(format!(
+                    "Unknown maxsamp argument: {}",
+                    arg
+                )))
+            }
+        }
+    }
+    let Some(input) = input else {
+        return Err(SndinfoError::InvalidFile(
+            "Usage: maxsamp <infile> [-p]".into(),
+        ));
+    };
+    let input = Path::new(input);
+
+    let report = maxsamp(input)?;
+    for (channel, max) in report.channels.iter().enumerate() {
+        println!(
+            "CH {}:\tmaximum sample = {:.6} ({:.2} dB)\tat frame {} ({:.4} secs)",
+            channel + 1,
+            max.level,
+            max.db(),
+            max.frame,
+            max.frame as f64 / report.sample_rate as f64
+        );
+    }
+    match report.max_gain() {
+        Some(gain) => println!("MAXIMUM POSSIBLE GAIN: {:.6}", gain),
+        None => println!("File is silent"),
+    }
+
+    if report.peak_is_current() {
+        println!("PEAK chunk is up to date");
+    } else {
+        if report.stored_peaks.is_some() {
+            println!("PEAK chunk does not match the data");
+        } else {
+            println!("No PEAK chunk in this file");
+        }
+        if rewrite && refresh_peak(input)? {
+            println!("PEAK chunk rewritten");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cdp_housekeep::wav_cdp::{WavFormat, WavWriter};
+    use tempfile::TempDir;
+
+    fn write_stereo(path: &Path, samples: &[f32]) {
+        let format = WavFormat {
+            channels: 2,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            data_size: 0,
+        };
+        let mut writer = WavWriter::create(path, &format).unwrap();
+        writer.write_block(samples).unwrap();
+        writer.finalize().unwrap();
+    }
+
+    #[test]
+    fn test_maxsamp_per_channel() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("stereo.wav");
+        write_stereo(&path, &[0.1, -0.25, -0.5, 0.2, 0.3, 0.25, 0.0, -1.0]);
+
+        let report = maxsamp(&path).unwrap();
+        assert_eq!(report.channels.len(), 2);
+        assert!((report.channels[0].level - 0.5).abs() < 1e-3);
+        assert_eq!(report.channels[0].frame, 1);
+        assert_eq!(report.channels[1].frame, 3);
+        assert!(report.channels[1].level > 1.0);
+        assert!((report.channels[0].db() + 6.02).abs() < 0.01);
+        assert_eq!(report.overall().frame, 3);
+
+        // The writer's own PEAK chunk agrees with the scan
+        assert!(report.peak_is_current());
+        assert!(!refresh_peak(&path).unwrap());
+    }
+
+    #[test]
+    fn test_refresh_stale_peak() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("stale.wav");
+        write_stereo(&path, &[0.1, -0.25, -0.5, 0.2]);
+
+        // Corrupt the first channel's stored peak value
+        let mut bytes = fs::read(&path).unwrap();
+        let peak = bytes.windows(4).position(|id| id == b"PEAK").unwrap();
+        bytes[peak + 16..peak + 20].copy_from_slice(&0.9f32.to_le_bytes());
+        fs::write(&path, &bytes).unwrap();
+
+        assert!(!maxsamp(&path).unwrap().peak_is_current());
+        assert!(refresh_peak(&path).unwrap());
+        let report = maxsamp(&path).unwrap();
+        assert!(report.peak_is_current());
+        assert!((report.channels[0].level - 0.5).abs() < 1e-3);
+        assert!(!dir.path().join("stale.wav.peak").exists());
+    }
+}
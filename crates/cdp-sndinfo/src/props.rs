@@ -1,74 +1,262 @@
 //! Properties display for sound files
 //!
-//! Shows format information, duration, peak levels, etc.
+//! Reads format information, duration, peak levels and CDP chunk presence,
+//! and formats them as CDP's props listing.
 
 use super::{Result, SndinfoError};
-use cdp_housekeep::wav_cdp;
-use std::fs::File;
+use cdp_core::fs;
+use cdp_housekeep::wav_cdp::{self, ChannelPeak, SampleType};
+use serde::Serialize;
+use std::fmt::{self, Write as _};
 use std::io::{BufReader, Read, Seek, SeekFrom};
 use std::path::Path;
 
-/// Display properties of a sound file
-pub fn show_props(input: &Path) -> Result<()> {
-    // Read the WAV file header and metadata
-    let mut reader = BufReader::new(File::open(input)?);
-
-    // Read basic format info
-    let (format, peak_info) = read_wav_with_metadata(&mut reader)?;
-
-    // Calculate duration
-    let total_samples = format.data_size as usize / 2 / format.channels as usize;
-    let duration_secs = total_samples as f64 / format.sample_rate as f64;
-
-    // Display CDP-style output
-    println!("CDP Release 7.1 2016"); // Match CDP's output format
-    println!("A SOUND file.");
-    println!("samples: ............ {}", total_samples);
-    println!("file type: ........... SOUND");
-    println!("sample rate: ........ {}", format.sample_rate);
-    println!("channels: ........... {}", format.channels);
-    println!("sample type:  {}bit", format.bits_per_sample);
-
-    // Show peak info if available
-    if let Some((peak_value, peak_pos)) = peak_info {
-        // Calculate dB value
-        let db = if peak_value > 0.0 {
-            20.0 * peak_value.log10()
-        } else {
-            -96.0 // Silence
-        };
+/// Properties of a sound file, as reported by sndinfo props
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoundFileInfo {
+    /// Sample rate in Hz
+    pub sample_rate: u32,
+    /// Number of channels
+    pub channels: u16,
+    /// Bits used to store one sample
+    pub bits_per_sample: u16,
+    /// Sample encoding, if the bit depth is one CDP supports
+    pub sample_type: Option<SampleType>,
+    /// Number of sample frames (samples per channel)
+    pub frames: usize,
+    /// Per-channel entries of the PEAK chunk, if the file has one
+    pub peaks: Option<Vec<ChannelPeak>>,
+    /// Whether the file has a cue chunk
+    pub has_cue: bool,
+    /// Whether the file has a LIST chunk
+    pub has_list: bool,
+}
+
+impl SoundFileInfo {
+    /// Duration in seconds
+    pub fn duration(&self) -> f64 {
+        self.frames as f64 / self.sample_rate as f64
+    }
+
+    /// PEAK entry of the loudest channel, earliest channel first on a tie
+    pub fn loudest_peak(&self) -> Option<ChannelPeak> {
+        self.peaks
+            .as_ref()?
+            .iter()
+            .copied()
+            .fold(None, |loudest: Option<ChannelPeak>, peak| match loudest {
+                Some(best) if best.value >= peak.value => Some(best),
+                _ => Some(peak),
+            })
+    }
+
+    /// Format as pretty-printed JSON
+    ///
+    /// Carries every field of the props listing, with each channel's PEAK
+    /// entry in full; `peaks` is null when the file has no PEAK chunk.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&JsonInfo::from(self))?)
+    }
+
+    /// Format as CSV, a header line and one row
+    ///
+    /// Each PEAK entry takes a level, dB and frame column, numbered by
+    /// channel; a file without a PEAK chunk has no peak columns.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "sample_rate,channels,bits_per_sample,sample_type,frames,duration,has_peak,has_cue,has_list",
+        );
+        let peaks = self.peaks.as_deref().unwrap_or_default();
+        for channel in 1..=peaks.len() {
+            let _ = write!(csv, ",ch{0}_peak,ch{0}_peak_db,ch{0}_peak_frame", channel);
+        }
+        csv.push('\n');
 
-        // For mono files, show channel-specific peak info
-        if format.channels == 1 {
-            println!("PEAK data (simplified)");
-            println!();
-            println!(
-                "CH 1:\tamp = {:.4} ({:.2} dB)\tFrame {}",
-                peak_value, db, peak_pos
+        let _ = write!(
+            csv,
+            "{},{},{},{},{},{},{},{},{}",
+            self.sample_rate,
+            self.channels,
+            self.bits_per_sample,
+            self.sample_type.map(|t| t.to_string()).unwrap_or_default(),
+            self.frames,
+            self.duration(),
+            self.peaks.is_some(),
+            self.has_cue,
+            self.has_list
+        );
+        for peak in peaks {
+            let _ = write!(
+                csv,
+                ",{},{},{}",
+                peak.value,
+                level_db(peak.value),
+                peak.position
             );
+        }
+        csv.push('\n');
+        csv
+    }
+}
+
+/// JSON layout of [`SoundFileInfo`]
+#[derive(Serialize)]
+struct JsonInfo {
+    file_type: &'static str,
+    sample_rate: u32,
+    channels: u16,
+    bits_per_sample: u16,
+    sample_type: Option<String>,
+    frames: usize,
+    duration: f64,
+    peaks: Option<Vec<JsonPeak>>,
+    has_cue: bool,
+    has_list: bool,
+}
+
+/// JSON layout of one PEAK entry
+#[derive(Serialize)]
+struct JsonPeak {
+    channel: usize,
+    value: f32,
+    db: f32,
+    frame: u32,
+}
+
+impl From<&SoundFileInfo> for JsonInfo {
+    fn from(info: &SoundFileInfo) -> Self {
+        Self {
+            file_type: "SOUND",
+            sample_rate: info.sample_rate,
+            channels: info.channels,
+            bits_per_sample: info.bits_per_sample,
+            sample_type: info.sample_type.map(|t| t.to_string()),
+            frames: info.frames,
+            duration: info.duration(),
+            peaks: info.peaks.as_ref().map(|peaks| {
+                peaks
+                    .iter()
+                    .enumerate()
+                    .map(|(channel, peak)| JsonPeak {
+                        channel: channel + 1,
+                        value: peak.value,
+                        db: level_db(peak.value),
+                        frame: peak.position,
+                    })
+                    .collect()
+            }),
+            has_cue: info.has_cue,
+            has_list: info.has_list,
+        }
+    }
+}
+
+impl fmt::Display for SoundFileInfo {
+    /// CDP-style properties listing
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "CDP Release 7.1 2016")?; // Match CDP's output format
+        writeln!(f, "A SOUND file.")?;
+        writeln!(f, "samples: ............ {}", self.frames)?;
+        writeln!(f, "file type: ........... SOUND")?;
+        writeln!(f, "sample rate: ........ {}", self.sample_rate)?;
+        writeln!(f, "channels: ........... {}", self.channels)?;
+        match self.sample_type {
+            Some(sample_type) => writeln!(f, "sample type:  {}", sample_type)?,
+            None => writeln!(f, "sample type:  {}bit", self.bits_per_sample)?,
+        }
+
+        // Show peak info if available
+        if let Some(peak) = self.loudest_peak() {
+            // For mono files, show channel-specific peak info
+            if self.channels == 1 {
+                writeln!(f, "PEAK data (simplified)")?;
+                writeln!(f)?;
+                writeln!(
+                    f,
+                    "CH 1:\tamp = {:.4} ({:.2} dB)\tFrame {}",
+                    peak.value,
+                    level_db(peak.value),
+                    peak.position
+                )?;
+            } else {
+                writeln!(f, "maximum level: ...... {:.6}", peak.value)?;
+            }
         } else {
-            println!("maximum level: ...... {:.6}", peak_value);
+            writeln!(f, "No PEAK chunk in this file")?;
+        }
+
+        // Show duration
+        let duration_secs = self.duration();
+        let mins = (duration_secs / 60.0) as i32;
+        let secs = duration_secs - (mins as f64 * 60.0);
+        if mins > 0 {
+            writeln!(f, "duration: ........... {} min {:.2} sec", mins, secs)
+        } else {
+            writeln!(f, "duration: ........... {:.2} sec", secs)
         }
-    } else {
-        println!("No PEAK chunk in this file");
     }
+}
 
-    // Show duration
-    let mins = (duration_secs / 60.0) as i32;
-    let secs = duration_secs - (mins as f64 * 60.0);
-    if mins > 0 {
-        println!("duration: ........... {} min {:.2} sec", mins, secs);
-    } else {
-        println!("duration: ........... {:.2} sec", secs);
+/// Read the properties of a sound file
+pub fn get_props(input: &Path) -> Result<SoundFileInfo> {
+    read_wav_with_metadata(&mut BufReader::new(fs::open(input)?))
+}
+
+/// Display properties of a sound file
+pub fn show_props(input: &Path) -> Result<()> {
+    print!("{}", get_props(input)?);
+    Ok(())
+}
+
+/// CLI compatibility layer for sndinfo props
+///
+/// Usage: `props infile [--json | --csv]`. Without a flag the CDP-style
+/// listing is printed.
+pub fn props_cli(args: &[&str]) -> Result<()> {
+    let mut input = None;
+    let mut json = false;
+    let mut csv = false;
+    for &arg in args {
+        match arg {
+            "--json" => json = true,
+            "--csv" => csv = true,
+            _ if input.is_none() && !arg.starts_with('-') => input = Some(arg),
+            _ => {
+                return Err(SndinfoError::InvalidFile(format!(
+                    "Unknown props argument: {}",
+                    arg
+                )))
+            }
+        }
     }
+    let (Some(input), false) = (input, json && csv) else {
+        return Err(SndinfoError::InvalidFile(
+            "Usage: props <infile> [--json | --csv]".into(),
+        ));
+    };
 
+    let info = get_props(Path::new(input))?;
+    if json {
+        println!("{}", info.to_json()?);
+    } else if csv {
+        print!("{}", info.to_csv());
+    } else {
+        print!("{}", info);
+    }
     Ok(())
 }
 
+/// Level in dB relative to full scale, with silence shown as -96 dB
+pub(crate) fn level_db(level: f32) -> f32 {
+    if level > 0.0 {
+        20.0 * level.log10()
+    } else {
+        -96.0
+    }
+}
+
 /// Read WAV file with metadata (including PEAK chunk if present)
-fn read_wav_with_metadata<R: Read + Seek>(
-    reader: &mut R,
-) -> Result<(wav_cdp::WavFormat, Option<(f32, u32)>)> {
+fn read_wav_with_metadata<R: Read + Seek>(reader: &mut R) -> Result<SoundFileInfo> {
     let mut header = [0u8; 12];
     reader.read_exact(&mut header)?;
 
@@ -80,6 +268,8 @@ fn read_wav_with_metadata<R: Read + Seek>(
     let mut format_info = None;
     let mut peak_info = None;
     let mut data_size = 0u32;
+    let mut has_cue = false;
+    let mut has_list = false;
 
     // Parse chunks
     loop {
@@ -112,28 +302,28 @@ fn read_wav_with_metadata<R: Read + Seek>(
             }
             b"PEAK" => {
                 if chunk_size >= 16 {
-                    let mut peak_data = [0u8; 16];
+                    let mut peak_data = vec![0u8; chunk_size as usize];
                     reader.read_exact(&mut peak_data)?;
 
                     // Skip version and timestamp
-                    let peak_bytes = [peak_data[8], peak_data[9], peak_data[10], peak_data[11]];
-                    let peak_value = f32::from_le_bytes(peak_bytes);
-
-                    let peak_pos = u32::from_le_bytes([
-                        peak_data[12],
-                        peak_data[13],
-                        peak_data[14],
-                        peak_data[15],
-                    ]);
-
-                    peak_info = Some((peak_value, peak_pos));
-
-                    // Skip any remaining data
-                    if chunk_size > 16 {
-                        reader.seek(SeekFrom::Current((chunk_size - 16) as i64))?;
-                    }
+                    peak_info = Some(
+                        peak_data[8..]
+                            .chunks_exact(8)
+                            .map(|entry| ChannelPeak {
+                                value: f32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]),
+                                position: u32::from_le_bytes([
+                                    entry[4], entry[5], entry[6], entry[7],
+                                ]),
+                            })
+                            .collect(),
+                    );
                 }
             }
+            b"cue " | b"LIST" => {
+                has_cue |= chunk_id == b"cue ";
+                has_list |= chunk_id == b"LIST";
+                reader.seek(SeekFrom::Current(chunk_size as i64))?;
+            }
             b"data" => {
                 data_size = chunk_size;
                 // Skip the actual audio data
@@ -158,7 +348,16 @@ fn read_wav_with_metadata<R: Read + Seek>(
             bits_per_sample,
             data_size,
         };
-        Ok((format, peak_info))
+        Ok(SoundFileInfo {
+            sample_rate,
+            channels,
+            bits_per_sample,
+            sample_type: format.sample_type().ok(),
+            frames: format.frame_count(),
+            peaks: peak_info,
+            has_cue,
+            has_list,
+        })
     } else {
         Err(SndinfoError::InvalidFile("No format chunk found".into()))
     }
@@ -174,4 +373,45 @@ mod tests {
         let result = show_props(Path::new("nonexistent.wav"));
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_get_props() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("mono.wav");
+        let format = wav_cdp::WavFormat {
+            channels: 1,
+            sample_rate: 22050,
+            bits_per_sample: 16,
+            data_size: 0,
+        };
+        let mut writer = wav_cdp::WavWriter::create(&path, &format).unwrap();
+        writer.write_block(&[0.0, 0.25, -0.5, 0.1]).unwrap();
+        writer.finalize().unwrap();
+
+        let info = get_props(&path).unwrap();
+        assert_eq!(info.frames, 4);
+        assert_eq!(info.sample_type, Some(SampleType::Int16));
+        assert!(info.has_cue && info.has_list);
+        assert_eq!(info.loudest_peak().unwrap().position, 2);
+
+        let text = info.to_string();
+        assert!(text.starts_with("CDP Release 7.1 2016\nA SOUND file.\nsamples: ............ 4\n"));
+        assert!(text.contains("CH 1:\tamp = 0.5000 (-6.02 dB)\tFrame 2\n"));
+        assert!(text.ends_with("duration: ........... 0.00 sec\n"));
+
+        let json = info.to_json().unwrap();
+        assert!(json.contains("\"sample_type\": \"16bit\""));
+        assert!(json.contains("\"frame\": 2"));
+        assert!(json.contains("\"has_cue\": true"));
+
+        let csv = info.to_csv();
+        let mut lines = csv.lines();
+        assert!(lines
+            .next()
+            .unwrap()
+            .ends_with(",has_list,ch1_peak,ch1_peak_db,ch1_peak_frame"));
+        let row: Vec<&str> = lines.next().unwrap().split(',').collect();
+        assert_eq!(row[..5], ["22050", "1", "16", "16bit", "4"]);
+        assert_eq!(row[11], "2");
+    }
 }
@@ -0,0 +1,281 @@
+//! Time unit conversions
+//!
+//! Converts between sample counts, seconds and SMPTE timecode. Timecode is
+//! non-drop-frame: at a fractional rate such as 29.97 each second of the
+//! label holds the rounded frame count (30), so labels run slightly slower
+//! than the clock, as on a non-drop-frame SMPTE counter.
+
+use super::{Result, SndinfoError};
+use std::fmt;
+use std::str::FromStr;
+
+/// Units accepted by the conversion helpers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeUnit {
+    /// Sample frames at a sample rate
+    Samples,
+    /// Seconds
+    Seconds,
+    /// SMPTE timecode at a frame rate
+    Smpte,
+}
+
+impl FromStr for TimeUnit {
+    type Err = SndinfoError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "samples" => Ok(TimeUnit::Samples),
+            "seconds" | "secs" => Ok(TimeUnit::Seconds),
+            "smpte" | "timecode" => Ok(TimeUnit::Smpte),
+            _ => Err(SndinfoError::InvalidParameter(format!(
+                "Unknown unit: {} (expected samples, seconds or smpte)",
+                s
+            ))),
+        }
+    }
+}
+
+/// SMPTE timecode `HH:MM:SS:FF`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timecode {
+    pub hours: u32,
+    pub minutes: u32,
+    pub seconds: u32,
+    pub frames: u32,
+}
+
+impl Timecode {
+    /// Timecode of a time in seconds, rounded down to a whole frame
+    pub fn from_seconds(seconds: f64, fps: f64) -> Result<Self> {
+        check_seconds(seconds)?;
+        let nominal = nominal_rate(fps)?;
+        // Nudge up so times landing exactly on a frame are not lost to rounding
+        let total = (seconds * fps + 1e-9).floor() as u64;
+        let (total_seconds, frames) = (total / nominal, total % nominal);
+        Ok(Self {
+            hours: (total_seconds / 3600) as u32,
+            minutes: (total_seconds / 60 % 60) as u32,
+            seconds: (total_seconds % 60) as u32,
+            frames: frames as u32,
+        })
+    }
+
+    /// Time in seconds at which this timecode's frame starts
+    pub fn to_seconds(&self, fps: f64) -> Result<f64> {
+        let nominal = nominal_rate(fps)?;
+        if self.minutes >= 60 || self.seconds >= 60 || self.frames as u64 >= nominal {
+            return Err(SndinfoError::InvalidParameter(format!(
+                "Timecode {} out of range at {} fps",
+                self, fps
+            )));
+        }
+        let total_seconds =
+            (self.hours as u64 * 60 + self.minutes as u64) * 60 + self.seconds as u64;
+        Ok((total_seconds * nominal + self.frames as u64) as f64 / fps)
+    }
+}
+
+impl fmt::Display for Timecode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}:{:02}:{:02}",
+            self.hours, self.minutes, self.seconds, self.frames
+        )
+    }
+}
+
+impl FromStr for Timecode {
+    type Err = SndinfoError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let fields = s
+            .split(':')
+            .map(|field| field.parse::<u32>())
+            .collect::<std::result::Result<Vec<u32>, _>>();
+        match fields.as_deref() {
+            Ok(&[hours, minutes, seconds, frames]) => Ok(Self {
+                hours,
+                minutes,
+                seconds,
+                frames,
+            }),
+            _ => Err(SndinfoError::InvalidParameter(format!(
+                "Invalid timecode: {} (expected HH:MM:SS:FF)",
+                s
+            ))),
+        }
+    }
+}
+
+/// Convert a sample frame count to seconds
+pub fn samples_to_seconds(samples: u64, sample_rate: u32) -> Result<f64> {
+    check_sample_rate(sample_rate)?;
+    Ok(samples as f64 / sample_rate as f64)
+}
+
+/// Convert seconds to the nearest sample frame count
+pub fn seconds_to_samples(seconds: f64, sample_rate: u32) -> Result<u64> {
+    check_sample_rate(sample_rate)?;
+    check_seconds(seconds)?;
+    Ok((seconds * sample_rate as f64).round() as u64)
+}
+
+/// Convert a sample frame count to timecode
+pub fn samples_to_timecode(samples: u64, sample_rate: u32, fps: f64) -> Result<Timecode> {
+    Timecode::from_seconds(samples_to_seconds(samples, sample_rate)?, fps)
+}
+
+/// Convert timecode to the sample frame at which its frame starts
+pub fn timecode_to_samples(timecode: &Timecode, sample_rate: u32, fps: f64) -> Result<u64> {
+    seconds_to_samples(timecode.to_seconds(fps)?, sample_rate)
+}
+
+/// Convert a value written in `from` units to `to` units
+///
+/// Values and results are text so timecode can pass through unchanged.
+pub fn convert(
+    value: &str,
+    from: TimeUnit,
+    to: TimeUnit,
+    sample_rate: u32,
+    fps: f64,
+) -> Result<String> {
+    let invalid = || SndinfoError::InvalidParameter(format!("Invalid value: {}", value));
+    let seconds = match from {
+        TimeUnit::Samples => {
+            samples_to_seconds(value.parse::<u64>().map_err(|_| invalid())?, sample_rate)?
+        }
+        TimeUnit::Seconds => value.parse::<f64>().map_err(|_| invalid())?,
+        TimeUnit::Smpte => value.parse::<Timecode>()?.to_seconds(fps)?,
+    };
+    Ok(match to {
+        TimeUnit::Samples => seconds_to_samples(seconds, sample_rate)?.to_string(),
+        TimeUnit::Seconds => {
+            check_seconds(seconds)?;
+            format!("{:.6}", seconds)
+        }
+        TimeUnit::Smpte => Timecode::from_seconds(seconds, fps)?.to_string(),
+    })
+}
+
+/// Frames counted per labelled second at `fps`
+fn nominal_rate(fps: f64) -> Result<u64> {
+    if !(1.0..=1000.0).contains(&fps) {
+        return Err(SndinfoError::InvalidParameter(format!(
+            "Frame rate must be between 1 and 1000 fps, got {}",
+            fps
+        )));
+    }
+    Ok(fps.round() as u64)
+}
+
+fn check_sample_rate(sample_rate: u32) -> Result<()> {
+    if sample_rate == 0 {
+        return Err(SndinfoError::InvalidParameter(
+            "Sample rate must be positive".into(),
+        ));
+    }
+    Ok(())
+}
+
+fn check_seconds(seconds: f64) -> Result<()> {
+    if !(seconds >= 0.0 && seconds.is_finite()) {
+        return Err(SndinfoError::InvalidParameter(format!(
+            "Time must be zero or more, got {}",
+            seconds
+        )));
+    }
+    Ok(())
+}
+
+/// CLI compatibility layer for unit conversion
+///
+/// Usage: `units from to value [-rsrate] [-ffps]`, where the units are
+/// `samples`, `seconds` or `smpte`. The sample rate defaults to 44100 and
+/// the frame rate to 25.
+pub fn units_cli(args: &[&str]) -> Result<()> {
+    let usage =
+        || SndinfoError::InvalidParameter("Usage: units from to value [-rsrate] [-ffps]".into());
+    let mut positional = Vec::new();
+    let mut sample_rate = 44100;
+    let mut fps = 25.0;
+    for &arg in args {
+        if let Some(value) = arg.strip_prefix("-r") {
+            sample_rate = value.parse().map_err(|_| {
+                SndinfoError::InvalidParameter(format!("Invalid sample rate: {}", value))
+            })?;
+        } else if let Some(value) = arg.strip_prefix("-f") {
+            fps = value.parse().map_err(|_| {
+                SndinfoError::InvalidParameter(format!("Invalid frame rate: {}", value))
+            })?;
+        } else {
+            positional.push(arg);
+        }
+    }
+    let &[from, to, value] = positional.as_slice() else {
+        return Err(usage());
+    };
+    println!(
+        "{}",
+        convert(value, from.parse()?, to.parse()?, sample_rate, fps)?
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timecode_round_trip() {
+        let timecode = Timecode::from_seconds(3723.5, 25.0).unwrap();
+        assert_eq!(timecode.to_string(), "01:02:03:12");
+        assert_eq!(timecode.to_seconds(25.0).unwrap(), 3723.48);
+        assert_eq!("01:02:03:12".parse::<Timecode>().unwrap(), timecode);
+
+        // Non-drop-frame labels count 30 frames a second at 29.97
+        let ntsc = Timecode::from_seconds(1.002, 29.97).unwrap();
+        assert_eq!(ntsc.to_string(), "00:00:01:00");
+
+        assert!("01:02:03".parse::<Timecode>().is_err());
+        assert!("00:00:00:25"
+            .parse::<Timecode>()
+            .unwrap()
+            .to_seconds(25.0)
+            .is_err());
+        assert!(Timecode::from_seconds(-1.0, 25.0).is_err());
+    }
+
+    #[test]
+    fn test_sample_conversions() {
+        assert_eq!(samples_to_seconds(22050, 44100).unwrap(), 0.5);
+        assert_eq!(seconds_to_samples(0.5, 48000).unwrap(), 24000);
+        assert_eq!(
+            samples_to_timecode(48000 * 61 + 24000, 48000, 24.0)
+                .unwrap()
+                .to_string(),
+            "00:01:01:12"
+        );
+        let timecode = "00:00:02:05".parse().unwrap();
+        assert_eq!(timecode_to_samples(&timecode, 44100, 25.0).unwrap(), 97020);
+        assert!(samples_to_seconds(1, 0).is_err());
+
+        assert_eq!(
+            convert("88200", TimeUnit::Samples, TimeUnit::Smpte, 44100, 30.0).unwrap(),
+            "00:00:02:00"
+        );
+        assert_eq!(
+            convert(
+                "00:00:01:15",
+                TimeUnit::Smpte,
+                TimeUnit::Seconds,
+                44100,
+                30.0
+            )
+            .unwrap(),
+            "1.500000"
+        );
+    }
+}
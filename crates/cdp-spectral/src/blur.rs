@@ -2,10 +2,16 @@
 //!
 //! Time-averages the spectrum across multiple windows to create a blurred effect.
 
-use crate::ana_io::{read_ana_file, write_ana_file};
-use crate::error::{Result, SpectralError};
+use crate::error::{check_param, write_frames, Result, SpectralError};
+use cdp_core::progress::{self, ProgressSink};
+use cdp_core::{AnaFile, AnaFrameReader, AnaFrameWriter, Breakpoints, Param, Rng};
+use std::collections::VecDeque;
+use std::io::{Read, Seek, Write};
 use std::path::Path;
 
+/// Largest step, in windows, allowed in a weave pattern
+const MAX_WEAVE_STEP: u32 = 256;
+
 /// Time-average the spectrum across multiple windows
 ///
 /// # Arguments
@@ -17,24 +23,162 @@ use std::path::Path;
 /// * `Ok(())` on success
 /// * `Err(SpectralError)` on failure
 pub fn blur(input_path: &Path, output_path: &Path, blur_windows: u32) -> Result<()> {
-    // Validate blur_windows
-    if blur_windows == 0 {
+    blur_with_progress(input_path, output_path, blur_windows, &mut ())
+}
+
+/// Time-average a spectral file, reporting each window to `progress`
+///
+/// As [`blur`]. Stops with [`cdp_core::CoreError::Cancelled`] if the sink
+/// asks. A failed or cancelled run leaves no output file.
+pub fn blur_with_progress(
+    input_path: &Path,
+    output_path: &Path,
+    blur_windows: u32,
+    progress: &mut dyn ProgressSink,
+) -> Result<()> {
+    let params = BlurParams {
+        windows: blur_windows,
+    };
+    blur_with_params(input_path, output_path, &params, progress)
+}
+
+/// Settings for [`blur_with_params`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct BlurParams {
+    /// Windows to average across, rounded up to an odd number
+    pub windows: u32,
+}
+
+impl Default for BlurParams {
+    /// A single window, leaving the spectrum unchanged
+    fn default() -> Self {
+        Self { windows: 1 }
+    }
+}
+
+impl BlurParams {
+    /// Start from the defaults
+    pub fn builder() -> BlurParamsBuilder {
+        BlurParamsBuilder(Self::default())
+    }
+}
+
+/// Builder for [`BlurParams`]
+#[derive(Debug, Clone, Copy)]
+pub struct BlurParamsBuilder(BlurParams);
+
+impl BlurParamsBuilder {
+    /// Windows to average across
+    pub fn windows(mut self, windows: u32) -> Self {
+        self.0.windows = windows;
+        self
+    }
+
+    /// Finish, checking the settings
+    pub fn build(self) -> Result<BlurParams> {
+        blur_span(self.0.windows)?;
+        Ok(self.0)
+    }
+}
+
+/// Time-average a spectral file with the given settings, reporting each
+/// window to `progress`
+///
+/// Stops with [`cdp_core::CoreError::Cancelled`] if the sink asks. A
+/// failed or cancelled run leaves no output file; pass `&mut ()` to ignore
+/// progress.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "info",
+        skip_all,
+        fields(input = %input_path.display(), output = %output_path.display()),
+        err
+    )
+)]
+pub fn blur_with_params(
+    input_path: &Path,
+    output_path: &Path,
+    params: &BlurParams,
+    progress: &mut dyn ProgressSink,
+) -> Result<()> {
+    blur_span(params.windows)?;
+    let mut reader = AnaFrameReader::open(input_path)?;
+    let header = reader.header().clone();
+    write_frames(output_path, header, |writer| {
+        stream_blur(&mut reader, writer, params.windows, progress)
+    })?;
+    #[cfg(feature = "tracing")]
+    tracing::debug!(frames = reader.num_frames(), "frames processed");
+    Ok(())
+}
+
+/// Time-average spectral frames read one at a time from `reader`
+///
+/// Streaming counterpart of [`blur`]: only the frames inside the blur
+/// window are held in memory. The writer is left for the caller to
+/// finalize.
+pub fn blur_stream<R: Read + Seek, W: Write + Seek>(
+    reader: &mut AnaFrameReader<R>,
+    writer: &mut AnaFrameWriter<W>,
+    blur_windows: u32,
+) -> Result<()> {
+    stream_blur(reader, writer, blur_windows, &mut ())
+}
+
+/// [`blur_stream`], reporting each window to `progress`
+fn stream_blur<R: Read + Seek, W: Write + Seek>(
+    reader: &mut AnaFrameReader<R>,
+    writer: &mut AnaFrameWriter<W>,
+    blur_windows: u32,
+    progress: &mut dyn ProgressSink,
+) -> Result<()> {
+    let blur_span = blur_span(blur_windows)? as usize;
+    let num_windows = reader.num_frames();
+    let window_size = reader.header().channels as usize;
+
+    if num_windows == 0 {
         return Err(SpectralError::InvalidInput(
-            "Blur windows must be greater than 0".to_string(),
+            "Input file has no spectral data".to_string(),
         ));
     }
 
-    // Make blur_windows odd if it isn't already
-    let blur_windows = if blur_windows % 2 == 0 {
-        blur_windows + 1
-    } else {
-        blur_windows
-    };
+    // Frames from `first_window` up to the last one read
+    let mut window: VecDeque<Vec<f32>> = VecDeque::with_capacity(blur_span * 2 + 1);
+    let mut first_window = 0;
+    let mut output = vec![0.0f32; window_size];
 
-    let blur_span = blur_windows / 2; // Number of windows on each side
+    for window_idx in 0..num_windows {
+        let start_window = window_idx.saturating_sub(blur_span);
+        let end_window = (window_idx + blur_span + 1).min(num_windows);
 
-    // Read input .ana file
-    let (header, samples) = read_ana_file(input_path)?;
+        while first_window + window.len() < end_window {
+            let mut frame = vec![0.0f32; window_size];
+            reader.read_frame(&mut frame)?;
+            window.push_back(frame);
+        }
+        while first_window < start_window {
+            window.pop_front();
+            first_window += 1;
+        }
+
+        average_frames(window.iter().map(Vec::as_slice), &mut output);
+        writer.write_frame(&output)?;
+        progress::report(progress, window_idx + 1, num_windows)?;
+    }
+
+    Ok(())
+}
+
+/// Time-average in-memory spectral frames across multiple windows
+///
+/// Buffer-based counterpart of [`blur`].
+pub fn blur_frames(input: &AnaFile, blur_windows: u32) -> Result<AnaFile> {
+    let blur_span = blur_span(blur_windows)?;
+    let header = &input.header;
+    let samples = &input.data;
 
     // Calculate window size (samples per window)
     let window_size = header.channels as usize;
@@ -60,25 +204,44 @@ pub fn blur(input_path: &Path, output_path: &Path, blur_windows: u32) -> Result<
             num_windows
         };
 
-        let actual_blur_windows = end_window - start_window;
-
         // Average each channel across the blur windows
-        for chan in 0..window_size {
-            let mut sum = 0.0f32;
+        let mut averaged = vec![0.0f32; window_size];
+        let frames =
+            samples[start_window * window_size..end_window * window_size].chunks_exact(window_size);
+        average_frames(frames, &mut averaged);
+        output.extend_from_slice(&averaged);
+    }
 
-            for w in start_window..end_window {
-                let sample_idx = w * window_size + chan;
-                sum += samples[sample_idx];
-            }
+    Ok(AnaFile::new(header.clone(), output)?)
+}
 
-            output.push(sum / actual_blur_windows as f32);
+/// Average each channel of `frames` into `output`
+fn average_frames<'a>(frames: impl Iterator<Item = &'a [f32]>, output: &mut [f32]) {
+    output.fill(0.0);
+    let mut count = 0;
+    for frame in frames {
+        for (sum, &value) in output.iter_mut().zip(frame) {
+            *sum += value;
         }
+        count += 1;
     }
+    for sum in output.iter_mut() {
+        *sum /= count as f32;
+    }
+}
 
-    // Write output .ana file
-    write_ana_file(output_path, &header, &output)?;
+/// Validate a blur width, returning the number of windows on each side
+fn blur_span(blur_windows: u32) -> Result<u32> {
+    check_param(Param::BlurWindows, blur_windows as f64)?;
 
-    Ok(())
+    // Make blur_windows odd if it isn't already
+    let blur_windows = if blur_windows % 2 == 0 {
+        blur_windows + 1
+    } else {
+        blur_windows
+    };
+
+    Ok(blur_windows / 2) // Number of windows on each side
 }
 
 /// Apply time-varying blur to spectrum
@@ -96,20 +259,26 @@ pub fn blur_varying(
     output_path: &Path,
     blur_values: &[(f64, u32)],
 ) -> Result<()> {
-    if blur_values.is_empty() {
-        return Err(SpectralError::InvalidInput(
-            "Blur values must not be empty".to_string(),
-        ));
-    }
+    check_blur_values(blur_values)?;
+    let input = AnaFile::load(input_path)?;
+    blur_varying_frames(&input, blur_values)?.save(output_path)?;
+    Ok(())
+}
 
-    // Read input .ana file
-    let (header, samples) = read_ana_file(input_path)?;
+/// Apply time-varying blur to in-memory spectral frames
+///
+/// Buffer-based counterpart of [`blur_varying`].
+pub fn blur_varying_frames(input: &AnaFile, blur_values: &[(f64, u32)]) -> Result<AnaFile> {
+    check_blur_values(blur_values)?;
+    let blur_values = blur_breakpoints(blur_values)?;
+    let header = &input.header;
+    let samples = &input.data;
 
     let window_size = header.channels as usize;
     let num_windows = samples.len() / window_size;
 
     // Calculate time per window from header metadata
-    let hop_size = header.window_len / header.dec_factor;
+    let hop_size = header.hop_size();
     let time_per_window = hop_size as f64 / header.sample_rate as f64;
 
     // Allocate output buffer
@@ -120,7 +289,7 @@ pub fn blur_varying(
         let current_time = window_idx as f64 * time_per_window;
 
         // Interpolate blur value at current time
-        let blur_windows = interpolate_blur_value(current_time, blur_values);
+        let blur_windows = blur_values.value_at(current_time).round() as u32;
         let blur_windows = if blur_windows % 2 == 0 {
             blur_windows + 1
         } else {
@@ -152,44 +321,533 @@ pub fn blur_varying(
         }
     }
 
-    // Write output
-    write_ana_file(output_path, &header, &output)?;
+    Ok(AnaFile::new(header.clone(), output)?)
+}
+
+fn check_blur_values(blur_values: &[(f64, u32)]) -> Result<()> {
+    if blur_values.is_empty() {
+        return Err(SpectralError::InvalidInput(
+            "Blur values must not be empty".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Blur window counts as breakpoints for interpolation
+fn blur_breakpoints(blur_values: &[(f64, u32)]) -> Result<Breakpoints> {
+    let points = blur_values
+        .iter()
+        .map(|&(time, windows)| (time, windows as f64))
+        .collect();
+    Ok(Breakpoints::new(points)?)
+}
+
+/// Randomly zero a proportion of channels in each window
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `proportion` - Fraction of channels to zero in each window (0-1)
+/// * `keep_loudest` - Number of loudest channels per window that are never zeroed
+/// * `seed` - Random seed; the same seed gives the same result
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn scatter(
+    input_path: &Path,
+    output_path: &Path,
+    proportion: f64,
+    keep_loudest: usize,
+    seed: u32,
+) -> Result<()> {
+    check_param(Param::ScatterProportion, proportion)?;
+    let input = AnaFile::load(input_path)?;
+    scatter_frames(&input, proportion, keep_loudest, seed)?.save(output_path)?;
+    Ok(())
+}
+
+/// Randomly zero a proportion of channels in in-memory spectral frames
+///
+/// Buffer-based counterpart of [`scatter`].
+pub fn scatter_frames(
+    input: &AnaFile,
+    proportion: f64,
+    keep_loudest: usize,
+    seed: u32,
+) -> Result<AnaFile> {
+    check_param(Param::ScatterProportion, proportion)?;
+    let mut output = input.clone();
+    let mut rng = Rng::new(seed);
+
+    for frame in output.frames_mut() {
+        let num_bins = frame.len() / 2;
+
+        // Channels eligible for zeroing, loudest first
+        let mut bins: Vec<usize> = (0..num_bins).collect();
+        bins.sort_by(|&a, &b| frame[b * 2].total_cmp(&frame[a * 2]));
+        let candidates = &mut bins[keep_loudest.min(num_bins)..];
+
+        // Partial Fisher-Yates shuffle picks the channels to zero
+        let count = (num_bins as f64 * proportion).round() as usize;
+        let count = count.min(candidates.len());
+        for i in 0..count {
+            let j = i + rng.below(candidates.len() - i);
+            candidates.swap(i, j);
+            frame[candidates[i] * 2] = 0.0;
+        }
+    }
+
+    Ok(output)
+}
+
+/// Spread each window's energy from its peaks across the spectrum
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `spread` - Degree of spreading (0 = unchanged, 1 = flat spectrum)
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn spread(input_path: &Path, output_path: &Path, spread: f64) -> Result<()> {
+    check_param(Param::Spread, spread)?;
+    let input = AnaFile::load(input_path)?;
+    spread_frames(&input, spread)?.save(output_path)?;
+    Ok(())
+}
+
+/// Spread peak energy across the spectrum of in-memory spectral frames
+///
+/// Buffer-based counterpart of [`spread`]. Each channel's amplitude moves
+/// toward the window peak by `spread`, then the window is rescaled to keep
+/// its total amplitude.
+pub fn spread_frames(input: &AnaFile, spread: f64) -> Result<AnaFile> {
+    check_param(Param::Spread, spread)?;
+    let spread = spread as f32;
+    let mut output = input.clone();
+
+    for frame in output.frames_mut() {
+        let total: f32 = frame.iter().step_by(2).sum();
+        let peak = frame.iter().step_by(2).fold(0.0f32, |a, &b| a.max(b));
+        if peak <= 0.0 {
+            continue;
+        }
+
+        for amp in frame.iter_mut().step_by(2) {
+            *amp += (peak - *amp) * spread;
+        }
+        let spread_total: f32 = frame.iter().step_by(2).sum();
+        let scale = total / spread_total;
+        for amp in frame.iter_mut().step_by(2) {
+            *amp *= scale;
+        }
+    }
+
+    Ok(output)
+}
+
+/// Add noise to the spectrum
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `amount` - Proportion of noise (0 = none, 1 = all noise)
+/// * `seed` - Random seed; the same seed gives the same result
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn noise(input_path: &Path, output_path: &Path, amount: f64, seed: u32) -> Result<()> {
+    check_param(Param::NoiseAmount, amount)?;
+    let input = AnaFile::load(input_path)?;
+    noise_frames(&input, amount, seed)?.save(output_path)?;
+    Ok(())
+}
+
+/// Add noise to in-memory spectral frames
+///
+/// Buffer-based counterpart of [`noise`]. Each channel's amplitude and
+/// frequency are mixed with random values around the window mean and the
+/// channel centre, then the window is rescaled to its original total
+/// amplitude so the level is unchanged.
+pub fn noise_frames(input: &AnaFile, amount: f64, seed: u32) -> Result<AnaFile> {
+    noise_varying_frames(input, &[(0.0, amount)], seed)
+}
+
+/// Add time-varying noise to the spectrum
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `amounts` - Vec of (time, amount) pairs for time-varying noise
+/// * `seed` - Random seed; the same seed gives the same result
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn noise_varying(
+    input_path: &Path,
+    output_path: &Path,
+    amounts: &[(f64, f64)],
+    seed: u32,
+) -> Result<()> {
+    check_noise_amounts(amounts)?;
+    let input = AnaFile::load(input_path)?;
+    noise_varying_frames(&input, amounts, seed)?.save(output_path)?;
+    Ok(())
+}
+
+/// Add time-varying noise to in-memory spectral frames
+///
+/// Buffer-based counterpart of [`noise_varying`].
+pub fn noise_varying_frames(input: &AnaFile, amounts: &[(f64, f64)], seed: u32) -> Result<AnaFile> {
+    check_noise_amounts(amounts)?;
+    let amounts = Breakpoints::new(amounts.to_vec())?;
+    let time_per_window = 1.0 / input.header.frame_rate() as f64;
+    let bin_width = input.header.bin_width();
+    let mut output = input.clone();
+    let mut rng = Rng::new(seed);
+
+    for (window_idx, frame) in output.frames_mut().enumerate() {
+        let amount = amounts.value_at(window_idx as f64 * time_per_window) as f32;
+        let total: f32 = frame.iter().step_by(2).sum();
+        if amount == 0.0 || total <= 0.0 {
+            continue;
+        }
+        let mean = total / (frame.len() / 2) as f32;
+
+        for (bin, pair) in frame.chunks_exact_mut(2).enumerate() {
+            let noise_amp = mean * 2.0 * rng.uniform() as f32;
+            let noise_freq = (bin as f32 + rng.uniform() as f32 - 0.5).max(0.0) * bin_width;
+            pair[0] += (noise_amp - pair[0]) * amount;
+            pair[1] += (noise_freq - pair[1]) * amount;
+        }
+
+        // Restore the original level
+        let noisy_total: f32 = frame.iter().step_by(2).sum();
+        if noisy_total > 0.0 {
+            let scale = total / noisy_total;
+            for amp in frame.iter_mut().step_by(2) {
+                *amp *= scale;
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+fn check_noise_amounts(amounts: &[(f64, f64)]) -> Result<()> {
+    if amounts.is_empty() {
+        return Err(SpectralError::InvalidInput(
+            "Noise amounts must not be empty".to_string(),
+        ));
+    }
+    for &(_, amount) in amounts {
+        check_param(Param::NoiseAmount, amount)?;
+    }
+    Ok(())
+}
+
+/// Random walk through the analysis windows
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `range` - Largest step of the walk, in seconds
+/// * `start_time` - Time of the window the walk starts from
+/// * `duration` - Duration of the output, in seconds
+/// * `allow_zero_steps` - Whether the walk may stay on the same window
+/// * `seed` - Random seed; the same seed gives the same result
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn drunk(
+    input_path: &Path,
+    output_path: &Path,
+    range: f64,
+    start_time: f64,
+    duration: f64,
+    allow_zero_steps: bool,
+    seed: u32,
+) -> Result<()> {
+    check_drunk_params(range, start_time, duration)?;
+    let input = AnaFile::load(input_path)?;
+    drunk_frames(&input, range, start_time, duration, allow_zero_steps, seed)?.save(output_path)?;
+    Ok(())
+}
+
+/// Random walk through in-memory analysis windows
+///
+/// Buffer-based counterpart of [`drunk`]. The walk is reflected at the ends
+/// of the file.
+pub fn drunk_frames(
+    input: &AnaFile,
+    range: f64,
+    start_time: f64,
+    duration: f64,
+    allow_zero_steps: bool,
+    seed: u32,
+) -> Result<AnaFile> {
+    check_drunk_params(range, start_time, duration)?;
+    let num_windows = input.num_frames();
+
+    if num_windows == 0 {
+        return Err(SpectralError::InvalidInput(
+            "Input file has no spectral data".to_string(),
+        ));
+    }
+
+    let frame_rate = input.header.frame_rate() as f64;
+    let max_step = (range * frame_rate).round() as i64;
+    if max_step == 0 && !allow_zero_steps {
+        return Err(SpectralError::InvalidInput(
+            "Range is less than one window, so every step would be zero".to_string(),
+        ));
+    }
+
+    let last = num_windows as i64 - 1;
+    let mut position = ((start_time * frame_rate).round() as i64).min(last);
+    let output_windows = ((duration * frame_rate).round() as usize).max(1);
+    let mut output = AnaFile::empty(input.header.clone())?;
+    let mut rng = Rng::new(seed);
+
+    for _ in 0..output_windows {
+        if let Some(frame) = input.frame(position as usize) {
+            output.push_frame(frame.values())?;
+        }
+
+        let step = loop {
+            let step = rng.below(2 * max_step as usize + 1) as i64 - max_step;
+            if step != 0 || allow_zero_steps {
+                break step;
+            }
+        };
+
+        // Reflect off the first and last windows
+        position += step;
+        if position < 0 {
+            position = -position;
+        }
+        if position > last {
+            position = 2 * last - position;
+        }
+        position = position.clamp(0, last);
+    }
+
+    Ok(output)
+}
+
+fn check_drunk_params(range: f64, start_time: f64, duration: f64) -> Result<()> {
+    if !range.is_finite() || range < 0.0 {
+        return Err(SpectralError::InvalidInput(
+            "Range must be zero or greater".to_string(),
+        ));
+    }
+    if !start_time.is_finite() || start_time < 0.0 {
+        return Err(SpectralError::InvalidInput(
+            "Start time must be zero or greater".to_string(),
+        ));
+    }
+    if !duration.is_finite() || duration <= 0.0 {
+        return Err(SpectralError::InvalidInput(
+            "Duration must be greater than 0".to_string(),
+        ));
+    }
+    Ok(())
+}
 
+/// Reorder groups of windows according to a pattern
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `pattern` - Domain and image, e.g. "abcd-dcba" or "abc-aabbcc"
+/// * `group_size` - Number of windows in each lettered group
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn shuffle(
+    input_path: &Path,
+    output_path: &Path,
+    pattern: &str,
+    group_size: usize,
+) -> Result<()> {
+    parse_shuffle_pattern(pattern, group_size)?;
+    let input = AnaFile::load(input_path)?;
+    shuffle_frames(&input, pattern, group_size)?.save(output_path)?;
     Ok(())
 }
 
-/// Helper function to interpolate blur value at a given time
-fn interpolate_blur_value(time: f64, blur_values: &[(f64, u32)]) -> u32 {
-    // Find surrounding points
-    let mut prev = blur_values[0];
-    let mut next = blur_values[blur_values.len() - 1];
+/// Reorder groups of in-memory analysis windows according to a pattern
+///
+/// Buffer-based counterpart of [`shuffle`]. The windows are taken in runs
+/// of one group per domain letter, and each run is replaced by the groups
+/// named in the image. Windows left over after the last whole run are
+/// copied unchanged.
+pub fn shuffle_frames(input: &AnaFile, pattern: &str, group_size: usize) -> Result<AnaFile> {
+    let (domain_len, image) = parse_shuffle_pattern(pattern, group_size)?;
+    let group_len = group_size * input.frame_len();
+    let run_len = domain_len * group_len;
+
+    let mut output = AnaFile::empty(input.header.clone())?;
+    let mut runs = input.data.chunks_exact(run_len);
+    for run in &mut runs {
+        for &group in &image {
+            output
+                .data
+                .extend_from_slice(&run[group * group_len..(group + 1) * group_len]);
+        }
+    }
+    output.data.extend_from_slice(runs.remainder());
+
+    Ok(output)
+}
+
+/// Parse a "domain-image" pattern into the domain length and the image
+/// positions within the domain
+fn parse_shuffle_pattern(pattern: &str, group_size: usize) -> Result<(usize, Vec<usize>)> {
+    let invalid = |reason: &str| {
+        Err(SpectralError::InvalidInput(format!(
+            "Invalid shuffle pattern '{}': {}",
+            pattern, reason
+        )))
+    };
+
+    if group_size == 0 {
+        return Err(SpectralError::InvalidInput(
+            "Group size must be greater than 0".to_string(),
+        ));
+    }
+    let Some((domain, image)) = pattern.split_once('-') else {
+        return invalid("expected domain-image");
+    };
+    if domain.is_empty() || image.is_empty() {
+        return invalid("domain and image must not be empty");
+    }
+
+    let domain: Vec<char> = domain.chars().collect();
+    if domain
+        .iter()
+        .enumerate()
+        .any(|(i, c)| domain[..i].contains(c))
+    {
+        return invalid("domain letters must be distinct");
+    }
 
-    for i in 0..blur_values.len() - 1 {
-        if time >= blur_values[i].0 && time <= blur_values[i + 1].0 {
-            prev = blur_values[i];
-            next = blur_values[i + 1];
-            break;
+    let mut positions = Vec::with_capacity(image.len());
+    for c in image.chars() {
+        match domain.iter().position(|&d| d == c) {
+            Some(position) => positions.push(position),
+            None => return invalid("image letters must appear in the domain"),
         }
     }
+    Ok((domain.len(), positions))
+}
+
+/// Resequence windows following the steps in a weave file
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `weave_path` - Text file of whitespace-separated window steps
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn weave(input_path: &Path, output_path: &Path, weave_path: &Path) -> Result<()> {
+    let steps = read_weave_file(weave_path)?;
+    check_weave_steps(&steps)?;
+    let input = AnaFile::load(input_path)?;
+    weave_frames(&input, &steps)?.save(output_path)?;
+    Ok(())
+}
+
+/// Read the window steps of a weave file
+pub fn read_weave_file(path: &Path) -> Result<Vec<i32>> {
+    cdp_core::fs::read_to_string(path)?
+        .split_whitespace()
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| SpectralError::InvalidInput(format!("Invalid weave step '{}'", value)))
+        })
+        .collect()
+}
+
+/// Resequence in-memory windows following a cycle of relative steps
+///
+/// Buffer-based counterpart of [`weave`]. Starting at the first window, the
+/// steps are applied in turn, repeating the cycle, until the position passes
+/// the end of the file. The steps must move forward overall and may not go
+/// back before the window a cycle starts from.
+pub fn weave_frames(input: &AnaFile, steps: &[i32]) -> Result<AnaFile> {
+    check_weave_steps(steps)?;
+    let num_windows = input.num_frames();
 
-    // Before first point
-    if time < blur_values[0].0 {
-        return blur_values[0].1;
+    // Furthest the pattern reaches beyond the start of a cycle
+    let reach = steps
+        .iter()
+        .scan(0i64, |position, &step| {
+            *position += step as i64;
+            Some(*position)
+        })
+        .max()
+        .unwrap_or_default();
+    if reach >= num_windows as i64 {
+        return Err(SpectralError::InvalidInput(format!(
+            "Weave pattern reaches {} windows ahead, but the file has only {} windows",
+            reach, num_windows
+        )));
     }
 
-    // After last point
-    if time > blur_values[blur_values.len() - 1].0 {
-        return blur_values[blur_values.len() - 1].1;
+    let mut output = AnaFile::empty(input.header.clone())?;
+    let mut position = 0i64;
+    for &step in steps.iter().cycle() {
+        match input.frame(position as usize) {
+            Some(frame) => output.push_frame(frame.values())?,
+            None => break,
+        }
+        position += step as i64;
     }
 
-    // Linear interpolation
-    if (next.0 - prev.0).abs() < 1e-10 {
-        return prev.1;
+    Ok(output)
+}
+
+fn check_weave_steps(steps: &[i32]) -> Result<()> {
+    if steps.is_empty() {
+        return Err(SpectralError::InvalidInput(
+            "Weave pattern must not be empty".to_string(),
+        ));
+    }
+    if steps
+        .iter()
+        .any(|step| step.unsigned_abs() > MAX_WEAVE_STEP)
+    {
+        return Err(SpectralError::InvalidInput(format!(
+            "Weave steps must be between -{} and {}",
+            MAX_WEAVE_STEP, MAX_WEAVE_STEP
+        )));
     }
 
-    let ratio = (time - prev.0) / (next.0 - prev.0);
-    let interpolated = prev.1 as f64 + ratio * (next.1 as f64 - prev.1 as f64);
-    interpolated.round() as u32
+    let mut position = 0i64;
+    for &step in steps {
+        position += step as i64;
+        if position < 0 {
+            return Err(SpectralError::InvalidInput(
+                "Weave pattern steps back before its starting window".to_string(),
+            ));
+        }
+    }
+    if position == 0 {
+        return Err(SpectralError::InvalidInput(
+            "Weave pattern must move forward overall".to_string(),
+        ));
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -207,23 +865,200 @@ mod tests {
         assert!(matches!(result, Err(SpectralError::InvalidInput(_))));
     }
 
+    #[test]
+    fn test_blur_params() {
+        assert_eq!(BlurParams::default().windows, 1);
+        assert_eq!(BlurParams::builder().windows(5).build().unwrap().windows, 5);
+        assert!(matches!(
+            BlurParams::builder().windows(0).build(),
+            Err(SpectralError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_cancelled_blur_leaves_no_output() {
+        use cdp_core::AnaHeader;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let input = dir.path().join("in.ana");
+        let output = dir.path().join("out.ana");
+        let header = AnaHeader::new(44100, 8, 3).unwrap();
+        AnaFile::new(header.clone(), vec![0.5; header.channels as usize * 4])
+            .unwrap()
+            .save(&input)
+            .unwrap();
+
+        let mut token = cdp_core::CancelToken::new();
+        token.cancel();
+        assert!(matches!(
+            blur_with_progress(&input, &output, 3, &mut token),
+            Err(SpectralError::Core(cdp_core::CoreError::Cancelled))
+        ));
+        assert!(!output.exists());
+    }
+
+    #[test]
+    fn test_failed_blur_leaves_no_output() {
+        use cdp_core::AnaHeader;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let input = dir.path().join("in.ana");
+        let output = dir.path().join("out.ana");
+        let header = AnaHeader::new(44100, 8, 3).unwrap();
+        AnaFile::new(header.clone(), vec![0.5; header.channels as usize * 4])
+            .unwrap()
+            .save(&input)
+            .unwrap();
+
+        // Cut the last frame short so reading fails part way through
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .open(&input)
+            .unwrap();
+        let len = file.metadata().unwrap().len();
+        file.set_len(len - 4).unwrap();
+
+        assert!(matches!(
+            blur(&input, &output, 1),
+            Err(SpectralError::Core(cdp_core::CoreError::Io(_)))
+        ));
+        assert!(!output.exists());
+    }
+
+    #[test]
+    fn test_blur_frames() {
+        use cdp_core::AnaHeader;
+        use std::io::Cursor;
+
+        // Four-float windows: two value pairs per window
+        let input = AnaFile::new(
+            AnaHeader::new(44100, 2, 2).unwrap(),
+            vec![0.0, 3.0, 0.0, 0.0, 3.0, 0.0, 0.0, 0.0, 6.0, 3.0, 0.0, 0.0],
+        )
+        .unwrap();
+
+        let output = blur_frames(&input, 3).unwrap();
+        assert_eq!(output.num_frames(), 3);
+        assert_eq!(
+            output.data,
+            vec![1.5, 1.5, 0.0, 0.0, 3.0, 2.0, 0.0, 0.0, 4.5, 1.5, 0.0, 0.0]
+        );
+
+        // Streaming gives the same frames without loading the whole file
+        let mut encoded =
+            AnaFrameWriter::new(Cursor::new(Vec::new()), input.header.clone()).unwrap();
+        for frame in input.data.chunks_exact(4) {
+            encoded.write_frame(frame).unwrap();
+        }
+        let mut encoded = encoded.finalize().unwrap();
+        encoded.set_position(0);
+
+        let mut reader = AnaFrameReader::new(encoded).unwrap();
+        let mut writer =
+            AnaFrameWriter::new(Cursor::new(Vec::new()), input.header.clone()).unwrap();
+        blur_stream(&mut reader, &mut writer, 3).unwrap();
+        let mut streamed = writer.finalize().unwrap();
+        streamed.set_position(0);
+
+        let mut reader = AnaFrameReader::new(streamed).unwrap();
+        let mut frame = [0.0f32; 4];
+        for expected in output.data.chunks_exact(4) {
+            assert!(reader.read_frame(&mut frame).unwrap());
+            assert_eq!(frame, expected);
+        }
+        assert_eq!(reader.frames_remaining(), 0);
+    }
+
+    #[test]
+    fn test_amplitude_randomising_frames() {
+        use cdp_core::AnaHeader;
+
+        // Five channels, one window, amplitudes 1-5
+        let frame: Vec<f32> = (0..5).flat_map(|bin| [bin as f32 + 1.0, 100.0]).collect();
+        let input = AnaFile::new(AnaHeader::new(800, 8, 2).unwrap(), frame).unwrap();
+
+        // Zeroing 60% while keeping the two loudest leaves only those
+        let output = scatter_frames(&input, 0.6, 2, 7).unwrap();
+        let amps: Vec<f32> = (0..5)
+            .map(|b| output.frame(0).unwrap().amplitude(b))
+            .collect();
+        assert_eq!(amps, vec![0.0, 0.0, 0.0, 4.0, 5.0]);
+        assert_eq!(output.frame(0).unwrap().frequency(0), 100.0);
+
+        // Full spread flattens the window, keeping its total amplitude
+        let output = spread_frames(&input, 1.0).unwrap();
+        let amps: Vec<f32> = (0..5)
+            .map(|b| output.frame(0).unwrap().amplitude(b))
+            .collect();
+        assert_eq!(amps, vec![3.0; 5]);
+
+        assert!(scatter_frames(&input, 1.5, 0, 0).is_err());
+
+        // Noise keeps the window level; no noise leaves the data alone
+        let output = noise_frames(&input, 0.5, 1).unwrap();
+        let total: f32 = (0..5).map(|b| output.frame(0).unwrap().amplitude(b)).sum();
+        assert!((total - 15.0).abs() < 1e-4);
+        assert_ne!(output, input);
+        assert_eq!(noise_frames(&input, 0.0, 1).unwrap(), input);
+    }
+
+    #[test]
+    fn test_window_reordering_frames() {
+        use cdp_core::AnaHeader;
+
+        // Six windows, one per second, numbered by amplitude
+        let header = AnaHeader::with_hop(4, 2, 4);
+        let data = (0..6).flat_map(|w| [w as f32, 0.0, 0.0, 0.0]).collect();
+        let input = AnaFile::new(header, data).unwrap();
+
+        // Steps of exactly one window either way
+        let output = drunk_frames(&input, 1.0, 2.0, 8.0, false, 3).unwrap();
+        let walk: Vec<f32> = output.frames().map(|f| f.amplitude(0)).collect();
+        assert_eq!(walk.len(), 8);
+        assert_eq!(walk[0], 2.0);
+        assert!(walk.windows(2).all(|w| (w[1] - w[0]).abs() == 1.0));
+
+        // Groups of two windows: "ab" swapped, trailing group untouched
+        let output = shuffle_frames(&input, "ab-ba", 2).unwrap();
+        let order: Vec<f32> = output.frames().map(|f| f.amplitude(0)).collect();
+        assert_eq!(order, vec![2.0, 3.0, 0.0, 1.0, 4.0, 5.0]);
+
+        // Domain letters outside ASCII count once each
+        let output = shuffle_frames(&input, "αβγ-γβα", 1).unwrap();
+        let order: Vec<f32> = output.frames().map(|f| f.amplitude(0)).collect();
+        assert_eq!(order, vec![2.0, 1.0, 0.0, 5.0, 4.0, 3.0]);
+
+        assert!(shuffle_frames(&input, "ab-bc", 1).is_err());
+        assert!(shuffle_frames(&input, "aab-ab", 1).is_err());
+
+        // Forward two, back one: 0 2 1 3 2 4 3 5 4
+        let output = weave_frames(&input, &[2, -1]).unwrap();
+        let order: Vec<f32> = output.frames().map(|f| f.amplitude(0)).collect();
+        assert_eq!(order, vec![0.0, 2.0, 1.0, 3.0, 2.0, 4.0, 3.0, 5.0, 4.0]);
+
+        assert!(weave_frames(&input, &[-1, 2]).is_err());
+        assert!(weave_frames(&input, &[1, -1]).is_err());
+        assert!(weave_frames(&input, &[6, -5]).is_err());
+    }
+
     #[test]
     fn test_interpolate_blur_value() {
-        let blur_values = vec![(0.0, 1), (1.0, 5), (2.0, 3)];
+        let blur_values = blur_breakpoints(&[(0.0, 1), (1.0, 5), (2.0, 3)]).unwrap();
+        let blur_at = |time| blur_values.value_at(time).round() as u32;
 
         // Test exact points
-        assert_eq!(interpolate_blur_value(0.0, &blur_values), 1);
-        assert_eq!(interpolate_blur_value(1.0, &blur_values), 5);
-        assert_eq!(interpolate_blur_value(2.0, &blur_values), 3);
+        assert_eq!(blur_at(0.0), 1);
+        assert_eq!(blur_at(1.0), 5);
+        assert_eq!(blur_at(2.0), 3);
 
         // Test interpolation
-        assert_eq!(interpolate_blur_value(0.5, &blur_values), 3); // Halfway between 1 and 5
-        assert_eq!(interpolate_blur_value(1.5, &blur_values), 4); // Halfway between 5 and 3
+        assert_eq!(blur_at(0.5), 3); // Halfway between 1 and 5
+        assert_eq!(blur_at(1.5), 4); // Halfway between 5 and 3
 
         // Test before first point
-        assert_eq!(interpolate_blur_value(-1.0, &blur_values), 1);
+        assert_eq!(blur_at(-1.0), 1);
 
         // Test after last point
-        assert_eq!(interpolate_blur_value(3.0, &blur_values), 3);
+        assert_eq!(blur_at(3.0), 3);
     }
 }
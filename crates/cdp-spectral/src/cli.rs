@@ -0,0 +1,325 @@
+//! Command-line interfaces of the spectral binaries
+//!
+//! Shared by the blur, stretch and pitch binaries and the cdp multicall
+//! binary.
+
+use crate::{
+    blur_with_params, calculate_output_duration, pitch_shift_with_params, semitones_to_factor,
+    stretch_time_with_params, BlurParams, PitchParams, StretchParams,
+};
+use cdp_core::TextProgress;
+use std::path::Path;
+
+/// Run blur with its command-line syntax, with `args[0]` the program name
+///
+/// Usage errors print the usage text and exit the process.
+pub fn blur_cli(args: &[String]) {
+    if args.len() < 2 {
+        eprintln!("CDP Release 7.1 2016");
+        eprintln!("blur     avrg     blur     bounce     ...other modes not implemented...");
+        eprintln!();
+        eprintln!("USAGE: blur NAME");
+        std::process::exit(1);
+    }
+
+    let mode = &args[1];
+
+    match mode.as_str() {
+        "blur" => {
+            if args.len() < 5 {
+                eprintln!("CDP Release 7.1 2016");
+                eprintln!("blur blur infile outfile blurring");
+                eprintln!();
+                eprintln!("TIME-AVERAGE THE SPECTRUM");
+                eprintln!();
+                eprintln!("blurring   is number of windows over which to average the spectrum.");
+                eprintln!();
+                eprintln!("blurring may vary over time.");
+                std::process::exit(1);
+            }
+
+            let infile = Path::new(&args[2]);
+            let outfile = Path::new(&args[3]);
+            let blurring = args[4].parse::<u32>().unwrap_or_else(|_| {
+                eprintln!("ERROR: Invalid blurring value: {}", args[4]);
+                std::process::exit(1);
+            });
+
+            if blurring == 0 {
+                eprintln!("ERROR: Blurring value must be greater than 0");
+                std::process::exit(1);
+            }
+
+            eprintln!("CDP Release 7.1 2016");
+            eprintln!("blur blur infile outfile blurring");
+            eprintln!();
+            eprintln!("TIME-AVERAGE THE SPECTRUM");
+            eprintln!();
+            eprintln!("blurring   is number of windows over which to average the spectrum.");
+            eprintln!();
+            eprintln!("blurring may vary over time.");
+            eprintln!();
+            eprintln!("spectral manipulation beginning");
+
+            let params = BlurParams { windows: blurring };
+            match blur_with_params(infile, outfile, &params, &mut TextProgress::new()) {
+                Ok(()) => {
+                    eprintln!("COMPLETED");
+                    std::process::exit(0);
+                }
+                Err(e) => {
+                    eprintln!("ERROR: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        "avrg" => {
+            eprintln!("CDP Release 7.1 2016");
+            eprintln!("blur avrg    NOT YET IMPLEMENTED");
+            std::process::exit(1);
+        }
+        _ => {
+            eprintln!("CDP Release 7.1 2016");
+            eprintln!("ERROR: Unknown mode: {}", mode);
+            eprintln!();
+            eprintln!("blur     avrg     blur     bounce     ...other modes not implemented...");
+            eprintln!();
+            eprintln!("USAGE: blur NAME");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Run stretch with its command-line syntax, with `args[0]` the program name
+///
+/// Usage errors print the usage text and exit the process.
+pub fn stretch_cli(args: &[String]) {
+    if args.len() < 2 {
+        eprintln!("CDP Release 7.1 2016");
+        eprintln!();
+        eprintln!("STRETCHING A SPECTRAL FILE");
+        eprintln!();
+        eprintln!("USAGE: stretch NAME (mode) infile outfile parameters:");
+        eprintln!();
+        eprintln!("where NAME can be any one of");
+        eprintln!("spectrum      time");
+        eprintln!();
+        eprintln!("Type 'stretch spectrum' for more info on stretch spectrum..ETC.");
+        std::process::exit(1);
+    }
+
+    let mode = &args[1];
+
+    match mode.as_str() {
+        "time" => {
+            if args.len() < 3 {
+                eprintln!("CDP Release 7.1 2016");
+                eprintln!("stretch time 1 infile outfile timestretch");
+                eprintln!("stretch time 2 infile timestretch");
+                eprintln!();
+                eprintln!("TIME-STRETCHING OF INFILE.");
+                eprintln!("In mode 2, program calculates length of output, only.");
+                eprintln!("Timestretch may itself vary over time.");
+                std::process::exit(1);
+            }
+
+            let submode = args[2].parse::<i32>().unwrap_or(0);
+
+            match submode {
+                1 => {
+                    // Mode 1: Actual time stretching
+                    if args.len() < 6 {
+                        eprintln!("CDP Release 7.1 2016");
+                        eprintln!("stretch time 1 infile outfile timestretch");
+                        eprintln!();
+                        eprintln!("TIME-STRETCHING OF INFILE.");
+                        eprintln!("Timestretch may itself vary over time.");
+                        std::process::exit(1);
+                    }
+
+                    let infile = Path::new(&args[3]);
+                    let outfile = Path::new(&args[4]);
+                    let timestretch = args[5].parse::<f64>().unwrap_or_else(|_| {
+                        eprintln!("ERROR: Invalid timestretch value: {}", args[5]);
+                        std::process::exit(1);
+                    });
+
+                    if timestretch <= 0.0 {
+                        eprintln!("ERROR: Timestretch must be greater than 0");
+                        std::process::exit(1);
+                    }
+
+                    eprintln!("CDP Release 7.1 2016");
+                    eprintln!("stretch time 1 infile outfile timestretch");
+                    eprintln!();
+                    eprintln!("TIME-STRETCHING OF INFILE.");
+                    eprintln!("Timestretch may itself vary over time.");
+                    eprintln!();
+                    eprintln!("time-stretching beginning");
+
+                    let params = StretchParams {
+                        factor: timestretch,
+                        ..StretchParams::default()
+                    };
+                    match stretch_time_with_params(
+                        infile,
+                        outfile,
+                        &params,
+                        &mut TextProgress::new(),
+                    ) {
+                        Ok(()) => {
+                            eprintln!("COMPLETED");
+                            std::process::exit(0);
+                        }
+                        Err(e) => {
+                            eprintln!("ERROR: {}", e);
+                            std::process::exit(1);
+                        }
+                    }
+                }
+                2 => {
+                    // Mode 2: Calculate output duration only
+                    if args.len() < 5 {
+                        eprintln!("CDP Release 7.1 2016");
+                        eprintln!("stretch time 2 infile timestretch");
+                        eprintln!();
+                        eprintln!("TIME-STRETCHING OF INFILE.");
+                        eprintln!("In mode 2, program calculates length of output, only.");
+                        eprintln!("Timestretch may itself vary over time.");
+                        std::process::exit(1);
+                    }
+
+                    let infile = Path::new(&args[3]);
+                    let timestretch = args[4].parse::<f64>().unwrap_or_else(|_| {
+                        eprintln!("ERROR: Invalid timestretch value: {}", args[4]);
+                        std::process::exit(1);
+                    });
+
+                    if timestretch <= 0.0 {
+                        eprintln!("ERROR: Timestretch must be greater than 0");
+                        std::process::exit(1);
+                    }
+
+                    eprintln!("CDP Release 7.1 2016");
+                    eprintln!("stretch time 2 infile timestretch");
+                    eprintln!();
+                    eprintln!("TIME-STRETCHING OF INFILE.");
+                    eprintln!("In mode 2, program calculates length of output, only.");
+                    eprintln!("Timestretch may itself vary over time.");
+                    eprintln!();
+
+                    match calculate_output_duration(infile, timestretch) {
+                        Ok(duration) => {
+                            println!("INFO: Length of output file will be {:.3} secs.", duration);
+                            std::process::exit(0);
+                        }
+                        Err(e) => {
+                            eprintln!("ERROR: {}", e);
+                            std::process::exit(1);
+                        }
+                    }
+                }
+                _ => {
+                    eprintln!("ERROR: Invalid mode: {}. Use 1 or 2", submode);
+                    std::process::exit(1);
+                }
+            }
+        }
+        "spectrum" => {
+            eprintln!("CDP Release 7.1 2016");
+            eprintln!("stretch spectrum    NOT YET IMPLEMENTED");
+            std::process::exit(1);
+        }
+        _ => {
+            eprintln!("CDP Release 7.1 2016");
+            eprintln!("ERROR: Unknown mode: {}", mode);
+            eprintln!();
+            eprintln!("STRETCHING A SPECTRAL FILE");
+            eprintln!();
+            eprintln!("USAGE: stretch NAME (mode) infile outfile parameters:");
+            eprintln!();
+            eprintln!("where NAME can be any one of");
+            eprintln!("spectrum      time");
+            eprintln!();
+            eprintln!("Type 'stretch spectrum' for more info on stretch spectrum..ETC.");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Run pitch with its command-line syntax, with `args[0]` the program name
+///
+/// Usage errors print the usage text and exit the process.
+pub fn pitch_cli(args: &[String]) {
+    if args.len() < 4 {
+        eprintln!("CDP-RS Pitch Shift");
+        eprintln!();
+        eprintln!("USAGE: pitch infile outfile shift [options]");
+        eprintln!();
+        eprintln!("  shift: Pitch shift in semitones (12 = octave up, -12 = octave down)");
+        eprintln!("         or as ratio (2.0 = octave up, 0.5 = octave down)");
+        eprintln!();
+        eprintln!("OPTIONS:");
+        eprintln!("  -f    Preserve formants (spectral envelope)");
+        eprintln!();
+        eprintln!("EXAMPLES:");
+        eprintln!("  pitch input.ana output.ana 12      # Octave up");
+        eprintln!("  pitch input.ana output.ana -7      # Perfect fifth down");
+        eprintln!("  pitch input.ana output.ana 2.0     # Octave up (ratio)");
+        eprintln!("  pitch input.ana output.ana 3 -f    # Minor third up, preserve formants");
+        std::process::exit(1);
+    }
+
+    let infile = Path::new(&args[1]);
+    let outfile = Path::new(&args[2]);
+    let shift_str = &args[3];
+
+    // Check for formant preservation flag
+    let preserve_formants = args.len() > 4 && args[4] == "-f";
+
+    // Parse shift value (could be semitones or ratio)
+    let shift_factor = if shift_str.contains('.') {
+        // Treat as ratio
+        shift_str.parse::<f64>().unwrap_or_else(|_| {
+            eprintln!("ERROR: Invalid shift ratio: {}", shift_str);
+            std::process::exit(1);
+        })
+    } else {
+        // Treat as semitones
+        let semitones = shift_str.parse::<f64>().unwrap_or_else(|_| {
+            eprintln!("ERROR: Invalid semitone value: {}", shift_str);
+            std::process::exit(1);
+        });
+        semitones_to_factor(semitones)
+    };
+
+    if shift_factor <= 0.0 || !(0.1..=10.0).contains(&shift_factor) {
+        eprintln!(
+            "ERROR: Shift factor must be between 0.1 and 10.0 (got {})",
+            shift_factor
+        );
+        std::process::exit(1);
+    }
+
+    eprintln!("CDP-RS Pitch Shift");
+    eprintln!("Shifting by factor: {:.3}", shift_factor);
+    if preserve_formants {
+        eprintln!("Preserving formants");
+    }
+    eprintln!("Processing...");
+
+    let params = PitchParams {
+        factor: shift_factor,
+        preserve_formants,
+    };
+    match pitch_shift_with_params(infile, outfile, &params, &mut TextProgress::new()) {
+        Ok(()) => {
+            eprintln!("COMPLETED");
+            std::process::exit(0);
+        }
+        Err(e) => {
+            eprintln!("ERROR: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
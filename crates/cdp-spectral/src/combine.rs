@@ -0,0 +1,251 @@
+//! Operations combining two analysis files
+//!
+//! Each output channel is built from the same channel of two inputs. The
+//! inputs must share sample rate, window size and hop.
+
+use crate::error::{Result, SpectralError};
+use cdp_core::AnaFile;
+use std::path::Path;
+
+/// How inputs of different lengths are combined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum LengthMode {
+    /// Stop at the end of the shorter input
+    Truncate,
+    /// Run for the longer input, looping the shorter one
+    Loop,
+}
+
+/// Impose the amplitudes of one file on the frequencies of another
+///
+/// # Arguments
+/// * `input_a` - Path to the .ana file supplying amplitudes
+/// * `input_b` - Path to the .ana file supplying frequencies
+/// * `output_path` - Path to output .ana file
+/// * `mode` - How to handle inputs of different lengths
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn cross(input_a: &Path, input_b: &Path, output_path: &Path, mode: LengthMode) -> Result<()> {
+    combine_files(input_a, input_b, output_path, mode, cross_frames)
+}
+
+/// Cross-synthesise in-memory spectral frames
+///
+/// Buffer-based counterpart of [`cross`].
+pub fn cross_frames(a: &AnaFile, b: &AnaFile, mode: LengthMode) -> Result<AnaFile> {
+    combine_with(a, b, mode, |(amp_a, _), (_, freq_b)| (amp_a, freq_b))
+}
+
+/// Keep whichever input is louder in each channel
+///
+/// # Arguments
+/// * `input_a` - Path to first .ana file
+/// * `input_b` - Path to second .ana file
+/// * `output_path` - Path to output .ana file
+/// * `mode` - How to handle inputs of different lengths
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn max(input_a: &Path, input_b: &Path, output_path: &Path, mode: LengthMode) -> Result<()> {
+    combine_files(input_a, input_b, output_path, mode, max_frames)
+}
+
+/// Keep the louder channel of in-memory spectral frames
+///
+/// Buffer-based counterpart of [`max`].
+pub fn max_frames(a: &AnaFile, b: &AnaFile, mode: LengthMode) -> Result<AnaFile> {
+    combine_with(a, b, mode, |pa, pb| if pb.0 > pa.0 { pb } else { pa })
+}
+
+/// Average the amplitudes and frequencies of two files
+///
+/// # Arguments
+/// * `input_a` - Path to first .ana file
+/// * `input_b` - Path to second .ana file
+/// * `output_path` - Path to output .ana file
+/// * `mode` - How to handle inputs of different lengths
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn mean(input_a: &Path, input_b: &Path, output_path: &Path, mode: LengthMode) -> Result<()> {
+    combine_files(input_a, input_b, output_path, mode, mean_frames)
+}
+
+/// Average in-memory spectral frames
+///
+/// Buffer-based counterpart of [`mean`].
+pub fn mean_frames(a: &AnaFile, b: &AnaFile, mode: LengthMode) -> Result<AnaFile> {
+    combine_with(a, b, mode, |(amp_a, freq_a), (amp_b, freq_b)| {
+        ((amp_a + amp_b) / 2.0, (freq_a + freq_b) / 2.0)
+    })
+}
+
+/// Subtract the amplitudes of the second file from the first
+///
+/// # Arguments
+/// * `input_a` - Path to .ana file to subtract from
+/// * `input_b` - Path to .ana file to subtract
+/// * `output_path` - Path to output .ana file
+/// * `mode` - How to handle inputs of different lengths
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn diff(input_a: &Path, input_b: &Path, output_path: &Path, mode: LengthMode) -> Result<()> {
+    combine_files(input_a, input_b, output_path, mode, diff_frames)
+}
+
+/// Subtract in-memory spectral frames
+///
+/// Buffer-based counterpart of [`diff`]. Frequencies come from the first
+/// input and amplitudes do not go below zero.
+pub fn diff_frames(a: &AnaFile, b: &AnaFile, mode: LengthMode) -> Result<AnaFile> {
+    combine_with(a, b, mode, |(amp_a, freq_a), (amp_b, _)| {
+        ((amp_a - amp_b).max(0.0), freq_a)
+    })
+}
+
+/// Add the amplitudes of two files
+///
+/// # Arguments
+/// * `input_a` - Path to first .ana file
+/// * `input_b` - Path to second .ana file
+/// * `output_path` - Path to output .ana file
+/// * `mode` - How to handle inputs of different lengths
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn sum(input_a: &Path, input_b: &Path, output_path: &Path, mode: LengthMode) -> Result<()> {
+    combine_files(input_a, input_b, output_path, mode, sum_frames)
+}
+
+/// Add in-memory spectral frames
+///
+/// Buffer-based counterpart of [`sum`]. Each channel takes the frequency
+/// of the louder input.
+pub fn sum_frames(a: &AnaFile, b: &AnaFile, mode: LengthMode) -> Result<AnaFile> {
+    combine_with(a, b, mode, |(amp_a, freq_a), (amp_b, freq_b)| {
+        let freq = if amp_b > amp_a { freq_b } else { freq_a };
+        (amp_a + amp_b, freq)
+    })
+}
+
+fn combine_files(
+    input_a: &Path,
+    input_b: &Path,
+    output_path: &Path,
+    mode: LengthMode,
+    process: fn(&AnaFile, &AnaFile, LengthMode) -> Result<AnaFile>,
+) -> Result<()> {
+    let a = AnaFile::load(input_a)?;
+    let b = AnaFile::load(input_b)?;
+    process(&a, &b, mode)?.save(output_path)?;
+    Ok(())
+}
+
+/// Combine matching channels of two inputs as (amplitude, frequency) pairs
+fn combine_with(
+    a: &AnaFile,
+    b: &AnaFile,
+    mode: LengthMode,
+    combine: impl Fn((f32, f32), (f32, f32)) -> (f32, f32),
+) -> Result<AnaFile> {
+    check_compatible(a, b)?;
+
+    let num_windows = match mode {
+        LengthMode::Truncate => a.num_frames().min(b.num_frames()),
+        LengthMode::Loop => a.num_frames().max(b.num_frames()),
+    };
+
+    let mut output = AnaFile::empty(a.header.clone())?;
+    let mut out_frame = vec![0.0f32; a.frame_len()];
+    for window_idx in 0..num_windows {
+        let frame_a = &a.data[(window_idx % a.num_frames()) * a.frame_len()..][..a.frame_len()];
+        let frame_b = &b.data[(window_idx % b.num_frames()) * b.frame_len()..][..b.frame_len()];
+
+        for ((out, pa), pb) in out_frame
+            .chunks_exact_mut(2)
+            .zip(frame_a.chunks_exact(2))
+            .zip(frame_b.chunks_exact(2))
+        {
+            let (amp, freq) = combine((pa[0], pa[1]), (pb[0], pb[1]));
+            out[0] = amp;
+            out[1] = freq;
+        }
+        output.push_frame(&out_frame)?;
+    }
+
+    Ok(output)
+}
+
+/// Check that two inputs hold data with the same analysis settings
+pub(crate) fn check_compatible(a: &AnaFile, b: &AnaFile) -> Result<()> {
+    let (ha, hb) = (&a.header, &b.header);
+    if ha.sample_rate != hb.sample_rate
+        || ha.fft_size() != hb.fft_size()
+        || ha.hop_size() != hb.hop_size()
+    {
+        return Err(SpectralError::InvalidInput(
+            "Input files must have the same sample rate, window size and hop".to_string(),
+        ));
+    }
+    if a.num_frames() == 0 || b.num_frames() == 0 {
+        return Err(SpectralError::InvalidInput(
+            "Input file has no spectral data".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cdp_core::AnaHeader;
+
+    #[test]
+    fn test_combine_validation() {
+        let a = AnaFile::new(AnaHeader::with_hop(800, 2, 800), vec![1.0, 0.0, 1.0, 400.0]).unwrap();
+        let b = AnaFile::new(AnaHeader::with_hop(400, 2, 400), vec![1.0, 0.0, 1.0, 200.0]).unwrap();
+        assert!(matches!(
+            cross_frames(&a, &b, LengthMode::Truncate),
+            Err(SpectralError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_combine_frames() {
+        let header = AnaHeader::with_hop(800, 2, 800);
+        let a = AnaFile::new(
+            header.clone(),
+            vec![1.0, 10.0, 0.2, 400.0, 2.0, 20.0, 0.4, 380.0],
+        )
+        .unwrap();
+        let b = AnaFile::new(header, vec![0.5, 30.0, 0.6, 300.0]).unwrap();
+
+        let output = cross_frames(&a, &b, LengthMode::Truncate).unwrap();
+        assert_eq!(output.data, vec![1.0, 30.0, 0.2, 300.0]);
+
+        // The shorter file loops to cover the longer one
+        let output = max_frames(&a, &b, LengthMode::Loop).unwrap();
+        assert_eq!(
+            output.data,
+            vec![1.0, 10.0, 0.6, 300.0, 2.0, 20.0, 0.6, 300.0]
+        );
+
+        let output = mean_frames(&a, &b, LengthMode::Truncate).unwrap();
+        assert_eq!(output.data, vec![0.75, 20.0, 0.4, 350.0]);
+
+        let output = diff_frames(&a, &b, LengthMode::Truncate).unwrap();
+        assert_eq!(output.data, vec![0.5, 10.0, 0.0, 400.0]);
+
+        let output = sum_frames(&a, &b, LengthMode::Truncate).unwrap();
+        assert_eq!(output.data, vec![1.5, 10.0, 0.8, 300.0]);
+    }
+}
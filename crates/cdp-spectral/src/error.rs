@@ -1,6 +1,9 @@
 //! Error types for spectral processing
 
-use std::io;
+use cdp_core::fs::File;
+use cdp_core::{AnaFrameWriter, AnaHeader};
+use std::io::{self, BufWriter};
+use std::path::Path;
 use thiserror::Error;
 
 /// Spectral processing errors
@@ -14,9 +17,13 @@ pub enum SpectralError {
     #[error("IO error: {0}")]
     Io(#[from] io::Error),
 
-    /// Hound WAV file error
+    /// WAV file error
     #[error("WAV file error: {0}")]
-    Hound(#[from] hound::Error),
+    Housekeep(#[from] cdp_housekeep::HousekeepError),
+
+    /// JSON serialization error
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
 
     /// Core DSP error
     #[error("Core DSP error: {0}")]
@@ -25,3 +32,31 @@ pub enum SpectralError {
 
 /// Result type for spectral operations
 pub type Result<T> = std::result::Result<T, SpectralError>;
+
+/// Check a value against its CDP range, reporting failures as invalid input
+pub(crate) fn check_param(param: cdp_core::Param, value: f64) -> Result<()> {
+    cdp_core::validate(param, value)
+        .map(|_| ())
+        .map_err(|e| SpectralError::InvalidInput(e.to_string()))
+}
+
+/// Create a .ana file, fill it through `stream` and finalize it
+///
+/// Any failure once the file exists, including cancellation, removes the
+/// partial output before the error is passed on.
+pub(crate) fn write_frames(
+    output_path: &Path,
+    header: AnaHeader,
+    stream: impl FnOnce(&mut AnaFrameWriter<BufWriter<File>>) -> Result<()>,
+) -> Result<()> {
+    let mut writer = AnaFrameWriter::create(output_path, header)?;
+    let result = stream(&mut writer).and_then(|()| {
+        writer.finalize()?;
+        Ok(())
+    });
+    if result.is_err() {
+        // The original failure is what the caller needs to hear about
+        let _ = cdp_core::fs::remove_file(output_path);
+    }
+    result
+}
@@ -0,0 +1,433 @@
+//! Spectral focus operations
+//!
+//! Accumulates each channel's loudest data over time, sustaining partials
+//! until louder data arrives in the same channel. Also exaggerates or
+//! flattens the spectral contour of each window, folds partials into a
+//! frequency range and holds the spectrum in steps.
+
+use crate::error::{Result, SpectralError};
+use crate::strange::move_partials;
+use cdp_core::{AnaFile, Breakpoints};
+use std::path::Path;
+
+/// Sustain each channel's data until louder data appears in that channel
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `decay` - Fraction of amplitude sustained data keeps per second (0-1, 1 = no decay)
+/// * `glissando` - Pitch drift of sustained data in octaves per second
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn accu(input_path: &Path, output_path: &Path, decay: f64, glissando: f64) -> Result<()> {
+    check_accu_params(decay, glissando)?;
+    let input = AnaFile::load(input_path)?;
+    accu_frames(&input, decay, glissando)?.save(output_path)?;
+    Ok(())
+}
+
+/// Accumulate in-memory spectral frames
+///
+/// Buffer-based counterpart of [`accu`].
+pub fn accu_frames(input: &AnaFile, decay: f64, glissando: f64) -> Result<AnaFile> {
+    accumulate(input, decay, glissando, 0)
+}
+
+/// Accumulate with each newly captured partial duplicated at higher octaves
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `decay` - Fraction of amplitude sustained data keeps per second (0-1, 1 = no decay)
+/// * `glissando` - Pitch drift of sustained data in octaves per second
+/// * `octaves` - Number of octave duplicates, each at half the amplitude of the one below
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn superaccu(
+    input_path: &Path,
+    output_path: &Path,
+    decay: f64,
+    glissando: f64,
+    octaves: u32,
+) -> Result<()> {
+    check_accu_params(decay, glissando)?;
+    let input = AnaFile::load(input_path)?;
+    superaccu_frames(&input, decay, glissando, octaves)?.save(output_path)?;
+    Ok(())
+}
+
+/// Accumulate in-memory spectral frames with octave duplication
+///
+/// Buffer-based counterpart of [`superaccu`].
+pub fn superaccu_frames(
+    input: &AnaFile,
+    decay: f64,
+    glissando: f64,
+    octaves: u32,
+) -> Result<AnaFile> {
+    accumulate(input, decay, glissando, octaves)
+}
+
+fn accumulate(input: &AnaFile, decay: f64, glissando: f64, octaves: u32) -> Result<AnaFile> {
+    check_accu_params(decay, glissando)?;
+    let header = &input.header;
+
+    if input.num_frames() == 0 {
+        return Err(SpectralError::InvalidInput(
+            "Input file has no spectral data".to_string(),
+        ));
+    }
+
+    // Per-window decay and pitch drift of sustained data
+    let frame_rate = header.frame_rate() as f64;
+    let decay_per_window = decay.powf(1.0 / frame_rate) as f32;
+    let gliss_per_window = 2.0f64.powf(glissando / frame_rate) as f32;
+    let nyquist = header.sample_rate as f32 / 2.0;
+    let bin_width = header.bin_width();
+
+    // Sustained (amplitude, frequency) per channel, starting silent at the bin centre
+    let mut store: Vec<(f32, f32)> = (0..header.num_bins())
+        .map(|bin| (0.0, bin as f32 * bin_width))
+        .collect();
+    let mut output = AnaFile::empty(header.clone())?;
+    let mut out_frame = vec![0.0f32; input.frame_len()];
+
+    for (window_idx, frame) in input.frames().enumerate() {
+        if window_idx > 0 {
+            for (amp, freq) in store.iter_mut() {
+                *amp *= decay_per_window;
+                *freq *= gliss_per_window;
+                if freq.abs() > nyquist {
+                    *amp = 0.0;
+                }
+            }
+        }
+
+        for bin in 0..frame.num_bins() {
+            let (amp, freq) = (frame.amplitude(bin), frame.frequency(bin));
+            if amp <= store[bin].0 {
+                continue;
+            }
+            store[bin] = (amp, freq);
+
+            // Duplicate the new partial at higher octaves
+            for octave in 1..=octaves {
+                let dup_freq = freq * 2.0f32.powi(octave as i32);
+                let dup_amp = amp * 0.5f32.powi(octave as i32);
+                let dup_bin = (dup_freq / bin_width).round() as usize;
+                if dup_bin >= store.len() {
+                    break;
+                }
+                if dup_amp > store[dup_bin].0 {
+                    store[dup_bin] = (dup_amp, dup_freq);
+                }
+            }
+        }
+
+        for (pair, &(amp, freq)) in out_frame.chunks_exact_mut(2).zip(&store) {
+            pair[0] = amp;
+            pair[1] = freq;
+        }
+        output.push_frame(&out_frame)?;
+    }
+
+    Ok(output)
+}
+
+/// Exaggerate or flatten the spectral contour, like CDP's focus exag
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `exponent` - Power applied to normalized amplitudes: above 1 exaggerates
+///   peaks, below 1 flattens the contour (> 0)
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn exag(input_path: &Path, output_path: &Path, exponent: f64) -> Result<()> {
+    check_exponents(&[(0.0, exponent)])?;
+    let input = AnaFile::load(input_path)?;
+    exag_frames(&input, exponent)?.save(output_path)?;
+    Ok(())
+}
+
+/// Exaggerate the spectral contour of in-memory spectral frames
+///
+/// Buffer-based counterpart of [`exag`]. Amplitudes are divided by the
+/// window's loudest, raised to `exponent`, and rescaled so the window keeps
+/// its total amplitude.
+pub fn exag_frames(input: &AnaFile, exponent: f64) -> Result<AnaFile> {
+    exag_varying_frames(input, &[(0.0, exponent)])
+}
+
+/// Exaggerate the spectral contour with an exponent that changes over time
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `exponents` - (time, exponent) breakpoints, interpolated linearly
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn exag_varying(input_path: &Path, output_path: &Path, exponents: &[(f64, f64)]) -> Result<()> {
+    check_exponents(exponents)?;
+    let input = AnaFile::load(input_path)?;
+    exag_varying_frames(&input, exponents)?.save(output_path)?;
+    Ok(())
+}
+
+/// Exaggerate the spectral contour of in-memory spectral frames over time
+///
+/// Buffer-based counterpart of [`exag_varying`].
+pub fn exag_varying_frames(input: &AnaFile, exponents: &[(f64, f64)]) -> Result<AnaFile> {
+    check_exponents(exponents)?;
+    let exponents = Breakpoints::new(exponents.to_vec())?;
+    let time_per_window = 1.0 / input.header.frame_rate() as f64;
+    let mut output = input.clone();
+
+    for (window_idx, frame) in output.frames_mut().enumerate() {
+        let exponent = exponents.value_at(window_idx as f64 * time_per_window) as f32;
+        let peak = frame.iter().step_by(2).fold(0.0f32, |max, &a| max.max(a));
+        let total: f32 = frame.iter().step_by(2).sum();
+        if peak <= 0.0 {
+            continue;
+        }
+
+        for amp in frame.iter_mut().step_by(2) {
+            *amp = (*amp / peak).powf(exponent);
+        }
+        let shaped_total: f32 = frame.iter().step_by(2).sum();
+        let scale = total / shaped_total;
+        for amp in frame.iter_mut().step_by(2) {
+            *amp *= scale;
+        }
+    }
+
+    Ok(output)
+}
+
+/// Octave-transpose partials into a frequency range, like CDP's focus fold
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `low` - Bottom of the range in Hz
+/// * `high` - Top of the range in Hz, at least an octave above `low`
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn fold(input_path: &Path, output_path: &Path, low: f64, high: f64) -> Result<()> {
+    check_fold_range(low, high)?;
+    let input = AnaFile::load(input_path)?;
+    fold_frames(&input, low, high)?.save(output_path)?;
+    Ok(())
+}
+
+/// Fold the partials of in-memory spectral frames into a frequency range
+///
+/// Buffer-based counterpart of [`fold`]. Partials above the range move down
+/// by octaves, and those below it up, until they lie inside it. Each lands
+/// in the channel nearest its new frequency, the loudest winning where
+/// several meet. Partials at 0 Hz have no octaves and are dropped.
+pub fn fold_frames(input: &AnaFile, low: f64, high: f64) -> Result<AnaFile> {
+    check_fold_range(low, high)?;
+    let (low, high) = (low as f32, high as f32);
+    let mut output = input.clone();
+
+    for (frame, source) in output.frames_mut().zip(input.frames()) {
+        move_partials(source.values(), frame, input.header.bin_width(), |freq| {
+            let mut folded = freq.abs();
+            if folded == 0.0 {
+                return None;
+            }
+            while folded > high {
+                folded /= 2.0;
+            }
+            while folded < low {
+                folded *= 2.0;
+            }
+            Some(folded)
+        });
+    }
+
+    Ok(output)
+}
+
+/// Hold the spectrum in steps, sampling it once per step
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `step_duration` - Length of each step in seconds
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn step(input_path: &Path, output_path: &Path, step_duration: f64) -> Result<()> {
+    check_step_duration(step_duration)?;
+    let input = AnaFile::load(input_path)?;
+    step_frames(&input, step_duration)?.save(output_path)?;
+    Ok(())
+}
+
+/// Hold in-memory spectral frames in steps
+///
+/// Buffer-based counterpart of [`step`]. The first window of each step
+/// replaces the rest of the step, so the output has the same length as the
+/// input. Steps are at least one window long.
+pub fn step_frames(input: &AnaFile, step_duration: f64) -> Result<AnaFile> {
+    check_step_duration(step_duration)?;
+    let step_windows = ((step_duration * input.header.frame_rate() as f64).round() as usize).max(1);
+    let mut output = input.clone();
+
+    for (window_idx, frame) in output.frames_mut().enumerate() {
+        let held = input
+            .frame(window_idx / step_windows * step_windows)
+            .unwrap();
+        frame.copy_from_slice(held.values());
+    }
+
+    Ok(output)
+}
+
+fn check_accu_params(decay: f64, glissando: f64) -> Result<()> {
+    if !(decay > 0.0 && decay <= 1.0) {
+        return Err(SpectralError::InvalidInput(
+            "Decay must be greater than 0 and at most 1".to_string(),
+        ));
+    }
+    if !(-11.7..=11.7).contains(&glissando) {
+        return Err(SpectralError::InvalidInput(
+            "Glissando must be between -11.7 and 11.7 octaves per second".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn check_exponents(exponents: &[(f64, f64)]) -> Result<()> {
+    if exponents.is_empty() {
+        return Err(SpectralError::InvalidInput(
+            "Exaggeration exponents must not be empty".to_string(),
+        ));
+    }
+    if exponents
+        .iter()
+        .any(|&(_, exponent)| !(exponent > 0.0 && exponent.is_finite()))
+    {
+        return Err(SpectralError::InvalidInput(
+            "Exaggeration exponent must be greater than 0".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn check_fold_range(low: f64, high: f64) -> Result<()> {
+    if !(low > 0.0 && high >= low * 2.0 && high.is_finite()) {
+        return Err(SpectralError::InvalidInput(
+            "Fold range must start above 0 Hz and span at least an octave".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn check_step_duration(step_duration: f64) -> Result<()> {
+    if !(step_duration > 0.0 && step_duration.is_finite()) {
+        return Err(SpectralError::InvalidInput(
+            "Step duration must be greater than 0".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cdp_core::AnaHeader;
+
+    #[test]
+    fn test_accu_validation() {
+        let input = Path::new("test.ana");
+        let output = Path::new("out.ana");
+
+        assert!(accu(input, output, 0.0, 0.0).is_err());
+        assert!(accu(input, output, 1.5, 0.0).is_err());
+        assert!(accu(input, output, 1.0, 20.0).is_err());
+    }
+
+    #[test]
+    fn test_accu_sustains_and_duplicates() {
+        // Five bins 100Hz apart, one window per second
+        let header = AnaHeader::with_hop(800, 8, 800);
+        let mut input = AnaFile::empty(header).unwrap();
+        input
+            .push_frame(&[0.0, 0.0, 1.0, 100.0, 0.0, 200.0, 0.0, 300.0, 0.0, 400.0])
+            .unwrap();
+        input
+            .push_frame(&[0.0, 0.0, 0.2, 100.0, 0.0, 200.0, 0.0, 300.0, 0.0, 400.0])
+            .unwrap();
+
+        // Bin 1 holds its first amplitude, halving each second
+        let output = accu_frames(&input, 0.5, 0.0).unwrap();
+        let second = output.frame(1).unwrap();
+        assert_eq!(second.pair(1), (0.5, 100.0));
+
+        // One octave up lands in bin 2 at half amplitude
+        let output = superaccu_frames(&input, 1.0, 0.0, 1).unwrap();
+        let first = output.frame(0).unwrap();
+        assert_eq!(first.pair(2), (0.5, 200.0));
+        assert_eq!(first.pair(4), (0.0, 400.0));
+    }
+
+    #[test]
+    fn test_exag_frames() {
+        let header = AnaHeader::with_hop(800, 8, 800);
+        let window = [0.0, 0.0, 1.0, 100.0, 0.5, 200.0, 0.5, 300.0, 0.0, 400.0];
+        let input = AnaFile::new(header, [window; 2].concat()).unwrap();
+        assert!(exag_frames(&input, 0.0).is_err());
+
+        // Squaring the contour keeps the total of 2 across 1 : 0.25 : 0.25
+        let output = exag_frames(&input, 2.0).unwrap();
+        let amps: Vec<f32> = (0..5)
+            .map(|b| output.frame(0).unwrap().amplitude(b))
+            .collect();
+        assert_eq!(amps, vec![0.0, 4.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0, 0.0]);
+
+        // An exponent of 1 leaves the second window unchanged
+        let output = exag_varying_frames(&input, &[(0.0, 2.0), (1.0, 1.0)]).unwrap();
+        assert_eq!(output.frame(1).unwrap().values(), &window);
+    }
+
+    #[test]
+    fn test_fold_and_step_frames() {
+        // Five bins 100Hz apart, one window per second
+        let header = AnaHeader::with_hop(800, 8, 800);
+        let data = (0..3)
+            .flat_map(|w| {
+                [
+                    0.5, 0.0, 1.0, 100.0, 0.0, 200.0, 0.0, 300.0, w as f32, 400.0,
+                ]
+            })
+            .collect();
+        let input = AnaFile::new(header, data).unwrap();
+        assert!(fold_frames(&input, 150.0, 250.0).is_err());
+        assert!(step_frames(&input, 0.0).is_err());
+
+        // 100Hz folds up and 400Hz down to 200Hz, where the louder one wins
+        let output = fold_frames(&input, 150.0, 300.0).unwrap();
+        let frame = output.frame(2).unwrap();
+        assert_eq!(frame.pair(2), (2.0, 200.0));
+        assert_eq!(frame.pair(0), (0.0, 0.0));
+        assert_eq!(frame.pair(1), (0.0, 100.0));
+
+        let output = step_frames(&input, 2.0).unwrap();
+        let amps: Vec<f32> = output.frames().map(|f| f.amplitude(4)).collect();
+        assert_eq!(amps, vec![0.0, 0.0, 2.0]);
+    }
+}
@@ -0,0 +1,224 @@
+//! Spectral envelope (formant) operations
+//!
+//! Extracts the spectral envelope of each analysis window, stores it in a
+//! formant file, and imposes a stored envelope on another sound.
+//!
+//! Formant files are 32-bit float WAV files with one channel per envelope
+//! band and one sample frame per analysis window. The sample rate field
+//! holds the sample rate of the analysed sound.
+
+use crate::error::{Result, SpectralError};
+use cdp_core::{AnaFile, AudioBuffer};
+use cdp_housekeep::wav_cdp::{self, SampleType};
+use std::path::Path;
+
+/// Spectral envelopes of successive analysis windows
+///
+/// Bands divide 0 Hz to Nyquist into equal widths; each value is the
+/// loudest amplitude found in that band.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormantEnvelope {
+    /// Sample rate of the analysed sound
+    pub sample_rate: u32,
+    /// Number of envelope bands per window
+    pub bands: usize,
+    /// Envelope values, `bands` per window
+    pub data: Vec<f32>,
+}
+
+impl FormantEnvelope {
+    /// Number of windows held
+    pub fn num_windows(&self) -> usize {
+        self.data.len() / self.bands
+    }
+
+    /// Envelope values of one window
+    pub fn window(&self, index: usize) -> Option<&[f32]> {
+        self.data.chunks_exact(self.bands).nth(index)
+    }
+
+    /// Load a formant file
+    pub fn load(path: &Path) -> Result<Self> {
+        let (audio, sample_type) = wav_cdp::read_audio_buffer(path)?;
+        if sample_type != SampleType::Float32 {
+            return Err(SpectralError::InvalidInput(
+                "Formant file must hold 32-bit float data".to_string(),
+            ));
+        }
+        Ok(Self {
+            sample_rate: audio.sample_rate,
+            bands: audio.channels as usize,
+            data: audio.data,
+        })
+    }
+
+    /// Save as a formant file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let audio = AudioBuffer::new(self.sample_rate, self.bands as u16, self.data.clone())?;
+        wav_cdp::write_audio_buffer(path, &audio, SampleType::Float32)?;
+        Ok(())
+    }
+
+    /// Envelope level at `freq` in one window, interpolated between band centres
+    fn level(&self, window: &[f32], freq: f32) -> f32 {
+        let band_width = self.sample_rate as f32 / 2.0 / self.bands as f32;
+        let position = (freq.abs() / band_width - 0.5).max(0.0);
+        let lower = (position as usize).min(self.bands - 1);
+        let upper = (lower + 1).min(self.bands - 1);
+        let frac = (position - lower as f32).min(1.0);
+        window[lower] + (window[upper] - window[lower]) * frac
+    }
+}
+
+/// Extract the spectral envelope of an analysis file to a formant file
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output formant file
+/// * `bands` - Number of envelope bands between 0 Hz and Nyquist
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn get_formants(input_path: &Path, output_path: &Path, bands: usize) -> Result<()> {
+    let input = AnaFile::load(input_path)?;
+    get_formants_frames(&input, bands)?.save(output_path)?;
+    Ok(())
+}
+
+/// Extract the spectral envelope of in-memory spectral frames
+///
+/// Buffer-based counterpart of [`get_formants`].
+pub fn get_formants_frames(input: &AnaFile, bands: usize) -> Result<FormantEnvelope> {
+    let num_bins = input.header.num_bins();
+    if !(2..=num_bins).contains(&bands) {
+        return Err(SpectralError::InvalidInput(format!(
+            "Formant bands must be between 2 and {}",
+            num_bins
+        )));
+    }
+    if input.num_frames() == 0 {
+        return Err(SpectralError::InvalidInput(
+            "Input file has no spectral data".to_string(),
+        ));
+    }
+
+    let band_width = input.header.sample_rate as f32 / 2.0 / bands as f32;
+    let mut data = vec![0.0f32; input.num_frames() * bands];
+    for (frame, envelope) in input.frames().zip(data.chunks_exact_mut(bands)) {
+        for bin in 0..frame.num_bins() {
+            let (amp, freq) = frame.pair(bin);
+            let band = ((freq.abs() / band_width) as usize).min(bands - 1);
+            envelope[band] = envelope[band].max(amp);
+        }
+    }
+
+    Ok(FormantEnvelope {
+        sample_rate: input.header.sample_rate,
+        bands,
+        data,
+    })
+}
+
+/// Impose a stored spectral envelope on an analysis file
+///
+/// The sound's own envelope is replaced by the stored one, as in formant
+/// vocoding. If the formant file is shorter than the sound, its last window
+/// is held.
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `formant_path` - Path to formant file to impose
+/// * `output_path` - Path to output .ana file
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn put_formants(input_path: &Path, formant_path: &Path, output_path: &Path) -> Result<()> {
+    let input = AnaFile::load(input_path)?;
+    let envelope = FormantEnvelope::load(formant_path)?;
+    put_formants_frames(&input, &envelope)?.save(output_path)?;
+    Ok(())
+}
+
+/// Impose a spectral envelope on in-memory spectral frames
+///
+/// Buffer-based counterpart of [`put_formants`].
+pub fn put_formants_frames(input: &AnaFile, envelope: &FormantEnvelope) -> Result<AnaFile> {
+    if envelope.sample_rate != input.header.sample_rate {
+        return Err(SpectralError::InvalidInput(
+            "Formant file must have the same sample rate as the input".to_string(),
+        ));
+    }
+    if envelope.bands == 0 || envelope.num_windows() == 0 {
+        return Err(SpectralError::InvalidInput(
+            "Formant file has no envelope data".to_string(),
+        ));
+    }
+
+    let own = get_formants_frames(input, envelope.bands)?;
+    let mut output = input.clone();
+    for (window_idx, frame) in output.frames_mut().enumerate() {
+        let own_window = own.window(window_idx).unwrap();
+        let new_window = envelope
+            .window(window_idx.min(envelope.num_windows() - 1))
+            .unwrap();
+
+        for pair in frame.chunks_exact_mut(2) {
+            let own_level = own.level(own_window, pair[1]);
+            pair[0] = if own_level > 0.0 {
+                pair[0] * envelope.level(new_window, pair[1]) / own_level
+            } else {
+                0.0
+            };
+        }
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cdp_core::AnaHeader;
+
+    #[test]
+    fn test_formant_file_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("env.for");
+        let envelope = FormantEnvelope {
+            sample_rate: 44100,
+            bands: 64,
+            data: (0..128).map(|i| i as f32 / 128.0).collect(),
+        };
+        envelope.save(&path).unwrap();
+        assert_eq!(FormantEnvelope::load(&path).unwrap(), envelope);
+    }
+
+    #[test]
+    fn test_get_and_put_formants_frames() {
+        // Five bins 100Hz apart; two bands of 200Hz
+        let header = AnaHeader::with_hop(800, 8, 800);
+        let flat = AnaFile::new(
+            header.clone(),
+            vec![1.0, 0.0, 1.0, 100.0, 1.0, 200.0, 1.0, 300.0, 1.0, 400.0],
+        )
+        .unwrap();
+        let shaped = AnaFile::new(
+            header,
+            vec![0.8, 0.0, 0.4, 100.0, 0.2, 200.0, 0.1, 300.0, 0.1, 400.0],
+        )
+        .unwrap();
+
+        let envelope = get_formants_frames(&shaped, 2).unwrap();
+        assert_eq!(envelope.window(0).unwrap(), &[0.8, 0.2]);
+        assert!(get_formants_frames(&shaped, 1).is_err());
+
+        // Band centres at 100Hz and 300Hz take the envelope values exactly
+        let output = put_formants_frames(&flat, &envelope).unwrap();
+        let frame = output.frame(0).unwrap();
+        assert_eq!(frame.pair(1), (0.8, 100.0));
+        assert_eq!(frame.pair(3), (0.2, 300.0));
+        assert!((frame.amplitude(2) - 0.5).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,207 @@
+//! Spectral freezing operations
+//!
+//! Holds the spectrum at chosen times, sustaining a single window in place
+//! of the windows that follow it, or grabs a single window as a frozen
+//! spectrum of its own.
+
+use crate::error::{Result, SpectralError};
+use cdp_core::AnaFile;
+use std::path::Path;
+
+/// Freeze the spectrum at specified times
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `holds` - (time, hold_duration) pairs in seconds
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn freeze(input_path: &Path, output_path: &Path, holds: &[(f64, f64)]) -> Result<()> {
+    check_holds(holds)?;
+    let input = AnaFile::load(input_path)?;
+    freeze_frames(&input, holds)?.save(output_path)?;
+    Ok(())
+}
+
+/// Freeze in-memory spectral frames at specified times
+///
+/// Buffer-based counterpart of [`freeze`]. The window nearest each hold
+/// time replaces the windows up to the end of the hold, so the output has
+/// the same length as the input. Where holds overlap the later one wins.
+pub fn freeze_frames(input: &AnaFile, holds: &[(f64, f64)]) -> Result<AnaFile> {
+    check_holds(holds)?;
+    let num_windows = input.num_frames();
+
+    if num_windows == 0 {
+        return Err(SpectralError::InvalidInput(
+            "Input file has no spectral data".to_string(),
+        ));
+    }
+
+    // Convert hold times to window ranges
+    let frame_rate = input.header.frame_rate() as f64;
+    let mut ranges: Vec<(usize, usize)> = holds
+        .iter()
+        .map(|&(time, duration)| {
+            let start = (time * frame_rate).round() as usize;
+            let length = ((duration * frame_rate).round() as usize).max(1);
+            (start, start + length)
+        })
+        .filter(|&(start, _)| start < num_windows)
+        .collect();
+    ranges.sort_by_key(|&(start, _)| start);
+
+    let mut output = input.clone();
+    let window_size = input.frame_len();
+    for (window_idx, frame) in output.frames_mut().enumerate() {
+        // Latest hold covering this window, if any
+        let held = ranges
+            .iter()
+            .rev()
+            .find(|&&(start, end)| (start..end).contains(&window_idx));
+
+        if let Some(&(start, _)) = held {
+            let source = &input.data[start * window_size..(start + 1) * window_size];
+            frame.copy_from_slice(source);
+        }
+    }
+
+    Ok(output)
+}
+
+/// Grab the window at a given time as a frozen spectrum, like CDP's spec grab
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `time` - Time in seconds of the window to grab
+/// * `duration` - Seconds to repeat the window for, or `None` for one window
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn grab(input_path: &Path, output_path: &Path, time: f64, duration: Option<f64>) -> Result<()> {
+    check_grab(time, duration)?;
+    let input = AnaFile::load(input_path)?;
+    grab_frames(&input, time, duration)?.save(output_path)?;
+    Ok(())
+}
+
+/// Grab one window of in-memory spectral frames
+///
+/// Buffer-based counterpart of [`grab`]. The window nearest `time` is used;
+/// times past the end take the last window.
+pub fn grab_frames(input: &AnaFile, time: f64, duration: Option<f64>) -> Result<AnaFile> {
+    check_grab(time, duration)?;
+    if input.num_frames() == 0 {
+        return Err(SpectralError::InvalidInput(
+            "Input file has no spectral data".to_string(),
+        ));
+    }
+
+    let frame_rate = input.header.frame_rate() as f64;
+    let window_idx = ((time * frame_rate).round() as usize).min(input.num_frames() - 1);
+    let repeats = duration.map_or(1, |duration| {
+        ((duration * frame_rate).round() as usize).max(1)
+    });
+
+    let frame = input.frame(window_idx).unwrap();
+    let mut output = AnaFile::empty(input.header.clone())?;
+    for _ in 0..repeats {
+        output.push_frame(frame.values())?;
+    }
+
+    Ok(output)
+}
+
+fn check_holds(holds: &[(f64, f64)]) -> Result<()> {
+    if holds.is_empty() {
+        return Err(SpectralError::InvalidInput(
+            "Freeze holds must not be empty".to_string(),
+        ));
+    }
+
+    for &(time, duration) in holds {
+        if !(time >= 0.0 && time.is_finite()) {
+            return Err(SpectralError::InvalidInput(
+                "Freeze times must be zero or greater".to_string(),
+            ));
+        }
+        if !(duration > 0.0 && duration.is_finite()) {
+            return Err(SpectralError::InvalidInput(
+                "Hold durations must be greater than 0".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn check_grab(time: f64, duration: Option<f64>) -> Result<()> {
+    if !(time >= 0.0 && time.is_finite()) {
+        return Err(SpectralError::InvalidInput(
+            "Grab time must be zero or greater".to_string(),
+        ));
+    }
+    if duration.is_some_and(|duration| !(duration > 0.0 && duration.is_finite())) {
+        return Err(SpectralError::InvalidInput(
+            "Grab duration must be greater than 0".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cdp_core::AnaHeader;
+
+    #[test]
+    fn test_freeze_validation() {
+        let input = Path::new("test.ana");
+        let output = Path::new("out.ana");
+
+        assert!(matches!(
+            freeze(input, output, &[]),
+            Err(SpectralError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            freeze(input, output, &[(-1.0, 1.0)]),
+            Err(SpectralError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            freeze(input, output, &[(0.0, 0.0)]),
+            Err(SpectralError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_freeze_frames() {
+        // One window per second: sample rate 4, hop 4
+        let header = AnaHeader::with_hop(4, 2, 4);
+        let data = (0..6).flat_map(|w| [w as f32, 100.0, 0.0, 200.0]).collect();
+        let input = AnaFile::new(header, data).unwrap();
+
+        let output = freeze_frames(&input, &[(1.0, 2.0), (4.0, 10.0)]).unwrap();
+        let amps: Vec<f32> = output.frames().map(|f| f.amplitude(0)).collect();
+        assert_eq!(amps, vec![0.0, 1.0, 1.0, 3.0, 4.0, 4.0]);
+    }
+
+    #[test]
+    fn test_grab_frames() {
+        let header = AnaHeader::with_hop(4, 2, 4);
+        let data = (0..6).flat_map(|w| [w as f32, 100.0, 0.0, 200.0]).collect();
+        let input = AnaFile::new(header, data).unwrap();
+        assert!(grab_frames(&input, -1.0, None).is_err());
+        assert!(grab_frames(&input, 1.0, Some(0.0)).is_err());
+
+        let output = grab_frames(&input, 2.4, None).unwrap();
+        assert_eq!(output.num_frames(), 1);
+        assert_eq!(output.frame(0).unwrap().values(), &[2.0, 100.0, 0.0, 200.0]);
+
+        let output = grab_frames(&input, 9.0, Some(3.0)).unwrap();
+        let amps: Vec<f32> = output.frames().map(|f| f.amplitude(0)).collect();
+        assert_eq!(amps, vec![5.0, 5.0, 5.0]);
+    }
+}
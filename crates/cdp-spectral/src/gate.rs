@@ -0,0 +1,199 @@
+//! Spectral gating operations
+//!
+//! Removes quiet spectral data, either below a fixed threshold or below a
+//! noise profile taken from a recording of the background noise.
+
+use crate::error::{Result, SpectralError};
+use cdp_core::AnaFile;
+use std::path::Path;
+
+/// How [`gate`] interprets its threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum GateMode {
+    /// Threshold is an absolute amplitude
+    Absolute,
+    /// Threshold is a fraction (0-1) of the loudest bin in each window
+    Relative,
+}
+
+/// Zero every channel whose amplitude falls below a threshold
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `threshold` - Gate level, interpreted according to `mode`
+/// * `mode` - Whether the threshold is absolute or relative to the window peak
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn gate(input_path: &Path, output_path: &Path, threshold: f64, mode: GateMode) -> Result<()> {
+    check_threshold(threshold, mode)?;
+    let input = AnaFile::load(input_path)?;
+    gate_frames(&input, threshold, mode)?.save(output_path)?;
+    Ok(())
+}
+
+/// Gate in-memory spectral frames
+///
+/// Buffer-based counterpart of [`gate`]. Gated channels keep their
+/// frequency and have their amplitude set to zero.
+pub fn gate_frames(input: &AnaFile, threshold: f64, mode: GateMode) -> Result<AnaFile> {
+    check_threshold(threshold, mode)?;
+    let mut output = input.clone();
+
+    for frame in output.frames_mut() {
+        let level = match mode {
+            GateMode::Absolute => threshold as f32,
+            GateMode::Relative => {
+                let peak = frame.iter().step_by(2).fold(0.0f32, |max, &a| max.max(a));
+                peak * threshold as f32
+            }
+        };
+        for amp in frame.iter_mut().step_by(2) {
+            if *amp < level {
+                *amp = 0.0;
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+/// Remove noise using a noise profile, like CDP's spec clean
+///
+/// The profile is the loudest amplitude each channel reaches anywhere in
+/// `noise_path`, an analysis of a region containing only background noise.
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `noise_path` - Path to .ana file of the background noise
+/// * `output_path` - Path to output .ana file
+/// * `gain` - Multiplier applied to the noise profile before gating (> 0)
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn clean(input_path: &Path, noise_path: &Path, output_path: &Path, gain: f64) -> Result<()> {
+    check_gain(gain)?;
+    let input = AnaFile::load(input_path)?;
+    let noise = AnaFile::load(noise_path)?;
+    clean_frames(&input, &noise, gain)?.save(output_path)?;
+    Ok(())
+}
+
+/// Remove noise from in-memory spectral frames using a noise profile
+///
+/// Buffer-based counterpart of [`clean`]. Any channel at or below the
+/// scaled noise level for that channel is zeroed.
+pub fn clean_frames(input: &AnaFile, noise: &AnaFile, gain: f64) -> Result<AnaFile> {
+    check_gain(gain)?;
+    if noise.num_frames() == 0 {
+        return Err(SpectralError::InvalidInput(
+            "Noise file has no spectral data".to_string(),
+        ));
+    }
+    if noise.header.fft_size() != input.header.fft_size()
+        || noise.header.sample_rate != input.header.sample_rate
+    {
+        return Err(SpectralError::InvalidInput(
+            "Noise file must have the same sample rate and window size as the input".to_string(),
+        ));
+    }
+
+    // Loudest amplitude of each channel across the noise file
+    let mut profile = vec![0.0f32; noise.header.num_bins()];
+    for frame in noise.frames() {
+        for (bin, level) in profile.iter_mut().enumerate() {
+            *level = level.max(frame.amplitude(bin));
+        }
+    }
+    for level in profile.iter_mut() {
+        *level *= gain as f32;
+    }
+
+    let mut output = input.clone();
+    for frame in output.frames_mut() {
+        for (pair, &level) in frame.chunks_exact_mut(2).zip(&profile) {
+            if pair[0] <= level {
+                pair[0] = 0.0;
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+fn check_threshold(threshold: f64, mode: GateMode) -> Result<()> {
+    match mode {
+        GateMode::Absolute if !threshold.is_finite() || threshold < 0.0 => Err(
+            SpectralError::InvalidInput("Gate threshold must be zero or greater".to_string()),
+        ),
+        GateMode::Relative if !(0.0..=1.0).contains(&threshold) => {
+            Err(SpectralError::InvalidInput(
+                "Relative gate threshold must be between 0 and 1".to_string(),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_gain(gain: f64) -> Result<()> {
+    if !(gain > 0.0 && gain.is_finite()) {
+        return Err(SpectralError::InvalidInput(
+            "Noise gain must be greater than 0".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cdp_core::AnaHeader;
+
+    #[test]
+    fn test_gate_validation() {
+        let input = Path::new("test.ana");
+        let output = Path::new("out.ana");
+
+        assert!(gate(input, output, -1.0, GateMode::Absolute).is_err());
+        assert!(gate(input, output, 1.5, GateMode::Relative).is_err());
+        assert!(clean(input, input, output, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_gate_and_clean_frames() {
+        let header = AnaHeader::with_hop(800, 8, 800);
+        let input = AnaFile::new(
+            header.clone(),
+            vec![0.1, 0.0, 1.0, 100.0, 0.3, 200.0, 0.05, 300.0, 0.6, 400.0],
+        )
+        .unwrap();
+        let amps = |file: &AnaFile| -> Vec<f32> {
+            let frame = file.frame(0).unwrap();
+            (0..5).map(|b| frame.amplitude(b)).collect()
+        };
+
+        let output = gate_frames(&input, 0.2, GateMode::Absolute).unwrap();
+        assert_eq!(amps(&output), vec![0.0, 1.0, 0.3, 0.0, 0.6]);
+
+        let output = gate_frames(&input, 0.5, GateMode::Relative).unwrap();
+        assert_eq!(amps(&output), vec![0.0, 1.0, 0.0, 0.0, 0.6]);
+
+        // Noise profile takes the loudest level per channel across windows
+        let mut noise = AnaFile::empty(header).unwrap();
+        noise
+            .push_frame(&[0.2, 0.0, 0.0, 100.0, 0.1, 200.0, 0.0, 300.0, 0.0, 400.0])
+            .unwrap();
+        noise
+            .push_frame(&[0.0, 0.0, 0.0, 100.0, 0.2, 200.0, 0.0, 300.0, 0.4, 400.0])
+            .unwrap();
+        let output = clean_frames(&input, &noise, 1.0).unwrap();
+        assert_eq!(amps(&output), vec![0.0, 1.0, 0.3, 0.05, 0.6]);
+        let output = clean_frames(&input, &noise, 2.0).unwrap();
+        assert_eq!(amps(&output), vec![0.0, 1.0, 0.0, 0.05, 0.0]);
+    }
+}
@@ -0,0 +1,435 @@
+//! Spectral highlighting operations
+//!
+//! Boosts, attenuates or isolates parts of the spectrum. Channels are
+//! selected by the frequency they carry rather than their bin position,
+//! or by loudness when tracing partials.
+
+use crate::error::{Result, SpectralError};
+use cdp_core::{AnaFile, Breakpoints};
+use std::path::Path;
+
+/// Response of [`filter`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FilterKind {
+    /// Pass frequencies up to the upper edge
+    Lowpass,
+    /// Pass frequencies from the lower edge up
+    Highpass,
+    /// Pass frequencies between the edges
+    Bandpass,
+    /// Remove frequencies between the edges
+    Notch,
+}
+
+impl FilterKind {
+    fn passes(self, freq: f32, lo: f32, hi: f32) -> bool {
+        match self {
+            FilterKind::Lowpass => freq <= hi,
+            FilterKind::Highpass => freq >= lo,
+            FilterKind::Bandpass => (lo..=hi).contains(&freq),
+            FilterKind::Notch => !(lo..=hi).contains(&freq),
+        }
+    }
+}
+
+/// Scale the amplitude of chosen frequency bands
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `bands` - (lo_hz, hi_hz, gain) triples; gain multiplies amplitude
+/// * `isolate` - If true, remove everything outside the bands
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn band(
+    input_path: &Path,
+    output_path: &Path,
+    bands: &[(f64, f64, f64)],
+    isolate: bool,
+) -> Result<()> {
+    check_bands(bands)?;
+    let input = AnaFile::load(input_path)?;
+    band_frames(&input, bands, isolate)?.save(output_path)?;
+    Ok(())
+}
+
+/// Scale chosen frequency bands of in-memory spectral frames
+///
+/// Buffer-based counterpart of [`band`]. Where bands overlap their gains
+/// multiply.
+pub fn band_frames(input: &AnaFile, bands: &[(f64, f64, f64)], isolate: bool) -> Result<AnaFile> {
+    check_bands(bands)?;
+    let mut output = input.clone();
+
+    for frame in output.frames_mut() {
+        for pair in frame.chunks_exact_mut(2) {
+            let freq = pair[1].abs() as f64;
+            let mut gain = 1.0;
+            let mut inside = false;
+            for &(lo, hi, band_gain) in bands {
+                if (lo..=hi).contains(&freq) {
+                    gain *= band_gain;
+                    inside = true;
+                }
+            }
+            pair[0] *= if inside || !isolate { gain as f32 } else { 0.0 };
+        }
+    }
+
+    Ok(output)
+}
+
+/// Filter the spectrum
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `kind` - Filter response
+/// * `lo` - Lower edge in Hz (unused by lowpass)
+/// * `hi` - Upper edge in Hz (unused by highpass)
+/// * `gain` - Amplitude multiplier applied to the passed frequencies
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn filter(
+    input_path: &Path,
+    output_path: &Path,
+    kind: FilterKind,
+    lo: f64,
+    hi: f64,
+    gain: f64,
+) -> Result<()> {
+    filter_varying(input_path, output_path, kind, &[(0.0, lo, hi)], gain)
+}
+
+/// Filter in-memory spectral frames
+///
+/// Buffer-based counterpart of [`filter`].
+pub fn filter_frames(
+    input: &AnaFile,
+    kind: FilterKind,
+    lo: f64,
+    hi: f64,
+    gain: f64,
+) -> Result<AnaFile> {
+    filter_varying_frames(input, kind, &[(0.0, lo, hi)], gain)
+}
+
+/// Filter the spectrum with time-varying edges
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `kind` - Filter response
+/// * `edges` - (time, lo_hz, hi_hz) breakpoints, interpolated linearly
+/// * `gain` - Amplitude multiplier applied to the passed frequencies
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn filter_varying(
+    input_path: &Path,
+    output_path: &Path,
+    kind: FilterKind,
+    edges: &[(f64, f64, f64)],
+    gain: f64,
+) -> Result<()> {
+    check_filter(kind, edges, gain)?;
+    let input = AnaFile::load(input_path)?;
+    filter_varying_frames(&input, kind, edges, gain)?.save(output_path)?;
+    Ok(())
+}
+
+/// Filter in-memory spectral frames with time-varying edges
+///
+/// Buffer-based counterpart of [`filter_varying`]. Rejected channels are
+/// zeroed.
+pub fn filter_varying_frames(
+    input: &AnaFile,
+    kind: FilterKind,
+    edges: &[(f64, f64, f64)],
+    gain: f64,
+) -> Result<AnaFile> {
+    check_filter(kind, edges, gain)?;
+    let lo_edges = Breakpoints::new(edges.iter().map(|&(t, lo, _)| (t, lo)).collect())?;
+    let hi_edges = Breakpoints::new(edges.iter().map(|&(t, _, hi)| (t, hi)).collect())?;
+    let time_per_window = 1.0 / input.header.frame_rate() as f64;
+    let mut output = input.clone();
+
+    for (window_idx, frame) in output.frames_mut().enumerate() {
+        let time = window_idx as f64 * time_per_window;
+        let (lo, hi) = (lo_edges.value_at(time), hi_edges.value_at(time));
+        for pair in frame.chunks_exact_mut(2) {
+            pair[0] *= if kind.passes(pair[1].abs(), lo as f32, hi as f32) {
+                gain as f32
+            } else {
+                0.0
+            };
+        }
+    }
+
+    Ok(output)
+}
+
+/// Graphic equalisation over the spectrum
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `bands` - (centre_hz, width_octaves, gain_db) triples
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn greq(input_path: &Path, output_path: &Path, bands: &[(f64, f64, f64)]) -> Result<()> {
+    check_greq_bands(bands)?;
+    let input = AnaFile::load(input_path)?;
+    greq_frames(&input, bands)?.save(output_path)?;
+    Ok(())
+}
+
+/// Graphic equalisation of in-memory spectral frames
+///
+/// Buffer-based counterpart of [`greq`]. Each band spans `width_octaves`
+/// centred on its frequency; frequencies outside every band are unchanged.
+pub fn greq_frames(input: &AnaFile, bands: &[(f64, f64, f64)]) -> Result<AnaFile> {
+    check_greq_bands(bands)?;
+    let edges: Vec<(f64, f64, f64)> = bands
+        .iter()
+        .map(|&(centre, width, gain_db)| {
+            let half = 2.0f64.powf(width / 2.0);
+            (centre / half, centre * half, 10.0f64.powf(gain_db / 20.0))
+        })
+        .collect();
+    band_frames(input, &edges, false)
+}
+
+/// Keep only the loudest partials in each window
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `n_partials` - Number of channels to keep per window
+/// * `inverse` - If true, remove the loudest channels and keep the rest
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn trace(input_path: &Path, output_path: &Path, n_partials: u32, inverse: bool) -> Result<()> {
+    trace_varying(input_path, output_path, &[(0.0, n_partials)], inverse)
+}
+
+/// Keep only the loudest partials of in-memory spectral frames
+///
+/// Buffer-based counterpart of [`trace`].
+pub fn trace_frames(input: &AnaFile, n_partials: u32, inverse: bool) -> Result<AnaFile> {
+    trace_varying_frames(input, &[(0.0, n_partials)], inverse)
+}
+
+/// Keep a time-varying number of the loudest partials in each window
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `counts` - Vec of (time, n_partials) pairs, interpolated linearly
+/// * `inverse` - If true, remove the loudest channels and keep the rest
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn trace_varying(
+    input_path: &Path,
+    output_path: &Path,
+    counts: &[(f64, u32)],
+    inverse: bool,
+) -> Result<()> {
+    check_counts(counts)?;
+    let input = AnaFile::load(input_path)?;
+    trace_varying_frames(&input, counts, inverse)?.save(output_path)?;
+    Ok(())
+}
+
+/// Keep a time-varying number of the loudest partials of in-memory frames
+///
+/// Buffer-based counterpart of [`trace_varying`]. Removed channels keep
+/// their frequency and have their amplitude set to zero.
+pub fn trace_varying_frames(
+    input: &AnaFile,
+    counts: &[(f64, u32)],
+    inverse: bool,
+) -> Result<AnaFile> {
+    check_counts(counts)?;
+    let counts = Breakpoints::new(counts.iter().map(|&(t, n)| (t, n as f64)).collect())?;
+    let time_per_window = 1.0 / input.header.frame_rate() as f64;
+    let mut output = input.clone();
+    let mut order: Vec<usize> = Vec::with_capacity(input.header.num_bins());
+
+    for (window_idx, frame) in output.frames_mut().enumerate() {
+        let num_bins = frame.len() / 2;
+        let keep = counts.value_at(window_idx as f64 * time_per_window).round() as usize;
+        let keep = keep.min(num_bins);
+
+        // Channels ordered loudest first; the first `keep` are the partials
+        order.clear();
+        order.extend(0..num_bins);
+        order.sort_by(|&a, &b| frame[b * 2].total_cmp(&frame[a * 2]));
+        let removed = if inverse {
+            &order[..keep]
+        } else {
+            &order[keep..]
+        };
+        for &bin in removed {
+            frame[bin * 2] = 0.0;
+        }
+    }
+
+    Ok(output)
+}
+
+fn check_bands(bands: &[(f64, f64, f64)]) -> Result<()> {
+    if bands.is_empty() {
+        return Err(SpectralError::InvalidInput(
+            "No frequency bands given".to_string(),
+        ));
+    }
+    for &(lo, hi, gain) in bands {
+        if !(lo >= 0.0 && hi >= lo && hi.is_finite()) {
+            return Err(SpectralError::InvalidInput(
+                "Band edges must be zero or greater, with the upper edge not below the lower"
+                    .to_string(),
+            ));
+        }
+        if !gain.is_finite() || gain < 0.0 {
+            return Err(SpectralError::InvalidInput(
+                "Band gain must be zero or greater".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn check_filter(kind: FilterKind, edges: &[(f64, f64, f64)], gain: f64) -> Result<()> {
+    // Only the edge a lowpass or highpass uses needs to be valid
+    let bands: Vec<(f64, f64, f64)> = edges
+        .iter()
+        .map(|&(_, lo, hi)| match kind {
+            FilterKind::Lowpass => (0.0, hi, gain),
+            FilterKind::Highpass => (lo, lo, gain),
+            FilterKind::Bandpass | FilterKind::Notch => (lo, hi, gain),
+        })
+        .collect();
+    check_bands(&bands)
+}
+
+fn check_counts(counts: &[(f64, u32)]) -> Result<()> {
+    if counts.is_empty() {
+        return Err(SpectralError::InvalidInput(
+            "Partial counts must not be empty".to_string(),
+        ));
+    }
+    if counts.iter().any(|&(_, n)| n == 0) {
+        return Err(SpectralError::InvalidInput(
+            "Partial count must be at least 1".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn check_greq_bands(bands: &[(f64, f64, f64)]) -> Result<()> {
+    if bands.is_empty() {
+        return Err(SpectralError::InvalidInput(
+            "No frequency bands given".to_string(),
+        ));
+    }
+    for &(centre, width, gain_db) in bands {
+        if !(centre > 0.0 && centre.is_finite() && width > 0.0 && width.is_finite()) {
+            return Err(SpectralError::InvalidInput(
+                "EQ bands need a positive centre frequency and width".to_string(),
+            ));
+        }
+        if !gain_db.is_finite() {
+            return Err(SpectralError::InvalidInput(
+                "EQ gain must be finite".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cdp_core::AnaHeader;
+
+    #[test]
+    fn test_hilite_validation() {
+        let input = Path::new("test.ana");
+        let output = Path::new("out.ana");
+
+        assert!(band(input, output, &[], false).is_err());
+        assert!(band(input, output, &[(500.0, 100.0, 1.0)], false).is_err());
+        assert!(filter(input, output, FilterKind::Lowpass, 0.0, 100.0, -1.0).is_err());
+        assert!(filter(input, output, FilterKind::Bandpass, 300.0, 100.0, 1.0).is_err());
+        assert!(greq(input, output, &[(0.0, 1.0, 6.0)]).is_err());
+        assert!(trace(input, output, 0, false).is_err());
+    }
+
+    #[test]
+    fn test_trace_frames() {
+        // One window per second, partial count rising from 1 to 3
+        let header = AnaHeader::with_hop(800, 8, 800);
+        let pairs = [0.1, 0.0, 0.5, 100.0, 0.3, 200.0, 0.9, 300.0, 0.2, 400.0];
+        let input = AnaFile::new(header, [pairs, pairs].concat()).unwrap();
+        let amps = |file: &AnaFile, window: usize| -> Vec<f32> {
+            let frame = file.frame(window).unwrap();
+            (0..5).map(|b| frame.amplitude(b)).collect()
+        };
+
+        let output = trace_varying_frames(&input, &[(0.0, 1), (1.0, 3)], false).unwrap();
+        assert_eq!(amps(&output, 0), vec![0.0, 0.0, 0.0, 0.9, 0.0]);
+        assert_eq!(amps(&output, 1), vec![0.0, 0.5, 0.3, 0.9, 0.0]);
+
+        let output = trace_frames(&input, 2, true).unwrap();
+        assert_eq!(amps(&output, 0), vec![0.1, 0.0, 0.3, 0.0, 0.2]);
+    }
+
+    #[test]
+    fn test_band_filter_and_greq_frames() {
+        // Five bins 100Hz apart, one window per second
+        let header = AnaHeader::with_hop(800, 8, 800);
+        let pairs = [1.0, 0.0, 1.0, 100.0, 1.0, 200.0, 1.0, 300.0, 1.0, 400.0];
+        let input = AnaFile::new(header, [pairs, pairs].concat()).unwrap();
+        let amps = |file: &AnaFile, window: usize| -> Vec<f32> {
+            let frame = file.frame(window).unwrap();
+            (0..5).map(|b| frame.amplitude(b)).collect()
+        };
+
+        let output = band_frames(&input, &[(50.0, 250.0, 2.0)], false).unwrap();
+        assert_eq!(amps(&output, 0), vec![1.0, 2.0, 2.0, 1.0, 1.0]);
+        let output = band_frames(&input, &[(50.0, 250.0, 2.0)], true).unwrap();
+        assert_eq!(amps(&output, 0), vec![0.0, 2.0, 2.0, 0.0, 0.0]);
+
+        // Lowpass cutoff sweeps from 100Hz to 300Hz
+        let output = filter_varying_frames(
+            &input,
+            FilterKind::Lowpass,
+            &[(0.0, 0.0, 100.0), (1.0, 0.0, 300.0)],
+            1.0,
+        )
+        .unwrap();
+        assert_eq!(amps(&output, 0), vec![1.0, 1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(amps(&output, 1), vec![1.0, 1.0, 1.0, 1.0, 0.0]);
+        let output = filter_frames(&input, FilterKind::Notch, 150.0, 350.0, 1.0).unwrap();
+        assert_eq!(amps(&output, 0), vec![1.0, 1.0, 0.0, 0.0, 1.0]);
+
+        // +6dB an octave wide around 200Hz
+        let output = greq_frames(&input, &[(200.0, 1.0, 6.0)]).unwrap();
+        let gain = 10.0f64.powf(0.3) as f32;
+        assert_eq!(amps(&output, 0), vec![1.0, 1.0, gain, 1.0, 1.0]);
+    }
+}
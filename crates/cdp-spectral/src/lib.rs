@@ -6,13 +6,70 @@
 //! This module will be FROZEN after validation against CDP.
 //! Do not modify without explicit approval and re-validation.
 
-mod ana_io;
 pub mod blur;
+pub mod cli;
+pub mod combine;
 pub mod error;
+pub mod focus;
+pub mod formants;
+pub mod freeze;
+pub mod gate;
+pub mod hilite;
+pub mod morph;
 pub mod pitch;
+pub mod repitch;
+pub mod report;
+pub mod strange;
 pub mod stretch;
+pub mod tune;
 
-pub use blur::{blur, blur_varying};
+pub use blur::{
+    blur, blur_frames, blur_stream, blur_varying, blur_varying_frames, blur_with_params,
+    blur_with_progress, drunk, drunk_frames, noise, noise_frames, noise_varying,
+    noise_varying_frames, read_weave_file, scatter, scatter_frames, shuffle, shuffle_frames,
+    spread, spread_frames, weave, weave_frames, BlurParams, BlurParamsBuilder,
+};
+pub use cdp_core::ana::{
+    AnaFile, AnaFrameReader, AnaFrameWriter, AnaHeader, PitchFile, SpectralFrame, NOT_PITCH,
+    NOT_SOUND,
+};
+pub use combine::{
+    cross, cross_frames, diff, diff_frames, max, max_frames, mean, mean_frames, sum, sum_frames,
+    LengthMode,
+};
 pub use error::{Result, SpectralError};
-pub use pitch::{factor_to_semitones, pitch_shift, pitch_shift_formant, semitones_to_factor};
-pub use stretch::{calculate_output_duration, stretch_time, stretch_time_varying};
+pub use focus::{
+    accu, accu_frames, exag, exag_frames, exag_varying, exag_varying_frames, fold, fold_frames,
+    step, step_frames, superaccu, superaccu_frames,
+};
+pub use formants::{
+    get_formants, get_formants_frames, put_formants, put_formants_frames, FormantEnvelope,
+};
+pub use freeze::{freeze, freeze_frames, grab, grab_frames};
+pub use gate::{clean, clean_frames, gate, gate_frames, GateMode};
+pub use hilite::{
+    band, band_frames, filter, filter_frames, filter_varying, filter_varying_frames, greq,
+    greq_frames, trace, trace_frames, trace_varying, trace_varying_frames, FilterKind,
+};
+pub use morph::{chain, chain_frames, glide, glide_frames, morph, morph_frames, MorphSpan};
+pub use pitch::{
+    add_harmonics, add_harmonics_frames, factor_to_semitones, octave_shift, octave_shift_frames,
+    pitch_shift, pitch_shift_formant, pitch_shift_formant_frames, pitch_shift_formant_stream,
+    pitch_shift_frames, pitch_shift_stream, pitch_shift_with_params, semitones_to_factor,
+    PitchParams, PitchParamsBuilder,
+};
+pub use repitch::{
+    follow_pitch, follow_pitch_frames, format_pitch_text, get_pitch, get_pitch_frames,
+    parse_pitch_text, pitch_to_text, text_to_pitch,
+};
+pub use report::{report, report_frames, Partial, SpectralReport, WindowReport};
+pub use strange::{
+    glis, glis_frames, invert, invert_frames, shift, shift_frames, waver, waver_frames, WaverTarget,
+};
+pub use stretch::{
+    calculate_output_duration, detect_transients, stretch_time, stretch_time_frames,
+    stretch_time_preserving, stretch_time_preserving_frames, stretch_time_stream,
+    stretch_time_varying, stretch_time_varying_frames, stretch_time_with_params,
+    stretch_time_with_progress, StretchParams, StretchParamsBuilder,
+};
+pub use tune::{tune, tune_frames, PitchUnits};
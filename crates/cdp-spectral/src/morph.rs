@@ -0,0 +1,371 @@
+//! Spectral morphing operations
+//!
+//! Moves the amplitudes and frequencies of one sound towards those of
+//! another, channel by channel.
+
+use crate::combine::check_compatible;
+use crate::error::{Result, SpectralError};
+use cdp_core::AnaFile;
+use std::path::Path;
+
+/// Time span and curve over which one property of a morph changes
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MorphSpan {
+    /// Time in seconds at which the change starts
+    pub start: f64,
+    /// Time in seconds at which the change is complete
+    pub end: f64,
+    /// Curve exponent: 1 is linear, above 1 starts slowly, below 1 starts quickly
+    pub exponent: f64,
+}
+
+impl MorphSpan {
+    /// Linear change between two times
+    pub fn linear(start: f64, end: f64) -> Self {
+        Self {
+            start,
+            end,
+            exponent: 1.0,
+        }
+    }
+
+    /// Proportion of the change reached at `time`
+    fn progress(&self, time: f64) -> f32 {
+        if time >= self.end {
+            1.0
+        } else if time <= self.start {
+            0.0
+        } else {
+            ((time - self.start) / (self.end - self.start)).powf(self.exponent) as f32
+        }
+    }
+}
+
+/// Morph from one sound to another
+///
+/// # Arguments
+/// * `input_a` - Path to the .ana file to morph from
+/// * `input_b` - Path to the .ana file to morph to
+/// * `output_path` - Path to output .ana file
+/// * `amplitude` - When and how the amplitudes move from A to B
+/// * `frequency` - When and how the frequencies move from A to B
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn morph(
+    input_a: &Path,
+    input_b: &Path,
+    output_path: &Path,
+    amplitude: MorphSpan,
+    frequency: MorphSpan,
+) -> Result<()> {
+    check_span(&amplitude)?;
+    check_span(&frequency)?;
+    let a = AnaFile::load(input_a)?;
+    let b = AnaFile::load(input_b)?;
+    morph_frames(&a, &b, amplitude, frequency)?.save(output_path)?;
+    Ok(())
+}
+
+/// Morph between in-memory spectral frames
+///
+/// Buffer-based counterpart of [`morph`]. The output runs until both the
+/// morph and sound B have finished. If A ends before the morph does, its
+/// last window is held; likewise for B before the morph ends.
+pub fn morph_frames(
+    a: &AnaFile,
+    b: &AnaFile,
+    amplitude: MorphSpan,
+    frequency: MorphSpan,
+) -> Result<AnaFile> {
+    check_span(&amplitude)?;
+    check_span(&frequency)?;
+    check_compatible(a, b)?;
+
+    let frame_rate = a.header.frame_rate() as f64;
+    let morph_end = amplitude.end.max(frequency.end);
+    let num_windows = b
+        .num_frames()
+        .max((morph_end * frame_rate).ceil() as usize + 1);
+
+    let mut output = AnaFile::empty(a.header.clone())?;
+    let mut out_frame = vec![0.0f32; a.frame_len()];
+    for window_idx in 0..num_windows {
+        let time = window_idx as f64 / frame_rate;
+        let amp_mix = amplitude.progress(time);
+        let freq_mix = frequency.progress(time);
+        let frame_a = a.frame(window_idx.min(a.num_frames() - 1)).unwrap();
+        let frame_b = b.frame(window_idx.min(b.num_frames() - 1)).unwrap();
+
+        interpolate_window(
+            frame_a.values(),
+            frame_b.values(),
+            amp_mix,
+            freq_mix,
+            &mut out_frame,
+        );
+        output.push_frame(&out_frame)?;
+    }
+
+    Ok(output)
+}
+
+/// Glide between two single spectral windows
+///
+/// The first window of each file is used, as when gliding between two
+/// frozen spectra.
+///
+/// # Arguments
+/// * `input_a` - Path to the .ana file holding the start window
+/// * `input_b` - Path to the .ana file holding the end window
+/// * `output_path` - Path to output .ana file
+/// * `duration` - Length of the glide in seconds
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn glide(input_a: &Path, input_b: &Path, output_path: &Path, duration: f64) -> Result<()> {
+    check_duration(duration)?;
+    let a = AnaFile::load(input_a)?;
+    let b = AnaFile::load(input_b)?;
+    glide_frames(&a, &b, duration)?.save(output_path)?;
+    Ok(())
+}
+
+/// Glide between the first windows of in-memory spectral frames
+///
+/// Buffer-based counterpart of [`glide`]. Amplitudes and frequencies are
+/// interpolated linearly, ending exactly on B's window.
+pub fn glide_frames(a: &AnaFile, b: &AnaFile, duration: f64) -> Result<AnaFile> {
+    check_duration(duration)?;
+    check_compatible(a, b)?;
+
+    let num_windows = ((duration * a.header.frame_rate() as f64).round() as usize).max(2);
+    let frame_a = a.frame(0).unwrap();
+    let frame_b = b.frame(0).unwrap();
+
+    let mut output = AnaFile::empty(a.header.clone())?;
+    let mut out_frame = vec![0.0f32; a.frame_len()];
+    for window_idx in 0..num_windows {
+        let mix = window_idx as f32 / (num_windows - 1) as f32;
+        interpolate_window(frame_a.values(), frame_b.values(), mix, mix, &mut out_frame);
+        output.push_frame(&out_frame)?;
+    }
+
+    Ok(output)
+}
+
+/// Morph through a sequence of sounds
+///
+/// # Arguments
+/// * `inputs` - Paths to the .ana files to morph through, in order
+/// * `output_path` - Path to output .ana file
+/// * `durations` - Length in seconds of each morph, one fewer than the inputs
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn chain(inputs: &[&Path], output_path: &Path, durations: &[f64]) -> Result<()> {
+    check_chain(inputs.len(), durations)?;
+    let files = inputs
+        .iter()
+        .map(|path| AnaFile::load(path))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let files: Vec<&AnaFile> = files.iter().collect();
+    chain_frames(&files, durations)?.save(output_path)?;
+    Ok(())
+}
+
+/// Morph through a sequence of in-memory spectral frames
+///
+/// Buffer-based counterpart of [`chain`]. Morph `k` moves amplitudes and
+/// frequencies linearly from input `k` to input `k + 1`. Each input is
+/// time-normalized to play exactly once across the morphs it takes part
+/// in, whatever its own length, so the output flows continuously from the
+/// start of the first input to the end of the last.
+pub fn chain_frames(inputs: &[&AnaFile], durations: &[f64]) -> Result<AnaFile> {
+    check_chain(inputs.len(), durations)?;
+    for input in &inputs[1..] {
+        check_compatible(inputs[0], input)?;
+    }
+
+    // Times at which each input is heard alone
+    let boundaries: Vec<f64> = std::iter::once(0.0)
+        .chain(durations.iter().scan(0.0, |time, &duration| {
+            *time += duration;
+            Some(*time)
+        }))
+        .collect();
+    let total = boundaries[boundaries.len() - 1];
+
+    // Window of `input` heard at `time`, spread over the morphs either side
+    let source_window = |input: usize, time: f64| {
+        let start = boundaries[input.saturating_sub(1)];
+        let end = boundaries[(input + 1).min(boundaries.len() - 1)];
+        let position = ((time - start) / (end - start)).clamp(0.0, 1.0);
+        let last = inputs[input].num_frames() - 1;
+        inputs[input]
+            .frame((position * last as f64).round() as usize)
+            .unwrap()
+    };
+
+    let frame_rate = inputs[0].header.frame_rate() as f64;
+    let num_windows = (total * frame_rate).round() as usize + 1;
+    let mut output = AnaFile::empty(inputs[0].header.clone())?;
+    let mut out_frame = vec![0.0f32; inputs[0].frame_len()];
+    for window_idx in 0..num_windows {
+        let time = (window_idx as f64 / frame_rate).min(total);
+        let segment = boundaries[1..durations.len()].partition_point(|&b| b <= time);
+        let mix = ((time - boundaries[segment]) / durations[segment]).min(1.0) as f32;
+
+        interpolate_window(
+            source_window(segment, time).values(),
+            source_window(segment + 1, time).values(),
+            mix,
+            mix,
+            &mut out_frame,
+        );
+        output.push_frame(&out_frame)?;
+    }
+
+    Ok(output)
+}
+
+/// Interpolate amplitudes and frequencies of two windows independently
+fn interpolate_window(a: &[f32], b: &[f32], amp_mix: f32, freq_mix: f32, output: &mut [f32]) {
+    for ((out, pa), pb) in output
+        .chunks_exact_mut(2)
+        .zip(a.chunks_exact(2))
+        .zip(b.chunks_exact(2))
+    {
+        out[0] = pa[0] + (pb[0] - pa[0]) * amp_mix;
+        out[1] = pa[1] + (pb[1] - pa[1]) * freq_mix;
+    }
+}
+
+fn check_span(span: &MorphSpan) -> Result<()> {
+    if !(span.start >= 0.0 && span.end >= span.start && span.end.is_finite()) {
+        return Err(SpectralError::InvalidInput(
+            "Morph times must be zero or greater, with the end not before the start".to_string(),
+        ));
+    }
+    if !(span.exponent > 0.0 && span.exponent.is_finite()) {
+        return Err(SpectralError::InvalidInput(
+            "Morph exponent must be greater than 0".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn check_duration(duration: f64) -> Result<()> {
+    if !(duration > 0.0 && duration.is_finite()) {
+        return Err(SpectralError::InvalidInput(
+            "Glide duration must be greater than 0".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn check_chain(num_inputs: usize, durations: &[f64]) -> Result<()> {
+    if num_inputs < 2 {
+        return Err(SpectralError::InvalidInput(
+            "A morph chain needs at least two inputs".to_string(),
+        ));
+    }
+    if durations.len() != num_inputs - 1 {
+        return Err(SpectralError::InvalidInput(format!(
+            "A morph chain of {} inputs needs {} durations",
+            num_inputs,
+            num_inputs - 1
+        )));
+    }
+    for &duration in durations {
+        check_duration(duration)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cdp_core::AnaHeader;
+
+    #[test]
+    fn test_morph_validation() {
+        let input = Path::new("test.ana");
+        let output = Path::new("out.ana");
+        let span = MorphSpan::linear(0.0, 1.0);
+
+        assert!(morph(input, input, output, MorphSpan::linear(2.0, 1.0), span).is_err());
+        assert!(morph(
+            input,
+            input,
+            output,
+            span,
+            MorphSpan {
+                exponent: 0.0,
+                ..span
+            }
+        )
+        .is_err());
+        assert!(glide(input, input, output, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_morph_and_glide_frames() {
+        // One window per second
+        let header = AnaHeader::with_hop(4, 2, 4);
+        let a = AnaFile::new(header.clone(), [[0.0, 0.0, 1.0, 100.0]; 4].concat()).unwrap();
+        let b = AnaFile::new(header, [[0.0, 0.0, 3.0, 300.0]; 2].concat()).unwrap();
+
+        // Amplitude moves over seconds 1-3, frequency jumps at second 2
+        let output = morph_frames(
+            &a,
+            &b,
+            MorphSpan::linear(1.0, 3.0),
+            MorphSpan::linear(2.0, 2.0),
+        )
+        .unwrap();
+        let pairs: Vec<(f32, f32)> = output.frames().map(|f| f.pair(1)).collect();
+        assert_eq!(
+            pairs,
+            vec![(1.0, 100.0), (1.0, 100.0), (2.0, 300.0), (3.0, 300.0)]
+        );
+
+        let output = glide_frames(&a, &b, 3.0).unwrap();
+        let pairs: Vec<(f32, f32)> = output.frames().map(|f| f.pair(1)).collect();
+        assert_eq!(pairs, vec![(1.0, 100.0), (2.0, 200.0), (3.0, 300.0)]);
+    }
+
+    #[test]
+    fn test_chain_frames() {
+        // One window per second
+        let header = AnaHeader::with_hop(4, 2, 4);
+        let a = AnaFile::new(header.clone(), [[0.0, 0.0, 1.0, 100.0]; 2].concat()).unwrap();
+        let b = AnaFile::new(header.clone(), vec![0.0, 0.0, 3.0, 300.0]).unwrap();
+        let c = AnaFile::new(
+            header,
+            vec![
+                0.0, 0.0, 5.0, 500.0, 0.0, 0.0, 6.0, 500.0, 0.0, 0.0, 7.0, 500.0,
+            ],
+        )
+        .unwrap();
+        assert!(chain_frames(&[&a, &b, &c], &[2.0]).is_err());
+
+        // C is stretched over the last morph, so its middle window is heard halfway
+        let output = chain_frames(&[&a, &b, &c], &[2.0, 2.0]).unwrap();
+        let pairs: Vec<(f32, f32)> = output.frames().map(|f| f.pair(1)).collect();
+        assert_eq!(
+            pairs,
+            vec![
+                (1.0, 100.0),
+                (2.0, 200.0),
+                (3.0, 300.0),
+                (4.5, 400.0),
+                (7.0, 500.0)
+            ]
+        );
+    }
+}
@@ -1,11 +1,23 @@
-//! Pitch shifting operations using spectral bin shifting
+//! Pitch shifting operations on amplitude/frequency spectra
 //!
-//! Shifts pitch by moving frequency bins up or down.
-
-use crate::ana_io::{read_ana_file, write_ana_file};
-use crate::error::{Result, SpectralError};
+//! Shifts pitch by scaling the frequency of every channel and moving its
+//! amplitude to the fractional channel position the new frequency falls on.
+//! Whole-octave moves and harmonic copies instead remap channels by exact
+//! integer ratios.
+
+use crate::error::{check_param, write_frames, Result, SpectralError};
+use cdp_core::progress::{self, ProgressSink};
+use cdp_core::{AnaFile, AnaFrameReader, AnaFrameWriter, Param};
+use std::f32::consts::PI;
+use std::io::{Read, Seek, Write};
 use std::path::Path;
 
+/// Half-width in channels of the smoothing that estimates the spectral envelope
+const ENVELOPE_RADIUS: usize = 4;
+
+/// Half-width in channels of the Hann window's main lobe
+const LOBE_HALF_WIDTH: usize = 2;
+
 /// Pitch shift a spectral file
 ///
 /// # Arguments
@@ -17,20 +29,156 @@ use std::path::Path;
 /// * `Ok(())` on success
 /// * `Err(SpectralError)` on failure
 pub fn pitch_shift(input_path: &Path, output_path: &Path, shift_factor: f64) -> Result<()> {
-    // Validate shift factor
-    if shift_factor <= 0.0 || !(0.1..=10.0).contains(&shift_factor) {
+    let params = PitchParams {
+        factor: shift_factor,
+        ..PitchParams::default()
+    };
+    pitch_shift_with_params(input_path, output_path, &params, &mut ())
+}
+
+/// Settings for [`pitch_shift_with_params`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct PitchParams {
+    /// Pitch shift as a frequency ratio (0.1-10)
+    pub factor: f64,
+    /// Keep the spectral envelope where it was
+    pub preserve_formants: bool,
+}
+
+impl Default for PitchParams {
+    /// No shift, without formant preservation
+    fn default() -> Self {
+        Self {
+            factor: 1.0,
+            preserve_formants: false,
+        }
+    }
+}
+
+impl PitchParams {
+    /// Start from the defaults
+    pub fn builder() -> PitchParamsBuilder {
+        PitchParamsBuilder(Self::default())
+    }
+}
+
+/// Builder for [`PitchParams`]
+#[derive(Debug, Clone, Copy)]
+pub struct PitchParamsBuilder(PitchParams);
+
+impl PitchParamsBuilder {
+    /// Shift as a frequency ratio (2.0 = octave up)
+    pub fn factor(mut self, factor: f64) -> Self {
+        self.0.factor = factor;
+        self
+    }
+
+    /// Shift in semitones (12 = octave up)
+    pub fn semitones(mut self, semitones: f64) -> Self {
+        self.0.factor = semitones_to_factor(semitones);
+        self
+    }
+
+    /// Keep the spectral envelope where it was
+    pub fn preserve_formants(mut self, preserve: bool) -> Self {
+        self.0.preserve_formants = preserve;
+        self
+    }
+
+    /// Finish, checking the settings
+    pub fn build(self) -> Result<PitchParams> {
+        check_shift_factor(self.0.factor)?;
+        Ok(self.0)
+    }
+}
+
+/// Pitch shift a spectral file with the given settings, reporting each
+/// window to `progress`
+///
+/// Stops with [`cdp_core::CoreError::Cancelled`] if the sink asks. A
+/// failed or cancelled run leaves no output file; pass `&mut ()` to ignore
+/// progress.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "info",
+        skip_all,
+        fields(input = %input_path.display(), output = %output_path.display()),
+        err
+    )
+)]
+pub fn pitch_shift_with_params(
+    input_path: &Path,
+    output_path: &Path,
+    params: &PitchParams,
+    progress: &mut dyn ProgressSink,
+) -> Result<()> {
+    check_shift_factor(params.factor)?;
+    let mut reader = AnaFrameReader::open(input_path)?;
+    let header = reader.header().clone();
+    write_frames(output_path, header, |writer| {
+        stream_shift(&mut reader, writer, params, progress)
+    })?;
+    #[cfg(feature = "tracing")]
+    tracing::debug!(frames = reader.num_frames(), "frames processed");
+    Ok(())
+}
+
+/// Pitch shift spectral frames read one at a time from `reader`
+///
+/// Streaming counterpart of [`pitch_shift`]. The writer is left for the
+/// caller to finalize.
+pub fn pitch_shift_stream<R: Read + Seek, W: Write + Seek>(
+    reader: &mut AnaFrameReader<R>,
+    writer: &mut AnaFrameWriter<W>,
+    shift_factor: f64,
+) -> Result<()> {
+    let params = PitchParams {
+        factor: shift_factor,
+        ..PitchParams::default()
+    };
+    stream_shift(reader, writer, &params, &mut ())
+}
+
+/// [`pitch_shift_formant_stream`], reporting each window to `progress`
+fn stream_shift<R: Read + Seek, W: Write + Seek>(
+    reader: &mut AnaFrameReader<R>,
+    writer: &mut AnaFrameWriter<W>,
+    params: &PitchParams,
+    progress: &mut dyn ProgressSink,
+) -> Result<()> {
+    let shift_factor = params.factor;
+    check_shift_factor(shift_factor)?;
+    let bin_width = reader.header().bin_width();
+    if params.preserve_formants {
+        return stream_windows(reader, writer, progress, |input, output| {
+            shift_window_formant(input, output, shift_factor, bin_width)
+        });
+    }
+
+    if reader.num_frames() == 0 {
         return Err(SpectralError::InvalidInput(
-            "Shift factor must be between 0.1 and 10".to_string(),
+            "Input file has no spectral data".to_string(),
         ));
     }
+    stream_windows(reader, writer, progress, |input, output| {
+        shift_window(input, output, shift_factor, bin_width)
+    })
+}
 
-    // Read input .ana file
-    let (header, samples) = read_ana_file(input_path)?;
+/// Pitch shift in-memory spectral frames
+///
+/// Buffer-based counterpart of [`pitch_shift`].
+pub fn pitch_shift_frames(input: &AnaFile, shift_factor: f64) -> Result<AnaFile> {
+    check_shift_factor(shift_factor)?;
+    let header = &input.header;
+    let samples = &input.data;
 
     // Calculate window size (samples per window)
     let window_size = header.channels as usize;
     let num_windows = samples.len() / window_size;
-    let num_bins = window_size / 2; // Real/imaginary pairs
 
     if num_windows == 0 {
         return Err(SpectralError::InvalidInput(
@@ -42,49 +190,97 @@ pub fn pitch_shift(input_path: &Path, output_path: &Path, shift_factor: f64) ->
     let mut output = vec![0.0f32; samples.len()];
 
     // Process each window
-    for window_idx in 0..num_windows {
-        let window_start = window_idx * window_size;
-
-        // Shift frequency bins
-        for bin in 0..num_bins {
-            let src_bin = bin;
-            let dst_bin = (bin as f64 * shift_factor).round() as usize;
-
-            if dst_bin < num_bins {
-                let src_real = samples[window_start + src_bin * 2];
-                let src_imag = samples[window_start + src_bin * 2 + 1];
+    for (input, output) in samples
+        .chunks_exact(window_size)
+        .zip(output.chunks_exact_mut(window_size))
+    {
+        shift_window(input, output, shift_factor, header.bin_width());
+    }
 
-                let dst_idx = window_start + dst_bin * 2;
+    Ok(AnaFile::new(header.clone(), output)?)
+}
 
-                // Add to destination (allows overlapping bins)
-                output[dst_idx] += src_real;
-                output[dst_idx + 1] += src_imag;
-            }
+/// Transpose the partials of one window
+///
+/// Each local amplitude peak is taken as a partial. Its true level is
+/// recovered from the analysis window's spectral shape at the peak, then
+/// the whole main lobe is redrawn around the fractional channel position of
+/// the transposed frequency. Small shifts therefore move energy smoothly
+/// between channels instead of jumping whole channels, and the level is
+/// kept. Where lobes overlap their amplitudes add and the louder partial
+/// sets the channel frequency.
+pub(crate) fn shift_window(input: &[f32], output: &mut [f32], shift_factor: f64, bin_width: f32) {
+    let num_bins = input.len() / 2;
+    let factor = shift_factor as f32;
+    for (bin, pair) in output.chunks_exact_mut(2).enumerate() {
+        pair[0] = 0.0;
+        pair[1] = bin as f32 * bin_width;
+    }
+    // Loudest contribution received by each channel so far
+    let mut loudest = vec![0.0f32; num_bins];
+
+    for bin in 0..num_bins {
+        let amp = input[bin * 2];
+        let is_peak = amp > 0.0
+            && (bin == 0 || amp >= input[(bin - 1) * 2])
+            && (bin + 1 == num_bins || amp > input[(bin + 1) * 2]);
+        if !is_peak {
+            continue;
         }
 
-        // Normalize to prevent clipping from overlapping bins
-        let mut max_magnitude = 0.0f32;
-        for bin in 0..num_bins {
-            let real = output[window_start + bin * 2];
-            let imag = output[window_start + bin * 2 + 1];
-            let magnitude = (real * real + imag * imag).sqrt();
-            max_magnitude = max_magnitude.max(magnitude);
+        let freq = input[bin * 2 + 1];
+        let new_freq = freq * factor;
+        let new_pos = new_freq.abs() / bin_width;
+        if new_pos >= num_bins as f32 {
+            continue;
         }
 
-        // Apply normalization if needed
-        if max_magnitude > 1.0 {
-            let scale = 0.95 / max_magnitude; // Scale to 95% to prevent clipping
-            for bin in 0..num_bins {
-                output[window_start + bin * 2] *= scale;
-                output[window_start + bin * 2 + 1] *= scale;
+        // Level of the partial at the centre of its lobe
+        let offset = bin as f32 - freq.abs() / bin_width;
+        let level = if offset.abs() < 1.0 {
+            amp / hann_lobe(offset)
+        } else {
+            amp
+        };
+
+        let first = (new_pos.floor() as usize).saturating_sub(LOBE_HALF_WIDTH - 1);
+        let last = (new_pos.floor() as usize + LOBE_HALF_WIDTH).min(num_bins - 1);
+        for dst_bin in first..=last {
+            let share = level * hann_lobe(dst_bin as f32 - new_pos);
+            if share <= 0.0 {
+                continue;
+            }
+            output[dst_bin * 2] += share;
+            if share > loudest[dst_bin] {
+                loudest[dst_bin] = share;
+                output[dst_bin * 2 + 1] = new_freq;
             }
         }
     }
 
-    // Write output .ana file
-    write_ana_file(output_path, &header, &output)?;
+    // Empty channels follow the nearest partial below, so their phase stays
+    // coherent with it should the partial move into them in a later window
+    let mut nearest = None;
+    for (bin, pair) in output.chunks_exact_mut(2).enumerate() {
+        if loudest[bin] > 0.0 {
+            nearest = Some(pair[1]);
+        } else if let Some(freq) = nearest {
+            pair[1] = freq;
+        }
+    }
+}
 
-    Ok(())
+/// Spectrum magnitude of the Hann analysis window `offset` channels from a
+/// partial, relative to the magnitude at the partial itself
+fn hann_lobe(offset: f32) -> f32 {
+    let sinc = |x: f32| {
+        if x.abs() < 1e-6 {
+            1.0
+        } else {
+            (PI * x).sin() / (PI * x)
+        }
+    };
+    (sinc(offset) + 0.5 * (sinc(offset - 1.0) + sinc(offset + 1.0))).abs()
 }
 
 /// Pitch shift with formant preservation (spectral envelope)
@@ -104,72 +300,284 @@ pub fn pitch_shift_formant(
     shift_factor: f64,
     preserve_formants: bool,
 ) -> Result<()> {
-    if !preserve_formants {
-        return pitch_shift(input_path, output_path, shift_factor);
-    }
+    let params = PitchParams {
+        factor: shift_factor,
+        preserve_formants,
+    };
+    pitch_shift_with_params(input_path, output_path, &params, &mut ())
+}
 
-    // Validate shift factor
-    if shift_factor <= 0.0 || !(0.1..=10.0).contains(&shift_factor) {
-        return Err(SpectralError::InvalidInput(
-            "Shift factor must be between 0.1 and 10".to_string(),
-        ));
+/// Pitch shift spectral frames read one at a time, optionally preserving formants
+///
+/// Streaming counterpart of [`pitch_shift_formant`]. The writer is left for
+/// the caller to finalize.
+pub fn pitch_shift_formant_stream<R: Read + Seek, W: Write + Seek>(
+    reader: &mut AnaFrameReader<R>,
+    writer: &mut AnaFrameWriter<W>,
+    shift_factor: f64,
+    preserve_formants: bool,
+) -> Result<()> {
+    let params = PitchParams {
+        factor: shift_factor,
+        preserve_formants,
+    };
+    stream_shift(reader, writer, &params, &mut ())
+}
+
+/// Pitch shift in-memory spectral frames with optional formant preservation
+///
+/// Buffer-based counterpart of [`pitch_shift_formant`].
+pub fn pitch_shift_formant_frames(
+    input: &AnaFile,
+    shift_factor: f64,
+    preserve_formants: bool,
+) -> Result<AnaFile> {
+    if !preserve_formants {
+        return pitch_shift_frames(input, shift_factor);
     }
 
-    // Read input .ana file
-    let (header, samples) = read_ana_file(input_path)?;
+    check_shift_factor(shift_factor)?;
+    let header = &input.header;
+    let samples = &input.data;
 
     let window_size = header.channels as usize;
-    let num_windows = samples.len() / window_size;
-    let num_bins = window_size / 2;
 
     let mut output = vec![0.0f32; samples.len()];
 
     // Process each window with formant preservation
-    for window_idx in 0..num_windows {
-        let window_start = window_idx * window_size;
-
-        // Extract spectral envelope (magnitude spectrum)
-        let mut envelope = vec![0.0f32; num_bins];
-        for bin in 0..num_bins {
-            let real = samples[window_start + bin * 2];
-            let imag = samples[window_start + bin * 2 + 1];
-            envelope[bin] = (real * real + imag * imag).sqrt();
+    for (input, output) in samples
+        .chunks_exact(window_size)
+        .zip(output.chunks_exact_mut(window_size))
+    {
+        shift_window_formant(input, output, shift_factor, header.bin_width());
+    }
+
+    Ok(AnaFile::new(header.clone(), output)?)
+}
+
+/// Transpose the partials of one window while keeping its spectral envelope
+///
+/// After shifting, each channel is rescaled by the ratio of the original
+/// envelope at that channel to the envelope the shifted partials carried
+/// with them, so formant regions stay where they were.
+fn shift_window_formant(input: &[f32], output: &mut [f32], shift_factor: f64, bin_width: f32) {
+    shift_window(input, output, shift_factor, bin_width);
+
+    let envelope = spectral_envelope(input);
+    let num_bins = envelope.len();
+    for (bin, pair) in output.chunks_exact_mut(2).enumerate() {
+        // Envelope at the position this channel's content came from
+        let src_pos = bin as f32 / shift_factor as f32;
+        let lower = src_pos.floor() as usize;
+        if lower >= num_bins {
+            pair[0] = 0.0;
+            continue;
         }
+        let upper = (lower + 1).min(num_bins - 1);
+        let frac = src_pos - lower as f32;
+        let carried = envelope[lower] + (envelope[upper] - envelope[lower]) * frac;
+
+        pair[0] = if carried > 0.0 {
+            pair[0] * envelope[bin] / carried
+        } else {
+            0.0
+        };
+    }
+}
 
-        // Shift harmonics while preserving envelope
-        for bin in 0..num_bins {
-            let src_bin = (bin as f64 / shift_factor).round() as usize;
-
-            if src_bin < num_bins {
-                let src_real = samples[window_start + src_bin * 2];
-                let src_imag = samples[window_start + src_bin * 2 + 1];
-
-                // Get source magnitude and phase
-                let src_mag = (src_real * src_real + src_imag * src_imag).sqrt();
-                let src_phase = src_imag.atan2(src_real);
-
-                // For formant preservation: keep original envelope magnitude ratios
-                // Apply the envelope characteristic from the original position
-                let envelope_factor = if envelope[src_bin] > 0.0 {
-                    envelope[bin] / envelope[src_bin]
-                } else {
-                    1.0
-                };
-                let new_mag = src_mag * envelope_factor;
-
-                // Convert back to rectangular using source phase
-                output[window_start + bin * 2] = new_mag * src_phase.cos();
-                output[window_start + bin * 2 + 1] = new_mag * src_phase.sin();
-            }
+/// Smoothed amplitude spectrum of one window
+fn spectral_envelope(window: &[f32]) -> Vec<f32> {
+    let amps: Vec<f32> = window.iter().step_by(2).copied().collect();
+    (0..amps.len())
+        .map(|bin| {
+            let lo = bin.saturating_sub(ENVELOPE_RADIUS);
+            let hi = (bin + ENVELOPE_RADIUS + 1).min(amps.len());
+            amps[lo..hi].iter().sum::<f32>() / (hi - lo) as f32
+        })
+        .collect()
+}
+
+/// Transpose by whole octaves, like CDP's spec octmove
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `octaves` - Octaves to move, up if positive or down if negative (-8 to 8)
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn octave_shift(input_path: &Path, output_path: &Path, octaves: i32) -> Result<()> {
+    check_octaves(octaves)?;
+    let input = AnaFile::load(input_path)?;
+    octave_shift_frames(&input, octaves)?.save(output_path)?;
+    Ok(())
+}
+
+/// Transpose in-memory spectral frames by whole octaves
+///
+/// Buffer-based counterpart of [`octave_shift`]. Channel `n` moves to
+/// channel `n * 2^octaves` with its frequency scaled to match, so the
+/// shape of each partial's lobe is kept exactly. Channels landing together
+/// going down sum their energy and the loudest sets the frequency.
+pub fn octave_shift_frames(input: &AnaFile, octaves: i32) -> Result<AnaFile> {
+    check_octaves(octaves)?;
+    let (numerator, denominator) = if octaves >= 0 {
+        (1 << octaves, 1)
+    } else {
+        (1, 1 << -octaves)
+    };
+    let bin_width = input.header.bin_width();
+    let mut output = input.clone();
+    let mut loudest = vec![0.0f32; input.header.num_bins()];
+
+    for (frame, source) in output.frames_mut().zip(input.frames()) {
+        for (bin, pair) in frame.chunks_exact_mut(2).enumerate() {
+            pair[0] = 0.0;
+            pair[1] = bin as f32 * bin_width;
+        }
+        loudest.fill(0.0);
+        add_remapped(
+            source.values(),
+            frame,
+            &mut loudest,
+            numerator,
+            denominator,
+            1.0,
+        );
+    }
+
+    Ok(output)
+}
+
+/// Add copies of the spectrum at harmonics of every frequency
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `count` - Number of harmonics in the output, including the original
+///   (2 up to the number of channels)
+/// * `rolloff` - Level (0-1] of each harmonic relative to the one below
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn add_harmonics(
+    input_path: &Path,
+    output_path: &Path,
+    count: u32,
+    rolloff: f64,
+) -> Result<()> {
+    check_harmonics(count, rolloff)?;
+    let input = AnaFile::load(input_path)?;
+    add_harmonics_frames(&input, count, rolloff)?.save(output_path)?;
+    Ok(())
+}
+
+/// Add harmonics of the whole spectrum of in-memory spectral frames
+///
+/// Buffer-based counterpart of [`add_harmonics`]. Copies of the spectrum
+/// at 2, 3, ... `count` times each frequency are remapped channel by
+/// channel as in [`octave_shift_frames`] and added to the original, the
+/// `n`th harmonic at `rolloff^(n - 1)` of the original level. Where copies
+/// overlap their energy sums and the loudest sets the frequency.
+pub fn add_harmonics_frames(input: &AnaFile, count: u32, rolloff: f64) -> Result<AnaFile> {
+    check_harmonics(count, rolloff)?;
+    let bins = input.header.num_bins();
+    if count as usize > bins {
+        return Err(SpectralError::InvalidInput(format!(
+            "Harmonic count must be at most the number of channels ({})",
+            bins
+        )));
+    }
+    let mut output = input.clone();
+    let mut loudest = vec![0.0f32; bins];
+
+    for (frame, source) in output.frames_mut().zip(input.frames()) {
+        for (level, pair) in loudest.iter_mut().zip(source.values().chunks_exact(2)) {
+            *level = pair[0];
+        }
+        for harmonic in 2..=count {
+            // Bounded by the channel count, so the exponent fits
+            let gain = rolloff.powi((harmonic - 1) as i32) as f32;
+            add_remapped(
+                source.values(),
+                frame,
+                &mut loudest,
+                harmonic as usize,
+                1,
+                gain,
+            );
         }
     }
 
-    // Write output .ana file
-    write_ana_file(output_path, &header, &output)?;
+    Ok(output)
+}
+
+/// Add `source` to `output` with channel `n` moved to `n * numerator / denominator`
+///
+/// Amplitudes are scaled by `gain` and combine by energy; each output
+/// channel takes the frequency of its loudest contribution, tracked in
+/// `loudest`.
+fn add_remapped(
+    source: &[f32],
+    output: &mut [f32],
+    loudest: &mut [f32],
+    numerator: usize,
+    denominator: usize,
+    gain: f32,
+) {
+    let ratio = numerator as f32 / denominator as f32;
+    for (bin, pair) in source.chunks_exact(2).enumerate() {
+        let dst_bin = bin * numerator / denominator;
+        let amp = pair[0] * gain;
+        if dst_bin >= loudest.len() || amp <= 0.0 {
+            continue;
+        }
+        let out = &mut output[dst_bin * 2..dst_bin * 2 + 2];
+        out[0] = out[0].hypot(amp);
+        if amp > loudest[dst_bin] {
+            loudest[dst_bin] = amp;
+            out[1] = pair[1] * ratio;
+        }
+    }
+}
 
+/// Apply a per-window transform to every frame of `reader`, reporting
+/// each to `progress`
+fn stream_windows<R: Read + Seek, W: Write + Seek>(
+    reader: &mut AnaFrameReader<R>,
+    writer: &mut AnaFrameWriter<W>,
+    progress: &mut dyn ProgressSink,
+    mut process: impl FnMut(&[f32], &mut [f32]),
+) -> Result<()> {
+    let window_size = reader.header().channels as usize;
+    let num_windows = reader.num_frames();
+    let mut input = vec![0.0f32; window_size];
+    let mut output = vec![0.0f32; window_size];
+    let mut done = 0;
+    while reader.read_frame(&mut input)? {
+        process(&input, &mut output);
+        writer.write_frame(&output)?;
+        done += 1;
+        progress::report(progress, done, num_windows)?;
+    }
     Ok(())
 }
 
+fn check_shift_factor(shift_factor: f64) -> Result<()> {
+    check_param(Param::ShiftFactor, shift_factor)
+}
+
+fn check_octaves(octaves: i32) -> Result<()> {
+    check_param(Param::OctaveShift, octaves as f64)
+}
+
+fn check_harmonics(count: u32, rolloff: f64) -> Result<()> {
+    check_param(Param::HarmonicCount, count as f64)?;
+    check_param(Param::HarmonicRolloff, rolloff)
+}
+
 /// Convert pitch shift factor to semitones
 pub fn factor_to_semitones(factor: f64) -> f64 {
     12.0 * factor.log2()
@@ -203,6 +611,105 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_pitch_params_match_positional() {
+        use cdp_core::AnaHeader;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let input = dir.path().join("in.ana");
+        let positional = dir.path().join("positional.ana");
+        let built = dir.path().join("built.ana");
+        let header = AnaHeader::new(44100, 64, 4).unwrap();
+        let frame: Vec<f32> = (0..header.num_bins())
+            .flat_map(|bin| [1.0 / (bin + 1) as f32, bin as f32 * header.bin_width()])
+            .collect();
+        AnaFile::new(header, frame.repeat(3))
+            .unwrap()
+            .save(&input)
+            .unwrap();
+
+        let params = PitchParams::builder()
+            .semitones(12.0)
+            .preserve_formants(true)
+            .build()
+            .unwrap();
+        assert!((params.factor - 2.0).abs() < 1e-12);
+        let mut windows = 0;
+        struct Count<'a>(&'a mut usize);
+        impl ProgressSink for Count<'_> {
+            fn progress(&mut self, done: usize, _total: usize) {
+                *self.0 = done;
+            }
+        }
+        pitch_shift_with_params(&input, &built, &params, &mut Count(&mut windows)).unwrap();
+        pitch_shift_formant(&input, &positional, 2.0, true).unwrap();
+        assert_eq!(windows, 3);
+        assert_eq!(
+            AnaFile::load(&built).unwrap().data,
+            AnaFile::load(&positional).unwrap().data
+        );
+
+        assert!(PitchParams::builder().factor(20.0).build().is_err());
+    }
+
+    #[test]
+    fn test_shift_window_redraws_partial_lobe() {
+        // A partial of level 1 at 1000Hz in 100Hz channels, as analysed
+        let bin_width = 100.0;
+        let mut input = vec![0.0f32; 64];
+        for (bin, pair) in input.chunks_exact_mut(2).enumerate() {
+            pair[0] = hann_lobe(bin as f32 - 10.0);
+            pair[1] = 1000.0;
+        }
+
+        // Up one semitone the lobe centres on 10.59 channels
+        let factor = semitones_to_factor(1.0);
+        let mut output = vec![0.0f32; 64];
+        shift_window(&input, &mut output, factor, bin_width);
+
+        let new_freq = 1000.0 * factor as f32;
+        for bin in 9..=12 {
+            let expected = hann_lobe(bin as f32 - new_freq / bin_width);
+            assert!((output[bin * 2] - expected).abs() < 1e-5);
+            assert_eq!(output[bin * 2 + 1], new_freq);
+        }
+        assert_eq!(output[16], 0.0);
+    }
+
+    #[test]
+    fn test_octave_shift_and_harmonics_frames() {
+        // Five channels 100Hz apart
+        let header = cdp_core::AnaHeader::with_hop(800, 8, 800);
+        let input = AnaFile::new(
+            header,
+            vec![0.0, 0.0, 1.0, 100.0, 3.0, 210.0, 4.0, 300.0, 0.0, 400.0],
+        )
+        .unwrap();
+        assert!(octave_shift_frames(&input, 9).is_err());
+        assert!(add_harmonics_frames(&input, 1, 0.5).is_err());
+        assert!(add_harmonics_frames(&input, 6, 0.5).is_err());
+        assert!(add_harmonics_frames(&input, u32::MAX, 0.5).is_err());
+
+        let output = octave_shift_frames(&input, 1).unwrap();
+        let frame = output.frame(0).unwrap();
+        assert_eq!(frame.pair(2), (1.0, 200.0));
+        assert_eq!(frame.pair(4), (3.0, 420.0));
+        assert_eq!(frame.pair(3), (0.0, 300.0));
+
+        // Channels 2 and 3 meet in channel 1, summing energy
+        let output = octave_shift_frames(&input, -1).unwrap();
+        let frame = output.frame(0).unwrap();
+        assert_eq!(frame.pair(1), (5.0, 150.0));
+        assert_eq!(frame.pair(2), (0.0, 200.0));
+
+        // Channel 1's second harmonic lands on the quieter channel 2
+        let output = add_harmonics_frames(&input, 3, 0.5).unwrap();
+        let frame = output.frame(0).unwrap();
+        assert_eq!(frame.pair(2), (3.0f32.hypot(0.5), 210.0));
+        assert_eq!(frame.pair(3), (4.0f32.hypot(0.25), 300.0));
+        assert_eq!(frame.pair(4), (3.0 * 0.5, 420.0));
+    }
+
     #[test]
     fn test_semitone_conversion() {
         // Test octave up (12 semitones = factor of 2)
@@ -0,0 +1,393 @@
+//! Pitch tracking and pitch data operations
+//!
+//! Extracts the fundamental of each analysis window into a CDP pitch data
+//! file, converts pitch data to and from text, and transposes sounds to
+//! follow a pitch contour.
+
+use crate::error::{Result, SpectralError};
+use crate::pitch::shift_window;
+use cdp_core::{AnaFile, AnaHeader, Breakpoints, Interpolation, PitchFile, NOT_PITCH, NOT_SOUND};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Number of loudest peaks considered when searching for a fundamental
+const MAX_PEAKS: usize = 8;
+
+/// Highest harmonic number a peak may be taken as
+const MAX_HARMONIC: usize = 8;
+
+/// Largest deviation, as a ratio, of a peak from an exact harmonic
+const HARMONIC_TOLERANCE: f32 = 0.03;
+
+/// Share of the peak amplitude that must be harmonic for a window to be pitched
+const VOICED_RATIO: f32 = 0.5;
+
+/// Window level, relative to the loudest window, below which it is silent
+const SILENCE_RATIO: f32 = 0.001;
+
+/// Track the pitch of an analysis file into a pitch data file
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output pitch data file
+/// * `low` - Lowest acceptable fundamental in Hz
+/// * `high` - Highest acceptable fundamental in Hz
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn get_pitch(input_path: &Path, output_path: &Path, low: f64, high: f64) -> Result<()> {
+    check_range(low, high)?;
+    let input = AnaFile::load(input_path)?;
+    get_pitch_frames(&input, low, high)?.save(output_path)?;
+    Ok(())
+}
+
+/// Track the pitch of in-memory spectral frames
+///
+/// Buffer-based counterpart of [`get_pitch`]. The loudest peaks of each
+/// window are tested as harmonics of candidate fundamentals in the range;
+/// the highest candidate explaining the most peak amplitude wins, refined
+/// from the frequencies of the peaks it explains. Windows whose peaks are
+/// mostly inharmonic are marked [`NOT_PITCH`] and near-silent windows
+/// [`NOT_SOUND`].
+pub fn get_pitch_frames(input: &AnaFile, low: f64, high: f64) -> Result<PitchFile> {
+    check_range(low, high)?;
+    if input.num_frames() == 0 {
+        return Err(SpectralError::InvalidInput(
+            "Input file has no spectral data".to_string(),
+        ));
+    }
+
+    let levels: Vec<f32> = input
+        .frames()
+        .map(|frame| frame.values().iter().step_by(2).sum())
+        .collect();
+    let loudest = levels.iter().fold(0.0f32, |max, &level| max.max(level));
+
+    let pitches = input
+        .frames()
+        .zip(&levels)
+        .map(|(frame, &level)| {
+            if level <= 0.0 || level < loudest * SILENCE_RATIO {
+                NOT_SOUND
+            } else {
+                window_pitch(frame.values(), low as f32, high as f32)
+            }
+        })
+        .collect();
+
+    Ok(PitchFile::new(input.header.clone(), pitches))
+}
+
+/// Fundamental of one window, or [`NOT_PITCH`]
+fn window_pitch(values: &[f32], low: f32, high: f32) -> f32 {
+    let num_bins = values.len() / 2;
+    let mut peaks: Vec<(f32, f32)> = (0..num_bins)
+        .filter(|&bin| {
+            let amp = values[bin * 2];
+            amp > 0.0
+                && (bin == 0 || amp >= values[(bin - 1) * 2])
+                && (bin + 1 == num_bins || amp > values[(bin + 1) * 2])
+        })
+        .map(|bin| (values[bin * 2], values[bin * 2 + 1].abs()))
+        .filter(|&(_, freq)| freq > 0.0)
+        .collect();
+    peaks.sort_by(|a, b| b.0.total_cmp(&a.0));
+    peaks.truncate(MAX_PEAKS);
+    let total: f32 = peaks.iter().map(|&(amp, _)| amp).sum();
+
+    // Best (score, fundamental), preferring the higher of equal scores so
+    // that subharmonics of the true fundamental are not chosen
+    let mut best: Option<(f32, f32)> = None;
+    for &(_, freq) in &peaks {
+        for harmonic in 1..=MAX_HARMONIC {
+            let candidate = freq / harmonic as f32;
+            if candidate < low || candidate > high {
+                continue;
+            }
+            let (score, _) = harmonic_fit(&peaks, candidate);
+            let better = match best {
+                None => true,
+                Some((best_score, best_freq)) => {
+                    score > best_score * (1.0 + 1e-4)
+                        || (score >= best_score * (1.0 - 1e-4) && candidate > best_freq)
+                }
+            };
+            if better {
+                best = Some((score, candidate));
+            }
+        }
+    }
+
+    match best {
+        Some((score, candidate)) if score >= total * VOICED_RATIO => {
+            harmonic_fit(&peaks, candidate).1
+        }
+        _ => NOT_PITCH,
+    }
+}
+
+/// Peak amplitude explained by harmonics of `fundamental`, and the
+/// amplitude-weighted fundamental implied by the peaks explained
+fn harmonic_fit(peaks: &[(f32, f32)], fundamental: f32) -> (f32, f32) {
+    let mut score = 0.0f32;
+    let mut weighted = 0.0f32;
+    for &(amp, freq) in peaks {
+        let ratio = freq / fundamental;
+        let harmonic = ratio.round();
+        if harmonic >= 1.0 && (ratio / harmonic - 1.0).abs() < HARMONIC_TOLERANCE {
+            score += amp;
+            weighted += amp * freq / harmonic;
+        }
+    }
+    let refined = if score > 0.0 {
+        weighted / score
+    } else {
+        fundamental
+    };
+    (score, refined)
+}
+
+/// Write pitch data as a text file of "time value" lines
+///
+/// # Arguments
+/// * `pitch_path` - Path to input pitch data file
+/// * `text_path` - Path to output text file
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn pitch_to_text(pitch_path: &Path, text_path: &Path) -> Result<()> {
+    let pitch = PitchFile::load(pitch_path)?;
+    cdp_core::fs::write(text_path, format_pitch_text(&pitch))?;
+    Ok(())
+}
+
+/// Format pitch data as text, one "time value" line per window
+///
+/// Buffer-based counterpart of [`pitch_to_text`]. Unpitched and silent
+/// windows keep their [`NOT_PITCH`] and [`NOT_SOUND`] values.
+pub fn format_pitch_text(pitch: &PitchFile) -> String {
+    let frame_rate = pitch.header.frame_rate() as f64;
+    let mut text = String::new();
+    for (window_idx, &value) in pitch.pitches.iter().enumerate() {
+        let _ = writeln!(text, "{:.6}\t{}", window_idx as f64 / frame_rate, value);
+    }
+    text
+}
+
+/// Convert a text file of "time value" pitch breakpoints to pitch data
+///
+/// The analysis settings, and so the window times, come from a reference
+/// analysis file, typically the one the pitch data will be applied to.
+///
+/// # Arguments
+/// * `text_path` - Path to input text file
+/// * `reference_path` - Path to .ana file supplying the analysis settings
+/// * `output_path` - Path to output pitch data file
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn text_to_pitch(text_path: &Path, reference_path: &Path, output_path: &Path) -> Result<()> {
+    let text = cdp_core::fs::read_to_string(text_path)?;
+    let reference = AnaFile::load(reference_path)?;
+    parse_pitch_text(&text, &reference.header)?.save(output_path)?;
+    Ok(())
+}
+
+/// Parse "time value" pitch breakpoints into pitch data
+///
+/// Buffer-based counterpart of [`text_to_pitch`]. Windows run from time
+/// zero to the last breakpoint. Between two pitches the value moves
+/// linearly in pitch (log frequency); a negative [`NOT_PITCH`] or
+/// [`NOT_SOUND`] value holds until the next breakpoint.
+pub fn parse_pitch_text(text: &str, header: &AnaHeader) -> Result<PitchFile> {
+    let values: Vec<f64> = text
+        .split_whitespace()
+        .map(|value| {
+            value.parse().map_err(|_| {
+                SpectralError::InvalidInput(format!("Invalid pitch value '{}'", value))
+            })
+        })
+        .collect::<Result<_>>()?;
+    if values.is_empty() || values.len() % 2 != 0 {
+        return Err(SpectralError::InvalidInput(
+            "Pitch text must hold time and value pairs".to_string(),
+        ));
+    }
+    let points: Vec<(f64, f64)> = values.chunks_exact(2).map(|p| (p[0], p[1])).collect();
+    let contour = Breakpoints::new(points)?;
+    check_pitches(&contour)?;
+
+    let frame_rate = header.frame_rate() as f64;
+    let num_windows = (contour.duration() * frame_rate + 1e-9).floor() as usize + 1;
+    let pitches = (0..num_windows)
+        .map(|window_idx| {
+            contour.value_at_with(window_idx as f64 / frame_rate, Interpolation::Pitch) as f32
+        })
+        .collect();
+
+    Ok(PitchFile::new(header.clone(), pitches))
+}
+
+/// Transpose a sound so its pitch follows the contour in a pitch data file
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `pitch_path` - Path to pitch data file giving the contour to follow
+/// * `output_path` - Path to output .ana file
+/// * `low` - Lowest fundamental in Hz when tracking the input's own pitch
+/// * `high` - Highest fundamental in Hz when tracking the input's own pitch
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn follow_pitch(
+    input_path: &Path,
+    pitch_path: &Path,
+    output_path: &Path,
+    low: f64,
+    high: f64,
+) -> Result<()> {
+    check_range(low, high)?;
+    let input = AnaFile::load(input_path)?;
+    let pitch = PitchFile::load(pitch_path)?;
+    follow_pitch_frames(&input, &pitch, low, high)?.save(output_path)?;
+    Ok(())
+}
+
+/// Transpose in-memory spectral frames to follow a pitch contour
+///
+/// Buffer-based counterpart of [`follow_pitch`]. Each window is shifted by
+/// the ratio of the contour's pitch at that time to the input's own tracked
+/// pitch. Where either is unpitched or silent the previous transposition is
+/// held, starting untransposed. If the contour is shorter than the input,
+/// its last value is held.
+pub fn follow_pitch_frames(
+    input: &AnaFile,
+    pitch: &PitchFile,
+    low: f64,
+    high: f64,
+) -> Result<AnaFile> {
+    if pitch.num_windows() == 0 {
+        return Err(SpectralError::InvalidInput(
+            "Pitch data file has no pitch data".to_string(),
+        ));
+    }
+    let own = get_pitch_frames(input, low, high)?;
+
+    let frame_rate = input.header.frame_rate() as f64;
+    let contour_rate = pitch.header.frame_rate() as f64;
+    let bin_width = input.header.bin_width();
+    let mut output = input.clone();
+    let mut factor = 1.0f64;
+    for ((window_idx, (out, frame)), &own_pitch) in output
+        .frames_mut()
+        .zip(input.frames())
+        .enumerate()
+        .zip(&own.pitches)
+    {
+        let time = window_idx as f64 / frame_rate;
+        let contour_idx = ((time * contour_rate).round() as usize).min(pitch.num_windows() - 1);
+        let target = pitch.pitches[contour_idx];
+        if own_pitch > 0.0 && target > 0.0 {
+            factor = target as f64 / own_pitch as f64;
+        }
+        shift_window(frame.values(), out, factor, bin_width);
+    }
+
+    Ok(output)
+}
+
+fn check_range(low: f64, high: f64) -> Result<()> {
+    if !(low > 0.0 && high > low && high.is_finite()) {
+        return Err(SpectralError::InvalidInput(
+            "Pitch range must be greater than 0 Hz, with the top above the bottom".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn check_pitches(contour: &Breakpoints) -> Result<()> {
+    if contour
+        .points()
+        .iter()
+        .any(|&(_, value)| value <= 0.0 && value != NOT_PITCH as f64 && value != NOT_SOUND as f64)
+    {
+        return Err(SpectralError::InvalidInput(format!(
+            "Pitch values must be greater than 0 Hz, or {} for no pitch and {} for silence",
+            NOT_PITCH, NOT_SOUND
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Harmonics of 201Hz, silence, then inharmonic peaks, 50Hz per channel
+    fn test_input() -> AnaFile {
+        let header = AnaHeader::with_hop(2000, 40, 2000);
+        let mut input = AnaFile::empty(header.clone()).unwrap();
+        for partials in [
+            &[(4, 1.0, 201.0), (8, 0.5, 402.0), (12, 0.3, 603.0)][..],
+            &[],
+            &[(5, 1.0, 250.0), (7, 1.0, 370.0), (11, 1.0, 530.0)],
+        ] {
+            let mut frame: Vec<f32> = (0..header.num_bins())
+                .flat_map(|bin| [0.0, bin as f32 * 50.0])
+                .collect();
+            for &(bin, amp, freq) in partials {
+                frame[bin * 2] = amp;
+                frame[bin * 2 + 1] = freq;
+            }
+            input.push_frame(&frame).unwrap();
+        }
+        input
+    }
+
+    #[test]
+    fn test_get_pitch_frames() {
+        let input = test_input();
+        assert!(get_pitch_frames(&input, 200.0, 100.0).is_err());
+
+        let pitch = get_pitch_frames(&input, 150.0, 1000.0).unwrap();
+        assert_eq!(pitch.pitches.len(), 3);
+        assert!((pitch.pitches[0] - 201.0).abs() < 1e-3);
+        assert_eq!(pitch.pitches[1], NOT_SOUND);
+        assert_eq!(pitch.pitches[2], NOT_PITCH);
+    }
+
+    #[test]
+    fn test_pitch_text_round_trip() {
+        let header = AnaHeader::with_hop(2000, 40, 1000);
+        let pitch = PitchFile::new(header.clone(), vec![100.0, 150.5, NOT_PITCH, NOT_SOUND]);
+        let text = format_pitch_text(&pitch);
+        assert_eq!(parse_pitch_text(&text, &header).unwrap(), pitch);
+
+        // Breakpoints are interpolated in pitch, and held from unpitched points
+        let parsed = parse_pitch_text("0 100 1 400 1.5 -1 2 200", &header).unwrap();
+        assert_eq!(parsed.pitches, vec![100.0, 200.0, 400.0, -1.0, 200.0]);
+        assert!(parse_pitch_text("0 100 0 200", &header).is_err());
+        assert!(parse_pitch_text("0 -3", &header).is_err());
+    }
+
+    #[test]
+    fn test_follow_pitch_frames() {
+        let input = test_input();
+        let contour = PitchFile::new(input.header.clone(), vec![301.5]);
+        let output = follow_pitch_frames(&input, &contour, 150.0, 1000.0).unwrap();
+
+        // The 201Hz harmonics move up by half to the contour's pitch
+        let frame = output.frame(0).unwrap();
+        assert!((frame.frequency(6) - 301.5).abs() < 1e-3);
+        assert!((frame.frequency(12) - 603.0).abs() < 1e-3);
+        assert!(frame.amplitude(6) > 0.9);
+
+        // The inharmonic window holds the transposition too
+        assert!(output.frame(2).unwrap().amplitude(5) < 0.5);
+    }
+}
@@ -0,0 +1,267 @@
+//! Spectral statistics reports
+//!
+//! Describes each analysis window by its centroid, spread, rolloff, flux
+//! and loudest partials, so analyses can be inspected without
+//! resynthesizing them.
+
+use crate::error::{Result, SpectralError};
+use cdp_core::AnaFile;
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Share of a window's amplitude lying below its rolloff frequency
+const ROLLOFF_FRACTION: f32 = 0.85;
+
+/// One partial of a window
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Partial {
+    /// Frequency in Hz
+    pub frequency: f32,
+    /// Amplitude
+    pub amplitude: f32,
+}
+
+/// Statistics of one analysis window
+///
+/// Silent windows report zero for every frequency statistic.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WindowReport {
+    /// Time of the window in seconds
+    pub time: f64,
+    /// Amplitude-weighted mean frequency in Hz
+    pub centroid: f32,
+    /// Amplitude-weighted standard deviation of frequency about the centroid, in Hz
+    pub spread: f32,
+    /// Frequency in Hz below which 85% of the window's amplitude lies
+    pub rolloff: f32,
+    /// Share (0-1) of the window's amplitude that rose since the previous window
+    pub flux: f32,
+    /// Loudest local amplitude peaks, loudest first
+    pub peaks: Vec<Partial>,
+}
+
+/// Statistics of every window of an analysis file
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpectralReport {
+    /// Sample rate of the analysed sound
+    pub sample_rate: u32,
+    /// Analysis FFT size
+    pub fft_size: usize,
+    /// Analysis windows per second
+    pub frame_rate: f32,
+    /// One report per analysis window
+    pub windows: Vec<WindowReport>,
+}
+
+impl SpectralReport {
+    /// Format as CSV, one row per window
+    ///
+    /// Peaks take a frequency and an amplitude column each, numbered from
+    /// the loudest; windows with fewer peaks leave the extra columns empty.
+    pub fn to_csv(&self) -> String {
+        let num_peaks = self
+            .windows
+            .iter()
+            .map(|w| w.peaks.len())
+            .max()
+            .unwrap_or(0);
+        let mut csv = String::from("time,centroid,spread,rolloff,flux");
+        for peak in 1..=num_peaks {
+            let _ = write!(csv, ",peak{0}_freq,peak{0}_amp", peak);
+        }
+        csv.push('\n');
+
+        for window in &self.windows {
+            let _ = write!(
+                csv,
+                "{},{},{},{},{}",
+                window.time, window.centroid, window.spread, window.rolloff, window.flux
+            );
+            for peak in 0..num_peaks {
+                match window.peaks.get(peak) {
+                    Some(partial) => {
+                        let _ = write!(csv, ",{},{}", partial.frequency, partial.amplitude);
+                    }
+                    None => csv.push_str(",,"),
+                }
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Format as pretty-printed JSON
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Write the report as a CSV file
+    pub fn save_csv(&self, path: &Path) -> Result<()> {
+        cdp_core::fs::write(path, self.to_csv())?;
+        Ok(())
+    }
+
+    /// Write the report as a JSON file
+    pub fn save_json(&self, path: &Path) -> Result<()> {
+        cdp_core::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+}
+
+/// Report the spectral statistics of an analysis file
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `num_peaks` - Number of loudest partials to list per window
+///
+/// # Returns
+/// * `Ok(SpectralReport)` on success
+/// * `Err(SpectralError)` on failure
+pub fn report(input_path: &Path, num_peaks: usize) -> Result<SpectralReport> {
+    let input = AnaFile::load(input_path)?;
+    report_frames(&input, num_peaks)
+}
+
+/// Report the spectral statistics of in-memory spectral frames
+///
+/// Buffer-based counterpart of [`report`]. Statistics use each channel's
+/// frequency rather than its centre, so they follow the partials exactly.
+/// Flux is measured as by [`detect_transients`](crate::detect_transients)
+/// and is zero for the first window.
+pub fn report_frames(input: &AnaFile, num_peaks: usize) -> Result<SpectralReport> {
+    if input.num_frames() == 0 {
+        return Err(SpectralError::InvalidInput(
+            "Input file has no spectral data".to_string(),
+        ));
+    }
+
+    let frame_rate = input.header.frame_rate();
+    let mut previous: Option<&[f32]> = None;
+    let windows = input
+        .frames()
+        .enumerate()
+        .map(|(window_idx, frame)| {
+            let values = frame.values();
+            let total: f32 = values.iter().step_by(2).sum();
+            let mut window = WindowReport {
+                time: window_idx as f64 / frame_rate as f64,
+                centroid: 0.0,
+                spread: 0.0,
+                rolloff: 0.0,
+                flux: 0.0,
+                peaks: window_peaks(values, num_peaks),
+            };
+
+            if total > 0.0 {
+                let pairs = || values.chunks_exact(2).map(|p| (p[0], p[1].abs()));
+                window.centroid = pairs().map(|(amp, freq)| amp * freq).sum::<f32>() / total;
+                window.spread = (pairs()
+                    .map(|(amp, freq)| amp * (freq - window.centroid).powi(2))
+                    .sum::<f32>()
+                    / total)
+                    .sqrt();
+
+                let mut cumulative = 0.0f32;
+                for (amp, freq) in pairs() {
+                    cumulative += amp;
+                    if cumulative >= total * ROLLOFF_FRACTION {
+                        window.rolloff = freq;
+                        break;
+                    }
+                }
+
+                if let Some(prev) = previous {
+                    let rise: f32 = values
+                        .iter()
+                        .step_by(2)
+                        .zip(prev.iter().step_by(2))
+                        .map(|(&amp, &prev_amp)| (amp - prev_amp).max(0.0))
+                        .sum();
+                    window.flux = rise / total;
+                }
+            }
+            previous = Some(values);
+            window
+        })
+        .collect();
+
+    Ok(SpectralReport {
+        sample_rate: input.header.sample_rate,
+        fft_size: input.header.fft_size(),
+        frame_rate,
+        windows,
+    })
+}
+
+/// Loudest `count` local amplitude peaks of a window, loudest first
+fn window_peaks(values: &[f32], count: usize) -> Vec<Partial> {
+    let num_bins = values.len() / 2;
+    let mut peaks: Vec<Partial> = (0..num_bins)
+        .filter(|&bin| {
+            let amp = values[bin * 2];
+            amp > 0.0
+                && (bin == 0 || amp >= values[(bin - 1) * 2])
+                && (bin + 1 == num_bins || amp > values[(bin + 1) * 2])
+        })
+        .map(|bin| Partial {
+            frequency: values[bin * 2 + 1],
+            amplitude: values[bin * 2],
+        })
+        .collect();
+    peaks.sort_by(|a, b| b.amplitude.total_cmp(&a.amplitude));
+    peaks.truncate(count);
+    peaks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cdp_core::AnaHeader;
+
+    #[test]
+    fn test_report_frames() {
+        // Five channels 100Hz apart, one window per second
+        let header = AnaHeader::with_hop(800, 8, 800);
+        let input = AnaFile::new(
+            header,
+            vec![
+                0.0, 0.0, 1.0, 100.0, 0.0, 200.0, 1.0, 300.0, 0.0, 400.0, // two partials
+                0.0, 0.0, 1.0, 100.0, 0.0, 200.0, 3.0, 300.0, 0.0, 400.0, // upper one rises
+                0.0, 0.0, 0.0, 100.0, 0.0, 200.0, 0.0, 300.0, 0.0, 400.0, // silence
+            ],
+        )
+        .unwrap();
+
+        let report = report_frames(&input, 1).unwrap();
+        let first = &report.windows[0];
+        assert_eq!(
+            (first.centroid, first.spread, first.rolloff, first.flux),
+            (200.0, 100.0, 300.0, 0.0)
+        );
+
+        let second = &report.windows[1];
+        assert_eq!(
+            (second.time, second.centroid, second.flux),
+            (1.0, 250.0, 0.5)
+        );
+        assert_eq!(
+            second.peaks,
+            vec![Partial {
+                frequency: 300.0,
+                amplitude: 3.0
+            }]
+        );
+        assert_eq!(report.windows[2].centroid, 0.0);
+        assert!(report.windows[2].peaks.is_empty());
+
+        let csv = report.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("time,centroid,spread,rolloff,flux,peak1_freq,peak1_amp")
+        );
+        assert_eq!(lines.nth(2), Some("2,0,0,0,0,,"));
+        assert!(report.to_json().unwrap().contains("\"centroid\": 250.0"));
+    }
+}
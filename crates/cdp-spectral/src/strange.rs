@@ -0,0 +1,356 @@
+//! Unusual spectral transformations
+//!
+//! Rearranges channel amplitudes or moves partials in ways that do not
+//! preserve the harmonic structure of the source, optionally oscillating
+//! between the source and the transformed spectrum.
+
+use crate::error::{Result, SpectralError};
+use cdp_core::{AnaFile, Breakpoints};
+use std::f64::consts::PI;
+use std::path::Path;
+
+/// State [`waver`] moves the spectrum towards
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum WaverTarget {
+    /// The amplitude-inverted spectrum, as from [`invert`]
+    Invert,
+    /// The spectrum shifted by this many Hz, folded into range, as from [`shift`]
+    Shift(f64),
+}
+
+/// Invert the amplitude ordering of the spectrum
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn invert(input_path: &Path, output_path: &Path) -> Result<()> {
+    let input = AnaFile::load(input_path)?;
+    invert_frames(&input)?.save(output_path)?;
+    Ok(())
+}
+
+/// Invert the amplitude ordering of in-memory spectral frames
+///
+/// Buffer-based counterpart of [`invert`]. In each window the loudest
+/// channel takes the quietest amplitude, the second loudest the second
+/// quietest, and so on, so the set of amplitudes is unchanged.
+pub fn invert_frames(input: &AnaFile) -> Result<AnaFile> {
+    let mut output = input.clone();
+    let mut order: Vec<usize> = Vec::with_capacity(input.header.num_bins());
+
+    for (frame, source) in output.frames_mut().zip(input.frames()) {
+        let num_bins = frame.len() / 2;
+        order.clear();
+        order.extend(0..num_bins);
+        order.sort_by(|&a, &b| source.amplitude(a).total_cmp(&source.amplitude(b)));
+
+        for (rank, &bin) in order.iter().enumerate() {
+            frame[bin * 2] = source.amplitude(order[num_bins - 1 - rank]);
+        }
+    }
+
+    Ok(output)
+}
+
+/// Shift every partial by a fixed number of Hz
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `shift_hz` - Frequency shift in Hz, up or down
+/// * `fold` - If true, partials pushed below 0 Hz or above Nyquist are
+///   reflected back into range; otherwise they are discarded
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn shift(input_path: &Path, output_path: &Path, shift_hz: f64, fold: bool) -> Result<()> {
+    check_finite(shift_hz, "Frequency shift")?;
+    let input = AnaFile::load(input_path)?;
+    shift_frames(&input, shift_hz, fold)?.save(output_path)?;
+    Ok(())
+}
+
+/// Shift every partial of in-memory spectral frames by a fixed number of Hz
+///
+/// Buffer-based counterpart of [`shift`]. Shifting by Hz rather than by a
+/// ratio makes harmonic sounds inharmonic.
+pub fn shift_frames(input: &AnaFile, shift_hz: f64, fold: bool) -> Result<AnaFile> {
+    check_finite(shift_hz, "Frequency shift")?;
+    let nyquist = input.header.sample_rate as f32 / 2.0;
+    let mut output = input.clone();
+
+    for (frame, source) in output.frames_mut().zip(input.frames()) {
+        move_partials(source.values(), frame, input.header.bin_width(), |freq| {
+            let shifted = freq + shift_hz as f32;
+            if (0.0..=nyquist).contains(&shifted) {
+                Some(shifted)
+            } else if fold {
+                Some(reflect(shifted, nyquist))
+            } else {
+                None
+            }
+        });
+    }
+
+    Ok(output)
+}
+
+/// Endless spectral glissando
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `rate` - Glissando speed in octaves per second (negative glides down)
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn glis(input_path: &Path, output_path: &Path, rate: f64) -> Result<()> {
+    check_finite(rate, "Glissando rate")?;
+    let input = AnaFile::load(input_path)?;
+    glis_frames(&input, rate)?.save(output_path)?;
+    Ok(())
+}
+
+/// Endless spectral glissando of in-memory spectral frames
+///
+/// Buffer-based counterpart of [`glis`]. Every partial glides at the same
+/// rate; the transposition wraps each octave, and partials carried above
+/// Nyquist drop by octaves until they fit, giving a Shepard-like effect.
+pub fn glis_frames(input: &AnaFile, rate: f64) -> Result<AnaFile> {
+    check_finite(rate, "Glissando rate")?;
+    let nyquist = input.header.sample_rate as f32 / 2.0;
+    let time_per_window = 1.0 / input.header.frame_rate() as f64;
+    let mut output = input.clone();
+
+    for (window_idx, (frame, source)) in output.frames_mut().zip(input.frames()).enumerate() {
+        let octaves = (rate * window_idx as f64 * time_per_window).rem_euclid(1.0);
+        let factor = 2.0f64.powf(octaves) as f32;
+        move_partials(source.values(), frame, input.header.bin_width(), |freq| {
+            let mut glided = freq * factor;
+            while glided > nyquist {
+                glided /= 2.0;
+            }
+            Some(glided)
+        });
+    }
+
+    Ok(output)
+}
+
+/// Oscillate the spectrum between its normal and a transformed state
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `rate` - Oscillation rate in Hz
+/// * `depths` - Vec of (time, depth) pairs; depth 0-1 is how far each cycle
+///   moves towards the transformed state
+/// * `target` - The transformed state
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn waver(
+    input_path: &Path,
+    output_path: &Path,
+    rate: f64,
+    depths: &[(f64, f64)],
+    target: WaverTarget,
+) -> Result<()> {
+    check_waver_params(rate, depths, target)?;
+    let input = AnaFile::load(input_path)?;
+    waver_frames(&input, rate, depths, target)?.save(output_path)?;
+    Ok(())
+}
+
+/// Oscillate in-memory spectral frames between normal and transformed states
+///
+/// Buffer-based counterpart of [`waver`]. Each cycle starts on the normal
+/// spectrum; amplitude and frequency of every channel move towards the
+/// transformed window along a raised cosine scaled by the depth.
+pub fn waver_frames(
+    input: &AnaFile,
+    rate: f64,
+    depths: &[(f64, f64)],
+    target: WaverTarget,
+) -> Result<AnaFile> {
+    check_waver_params(rate, depths, target)?;
+    let depths = Breakpoints::new(depths.to_vec())?;
+    let transformed = match target {
+        WaverTarget::Invert => invert_frames(input)?,
+        WaverTarget::Shift(shift_hz) => shift_frames(input, shift_hz, true)?,
+    };
+    let time_per_window = 1.0 / input.header.frame_rate() as f64;
+    let mut output = input.clone();
+
+    for (window_idx, (frame, other)) in output.frames_mut().zip(transformed.frames()).enumerate() {
+        let time = window_idx as f64 * time_per_window;
+        let swing = (1.0 - (2.0 * PI * rate * time).cos()) / 2.0;
+        let mix = (depths.value_at(time) * swing) as f32;
+        for (value, &target_value) in frame.iter_mut().zip(other.values()) {
+            *value += (target_value - *value) * mix;
+        }
+    }
+
+    Ok(output)
+}
+
+/// Move each partial of `source` to the channel nearest its new frequency
+///
+/// `map` gives the new frequency, or `None` to discard the partial. Where
+/// several partials land in one channel the loudest is kept; channels left
+/// empty are silent at their centre frequency.
+pub(crate) fn move_partials(
+    source: &[f32],
+    output: &mut [f32],
+    bin_width: f32,
+    mut map: impl FnMut(f32) -> Option<f32>,
+) {
+    for (bin, pair) in output.chunks_exact_mut(2).enumerate() {
+        pair[0] = 0.0;
+        pair[1] = bin as f32 * bin_width;
+    }
+
+    let num_bins = output.len() / 2;
+    for pair in source.chunks_exact(2) {
+        let (amp, freq) = (pair[0], pair[1]);
+        let Some(new_freq) = map(freq) else {
+            continue;
+        };
+        let bin = ((new_freq.abs() / bin_width).round() as usize).min(num_bins - 1);
+        if amp > output[bin * 2] {
+            output[bin * 2] = amp;
+            output[bin * 2 + 1] = new_freq;
+        }
+    }
+}
+
+/// Reflect a frequency at 0 Hz and Nyquist until it lies in range
+fn reflect(freq: f32, nyquist: f32) -> f32 {
+    let period = nyquist * 2.0;
+    let wrapped = freq.rem_euclid(period);
+    if wrapped > nyquist {
+        period - wrapped
+    } else {
+        wrapped
+    }
+}
+
+fn check_waver_params(rate: f64, depths: &[(f64, f64)], target: WaverTarget) -> Result<()> {
+    if !(rate > 0.0 && rate.is_finite()) {
+        return Err(SpectralError::InvalidInput(
+            "Waver rate must be greater than 0".to_string(),
+        ));
+    }
+    if depths.is_empty() {
+        return Err(SpectralError::InvalidInput(
+            "Waver depths must not be empty".to_string(),
+        ));
+    }
+    if depths
+        .iter()
+        .any(|&(_, depth)| !(0.0..=1.0).contains(&depth))
+    {
+        return Err(SpectralError::InvalidInput(
+            "Waver depth must be between 0 and 1".to_string(),
+        ));
+    }
+    if let WaverTarget::Shift(shift_hz) = target {
+        check_finite(shift_hz, "Frequency shift")?;
+    }
+    Ok(())
+}
+
+fn check_finite(value: f64, name: &str) -> Result<()> {
+    if !value.is_finite() {
+        return Err(SpectralError::InvalidInput(format!(
+            "{} must be a finite number",
+            name
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cdp_core::AnaHeader;
+
+    #[test]
+    fn test_strange_validation() {
+        let input = Path::new("test.ana");
+        let output = Path::new("out.ana");
+
+        assert!(shift(input, output, f64::NAN, false).is_err());
+        assert!(glis(input, output, f64::INFINITY).is_err());
+        assert!(waver(input, output, 0.0, &[(0.0, 1.0)], WaverTarget::Invert).is_err());
+        assert!(waver(input, output, 1.0, &[(0.0, 1.5)], WaverTarget::Invert).is_err());
+    }
+
+    #[test]
+    fn test_invert_shift_and_glis_frames() {
+        // Five bins 100Hz apart, one window per second
+        let header = AnaHeader::with_hop(800, 8, 800);
+        let pairs = [0.1, 0.0, 0.5, 100.0, 0.3, 200.0, 0.9, 300.0, 0.2, 400.0];
+        let input = AnaFile::new(header, [pairs, pairs].concat()).unwrap();
+
+        let output = invert_frames(&input).unwrap();
+        let amps: Vec<f32> = (0..5)
+            .map(|b| output.frame(0).unwrap().amplitude(b))
+            .collect();
+        assert_eq!(amps, vec![0.9, 0.2, 0.3, 0.1, 0.5]);
+
+        // Up 150Hz: 300Hz and 400Hz leave the range unless folded
+        let output = shift_frames(&input, 150.0, false).unwrap();
+        let frame = output.frame(0).unwrap();
+        assert_eq!(frame.pair(2), (0.1, 150.0));
+        assert_eq!(frame.pair(4), (0.3, 350.0));
+        let output = shift_frames(&input, 150.0, true).unwrap();
+        let frame = output.frame(0).unwrap();
+        assert_eq!(frame.pair(4), (0.9, 350.0));
+
+        // Half an octave per second: the second window is transposed
+        let output = glis_frames(&input, 0.5).unwrap();
+        assert_eq!(
+            output.frame(0).unwrap().values(),
+            input.frame(0).unwrap().values()
+        );
+        let factor = 2.0f32.sqrt();
+        let (amp, freq) = output.frame(1).unwrap().pair(1);
+        assert_eq!(amp, 0.5);
+        assert!((freq - 100.0 * factor).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_waver_frames() {
+        // Four windows per second, one cycle per second
+        let header = AnaHeader::with_hop(800, 8, 200);
+        let pairs = [0.1, 0.0, 0.5, 100.0, 0.3, 200.0, 0.9, 300.0, 0.2, 400.0];
+        let input = AnaFile::new(header, [pairs; 3].concat()).unwrap();
+        let inverted = invert_frames(&input).unwrap();
+
+        // Normal at the start of the cycle, fully inverted half way through
+        let output = waver_frames(&input, 1.0, &[(0.0, 1.0)], WaverTarget::Invert).unwrap();
+        assert_eq!(output.frame(0).unwrap().values(), &pairs);
+        let middle = output.frame(2).unwrap();
+        for (value, expected) in middle
+            .values()
+            .iter()
+            .zip(inverted.frame(2).unwrap().values())
+        {
+            assert!((value - expected).abs() < 1e-6);
+        }
+
+        // Half depth goes half way
+        let output = waver_frames(&input, 1.0, &[(0.0, 0.5)], WaverTarget::Invert).unwrap();
+        assert!((output.frame(2).unwrap().amplitude(3) - 0.5).abs() < 1e-6);
+    }
+}
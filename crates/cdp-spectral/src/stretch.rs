@@ -2,8 +2,10 @@
 //!
 //! Stretches or compresses time without changing pitch.
 
-use crate::ana_io::{read_ana_file, write_ana_file};
-use crate::error::{Result, SpectralError};
+use crate::error::{check_param, write_frames, Result, SpectralError};
+use cdp_core::progress::{self, ProgressSink};
+use cdp_core::{AnaFile, AnaFrameReader, AnaFrameWriter, Breakpoints, Param};
+use std::io::{Read, Seek, Write};
 use std::path::Path;
 
 /// Time-stretch a spectral file
@@ -17,21 +19,202 @@ use std::path::Path;
 /// * `Ok(())` on success
 /// * `Err(SpectralError)` on failure
 pub fn stretch_time(input_path: &Path, output_path: &Path, stretch_factor: f64) -> Result<()> {
-    // Validate stretch factor
-    if stretch_factor <= 0.0 {
-        return Err(SpectralError::InvalidInput(
-            "Stretch factor must be greater than 0".to_string(),
-        ));
+    stretch_time_with_progress(input_path, output_path, stretch_factor, &mut ())
+}
+
+/// Time-stretch a spectral file, reporting each output window to `progress`
+///
+/// As [`stretch_time`]. Stops with [`cdp_core::CoreError::Cancelled`] if
+/// the sink asks. A failed or cancelled run leaves no output file.
+pub fn stretch_time_with_progress(
+    input_path: &Path,
+    output_path: &Path,
+    stretch_factor: f64,
+    progress: &mut dyn ProgressSink,
+) -> Result<()> {
+    let params = StretchParams {
+        factor: stretch_factor,
+        ..StretchParams::default()
+    };
+    stretch_time_with_params(input_path, output_path, &params, progress)
+}
+
+/// Settings for [`stretch_time_with_params`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct StretchParams {
+    /// Time stretch factor (>1 = slower, <1 = faster)
+    pub factor: f64,
+    /// Spectral flux (0-1) above which a window is an attack left
+    /// unstretched, as in [`stretch_time_preserving`]
+    pub transient_threshold: Option<f64>,
+}
+
+impl Default for StretchParams {
+    /// No stretch, with attacks stretched like everything else
+    fn default() -> Self {
+        Self {
+            factor: 1.0,
+            transient_threshold: None,
+        }
+    }
+}
+
+impl StretchParams {
+    /// Start from the defaults
+    pub fn builder() -> StretchParamsBuilder {
+        StretchParamsBuilder(Self::default())
     }
 
-    if !(0.01..=100.0).contains(&stretch_factor) {
+    /// Check every setting against its valid range
+    fn check(&self) -> Result<()> {
+        check_stretch_factor(self.factor)?;
+        if let Some(threshold) = self.transient_threshold {
+            check_threshold(threshold)?;
+        }
+        Ok(())
+    }
+}
+
+/// Builder for [`StretchParams`]
+#[derive(Debug, Clone, Copy)]
+pub struct StretchParamsBuilder(StretchParams);
+
+impl StretchParamsBuilder {
+    /// Time stretch factor (>1 = slower, <1 = faster)
+    pub fn factor(mut self, factor: f64) -> Self {
+        self.0.factor = factor;
+        self
+    }
+
+    /// Leave windows whose spectral flux exceeds `threshold` (0-1)
+    /// unstretched
+    pub fn preserve_transients(mut self, threshold: f64) -> Self {
+        self.0.transient_threshold = Some(threshold);
+        self
+    }
+
+    /// Finish, checking the settings
+    pub fn build(self) -> Result<StretchParams> {
+        self.0.check()?;
+        Ok(self.0)
+    }
+}
+
+/// Time-stretch a spectral file with the given settings, reporting each
+/// output window to `progress`
+///
+/// Plain stretches stream the file; preserving transients loads it whole.
+/// Stops with [`cdp_core::CoreError::Cancelled`] if the sink asks. A
+/// failed or cancelled run leaves no output file; pass `&mut ()` to ignore
+/// progress.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "info",
+        skip_all,
+        fields(input = %input_path.display(), output = %output_path.display()),
+        err
+    )
+)]
+pub fn stretch_time_with_params(
+    input_path: &Path,
+    output_path: &Path,
+    params: &StretchParams,
+    progress: &mut dyn ProgressSink,
+) -> Result<()> {
+    params.check()?;
+    if let Some(threshold) = params.transient_threshold {
+        let input = AnaFile::load(input_path)?;
+        preserve_transients(&input, params.factor, threshold, progress)?.save(output_path)?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(frames = input.num_frames(), "frames processed");
+        return Ok(());
+    }
+
+    let mut reader = AnaFrameReader::open(input_path)?;
+    let header = reader.header().clone();
+    write_frames(output_path, header, |writer| {
+        stream_stretch(&mut reader, writer, params.factor, progress)
+    })?;
+    #[cfg(feature = "tracing")]
+    tracing::debug!(frames = reader.num_frames(), "frames processed");
+    Ok(())
+}
+
+/// Time-stretch spectral frames read one at a time from `reader`
+///
+/// Streaming counterpart of [`stretch_time`]: only the two input frames
+/// being interpolated are held in memory. The writer is left for the
+/// caller to finalize.
+pub fn stretch_time_stream<R: Read + Seek, W: Write + Seek>(
+    reader: &mut AnaFrameReader<R>,
+    writer: &mut AnaFrameWriter<W>,
+    stretch_factor: f64,
+) -> Result<()> {
+    stream_stretch(reader, writer, stretch_factor, &mut ())
+}
+
+/// [`stretch_time_stream`], reporting each output window to `progress`
+fn stream_stretch<R: Read + Seek, W: Write + Seek>(
+    reader: &mut AnaFrameReader<R>,
+    writer: &mut AnaFrameWriter<W>,
+    stretch_factor: f64,
+    progress: &mut dyn ProgressSink,
+) -> Result<()> {
+    check_stretch_factor(stretch_factor)?;
+    let window_size = reader.header().channels as usize;
+    let num_windows = reader.num_frames();
+
+    if num_windows == 0 {
         return Err(SpectralError::InvalidInput(
-            "Stretch factor must be between 0.01 and 100".to_string(),
+            "Input file has no spectral data".to_string(),
         ));
     }
 
-    // Read input .ana file
-    let (header, samples) = read_ana_file(input_path)?;
+    // Input frames `current_idx` and `current_idx + 1`
+    let mut current = vec![0.0f32; window_size];
+    let mut next = vec![0.0f32; window_size];
+    let mut current_idx = 0;
+    reader.read_frame(&mut current)?;
+    reader.read_frame(&mut next)?;
+
+    let output_windows = (num_windows as f64 * stretch_factor).round() as usize;
+    let mut output = Vec::with_capacity(window_size);
+
+    for out_idx in 0..output_windows {
+        let input_pos = out_idx as f64 / stretch_factor;
+        let input_idx = input_pos.floor() as usize;
+        let frac = input_pos - input_idx as f64;
+
+        while current_idx < input_idx.min(num_windows - 1) {
+            std::mem::swap(&mut current, &mut next);
+            current_idx += 1;
+            reader.read_frame(&mut next)?;
+        }
+
+        output.clear();
+        if input_idx >= num_windows - 1 {
+            // Use last window
+            output.extend_from_slice(&current);
+        } else {
+            interpolate_frames(&current, &next, frac as f32, &mut output);
+        }
+        writer.write_frame(&output)?;
+        progress::report(progress, out_idx + 1, output_windows)?;
+    }
+
+    Ok(())
+}
+
+/// Time-stretch in-memory spectral frames
+///
+/// Buffer-based counterpart of [`stretch_time`].
+pub fn stretch_time_frames(input: &AnaFile, stretch_factor: f64) -> Result<AnaFile> {
+    check_stretch_factor(stretch_factor)?;
+    let header = &input.header;
+    let samples = &input.data;
 
     // Calculate window size (samples per window)
     let window_size = header.channels as usize;
@@ -64,43 +247,167 @@ pub fn stretch_time(input_path: &Path, output_path: &Path, stretch_factor: f64)
             // Interpolate between two adjacent windows
             let window1_start = input_idx * window_size;
             let window2_start = (input_idx + 1) * window_size;
+            interpolate_frames(
+                &samples[window1_start..window1_start + window_size],
+                &samples[window2_start..window2_start + window_size],
+                frac as f32,
+                &mut output,
+            );
+        }
+    }
 
-            // Process each channel (real/imaginary pairs)
-            for chan in 0..window_size / 2 {
-                let real_idx = chan * 2;
-                let imag_idx = chan * 2 + 1;
+    Ok(AnaFile::new(header.clone(), output)?)
+}
 
-                // Get complex values from both windows
-                let real1 = samples[window1_start + real_idx];
-                let imag1 = samples[window1_start + imag_idx];
-                let real2 = samples[window2_start + real_idx];
-                let imag2 = samples[window2_start + imag_idx];
+/// Time-stretch a spectral file, leaving attacks unstretched
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `stretch_factor` - Time stretch factor (>1 = slower, <1 = faster)
+/// * `threshold` - Spectral flux (0-1) above which a window is a transient
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn stretch_time_preserving(
+    input_path: &Path,
+    output_path: &Path,
+    stretch_factor: f64,
+    threshold: f64,
+) -> Result<()> {
+    let params = StretchParams {
+        factor: stretch_factor,
+        transient_threshold: Some(threshold),
+    };
+    stretch_time_with_params(input_path, output_path, &params, &mut ())
+}
 
-                // Convert to polar
-                let (mag1, phase1) = rect_to_polar(real1, imag1);
-                let (mag2, phase2) = rect_to_polar(real2, imag2);
+/// Time-stretch in-memory spectral frames, leaving attacks unstretched
+///
+/// Buffer-based counterpart of [`stretch_time_preserving`]. Windows found
+/// by [`detect_transients`] are copied once each; the remaining windows
+/// are stretched by whatever is needed to keep the overall duration at
+/// `stretch_factor` times the input.
+pub fn stretch_time_preserving_frames(
+    input: &AnaFile,
+    stretch_factor: f64,
+    threshold: f64,
+) -> Result<AnaFile> {
+    preserve_transients(input, stretch_factor, threshold, &mut ())
+}
 
-                // Interpolate magnitude
-                let mag = mag1 + (mag2 - mag1) * frac as f32;
+/// [`stretch_time_preserving_frames`], reporting each output window to
+/// `progress`
+fn preserve_transients(
+    input: &AnaFile,
+    stretch_factor: f64,
+    threshold: f64,
+    progress: &mut dyn ProgressSink,
+) -> Result<AnaFile> {
+    check_stretch_factor(stretch_factor)?;
+    let transients = detect_transients(input, threshold)?;
+    let num_windows = input.num_frames();
 
-                // Interpolate phase (with unwrapping)
-                let phase = interpolate_phase(phase1, phase2, frac as f32);
+    if num_windows == 0 {
+        return Err(SpectralError::InvalidInput(
+            "Input file has no spectral data".to_string(),
+        ));
+    }
 
-                // Convert back to rectangular
-                let (real, imag) = polar_to_rect(mag, phase);
+    // Output windows given to each input window
+    let target = num_windows as f64 * stretch_factor;
+    let num_transients = transients.iter().filter(|&&t| t).count();
+    let steady_factor = if num_transients < num_windows {
+        ((target - num_transients as f64) / (num_windows - num_transients) as f64).max(0.01)
+    } else {
+        1.0
+    };
+    let durations: Vec<f64> = transients
+        .iter()
+        .map(|&t| if t { 1.0 } else { steady_factor })
+        .collect();
+    let output_windows = durations.iter().sum::<f64>().round() as usize;
+
+    let mut output = AnaFile::empty(input.header.clone())?;
+    let mut out_frame = Vec::with_capacity(input.frame_len());
+    let mut input_idx = 0;
+    let mut input_start = 0.0;
+    for out_idx in 0..output_windows {
+        let out_pos = out_idx as f64;
+        while input_idx < num_windows - 1 && out_pos >= input_start + durations[input_idx] {
+            input_start += durations[input_idx];
+            input_idx += 1;
+        }
 
-                output.push(real);
-                output.push(imag);
-            }
+        out_frame.clear();
+        let current = input.frame(input_idx).unwrap().values();
+        if transients[input_idx] || input_idx == num_windows - 1 {
+            out_frame.extend_from_slice(current);
+        } else {
+            let frac = ((out_pos - input_start) / durations[input_idx]).min(1.0);
+            let next = input.frame(input_idx + 1).unwrap().values();
+            interpolate_frames(current, next, frac as f32, &mut out_frame);
         }
+        output.push_frame(&out_frame)?;
+        progress::report(progress, out_idx + 1, output_windows)?;
+    }
+
+    Ok(output)
+}
+
+/// Find transient windows by spectral flux
+///
+/// The flux of a window is the summed amplitude increase over the previous
+/// window, as a proportion of the window's total amplitude. Windows whose
+/// flux exceeds `threshold` (0-1) are reported as transients.
+pub fn detect_transients(input: &AnaFile, threshold: f64) -> Result<Vec<bool>> {
+    check_threshold(threshold)?;
+    let mut transients = Vec::with_capacity(input.num_frames());
+    let mut previous: Option<&[f32]> = None;
+
+    for frame in input.frames() {
+        let values = frame.values();
+        let is_transient = previous.is_some_and(|prev| {
+            let total: f32 = values.iter().step_by(2).sum();
+            let rise: f32 = values
+                .iter()
+                .step_by(2)
+                .zip(prev.iter().step_by(2))
+                .map(|(&amp, &prev_amp)| (amp - prev_amp).max(0.0))
+                .sum();
+            total > 0.0 && (rise / total) as f64 > threshold
+        });
+        transients.push(is_transient);
+        previous = Some(values);
     }
 
-    // Write output .ana file
-    write_ana_file(output_path, &header, &output)?;
+    Ok(transients)
+}
 
+fn check_threshold(threshold: f64) -> Result<()> {
+    if !(threshold > 0.0 && threshold < 1.0) {
+        return Err(SpectralError::InvalidInput(
+            "Transient threshold must be between 0 and 1".to_string(),
+        ));
+    }
     Ok(())
 }
 
+/// Interpolate two adjacent windows, appending to `output`
+///
+/// Amplitudes and frequencies of each bin are interpolated linearly; the
+/// phase is rebuilt from the frequency track at resynthesis.
+fn interpolate_frames(window1: &[f32], window2: &[f32], frac: f32, output: &mut Vec<f32>) {
+    for (&value1, &value2) in window1.iter().zip(window2) {
+        output.push(value1 + (value2 - value1) * frac);
+    }
+}
+
+fn check_stretch_factor(stretch_factor: f64) -> Result<()> {
+    check_param(Param::StretchFactor, stretch_factor)
+}
+
 /// Apply time-varying stretch to spectrum
 ///
 /// # Arguments
@@ -116,29 +423,29 @@ pub fn stretch_time_varying(
     output_path: &Path,
     stretch_values: &[(f64, f64)],
 ) -> Result<()> {
-    if stretch_values.is_empty() {
-        return Err(SpectralError::InvalidInput(
-            "Stretch values must not be empty".to_string(),
-        ));
-    }
-
-    // Validate all stretch factors
-    for (_, stretch) in stretch_values {
-        if *stretch <= 0.0 || *stretch < 0.01 || *stretch > 100.0 {
-            return Err(SpectralError::InvalidInput(
-                "All stretch factors must be between 0.01 and 100".to_string(),
-            ));
-        }
-    }
+    check_stretch_values(stretch_values)?;
+    let input = AnaFile::load(input_path)?;
+    stretch_time_varying_frames(&input, stretch_values)?.save(output_path)?;
+    Ok(())
+}
 
-    // Read input .ana file
-    let (header, samples) = read_ana_file(input_path)?;
+/// Apply time-varying stretch to in-memory spectral frames
+///
+/// Buffer-based counterpart of [`stretch_time_varying`].
+pub fn stretch_time_varying_frames(
+    input: &AnaFile,
+    stretch_values: &[(f64, f64)],
+) -> Result<AnaFile> {
+    check_stretch_values(stretch_values)?;
+    let stretch_values = Breakpoints::new(stretch_values.to_vec())?;
+    let header = &input.header;
+    let samples = &input.data;
 
     let window_size = header.channels as usize;
     let num_windows = samples.len() / window_size;
 
     // Calculate time per window from header metadata
-    let hop_size = header.window_len / header.dec_factor;
+    let hop_size = header.hop_size();
     let time_per_window = hop_size as f64 / header.sample_rate as f64;
 
     // Calculate total output windows needed
@@ -147,7 +454,7 @@ pub fn stretch_time_varying(
     let mut input_window = 0.0;
 
     while input_window < num_windows as f64 - 1.0 {
-        let stretch = interpolate_stretch_value(current_time, stretch_values);
+        let stretch = stretch_values.value_at(current_time);
         let step = 1.0 / stretch;
         input_window += step;
         current_time = input_window * time_per_window;
@@ -162,7 +469,7 @@ pub fn stretch_time_varying(
     input_window = 0.0;
 
     for _ in 0..output_windows {
-        let stretch = interpolate_stretch_value(current_time, stretch_values);
+        let stretch = stretch_values.value_at(current_time);
 
         // Get integer and fractional parts
         let input_idx = input_window.floor() as usize;
@@ -178,27 +485,12 @@ pub fn stretch_time_varying(
             // Interpolate between windows
             let window1_start = input_idx * window_size;
             let window2_start = (input_idx + 1) * window_size;
-
-            for chan in 0..window_size / 2 {
-                let real_idx = chan * 2;
-                let imag_idx = chan * 2 + 1;
-
-                let real1 = samples[window1_start + real_idx];
-                let imag1 = samples[window1_start + imag_idx];
-                let real2 = samples[window2_start + real_idx];
-                let imag2 = samples[window2_start + imag_idx];
-
-                let (mag1, phase1) = rect_to_polar(real1, imag1);
-                let (mag2, phase2) = rect_to_polar(real2, imag2);
-
-                let mag = mag1 + (mag2 - mag1) * frac as f32;
-                let phase = interpolate_phase(phase1, phase2, frac as f32);
-
-                let (real, imag) = polar_to_rect(mag, phase);
-
-                output.push(real);
-                output.push(imag);
-            }
+            interpolate_frames(
+                &samples[window1_start..window1_start + window_size],
+                &samples[window2_start..window2_start + window_size],
+                frac as f32,
+                &mut output,
+            );
         }
 
         // Advance input position
@@ -207,100 +499,38 @@ pub fn stretch_time_varying(
         current_time = input_window * time_per_window;
     }
 
-    // Write output
-    write_ana_file(output_path, &header, &output)?;
+    Ok(AnaFile::new(header.clone(), output)?)
+}
 
+fn check_stretch_values(stretch_values: &[(f64, f64)]) -> Result<()> {
+    if stretch_values.is_empty() {
+        return Err(SpectralError::InvalidInput(
+            "Stretch values must not be empty".to_string(),
+        ));
+    }
+
+    // Validate all stretch factors
+    for &(_, stretch) in stretch_values {
+        check_stretch_factor(stretch)?;
+    }
     Ok(())
 }
 
 /// Calculate output duration for a given stretch
 pub fn calculate_output_duration(input_path: &Path, stretch_factor: f64) -> Result<f64> {
     // Open input to get duration
-    let (header, samples) = read_ana_file(input_path)?;
+    let input = AnaFile::load(input_path)?;
 
-    let num_samples = samples.len() as f64;
-    let duration = num_samples / header.sample_rate as f64;
+    let num_samples = input.data.len() as f64;
+    let duration = num_samples / input.header.sample_rate as f64;
 
     Ok(duration * stretch_factor)
 }
 
-/// Convert rectangular to polar coordinates
-fn rect_to_polar(real: f32, imag: f32) -> (f32, f32) {
-    let mag = (real * real + imag * imag).sqrt();
-    let phase = imag.atan2(real);
-    (mag, phase)
-}
-
-/// Convert polar to rectangular coordinates
-fn polar_to_rect(mag: f32, phase: f32) -> (f32, f32) {
-    let real = mag * phase.cos();
-    let imag = mag * phase.sin();
-    (real, imag)
-}
-
-/// Interpolate phase with unwrapping
-fn interpolate_phase(phase1: f32, phase2: f32, frac: f32) -> f32 {
-    use std::f32::consts::PI;
-
-    // Unwrap phase difference
-    let mut diff = phase2 - phase1;
-    while diff > PI {
-        diff -= 2.0 * PI;
-    }
-    while diff < -PI {
-        diff += 2.0 * PI;
-    }
-
-    // Linear interpolation
-    let mut phase = phase1 + diff * frac;
-
-    // Wrap result to [-PI, PI]
-    while phase > PI {
-        phase -= 2.0 * PI;
-    }
-    while phase < -PI {
-        phase += 2.0 * PI;
-    }
-
-    phase
-}
-
-/// Helper function to interpolate stretch value at a given time
-fn interpolate_stretch_value(time: f64, stretch_values: &[(f64, f64)]) -> f64 {
-    // Find surrounding points
-    let mut prev = stretch_values[0];
-    let mut next = stretch_values[stretch_values.len() - 1];
-
-    for i in 0..stretch_values.len() - 1 {
-        if time >= stretch_values[i].0 && time <= stretch_values[i + 1].0 {
-            prev = stretch_values[i];
-            next = stretch_values[i + 1];
-            break;
-        }
-    }
-
-    // Before first point
-    if time < stretch_values[0].0 {
-        return stretch_values[0].1;
-    }
-
-    // After last point
-    if time > stretch_values[stretch_values.len() - 1].0 {
-        return stretch_values[stretch_values.len() - 1].1;
-    }
-
-    // Linear interpolation
-    if (next.0 - prev.0).abs() < 1e-10 {
-        return prev.1;
-    }
-
-    let ratio = (time - prev.0) / (next.0 - prev.0);
-    prev.1 + ratio * (next.1 - prev.1)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use cdp_core::AnaHeader;
 
     #[test]
     fn test_stretch_validation() {
@@ -322,29 +552,97 @@ mod tests {
     }
 
     #[test]
-    fn test_phase_interpolation() {
-        use std::f32::consts::PI;
-
-        // Test basic interpolation
-        let phase = interpolate_phase(0.0, PI / 2.0, 0.5);
-        assert!((phase - PI / 4.0).abs() < 1e-6);
+    fn test_stretch_progress_and_cancel() {
+        struct Windows(Vec<usize>);
+        impl ProgressSink for Windows {
+            fn progress(&mut self, done: usize, total: usize) {
+                assert_eq!(total, 8);
+                self.0.push(done);
+            }
+        }
 
-        // Test phase unwrapping
-        let phase = interpolate_phase(3.0 * PI / 4.0, -3.0 * PI / 4.0, 0.5);
-        assert!((phase - PI).abs() < 1e-6 || (phase + PI).abs() < 1e-6);
+        let dir = tempfile::TempDir::new().unwrap();
+        let input = dir.path().join("in.ana");
+        let output = dir.path().join("out.ana");
+        let header = AnaHeader::new(44100, 8, 3).unwrap();
+        let frames = AnaFile::new(header.clone(), vec![0.5; header.channels as usize * 4]).unwrap();
+        frames.save(&input).unwrap();
+
+        let mut windows = Windows(Vec::new());
+        stretch_time_with_progress(&input, &output, 2.0, &mut windows).unwrap();
+        assert_eq!(windows.0, (1..=8).collect::<Vec<_>>());
+
+        let mut token = cdp_core::CancelToken::new();
+        token.cancel();
+        assert!(matches!(
+            stretch_time_with_progress(&input, &output, 2.0, &mut token),
+            Err(SpectralError::Core(cdp_core::CoreError::Cancelled))
+        ));
+        assert!(!output.exists());
     }
 
     #[test]
-    fn test_polar_conversion() {
-        // Test conversion roundtrip
-        let real = 3.0;
-        let imag = 4.0;
+    fn test_stretch_params() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let input = dir.path().join("in.ana");
+        let output = dir.path().join("out.ana");
+        let header = AnaHeader::new(44100, 8, 3).unwrap();
+        let mut data = vec![0.0; header.channels as usize * 2];
+        data.extend(vec![0.5; header.channels as usize * 4]);
+        AnaFile::new(header, data).unwrap().save(&input).unwrap();
+
+        let params = StretchParams::builder()
+            .factor(2.0)
+            .preserve_transients(0.5)
+            .build()
+            .unwrap();
+        let mut total = 0;
+        struct Total<'a>(&'a mut usize);
+        impl ProgressSink for Total<'_> {
+            fn progress(&mut self, _done: usize, total: usize) {
+                *self.0 = total;
+            }
+        }
+        stretch_time_with_params(&input, &output, &params, &mut Total(&mut total)).unwrap();
+        let expected = stretch_time_preserving_frames(&AnaFile::load(&input).unwrap(), 2.0, 0.5);
+        assert_eq!(AnaFile::load(&output).unwrap().data, expected.unwrap().data);
+        assert_eq!(total, 12);
+
+        assert_eq!(StretchParams::default().factor, 1.0);
+        assert!(StretchParams::builder().factor(0.0).build().is_err());
+        assert!(StretchParams::builder()
+            .preserve_transients(1.5)
+            .build()
+            .is_err());
+    }
 
-        let (mag, phase) = rect_to_polar(real, imag);
-        assert!((mag - 5.0).abs() < 1e-6);
+    #[test]
+    fn test_interpolate_frames() {
+        // Amplitude/frequency pairs for two bins
+        let window1 = [1.0, 100.0, 0.0, 200.0];
+        let window2 = [0.5, 110.0, 1.0, 220.0];
+
+        let mut output = Vec::new();
+        interpolate_frames(&window1, &window2, 0.5, &mut output);
+        assert_eq!(output, vec![0.75, 105.0, 0.5, 210.0]);
+    }
 
-        let (real2, imag2) = polar_to_rect(mag, phase);
-        assert!((real2 - real).abs() < 1e-6);
-        assert!((imag2 - imag).abs() < 1e-6);
+    #[test]
+    fn test_stretch_time_preserving_frames() {
+        // Quiet, a sudden attack in window 2, then steady decay
+        let header = AnaHeader::with_hop(800, 2, 200);
+        let amps = [0.1, 0.1, 1.0, 0.9, 0.8, 0.7];
+        let data = amps.iter().flat_map(|&a| [a, 0.0, 0.0, 400.0]).collect();
+        let input = AnaFile::new(header, data).unwrap();
+
+        let transients = detect_transients(&input, 0.5).unwrap();
+        assert_eq!(transients, vec![false, false, true, false, false, false]);
+
+        // Overall length still doubles, but the attack window appears once
+        let output = stretch_time_preserving_frames(&input, 2.0, 0.5).unwrap();
+        assert_eq!(output.num_frames(), 12);
+        let attack_copies = output.frames().filter(|f| f.amplitude(0) == 1.0).count();
+        assert_eq!(attack_copies, 1);
+        assert!(stretch_time_preserving_frames(&input, 2.0, 1.5).is_err());
     }
 }
@@ -0,0 +1,177 @@
+//! Spectral tuning operations
+//!
+//! Pulls the partials of a sound onto the harmonics of a set of pitches,
+//! like CDP's spec tune, harmonizing the spectrum to that set.
+
+use crate::error::{Result, SpectralError};
+use cdp_core::AnaFile;
+use std::path::Path;
+
+/// How the pitches given to [`tune`] are expressed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PitchUnits {
+    /// Frequencies in Hz
+    Hz,
+    /// MIDI note numbers (69 is A 440 Hz), fractional values allowed
+    Midi,
+}
+
+/// Tune the spectrum to the harmonics of a set of pitches
+///
+/// # Arguments
+/// * `input_path` - Path to input .ana file
+/// * `output_path` - Path to output .ana file
+/// * `pitches` - Pitches whose harmonics make up the tuning template
+/// * `units` - Whether `pitches` are in Hz or MIDI note numbers
+/// * `focus` - How far (0-1) partials move onto the nearest template harmonic
+/// * `clarity` - How much (0-1) channels away from the template are attenuated
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(SpectralError)` on failure
+pub fn tune(
+    input_path: &Path,
+    output_path: &Path,
+    pitches: &[f64],
+    units: PitchUnits,
+    focus: f64,
+    clarity: f64,
+) -> Result<()> {
+    check_tune_params(pitches, units, focus, clarity)?;
+    let input = AnaFile::load(input_path)?;
+    tune_frames(&input, pitches, units, focus, clarity)?.save(output_path)?;
+    Ok(())
+}
+
+/// Tune in-memory spectral frames to the harmonics of a set of pitches
+///
+/// Buffer-based counterpart of [`tune`]. Every channel's frequency moves
+/// `focus` of the way to the nearest harmonic, up to Nyquist, of any pitch
+/// in the set. Channels whose frequency starts more than one channel width
+/// from that harmonic belong to no template partial and have their
+/// amplitude scaled by `1 - clarity`.
+pub fn tune_frames(
+    input: &AnaFile,
+    pitches: &[f64],
+    units: PitchUnits,
+    focus: f64,
+    clarity: f64,
+) -> Result<AnaFile> {
+    check_tune_params(pitches, units, focus, clarity)?;
+
+    let nyquist = input.header.sample_rate as f64 / 2.0;
+    let mut template: Vec<f32> = Vec::new();
+    for &pitch in pitches {
+        let fundamental = match units {
+            PitchUnits::Hz => pitch,
+            PitchUnits::Midi => 440.0 * 2.0f64.powf((pitch - 69.0) / 12.0),
+        };
+        template.extend(
+            (1..)
+                .map(|harmonic| fundamental * harmonic as f64)
+                .take_while(|&freq| freq <= nyquist)
+                .map(|freq| freq as f32),
+        );
+    }
+    if template.is_empty() {
+        return Err(SpectralError::InvalidInput(
+            "Tuning pitches must lie below Nyquist".to_string(),
+        ));
+    }
+    template.sort_by(f32::total_cmp);
+    template.dedup();
+
+    let bin_width = input.header.bin_width();
+    let focus = focus as f32;
+    let attenuation = 1.0 - clarity as f32;
+    let mut output = input.clone();
+    for frame in output.frames_mut() {
+        for pair in frame.chunks_exact_mut(2) {
+            let freq = pair[1].abs();
+            let target = nearest(&template, freq);
+            if (target - freq).abs() > bin_width {
+                pair[0] *= attenuation;
+            }
+            pair[1] = freq + (target - freq) * focus;
+        }
+    }
+
+    Ok(output)
+}
+
+/// Member of a sorted, non-empty template closest to `freq`
+fn nearest(template: &[f32], freq: f32) -> f32 {
+    let upper = template.partition_point(|&t| t < freq);
+    match (upper.checked_sub(1), template.get(upper)) {
+        (Some(lower), Some(&above)) if above - freq < freq - template[lower] => above,
+        (Some(lower), _) => template[lower],
+        (None, _) => template[0],
+    }
+}
+
+fn check_tune_params(pitches: &[f64], units: PitchUnits, focus: f64, clarity: f64) -> Result<()> {
+    if pitches.is_empty() {
+        return Err(SpectralError::InvalidInput(
+            "Tuning pitches must not be empty".to_string(),
+        ));
+    }
+    let valid = |&pitch: &f64| match units {
+        PitchUnits::Hz => pitch > 0.0 && pitch.is_finite(),
+        PitchUnits::Midi => (0.0..=127.0).contains(&pitch),
+    };
+    if !pitches.iter().all(valid) {
+        return Err(SpectralError::InvalidInput(match units {
+            PitchUnits::Hz => "Tuning frequencies must be greater than 0 Hz".to_string(),
+            PitchUnits::Midi => "Tuning MIDI notes must be between 0 and 127".to_string(),
+        }));
+    }
+    if !(0.0..=1.0).contains(&focus) || !(0.0..=1.0).contains(&clarity) {
+        return Err(SpectralError::InvalidInput(
+            "Tuning focus and clarity must be between 0 and 1".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cdp_core::AnaHeader;
+
+    #[test]
+    fn test_tune_validation() {
+        let input = Path::new("test.ana");
+        let output = Path::new("out.ana");
+        assert!(tune(input, output, &[], PitchUnits::Hz, 1.0, 0.0).is_err());
+        assert!(tune(input, output, &[0.0], PitchUnits::Hz, 1.0, 0.0).is_err());
+        assert!(tune(input, output, &[128.0], PitchUnits::Midi, 1.0, 0.0).is_err());
+        assert!(tune(input, output, &[200.0], PitchUnits::Hz, 1.5, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_tune_frames() {
+        // Twenty-one channels 50Hz apart
+        let header = AnaHeader::with_hop(2000, 40, 2000);
+        let mut frame: Vec<f32> = (0..header.num_bins())
+            .flat_map(|bin| [1.0, bin as f32 * 50.0])
+            .collect();
+        frame[4 * 2 + 1] = 210.0;
+        frame[6 * 2 + 1] = 310.0;
+        frame[9 * 2 + 1] = 445.0;
+        let input = AnaFile::new(header, frame).unwrap();
+
+        // Half way onto harmonics of 200Hz, halving channels off the template
+        let output = tune_frames(&input, &[200.0], PitchUnits::Hz, 0.5, 0.5).unwrap();
+        let frame = output.frame(0).unwrap();
+        assert_eq!(frame.pair(4), (1.0, 205.0));
+        assert_eq!(frame.pair(6), (0.5, 355.0));
+
+        // MIDI 69 is 440Hz
+        let output = tune_frames(&input, &[69.0], PitchUnits::Midi, 1.0, 0.0).unwrap();
+        let frame = output.frame(0).unwrap();
+        assert_eq!(frame.pair(9), (1.0, 440.0));
+        assert_eq!(frame.pair(4), (1.0, 440.0));
+    }
+}
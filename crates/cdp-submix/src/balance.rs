@@ -0,0 +1,97 @@
+//! Mix level balancing
+//!
+//! Renders a mix to find its peak and works out the attenuation that
+//! keeps it below a ceiling, either as a dry-run report or applied to
+//! every element of the mix.
+
+use super::{Result, SubmixError};
+use crate::mix::Mix;
+
+/// Peak level of a mix and the gain needed to bring it under a ceiling
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BalanceReport {
+    /// Peak level of the mix as it stands
+    pub peak: f64,
+    /// Gain that brings the peak to the ceiling; 1 when it is already below
+    pub gain: f64,
+}
+
+impl BalanceReport {
+    /// The gain in dB, zero or negative
+    pub fn gain_db(&self) -> f64 {
+        20.0 * self.gain.log10()
+    }
+
+    /// Whether the mix would exceed the ceiling without attenuation
+    pub fn needs_attenuation(&self) -> bool {
+        self.gain < 1.0
+    }
+}
+
+impl Mix {
+    /// Report the attenuation needed to keep the mix under `ceiling`
+    /// without changing it
+    pub fn balance_report(&self, ceiling: f64) -> Result<BalanceReport> {
+        if !(ceiling > 0.0 && ceiling <= 1.0) {
+            return Err(SubmixError::InvalidParameter(
+                "Ceiling must be greater than 0 and at most 1".into(),
+            ));
+        }
+        let peak = self.render()?.peak();
+        // Compared at sample precision, so a balanced mix sits on the ceiling
+        let gain = if peak > ceiling as f32 {
+            ceiling / peak as f64
+        } else {
+            1.0
+        };
+        let peak = peak as f64;
+        Ok(BalanceReport { peak, gain })
+    }
+
+    /// Attenuate every element of the mix equally so it peaks no higher
+    /// than `ceiling`
+    ///
+    /// Mixes already under the ceiling are left alone. Returns the report
+    /// for the mix as it was.
+    pub fn balance(&mut self, ceiling: f64) -> Result<BalanceReport> {
+        let report = self.balance_report(ceiling)?;
+        if report.needs_attenuation() {
+            self.scale_gains(report.gain);
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cdp_housekeep::wav_cdp;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_balance() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("loud.wav");
+        let format = wav_cdp::WavFormat {
+            channels: 1,
+            sample_rate: 1000,
+            bits_per_sample: 32,
+            data_size: 0,
+        };
+        wav_cdp::write_wav_cdp_samples(&path, &format, &wav_cdp::Samples::F32(vec![0.8, -0.4]))
+            .unwrap();
+
+        // Two copies on top of each other peak at 1.6
+        let mut mix = Mix::new(1).unwrap();
+        mix.add(&path, 0.0, 1.0, 0.0).add(&path, 0.0, 1.0, 0.0);
+        let report = mix.balance_report(0.8).unwrap();
+        assert!((report.peak - 1.6).abs() < 1e-6);
+        assert!((report.gain - 0.5).abs() < 1e-6);
+        assert!((report.gain_db() + 6.0206).abs() < 1e-3);
+
+        mix.balance(0.8).unwrap();
+        let balanced = mix.balance_report(0.8).unwrap();
+        assert!((balanced.peak - 0.8).abs() < 1e-6);
+        assert!(!balanced.needs_attenuation());
+    }
+}
@@ -0,0 +1,244 @@
+//! Crossfading two sounds
+//!
+//! Mixes two sounds so the first gives way to the second over a fade
+//! region, like CDP's submix crossfade.
+
+use super::{Result, SubmixError};
+use cdp_core::AudioBuffer;
+use cdp_housekeep::wav_cdp;
+use std::path::Path;
+
+/// Shape of the crossfade
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CrossfadeCurve {
+    /// Straight-line gains that always sum to one
+    Linear,
+    /// Raised-cosine gains that sum to one, easing in and out of the fade
+    Cosine,
+    /// Sine and cosine gains whose powers sum to one, for uncorrelated
+    /// sounds that would dip in the middle of a linear fade
+    EqualPower,
+}
+
+impl CrossfadeCurve {
+    /// Gains of the outgoing and incoming sound at `position` (0-1) through
+    /// the fade
+    pub fn gains(self, position: f64) -> (f64, f64) {
+        let position = position.clamp(0.0, 1.0);
+        match self {
+            CrossfadeCurve::Linear => (1.0 - position, position),
+            CrossfadeCurve::Cosine => {
+                let incoming = (1.0 - (position * std::f64::consts::PI).cos()) / 2.0;
+                (1.0 - incoming, incoming)
+            }
+            CrossfadeCurve::EqualPower => {
+                let angle = position * std::f64::consts::FRAC_PI_2;
+                (angle.cos(), angle.sin())
+            }
+        }
+    }
+}
+
+/// Settings for [`crossfade`]
+///
+/// Times are in seconds from the start of the output.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct CrossfadeOptions {
+    /// When the second sound starts
+    pub stagger: f64,
+    /// Start of the fade; `None` starts it with the second sound
+    pub begin: Option<f64>,
+    /// End of the fade; `None` ends it when the first sound ends
+    pub end: Option<f64>,
+    /// Shape of the fade
+    pub curve: CrossfadeCurve,
+}
+
+impl Default for CrossfadeOptions {
+    fn default() -> Self {
+        Self {
+            stagger: 0.0,
+            begin: None,
+            end: None,
+            curve: CrossfadeCurve::Linear,
+        }
+    }
+}
+
+/// Crossfade from one sound to another
+pub fn crossfade(
+    first: &Path,
+    second: &Path,
+    output: &Path,
+    options: &CrossfadeOptions,
+) -> Result<()> {
+    let (a, sample_type) = wav_cdp::read_audio_buffer(first)?;
+    let (b, _) = wav_cdp::read_audio_buffer(second)?;
+    wav_cdp::write_audio_buffer(output, &crossfade_buf(&a, &b, options)?, sample_type)?;
+    Ok(())
+}
+
+/// Crossfade from one in-memory sound to another
+///
+/// Buffer-based counterpart of [`crossfade`]. Only the first sound is
+/// heard before the fade and only the second after it. The output lasts
+/// until the later of the two ends, and both must share a sample rate and
+/// channel count.
+pub fn crossfade_buf(
+    first: &AudioBuffer,
+    second: &AudioBuffer,
+    options: &CrossfadeOptions,
+) -> Result<AudioBuffer> {
+    if first.sample_rate != second.sample_rate || first.channels != second.channels {
+        return Err(SubmixError::InvalidParameter(
+            "Sounds must share a sample rate and channel count".into(),
+        ));
+    }
+    if !(options.stagger >= 0.0 && options.stagger <= first.duration()) {
+        return Err(SubmixError::InvalidParameter(
+            "Stagger must be within the first sound".into(),
+        ));
+    }
+    let begin = options.begin.unwrap_or(options.stagger);
+    let end = options
+        .end
+        .unwrap_or_else(|| first.duration().min(options.stagger + second.duration()));
+    if !(begin >= options.stagger && begin <= end && end <= first.duration()) {
+        return Err(SubmixError::InvalidParameter(
+            "Fade must start after the second sound and end within the first".into(),
+        ));
+    }
+
+    let rate = first.sample_rate as f64;
+    let channels = first.channels as usize;
+    let stagger = (options.stagger * rate).round() as usize;
+    let begin = (begin * rate).round() as usize;
+    let end = (end * rate).round() as usize;
+    let frames = first.frame_count().max(stagger + second.frame_count());
+
+    let mut data = Vec::with_capacity(frames * channels);
+    for frame in 0..frames {
+        let (gain_a, gain_b) = if frame < begin {
+            (1.0, 0.0)
+        } else if frame >= end {
+            (0.0, 1.0)
+        } else {
+            options
+                .curve
+                .gains((frame - begin) as f64 / (end - begin) as f64)
+        };
+        for ch in 0..channels {
+            let a = first
+                .data
+                .get(frame * channels + ch)
+                .copied()
+                .unwrap_or(0.0);
+            let b = frame
+                .checked_sub(stagger)
+                .and_then(|offset| second.data.get(offset * channels + ch))
+                .copied()
+                .unwrap_or(0.0);
+            data.push(a * gain_a as f32 + b * gain_b as f32);
+        }
+    }
+    Ok(AudioBuffer::new(first.sample_rate, first.channels, data)?)
+}
+
+/// CLI compatibility layer for submix crossfade
+///
+/// Usage: `crossfade mode infile1 infile2 outfile [-sstagger] [-bbegin]
+/// [-eend]`, with mode 1 for a linear fade, 2 for a cosine fade and 3 for
+/// an equal-power fade.
+pub fn crossfade_cli(args: &[&str]) -> Result<()> {
+    if args.len() < 4 {
+        return Err(SubmixError::InvalidParameter(
+            "Usage: crossfade mode infile1 infile2 outfile [-sstagger] [-bbegin] [-eend]".into(),
+        ));
+    }
+    let curve = match args[0] {
+        "1" => CrossfadeCurve::Linear,
+        "2" => CrossfadeCurve::Cosine,
+        "3" => CrossfadeCurve::EqualPower,
+        mode => {
+            return Err(SubmixError::UnsupportedOperation(format!(
+                "Crossfade mode {} not yet implemented",
+                mode
+            )))
+        }
+    };
+    let number = |arg: &str| {
+        arg.parse::<f64>()
+            .map_err(|_| SubmixError::InvalidParameter(format!("Invalid value: {}", arg)))
+    };
+    let mut options = CrossfadeOptions {
+        curve,
+        ..CrossfadeOptions::default()
+    };
+    for arg in &args[4..] {
+        if let Some(value) = arg.strip_prefix("-s") {
+            options.stagger = number(value)?;
+        } else if let Some(value) = arg.strip_prefix("-b") {
+            options.begin = Some(number(value)?);
+        } else if let Some(value) = arg.strip_prefix("-e") {
+            options.end = Some(number(value)?);
+        } else {
+            return Err(SubmixError::InvalidParameter(format!(
+                "Unknown crossfade flag: {}",
+                arg
+            )));
+        }
+    }
+    crossfade(
+        Path::new(args[1]),
+        Path::new(args[2]),
+        Path::new(args[3]),
+        &options,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crossfade_buf() {
+        let first = AudioBuffer::new(10, 1, vec![1.0; 10]).unwrap();
+        let second = AudioBuffer::new(10, 1, vec![-1.0; 6]).unwrap();
+        let options = CrossfadeOptions {
+            stagger: 0.5,
+            begin: Some(0.6),
+            end: Some(1.0),
+            curve: CrossfadeCurve::Linear,
+        };
+        let output = crossfade_buf(&first, &second, &options).unwrap();
+        assert_eq!(
+            output.data,
+            vec![1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.5, 0.0, -0.5, -1.0]
+        );
+
+        let early = CrossfadeOptions {
+            begin: Some(0.2),
+            ..options
+        };
+        assert!(crossfade_buf(&first, &second, &early).is_err());
+    }
+
+    #[test]
+    fn test_curves() {
+        for curve in [
+            CrossfadeCurve::Linear,
+            CrossfadeCurve::Cosine,
+            CrossfadeCurve::EqualPower,
+        ] {
+            assert_eq!(curve.gains(0.0), (1.0, 0.0));
+            let (out, into) = curve.gains(1.0);
+            assert!(out.abs() < 1e-12 && (into - 1.0).abs() < 1e-12);
+        }
+        let (out, into) = CrossfadeCurve::EqualPower.gains(0.5);
+        assert!((out * out + into * into - 1.0).abs() < 1e-12);
+    }
+}
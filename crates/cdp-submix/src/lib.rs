@@ -0,0 +1,105 @@
+//! CDP Submix module - Multi-file mixing
+//!
+//! This module implements CDP's submix operations including:
+//! - Parsing CDP mixfiles of sound, start time, channels, level and pan
+//! - Checking the files a mix refers to before rendering
+//! - Rendering a mix to any number of output channels
+//! - Balancing a mix under a ceiling, with a dry-run attenuation report
+//! - Crossfading two sounds with linear, cosine or equal-power curves
+//!
+//! Mixes can also be built in code with [`Mix::add`].
+
+use std::path::Path;
+use thiserror::Error;
+
+pub mod balance;
+pub mod crossfade;
+pub mod mix;
+pub mod mixfile;
+
+/// Result type for submix operations
+pub type Result<T> = std::result::Result<T, SubmixError>;
+
+/// Errors that can occur during submix operations
+#[derive(Error, Debug)]
+pub enum SubmixError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Housekeep error: {0}")]
+    Housekeep(#[from] cdp_housekeep::HousekeepError),
+
+    #[error("Core error: {0}")]
+    Core(#[from] cdp_core::CoreError),
+
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
+
+    #[error("Invalid mixfile: {0}")]
+    InvalidMixfile(String),
+
+    #[error("Unsupported operation: {0}")]
+    UnsupportedOperation(String),
+}
+
+// Re-export main types for convenience
+pub use balance::BalanceReport;
+pub use crossfade::{crossfade, crossfade_buf, CrossfadeCurve, CrossfadeOptions};
+pub use mix::{Mix, MixEntry, Route, Routing};
+pub use mixfile::{parse_mixfile, MixLine};
+
+/// CLI compatibility layer - matches CDP's command-line interface
+/// This is just for oracle testing. Real users should use the library functions directly.
+pub fn submix(operation: &str, args: &[&str]) -> Result<()> {
+    match operation {
+        "mix" | "getlevel" => {
+            // Usage: mix mixfile outfile [-cchannels] [-b]
+            //        getlevel mixfile [-cchannels]
+            let files = if operation == "mix" { 2 } else { 1 };
+            if args.len() < files {
+                return Err(SubmixError::InvalidParameter(
+                    "Usage: mix mixfile outfile [-cchannels] [-b] | getlevel mixfile [-cchannels]"
+                        .into(),
+                ));
+            }
+            let mut channels = None;
+            let mut auto_balance = false;
+            for arg in &args[files..] {
+                if let Some(value) = arg.strip_prefix("-c") {
+                    channels = Some(value.parse::<u16>().map_err(|_| {
+                        SubmixError::InvalidParameter(format!("Invalid channel count: {}", value))
+                    })?);
+                } else if *arg == "-b" && operation == "mix" {
+                    auto_balance = true;
+                } else {
+                    return Err(SubmixError::InvalidParameter(format!(
+                        "Unknown {} flag: {}",
+                        operation, arg
+                    )));
+                }
+            }
+            let mut mix = Mix::from_mixfile(Path::new(args[0]), channels)?;
+            if operation == "getlevel" {
+                let report = mix.balance_report(1.0)?;
+                println!("Peak level {:.6}", report.peak);
+                if report.needs_attenuation() {
+                    println!(
+                        "Attenuate by {:.6} ({:.2} dB) to avoid clipping",
+                        report.gain,
+                        report.gain_db()
+                    );
+                }
+                return Ok(());
+            }
+            if auto_balance {
+                mix.balance(1.0)?;
+            }
+            mix.render_to(Path::new(args[1]))
+        }
+        "crossfade" => crossfade::crossfade_cli(args),
+        _ => Err(SubmixError::UnsupportedOperation(format!(
+            "Unknown operation: {}",
+            operation
+        ))),
+    }
+}
@@ -0,0 +1,333 @@
+//! Mixing engine
+//!
+//! Places sound files at start times, each channel routed to the output
+//! with its own gain and pan position, and sums them into one sound.
+
+use super::{Result, SubmixError};
+use crate::mixfile::parse_mixfile;
+use cdp_core::AudioBuffer;
+use cdp_housekeep::wav_cdp::{self, SampleType, WavReader};
+use std::path::{Path, PathBuf};
+
+/// Gain and pan position for one input channel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Route {
+    /// Linear gain
+    pub gain: f64,
+    /// Position from -1 (first output channel) to 1 (last)
+    pub pan: f64,
+}
+
+/// How an entry's channels reach the output
+#[derive(Debug, Clone, PartialEq)]
+pub enum Routing {
+    /// One gain and pan for the whole file; multichannel files keep their
+    /// spread, shifted by the pan
+    Panned(Route),
+    /// One route per input channel, which must match the file
+    PerChannel(Vec<Route>),
+}
+
+/// A sound file placed in a mix
+#[derive(Debug, Clone, PartialEq)]
+pub struct MixEntry {
+    /// Sound file to play
+    pub path: PathBuf,
+    /// Start time in seconds
+    pub at: f64,
+    /// How its channels reach the output
+    pub routing: Routing,
+}
+
+/// A mix of sound files rendered to a fixed number of channels
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mix {
+    channels: u16,
+    entries: Vec<MixEntry>,
+}
+
+impl Mix {
+    /// Create an empty mix with `channels` output channels
+    pub fn new(channels: u16) -> Result<Self> {
+        if channels == 0 {
+            return Err(SubmixError::InvalidParameter(
+                "Mix needs at least one output channel".into(),
+            ));
+        }
+        Ok(Self {
+            channels,
+            entries: Vec::new(),
+        })
+    }
+
+    /// Read a CDP mixfile
+    ///
+    /// Relative file names are resolved against the mixfile's directory.
+    /// With `channels` of `None` the mix has as many output channels as
+    /// its widest line.
+    pub fn from_mixfile(path: &Path, channels: Option<u16>) -> Result<Self> {
+        let base = path.parent().unwrap_or_else(|| Path::new(""));
+        let lines = parse_mixfile(&cdp_core::fs::read_to_string(path)?, base)?;
+        let widest = lines.iter().map(|line| line.channels).max().unwrap_or(1);
+        let mut mix = Self::new(channels.unwrap_or(widest))?;
+        for line in lines {
+            mix.entries.push(MixEntry {
+                path: line.path,
+                at: line.time,
+                routing: Routing::PerChannel(line.routes),
+            });
+        }
+        Ok(mix)
+    }
+
+    /// Add a file at `at` seconds with a gain and pan position
+    pub fn add(&mut self, path: impl Into<PathBuf>, at: f64, gain: f64, pan: f64) -> &mut Self {
+        self.add_routed(path, at, Routing::Panned(Route { gain, pan }))
+    }
+
+    /// Add a file at `at` seconds with explicit routing
+    pub fn add_routed(&mut self, path: impl Into<PathBuf>, at: f64, routing: Routing) -> &mut Self {
+        self.entries.push(MixEntry {
+            path: path.into(),
+            at,
+            routing,
+        });
+        self
+    }
+
+    /// Number of output channels
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// The entries in the order they were added
+    pub fn entries(&self) -> &[MixEntry] {
+        &self.entries
+    }
+
+    /// Multiply the gain of every route by `factor`
+    pub(crate) fn scale_gains(&mut self, factor: f64) {
+        for entry in &mut self.entries {
+            match &mut entry.routing {
+                Routing::Panned(route) => route.gain *= factor,
+                Routing::PerChannel(routes) => {
+                    for route in routes {
+                        route.gain *= factor;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Check every entry without reading its samples
+    ///
+    /// Each file must exist and be a readable WAV file, all must share a
+    /// sample rate, per-channel routing must match each file's channel
+    /// count, and times, gains and pans must be in range.
+    pub fn validate(&self) -> Result<()> {
+        self.check_entries()?;
+        Ok(())
+    }
+
+    /// Render the mix
+    ///
+    /// The output runs until the last entry ends.
+    pub fn render(&self) -> Result<AudioBuffer> {
+        self.render_typed().map(|(audio, _)| audio)
+    }
+
+    /// Render the mix to a file, in the sample type of the first entry
+    pub fn render_to(&self, output: &Path) -> Result<()> {
+        let (audio, sample_type) = self.render_typed()?;
+        wav_cdp::write_audio_buffer(output, &audio, sample_type)?;
+        Ok(())
+    }
+
+    fn render_typed(&self) -> Result<(AudioBuffer, SampleType)> {
+        let sample_rate = self.check_entries()?;
+        let out_channels = self.channels as usize;
+        let mut data: Vec<f32> = Vec::new();
+        let mut sample_type = SampleType::Int16;
+        for (index, entry) in self.entries.iter().enumerate() {
+            let (audio, entry_type) = wav_cdp::read_audio_buffer(&entry.path)?;
+            if index == 0 {
+                sample_type = entry_type;
+            }
+            let in_channels = audio.channels as usize;
+            let gains: Vec<Vec<f32>> = routes(&entry.routing, in_channels)
+                .iter()
+                .map(|route| {
+                    pan_gains(route.pan, out_channels)
+                        .into_iter()
+                        .map(|gain| (gain * route.gain) as f32)
+                        .collect()
+                })
+                .collect();
+
+            let start = (entry.at * sample_rate as f64).round() as usize;
+            let end = (start + audio.frame_count()) * out_channels;
+            if data.len() < end {
+                data.resize(end, 0.0);
+            }
+            for (offset, frame) in audio.frames().enumerate() {
+                let out = &mut data[(start + offset) * out_channels..][..out_channels];
+                for (&sample, channel_gains) in frame.iter().zip(&gains) {
+                    for (out, gain) in out.iter_mut().zip(channel_gains) {
+                        *out += sample * gain;
+                    }
+                }
+            }
+        }
+        Ok((
+            AudioBuffer::new(sample_rate, self.channels, data)?,
+            sample_type,
+        ))
+    }
+
+    /// Validate the entries, returning their shared sample rate
+    fn check_entries(&self) -> Result<u32> {
+        let mut sample_rate = None;
+        for entry in &self.entries {
+            let name = entry.path.display();
+            let reader = WavReader::open(&entry.path)
+                .map_err(|e| SubmixError::InvalidMixfile(format!("cannot read {}: {}", name, e)))?;
+            let format = reader.format();
+            if *sample_rate.get_or_insert(format.sample_rate) != format.sample_rate {
+                return Err(SubmixError::InvalidMixfile(format!(
+                    "{} has a different sample rate from the other files",
+                    name
+                )));
+            }
+            if let Routing::PerChannel(routes) = &entry.routing {
+                if routes.len() != format.channels as usize {
+                    return Err(SubmixError::InvalidMixfile(format!(
+                        "{} has {} channels but {} are routed",
+                        name,
+                        format.channels,
+                        routes.len()
+                    )));
+                }
+            }
+            if !(entry.at >= 0.0 && entry.at.is_finite()) {
+                return Err(SubmixError::InvalidParameter(format!(
+                    "Start time of {} cannot be negative",
+                    name
+                )));
+            }
+            let checked = match &entry.routing {
+                Routing::Panned(route) => std::slice::from_ref(route),
+                Routing::PerChannel(routes) => routes.as_slice(),
+            };
+            for route in checked {
+                check_route(route)?;
+            }
+        }
+        sample_rate.ok_or_else(|| SubmixError::InvalidParameter("Mix has no entries".into()))
+    }
+}
+
+fn check_route(route: &Route) -> Result<()> {
+    if !(route.gain >= 0.0 && route.gain.is_finite()) {
+        return Err(SubmixError::InvalidParameter(
+            "Gain cannot be negative".into(),
+        ));
+    }
+    if !(-1.0..=1.0).contains(&route.pan) {
+        return Err(SubmixError::InvalidParameter(
+            "Pan must be between -1 and 1".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Per-channel routes for a file with `channels` channels
+///
+/// A single pan spreads multichannel files evenly from -1 to 1 before
+/// shifting them, so a centred stereo file stays left and right.
+fn routes(routing: &Routing, channels: usize) -> Vec<Route> {
+    match routing {
+        Routing::PerChannel(routes) => routes.clone(),
+        Routing::Panned(route) if channels == 1 => vec![*route],
+        Routing::Panned(route) => (0..channels)
+            .map(|ch| Route {
+                gain: route.gain,
+                pan: (2.0 * ch as f64 / (channels - 1) as f64 - 1.0 + route.pan).clamp(-1.0, 1.0),
+            })
+            .collect(),
+    }
+}
+
+/// Equal-power gains placing a pan position between adjacent outputs
+pub(crate) fn pan_gains(pan: f64, channels: usize) -> Vec<f64> {
+    let mut gains = vec![0.0; channels];
+    if channels == 1 {
+        gains[0] = 1.0;
+        return gains;
+    }
+    let position = (pan + 1.0) / 2.0 * (channels - 1) as f64;
+    let left = (position.floor() as usize).min(channels - 2);
+    let frac = (position - left as f64) * std::f64::consts::FRAC_PI_2;
+    gains[left] = frac.cos();
+    gains[left + 1] = frac.sin();
+    gains
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(path: &Path, channels: u16, samples: Vec<f32>) {
+        let format = wav_cdp::WavFormat {
+            channels,
+            sample_rate: 1000,
+            bits_per_sample: 32,
+            data_size: 0,
+        };
+        wav_cdp::write_wav_cdp_samples(path, &format, &wav_cdp::Samples::F32(samples)).unwrap();
+    }
+
+    #[test]
+    fn test_mix_render() {
+        let temp_dir = TempDir::new().unwrap();
+        let mono = temp_dir.path().join("mono.wav");
+        let stereo = temp_dir.path().join("stereo.wav");
+        write(&mono, 1, vec![1.0, 1.0]);
+        write(&stereo, 2, vec![0.25, 0.5]);
+
+        let mut mix = Mix::new(2).unwrap();
+        mix.add(&mono, 0.0, 0.5, -1.0).add(&stereo, 0.001, 1.0, 0.0);
+        let audio = mix.render().unwrap();
+        assert_eq!(audio.channels, 2);
+        assert_eq!(audio.data, vec![0.5, 0.0, 0.75, 0.5]);
+
+        // Centred mono is shared equally between the pair
+        let mut mix = Mix::new(2).unwrap();
+        mix.add(&mono, 0.0, 1.0, 0.0);
+        let audio = mix.render().unwrap();
+        assert!((audio.data[0] - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+        assert_eq!(audio.data[0], audio.data[1]);
+
+        mix.add_routed(
+            &stereo,
+            0.0,
+            Routing::PerChannel(vec![Route {
+                gain: 1.0,
+                pan: 0.0,
+            }]),
+        );
+        assert!(mix.validate().is_err());
+        let mut mix = Mix::new(2).unwrap();
+        mix.add(temp_dir.path().join("missing.wav"), 0.0, 1.0, 0.0);
+        assert!(mix.validate().is_err());
+    }
+
+    #[test]
+    fn test_pan_gains() {
+        assert_eq!(pan_gains(-1.0, 2), vec![1.0, 0.0]);
+        assert_eq!(pan_gains(0.3, 1), vec![1.0]);
+        let quad = pan_gains(0.0, 4);
+        assert!(quad[0] == 0.0 && quad[3] == 0.0);
+        assert!((quad[1] - quad[2]).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,134 @@
+//! CDP mixfile parsing
+//!
+//! A mixfile has one line per sound: `name time channels level [pan]` for
+//! a mono file, or `name time channels level pan level pan` routing each
+//! channel of a stereo file. Levels are linear gains or dB values written
+//! like `-6dB`; pans are `L`, `C`, `R` or a position from -1 to 1. Lines
+//! starting with `;` are comments.
+
+use super::{Result, SubmixError};
+use crate::mix::Route;
+use std::path::{Path, PathBuf};
+
+/// One line of a mixfile
+#[derive(Debug, Clone, PartialEq)]
+pub struct MixLine {
+    /// Sound file, resolved against the mixfile's directory
+    pub path: PathBuf,
+    /// Start time in seconds
+    pub time: f64,
+    /// Channel count the line declares for the file
+    pub channels: u16,
+    /// Route for each channel of the file
+    pub routes: Vec<Route>,
+}
+
+/// Parse mixfile text, resolving relative names against `base`
+pub fn parse_mixfile(text: &str, base: &Path) -> Result<Vec<MixLine>> {
+    let mut lines = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') {
+            continue;
+        }
+        let error = |message: &str| {
+            SubmixError::InvalidMixfile(format!("line {}: {}", number + 1, message))
+        };
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 4 {
+            return Err(error("expected name, time, channels and level"));
+        }
+        let time = fields[1]
+            .parse::<f64>()
+            .map_err(|_| error("invalid time"))?;
+        let channels = fields[2]
+            .parse::<u16>()
+            .map_err(|_| error("invalid channel count"))?;
+        let route = |level: &str, pan: Option<&str>, default_pan: f64| -> Result<Route> {
+            Ok(Route {
+                gain: parse_level(level).ok_or_else(|| error("invalid level"))?,
+                pan: match pan {
+                    Some(pan) => parse_pan(pan).ok_or_else(|| error("invalid pan"))?,
+                    None => default_pan,
+                },
+            })
+        };
+        let routes = match (channels, fields.len()) {
+            (1, 4 | 5) => vec![route(fields[3], fields.get(4).copied(), 0.0)?],
+            (2, 4) => vec![route(fields[3], None, -1.0)?, route(fields[3], None, 1.0)?],
+            (2, 7) => vec![
+                route(fields[3], Some(fields[4]), -1.0)?,
+                route(fields[5], Some(fields[6]), 1.0)?,
+            ],
+            (1 | 2, _) => return Err(error("wrong number of fields for the channel count")),
+            _ => return Err(error("only mono and stereo files can be mixed")),
+        };
+        lines.push(MixLine {
+            path: base.join(fields[0]),
+            time,
+            channels,
+            routes,
+        });
+    }
+    if lines.is_empty() {
+        return Err(SubmixError::InvalidMixfile("no sounds listed".into()));
+    }
+    Ok(lines)
+}
+
+/// A linear level, or a dB level with a `dB` suffix
+fn parse_level(field: &str) -> Option<f64> {
+    let lower = field.to_ascii_lowercase();
+    match lower.strip_suffix("db") {
+        Some(db) => db.parse::<f64>().ok().map(|db| 10.0f64.powf(db / 20.0)),
+        None => field.parse().ok(),
+    }
+}
+
+/// `L`, `C`, `R` or a number
+fn parse_pan(field: &str) -> Option<f64> {
+    match field {
+        "L" | "l" => Some(-1.0),
+        "C" | "c" => Some(0.0),
+        "R" | "r" => Some(1.0),
+        _ => field.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mixfile() {
+        let text = "; a comment\n\
+                    kick.wav 0 1 0.8\n\
+                    pad.wav 1.5 2 -6dB L 1 0.5\n\
+                    bell.wav 2 1 1 R\n";
+        let lines = parse_mixfile(text, Path::new("/mixes")).unwrap();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].path, Path::new("/mixes/kick.wav"));
+        assert_eq!(
+            lines[0].routes,
+            vec![Route {
+                gain: 0.8,
+                pan: 0.0
+            }]
+        );
+        assert_eq!(lines[1].time, 1.5);
+        assert!((lines[1].routes[0].gain - 0.501187).abs() < 1e-6);
+        assert_eq!(
+            lines[1].routes[1],
+            Route {
+                gain: 1.0,
+                pan: 0.5
+            }
+        );
+        assert_eq!(lines[2].routes[0].pan, 1.0);
+
+        let err = parse_mixfile("a.wav 0 2 1 L\n", Path::new("")).unwrap_err();
+        assert!(err.to_string().contains("line 1"));
+        assert!(parse_mixfile("a.wav x 1 1\n", Path::new("")).is_err());
+        assert!(parse_mixfile("; empty\n", Path::new("")).is_err());
+    }
+}
@@ -0,0 +1,24 @@
+//! Thin binary wrapper for synth operations
+//!
+//! Generates test signals for the oracle scripts and examples.
+
+use std::env;
+use std::process;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() < 2 {
+        eprintln!("CDP-RS Synth (Test Signal Binary)");
+        eprintln!("Usage: synth <operation> [mode] <outfile> <srate> <chans> <dur> [args...]");
+        eprintln!("Operations: wave, noise, silence, chirp, clicks");
+        process::exit(1);
+    }
+
+    let op_args: Vec<&str> = args[2..].iter().map(|s| s.as_str()).collect();
+
+    if let Err(e) = cdp_synth::synth(&args[1], &op_args) {
+        eprintln!("ERROR: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,176 @@
+//! CDP Synth module - Signal generation
+//!
+//! This module implements CDP's synth operations including:
+//! - Sine tones and sums of partials
+//! - Band-limited square and sawtooth waves
+//! - White and pink noise
+//! - Silence
+//! - Linear and exponential chirps
+//! - Click trains
+//!
+//! Every generator returns a mono [`cdp_core::AudioBuffer`], which can be
+//! written with `cdp_housekeep::wav_cdp::write_audio_buffer` or combined
+//! into more channels with `AudioBuffer::from_channels`.
+
+use cdp_core::AudioBuffer;
+use cdp_housekeep::wav_cdp::{self, SampleType};
+use std::path::Path;
+use thiserror::Error;
+
+pub mod noise;
+pub mod wave;
+
+/// Result type for synth operations
+pub type Result<T> = std::result::Result<T, SynthError>;
+
+/// Errors that can occur during synth operations
+#[derive(Error, Debug)]
+pub enum SynthError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Housekeep error: {0}")]
+    Housekeep(#[from] cdp_housekeep::HousekeepError),
+
+    #[error("Core error: {0}")]
+    Core(#[from] cdp_core::CoreError),
+
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
+
+    #[error("Unsupported operation: {0}")]
+    UnsupportedOperation(String),
+}
+
+// Re-export main functions for convenience
+pub use noise::{pink_noise, white_noise};
+pub use wave::{chirp, click_train, partials, sawtooth, silence, sine, square, Sweep};
+
+/// CLI compatibility layer - matches CDP's command-line interface
+/// This is just for oracle testing. Real users should use the library functions directly.
+///
+/// Operations are `wave mode outfile srate chans dur freq` (modes 1 sine,
+/// 2 square, 3 sawtooth), `noise mode outfile srate chans dur` (modes 1
+/// white, 2 pink), `silence outfile srate chans dur`, `chirp mode outfile
+/// srate chans dur startfreq endfreq` (modes 1 linear, 2 exponential) and
+/// `clicks outfile srate chans dur rate`. All take `-aamp` (default 1) and
+/// noise takes `-sseed`. Files are written as 16-bit, with every channel
+/// the same.
+pub fn synth(operation: &str, args: &[&str]) -> Result<()> {
+    let number = |arg: &str| {
+        arg.parse::<f64>()
+            .map_err(|_| SynthError::InvalidParameter(format!("Invalid value: {}", arg)))
+    };
+    let mut amplitude = 1.0;
+    let mut seed = 0;
+    let mut params = Vec::new();
+    for &arg in args {
+        if let Some(value) = arg.strip_prefix("-a") {
+            amplitude = number(value)?;
+        } else if let Some(value) = arg.strip_prefix("-s") {
+            seed = number(value)? as u32;
+        } else {
+            params.push(arg);
+        }
+    }
+
+    let usage = match operation {
+        "wave" => "wave mode outfile srate chans dur freq [-aamp]",
+        "noise" => "noise mode outfile srate chans dur [-aamp] [-sseed]",
+        "silence" => "silence outfile srate chans dur",
+        "chirp" => "chirp mode outfile srate chans dur startfreq endfreq [-aamp]",
+        "clicks" => "clicks outfile srate chans dur rate [-aamp]",
+        _ => {
+            return Err(SynthError::UnsupportedOperation(format!(
+                "Unknown operation: {}",
+                operation
+            )))
+        }
+    };
+    let expected = usage
+        .split_whitespace()
+        .filter(|w| !w.starts_with('['))
+        .count()
+        - 1;
+    if params.len() != expected {
+        return Err(SynthError::InvalidParameter(format!("Usage: {}", usage)));
+    }
+    let (mode, params) = match operation {
+        "wave" | "noise" | "chirp" => (params[0], &params[1..]),
+        _ => ("1", &params[..]),
+    };
+    let output = Path::new(params[0]);
+    let sample_rate = number(params[1])? as u32;
+    let channels = number(params[2])? as u16;
+    let duration = number(params[3])?;
+    let values = params[4..]
+        .iter()
+        .map(|arg| number(arg))
+        .collect::<Result<Vec<f64>>>()?;
+
+    let audio = match (operation, mode) {
+        ("wave", "1") => sine(values[0], duration, sample_rate, amplitude)?,
+        ("wave", "2") => square(values[0], duration, sample_rate, amplitude)?,
+        ("wave", "3") => sawtooth(values[0], duration, sample_rate, amplitude)?,
+        ("noise", "1") => white_noise(duration, sample_rate, amplitude, seed)?,
+        ("noise", "2") => pink_noise(duration, sample_rate, amplitude, seed)?,
+        ("silence", _) => silence(duration, sample_rate)?,
+        ("chirp", "1") => chirp(
+            values[0],
+            values[1],
+            Sweep::Linear,
+            duration,
+            sample_rate,
+            amplitude,
+        )?,
+        ("chirp", "2") => chirp(
+            values[0],
+            values[1],
+            Sweep::Exponential,
+            duration,
+            sample_rate,
+            amplitude,
+        )?,
+        ("clicks", _) => click_train(values[0], duration, sample_rate, amplitude)?,
+        _ => {
+            return Err(SynthError::UnsupportedOperation(format!(
+                "Synth {} mode {} not yet implemented",
+                operation, mode
+            )))
+        }
+    };
+
+    if channels == 0 {
+        return Err(SynthError::InvalidParameter(
+            "Channel count must be at least 1".into(),
+        ));
+    }
+    let audio = AudioBuffer::from_channels(sample_rate, &vec![audio.data; channels as usize])?;
+    wav_cdp::write_audio_buffer(output, &audio, SampleType::Int16)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_synth_cli() {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path().join("tone.wav");
+        let path = output.to_str().unwrap();
+        synth("wave", &["1", path, "8000", "2", "0.5", "440", "-a0.5"]).unwrap();
+
+        let (audio, sample_type) = wav_cdp::read_audio_buffer(&output).unwrap();
+        assert_eq!(sample_type, SampleType::Int16);
+        assert_eq!(audio.channels, 2);
+        assert_eq!(audio.frame_count(), 4000);
+        assert_eq!(audio.channel(0).unwrap(), audio.channel(1).unwrap());
+        assert!((audio.peak() - 0.5).abs() < 1e-3);
+
+        assert!(synth("wave", &["4", path, "8000", "1", "1", "440"]).is_err());
+        assert!(synth("silence", &[path, "8000", "1"]).is_err());
+        assert!(synth("sweep", &[path]).is_err());
+    }
+}
@@ -0,0 +1,88 @@
+//! Noise generators
+//!
+//! Seeded white and pink noise, so a given seed always gives the same
+//! mono buffer.
+
+use super::Result;
+use crate::wave::{check_amplitude, frame_count};
+use cdp_core::{AudioBuffer, Rng};
+
+/// White noise with levels spread evenly up to `amplitude` (0-1)
+pub fn white_noise(
+    duration: f64,
+    sample_rate: u32,
+    amplitude: f64,
+    seed: u32,
+) -> Result<AudioBuffer> {
+    check_amplitude(amplitude)?;
+    let frames = frame_count(duration, sample_rate)?;
+    let mut rng = Rng::new(seed);
+    let data = (0..frames)
+        .map(|_| (amplitude * (2.0 * rng.uniform() - 1.0)) as f32)
+        .collect();
+    Ok(AudioBuffer::new(sample_rate, 1, data)?)
+}
+
+/// Pink noise, falling 3dB per octave, peaking at `amplitude` (0-1)
+///
+/// White noise is shaped by Paul Kellett's bank of one-pole filters, which
+/// holds the -3dB/octave slope to within half a dB across the audio band.
+pub fn pink_noise(
+    duration: f64,
+    sample_rate: u32,
+    amplitude: f64,
+    seed: u32,
+) -> Result<AudioBuffer> {
+    check_amplitude(amplitude)?;
+    let mut audio = white_noise(duration, sample_rate, 1.0, seed)?;
+    let mut b = [0.0f64; 7];
+    for sample in &mut audio.data {
+        let white = *sample as f64;
+        b[0] = 0.99886 * b[0] + white * 0.0555179;
+        b[1] = 0.99332 * b[1] + white * 0.0750759;
+        b[2] = 0.96900 * b[2] + white * 0.1538520;
+        b[3] = 0.86650 * b[3] + white * 0.3104856;
+        b[4] = 0.55000 * b[4] + white * 0.5329522;
+        b[5] = -0.7616 * b[5] - white * 0.0168980;
+        let pink = b.iter().sum::<f64>() + white * 0.5362;
+        b[6] = white * 0.115926;
+        *sample = pink as f32;
+    }
+
+    let peak = audio.peak();
+    if peak > 0.0 {
+        let gain = (amplitude / peak as f64) as f32;
+        for sample in &mut audio.data {
+            *sample *= gain;
+        }
+    }
+    Ok(audio)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Correlation between neighbouring samples
+    fn smoothness(data: &[f32]) -> f64 {
+        let lagged: f64 = data.windows(2).map(|p| (p[0] * p[1]) as f64).sum();
+        let power: f64 = data.iter().map(|&s| (s * s) as f64).sum();
+        lagged / power
+    }
+
+    #[test]
+    fn test_noise() {
+        let white = white_noise(1.0, 8000, 0.5, 3).unwrap();
+        assert_eq!(white, white_noise(1.0, 8000, 0.5, 3).unwrap());
+        assert_ne!(white, white_noise(1.0, 8000, 0.5, 4).unwrap());
+        assert!(white.peak() <= 0.5);
+        assert!(smoothness(&white.data).abs() < 0.05);
+
+        // Pink noise leans toward the low end, so neighbours are alike
+        let pink = pink_noise(1.0, 8000, 0.5, 3).unwrap();
+        assert!((pink.peak() - 0.5).abs() < 1e-6);
+        assert!(smoothness(&pink.data) > 0.5);
+
+        assert!(white_noise(1.0, 8000, 2.0, 3).is_err());
+    }
+}
@@ -0,0 +1,282 @@
+//! Tones and test signals
+//!
+//! Sine tones, sums of partials, band-limited square and sawtooth waves,
+//! frequency sweeps, click trains and silence. Every generator returns a
+//! mono buffer.
+
+use super::{Result, SynthError};
+use cdp_core::AudioBuffer;
+use std::f64::consts::PI;
+
+/// How a chirp moves from its start frequency to its end frequency
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Sweep {
+    /// Equal steps in Hz
+    Linear,
+    /// Equal steps in pitch
+    Exponential,
+}
+
+/// A sine tone at `amplitude` (0-1)
+pub fn sine(
+    frequency: f64,
+    duration: f64,
+    sample_rate: u32,
+    amplitude: f64,
+) -> Result<AudioBuffer> {
+    check_tone(frequency, amplitude, sample_rate)?;
+    partials(&[(frequency, amplitude)], duration, sample_rate)
+}
+
+/// A sum of sine partials, each given as a frequency and amplitude
+///
+/// Partials at or above the Nyquist frequency are left out. The sum is not
+/// scaled, so the amplitudes should leave headroom for each other.
+pub fn partials(partials: &[(f64, f64)], duration: f64, sample_rate: u32) -> Result<AudioBuffer> {
+    let frames = frame_count(duration, sample_rate)?;
+    if partials
+        .iter()
+        .any(|&(frequency, amplitude)| !(frequency > 0.0 && amplitude.is_finite()))
+    {
+        return Err(SynthError::InvalidParameter(
+            "Partials need positive frequencies and finite amplitudes".into(),
+        ));
+    }
+    let nyquist = sample_rate as f64 / 2.0;
+    let steps: Vec<(f64, f64)> = partials
+        .iter()
+        .filter(|&&(frequency, _)| frequency < nyquist)
+        .map(|&(frequency, amplitude)| (2.0 * PI * frequency / sample_rate as f64, amplitude))
+        .collect();
+    let data = (0..frames)
+        .map(|frame| {
+            steps
+                .iter()
+                .map(|&(step, amplitude)| amplitude * (step * frame as f64).sin())
+                .sum::<f64>() as f32
+        })
+        .collect();
+    Ok(AudioBuffer::new(sample_rate, 1, data)?)
+}
+
+/// A band-limited square wave peaking at `amplitude` (0-1)
+///
+/// Built from the odd harmonics below the Nyquist frequency, so it does
+/// not alias.
+pub fn square(
+    frequency: f64,
+    duration: f64,
+    sample_rate: u32,
+    amplitude: f64,
+) -> Result<AudioBuffer> {
+    check_tone(frequency, amplitude, sample_rate)?;
+    let harmonics = harmonics(frequency, sample_rate)
+        .filter(|k| k % 2 == 1)
+        .map(|k| (k as f64 * frequency, 4.0 / (PI * k as f64)));
+    band_limited(harmonics, duration, sample_rate, amplitude)
+}
+
+/// A band-limited rising sawtooth wave peaking at `amplitude` (0-1)
+///
+/// Built from the harmonics below the Nyquist frequency, so it does not
+/// alias.
+pub fn sawtooth(
+    frequency: f64,
+    duration: f64,
+    sample_rate: u32,
+    amplitude: f64,
+) -> Result<AudioBuffer> {
+    check_tone(frequency, amplitude, sample_rate)?;
+    let harmonics = harmonics(frequency, sample_rate).map(|k| {
+        let sign = if k % 2 == 1 { 1.0 } else { -1.0 };
+        (k as f64 * frequency, sign * 2.0 / (PI * k as f64))
+    });
+    band_limited(harmonics, duration, sample_rate, amplitude)
+}
+
+/// A sine sweep from `start` to `end` Hz over the whole duration
+///
+/// The phase is accumulated sample by sample, so the frequency heard is
+/// the one asked for at every point of the sweep.
+pub fn chirp(
+    start: f64,
+    end: f64,
+    sweep: Sweep,
+    duration: f64,
+    sample_rate: u32,
+    amplitude: f64,
+) -> Result<AudioBuffer> {
+    check_tone(start, amplitude, sample_rate)?;
+    check_tone(end, amplitude, sample_rate)?;
+    let frames = frame_count(duration, sample_rate)?;
+    let mut phase = 0.0f64;
+    let mut data = Vec::with_capacity(frames);
+    for frame in 0..frames {
+        data.push((amplitude * phase.sin()) as f32);
+        let position = frame as f64 / frames as f64;
+        let frequency = match sweep {
+            Sweep::Linear => start + (end - start) * position,
+            Sweep::Exponential => start * (end / start).powf(position),
+        };
+        phase = (phase + 2.0 * PI * frequency / sample_rate as f64) % (2.0 * PI);
+    }
+    Ok(AudioBuffer::new(sample_rate, 1, data)?)
+}
+
+/// Single-sample clicks at `amplitude` (0-1), `rate` times a second
+///
+/// The first click falls on the first sample.
+pub fn click_train(
+    rate: f64,
+    duration: f64,
+    sample_rate: u32,
+    amplitude: f64,
+) -> Result<AudioBuffer> {
+    check_amplitude(amplitude)?;
+    if !(rate > 0.0 && rate <= sample_rate as f64) {
+        return Err(SynthError::InvalidParameter(
+            "Click rate must be greater than 0 and at most the sample rate".into(),
+        ));
+    }
+    let frames = frame_count(duration, sample_rate)?;
+    let mut data = vec![0.0f32; frames];
+    let spacing = sample_rate as f64 / rate;
+    for click in 0.. {
+        let frame = (click as f64 * spacing).round() as usize;
+        if frame >= frames {
+            break;
+        }
+        data[frame] = amplitude as f32;
+    }
+    Ok(AudioBuffer::new(sample_rate, 1, data)?)
+}
+
+/// Silence
+pub fn silence(duration: f64, sample_rate: u32) -> Result<AudioBuffer> {
+    let frames = frame_count(duration, sample_rate)?;
+    Ok(AudioBuffer::silence(sample_rate, 1, frames)?)
+}
+
+/// Number of frames in `duration` seconds
+pub(crate) fn frame_count(duration: f64, sample_rate: u32) -> Result<usize> {
+    if sample_rate == 0 {
+        return Err(SynthError::InvalidParameter(
+            "Sample rate must be greater than 0".into(),
+        ));
+    }
+    if !(0.0..=32767.0).contains(&duration) {
+        return Err(SynthError::InvalidParameter(
+            "Duration must be between 0 and 32767 seconds".into(),
+        ));
+    }
+    Ok((duration * sample_rate as f64).round() as usize)
+}
+
+pub(crate) fn check_amplitude(amplitude: f64) -> Result<()> {
+    if !(0.0..=1.0).contains(&amplitude) {
+        return Err(SynthError::InvalidParameter(
+            "Amplitude must be between 0 and 1".into(),
+        ));
+    }
+    Ok(())
+}
+
+fn check_tone(frequency: f64, amplitude: f64, sample_rate: u32) -> Result<()> {
+    check_amplitude(amplitude)?;
+    if !(frequency > 0.0 && frequency < sample_rate as f64 / 2.0) {
+        return Err(SynthError::InvalidParameter(format!(
+            "Frequency {} must be between 0 and the Nyquist frequency",
+            frequency
+        )));
+    }
+    Ok(())
+}
+
+/// Harmonic numbers of `frequency` below the Nyquist frequency
+fn harmonics(frequency: f64, sample_rate: u32) -> impl Iterator<Item = u32> {
+    let nyquist = sample_rate as f64 / 2.0;
+    (1..).take_while(move |&k| k as f64 * frequency < nyquist)
+}
+
+/// Sum harmonics and scale the result to peak at `amplitude`
+fn band_limited(
+    harmonics: impl Iterator<Item = (f64, f64)>,
+    duration: f64,
+    sample_rate: u32,
+    amplitude: f64,
+) -> Result<AudioBuffer> {
+    let harmonics: Vec<(f64, f64)> = harmonics.collect();
+    let mut audio = partials(&harmonics, duration, sample_rate)?;
+    let peak = audio.peak();
+    if peak > 0.0 {
+        let gain = (amplitude / peak as f64) as f32;
+        for sample in &mut audio.data {
+            *sample *= gain;
+        }
+    }
+    Ok(audio)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sine_and_partials() {
+        let tone = sine(250.0, 0.01, 1000, 0.5).unwrap();
+        assert_eq!(tone.frame_count(), 10);
+        let expected = [0.0, 0.5, 0.0, -0.5];
+        for (sample, expected) in tone.data.iter().zip(expected) {
+            assert!((sample - expected).abs() < 1e-6);
+        }
+
+        // The partial above Nyquist is dropped
+        let sum = partials(&[(250.0, 0.5), (600.0, 0.5)], 0.01, 1000).unwrap();
+        assert_eq!(sum, tone);
+
+        assert!(sine(500.0, 1.0, 1000, 0.5).is_err());
+        assert!(sine(100.0, 1.0, 1000, 1.5).is_err());
+        assert!(sine(100.0, -1.0, 1000, 0.5).is_err());
+    }
+
+    #[test]
+    fn test_band_limited_waves() {
+        let wave = square(100.0, 0.1, 8000, 0.8).unwrap();
+        assert!((wave.peak() - 0.8).abs() < 1e-6);
+        // Symmetric about the half cycle
+        assert!((wave.data[20] + wave.data[60]).abs() < 1e-5);
+
+        let wave = sawtooth(100.0, 0.1, 8000, 0.8).unwrap();
+        assert!((wave.peak() - 0.8).abs() < 1e-6);
+        // Rises through the first half cycle
+        assert!(wave.data[10] < wave.data[30]);
+    }
+
+    #[test]
+    fn test_chirp() {
+        let sweep = chirp(100.0, 400.0, Sweep::Exponential, 1.0, 8000, 1.0).unwrap();
+        let crossings = |data: &[f32]| {
+            data.windows(2)
+                .filter(|pair| pair[0] < 0.0 && pair[1] >= 0.0)
+                .count()
+        };
+        // An octave up halfway through
+        let (first, second) = sweep.data.split_at(4000);
+        assert!((crossings(first) as i32 - 72).abs() <= 1);
+        assert!((crossings(second) as i32 - 144).abs() <= 1);
+        assert!(chirp(100.0, 5000.0, Sweep::Linear, 1.0, 8000, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_click_train_and_silence() {
+        let clicks = click_train(4.0, 1.0, 10, 0.5).unwrap();
+        assert_eq!(
+            clicks.data,
+            vec![0.5, 0.0, 0.0, 0.5, 0.0, 0.5, 0.0, 0.0, 0.5, 0.0]
+        );
+        assert_eq!(silence(0.5, 10).unwrap().data, vec![0.0; 5]);
+        assert!(click_train(0.0, 1.0, 10, 0.5).is_err());
+    }
+}
@@ -0,0 +1,54 @@
+//! CDP Texture module - Texture generation
+//!
+//! This module implements CDP's texture operations including:
+//! - Simple textures: copies of input sounds scattered through time with
+//!   random pitch, level and length drawn from given ranges
+//! - Note-data files giving the pitch of each input sound
+//!
+//! The ornamented, grouped and motif modes are not yet implemented.
+
+use thiserror::Error;
+
+pub mod notedata;
+pub mod simple;
+
+/// Result type for texture operations
+pub type Result<T> = std::result::Result<T, TextureError>;
+
+/// Errors that can occur during texture operations
+#[derive(Error, Debug)]
+pub enum TextureError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Housekeep error: {0}")]
+    Housekeep(#[from] cdp_housekeep::HousekeepError),
+
+    #[error("Core error: {0}")]
+    Core(#[from] cdp_core::CoreError),
+
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
+
+    #[error("Invalid note data: {0}")]
+    InvalidNoteData(String),
+
+    #[error("Unsupported operation: {0}")]
+    UnsupportedOperation(String),
+}
+
+// Re-export main functions for convenience
+pub use notedata::NoteData;
+pub use simple::{texture, texture_buf, texture_with_progress, TextureOptions};
+
+/// CLI compatibility layer - matches CDP's command-line interface
+/// This is just for oracle testing. Real users should use the library functions directly.
+pub fn texture_cli(operation: &str, mode: i32, args: &[&str]) -> Result<()> {
+    match operation {
+        "simple" => simple::simple_cli(mode, args),
+        _ => Err(TextureError::UnsupportedOperation(format!(
+            "Unknown operation: {}",
+            operation
+        ))),
+    }
+}
@@ -0,0 +1,62 @@
+//! Texture note data
+//!
+//! CDP texture reads the pitch of each input sound from a note-data
+//! file, so it can transpose copies to the pitches the texture asks for.
+//! The first line lists one MIDI pitch per input sound, in input order.
+//! Later lines hold the harmonic fields and note lists of the ornamented
+//! modes; the simple modes do not use them.
+
+use super::{Result, TextureError};
+use std::path::Path;
+
+/// Pitches of the texture's input sounds
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoteData {
+    /// MIDI pitch of each input sound
+    pub pitches: Vec<f64>,
+}
+
+impl NoteData {
+    /// Parse note-data text
+    pub fn parse(text: &str) -> Result<Self> {
+        let line = text
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .ok_or_else(|| TextureError::InvalidNoteData("file is empty".into()))?;
+        let pitches = line
+            .split_whitespace()
+            .map(|field| {
+                field
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|pitch| (0.0..=127.0).contains(pitch))
+                    .ok_or_else(|| {
+                        TextureError::InvalidNoteData(format!(
+                            "'{}' is not a MIDI pitch between 0 and 127",
+                            field
+                        ))
+                    })
+            })
+            .collect::<Result<Vec<f64>>>()?;
+        Ok(Self { pitches })
+    }
+
+    /// Read a note-data file
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::parse(&cdp_core::fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_note_data() {
+        let data = NoteData::parse("\n60 67.5\n#2\n0 1 60 0 0\n").unwrap();
+        assert_eq!(data.pitches, vec![60.0, 67.5]);
+        assert!(NoteData::parse("  \n").is_err());
+        assert!(NoteData::parse("60 200").is_err());
+    }
+}
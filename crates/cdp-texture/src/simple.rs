@@ -0,0 +1,340 @@
+//! Simple textures
+//!
+//! Scatters copies of the input sounds through time, each at a random
+//! pitch, level and length drawn from the given ranges, like CDP's
+//! texture simple mode.
+
+use super::{Result, TextureError};
+use crate::notedata::NoteData;
+use cdp_core::audio::{self, AudioBuffer};
+use cdp_core::progress::{self, ProgressSink};
+use cdp_core::{Rng, TextProgress};
+use cdp_housekeep::wav_cdp;
+use std::path::Path;
+
+/// Fade applied where an event is cut short, in seconds
+const EVENT_FADE: f64 = 0.005;
+
+/// Settings for [`texture`]
+///
+/// Times are in seconds, pitches are MIDI note numbers and gains are
+/// linear levels (0-1].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextureOptions {
+    /// Time over which events start
+    pub duration: f64,
+    /// Average time between event starts
+    pub packing: f64,
+    /// Randomisation of event starts (0-1), as a fraction of the packing
+    pub scatter: f64,
+    /// Lowest and highest event level
+    pub gain_range: (f64, f64),
+    /// Shortest and longest event
+    pub dur_range: (f64, f64),
+    /// Lowest and highest event pitch
+    pub pitch_range: (f64, f64),
+    /// Gain applied to the whole texture
+    pub attenuation: f64,
+    /// Seed for the event choices
+    pub seed: u32,
+}
+
+/// Generate a texture from input sounds and their note data
+pub fn texture(
+    inputs: &[&Path],
+    notedata: &Path,
+    output: &Path,
+    options: &TextureOptions,
+) -> Result<()> {
+    texture_with_progress(inputs, notedata, output, options, &mut ())
+}
+
+/// As [`texture`], reporting each event placed to `progress`
+///
+/// Stops with [`cdp_core::CoreError::Cancelled`] if the sink asks, leaving
+/// no output file.
+pub fn texture_with_progress(
+    inputs: &[&Path],
+    notedata: &Path,
+    output: &Path,
+    options: &TextureOptions,
+    progress: &mut dyn ProgressSink,
+) -> Result<()> {
+    let mut sources = Vec::with_capacity(inputs.len());
+    let mut sample_type = None;
+    for input in inputs {
+        let (audio, input_type) = wav_cdp::read_audio_buffer(input)?;
+        sample_type.get_or_insert(input_type);
+        sources.push(audio);
+    }
+    let sample_type = sample_type
+        .ok_or_else(|| TextureError::InvalidParameter("No input sounds given".into()))?;
+    let notes = NoteData::load(notedata)?;
+    let audio = scatter(&sources, &notes.pitches, options, progress)?;
+    wav_cdp::write_audio_buffer(output, &audio, sample_type)?;
+    Ok(())
+}
+
+/// Generate a texture from in-memory sounds
+///
+/// Buffer-based counterpart of [`texture`]. `pitches` gives the MIDI pitch
+/// of each source. Events start every `packing` seconds, moved by up to
+/// half of `scatter * packing` either way, until `duration`. Each plays a
+/// random source transposed to a random pitch in range, at a random level
+/// and for a random length, fading out if cut short. The output runs on
+/// until the last event ends.
+pub fn texture_buf(
+    sources: &[AudioBuffer],
+    pitches: &[f64],
+    options: &TextureOptions,
+) -> Result<AudioBuffer> {
+    scatter(sources, pitches, options, &mut ())
+}
+
+/// [`texture_buf`], reporting each event placed to `progress`
+fn scatter(
+    sources: &[AudioBuffer],
+    pitches: &[f64],
+    options: &TextureOptions,
+    progress: &mut dyn ProgressSink,
+) -> Result<AudioBuffer> {
+    let first = sources
+        .first()
+        .ok_or_else(|| TextureError::InvalidParameter("No input sounds given".into()))?;
+    if sources
+        .iter()
+        .any(|s| s.sample_rate != first.sample_rate || s.channels != first.channels)
+    {
+        return Err(TextureError::InvalidParameter(
+            "Input sounds must share a sample rate and channel count".into(),
+        ));
+    }
+    if pitches.len() != sources.len() {
+        return Err(TextureError::InvalidNoteData(format!(
+            "{} pitches given for {} input sounds",
+            pitches.len(),
+            sources.len()
+        )));
+    }
+    check_options(options)?;
+
+    let rate = first.sample_rate as f64;
+    let channels = first.channels as usize;
+    let fade = (EVENT_FADE * rate).round() as usize;
+    let mut rng = Rng::new(options.seed);
+    let between = |rng: &mut Rng, (low, high): (f64, f64)| low + rng.uniform() * (high - low);
+
+    let events = (0..)
+        .take_while(|&index| index as f64 * options.packing < options.duration)
+        .count();
+    let mut data: Vec<f32> = Vec::new();
+    for event_index in 0..events {
+        let nominal = event_index as f64 * options.packing;
+        let jitter = (between(&mut rng, (0.0, 1.0)) - 0.5) * options.scatter * options.packing;
+        let start = ((nominal + jitter).max(0.0) * rate).round() as usize;
+        let index = rng.below(sources.len());
+        let ratio = 2.0f64.powf((between(&mut rng, options.pitch_range) - pitches[index]) / 12.0);
+        let gain = between(&mut rng, options.gain_range) as f32;
+        let max_frames = (between(&mut rng, options.dur_range) * rate).round() as usize;
+
+        let event = audio::transpose(&sources[index].data, channels, ratio);
+        let frames = (event.len() / channels).min(max_frames.max(1));
+        let end = (start + frames) * channels;
+        if data.len() < end {
+            data.resize(end, 0.0);
+        }
+        let cut = frames < event.len() / channels;
+        for frame in 0..frames {
+            let remaining = frames - frame;
+            let level = if cut && remaining < fade {
+                gain * remaining as f32 / fade as f32
+            } else {
+                gain
+            };
+            for ch in 0..channels {
+                data[(start + frame) * channels + ch] += event[frame * channels + ch] * level;
+            }
+        }
+        progress::report(progress, event_index + 1, events)?;
+    }
+
+    let attenuation = options.attenuation as f32;
+    for sample in &mut data {
+        *sample *= attenuation;
+    }
+    Ok(AudioBuffer::new(first.sample_rate, first.channels, data)?)
+}
+
+fn check_options(options: &TextureOptions) -> Result<()> {
+    let check = |ok: bool, message: &str| {
+        if ok {
+            Ok(())
+        } else {
+            Err(TextureError::InvalidParameter(message.into()))
+        }
+    };
+    let ordered = |(low, high): (f64, f64)| low <= high;
+    check(
+        options.duration > 0.0 && options.duration <= 32767.0,
+        "Output duration must be between 0 and 32767 seconds",
+    )?;
+    check(
+        options.packing > 0.0 && options.packing.is_finite(),
+        "Packing must be greater than 0",
+    )?;
+    check(
+        (0.0..=1.0).contains(&options.scatter),
+        "Scatter must be between 0 and 1",
+    )?;
+    check(
+        ordered(options.gain_range) && options.gain_range.0 > 0.0 && options.gain_range.1 <= 1.0,
+        "Gains must be greater than 0, at most 1, and in order",
+    )?;
+    check(
+        ordered(options.dur_range) && options.dur_range.0 > 0.0 && options.dur_range.1.is_finite(),
+        "Event durations must be greater than 0 and in order",
+    )?;
+    check(
+        ordered(options.pitch_range)
+            && options.pitch_range.0 >= 0.0
+            && options.pitch_range.1 <= 127.0,
+        "Pitches must be MIDI notes between 0 and 127, in order",
+    )?;
+    check(
+        options.attenuation > 0.0 && options.attenuation <= 1.0,
+        "Attenuation must be greater than 0 and at most 1",
+    )
+}
+
+/// CLI compatibility layer for texture simple
+///
+/// Usage: `simple 1 infile [infile...] outfile notedata outdur packing
+/// scatter mingain maxgain mindur maxdur minpitch maxpitch [-aatten]
+/// [-sseed]`
+pub fn simple_cli(mode: i32, args: &[&str]) -> Result<()> {
+    if mode != 1 {
+        return Err(TextureError::UnsupportedOperation(format!(
+            "Simple texture mode {} not yet implemented",
+            mode
+        )));
+    }
+    let number = |arg: &str| {
+        arg.parse::<f64>()
+            .map_err(|_| TextureError::InvalidParameter(format!("Invalid value: {}", arg)))
+    };
+    let mut attenuation = 1.0;
+    let mut seed = 0;
+    let mut rest = Vec::new();
+    for &arg in args {
+        if let Some(value) = arg.strip_prefix("-a") {
+            attenuation = number(value)?;
+        } else if let Some(value) = arg.strip_prefix("-s") {
+            seed = number(value)? as u32;
+        } else {
+            rest.push(arg);
+        }
+    }
+    if rest.len() < 12 {
+        return Err(TextureError::InvalidParameter(
+            "Usage: simple 1 infile [infile...] outfile notedata outdur packing scatter \
+             mingain maxgain mindur maxdur minpitch maxpitch [-aatten] [-sseed]"
+                .into(),
+        ));
+    }
+    let (inputs, rest) = rest.split_at(rest.len() - 11);
+    let params = rest[2..]
+        .iter()
+        .map(|arg| number(arg))
+        .collect::<Result<Vec<f64>>>()?;
+    let options = TextureOptions {
+        duration: params[0],
+        packing: params[1],
+        scatter: params[2],
+        gain_range: (params[3], params[4]),
+        dur_range: (params[5], params[6]),
+        pitch_range: (params[7], params[8]),
+        attenuation,
+        seed,
+    };
+    let inputs: Vec<&Path> = inputs.iter().map(Path::new).collect();
+    texture_with_progress(
+        &inputs,
+        Path::new(rest[1]),
+        Path::new(rest[0]),
+        &options,
+        &mut TextProgress::new(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> TextureOptions {
+        TextureOptions {
+            duration: 1.0,
+            packing: 0.1,
+            scatter: 0.0,
+            gain_range: (0.5, 0.5),
+            dur_range: (0.02, 0.02),
+            pitch_range: (60.0, 60.0),
+            attenuation: 1.0,
+            seed: 1,
+        }
+    }
+
+    #[test]
+    fn test_texture_events() {
+        // Untransposed clicks every 100 frames at the given level
+        let mut click = vec![0.0f32; 50];
+        click[0] = 1.0;
+        let source = vec![AudioBuffer::new(1000, 1, click).unwrap()];
+        let output = texture_buf(&source, &[60.0], &options()).unwrap();
+        assert_eq!(output.frame_count(), 920);
+        let onsets: Vec<usize> = (0..output.data.len())
+            .filter(|&i| output.data[i] != 0.0)
+            .collect();
+        assert_eq!(onsets, (0..10).map(|k| k * 100).collect::<Vec<_>>());
+        assert!(onsets.iter().all(|&i| output.data[i] == 0.5));
+
+        // An octave up halves each event, which is then not cut short
+        let up = TextureOptions {
+            pitch_range: (72.0, 72.0),
+            dur_range: (1.0, 1.0),
+            ..options()
+        };
+        let output = texture_buf(&source, &[60.0], &up).unwrap();
+        assert_eq!(output.frame_count(), 925);
+
+        assert!(texture_buf(&source, &[60.0, 62.0], &options()).is_err());
+        let reversed = TextureOptions {
+            gain_range: (0.8, 0.2),
+            ..options()
+        };
+        assert!(texture_buf(&source, &[60.0], &reversed).is_err());
+    }
+
+    #[test]
+    fn test_texture_progress() {
+        struct Events(Vec<usize>);
+        impl ProgressSink for Events {
+            fn progress(&mut self, done: usize, total: usize) {
+                assert_eq!(total, 10);
+                self.0.push(done);
+            }
+        }
+
+        let source = vec![AudioBuffer::new(1000, 1, vec![1.0; 50]).unwrap()];
+        let mut events = Events(Vec::new());
+        scatter(&source, &[60.0], &options(), &mut events).unwrap();
+        assert_eq!(events.0, (1..=10).collect::<Vec<_>>());
+
+        let mut token = cdp_core::CancelToken::new();
+        token.cancel();
+        assert!(matches!(
+            scatter(&source, &[60.0], &options(), &mut token),
+            Err(TextureError::Core(cdp_core::CoreError::Cancelled))
+        ));
+    }
+}
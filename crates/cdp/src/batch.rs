@@ -0,0 +1,204 @@
+//! One operation applied to a directory of files at once
+//!
+//! [`process_dir`] runs an operation on every WAV file in a directory,
+//! several files at a time, and collects what each one returned:
+//!
+//! ```no_run
+//! use cdp::batch;
+//! use cdp::pipeline::Pipeline;
+//! use std::path::Path;
+//!
+//! # fn main() -> cdp::Result<()> {
+//! let pipeline = Pipeline::new().normalize(Some(0.95));
+//! let output_dir = Path::new("normalized");
+//! let report = batch::process_dir(
+//!     Path::new("samples"),
+//!     |input: &Path| pipeline.run(input, &output_dir.join(input.file_name().unwrap())),
+//!     4,
+//! )?;
+//! for failure in report.failed() {
+//!     eprintln!("{}: {}", failure.path.display(), failure.result.as_ref().unwrap_err());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! By default every file is processed whatever happens to the others.
+//! [`ErrorMode::FailFast`] stops at the first failure instead.
+
+use crate::{CdpError, Result};
+use rayon::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// What to do when the operation fails on a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorMode {
+    /// Process every file, recording each failure
+    #[default]
+    Continue,
+    /// Start no more files after the first failure, and return it
+    FailFast,
+}
+
+/// Batch processing settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BatchOptions {
+    /// Files processed at once, or 0 for one per CPU
+    pub parallelism: usize,
+    /// What to do when a file fails
+    pub mode: ErrorMode,
+}
+
+/// The outcome for one file
+#[derive(Debug)]
+pub struct FileResult<T> {
+    pub path: PathBuf,
+    pub result: Result<T>,
+}
+
+/// The outcome for every file, in file name order
+#[derive(Debug)]
+pub struct BatchReport<T> {
+    pub results: Vec<FileResult<T>>,
+}
+
+impl<T> BatchReport<T> {
+    /// Files the operation succeeded on
+    pub fn succeeded(&self) -> impl Iterator<Item = &FileResult<T>> {
+        self.results.iter().filter(|file| file.result.is_ok())
+    }
+
+    /// Files the operation failed on
+    pub fn failed(&self) -> impl Iterator<Item = &FileResult<T>> {
+        self.results.iter().filter(|file| file.result.is_err())
+    }
+
+    /// Whether every file succeeded
+    pub fn is_success(&self) -> bool {
+        self.results.iter().all(|file| file.result.is_ok())
+    }
+}
+
+/// Apply `op` to every WAV file in `dir`, `parallelism` files at a time
+///
+/// Failures are recorded in the report rather than stopping the batch.
+pub fn process_dir<T, F>(dir: &Path, op: F, parallelism: usize) -> Result<BatchReport<T>>
+where
+    F: Fn(&Path) -> Result<T> + Sync,
+    T: Send,
+{
+    process_dir_with_options(
+        dir,
+        op,
+        &BatchOptions {
+            parallelism,
+            ..BatchOptions::default()
+        },
+    )
+}
+
+/// Apply `op` to every WAV file in `dir`, with full control over the batch
+///
+/// In [`ErrorMode::FailFast`] the first failure is returned as
+/// [`CdpError::Batch`], and files that had not started are left alone.
+pub fn process_dir_with_options<T, F>(
+    dir: &Path,
+    op: F,
+    options: &BatchOptions,
+) -> Result<BatchReport<T>>
+where
+    F: Fn(&Path) -> Result<T> + Sync,
+    T: Send,
+{
+    process_files(&cdp_core::fs::wav_files(dir, false)?, op, options)
+}
+
+/// Apply `op` to each of `paths`, with full control over the batch
+pub fn process_files<T, F>(
+    paths: &[PathBuf],
+    op: F,
+    options: &BatchOptions,
+) -> Result<BatchReport<T>>
+where
+    F: Fn(&Path) -> Result<T> + Sync,
+    T: Send,
+{
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(options.parallelism)
+        .build()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+
+    let apply = |path: &PathBuf| FileResult {
+        path: path.clone(),
+        result: op(path),
+    };
+    let results = match options.mode {
+        ErrorMode::Continue => pool.install(|| paths.par_iter().map(apply).collect()),
+        ErrorMode::FailFast => pool.install(|| {
+            paths
+                .par_iter()
+                .map(apply)
+                .map(|file| match file.result {
+                    Ok(_) => Ok(file),
+                    Err(e) => Err(CdpError::Batch {
+                        path: file.path,
+                        source: Box::new(e),
+                    }),
+                })
+                .collect::<Result<_>>()
+        })?,
+    };
+    Ok(BatchReport { results })
+}
+
+#[cfg(all(test, feature = "modify", feature = "spectral"))]
+mod tests {
+    use super::*;
+    use crate::pipeline::Pipeline;
+    use cdp_core::AudioBuffer;
+    use cdp_housekeep::wav_cdp::SampleType;
+    use cdp_housekeep::{read_audio_buffer, write_audio_buffer};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_process_dir() {
+        let dir = TempDir::new().unwrap();
+        let output_dir = TempDir::new().unwrap();
+        for i in 1..=6 {
+            let audio = AudioBuffer::new(44100, 1, vec![0.0, 0.1 * i as f32, -0.05]).unwrap();
+            let path = dir.path().join(format!("{}.wav", i));
+            write_audio_buffer(&path, &audio, SampleType::Float32).unwrap();
+        }
+        cdp_core::fs::write(dir.path().join("notes.txt"), "not audio").unwrap();
+        cdp_core::fs::write(dir.path().join("broken.wav"), "not audio").unwrap();
+
+        let pipeline = Pipeline::new().normalize(Some(0.5));
+        let op = |input: &Path| {
+            let output = output_dir.path().join(input.file_name().unwrap());
+            pipeline.run(input, &output)?;
+            Ok(read_audio_buffer(&output)?.0.data[1])
+        };
+
+        let report = process_dir(dir.path(), op, 3).unwrap();
+        assert_eq!(report.results.len(), 7);
+        assert_eq!(report.succeeded().count(), 6);
+        assert!(!report.is_success());
+        let failed: Vec<_> = report.failed().collect();
+        assert_eq!(failed[0].path, dir.path().join("broken.wav"));
+        for file in report.succeeded() {
+            assert!((file.result.as_ref().unwrap() - 0.5).abs() < 1e-6);
+        }
+
+        let options = BatchOptions {
+            parallelism: 2,
+            mode: ErrorMode::FailFast,
+        };
+        match process_dir_with_options(dir.path(), op, &options) {
+            Err(CdpError::Batch { path, .. }) => assert_eq!(path, dir.path().join("broken.wav")),
+            _ => panic!("expected a batch error"),
+        }
+
+        let missing = dir.path().join("missing");
+        assert!(matches!(process_dir(&missing, op, 1), Err(CdpError::Io(_))));
+    }
+}
@@ -0,0 +1,162 @@
+//! Multicall binary running every CDP-RS program
+//!
+//! `cdp <program> [args...]` runs a program with the arguments its own
+//! binary takes. With `--cdp-args` the arguments are taken exactly as the
+//! original CDP program takes them; this only changes distort, whose own
+//! binary uses named flags where CDP's uses positional arguments. Invoked
+//! through a link named after a program, e.g. `blur`, the binary behaves
+//! as that CDP program.
+
+use cdp::prelude::*;
+use clap::{Args, Parser, Subcommand};
+use std::env;
+use std::path::Path;
+use std::process;
+
+/// Programs the binary can be invoked as directly
+const PROGRAMS: [&str; 9] = [
+    "pvoc",
+    "blur",
+    "stretch",
+    "pitch",
+    "housekeep",
+    "modify",
+    "sndinfo",
+    "distort",
+    "synth",
+];
+
+#[derive(Parser)]
+#[command(name = "cdp", version)]
+#[command(about = "CDP-RS sound transformation programs")]
+struct Cli {
+    /// Take program arguments exactly as the original CDP programs do
+    #[arg(long)]
+    cdp_args: bool,
+
+    #[command(subcommand)]
+    program: Program,
+}
+
+#[derive(Subcommand)]
+enum Program {
+    /// Phase vocoder analysis, resynthesis and band extraction
+    Pvoc(ProgramArgs),
+    /// Time-average a spectrum
+    Blur(ProgramArgs),
+    /// Time-stretch a spectrum
+    Stretch(ProgramArgs),
+    /// Pitch-shift a spectrum
+    Pitch(ProgramArgs),
+    /// Copying, channels, extraction, sample rate and format conversion
+    Housekeep(ProgramArgs),
+    /// Loudness, dynamics, fades, speed and spatial operations
+    Modify(ProgramArgs),
+    /// Sound file properties and analysis
+    Sndinfo(ProgramArgs),
+    /// Waveset and overload distortion
+    Distort(ProgramArgs),
+    /// Test signal generation
+    Synth(ProgramArgs),
+}
+
+/// Arguments handed on to the program unparsed
+#[derive(Args)]
+#[command(disable_help_flag = true)]
+struct ProgramArgs {
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    args: Vec<String>,
+}
+
+impl Program {
+    /// Name of the program and its arguments
+    fn into_parts(self) -> (&'static str, Vec<String>) {
+        match self {
+            Program::Pvoc(p) => ("pvoc", p.args),
+            Program::Blur(p) => ("blur", p.args),
+            Program::Stretch(p) => ("stretch", p.args),
+            Program::Pitch(p) => ("pitch", p.args),
+            Program::Housekeep(p) => ("housekeep", p.args),
+            Program::Modify(p) => ("modify", p.args),
+            Program::Sndinfo(p) => ("sndinfo", p.args),
+            Program::Distort(p) => ("distort", p.args),
+            Program::Synth(p) => ("synth", p.args),
+        }
+    }
+}
+
+fn main() {
+    let argv: Vec<String> = env::args().collect();
+    let invoked_as = argv
+        .first()
+        .and_then(|arg| Path::new(arg).file_stem())
+        .and_then(|stem| stem.to_str())
+        .filter(|stem| PROGRAMS.contains(stem));
+
+    let (program, args, cdp_args) = match invoked_as {
+        Some(program) => (program, argv[1..].to_vec(), true),
+        None => {
+            let cli = Cli::parse_from(&argv);
+            let (program, args) = cli.program.into_parts();
+            (program, args, cli.cdp_args)
+        }
+    };
+
+    if let Err(e) = run(program, &args, cdp_args) {
+        eprintln!("ERROR: {}", e);
+        process::exit(1);
+    }
+}
+
+/// Run one program, as its own binary would with `args`
+fn run(program: &str, args: &[String], cdp_args: bool) -> Result<()> {
+    let argv: Vec<String> = std::iter::once(program.to_string())
+        .chain(args.iter().cloned())
+        .collect();
+    match program {
+        "pvoc" => pvoc::cli::pvoc_cli(&argv),
+        "blur" => spectral::cli::blur_cli(&argv),
+        "stretch" => spectral::cli::stretch_cli(&argv),
+        "pitch" => spectral::cli::pitch_cli(&argv),
+        "distort" if !cdp_args => distort::cli::run(&argv)?,
+        _ => {
+            let Some((operation, rest)) = args.split_first() else {
+                exit_with_usage(program);
+            };
+            let rest: Vec<&str> = rest.iter().map(String::as_str).collect();
+            match program {
+                "housekeep" => housekeep::housekeep(operation, &rest)?,
+                "sndinfo" => sndinfo::sndinfo(operation, &rest)?,
+                "distort" => distort::distort(operation, &rest)?,
+                "synth" => synth::synth(operation, &rest)?,
+                "modify" => {
+                    let Some((mode, rest)) = rest.split_first() else {
+                        exit_with_usage(program);
+                    };
+                    let mode = mode.parse::<i32>().map_err(|_| {
+                        modify::ModifyError::InvalidParameter(format!(
+                            "Invalid mode number: {}",
+                            mode
+                        ))
+                    })?;
+                    modify::modify(operation, mode, rest)?;
+                }
+                _ => unreachable!("unknown program {}", program),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Print the usage line of an operation-based program and exit
+fn exit_with_usage(program: &str) -> ! {
+    let usage = match program {
+        "housekeep" => "<operation> [args...]",
+        "modify" => "<operation> <mode> <infile> <outfile> [args...]",
+        "sndinfo" => "<operation> <infile> [args...]",
+        "distort" => "<operation> [mode] <infile> <outfile> [args...]",
+        _ => "<operation> [mode] <outfile> <srate> <chans> <dur> [args...]",
+    };
+    eprintln!("Usage: {} {}", program, usage);
+    process::exit(1);
+}
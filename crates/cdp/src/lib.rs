@@ -0,0 +1,183 @@
+//! CDP-RS - one crate for the whole toolkit
+//!
+//! Re-exports the CDP subsystems under short names so applications can
+//! depend on a single crate:
+//! - [`housekeep`]: file copying, channels, format conversion and WAV I/O
+//! - [`modify`]: gain, normalization, dynamics, fades, speed and space
+//! - [`spectral`]: blur, stretch, pitch and the other spectral processes
+//! - [`pvoc`]: phase vocoder analysis and resynthesis
+//! - [`distort`]: waveset and overload distortions
+//! - [`sndinfo`]: sound file properties and analysis
+//! - [`synth`]: test signal generation
+//!
+//! Each subsystem sits behind a feature of the same name. The default `cli`
+//! feature enables them all, along with the `cdp` multicall binary. Errors
+//! from every subsystem convert into [`CdpError`], so `?` works across
+//! them in a function returning [`Result`]. Most programs start with
+//! `use cdp::prelude::*;`.
+//!
+//! With `modify` and `spectral` enabled, [`pipeline::Pipeline`] chains
+//! operations in memory instead of through temporary files. The `serde`
+//! feature makes the parameter structs and option enums of every subsystem
+//! serializable, and with `distort` also enabled, [`preset::Preset`]
+//! saves such chains as TOML or JSON. The `batch` feature adds
+//! [`batch::process_dir`], which applies an operation to a directory of
+//! files in parallel.
+//!
+//! Files are read and written through [`core::fs`], which keeps them in
+//! memory on `wasm32-unknown-unknown`. The `wasm` feature adds [`wasm`],
+//! whose functions take and return WAV files as bytes for browser hosts.
+//!
+//! The `tracing` feature has housekeep, pvoc and spectral report what they
+//! are doing (files opened, frames processed, peaks computed, chunks
+//! written) as [`tracing`](https://docs.rs/tracing) spans and events, for
+//! following long batch jobs. Without it the instrumentation compiles away.
+
+use thiserror::Error;
+
+#[cfg(feature = "batch")]
+pub mod batch;
+pub use cdp_core as core;
+#[cfg(all(feature = "modify", feature = "spectral"))]
+pub mod pipeline;
+#[cfg(feature = "distort")]
+pub use cdp_distort as distort;
+#[cfg(feature = "housekeep")]
+pub use cdp_housekeep as housekeep;
+#[cfg(feature = "modify")]
+pub use cdp_modify as modify;
+#[cfg(all(
+    feature = "serde",
+    feature = "modify",
+    feature = "spectral",
+    feature = "distort"
+))]
+pub mod preset;
+#[cfg(feature = "pvoc")]
+pub use cdp_pvoc as pvoc;
+#[cfg(feature = "sndinfo")]
+pub use cdp_sndinfo as sndinfo;
+#[cfg(feature = "spectral")]
+pub use cdp_spectral as spectral;
+#[cfg(feature = "synth")]
+pub use cdp_synth as synth;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub type Result<T> = std::result::Result<T, CdpError>;
+
+/// Error from any CDP subsystem
+#[derive(Error, Debug)]
+pub enum CdpError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Core error: {0}")]
+    Core(#[from] cdp_core::CoreError),
+
+    #[cfg(feature = "housekeep")]
+    #[error("Housekeep error: {0}")]
+    Housekeep(#[from] cdp_housekeep::HousekeepError),
+
+    #[cfg(feature = "modify")]
+    #[error("Modify error: {0}")]
+    Modify(#[from] cdp_modify::ModifyError),
+
+    #[cfg(feature = "spectral")]
+    #[error("Spectral error: {0}")]
+    Spectral(#[from] cdp_spectral::SpectralError),
+
+    #[cfg(feature = "pvoc")]
+    #[error("Pvoc error: {0}")]
+    Pvoc(#[from] cdp_pvoc::PvocError),
+
+    #[cfg(feature = "distort")]
+    #[error("Distort error: {0}")]
+    Distort(#[from] cdp_distort::DistortError),
+
+    #[cfg(feature = "sndinfo")]
+    #[error("Sndinfo error: {0}")]
+    Sndinfo(#[from] cdp_sndinfo::SndinfoError),
+
+    #[cfg(feature = "synth")]
+    #[error("Synth error: {0}")]
+    Synth(#[from] cdp_synth::SynthError),
+
+    #[cfg(all(feature = "modify", feature = "spectral"))]
+    #[error("Pipeline error: {0}")]
+    Pipeline(String),
+
+    #[cfg(feature = "batch")]
+    #[error("Failed on {path}: {source}")]
+    Batch {
+        path: std::path::PathBuf,
+        source: Box<CdpError>,
+    },
+
+    #[cfg(feature = "serde")]
+    #[error("Preset error: {0}")]
+    Preset(String),
+}
+
+/// The types most programs need, and the enabled subsystems by name
+///
+/// Functions stay behind their subsystem, e.g. `modify::reverse` or
+/// `distort::reverse`, since several subsystems share operation names.
+pub mod prelude {
+    pub use crate::{CdpError, Result};
+    pub use cdp_core::{
+        AnaFile, AnaHeader, AudioBuffer, Breakpoints, CancelToken, Interpolation, ProgressSink,
+        WindowFunction,
+    };
+
+    #[cfg(feature = "distort")]
+    pub use crate::distort;
+    #[cfg(feature = "housekeep")]
+    pub use crate::housekeep;
+    #[cfg(feature = "modify")]
+    pub use crate::modify;
+    #[cfg(feature = "pvoc")]
+    pub use crate::pvoc;
+    #[cfg(feature = "sndinfo")]
+    pub use crate::sndinfo;
+    #[cfg(feature = "spectral")]
+    pub use crate::spectral;
+    #[cfg(feature = "synth")]
+    pub use crate::synth;
+    #[cfg(feature = "housekeep")]
+    pub use cdp_housekeep::wav_cdp::SampleType;
+    #[cfg(feature = "housekeep")]
+    pub use cdp_housekeep::{read_audio_buffer, write_audio_buffer};
+}
+
+#[cfg(all(test, feature = "housekeep", feature = "modify", feature = "sndinfo"))]
+mod tests {
+    use super::prelude::*;
+    use tempfile::TempDir;
+
+    /// Chains several subsystems, converting each error with `?`
+    fn halve_and_measure(input: &std::path::Path, output: &std::path::Path) -> Result<f32> {
+        let (mut audio, sample_type) = read_audio_buffer(input)?;
+        modify::apply_gain_buf(&mut audio.data, 0.5);
+        write_audio_buffer(output, &audio, sample_type)?;
+        Ok(sndinfo::maxsamp(output)?.overall().level)
+    }
+
+    #[test]
+    fn test_prelude_spans_subsystems() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("input.wav");
+        let output = dir.path().join("output.wav");
+
+        let audio = AudioBuffer::new(44100, 1, vec![0.0, 0.5, -0.5, 0.25]).unwrap();
+        write_audio_buffer(&input, &audio, SampleType::Float32).unwrap();
+        let peak = halve_and_measure(&input, &output).unwrap();
+        assert!((peak - 0.25).abs() < 1e-6);
+
+        let missing = dir.path().join("missing.wav");
+        assert!(matches!(
+            halve_and_measure(&missing, &output),
+            Err(CdpError::Housekeep(_))
+        ));
+    }
+}
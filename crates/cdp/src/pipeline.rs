@@ -0,0 +1,312 @@
+//! Chains of operations run in memory
+//!
+//! A [`Pipeline`] passes an [`AudioBuffer`] from stage to stage, and once
+//! analysed, the spectral frames of each channel, so a multi-stage process
+//! reads its input and writes its output once, with no temporary files:
+//!
+//! ```no_run
+//! use cdp::pipeline::Pipeline;
+//! use cdp::prelude::*;
+//! use cdp::pvoc::{PvocAnalParams, PvocSynthParams};
+//! use cdp::spectral::{BlurParams, StretchParams};
+//! use std::path::Path;
+//!
+//! # fn main() -> cdp::Result<()> {
+//! Pipeline::new()
+//!     .gain(0.5)
+//!     .analyse(PvocAnalParams::default())
+//!     .blur(BlurParams::builder().windows(9).build()?)
+//!     .stretch(StretchParams::builder().factor(4.0).build()?)
+//!     .synthesise(PvocSynthParams::default())
+//!     .normalize(Some(0.9))
+//!     .run(Path::new("in.wav"), Path::new("out.wav"))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Audio stages must come before [`Pipeline::analyse`] or after
+//! [`Pipeline::synthesise`], and spectral stages between them. The order is
+//! checked before any input is read.
+
+use crate::{CdpError, Result};
+use cdp_core::{AnaFile, AudioBuffer};
+use cdp_housekeep::{read_audio_buffer, write_audio_buffer};
+use cdp_pvoc::{PvocAnalParams, PvocSynthParams};
+use cdp_spectral::{BlurParams, PitchParams, StretchParams};
+use std::path::Path;
+
+type AudioStage = Box<dyn Fn(AudioBuffer) -> Result<AudioBuffer> + Send + Sync>;
+type SpectralStage = Box<dyn Fn(&AnaFile) -> Result<AnaFile> + Send + Sync>;
+
+/// One step of a pipeline
+enum Operation {
+    Audio(AudioStage),
+    Analyse(PvocAnalParams),
+    Spectral(SpectralStage),
+    Synthesise(PvocSynthParams),
+}
+
+/// A named step, the name used in error messages
+struct Stage {
+    name: &'static str,
+    operation: Operation,
+}
+
+/// What passes between stages
+enum Signal {
+    Audio(AudioBuffer),
+    /// One analysis per channel
+    Spectrum(Vec<AnaFile>),
+}
+
+/// A chain of operations applied in memory
+///
+/// A pipeline can be shared between threads, to process several files at
+/// once with [`crate::batch`].
+#[derive(Default)]
+pub struct Pipeline {
+    stages: Vec<Stage>,
+}
+
+impl Pipeline {
+    /// An empty pipeline, which copies its input
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn push(mut self, name: &'static str, operation: Operation) -> Self {
+        self.stages.push(Stage { name, operation });
+        self
+    }
+
+    /// Transform the whole buffer
+    pub fn audio<F, E>(self, stage: F) -> Self
+    where
+        F: Fn(AudioBuffer) -> std::result::Result<AudioBuffer, E> + Send + Sync + 'static,
+        E: Into<CdpError>,
+    {
+        let stage = move |audio| stage(audio).map_err(Into::into);
+        self.push("audio", Operation::Audio(Box::new(stage)))
+    }
+
+    /// Transform each channel on its own, as the distort operations do
+    ///
+    /// Channels may change length, but must all end up the same length.
+    pub fn per_channel<F, E>(self, stage: F) -> Self
+    where
+        F: Fn(&[f32]) -> std::result::Result<Vec<f32>, E> + Send + Sync + 'static,
+        E: Into<CdpError>,
+    {
+        let stage = move |audio: AudioBuffer| {
+            let channels = (0..audio.channels as usize)
+                .map(|channel| stage(&audio.channel(channel)?).map_err(Into::into))
+                .collect::<Result<Vec<_>>>()?;
+            AudioBuffer::from_channels(audio.sample_rate, &channels)
+                .map_err(|_| CdpError::Pipeline("Channels changed to different lengths".into()))
+        };
+        self.push("per-channel", Operation::Audio(Box::new(stage)))
+    }
+
+    /// Multiply every sample by `gain`
+    pub fn gain(self, gain: f32) -> Self {
+        let stage = move |mut audio: AudioBuffer| {
+            cdp_modify::apply_gain_buf(&mut audio.data, gain);
+            Ok(audio)
+        };
+        self.push("gain", Operation::Audio(Box::new(stage)))
+    }
+
+    /// Scale the peak to `target_level`, or full scale
+    pub fn normalize(self, target_level: Option<f32>) -> Self {
+        let stage = move |mut audio: AudioBuffer| {
+            cdp_modify::normalize_buf(&mut audio.data, target_level)?;
+            Ok(audio)
+        };
+        self.push("normalize", Operation::Audio(Box::new(stage)))
+    }
+
+    /// Phase vocoder analysis, starting the spectral stages
+    pub fn analyse(self, params: PvocAnalParams) -> Self {
+        self.push("analyse", Operation::Analyse(params))
+    }
+
+    /// Transform the spectrum of each channel
+    pub fn spectral<F, E>(self, stage: F) -> Self
+    where
+        F: Fn(&AnaFile) -> std::result::Result<AnaFile, E> + Send + Sync + 'static,
+        E: Into<CdpError>,
+    {
+        let stage = move |analysis: &AnaFile| stage(analysis).map_err(Into::into);
+        self.push("spectral", Operation::Spectral(Box::new(stage)))
+    }
+
+    /// Time-average the spectrum
+    pub fn blur(self, params: BlurParams) -> Self {
+        let stage =
+            move |analysis: &AnaFile| Ok(cdp_spectral::blur_frames(analysis, params.windows)?);
+        self.push("blur", Operation::Spectral(Box::new(stage)))
+    }
+
+    /// Time-stretch the spectrum
+    pub fn stretch(self, params: StretchParams) -> Self {
+        let stage = move |analysis: &AnaFile| {
+            Ok(match params.transient_threshold {
+                Some(threshold) => cdp_spectral::stretch_time_preserving_frames(
+                    analysis,
+                    params.factor,
+                    threshold,
+                )?,
+                None => cdp_spectral::stretch_time_frames(analysis, params.factor)?,
+            })
+        };
+        self.push("stretch", Operation::Spectral(Box::new(stage)))
+    }
+
+    /// Pitch-shift the spectrum
+    pub fn pitch(self, params: PitchParams) -> Self {
+        let stage = move |analysis: &AnaFile| {
+            Ok(cdp_spectral::pitch_shift_formant_frames(
+                analysis,
+                params.factor,
+                params.preserve_formants,
+            )?)
+        };
+        self.push("pitch", Operation::Spectral(Box::new(stage)))
+    }
+
+    /// Phase vocoder resynthesis, returning to audio stages
+    pub fn synthesise(self, params: PvocSynthParams) -> Self {
+        self.push("synthesise", Operation::Synthesise(params))
+    }
+
+    /// Check that every stage receives the kind of signal it works on
+    fn check(&self) -> Result<()> {
+        let mut spectral = false;
+        for stage in &self.stages {
+            let expected = match stage.operation {
+                Operation::Audio(_) | Operation::Analyse(_) => false,
+                Operation::Spectral(_) | Operation::Synthesise(_) => true,
+            };
+            if spectral != expected {
+                let needs = if expected { "a spectrum" } else { "audio" };
+                return Err(CdpError::Pipeline(format!(
+                    "{} stage needs {}",
+                    stage.name, needs
+                )));
+            }
+            spectral = match stage.operation {
+                Operation::Analyse(_) => true,
+                Operation::Synthesise(_) => false,
+                _ => spectral,
+            };
+        }
+        if spectral {
+            return Err(CdpError::Pipeline(
+                "Pipeline ends with a spectrum: add a synthesise stage".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Apply every stage to an in-memory buffer
+    pub fn process(&self, audio: AudioBuffer) -> Result<AudioBuffer> {
+        self.check()?;
+        let mut signal = Signal::Audio(audio);
+        for stage in &self.stages {
+            signal = match (&stage.operation, signal) {
+                (Operation::Audio(apply), Signal::Audio(audio)) => Signal::Audio(apply(audio)?),
+                (Operation::Analyse(params), Signal::Audio(audio)) => {
+                    Signal::Spectrum(cdp_pvoc::pvoc_anal_buf(&audio, params, &mut ())?)
+                }
+                (Operation::Spectral(apply), Signal::Spectrum(analyses)) => {
+                    Signal::Spectrum(analyses.iter().map(apply).collect::<Result<_>>()?)
+                }
+                (Operation::Synthesise(params), Signal::Spectrum(analyses)) => {
+                    Signal::Audio(cdp_pvoc::pvoc_synth_buf(&analyses, params, &mut ())?)
+                }
+                _ => unreachable!("stage order is checked first"),
+            };
+        }
+        match signal {
+            Signal::Audio(audio) => Ok(audio),
+            Signal::Spectrum(_) => unreachable!("stage order is checked first"),
+        }
+    }
+
+    /// Read `input`, apply every stage and write `output`
+    ///
+    /// The output keeps the sample type of the input.
+    pub fn run(&self, input: &Path, output: &Path) -> Result<()> {
+        self.check()?;
+        let (audio, sample_type) = read_audio_buffer(input)?;
+        let audio = self.process(audio)?;
+        write_audio_buffer(output, &audio, sample_type)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cdp_housekeep::wav_cdp::SampleType;
+    use std::f32::consts::PI;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_pipeline_run() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("input.wav");
+        let output = dir.path().join("output.wav");
+
+        let data: Vec<f32> = (0..44100)
+            .map(|i| 0.25 * (2.0 * PI * 440.0 * i as f32 / 44100.0).sin())
+            .collect();
+        let audio = AudioBuffer::new(44100, 1, data).unwrap();
+        write_audio_buffer(&input, &audio, SampleType::Int16).unwrap();
+
+        Pipeline::new()
+            .gain(2.0)
+            .per_channel(|samples| Ok::<_, CdpError>(samples.iter().map(|s| -s).collect()))
+            .analyse(PvocAnalParams::default())
+            .blur(BlurParams::builder().windows(3).build().unwrap())
+            .stretch(StretchParams::builder().factor(2.0).build().unwrap())
+            .synthesise(PvocSynthParams::default())
+            .normalize(Some(0.5))
+            .run(&input, &output)
+            .unwrap();
+
+        // 127 windows of 1024 points, 341 samples apart, stretched to 254
+        let (result, sample_type) = read_audio_buffer(&output).unwrap();
+        assert_eq!(sample_type, SampleType::Int16);
+        assert_eq!(result.frame_count(), 253 * 341 + 1024);
+        let peak = result.data.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+        assert!((peak - 0.5).abs() < 1e-3, "peak {}", peak);
+    }
+
+    #[test]
+    fn test_pipeline_checks_stage_order() {
+        let audio = AudioBuffer::new(44100, 1, vec![0.0; 4096]).unwrap();
+        let misplaced = Pipeline::new().blur(BlurParams::default());
+        let unfinished = Pipeline::new().analyse(PvocAnalParams::default());
+        let doubled = unfinished.analyse(PvocAnalParams::default());
+
+        for (pipeline, message) in [
+            (misplaced, "blur stage needs a spectrum"),
+            (doubled, "analyse stage needs audio"),
+        ] {
+            match pipeline.process(audio.clone()) {
+                Err(CdpError::Pipeline(e)) => assert_eq!(e, message),
+                _ => panic!("expected a pipeline error"),
+            }
+        }
+        let unfinished = Pipeline::new().analyse(PvocAnalParams::default());
+        assert!(matches!(
+            unfinished.process(audio.clone()),
+            Err(CdpError::Pipeline(_))
+        ));
+        assert_eq!(
+            Pipeline::new().process(audio.clone()).unwrap().data,
+            audio.data
+        );
+    }
+}
@@ -0,0 +1,264 @@
+//! Sound-design recipes saved as files
+//!
+//! A [`Preset`] names a list of [`Step`]s that replay as a
+//! [`Pipeline`](crate::pipeline::Pipeline), so a recipe worked out once can
+//! be saved, shared and applied to other sounds. Presets are stored as TOML
+//! or JSON:
+//!
+//! ```toml
+//! name = "808 bass"
+//! description = "Sub octave with soft saturation"
+//!
+//! [[steps]]
+//! op = "divide"
+//! factor = 2
+//! mix = 0.5
+//!
+//! [[steps]]
+//! op = "overload"
+//! threshold = 0.6
+//! drive = 2.5
+//! clip = "soft"
+//! ```
+//!
+//! Settings left out of an analysis, spectral or synthesis step take the
+//! defaults of its parameter struct, and a normalize step without a level
+//! normalizes to full scale. Gain, multiply, divide and overload steps
+//! need every setting. Settings are checked when the preset runs, not
+//! when it is read.
+
+use crate::pipeline::Pipeline;
+use crate::{CdpError, Result};
+use cdp_core::AudioBuffer;
+use cdp_distort::overload::ClipType;
+use cdp_pvoc::{PvocAnalParams, PvocSynthParams};
+use cdp_spectral::{BlurParams, PitchParams, StretchParams};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// One operation of a preset
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Step {
+    /// Multiply every sample by `gain`
+    Gain { gain: f32 },
+    /// Scale the peak to `level`, or full scale
+    Normalize {
+        #[serde(default)]
+        level: Option<f32>,
+    },
+    /// Waveset multiplication, mixed with the dry signal
+    Multiply { factor: f32, mix: f32 },
+    /// Waveset division, mixed with the dry signal
+    Divide { factor: u32, mix: f32 },
+    /// Clipping distortion
+    Overload {
+        threshold: f32,
+        drive: f32,
+        clip: ClipType,
+    },
+    /// Phase vocoder analysis, starting the spectral steps
+    Analyse(PvocAnalParams),
+    /// Time-average the spectrum
+    Blur(BlurParams),
+    /// Time-stretch the spectrum
+    Stretch(StretchParams),
+    /// Pitch-shift the spectrum
+    Pitch(PitchParams),
+    /// Phase vocoder resynthesis, returning to audio steps
+    Synthesise(PvocSynthParams),
+}
+
+/// A named list of steps
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Preset {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub steps: Vec<Step>,
+}
+
+impl Preset {
+    /// A preset with no description
+    pub fn new(name: impl Into<String>, steps: Vec<Step>) -> Self {
+        Self {
+            name: name.into(),
+            description: String::new(),
+            steps,
+        }
+    }
+
+    /// The steps as a pipeline
+    pub fn pipeline(&self) -> Pipeline {
+        self.steps
+            .iter()
+            .fold(Pipeline::new(), |pipeline, step| match *step {
+                Step::Gain { gain } => pipeline.gain(gain),
+                Step::Normalize { level } => pipeline.normalize(level),
+                Step::Multiply { factor, mix } => pipeline
+                    .per_channel(move |samples| cdp_distort::multiply_buf(samples, factor, mix)),
+                Step::Divide { factor, mix } => pipeline
+                    .per_channel(move |samples| cdp_distort::divide_buf(samples, 1, factor, mix)),
+                Step::Overload {
+                    threshold,
+                    drive,
+                    clip,
+                } => pipeline.per_channel(move |samples| {
+                    cdp_distort::overload_buf(samples, threshold, drive, clip)
+                }),
+                Step::Analyse(params) => pipeline.analyse(params),
+                Step::Blur(params) => pipeline.blur(params),
+                Step::Stretch(params) => pipeline.stretch(params),
+                Step::Pitch(params) => pipeline.pitch(params),
+                Step::Synthesise(params) => pipeline.synthesise(params),
+            })
+    }
+
+    /// Apply the steps to an in-memory buffer
+    pub fn process(&self, audio: AudioBuffer) -> Result<AudioBuffer> {
+        self.pipeline().process(audio)
+    }
+
+    /// Read `input`, apply the steps and write `output`
+    pub fn run(&self, input: &Path, output: &Path) -> Result<()> {
+        self.pipeline().run(input, output)
+    }
+
+    /// Parse a JSON preset
+    pub fn from_json(text: &str) -> Result<Self> {
+        serde_json::from_str(text).map_err(|e| CdpError::Preset(e.to_string()))
+    }
+
+    /// Format as pretty-printed JSON
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| CdpError::Preset(e.to_string()))
+    }
+
+    /// Parse a TOML preset
+    pub fn from_toml(text: &str) -> Result<Self> {
+        toml::from_str(text).map_err(|e| CdpError::Preset(e.to_string()))
+    }
+
+    /// Format as TOML
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string(self).map_err(|e| CdpError::Preset(e.to_string()))
+    }
+
+    /// Read a preset file: TOML if its extension is `.toml`, otherwise JSON
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = cdp_core::fs::read_to_string(path)?;
+        if is_toml(path) {
+            Self::from_toml(&text)
+        } else {
+            Self::from_json(&text)
+        }
+    }
+
+    /// Write a preset file, in the format [`Preset::load`] expects
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let text = if is_toml(path) {
+            self.to_toml()?
+        } else {
+            self.to_json()?
+        };
+        cdp_core::fs::write(path, text)?;
+        Ok(())
+    }
+}
+
+fn is_toml(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cdp_core::WindowFunction;
+    use std::f32::consts::PI;
+    use tempfile::TempDir;
+
+    fn telephone_voice() -> Preset {
+        let mut preset = Preset::new(
+            "telephone voice",
+            vec![
+                Step::Overload {
+                    threshold: 0.4,
+                    drive: 2.0,
+                    clip: ClipType::Hard,
+                },
+                Step::Analyse(PvocAnalParams {
+                    window: WindowFunction::Kaiser(8.0),
+                    ..PvocAnalParams::default()
+                }),
+                Step::Blur(BlurParams { windows: 3 }),
+                Step::Synthesise(PvocSynthParams::default()),
+                Step::Normalize { level: None },
+            ],
+        );
+        preset.description = "Lo-fi telephone/radio voice".into();
+        preset
+    }
+
+    #[test]
+    fn test_preset_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let preset = telephone_voice();
+        for name in ["voice.toml", "voice.json"] {
+            let path = dir.path().join(name);
+            preset.save(&path).unwrap();
+            assert_eq!(Preset::load(&path).unwrap(), preset);
+        }
+
+        let bass = Preset::from_toml(
+            r#"
+            name = "808 bass"
+            [[steps]]
+            op = "divide"
+            factor = 2
+            mix = 0.5
+            [[steps]]
+            op = "stretch"
+            factor = 2.0
+            "#,
+        )
+        .unwrap();
+        assert_eq!(bass.description, "");
+        assert_eq!(
+            bass.steps,
+            [
+                Step::Divide {
+                    factor: 2,
+                    mix: 0.5
+                },
+                Step::Stretch(StretchParams {
+                    factor: 2.0,
+                    transient_threshold: None
+                }),
+            ]
+        );
+        assert!(matches!(
+            Preset::from_json(r#"{"name": "x", "steps": [{"op": "wobble"}]}"#),
+            Err(CdpError::Preset(_))
+        ));
+    }
+
+    #[test]
+    fn test_preset_process() {
+        let data: Vec<f32> = (0..22050)
+            .map(|i| 0.5 * (2.0 * PI * 220.0 * i as f32 / 44100.0).sin())
+            .collect();
+        let audio = AudioBuffer::new(44100, 1, data).unwrap();
+
+        let result = telephone_voice().process(audio).unwrap();
+        let peak = result.data.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+        assert!((peak - 1.0).abs() < 1e-3, "peak {}", peak);
+
+        let misplaced = Preset::new("misplaced", vec![Step::Blur(BlurParams::default())]);
+        let audio = AudioBuffer::new(44100, 1, vec![0.0; 4096]).unwrap();
+        assert!(matches!(
+            misplaced.process(audio),
+            Err(CdpError::Pipeline(_))
+        ));
+    }
+}
@@ -0,0 +1,114 @@
+//! Byte-buffer entry points for WebAssembly hosts
+//!
+//! A browser has no paths to hand the path-based functions, but it does
+//! have the bytes of the files a user picked. These functions take and
+//! return whole WAV files as bytes, so a `wasm-bindgen` wrapper only needs
+//! to pass `Uint8Array`s through:
+//!
+//! ```
+//! use cdp::prelude::*;
+//! use cdp::spectral::BlurParams;
+//! use cdp::wasm;
+//!
+//! # fn main() -> cdp::Result<()> {
+//! let audio = AudioBuffer::new(44100, 1, vec![0.0; 8192])?;
+//! let wav = wasm::encode_wav(&audio, SampleType::Int16)?;
+//! let blurred = wasm::blur(&wav, &BlurParams::builder().windows(9).build()?)?;
+//! assert_eq!(wasm::decode_wav(&blurred)?.1, SampleType::Int16);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Outputs keep the sample type of their input. Build with
+//! `default-features = false, features = ["wasm"]` to leave out the
+//! command-line programs and batch processing, which a browser cannot use.
+
+use crate::pipeline::Pipeline;
+use crate::Result;
+use cdp_core::AudioBuffer;
+use cdp_housekeep::wav_cdp::SampleType;
+use cdp_housekeep::{read_audio_buffer_from, write_audio_buffer_to};
+use cdp_pvoc::{PvocAnalParams, PvocSynthParams};
+use cdp_spectral::{BlurParams, PitchParams, StretchParams};
+use std::io::Cursor;
+
+/// Read a WAV file held in memory
+pub fn decode_wav(wav: &[u8]) -> Result<(AudioBuffer, SampleType)> {
+    Ok(read_audio_buffer_from(wav)?)
+}
+
+/// Write an [`AudioBuffer`] as a CDP-format WAV file in memory
+pub fn encode_wav(audio: &AudioBuffer, sample_type: SampleType) -> Result<Vec<u8>> {
+    let mut wav = Cursor::new(Vec::new());
+    write_audio_buffer_to(&mut wav, audio, sample_type)?;
+    Ok(wav.into_inner())
+}
+
+/// Apply every stage of `pipeline` to a WAV file in memory
+pub fn process_wav(wav: &[u8], pipeline: &Pipeline) -> Result<Vec<u8>> {
+    let (audio, sample_type) = decode_wav(wav)?;
+    encode_wav(&pipeline.process(audio)?, sample_type)
+}
+
+/// Analyse with CDP's default settings, apply one spectral stage and
+/// resynthesise
+fn spectral(wav: &[u8], stage: impl FnOnce(Pipeline) -> Pipeline) -> Result<Vec<u8>> {
+    let pipeline = stage(Pipeline::new().analyse(PvocAnalParams::default()))
+        .synthesise(PvocSynthParams::default());
+    process_wav(wav, &pipeline)
+}
+
+/// Time-average the spectrum of a WAV file in memory
+pub fn blur(wav: &[u8], params: &BlurParams) -> Result<Vec<u8>> {
+    spectral(wav, |pipeline| pipeline.blur(*params))
+}
+
+/// Time-stretch a WAV file in memory
+pub fn stretch(wav: &[u8], params: &StretchParams) -> Result<Vec<u8>> {
+    spectral(wav, |pipeline| pipeline.stretch(*params))
+}
+
+/// Pitch-shift a WAV file in memory
+pub fn pitch(wav: &[u8], params: &PitchParams) -> Result<Vec<u8>> {
+    spectral(wav, |pipeline| pipeline.pitch(*params))
+}
+
+/// Apply a JSON preset, as saved by [`crate::preset::Preset::save`], to a
+/// WAV file in memory
+#[cfg(all(feature = "serde", feature = "distort"))]
+pub fn apply_preset(wav: &[u8], preset_json: &str) -> Result<Vec<u8>> {
+    let preset = crate::preset::Preset::from_json(preset_json)?;
+    process_wav(wav, &preset.pipeline())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CdpError;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn test_wav_bytes() {
+        let data: Vec<f32> = (0..22050)
+            .map(|i| 0.5 * (2.0 * PI * 440.0 * i as f32 / 44100.0).sin())
+            .collect();
+        let audio = AudioBuffer::new(44100, 1, data).unwrap();
+        let wav = encode_wav(&audio, SampleType::Float32).unwrap();
+        let (decoded, sample_type) = decode_wav(&wav).unwrap();
+        assert_eq!(sample_type, SampleType::Float32);
+        assert_eq!(decoded.data, audio.data);
+
+        let params = StretchParams::builder().factor(2.0).build().unwrap();
+        let (stretched, _) = decode_wav(&stretch(&wav, &params).unwrap()).unwrap();
+        assert!(stretched.frame_count() > 2 * audio.frame_count() - 2048);
+
+        let quiet = process_wav(&wav, &Pipeline::new().gain(0.5)).unwrap();
+        let (quiet, _) = decode_wav(&quiet).unwrap();
+        assert_eq!(quiet.data[100], audio.data[100] * 0.5);
+
+        assert!(matches!(
+            decode_wav(b"not a wav"),
+            Err(CdpError::Housekeep(_))
+        ));
+    }
+}
@@ -42,7 +42,7 @@ mkdir -p "$DEMO_DIR"
 cd "$DEMO_DIR"
 
 echo -e "\n${BLUE}Step 1: Generating test audio (440Hz sine wave)...${NC}"
-python3 ../scripts/generate-test-audio.py original.wav
+cargo run -q --manifest-path ../Cargo.toml -p cdp-synth --bin synth -- wave 1 original.wav 44100 1 2 440
 [ -n "$PLAYER" ] && echo "Playing original..." && $PLAYER original.wav 2>/dev/null
 
 echo -e "\n${BLUE}Step 2: Time-stretching by 1.5x...${NC}"
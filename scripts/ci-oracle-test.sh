@@ -13,7 +13,7 @@ cd oracle-test
 # Generate test audio if it doesn't exist
 if [ ! -f "input.wav" ]; then
     echo "Generating test audio..."
-    python3 ../scripts/generate-test-audio.py input.wav
+    cargo run -q --manifest-path ../Cargo.toml -p cdp-synth --bin synth -- wave 1 input.wav 44100 1 2 440
 fi
 
 # Test 1: housekeep copy
@@ -34,10 +34,10 @@ rm -rf "$TEST_DIR"
 mkdir -p "$TEST_DIR"
 cd "$TEST_DIR"
 
-# Generate test audio using Python
+# Generate test audio
 echo ""
 echo "Generating test audio..."
-python3 "$SCRIPT_DIR/generate-test-audio.py" "test.wav"
+cargo run -q --manifest-path "$PROJECT_ROOT/Cargo.toml" -p cdp-synth --bin synth -- wave 1 test.wav 44100 1 2 440
 
 if [ ! -f "test.wav" ]; then
     echo "ERROR: Failed to generate test audio"
@@ -45,20 +45,9 @@ setup_test_dir() {
 generate_test_audio() {
     echo -e "\n${BLUE}Generating test audio...${NC}"
     
-    # Check for Python
-    if command -v python3 &> /dev/null; then
-        python3 "$SCRIPT_DIR/generate-test-audio.py" "test_input.wav"
-    else
-        echo -e "${YELLOW}Python3 not found, generating with sox if available...${NC}"
-        if command -v sox &> /dev/null; then
-            # Generate 2-second 440Hz sine wave
-            sox -n test_input.wav synth 2 sine 440
-        else
-            echo -e "${RED}Neither Python3 nor sox found. Please install one to generate test audio.${NC}"
-            echo "You can install sox with: brew install sox"
-            exit 1
-        fi
-    fi
+    # Generate a 2-second 440Hz sine wave
+    cargo run -q --manifest-path "$PROJECT_ROOT/Cargo.toml" -p cdp-synth --bin synth -- \
+        wave 1 test_input.wav 44100 1 2 440
     
     if [ -f "test_input.wav" ]; then
         echo -e "${GREEN}✓ Generated test_input.wav${NC}"
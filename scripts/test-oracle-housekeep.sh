@@ -22,7 +22,7 @@ cd "$TEST_DIR"
 
 # Generate test input
 echo "Generating test input..."
-python3 "$SCRIPT_DIR/generate-test-audio.py" input.wav
+cargo run -q --manifest-path "$PROJECT_ROOT/Cargo.toml" -p cdp-synth --bin synth -- wave 1 input.wav 44100 1 2 440
 
 # Run CDP version
 echo ""